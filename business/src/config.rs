@@ -2,6 +2,9 @@ use collects_states::{SnapshotClone, State, state_assign_impl};
 use std::any::Any;
 use ustr::Ustr;
 
+/// Files at least this large are uploaded in parts (multipart upload).
+pub const DEFAULT_MULTIPART_THRESHOLD: usize = 32 * 1024 * 1024;
+
 #[derive(Debug, Clone)]
 pub struct BusinessConfig {
     pub api_base_url: String,
@@ -9,6 +12,8 @@ pub struct BusinessConfig {
     ///
     /// When present, internal API calls should send it via the `cf-authorization` header.
     pub cf_authorization: Option<String>,
+    /// Files of at least this many bytes are uploaded with a multipart upload.
+    pub multipart_threshold: usize,
 }
 
 impl SnapshotClone for BusinessConfig {
@@ -22,9 +27,16 @@ impl BusinessConfig {
         Self {
             api_base_url: base_url,
             cf_authorization: None,
+            multipart_threshold: DEFAULT_MULTIPART_THRESHOLD,
         }
     }
 
+    /// Set the size at which uploads switch to multipart.
+    pub fn with_multipart_threshold(mut self, threshold: usize) -> Self {
+        self.multipart_threshold = threshold;
+        self
+    }
+
     pub fn api_url(&self) -> Ustr {
         if self.api_base_url.is_empty() {
             Ustr::from("/api")
@@ -55,6 +67,7 @@ impl Default for BusinessConfig {
                 "https://collects.lqxclqxc.com".to_owned()
             },
             cf_authorization: None,
+            multipart_threshold: DEFAULT_MULTIPART_THRESHOLD,
         }
     }
 }
@@ -1,6 +1,7 @@
 use std::any::Any;
 
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 
 use crate::BusinessConfig;
 use crate::http::Client;
@@ -127,6 +128,7 @@ struct InitUploadRequest {
     pub filename: String,
     pub content_type: String,
     pub file_size: usize,
+    pub sha256: String,
 }
 
 #[derive(Deserialize)]
@@ -141,6 +143,56 @@ struct CompleteUploadRequest {
     pub upload_id: String,
     pub title: Option<String>,
     pub description: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub parts: Vec<CompletedPart>,
+}
+
+#[derive(Serialize)]
+struct InitMultipartUploadRequest {
+    pub filename: String,
+    pub content_type: String,
+    pub file_size: usize,
+    pub sha256: String,
+}
+
+#[derive(Deserialize)]
+struct InitMultipartUploadResponse {
+    pub upload_id: String,
+    pub part_size: usize,
+    // other fields ignored
+}
+
+/// Error code of a multipart init rejected because the storage backend has
+/// no multipart support.
+const MULTIPART_UNSUPPORTED: &str = "multipart_unsupported";
+
+#[derive(Deserialize)]
+struct ErrorResponse {
+    pub error: String,
+    // other fields ignored
+}
+
+#[derive(Serialize)]
+struct UploadPartsRequest {
+    pub part_numbers: Vec<u32>,
+}
+
+#[derive(Deserialize)]
+struct UploadPartUrl {
+    pub part_number: u32,
+    pub upload_url: String,
+    // other fields ignored
+}
+
+#[derive(Deserialize)]
+struct UploadPartsResponse {
+    pub parts: Vec<UploadPartUrl>,
+}
+
+#[derive(Serialize)]
+struct CompletedPart {
+    pub part_number: u32,
+    pub etag: String,
 }
 
 #[derive(Deserialize)]
@@ -320,6 +372,13 @@ async fn upload_file(
     cf_token: &CFTokenCompute,
     params: FileUploadParams,
 ) -> Result<String, String> {
+    if params.data.len() >= config.multipart_threshold {
+        // Backends without multipart support (GCS) take the file in one PUT
+        if let Some(init_resp) = init_multipart_upload(config, token, cf_token, &params).await? {
+            return upload_file_multipart(config, token, cf_token, init_resp, params).await;
+        }
+    }
+
     // 1. Init Upload
     let url = format!("{}/v1/uploads/init", config.api_url());
     let request = Client::post(&url)
@@ -328,6 +387,7 @@ async fn upload_file(
             filename: params.filename.clone(),
             content_type: params.content_type.clone(),
             file_size: params.data.len(),
+            sha256: sha256_hex(&params.data),
         })
         .map_err(|e| e.to_string())?;
 
@@ -373,6 +433,7 @@ async fn upload_file(
             upload_id: init_resp.upload_id,
             title: params.title,
             description: params.description,
+            parts: Vec::new(),
         })
         .map_err(|e| e.to_string())?;
 
@@ -391,3 +452,188 @@ async fn upload_file(
         Err(complete_response.text().unwrap_or_default())
     }
 }
+
+/// Send an authenticated JSON POST to the API.
+async fn post_json<T: Serialize>(
+    url: &str,
+    token: &str,
+    cf_token: &CFTokenCompute,
+    body: &T,
+) -> Result<crate::http::Response, String> {
+    let request = Client::post(url)
+        .header("Authorization", format!("Bearer {token}"))
+        .json(body)
+        .map_err(|e| e.to_string())?;
+
+    let request = if let Some(cf) = cf_token.token() {
+        request.header("cf-access-token", cf)
+    } else {
+        request
+    };
+
+    request.send().await.map_err(|e| e.to_string())
+}
+
+/// Hex-encoded SHA-256 of a file, declared at upload init so the server can
+/// verify and deduplicate it.
+fn sha256_hex(data: &[u8]) -> String {
+    hex::encode(Sha256::digest(data))
+}
+
+/// Initialize a multipart upload.
+///
+/// Returns `None` if the storage backend does not support multipart uploads.
+async fn init_multipart_upload(
+    config: &BusinessConfig,
+    token: &str,
+    cf_token: &CFTokenCompute,
+    params: &FileUploadParams,
+) -> Result<Option<InitMultipartUploadResponse>, String> {
+    let url = format!("{}/v1/uploads/multipart/init", config.api_url());
+    let response = post_json(
+        &url,
+        token,
+        cf_token,
+        &InitMultipartUploadRequest {
+            filename: params.filename.clone(),
+            content_type: params.content_type.clone(),
+            file_size: params.data.len(),
+            sha256: sha256_hex(&params.data),
+        },
+    )
+    .await?;
+
+    if !response.is_success() {
+        if response
+            .json::<ErrorResponse>()
+            .is_ok_and(|error| error.error == MULTIPART_UNSUPPORTED)
+        {
+            return Ok(None);
+        }
+        return Err(format!(
+            "Init upload failed: {}",
+            response.text().unwrap_or_default()
+        ));
+    }
+
+    response.json().map(Some).map_err(|e| e.to_string())
+}
+
+/// Upload a large file in the parts of an initialized multipart upload.
+///
+/// The upload is aborted if any step fails.
+async fn upload_file_multipart(
+    config: &BusinessConfig,
+    token: &str,
+    cf_token: &CFTokenCompute,
+    init_resp: InitMultipartUploadResponse,
+    params: FileUploadParams,
+) -> Result<String, String> {
+    let upload_id = init_resp.upload_id.clone();
+
+    let result = upload_parts_and_complete(config, token, cf_token, init_resp, params).await;
+
+    if result.is_err() {
+        // Best effort: discard the uploaded parts
+        let abort_url = format!("{}/v1/uploads/{upload_id}/abort", config.api_url());
+        let _ = post_json(&abort_url, token, cf_token, &serde_json::json!({})).await;
+    }
+
+    result
+}
+
+async fn upload_parts_and_complete(
+    config: &BusinessConfig,
+    token: &str,
+    cf_token: &CFTokenCompute,
+    init_resp: InitMultipartUploadResponse,
+    params: FileUploadParams,
+) -> Result<String, String> {
+    // 2. Presign Part URLs
+    let parts_url = format!(
+        "{}/v1/uploads/{}/parts",
+        config.api_url(),
+        init_resp.upload_id
+    );
+    let response = post_json(
+        &parts_url,
+        token,
+        cf_token,
+        &UploadPartsRequest {
+            part_numbers: Vec::new(),
+        },
+    )
+    .await?;
+
+    if !response.is_success() {
+        return Err(format!(
+            "Presign upload parts failed: {}",
+            response.text().unwrap_or_default()
+        ));
+    }
+
+    let parts_resp: UploadPartsResponse = response.json().map_err(|e| e.to_string())?;
+
+    // 3. Upload Each Part (Direct PUT)
+    let mut completed = Vec::with_capacity(parts_resp.parts.len());
+    for part in parts_resp.parts {
+        // Part numbers start at 1
+        let Some(start) = (part.part_number as usize)
+            .checked_sub(1)
+            .map(|index| index * init_resp.part_size)
+        else {
+            return Err(format!("Invalid part number {}", part.part_number));
+        };
+        let end = (start + init_resp.part_size).min(params.data.len());
+        let Some(chunk) = params.data.get(start..end) else {
+            return Err(format!("Invalid part number {}", part.part_number));
+        };
+
+        let upload_response = Client::put(&part.upload_url)
+            .body(chunk.to_vec())
+            .send()
+            .await
+            .map_err(|e| e.to_string())?;
+
+        if !upload_response.is_success() {
+            return Err(format!(
+                "Upload of part {} failed: status {}",
+                part.part_number, upload_response.status
+            ));
+        }
+
+        let Some(etag) = upload_response.header("etag") else {
+            return Err(format!(
+                "Upload of part {} returned no ETag",
+                part.part_number
+            ));
+        };
+
+        completed.push(CompletedPart {
+            part_number: part.part_number,
+            etag: etag.to_owned(),
+        });
+    }
+
+    // 4. Complete Upload
+    let complete_url = format!("{}/v1/uploads/complete", config.api_url());
+    let complete_response = post_json(
+        &complete_url,
+        token,
+        cf_token,
+        &CompleteUploadRequest {
+            upload_id: init_resp.upload_id,
+            title: params.title,
+            description: params.description,
+            parts: completed,
+        },
+    )
+    .await?;
+
+    if complete_response.is_success() {
+        let resp: CompleteUploadResponse = complete_response.json().map_err(|e| e.to_string())?;
+        Ok(resp.content.id)
+    } else {
+        Err(complete_response.text().unwrap_or_default())
+    }
+}
@@ -19,6 +19,7 @@ pub enum Method {
     Get,
     Post,
     Put,
+    Patch,
     Delete,
 }
 
@@ -151,6 +152,7 @@ impl RequestBuilder {
             Method::Get => client.get(&self.url),
             Method::Post => client.post(&self.url),
             Method::Put => client.put(&self.url),
+            Method::Patch => client.patch(&self.url),
             Method::Delete => client.delete(&self.url),
         };
 
@@ -234,6 +236,7 @@ impl RequestBuilder {
             Method::Get => client.get(&url),
             Method::Post => client.post(&url),
             Method::Put => client.put(&url),
+            Method::Patch => client.patch(&url),
             Method::Delete => client.delete(&url),
         };
 
@@ -314,6 +317,11 @@ impl Client {
         RequestBuilder::new(Method::Put, url)
     }
 
+    /// Create a PATCH request.
+    pub fn patch(url: impl Into<String>) -> RequestBuilder {
+        RequestBuilder::new(Method::Patch, url)
+    }
+
     /// Create a DELETE request.
     pub fn delete(url: impl Into<String>) -> RequestBuilder {
         RequestBuilder::new(Method::Delete, url)
@@ -126,3 +126,29 @@ pub struct UpdateProfileResponse {
     /// The updated avatar URL.
     pub avatar_url: Option<String>,
 }
+
+/// Request to set a user's storage quota.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpdateQuotaRequest {
+    /// The quota in bytes (pass null to remove the limit).
+    pub quota_bytes: Option<i64>,
+}
+
+/// A user's storage quota and usage.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UserQuotaResponse {
+    /// The username.
+    pub username: String,
+    /// The quota in bytes, null when unlimited.
+    pub quota_bytes: Option<i64>,
+    /// Total bytes counted against the quota.
+    pub used_bytes: i64,
+    /// Bytes of active contents.
+    pub active_bytes: i64,
+    /// Bytes of archived contents.
+    pub archived_bytes: i64,
+    /// Bytes of trashed contents.
+    pub trashed_bytes: i64,
+    /// Declared bytes of uploads not yet completed.
+    pub pending_bytes: i64,
+}
@@ -17,7 +17,7 @@ use crate::http::Client;
 use crate::internal::{
     CreateUserRequest, CreateUserResponse, DeleteUserResponse, GetUserResponse, InternalUserItem,
     ListUsersResponse, RevokeOtpResponse, UpdateProfileRequest, UpdateProfileResponse,
-    UpdateUsernameRequest, UpdateUsernameResponse,
+    UpdateQuotaRequest, UpdateUsernameRequest, UpdateUsernameResponse, UserQuotaResponse,
 };
 
 /// Minimal error wrapper for API calls.
@@ -179,6 +179,63 @@ pub async fn update_profile(
     })
 }
 
+/// GET `/internal/users/{username}/quota`
+pub async fn get_quota(
+    api_base_url: &str,
+    cf_token: &CFTokenCompute,
+    username: &str,
+) -> ApiResult<UserQuotaResponse> {
+    let url = format!("{api_base_url}/internal/users/{username}/quota");
+
+    let response = build_request(&url, cf_token)
+        .send()
+        .await
+        .map_err(|e| InternalUsersApiError::new(e.to_string()))?;
+
+    if response.status != 200 {
+        return Err(http_status_error(response.status));
+    }
+
+    response
+        .json()
+        .map_err(|e| InternalUsersApiError::new(format!("Failed to parse UserQuotaResponse: {e}")))
+}
+
+/// PUT `/internal/users/{username}/quota`
+/// Body: `{ "quota_bytes": <number|null> }`
+pub async fn update_quota(
+    api_base_url: &str,
+    cf_token: &CFTokenCompute,
+    username: &str,
+    quota_bytes: Option<i64>,
+) -> ApiResult<UserQuotaResponse> {
+    let url = format!("{api_base_url}/internal/users/{username}/quota");
+
+    let body = UpdateQuotaRequest { quota_bytes };
+
+    let mut request = Client::put(&url).header("content-type", "application/json");
+    if let Some(token) = cf_token.token() {
+        request = request.header("cf-authorization", token);
+    }
+
+    let request = request
+        .json(&body)
+        .map_err(|e| InternalUsersApiError::new(format!("Failed to serialize request: {e}")))?;
+
+    let response = request
+        .send()
+        .await
+        .map_err(|e| InternalUsersApiError::new(e.to_string()))?;
+
+    if response.status != 200 {
+        return Err(http_status_error(response.status));
+    }
+
+    response
+        .json()
+        .map_err(|e| InternalUsersApiError::new(format!("Failed to parse UserQuotaResponse: {e}")))
+}
+
 /// DELETE `/internal/users/{username}`
 pub async fn delete_user(
     api_base_url: &str,
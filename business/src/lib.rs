@@ -18,7 +18,7 @@ pub use collects_utils::version_info;
 
 pub use api_status::{APIAvailability, ApiStatus, FetchApiStatusCommand, ToggleApiStatusCommand};
 pub use cf_token_compute::{CFTokenCompute, CFTokenInput, CFTokenResult, SetCFTokenCommand};
-pub use config::BusinessConfig;
+pub use config::{BusinessConfig, DEFAULT_MULTIPART_THRESHOLD};
 pub use content::{
     Attachment, ContentCreationStatus, CreateContentCommand, CreateContentCompute,
     CreateContentInput,
@@ -29,7 +29,8 @@ pub use create_user_compute::{
 pub use internal::{
     CreateUserRequest, CreateUserResponse, DeleteUserResponse, GetUserResponse, InternalUserItem,
     ListUsersResponse, RevokeOtpResponse, UpdateProfileRequest, UpdateProfileResponse,
-    UpdateUsernameRequest, UpdateUsernameResponse, is_internal_build,
+    UpdateQuotaRequest, UpdateUsernameRequest, UpdateUsernameResponse, UserQuotaResponse,
+    is_internal_build,
 };
 pub use internal_api_status::{
     FetchInternalApiStatusCommand, InternalAPIAvailability, InternalApiStatus,
@@ -48,9 +49,11 @@ pub use internal_users::{
     UpdateProfileCommand, UpdateUsernameCommand,
 };
 pub use list_content::{
-    ContentItem, GetContentCommand, GetContentCompute, GetContentInput, GetContentStatus,
-    GetViewUrlCommand, GetViewUrlCompute, GetViewUrlInput, GetViewUrlStatus, ListContentsCommand,
-    ListContentsCompute, ListContentsInput, ListContentsResponse, ListContentsStatus, ViewUrlData,
+    ContentItem, ContentMetadata, GetContentCommand, GetContentCompute, GetContentInput,
+    GetContentStatus, GetViewUrlCommand, GetViewUrlCompute, GetViewUrlInput, GetViewUrlStatus,
+    ListContentsCommand, ListContentsCompute, ListContentsInput, ListContentsResponse,
+    ListContentsStatus, UpdateContentCommand, UpdateContentCompute, UpdateContentInput,
+    UpdateContentStatus, ViewUrlData,
 };
 pub use list_groups::{
     AddGroupContentsCommand, AddGroupContentsCompute, AddGroupContentsInput,
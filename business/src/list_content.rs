@@ -1,6 +1,7 @@
-//! List and get content business operations.
+//! List, get and update content business operations.
 //!
-//! Provides commands and computes for listing user's content and getting specific content by ID.
+//! Provides commands and computes for listing user's content, getting specific content by ID
+//! and updating it.
 
 use std::any::Any;
 
@@ -49,6 +50,9 @@ pub struct ContentItem {
     /// Inline text content (only present when kind="text").
     #[serde(default)]
     pub body: Option<String>,
+    /// Media metadata extracted from the file.
+    #[serde(default)]
+    pub metadata: ContentMetadata,
     /// Timestamp when content was trashed (ISO 8601 format).
     #[serde(default)]
     pub trashed_at: Option<Ustr>,
@@ -61,6 +65,30 @@ pub struct ContentItem {
     pub updated_at: Ustr,
 }
 
+/// Media metadata of a content item.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ContentMetadata {
+    /// Image width in pixels, before applying `orientation`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub width: Option<u32>,
+    /// Image height in pixels, before applying `orientation`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub height: Option<u32>,
+    /// EXIF orientation (1-8).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub orientation: Option<u16>,
+    /// EXIF capture time in the camera's local time (`YYYY-MM-DDTHH:MM:SS`),
+    /// with its UTC offset when recorded.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub captured_at: Option<Ustr>,
+    /// Number of pages of a PDF.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub page_count: Option<u32>,
+    /// Duration of audio or video, in seconds.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duration_secs: Option<f64>,
+}
+
 impl ContentItem {
     /// Returns true if this is a file (stored in R2).
     pub fn is_file(&self) -> bool {
@@ -96,6 +124,9 @@ pub struct ListContentsResponse {
     pub items: Vec<ContentItem>,
     /// Total number of items returned.
     pub total: usize,
+    /// Cursor for the next page, absent on the last page.
+    #[serde(default)]
+    pub next_cursor: Option<String>,
 }
 
 // ============================================================================
@@ -111,6 +142,10 @@ pub struct ListContentsInput {
     pub offset: Option<i32>,
     /// Filter by status: "active", "archived", "trashed".
     pub status: Option<Ustr>,
+    /// Sort key, e.g. "created_at_desc" (default) or "title_asc".
+    pub sort: Option<Ustr>,
+    /// Cursor from a previous response's `next_cursor`.
+    pub cursor: Option<String>,
 }
 
 impl SnapshotClone for ListContentsInput {
@@ -230,6 +265,12 @@ impl Command for ListContentsCommand {
             if let Some(status) = &input.status {
                 params.push(format!("status={}", status));
             }
+            if let Some(sort) = &input.sort {
+                params.push(format!("sort={}", sort));
+            }
+            if let Some(cursor) = &input.cursor {
+                params.push(format!("cursor={}", cursor));
+            }
 
             let url = if params.is_empty() {
                 format!("{}/v1/contents", config.api_url())
@@ -328,6 +369,8 @@ pub enum GetContentStatus {
 #[derive(Default, Debug, Clone)]
 pub struct GetContentCompute {
     pub status: GetContentStatus,
+    /// ETag of the fetched version, to send as `UpdateContentInput::if_match`.
+    pub etag: Option<Ustr>,
 }
 
 impl SnapshotClone for GetContentCompute {
@@ -390,6 +433,7 @@ impl Command for GetContentCommand {
             if !auth.is_authenticated() {
                 updater.set(GetContentCompute {
                     status: GetContentStatus::Error("Not authenticated".to_owned()),
+                    etag: None,
                 });
                 return;
             }
@@ -397,12 +441,14 @@ impl Command for GetContentCommand {
             if input.id.is_empty() {
                 updater.set(GetContentCompute {
                     status: GetContentStatus::Error("Content ID is required".to_owned()),
+                    etag: None,
                 });
                 return;
             }
 
             updater.set(GetContentCompute {
                 status: GetContentStatus::Loading,
+                etag: None,
             });
 
             let token = auth.token().unwrap_or_default();
@@ -419,10 +465,12 @@ impl Command for GetContentCommand {
             match request.send().await {
                 Ok(response) => {
                     if response.is_success() {
+                        let etag = response.header("etag").map(Ustr::from);
                         match response.json::<ContentItem>() {
                             Ok(item) => {
                                 updater.set(GetContentCompute {
                                     status: GetContentStatus::Success(item),
+                                    etag,
                                 });
                             }
                             Err(e) => {
@@ -431,12 +479,14 @@ impl Command for GetContentCommand {
                                         "Failed to parse response: {}",
                                         e
                                     )),
+                                    etag: None,
                                 });
                             }
                         }
                     } else if response.status == 404 {
                         updater.set(GetContentCompute {
                             status: GetContentStatus::NotFound,
+                            etag: None,
                         });
                     } else {
                         let error = response
@@ -444,12 +494,251 @@ impl Command for GetContentCommand {
                             .unwrap_or_else(|_| "Unknown error".to_owned());
                         updater.set(GetContentCompute {
                             status: GetContentStatus::Error(error),
+                            etag: None,
                         });
                     }
                 }
                 Err(e) => {
                     updater.set(GetContentCompute {
                         status: GetContentStatus::Error(e.to_string()),
+                        etag: None,
+                    });
+                }
+            }
+        })
+    }
+}
+
+// ============================================================================
+// Update Content
+// ============================================================================
+
+/// Input for updating a content's title, description or text body.
+#[derive(Default, Debug, Clone)]
+pub struct UpdateContentInput {
+    /// Content ID (UUID string).
+    pub id: Ustr,
+    /// New title, or `None` to keep it.
+    pub title: Option<String>,
+    /// New description, or `None` to keep it.
+    pub description: Option<String>,
+    /// New text body, or `None` to keep it.
+    pub body: Option<String>,
+    /// ETag the edit was based on (see `GetContentCompute::etag`). When set,
+    /// the update fails with `Conflict` if the content changed meanwhile.
+    pub if_match: Option<Ustr>,
+}
+
+impl SnapshotClone for UpdateContentInput {
+    fn clone_boxed(&self) -> Option<Box<dyn Any + Send>> {
+        Some(Box::new(self.clone()))
+    }
+}
+
+impl State for UpdateContentInput {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    fn assign_box(&mut self, new_self: Box<dyn Any + Send>) {
+        state_assign_impl(self, new_self);
+    }
+}
+
+/// Status of the update content operation.
+#[derive(Debug, Clone, Default)]
+pub enum UpdateContentStatus {
+    #[default]
+    Idle,
+    Updating,
+    Success(ContentItem),
+    /// The content changed since `if_match` was fetched; reload and retry.
+    Conflict,
+    NotFound,
+    Error(String),
+}
+
+/// Compute to track update content status.
+#[derive(Default, Debug, Clone)]
+pub struct UpdateContentCompute {
+    pub status: UpdateContentStatus,
+    /// ETag of the updated version, for a follow-up edit.
+    pub etag: Option<Ustr>,
+}
+
+impl SnapshotClone for UpdateContentCompute {
+    fn clone_boxed(&self) -> Option<Box<dyn Any + Send>> {
+        Some(Box::new(self.clone()))
+    }
+}
+
+impl Compute for UpdateContentCompute {
+    fn deps(&self) -> ComputeDeps {
+        const STATE_IDS: [std::any::TypeId; 0] = [];
+        const COMPUTE_IDS: [std::any::TypeId; 0] = [];
+        (&STATE_IDS, &COMPUTE_IDS)
+    }
+
+    fn compute(&self, _deps: Dep, _updater: Updater) {
+        // No-op, updated by command
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn assign_box(&mut self, new_self: Box<dyn Any + Send>) {
+        assign_impl(self, new_self);
+    }
+}
+
+impl State for UpdateContentCompute {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    fn assign_box(&mut self, new_self: Box<dyn Any + Send>) {
+        state_assign_impl(self, new_self);
+    }
+}
+
+/// Request body for the update content endpoint.
+#[derive(Debug, Serialize)]
+struct UpdateContentRequest {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    title: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    body: Option<String>,
+}
+
+/// Command to update a content's title, description or text body.
+#[derive(Default, Debug)]
+pub struct UpdateContentCommand;
+
+impl Command for UpdateContentCommand {
+    fn run(
+        &self,
+        snap: CommandSnapshot,
+        updater: LatestOnlyUpdater,
+        _cancel: tokio_util::sync::CancellationToken,
+    ) -> std::pin::Pin<Box<dyn std::future::Future<Output = ()> + Send>> {
+        let input: UpdateContentInput = snap.state::<UpdateContentInput>().clone();
+        let config: BusinessConfig = snap.state::<BusinessConfig>().clone();
+        let auth: AuthCompute = snap.compute::<AuthCompute>().clone();
+        let cf_token: CFTokenCompute = snap.compute::<CFTokenCompute>().clone();
+
+        Box::pin(async move {
+            if !auth.is_authenticated() {
+                updater.set(UpdateContentCompute {
+                    status: UpdateContentStatus::Error("Not authenticated".to_owned()),
+                    etag: None,
+                });
+                return;
+            }
+
+            if input.id.is_empty() {
+                updater.set(UpdateContentCompute {
+                    status: UpdateContentStatus::Error("Content ID is required".to_owned()),
+                    etag: None,
+                });
+                return;
+            }
+
+            updater.set(UpdateContentCompute {
+                status: UpdateContentStatus::Updating,
+                etag: None,
+            });
+
+            let token = auth.token().unwrap_or_default();
+            let url = format!("{}/v1/contents/{}", config.api_url(), input.id);
+
+            let request = match Client::patch(&url)
+                .header("Authorization", format!("Bearer {token}"))
+                .json(&UpdateContentRequest {
+                    title: input.title,
+                    description: input.description,
+                    body: input.body,
+                }) {
+                Ok(r) => r,
+                Err(e) => {
+                    updater.set(UpdateContentCompute {
+                        status: UpdateContentStatus::Error(format!(
+                            "Failed to build request: {}",
+                            e
+                        )),
+                        etag: None,
+                    });
+                    return;
+                }
+            };
+
+            let request = if let Some(if_match) = input.if_match {
+                request.header("If-Match", if_match.as_str())
+            } else {
+                request
+            };
+
+            let request = if let Some(cf) = cf_token.token() {
+                request.header("cf-access-token", cf)
+            } else {
+                request
+            };
+
+            match request.send().await {
+                Ok(response) => {
+                    if response.is_success() {
+                        let etag = response.header("etag").map(Ustr::from);
+                        match response.json::<ContentItem>() {
+                            Ok(item) => {
+                                updater.set(UpdateContentCompute {
+                                    status: UpdateContentStatus::Success(item),
+                                    etag,
+                                });
+                            }
+                            Err(e) => {
+                                updater.set(UpdateContentCompute {
+                                    status: UpdateContentStatus::Error(format!(
+                                        "Failed to parse response: {}",
+                                        e
+                                    )),
+                                    etag: None,
+                                });
+                            }
+                        }
+                    } else if response.status == 412 {
+                        updater.set(UpdateContentCompute {
+                            status: UpdateContentStatus::Conflict,
+                            etag: None,
+                        });
+                    } else if response.status == 404 {
+                        updater.set(UpdateContentCompute {
+                            status: UpdateContentStatus::NotFound,
+                            etag: None,
+                        });
+                    } else {
+                        let error = response
+                            .text()
+                            .unwrap_or_else(|_| "Unknown error".to_owned());
+                        updater.set(UpdateContentCompute {
+                            status: UpdateContentStatus::Error(error),
+                            etag: None,
+                        });
+                    }
+                }
+                Err(e) => {
+                    updater.set(UpdateContentCompute {
+                        status: UpdateContentStatus::Error(e.to_string()),
+                        etag: None,
                     });
                 }
             }
@@ -468,6 +757,8 @@ pub struct GetViewUrlInput {
     pub content_id: Ustr,
     /// Disposition: "inline" or "attachment".
     pub disposition: Ustr,
+    /// Thumbnail size (128, 512 or 1024) to view instead of the original image.
+    pub thumbnail_size: Option<u32>,
 }
 
 impl SnapshotClone for GetViewUrlInput {
@@ -497,6 +788,9 @@ pub struct ViewUrlData {
     pub url: Ustr,
     /// Expiration timestamp (ISO 8601 format).
     pub expires_at: Ustr,
+    /// Size of the thumbnail the URL points to, `None` for the original.
+    #[serde(default)]
+    pub thumbnail_size: Option<u32>,
 }
 
 /// Status of the get view URL operation.
@@ -598,11 +892,14 @@ impl Command for GetViewUrlCommand {
             });
 
             let token = auth.token().unwrap_or_default();
-            let url = format!(
+            let mut url = format!(
                 "{}/v1/contents/{}/view-url",
                 config.api_url(),
                 input.content_id
             );
+            if let Some(size) = input.thumbnail_size {
+                url.push_str(&format!("?size={size}"));
+            }
 
             let disposition = if input.disposition.is_empty() {
                 "inline".to_owned()
@@ -36,7 +36,7 @@ use std::time::Duration;
 use ustr::Ustr;
 use wiremock::{
     Mock, MockServer, ResponseTemplate,
-    matchers::{header, method, path},
+    matchers::{body_partial_json, header, method, path, query_param},
 };
 
 use crate::{
@@ -47,7 +47,8 @@ use crate::{
     GetViewUrlCommand, GetViewUrlCompute, GetViewUrlInput, GroupContentItem, GroupItem,
     ListContentsCommand, ListContentsCompute, ListContentsInput, ListGroupsCommand,
     ListGroupsCompute, ListGroupsInput, LoginCommand, LoginInput, PendingTokenValidation,
-    ValidateTokenCommand, list_content::ContentItem,
+    UpdateContentCommand, UpdateContentCompute, UpdateContentInput, ValidateTokenCommand,
+    list_content::{ContentItem, ContentMetadata},
 };
 use collects_states::StateCtx;
 
@@ -83,6 +84,13 @@ impl TestContext {
         self.ctx.sync_computes();
     }
 
+    /// Upload files of at least `threshold` bytes with multipart uploads.
+    pub fn set_multipart_threshold(&mut self, threshold: usize) {
+        self.ctx.update::<BusinessConfig>(|config| {
+            config.multipart_threshold = threshold;
+        });
+    }
+
     /// Set a CF Access token for internal endpoints.
     pub fn set_cf_token(&mut self, token: &str) {
         // Update CFTokenCompute via the Updater (since it's a Compute, not a State)
@@ -212,7 +220,9 @@ impl TestContext {
     /// Mock the get content endpoint.
     pub async fn mock_get_content(&self, id: &str, content: ContentItem) {
         // GetContentCommand expects a flat ContentItem response, not wrapped in {"content": ...}
-        let response = ResponseTemplate::new(200).set_body_json(content);
+        let response = ResponseTemplate::new(200)
+            .insert_header("etag", "\"1\"")
+            .set_body_json(content);
 
         Mock::given(method("GET"))
             .and(path(format!("/api/v1/contents/{}", id)))
@@ -235,6 +245,31 @@ impl TestContext {
             .await;
     }
 
+    /// Mock the update content endpoint, accepting only `If-Match: "1"`.
+    pub async fn mock_update_content(&self, id: &str, content: ContentItem) {
+        let response = ResponseTemplate::new(200)
+            .insert_header("etag", "\"2\"")
+            .set_body_json(content);
+
+        Mock::given(method("PATCH"))
+            .and(path(format!("/api/v1/contents/{}", id)))
+            .and(header("If-Match", "\"1\""))
+            .respond_with(response)
+            .mount(&self.mock_server)
+            .await;
+
+        let response = ResponseTemplate::new(412).set_body_json(serde_json::json!({
+            "error": "precondition_failed",
+            "message": "The resource was modified since it was fetched"
+        }));
+
+        Mock::given(method("PATCH"))
+            .and(path(format!("/api/v1/contents/{}", id)))
+            .respond_with(response)
+            .mount(&self.mock_server)
+            .await;
+    }
+
     /// Mock the view URL endpoint.
     pub async fn mock_view_url(&self, content_id: &str, url: &str, expires_at: &str) {
         let response = ResponseTemplate::new(200).set_body_json(serde_json::json!({
@@ -250,6 +285,23 @@ impl TestContext {
             .await;
     }
 
+    /// Mock the view URL endpoint for a thumbnail of `size`.
+    pub async fn mock_thumbnail_view_url(&self, content_id: &str, size: u32, url: &str) {
+        let response = ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "url": url,
+            "expires_at": "2024-12-31T23:59:59Z",
+            "thumbnail_size": size
+        }));
+
+        Mock::given(method("POST"))
+            .and(path(format!("/api/v1/contents/{}/view-url", content_id)))
+            .and(query_param("size", size.to_string()))
+            .and(header("Authorization", "Bearer test_token"))
+            .respond_with(response)
+            .mount(&self.mock_server)
+            .await;
+    }
+
     /// Mock the view URL endpoint with error.
     pub async fn mock_view_url_error(&self, content_id: &str, status: u16, error: &str) {
         let response = ResponseTemplate::new(status).set_body_json(serde_json::json!({
@@ -331,6 +383,74 @@ impl TestContext {
         self.mock_upload_complete(content_id).await;
     }
 
+    /// Mock a multipart upload of `part_count` parts of `part_size` bytes.
+    ///
+    /// Each part PUT returns the ETag `"etag-{part_number}"`, and completion
+    /// only matches when every part is reported with its ETag.
+    pub async fn mock_multipart_upload(
+        &self,
+        upload_id: &str,
+        content_id: &str,
+        part_size: usize,
+        part_count: u32,
+    ) {
+        Mock::given(method("POST"))
+            .and(path("/api/v1/uploads/multipart/init"))
+            .and(header("Authorization", "Bearer test_token"))
+            .respond_with(ResponseTemplate::new(201).set_body_json(serde_json::json!({
+                "upload_id": upload_id,
+                "part_size": part_size,
+                "part_count": part_count
+            })))
+            .mount(&self.mock_server)
+            .await;
+
+        let parts: Vec<_> = (1..=part_count)
+            .map(|n| {
+                serde_json::json!({
+                    "part_number": n,
+                    "upload_url": format!("{}/mock-upload/{upload_id}/{n}", self.mock_server.uri())
+                })
+            })
+            .collect();
+        Mock::given(method("POST"))
+            .and(path(format!("/api/v1/uploads/{upload_id}/parts")))
+            .and(header("Authorization", "Bearer test_token"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(serde_json::json!({ "parts": parts })),
+            )
+            .mount(&self.mock_server)
+            .await;
+
+        for n in 1..=part_count {
+            Mock::given(method("PUT"))
+                .and(path(format!("/mock-upload/{upload_id}/{n}")))
+                .respond_with(
+                    ResponseTemplate::new(200).insert_header("ETag", format!("\"etag-{n}\"")),
+                )
+                .mount(&self.mock_server)
+                .await;
+        }
+
+        let completed: Vec<_> = (1..=part_count)
+            .map(|n| serde_json::json!({ "part_number": n, "etag": format!("\"etag-{n}\"") }))
+            .collect();
+        Mock::given(method("POST"))
+            .and(path("/api/v1/uploads/complete"))
+            .and(header("Authorization", "Bearer test_token"))
+            .and(body_partial_json(serde_json::json!({
+                "upload_id": upload_id,
+                "parts": completed
+            })))
+            .respond_with(ResponseTemplate::new(201).set_body_json(serde_json::json!({
+                "content": {
+                    "id": content_id
+                }
+            })))
+            .mount(&self.mock_server)
+            .await;
+    }
+
     // =========================================================================
     // Group (Collect) mock helpers
     // =========================================================================
@@ -456,6 +576,10 @@ fn build_test_state_ctx(config: BusinessConfig) -> StateCtx {
     ctx.add_state(GetContentInput::default());
     ctx.record_compute(GetContentCompute::default());
 
+    // Update content states and computes
+    ctx.add_state(UpdateContentInput::default());
+    ctx.record_compute(UpdateContentCompute::default());
+
     // Get view URL states and computes
     ctx.add_state(GetViewUrlInput::default());
     ctx.record_compute(GetViewUrlCompute::default());
@@ -476,6 +600,7 @@ fn build_test_state_ctx(config: BusinessConfig) -> StateCtx {
     ctx.record_command(CreateContentCommand);
     ctx.record_command(ListContentsCommand);
     ctx.record_command(GetContentCommand);
+    ctx.record_command(UpdateContentCommand);
     ctx.record_command(GetViewUrlCommand);
     ctx.record_command(ListGroupsCommand);
     ctx.record_command(CreateGroupCommand);
@@ -500,6 +625,7 @@ pub fn sample_content_item(id: &str) -> ContentItem {
         visibility: Ustr::from("private"),
         kind: Ustr::from("file"),
         body: None,
+        metadata: ContentMetadata::default(),
         trashed_at: None,
         archived_at: None,
         created_at: Ustr::from("2024-01-01T00:00:00Z"),
@@ -522,6 +648,7 @@ pub fn sample_text_content(id: &str, body: &str) -> ContentItem {
         visibility: Ustr::from("private"),
         kind: Ustr::from("text"),
         body: Some(body.to_owned()),
+        metadata: ContentMetadata::default(),
         trashed_at: None,
         archived_at: None,
         created_at: Ustr::from("2024-01-01T00:00:00Z"),
@@ -559,7 +686,9 @@ pub fn sample_group_content_item(id: &str, group_id: &str, content_id: &str) ->
 mod tests {
     use super::*;
     use crate::content::ContentCreationStatus;
-    use crate::list_content::{GetContentStatus, GetViewUrlStatus, ListContentsStatus};
+    use crate::list_content::{
+        GetContentStatus, GetViewUrlStatus, ListContentsStatus, UpdateContentStatus,
+    };
     use crate::list_groups::{
         AddGroupContentsStatus, CreateGroupStatus, GetGroupContentsStatus, ListGroupsStatus,
     };
@@ -652,6 +781,7 @@ mod tests {
             }
             other => panic!("Expected Success, got {:?}", other),
         }
+        assert_eq!(compute.etag.as_deref(), Some("\"1\""));
 
         test_ctx.shutdown().await;
     }
@@ -675,6 +805,56 @@ mod tests {
         test_ctx.shutdown().await;
     }
 
+    #[tokio::test]
+    async fn test_update_content_with_if_match() {
+        let mut test_ctx = TestContext::new().await;
+        test_ctx.set_authenticated("test_token");
+
+        let content = sample_text_content("123", "Edited");
+        test_ctx.mock_update_content("123", content).await;
+
+        test_ctx.ctx.update::<UpdateContentInput>(|input| {
+            input.id = Ustr::from("123");
+            input.body = Some("Edited".to_owned());
+            input.if_match = Some(Ustr::from("\"1\""));
+        });
+        test_ctx.ctx.enqueue_command::<UpdateContentCommand>();
+        test_ctx.flush_and_wait().await;
+
+        let compute = test_ctx.ctx.compute::<UpdateContentCompute>();
+        match &compute.status {
+            UpdateContentStatus::Success(item) => {
+                assert_eq!(item.body, Some("Edited".to_owned()));
+            }
+            other => panic!("Expected Success, got {:?}", other),
+        }
+        assert_eq!(compute.etag.as_deref(), Some("\"2\""));
+
+        test_ctx.shutdown().await;
+    }
+
+    #[tokio::test]
+    async fn test_update_content_stale_if_match_conflicts() {
+        let mut test_ctx = TestContext::new().await;
+        test_ctx.set_authenticated("test_token");
+
+        let content = sample_text_content("123", "Edited");
+        test_ctx.mock_update_content("123", content).await;
+
+        test_ctx.ctx.update::<UpdateContentInput>(|input| {
+            input.id = Ustr::from("123");
+            input.body = Some("Edited".to_owned());
+            input.if_match = Some(Ustr::from("\"0\""));
+        });
+        test_ctx.ctx.enqueue_command::<UpdateContentCommand>();
+        test_ctx.flush_and_wait().await;
+
+        let compute = test_ctx.ctx.compute::<UpdateContentCompute>();
+        assert!(matches!(compute.status, UpdateContentStatus::Conflict));
+
+        test_ctx.shutdown().await;
+    }
+
     #[tokio::test]
     async fn test_get_view_url_success() {
         let mut test_ctx = TestContext::new().await;
@@ -707,6 +887,35 @@ mod tests {
         test_ctx.shutdown().await;
     }
 
+    #[tokio::test]
+    async fn test_get_view_url_thumbnail() {
+        let mut test_ctx = TestContext::new().await;
+        test_ctx.set_authenticated("test_token");
+
+        test_ctx
+            .mock_thumbnail_view_url("123", 512, "https://example.com/a.png.thumb-512.png")
+            .await;
+
+        test_ctx.ctx.update::<GetViewUrlInput>(|input| {
+            input.content_id = Ustr::from("123");
+            input.disposition = Ustr::from("inline");
+            input.thumbnail_size = Some(512);
+        });
+        test_ctx.ctx.enqueue_command::<GetViewUrlCommand>();
+        test_ctx.flush_and_wait().await;
+
+        let compute = test_ctx.ctx.compute::<GetViewUrlCompute>();
+        match &compute.status {
+            GetViewUrlStatus::Success(data) => {
+                assert_eq!(data.url, "https://example.com/a.png.thumb-512.png");
+                assert_eq!(data.thumbnail_size, Some(512));
+            }
+            other => panic!("Expected Success, got {:?}", other),
+        }
+
+        test_ctx.shutdown().await;
+    }
+
     #[tokio::test]
     async fn test_create_inline_content_success() {
         let mut test_ctx = TestContext::new().await;
@@ -785,6 +994,141 @@ mod tests {
             other => panic!("Expected Success, got {:?}", other),
         }
 
+        // The file's SHA-256 is declared at init
+        let requests = test_ctx.mock_server.received_requests().await.unwrap();
+        let init = requests
+            .iter()
+            .find(|r| r.url.path() == "/api/v1/uploads/init")
+            .unwrap();
+        let init: serde_json::Value = serde_json::from_slice(&init.body).unwrap();
+        assert_eq!(
+            init["sha256"],
+            "dffd6021bb2bd5b0af676290809ec3a53191dd81c7f70a4b28688a362182986f"
+        );
+
+        test_ctx.shutdown().await;
+    }
+
+    #[tokio::test]
+    async fn test_upload_large_file_uses_multipart() {
+        let mut test_ctx = TestContext::new().await;
+        test_ctx.set_authenticated("test_token");
+        test_ctx.set_multipart_threshold(8);
+
+        test_ctx
+            .mock_multipart_upload("upload-789", "content-789", 4, 3)
+            .await;
+
+        test_ctx.ctx.update::<CreateContentInput>(|input| {
+            input.attachments = vec![crate::Attachment {
+                filename: "big.bin".to_owned(),
+                mime_type: "application/octet-stream".to_owned(),
+                data: b"0123456789".to_vec(),
+            }];
+        });
+        test_ctx.ctx.enqueue_command::<CreateContentCommand>();
+        test_ctx.flush_and_wait().await;
+
+        let compute = test_ctx.ctx.compute::<CreateContentCompute>();
+        match &compute.status {
+            ContentCreationStatus::Success(ids) => {
+                assert_eq!(ids, &vec!["content-789".to_owned()]);
+            }
+            other => panic!("Expected Success, got {:?}", other),
+        }
+
+        let requests = test_ctx.mock_server.received_requests().await.unwrap();
+        let part_bodies: Vec<_> = requests
+            .iter()
+            .filter(|r| r.url.path().starts_with("/mock-upload/"))
+            .map(|r| r.body.clone())
+            .collect();
+        assert_eq!(
+            part_bodies,
+            vec![b"0123".to_vec(), b"4567".to_vec(), b"89".to_vec()]
+        );
+
+        test_ctx.shutdown().await;
+    }
+
+    #[tokio::test]
+    async fn test_multipart_upload_failure_aborts_upload() {
+        let mut test_ctx = TestContext::new().await;
+        test_ctx.set_authenticated("test_token");
+        test_ctx.set_multipart_threshold(8);
+
+        // A failing part upload takes precedence over the mocked success
+        Mock::given(method("PUT"))
+            .and(path("/mock-upload/upload-bad/2"))
+            .respond_with(ResponseTemplate::new(500))
+            .mount(&test_ctx.mock_server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/api/v1/uploads/upload-bad/abort"))
+            .respond_with(ResponseTemplate::new(204))
+            .expect(1)
+            .mount(&test_ctx.mock_server)
+            .await;
+        test_ctx
+            .mock_multipart_upload("upload-bad", "content-bad", 4, 3)
+            .await;
+
+        test_ctx.ctx.update::<CreateContentInput>(|input| {
+            input.attachments = vec![crate::Attachment {
+                filename: "big.bin".to_owned(),
+                mime_type: "application/octet-stream".to_owned(),
+                data: b"0123456789".to_vec(),
+            }];
+        });
+        test_ctx.ctx.enqueue_command::<CreateContentCommand>();
+        test_ctx.flush_and_wait().await;
+
+        let compute = test_ctx.ctx.compute::<CreateContentCompute>();
+        match &compute.status {
+            ContentCreationStatus::Error(msg) => assert!(msg.contains("part 2")),
+            other => panic!("Expected Error, got {:?}", other),
+        }
+
+        test_ctx.mock_server.verify().await;
+        test_ctx.shutdown().await;
+    }
+
+    #[tokio::test]
+    async fn test_multipart_unsupported_falls_back_to_single_upload() {
+        let mut test_ctx = TestContext::new().await;
+        test_ctx.set_authenticated("test_token");
+        test_ctx.set_multipart_threshold(8);
+
+        Mock::given(method("POST"))
+            .and(path("/api/v1/uploads/multipart/init"))
+            .respond_with(ResponseTemplate::new(400).set_body_json(serde_json::json!({
+                "error": "multipart_unsupported",
+                "message": "multipart uploads are not supported by gcs"
+            })))
+            .expect(1)
+            .mount(&test_ctx.mock_server)
+            .await;
+        test_ctx.mock_full_upload("upload-gcs", "content-gcs").await;
+
+        test_ctx.ctx.update::<CreateContentInput>(|input| {
+            input.attachments = vec![crate::Attachment {
+                filename: "big.bin".to_owned(),
+                mime_type: "application/octet-stream".to_owned(),
+                data: b"0123456789".to_vec(),
+            }];
+        });
+        test_ctx.ctx.enqueue_command::<CreateContentCommand>();
+        test_ctx.flush_and_wait().await;
+
+        let compute = test_ctx.ctx.compute::<CreateContentCompute>();
+        match &compute.status {
+            ContentCreationStatus::Success(ids) => {
+                assert_eq!(ids, &vec!["content-gcs".to_owned()]);
+            }
+            other => panic!("Expected Success, got {:?}", other),
+        }
+
+        test_ctx.mock_server.verify().await;
         test_ctx.shutdown().await;
     }
 
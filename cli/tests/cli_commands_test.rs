@@ -21,7 +21,7 @@ use collects_states::StateCtx;
 use ustr::Ustr;
 use wiremock::{
     Mock, MockServer, ResponseTemplate,
-    matchers::{header, method, path},
+    matchers::{body_partial_json, header, method, path},
 };
 
 /// Test context for CLI integration tests.
@@ -177,6 +177,62 @@ impl CliTestContext {
             .await;
     }
 
+    /// Mock a multipart upload of two 4-byte parts.
+    async fn mock_multipart_upload(&self, upload_id: &str, content_id: &str) {
+        Mock::given(method("POST"))
+            .and(path("/api/v1/uploads/multipart/init"))
+            .and(header("Authorization", "Bearer test_token"))
+            .respond_with(ResponseTemplate::new(201).set_body_json(serde_json::json!({
+                "upload_id": upload_id,
+                "part_size": 4,
+                "part_count": 2
+            })))
+            .mount(&self.mock_server)
+            .await;
+
+        let parts: Vec<_> = (1..=2)
+            .map(|n| {
+                serde_json::json!({
+                    "part_number": n,
+                    "upload_url": format!("{}/mock-upload/{upload_id}/{n}", self.mock_server.uri())
+                })
+            })
+            .collect();
+        Mock::given(method("POST"))
+            .and(path(format!("/api/v1/uploads/{upload_id}/parts")))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(serde_json::json!({ "parts": parts })),
+            )
+            .mount(&self.mock_server)
+            .await;
+
+        for n in 1..=2 {
+            Mock::given(method("PUT"))
+                .and(path(format!("/mock-upload/{upload_id}/{n}")))
+                .respond_with(ResponseTemplate::new(200).insert_header("ETag", format!("etag-{n}")))
+                .expect(1)
+                .mount(&self.mock_server)
+                .await;
+        }
+
+        Mock::given(method("POST"))
+            .and(path("/api/v1/uploads/complete"))
+            .and(body_partial_json(serde_json::json!({
+                "upload_id": upload_id,
+                "parts": [
+                    { "part_number": 1, "etag": "etag-1" },
+                    { "part_number": 2, "etag": "etag-2" }
+                ]
+            })))
+            .respond_with(ResponseTemplate::new(201).set_body_json(serde_json::json!({
+                "content": {
+                    "id": content_id
+                }
+            })))
+            .mount(&self.mock_server)
+            .await;
+    }
+
     /// Mock the full upload flow (init + put + complete).
     async fn mock_full_upload(&self, upload_id: &str, content_id: &str) {
         // Mock upload init
@@ -393,6 +449,40 @@ async fn test_new_collect_with_file() {
     test_ctx.shutdown().await;
 }
 
+/// Test that files above the multipart threshold are uploaded in parts.
+#[tokio::test]
+async fn test_new_collect_with_large_file_uses_multipart() {
+    let mut test_ctx = CliTestContext::new().await;
+    test_ctx.set_authenticated("test_token");
+    test_ctx.ctx.update::<BusinessConfig>(|config| {
+        config.multipart_threshold = 8;
+    });
+
+    test_ctx
+        .mock_multipart_upload("upload-large", "content-large")
+        .await;
+
+    test_ctx.ctx.update::<CreateContentInput>(|input| {
+        input.attachments = vec![Attachment {
+            filename: "video.mp4".to_owned(),
+            mime_type: "video/mp4".to_owned(),
+            data: b"12345678".to_vec(),
+        }];
+    });
+    test_ctx.ctx.enqueue_command::<CreateContentCommand>();
+    test_ctx.flush_and_wait().await;
+
+    match &test_ctx.ctx.compute::<CreateContentCompute>().status {
+        collects_business::ContentCreationStatus::Success(ids) => {
+            assert_eq!(ids, &vec!["content-large".to_owned()]);
+        }
+        other => panic!("Expected ContentCreationStatus::Success, got {other:?}"),
+    }
+
+    test_ctx.mock_server.verify().await;
+    test_ctx.shutdown().await;
+}
+
 /// Test that creating a collect fails properly when not authenticated.
 #[tokio::test]
 async fn test_new_collect_unauthenticated() {
@@ -0,0 +1,21 @@
+-- Add multipart upload support
+--
+-- Large files are uploaded in parts, each with its own presigned URL.
+-- `uploads.multipart_upload_id` is the backend's upload ID (NULL for single PUT uploads)
+-- and `upload_parts` tracks which parts the client has reported as uploaded so an
+-- interrupted upload can be resumed.
+
+ALTER TABLE uploads
+    ADD COLUMN multipart_upload_id TEXT,
+    ADD COLUMN part_size BIGINT;
+
+CREATE TABLE upload_parts (
+    upload_id UUID NOT NULL REFERENCES uploads(id) ON DELETE CASCADE,
+    part_number INTEGER NOT NULL CHECK (part_number BETWEEN 1 AND 10000),
+    size BIGINT NOT NULL,
+    etag TEXT,
+    status VARCHAR(20) NOT NULL DEFAULT 'pending' CHECK (status IN ('pending', 'uploaded')),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    PRIMARY KEY (upload_id, part_number)
+);
@@ -0,0 +1,33 @@
+-- Add content checksums and deduplicated storage objects
+--
+-- Clients declare the SHA-256 of a file when initializing its upload; the digest is
+-- verified against the stored object on completion and kept on the content.
+-- `storage_objects` maps a user's digest to the single stored object shared by all of
+-- their contents with that digest. `ref_count` is the number of contents pointing at
+-- it; the object is deleted from storage once the count drops to zero.
+
+ALTER TABLE uploads
+    ADD COLUMN sha256 CHAR(64) CHECK (sha256 ~ '^[0-9a-f]{64}$');
+
+ALTER TABLE contents
+    ADD COLUMN sha256 CHAR(64) CHECK (sha256 ~ '^[0-9a-f]{64}$');
+
+CREATE TABLE storage_objects (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    sha256 CHAR(64) NOT NULL CHECK (sha256 ~ '^[0-9a-f]{64}$'),
+
+    storage_backend TEXT NOT NULL,
+    storage_profile TEXT NOT NULL,
+    storage_key TEXT NOT NULL,
+    file_size BIGINT NOT NULL,
+
+    ref_count INTEGER NOT NULL DEFAULT 1 CHECK (ref_count >= 0),
+
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+
+    CONSTRAINT storage_objects_user_sha256_unique UNIQUE (user_id, sha256)
+);
+
+CREATE INDEX idx_contents_user_sha256 ON contents(user_id, sha256) WHERE sha256 IS NOT NULL;
@@ -0,0 +1,7 @@
+-- Index trashed groups by trash time
+--
+-- Trashed contents and groups are purged once they have been in the trash longer
+-- than the retention period (TRASH_RETENTION_DAYS). Contents already have
+-- idx_contents_trashed_at.
+
+CREATE INDEX idx_content_groups_trashed_at ON content_groups(trashed_at) WHERE trashed_at IS NOT NULL;
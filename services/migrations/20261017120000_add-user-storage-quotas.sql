@@ -0,0 +1,10 @@
+-- Add per-user storage quotas
+--
+-- `storage_quota_bytes` caps the total `file_size` of a user's contents (active,
+-- archived and trashed) plus their uploads still in flight. NULL means unlimited.
+-- Managers set it through the internal user routes.
+
+ALTER TABLE users
+    ADD COLUMN storage_quota_bytes BIGINT CHECK (storage_quota_bytes >= 0);
+
+CREATE INDEX idx_contents_user_status ON contents(user_id, status);
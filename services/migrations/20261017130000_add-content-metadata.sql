@@ -0,0 +1,16 @@
+-- Add extracted media metadata to contents
+--
+-- `metadata` holds what was read from an uploaded file once it completed:
+-- image dimensions, EXIF capture time and orientation, PDF page count, and
+-- audio/video duration. Text contents and files nothing could be read from
+-- keep an empty object.
+--
+-- `captured_at` is the camera's local time (`YYYY-MM-DDTHH:MM:SS`, with an
+-- offset when EXIF records one), so photos sort by when they were taken.
+
+ALTER TABLE contents
+    ADD COLUMN metadata JSONB NOT NULL DEFAULT '{}'::jsonb;
+
+CREATE INDEX idx_contents_user_captured_at
+    ON contents(user_id, (metadata->>'captured_at'))
+    WHERE metadata ? 'captured_at';
@@ -0,0 +1,16 @@
+-- Add full-text search over contents
+--
+-- `search_vector` indexes the title (weight A), description (B) and inline
+-- text body (C) with the `english` configuration, so searches match stemmed
+-- words ("notes" finds "note"). It is generated, so it stays current on every
+-- insert and update without triggers. Text bodies are capped at 64 KiB by
+-- the API, well under the tsvector size limit.
+
+ALTER TABLE contents
+    ADD COLUMN search_vector TSVECTOR GENERATED ALWAYS AS (
+        setweight(to_tsvector('english', coalesce(title, '')), 'A')
+        || setweight(to_tsvector('english', coalesce(description, '')), 'B')
+        || setweight(to_tsvector('english', coalesce(body, '')), 'C')
+    ) STORED;
+
+CREATE INDEX idx_contents_search_vector ON contents USING GIN (search_vector);
@@ -0,0 +1,18 @@
+-- Add revision history for text contents
+--
+-- Before a text content's title, description or body changes, its previous
+-- values are stored here under the next `revision` number for that content.
+-- The API keeps at most `CONTENT_REVISIONS_MAX` revisions per content and
+-- drops the oldest beyond that. Revisions go away with their content.
+
+CREATE TABLE content_revisions (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    content_id UUID NOT NULL REFERENCES contents(id) ON DELETE CASCADE,
+    revision INTEGER NOT NULL CHECK (revision > 0),
+    title VARCHAR(255) NOT NULL,
+    description TEXT,
+    body TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+
+    CONSTRAINT content_revisions_unique_number UNIQUE (content_id, revision)
+);
@@ -0,0 +1,22 @@
+-- Track when tags and share link settings last changed
+--
+-- The API derives ETags from `updated_at` so clients can make conditional
+-- updates (`If-Match`). Tags get the same trigger as contents and groups.
+-- Share links are stamped by the settings update itself rather than a trigger:
+-- every public access bumps `access_count`, which must not invalidate the
+-- owner's ETag.
+
+ALTER TABLE tags
+    ADD COLUMN updated_at TIMESTAMPTZ NOT NULL DEFAULT now();
+
+UPDATE tags SET updated_at = created_at;
+
+CREATE TRIGGER update_tags_updated_at
+    BEFORE UPDATE ON tags
+    FOR EACH ROW
+    EXECUTE FUNCTION update_updated_at_column();
+
+ALTER TABLE share_links
+    ADD COLUMN updated_at TIMESTAMPTZ NOT NULL DEFAULT now();
+
+UPDATE share_links SET updated_at = created_at;
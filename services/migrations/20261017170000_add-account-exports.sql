@@ -0,0 +1,24 @@
+-- Add account export jobs
+--
+-- An export job snapshots a user's contents, tags, groups and share link
+-- settings into a versioned JSON manifest, stored here once the job is done.
+-- The archive itself (manifest plus file blobs) is streamed from this
+-- snapshot on download and is never stored. Exports can be downloaded until
+-- `expires_at`.
+
+CREATE TABLE account_exports (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+
+    status VARCHAR(20) NOT NULL DEFAULT 'pending',  -- pending|completed|failed
+    manifest JSONB,
+    error TEXT,
+
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    completed_at TIMESTAMPTZ,
+    expires_at TIMESTAMPTZ NOT NULL,
+
+    CONSTRAINT account_exports_status_check CHECK (status IN ('pending', 'completed', 'failed'))
+);
+
+CREATE INDEX idx_account_exports_user_created_at ON account_exports(user_id, created_at DESC);
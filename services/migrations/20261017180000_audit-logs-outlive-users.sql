@@ -0,0 +1,10 @@
+-- Keep audit log entries when their user is deleted
+--
+-- `audit_logs.user_id` referenced users without an ON DELETE action, so a user
+-- with audit entries could not be deleted. Their entries are kept with the
+-- user cleared; entries about the user still name it in `entity_id`.
+
+ALTER TABLE audit_logs DROP CONSTRAINT audit_logs_user_id_fkey;
+ALTER TABLE audit_logs
+    ADD CONSTRAINT audit_logs_user_id_fkey
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE SET NULL;
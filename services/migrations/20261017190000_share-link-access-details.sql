@@ -0,0 +1,16 @@
+-- Record public share link accesses
+--
+-- Every public access to a share link appends a row to `share_link_accesses`.
+-- `password_supplied` tells whether the visitor sent a password. Accesses by
+-- a user are kept when the user is deleted, as anonymous ones.
+
+ALTER TABLE share_link_accesses
+    ADD COLUMN password_supplied BOOLEAN NOT NULL DEFAULT false;
+
+ALTER TABLE share_link_accesses DROP CONSTRAINT share_link_accesses_accessed_by_user_id_fkey;
+ALTER TABLE share_link_accesses
+    ADD CONSTRAINT share_link_accesses_accessed_by_user_id_fkey
+    FOREIGN KEY (accessed_by_user_id) REFERENCES users(id) ON DELETE SET NULL;
+
+CREATE INDEX idx_share_link_accesses_link_accessed_at
+    ON share_link_accesses(share_link_id, accessed_at DESC);
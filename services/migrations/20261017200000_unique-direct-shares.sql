@@ -0,0 +1,27 @@
+-- Make direct shares unique per item and recipient
+--
+-- Sharing a content or group with a user again updates the permission of the
+-- existing share rather than adding another one. Duplicates are collapsed to
+-- the most recent share first.
+
+DELETE FROM content_shares a
+USING content_shares b
+WHERE a.shared_with_user_id IS NOT NULL
+  AND a.content_id = b.content_id
+  AND a.shared_with_user_id = b.shared_with_user_id
+  AND (a.created_at, a.id) < (b.created_at, b.id);
+
+DELETE FROM content_group_shares a
+USING content_group_shares b
+WHERE a.shared_with_user_id IS NOT NULL
+  AND a.group_id = b.group_id
+  AND a.shared_with_user_id = b.shared_with_user_id
+  AND (a.created_at, a.id) < (b.created_at, b.id);
+
+CREATE UNIQUE INDEX idx_content_shares_content_user
+    ON content_shares(content_id, shared_with_user_id)
+    WHERE shared_with_user_id IS NOT NULL;
+
+CREATE UNIQUE INDEX idx_group_shares_group_user
+    ON content_group_shares(group_id, shared_with_user_id)
+    WHERE shared_with_user_id IS NOT NULL;
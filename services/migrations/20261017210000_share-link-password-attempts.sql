@@ -0,0 +1,18 @@
+-- Rate limiting for share link passwords
+--
+-- Every password check of a password-protected share link appends a row to
+-- `share_link_password_attempts`, like `otp_attempts` for OTP logins. Failed
+-- attempts are counted per link and per IP address within a time window.
+
+CREATE TABLE share_link_password_attempts (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    share_link_id UUID NOT NULL REFERENCES share_links(id) ON DELETE CASCADE,
+    attempted_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    success BOOLEAN NOT NULL DEFAULT false,
+    ip_address INET
+);
+
+CREATE INDEX idx_share_link_password_attempts_link_time
+    ON share_link_password_attempts(share_link_id, attempted_at DESC);
+CREATE INDEX idx_share_link_password_attempts_ip_time
+    ON share_link_password_attempts(ip_address, attempted_at DESC);
@@ -0,0 +1,7 @@
+-- Index contents by creation time for keyset pagination
+--
+-- Content listings sorted by creation time page on (created_at, id). Ids only
+-- follow creation order for contents created since ids became UUIDv7, so the
+-- id alone cannot serve as the key.
+
+CREATE INDEX idx_contents_user_created_at ON contents(user_id, created_at, id);
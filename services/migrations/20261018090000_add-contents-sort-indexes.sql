@@ -0,0 +1,28 @@
+-- Index contents for the remaining listing sort keys
+--
+-- Like creation time, every content listing sort pages on (sort value, id), so
+-- each sort key gets a matching (user_id, key, id) index.
+--
+-- The capture time sort orders by the EXIF capture time, falling back to the UTC
+-- creation time. to_char() is only STABLE because some patterns depend on the
+-- locale; this one does not, so the expression is wrapped in an IMMUTABLE
+-- function that can be indexed. Listings must sort on the function for the index
+-- to apply. It replaces the partial metadata index, which the fallback made
+-- unusable for sorting.
+
+CREATE FUNCTION content_captured_at(metadata JSONB, created_at TIMESTAMPTZ)
+RETURNS TEXT
+LANGUAGE SQL IMMUTABLE PARALLEL SAFE
+AS $$
+    SELECT coalesce(
+        metadata->>'captured_at',
+        to_char(created_at AT TIME ZONE 'UTC', 'YYYY-MM-DD"T"HH24:MI:SS')
+    )
+$$;
+
+DROP INDEX idx_contents_user_captured_at;
+
+CREATE INDEX idx_contents_user_updated_at ON contents(user_id, updated_at, id);
+CREATE INDEX idx_contents_user_title ON contents(user_id, title, id);
+CREATE INDEX idx_contents_user_captured_at
+    ON contents(user_id, content_captured_at(metadata, created_at), id);
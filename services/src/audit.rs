@@ -0,0 +1,95 @@
+//! Audit logging of mutating API calls.
+//!
+//! Handlers append an entry to the `audit_logs` table after a mutation
+//! succeeds. Actions are named `<entity type>.<verb>` (e.g. `content.trash`);
+//! the entity type stored with the entry is the part before the dot. Writes
+//! are best-effort: a failure is logged and never fails the request.
+
+use std::convert::Infallible;
+use std::net::IpAddr;
+
+use axum::{extract::FromRequestParts, http::request::Parts};
+
+use crate::database::{AuditLogInsert, SqlStorage};
+use crate::users::routes::extract_client_ip;
+
+/// Client IP address of a request, taken from the proxy headers.
+///
+/// See [`extract_client_ip`] for the headers consulted. Never rejects; the
+/// address is `None` when no header carries a valid IP.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ClientIp(pub Option<IpAddr>);
+
+impl<S> FromRequestParts<S> for ClientIp
+where
+    S: Send + Sync,
+{
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        Ok(Self(extract_client_ip(&parts.headers)))
+    }
+}
+
+/// Appends an audit log entry for `action`.
+///
+/// `user_id` is the acting user, or `None` for internal (operator) actions.
+pub async fn record<S: SqlStorage>(
+    storage: &S,
+    user_id: Option<uuid::Uuid>,
+    action: &str,
+    entity_id: Option<uuid::Uuid>,
+    details: Option<serde_json::Value>,
+    ClientIp(ip_address): ClientIp,
+) {
+    let entry = AuditLogInsert {
+        user_id,
+        action: action.to_owned(),
+        entity_type: entity_type(action).to_owned(),
+        entity_id,
+        details,
+        ip_address,
+    };
+
+    if let Err(e) = storage.audit_logs_insert(entry).await {
+        tracing::error!(
+            action = %action,
+            entity_id = ?entity_id,
+            error = %e,
+            "Failed to record audit log entry"
+        );
+    }
+}
+
+/// Entity type of an action name: the part before the first dot.
+fn entity_type(action: &str) -> &str {
+    action.split_once('.').map_or(action, |(entity, _)| entity)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::Request;
+
+    #[test]
+    fn test_entity_type() {
+        assert_eq!(entity_type("content.trash"), "content");
+        assert_eq!(entity_type("share_link.create"), "share_link");
+        assert_eq!(entity_type("login"), "login");
+    }
+
+    #[tokio::test]
+    async fn test_client_ip_extractor() {
+        let (mut parts, ()) = Request::builder()
+            .header("x-forwarded-for", "203.0.113.7, 10.0.0.1")
+            .body(())
+            .unwrap()
+            .into_parts();
+        let ClientIp(ip) = ClientIp::from_request_parts(&mut parts, &()).await.unwrap();
+        assert_eq!(ip, Some("203.0.113.7".parse().unwrap()));
+
+        let (mut parts, ()) = Request::builder().body(()).unwrap().into_parts();
+        let ClientIp(ip) = ClientIp::from_request_parts(&mut parts, &()).await.unwrap();
+        assert_eq!(ip, None);
+    }
+}
@@ -1,9 +1,26 @@
 use collects_utils::version_info::RuntimeEnv;
 use serde::Deserialize;
+use std::collections::HashMap;
 use std::env::vars;
 use std::fmt::Display;
+use std::time::Duration;
 use tracing::info;
 
+/// Default interval between sweeps of expired uploads, in seconds.
+pub const DEFAULT_UPLOAD_SWEEP_INTERVAL_SECS: u64 = 300;
+
+/// Default number of days trashed contents and groups are kept before purge.
+pub const DEFAULT_TRASH_RETENTION_DAYS: u64 = 30;
+
+/// Default interval between purges of expired trash, in seconds.
+pub const DEFAULT_TRASH_PURGE_INTERVAL_SECS: u64 = 3600;
+
+/// Default number of earlier versions kept for each text content.
+pub const DEFAULT_CONTENT_REVISIONS_MAX: u32 = 50;
+
+/// Default base URL of share links; the share page is served at `/s/{token}`.
+pub const DEFAULT_SHARE_BASE_URL: &str = "https://app.collects.io";
+
 #[derive(Debug, Clone, Deserialize, PartialEq, Eq)]
 pub enum Env {
     #[serde(rename = "local")]
@@ -51,7 +68,8 @@ impl Display for Env {
 }
 
 impl Env {
-    /// Returns true if this environment requires R2 storage credentials.
+    /// Returns true if this environment requires R2 storage credentials
+    /// when R2 is the selected storage backend.
     fn requires_r2(&self) -> bool {
         // R2 is required for all runtime environments (including local/test).
         true
@@ -81,6 +99,31 @@ impl Env {
     }
 }
 
+/// Storage backend used for new uploads.
+#[derive(Debug, Clone, Copy, Default, Deserialize, PartialEq, Eq)]
+pub enum StorageBackend {
+    /// Cloudflare R2 (requires `CF_*` credentials).
+    #[default]
+    #[serde(rename = "r2")]
+    R2,
+    /// Google Cloud Storage (requires `GCS_BUCKET`).
+    #[serde(rename = "gcs")]
+    Gcs,
+    /// Local filesystem served through signed URLs (requires `LOCAL_STORAGE_ROOT`).
+    #[serde(rename = "local")]
+    Local,
+}
+
+impl Display for StorageBackend {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::R2 => write!(f, "r2"),
+            Self::Gcs => write!(f, "gcs"),
+            Self::Local => write!(f, "local"),
+        }
+    }
+}
+
 /// Cloudflare R2 storage configuration.
 /// All fields are required when this config is present.
 #[derive(Debug, Clone)]
@@ -109,6 +152,139 @@ impl R2Config {
     }
 }
 
+/// Google Cloud Storage configuration.
+#[derive(Debug, Clone)]
+pub struct GcsConfig {
+    bucket: String,
+    credential: Option<String>,
+    endpoint: Option<String>,
+    token: Option<String>,
+}
+
+impl GcsConfig {
+    pub fn bucket(&self) -> &str {
+        &self.bucket
+    }
+
+    /// Base64 encoded service account JSON.
+    pub fn credential(&self) -> Option<&str> {
+        self.credential.as_deref()
+    }
+
+    /// Custom endpoint, e.g. a GCS emulator.
+    pub fn endpoint(&self) -> Option<&str> {
+        self.endpoint.as_deref()
+    }
+
+    /// Static OAuth2 access token.
+    pub fn token(&self) -> Option<&str> {
+        self.token.as_deref()
+    }
+}
+
+/// Local filesystem storage configuration.
+#[derive(Debug, Clone)]
+pub struct LocalStorageConfig {
+    root: String,
+    base_url: String,
+    signing_secret: String,
+}
+
+impl LocalStorageConfig {
+    /// Directory objects are stored under.
+    pub fn root(&self) -> &str {
+        &self.root
+    }
+
+    /// Public base URL used when building signed URLs.
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    /// Secret used to sign local storage URLs.
+    pub fn signing_secret(&self) -> &str {
+        &self.signing_secret
+    }
+}
+
+/// Backend settings of a named storage profile.
+#[derive(Debug, Clone)]
+pub enum StorageProfileBackend {
+    R2(R2Config),
+    Gcs(GcsConfig),
+    Local(LocalStorageConfig),
+}
+
+/// A named storage profile from `STORAGE_PROFILES`.
+///
+/// Profiles let uploads land in different buckets (or backends) depending on
+/// what is being stored, e.g. one bucket for images and one for large archives.
+#[derive(Debug, Clone)]
+pub struct StorageProfileConfig {
+    name: String,
+    backend: StorageProfileBackend,
+    max_object_size: Option<u64>,
+    allowed_content_types: Vec<String>,
+}
+
+impl StorageProfileConfig {
+    /// Profile name recorded in `storage_profile`.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn backend(&self) -> &StorageProfileBackend {
+        &self.backend
+    }
+
+    pub fn storage_backend(&self) -> StorageBackend {
+        match self.backend {
+            StorageProfileBackend::R2(_) => StorageBackend::R2,
+            StorageProfileBackend::Gcs(_) => StorageBackend::Gcs,
+            StorageProfileBackend::Local(_) => StorageBackend::Local,
+        }
+    }
+
+    /// Largest object (in bytes) accepted by this profile.
+    pub fn max_object_size(&self) -> Option<u64> {
+        self.max_object_size
+    }
+
+    /// MIME types accepted by this profile (`image/*` style wildcards allowed).
+    /// Empty means any type.
+    pub fn allowed_content_types(&self) -> &[String] {
+        &self.allowed_content_types
+    }
+}
+
+/// One entry of the `STORAGE_PROFILES` JSON array.
+///
+/// Credentials that are left out fall back to the top-level `CF_*`, `GCS_*`
+/// and `LOCAL_STORAGE_*` values.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawStorageProfile {
+    name: String,
+    backend: StorageBackend,
+    /// Bucket name (r2, gcs).
+    bucket: Option<String>,
+    // R2 credentials
+    account_id: Option<String>,
+    access_key_id: Option<String>,
+    secret_access_key: Option<String>,
+    // GCS credentials
+    credential: Option<String>,
+    endpoint: Option<String>,
+    token: Option<String>,
+    // Local filesystem settings
+    root: Option<String>,
+    base_url: Option<String>,
+    secret: Option<String>,
+    max_object_size: Option<u64>,
+    #[serde(default)]
+    allowed_content_types: Vec<String>,
+}
+
 /// Cloudflare Zero Trust configuration for internal routes.
 /// All fields are required when this config is present.
 #[derive(Debug, Clone)]
@@ -143,6 +319,36 @@ struct RawConfig {
     cf_secret_access_key: Option<String>,
     cf_bucket: Option<String>,
 
+    // Storage backend selection and local filesystem storage fields
+    storage_backend: Option<StorageBackend>,
+    local_storage_root: Option<String>,
+    local_storage_base_url: Option<String>,
+    local_storage_secret: Option<String>,
+
+    // Google Cloud Storage fields
+    gcs_bucket: Option<String>,
+    gcs_credential: Option<String>,
+    gcs_endpoint: Option<String>,
+    gcs_token: Option<String>,
+
+    // Named storage profiles and the policy selecting between them
+    storage_profiles: Option<String>,
+    storage_default_profile: Option<String>,
+    storage_user_profiles: Option<String>,
+
+    // Background sweep of expired uploads (0 disables it)
+    upload_sweep_interval_secs: Option<u64>,
+
+    // Trash retention and background purge of expired trash (0 disables it)
+    trash_retention_days: Option<u64>,
+    trash_purge_interval_secs: Option<u64>,
+
+    // Revision history kept per text content (0 disables it)
+    content_revisions_max: Option<u32>,
+
+    // Base URL of share links, where this service's share pages are reachable
+    share_base_url: Option<String>,
+
     // Zero Trust fields (grouped logically, validated together)
     cf_access_team_domain: Option<String>,
     cf_access_aud: Option<String>,
@@ -174,6 +380,166 @@ impl RawConfig {
         }
     }
 
+    /// Try to construct `LocalStorageConfig` if `LOCAL_STORAGE_ROOT` is set.
+    /// The base URL defaults to the server address and the signing secret to
+    /// the JWT secret. Returns Err if optional fields are set without a root.
+    fn try_local_storage_config(
+        &self,
+        server_addr: &str,
+        port: u16,
+        jwt_secret: &str,
+    ) -> Result<Option<LocalStorageConfig>, &'static str> {
+        let Some(root) = &self.local_storage_root else {
+            if self.local_storage_base_url.is_some() || self.local_storage_secret.is_some() {
+                return Err(
+                    "Partial local storage configuration: LOCAL_STORAGE_ROOT must be set \
+                     when LOCAL_STORAGE_BASE_URL or LOCAL_STORAGE_SECRET is set",
+                );
+            }
+            return Ok(None);
+        };
+
+        Ok(Some(LocalStorageConfig {
+            root: root.clone(),
+            base_url: self
+                .local_storage_base_url
+                .clone()
+                .unwrap_or_else(|| format!("http://{server_addr}:{port}")),
+            signing_secret: self
+                .local_storage_secret
+                .clone()
+                .unwrap_or_else(|| jwt_secret.to_owned()),
+        }))
+    }
+
+    /// Try to construct `GcsConfig` if `GCS_BUCKET` is set.
+    /// Returns Err if other GCS fields are set without a bucket.
+    fn try_gcs_config(&self) -> Result<Option<GcsConfig>, &'static str> {
+        let Some(bucket) = &self.gcs_bucket else {
+            if self.gcs_credential.is_some()
+                || self.gcs_endpoint.is_some()
+                || self.gcs_token.is_some()
+            {
+                return Err(
+                    "Partial GCS configuration: GCS_BUCKET must be set when GCS_CREDENTIAL, \
+                     GCS_ENDPOINT or GCS_TOKEN is set",
+                );
+            }
+            return Ok(None);
+        };
+
+        Ok(Some(GcsConfig {
+            bucket: bucket.clone(),
+            credential: self.gcs_credential.clone(),
+            endpoint: self.gcs_endpoint.clone(),
+            token: self.gcs_token.clone(),
+        }))
+    }
+
+    /// Parse `STORAGE_PROFILES` (a JSON array of profiles).
+    ///
+    /// Credentials missing from a profile are taken from the top-level
+    /// backend fields; the local base URL and secret default like
+    /// `LOCAL_STORAGE_*` does.
+    fn try_storage_profiles(
+        &self,
+        server_addr: &str,
+        port: u16,
+        jwt_secret: &str,
+    ) -> anyhow::Result<Vec<StorageProfileConfig>> {
+        let Some(json) = &self.storage_profiles else {
+            return Ok(Vec::new());
+        };
+        let raw_profiles: Vec<RawStorageProfile> = serde_json::from_str(json)
+            .map_err(|e| anyhow::anyhow!("STORAGE_PROFILES is not valid JSON: {e}"))?;
+
+        let mut profiles: Vec<StorageProfileConfig> = Vec::with_capacity(raw_profiles.len());
+        for raw in raw_profiles {
+            let name = raw.name;
+            if name.is_empty()
+                || !name
+                    .chars()
+                    .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_')
+            {
+                anyhow::bail!(
+                    "Storage profile name '{name}' must only contain lowercase letters, digits, '-' and '_'"
+                );
+            }
+            if name == crate::storage::DEFAULT_STORAGE_PROFILE {
+                anyhow::bail!("Storage profile name '{name}' is reserved");
+            }
+            if profiles.iter().any(|p| p.name == name) {
+                anyhow::bail!("Storage profile '{name}' is defined more than once");
+            }
+
+            let backend = match raw.backend {
+                StorageBackend::R2 => {
+                    let bucket = raw.bucket.ok_or_else(|| {
+                        anyhow::anyhow!("Storage profile '{name}': bucket must be set")
+                    })?;
+                    match (
+                        raw.account_id.or_else(|| self.cf_account_id.clone()),
+                        raw.access_key_id.or_else(|| self.cf_access_key_id.clone()),
+                        raw.secret_access_key
+                            .or_else(|| self.cf_secret_access_key.clone()),
+                    ) {
+                        (Some(account_id), Some(access_key_id), Some(secret_access_key)) => {
+                            StorageProfileBackend::R2(R2Config {
+                                account_id,
+                                access_key_id,
+                                secret_access_key,
+                                bucket,
+                            })
+                        }
+                        _ => anyhow::bail!(
+                            "Storage profile '{name}': account_id, access_key_id and \
+                             secret_access_key must be set (or CF_* credentials configured)"
+                        ),
+                    }
+                }
+                StorageBackend::Gcs => StorageProfileBackend::Gcs(GcsConfig {
+                    bucket: raw.bucket.ok_or_else(|| {
+                        anyhow::anyhow!("Storage profile '{name}': bucket must be set")
+                    })?,
+                    credential: raw.credential.or_else(|| self.gcs_credential.clone()),
+                    endpoint: raw.endpoint.or_else(|| self.gcs_endpoint.clone()),
+                    token: raw.token.or_else(|| self.gcs_token.clone()),
+                }),
+                StorageBackend::Local => StorageProfileBackend::Local(LocalStorageConfig {
+                    root: raw.root.ok_or_else(|| {
+                        anyhow::anyhow!("Storage profile '{name}': root must be set")
+                    })?,
+                    base_url: raw
+                        .base_url
+                        .or_else(|| self.local_storage_base_url.clone())
+                        .unwrap_or_else(|| format!("http://{server_addr}:{port}")),
+                    signing_secret: raw
+                        .secret
+                        .or_else(|| self.local_storage_secret.clone())
+                        .unwrap_or_else(|| jwt_secret.to_owned()),
+                }),
+            };
+
+            profiles.push(StorageProfileConfig {
+                name,
+                backend,
+                max_object_size: raw.max_object_size,
+                allowed_content_types: raw.allowed_content_types,
+            });
+        }
+
+        Ok(profiles)
+    }
+
+    /// Parse `STORAGE_USER_PROFILES` (a JSON object mapping usernames to profiles).
+    fn try_storage_user_profiles(&self) -> anyhow::Result<HashMap<String, String>> {
+        match &self.storage_user_profiles {
+            Some(json) => serde_json::from_str(json)
+                .map_err(|e| anyhow::anyhow!("STORAGE_USER_PROFILES is not valid JSON: {e}")),
+            None => Ok(HashMap::new()),
+        }
+    }
+
     /// Try to construct `ZeroTrustConfig` if all required fields are present.
     /// Returns None if no Zero Trust fields are set, or Err if partially configured.
     fn try_zero_trust_config(&self) -> Result<Option<ZeroTrustConfig>, &'static str> {
@@ -199,7 +565,18 @@ pub struct Config {
     server_addr: String,
     port: u16,
     jwt_secret: String,
+    storage_backend: StorageBackend,
     r2: Option<R2Config>,
+    gcs: Option<GcsConfig>,
+    local_storage: Option<LocalStorageConfig>,
+    storage_profiles: Vec<StorageProfileConfig>,
+    storage_default_profile: String,
+    storage_user_profiles: HashMap<String, String>,
+    upload_sweep_interval: Option<Duration>,
+    trash_retention: Duration,
+    trash_purge_interval: Option<Duration>,
+    content_revisions_max: u32,
+    share_base_url: String,
     zero_trust: Option<ZeroTrustConfig>,
 }
 
@@ -215,12 +592,23 @@ impl Config {
             server_addr: "127.0.0.1".to_string(),
             port: 8080,
             jwt_secret: "test-jwt-secret-key-for-local-development".to_string(),
+            storage_backend: StorageBackend::R2,
             r2: Some(R2Config {
                 account_id: "test-account".to_string(),
                 access_key_id: "test-access-key".to_string(),
                 secret_access_key: "test-secret".to_string(),
                 bucket: "test-bucket".to_string(),
             }),
+            gcs: None,
+            local_storage: None,
+            storage_profiles: Vec::new(),
+            storage_default_profile: crate::storage::DEFAULT_STORAGE_PROFILE.to_string(),
+            storage_user_profiles: HashMap::new(),
+            upload_sweep_interval: Some(Duration::from_secs(DEFAULT_UPLOAD_SWEEP_INTERVAL_SECS)),
+            trash_retention: Duration::from_secs(DEFAULT_TRASH_RETENTION_DAYS * 24 * 60 * 60),
+            trash_purge_interval: Some(Duration::from_secs(DEFAULT_TRASH_PURGE_INTERVAL_SECS)),
+            content_revisions_max: DEFAULT_CONTENT_REVISIONS_MAX,
+            share_base_url: DEFAULT_SHARE_BASE_URL.to_string(),
             zero_trust: None,
         }
     }
@@ -242,6 +630,76 @@ impl Config {
         }
     }
 
+    /// Create a test configuration using the local filesystem storage backend.
+    ///
+    /// Objects are stored under `root` and signed URLs point at the test server address.
+    pub fn new_for_test_local_storage(root: impl Into<String>) -> Self {
+        let base = Self::new_for_test();
+        Self {
+            storage_backend: StorageBackend::Local,
+            local_storage: Some(LocalStorageConfig {
+                root: root.into(),
+                base_url: format!("http://{}:{}", base.server_addr, base.port),
+                signing_secret: base.jwt_secret.clone(),
+            }),
+            ..base
+        }
+    }
+
+    /// Create a test configuration using Google Cloud Storage as the default backend.
+    ///
+    /// R2 stays configured so objects uploaded there remain reachable.
+    pub fn new_for_test_gcs(endpoint: impl Into<String>) -> Self {
+        Self {
+            storage_backend: StorageBackend::Gcs,
+            gcs: Some(GcsConfig {
+                bucket: "test-bucket".to_string(),
+                credential: None,
+                endpoint: Some(endpoint.into()),
+                token: None,
+            }),
+            ..Self::new_for_test()
+        }
+    }
+
+    /// Create a test configuration with named local filesystem storage profiles.
+    ///
+    /// Each `(name, max_object_size, allowed_content_types)` entry gets its own
+    /// directory under `root`. The default profile stays on R2.
+    pub fn new_for_test_storage_profiles(
+        root: impl AsRef<std::path::Path>,
+        profiles: &[(&str, Option<u64>, &[&str])],
+        user_profiles: &[(&str, &str)],
+    ) -> Self {
+        let base = Self::new_for_test();
+        let base_url = format!("http://{}:{}", base.server_addr, base.port);
+        Self {
+            storage_profiles: profiles
+                .iter()
+                .map(
+                    |(name, max_object_size, allowed_content_types)| StorageProfileConfig {
+                        name: (*name).to_string(),
+                        backend: StorageProfileBackend::Local(LocalStorageConfig {
+                            root: root.as_ref().join(name).to_string_lossy().into_owned(),
+                            base_url: base_url.clone(),
+                            signing_secret: base.jwt_secret.clone(),
+                        }),
+                        max_object_size: *max_object_size,
+                        allowed_content_types: allowed_content_types
+                            .iter()
+                            .map(|t| (*t).to_string())
+                            .collect(),
+                    },
+                )
+                .collect(),
+            storage_user_profiles: user_profiles
+                .iter()
+                .map(|(user, profile)| ((*user).to_string(), (*profile).to_string()))
+                .collect(),
+            ..base
+        }
+    }
+
     /// Create a test configuration with a specific environment.
     ///
     /// This is intended for unit tests that need to test behavior with different environments.
@@ -303,6 +761,62 @@ impl Config {
         self.r2.as_ref().map(|r| r.bucket())
     }
 
+    /// Storage backend used for new uploads.
+    pub fn storage_backend(&self) -> StorageBackend {
+        self.storage_backend
+    }
+
+    // Google Cloud Storage configuration
+    pub fn gcs(&self) -> Option<&GcsConfig> {
+        self.gcs.as_ref()
+    }
+
+    // Local filesystem storage configuration
+    pub fn local_storage(&self) -> Option<&LocalStorageConfig> {
+        self.local_storage.as_ref()
+    }
+
+    /// Named storage profiles, in configuration order.
+    pub fn storage_profiles(&self) -> &[StorageProfileConfig] {
+        &self.storage_profiles
+    }
+
+    /// Profile receiving uploads no other policy rule applies to.
+    pub fn storage_default_profile(&self) -> &str {
+        &self.storage_default_profile
+    }
+
+    /// Per-user profile assignments (username -> profile name).
+    pub fn storage_user_profiles(&self) -> &HashMap<String, String> {
+        &self.storage_user_profiles
+    }
+
+    /// Interval between sweeps of expired uploads, `None` when disabled.
+    pub fn upload_sweep_interval(&self) -> Option<Duration> {
+        self.upload_sweep_interval
+    }
+
+    /// How long trashed contents and groups are kept before they are purged.
+    pub fn trash_retention(&self) -> Duration {
+        self.trash_retention
+    }
+
+    /// Interval between purges of expired trash, `None` when disabled.
+    pub fn trash_purge_interval(&self) -> Option<Duration> {
+        self.trash_purge_interval
+    }
+
+    /// How many earlier versions are kept for each text content, 0 when
+    /// revision history is disabled.
+    pub fn content_revisions_max(&self) -> u32 {
+        self.content_revisions_max
+    }
+
+    /// Base URL of share links, without a trailing slash.
+    pub fn share_base_url(&self) -> &str {
+        &self.share_base_url
+    }
+
     // Zero Trust configuration
     pub fn zero_trust(&self) -> Option<&ZeroTrustConfig> {
         self.zero_trust.as_ref()
@@ -357,9 +871,50 @@ impl Config {
             None => anyhow::bail!("JWT_SECRET must be set for {env} environment"),
         };
 
+        let storage_backend = raw.storage_backend.unwrap_or_default();
+        info!("Using {storage_backend} storage backend for {env} environment");
+
+        // Build and validate named storage profiles and the selection policy
+        let storage_profiles = raw.try_storage_profiles(&server_addr, port, &jwt_secret)?;
+        let storage_default_profile = raw
+            .storage_default_profile
+            .take()
+            .unwrap_or_else(|| crate::storage::DEFAULT_STORAGE_PROFILE.to_string());
+        let storage_user_profiles = raw.try_storage_user_profiles()?;
+        let is_known_profile = |name: &str| {
+            name == crate::storage::DEFAULT_STORAGE_PROFILE
+                || storage_profiles.iter().any(|p| p.name == name)
+        };
+        if !is_known_profile(&storage_default_profile) {
+            anyhow::bail!(
+                "STORAGE_DEFAULT_PROFILE '{storage_default_profile}' is not a configured storage profile"
+            );
+        }
+        if let Some((user, profile)) = storage_user_profiles
+            .iter()
+            .find(|(_, profile)| !is_known_profile(profile))
+        {
+            anyhow::bail!(
+                "STORAGE_USER_PROFILES assigns unknown storage profile '{profile}' to user '{user}'"
+            );
+        }
+        if !storage_profiles.is_empty() {
+            info!(
+                "Configured {} named storage profile(s), default profile is '{storage_default_profile}'",
+                storage_profiles.len()
+            );
+        }
+        // STORAGE_BACKEND only has to be usable when the default profile receives uploads
+        let uses_default_profile =
+            storage_default_profile == crate::storage::DEFAULT_STORAGE_PROFILE;
+
         // Build and validate R2 config
         let r2 = raw.try_r2_config().map_err(anyhow::Error::msg)?;
-        if env.requires_r2() && r2.is_none() {
+        if uses_default_profile
+            && storage_backend == StorageBackend::R2
+            && env.requires_r2()
+            && r2.is_none()
+        {
             anyhow::bail!(
                 "R2 storage credentials (CF_ACCOUNT_ID, CF_ACCESS_KEY_ID, CF_SECRET_ACCESS_KEY, CF_BUCKET) \
                  must be set for {env} environment"
@@ -369,6 +924,61 @@ impl Config {
             info!("R2 storage credentials validated for {env} environment");
         }
 
+        // Build and validate GCS config
+        let gcs = raw.try_gcs_config().map_err(anyhow::Error::msg)?;
+        if uses_default_profile && storage_backend == StorageBackend::Gcs && gcs.is_none() {
+            anyhow::bail!("GCS_BUCKET must be set when STORAGE_BACKEND is gcs");
+        }
+
+        // Build and validate local storage config
+        let local_storage = raw
+            .try_local_storage_config(&server_addr, port, &jwt_secret)
+            .map_err(anyhow::Error::msg)?;
+        if uses_default_profile
+            && storage_backend == StorageBackend::Local
+            && local_storage.is_none()
+        {
+            anyhow::bail!("LOCAL_STORAGE_ROOT must be set when STORAGE_BACKEND is local");
+        }
+
+        let upload_sweep_interval = match raw
+            .upload_sweep_interval_secs
+            .unwrap_or(DEFAULT_UPLOAD_SWEEP_INTERVAL_SECS)
+        {
+            0 => {
+                info!("UPLOAD_SWEEP_INTERVAL_SECS is 0, expired uploads will not be swept");
+                None
+            }
+            secs => Some(Duration::from_secs(secs)),
+        };
+
+        let trash_retention = Duration::from_secs(
+            raw.trash_retention_days
+                .unwrap_or(DEFAULT_TRASH_RETENTION_DAYS)
+                .saturating_mul(24 * 60 * 60),
+        );
+        let trash_purge_interval = match raw
+            .trash_purge_interval_secs
+            .unwrap_or(DEFAULT_TRASH_PURGE_INTERVAL_SECS)
+        {
+            0 => {
+                info!("TRASH_PURGE_INTERVAL_SECS is 0, trash will only be purged on request");
+                None
+            }
+            secs => Some(Duration::from_secs(secs)),
+        };
+
+        let content_revisions_max = raw
+            .content_revisions_max
+            .unwrap_or(DEFAULT_CONTENT_REVISIONS_MAX);
+
+        let share_base_url = raw
+            .share_base_url
+            .as_deref()
+            .unwrap_or(DEFAULT_SHARE_BASE_URL)
+            .trim_end_matches('/')
+            .to_string();
+
         // Build and validate Zero Trust config
         let zero_trust = raw.try_zero_trust_config().map_err(anyhow::Error::msg)?;
         if env.requires_zero_trust() && zero_trust.is_none() {
@@ -384,7 +994,18 @@ impl Config {
             server_addr,
             port,
             jwt_secret,
+            storage_backend,
             r2,
+            gcs,
+            local_storage,
+            storage_profiles,
+            storage_default_profile,
+            storage_user_profiles,
+            upload_sweep_interval,
+            trash_retention,
+            trash_purge_interval,
+            content_revisions_max,
+            share_base_url,
             zero_trust,
         })
     }
@@ -462,6 +1083,110 @@ mod tests {
         assert_eq!(r2.bucket(), "my-bucket");
     }
 
+    #[test]
+    fn upload_sweep_interval_defaults_and_can_be_disabled() {
+        let local = |extra: Vec<(&'static str, &'static str)>| {
+            let mut vars = vec![
+                ("ENV", "local"),
+                ("DATABASE_URL", "postgres://example"),
+                ("STORAGE_BACKEND", "local"),
+                ("LOCAL_STORAGE_ROOT", "/tmp/collects"),
+            ];
+            vars.extend(extra);
+            Config::from_raw(make_raw(vars)).expect("config should build")
+        };
+
+        assert_eq!(
+            local(vec![]).upload_sweep_interval(),
+            Some(Duration::from_secs(DEFAULT_UPLOAD_SWEEP_INTERVAL_SECS))
+        );
+        assert_eq!(
+            local(vec![("UPLOAD_SWEEP_INTERVAL_SECS", "60")]).upload_sweep_interval(),
+            Some(Duration::from_secs(60))
+        );
+        assert_eq!(
+            local(vec![("UPLOAD_SWEEP_INTERVAL_SECS", "0")]).upload_sweep_interval(),
+            None
+        );
+    }
+
+    #[test]
+    fn trash_retention_and_purge_interval() {
+        let local = |extra: Vec<(&'static str, &'static str)>| {
+            let mut vars = vec![
+                ("ENV", "local"),
+                ("DATABASE_URL", "postgres://example"),
+                ("STORAGE_BACKEND", "local"),
+                ("LOCAL_STORAGE_ROOT", "/tmp/collects"),
+            ];
+            vars.extend(extra);
+            Config::from_raw(make_raw(vars)).expect("config should build")
+        };
+
+        let config = local(vec![]);
+        assert_eq!(
+            config.trash_retention(),
+            Duration::from_secs(DEFAULT_TRASH_RETENTION_DAYS * 24 * 60 * 60)
+        );
+        assert_eq!(
+            config.trash_purge_interval(),
+            Some(Duration::from_secs(DEFAULT_TRASH_PURGE_INTERVAL_SECS))
+        );
+
+        let config = local(vec![
+            ("TRASH_RETENTION_DAYS", "7"),
+            ("TRASH_PURGE_INTERVAL_SECS", "0"),
+        ]);
+        assert_eq!(
+            config.trash_retention(),
+            Duration::from_secs(7 * 24 * 60 * 60)
+        );
+        assert_eq!(config.trash_purge_interval(), None);
+    }
+
+    #[test]
+    fn content_revisions_max() {
+        let local = |extra: Vec<(&'static str, &'static str)>| {
+            let mut vars = vec![
+                ("ENV", "local"),
+                ("DATABASE_URL", "postgres://example"),
+                ("STORAGE_BACKEND", "local"),
+                ("LOCAL_STORAGE_ROOT", "/tmp/collects"),
+            ];
+            vars.extend(extra);
+            Config::from_raw(make_raw(vars)).expect("config should build")
+        };
+
+        assert_eq!(
+            local(vec![]).content_revisions_max(),
+            DEFAULT_CONTENT_REVISIONS_MAX
+        );
+        assert_eq!(
+            local(vec![("CONTENT_REVISIONS_MAX", "0")]).content_revisions_max(),
+            0
+        );
+    }
+
+    #[test]
+    fn share_base_url() {
+        let local = |extra: Vec<(&'static str, &'static str)>| {
+            let mut vars = vec![
+                ("ENV", "local"),
+                ("DATABASE_URL", "postgres://example"),
+                ("STORAGE_BACKEND", "local"),
+                ("LOCAL_STORAGE_ROOT", "/tmp/collects"),
+            ];
+            vars.extend(extra);
+            Config::from_raw(make_raw(vars)).expect("config should build")
+        };
+
+        assert_eq!(local(vec![]).share_base_url(), DEFAULT_SHARE_BASE_URL);
+        assert_eq!(
+            local(vec![("SHARE_BASE_URL", "http://localhost:8080/")]).share_base_url(),
+            "http://localhost:8080"
+        );
+    }
+
     #[test]
     fn r2_config_partial_fields_error() {
         let raw = make_raw(vec![
@@ -476,6 +1201,284 @@ mod tests {
         assert!(result.unwrap_err().to_string().contains("Partial R2"));
     }
 
+    #[test]
+    fn local_storage_backend_does_not_require_r2() {
+        let raw = make_raw(vec![
+            ("ENV", "local"),
+            ("DATABASE_URL", "postgres://example"),
+            ("STORAGE_BACKEND", "local"),
+            ("LOCAL_STORAGE_ROOT", "/tmp/collects"),
+        ]);
+
+        let config = Config::from_raw(raw).expect("local storage config should build");
+        assert_eq!(config.storage_backend(), StorageBackend::Local);
+        assert!(config.r2().is_none());
+        let local = config
+            .local_storage()
+            .expect("local storage config should be present");
+        assert_eq!(local.root(), "/tmp/collects");
+        assert_eq!(local.base_url(), "http://127.0.0.1:8080");
+        assert_eq!(local.signing_secret(), config.jwt_secret());
+    }
+
+    #[test]
+    fn local_storage_backend_requires_root() {
+        let raw = make_raw(vec![
+            ("ENV", "local"),
+            ("DATABASE_URL", "postgres://example"),
+            ("STORAGE_BACKEND", "local"),
+        ]);
+
+        let result = Config::from_raw(raw);
+        assert!(result.is_err());
+        assert!(
+            result
+                .unwrap_err()
+                .to_string()
+                .contains("LOCAL_STORAGE_ROOT")
+        );
+    }
+
+    #[test]
+    fn local_storage_partial_fields_error() {
+        let raw = make_raw(vec![
+            ("ENV", "local"),
+            ("DATABASE_URL", "postgres://example"),
+            ("STORAGE_BACKEND", "local"),
+            ("LOCAL_STORAGE_SECRET", "secret"),
+        ]);
+
+        let result = Config::from_raw(raw);
+        assert!(result.is_err());
+        assert!(
+            result
+                .unwrap_err()
+                .to_string()
+                .contains("Partial local storage")
+        );
+    }
+
+    #[test]
+    fn local_storage_explicit_base_url_and_secret() {
+        let raw = make_raw(vec![
+            ("ENV", "prod"),
+            ("DATABASE_URL", "postgres://example"),
+            ("PORT", "8080"),
+            ("JWT_SECRET", "test-jwt-secret"),
+            ("STORAGE_BACKEND", "local"),
+            ("LOCAL_STORAGE_ROOT", "/var/lib/collects"),
+            ("LOCAL_STORAGE_BASE_URL", "https://files.example.com"),
+            ("LOCAL_STORAGE_SECRET", "signing-secret"),
+        ]);
+
+        let config = Config::from_raw(raw).expect("prod config with local storage should build");
+        let local = config
+            .local_storage()
+            .expect("local storage config should be present");
+        assert_eq!(local.base_url(), "https://files.example.com");
+        assert_eq!(local.signing_secret(), "signing-secret");
+    }
+
+    #[test]
+    fn gcs_storage_backend_does_not_require_r2() {
+        let raw = make_raw(vec![
+            ("ENV", "local"),
+            ("DATABASE_URL", "postgres://example"),
+            ("STORAGE_BACKEND", "gcs"),
+            ("GCS_BUCKET", "collects"),
+            ("GCS_ENDPOINT", "http://127.0.0.1:4443"),
+        ]);
+
+        let config = Config::from_raw(raw).expect("gcs config should build");
+        assert_eq!(config.storage_backend(), StorageBackend::Gcs);
+        assert!(config.r2().is_none());
+        let gcs = config.gcs().expect("gcs config should be present");
+        assert_eq!(gcs.bucket(), "collects");
+        assert_eq!(gcs.endpoint(), Some("http://127.0.0.1:4443"));
+        assert!(gcs.credential().is_none());
+    }
+
+    #[test]
+    fn gcs_storage_backend_requires_bucket() {
+        let raw = make_raw(vec![
+            ("ENV", "local"),
+            ("DATABASE_URL", "postgres://example"),
+            ("STORAGE_BACKEND", "gcs"),
+        ]);
+
+        let result = Config::from_raw(raw);
+        assert!(result.is_err());
+        assert!(result.unwrap_err().to_string().contains("GCS_BUCKET"));
+    }
+
+    #[test]
+    fn gcs_partial_fields_error() {
+        let raw = make_raw(vec![
+            ("ENV", "local"),
+            ("DATABASE_URL", "postgres://example"),
+            ("STORAGE_BACKEND", "local"),
+            ("LOCAL_STORAGE_ROOT", "/tmp/collects"),
+            ("GCS_CREDENTIAL", "e30="),
+        ]);
+
+        let result = Config::from_raw(raw);
+        assert!(result.is_err());
+        assert!(result.unwrap_err().to_string().contains("Partial GCS"));
+    }
+
+    #[test]
+    fn storage_profiles_parsed_with_inherited_credentials() {
+        let raw = make_raw(vec![
+            ("ENV", "local"),
+            ("DATABASE_URL", "postgres://example"),
+            ("CF_ACCOUNT_ID", "test-account"),
+            ("CF_ACCESS_KEY_ID", "test-access-key"),
+            ("CF_SECRET_ACCESS_KEY", "test-secret"),
+            ("CF_BUCKET", "test-bucket"),
+            (
+                "STORAGE_PROFILES",
+                r#"[
+                    {"name":"images","backend":"r2","bucket":"collects-images",
+                     "max_object_size":26214400,"allowed_content_types":["image/*"]},
+                    {"name":"archives","backend":"gcs","bucket":"collects-archives",
+                     "endpoint":"http://127.0.0.1:4443"}
+                ]"#,
+            ),
+            ("STORAGE_DEFAULT_PROFILE", "images"),
+            ("STORAGE_USER_PROFILES", r#"{"archivist":"archives"}"#),
+        ]);
+
+        let config = Config::from_raw(raw).expect("profile config should build");
+        assert_eq!(config.storage_default_profile(), "images");
+        assert_eq!(
+            config
+                .storage_user_profiles()
+                .get("archivist")
+                .map(String::as_str),
+            Some("archives")
+        );
+
+        let [images, archives] = config.storage_profiles() else {
+            panic!("expected two profiles");
+        };
+        assert_eq!(images.name(), "images");
+        assert_eq!(images.storage_backend(), StorageBackend::R2);
+        assert_eq!(images.max_object_size(), Some(26214400));
+        assert_eq!(images.allowed_content_types(), ["image/*"]);
+        let StorageProfileBackend::R2(r2) = images.backend() else {
+            panic!("images should be an r2 profile");
+        };
+        assert_eq!(r2.bucket(), "collects-images");
+        assert_eq!(r2.account_id(), "test-account");
+
+        assert_eq!(archives.storage_backend(), StorageBackend::Gcs);
+        assert!(archives.allowed_content_types().is_empty());
+        let StorageProfileBackend::Gcs(gcs) = archives.backend() else {
+            panic!("archives should be a gcs profile");
+        };
+        assert_eq!(gcs.bucket(), "collects-archives");
+        assert_eq!(gcs.endpoint(), Some("http://127.0.0.1:4443"));
+    }
+
+    #[test]
+    fn named_default_profile_does_not_require_r2() {
+        let raw = make_raw(vec![
+            ("ENV", "local"),
+            ("DATABASE_URL", "postgres://example"),
+            (
+                "STORAGE_PROFILES",
+                r#"[{"name":"files","backend":"local","root":"/tmp/collects"}]"#,
+            ),
+            ("STORAGE_DEFAULT_PROFILE", "files"),
+        ]);
+
+        let config = Config::from_raw(raw).expect("profile config should build");
+        assert!(config.r2().is_none());
+        let StorageProfileBackend::Local(local) = config.storage_profiles()[0].backend() else {
+            panic!("files should be a local profile");
+        };
+        assert_eq!(local.root(), "/tmp/collects");
+        assert_eq!(local.base_url(), "http://127.0.0.1:8080");
+    }
+
+    #[test]
+    fn storage_profile_errors() {
+        let cases = [
+            (
+                r#"[{"name":"images","backend":"r2","bucket":"b"}]"#,
+                None,
+                None,
+                "access_key_id",
+            ),
+            (
+                r#"[{"name":"images","backend":"gcs"}]"#,
+                None,
+                None,
+                "bucket must be set",
+            ),
+            (
+                r#"[{"name":"a","backend":"local","root":"/a"},{"name":"a","backend":"local","root":"/b"}]"#,
+                None,
+                None,
+                "more than once",
+            ),
+            (
+                r#"[{"name":"default","backend":"local","root":"/a"}]"#,
+                None,
+                None,
+                "reserved",
+            ),
+            (
+                r#"[{"name":"Images","backend":"local","root":"/a"}]"#,
+                None,
+                None,
+                "lowercase",
+            ),
+            (
+                r#"[{"name":"a","backend":"local","root":"/a","bucket_name":"x"}]"#,
+                None,
+                None,
+                "not valid JSON",
+            ),
+            (
+                r#"[{"name":"a","backend":"local","root":"/a"}]"#,
+                Some("b"),
+                None,
+                "STORAGE_DEFAULT_PROFILE",
+            ),
+            (
+                r#"[{"name":"a","backend":"local","root":"/a"}]"#,
+                None,
+                Some(r#"{"alice":"b"}"#),
+                "unknown storage profile 'b'",
+            ),
+        ];
+
+        for (profiles, default_profile, user_profiles, expected) in cases {
+            let mut vars = vec![
+                ("ENV", "local"),
+                ("DATABASE_URL", "postgres://example"),
+                ("STORAGE_BACKEND", "local"),
+                ("LOCAL_STORAGE_ROOT", "/tmp/collects"),
+                ("STORAGE_PROFILES", profiles),
+            ];
+            if let Some(default_profile) = default_profile {
+                vars.push(("STORAGE_DEFAULT_PROFILE", default_profile));
+            }
+            if let Some(user_profiles) = user_profiles {
+                vars.push(("STORAGE_USER_PROFILES", user_profiles));
+            }
+
+            let error = Config::from_raw(make_raw(vars))
+                .expect_err("profile config should be rejected")
+                .to_string();
+            assert!(
+                error.contains(expected),
+                "expected {expected:?} in error {error:?}"
+            );
+        }
+    }
+
     #[test]
     fn default_server_addr_for_test_internal_is_public() {
         let raw = make_raw(vec![
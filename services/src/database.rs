@@ -1,6 +1,6 @@
 use crate::config::Config;
 use ipnet::IpNet;
-use sqlx::postgres::{PgPool, PgPoolOptions};
+use sqlx::postgres::{PgConnection, PgPool, PgPoolOptions};
 use std::future::Future;
 use std::net::IpAddr;
 
@@ -36,6 +36,15 @@ pub trait SqlStorage: Clone + Send + Sync + 'static {
         input: ContentsInsert,
     ) -> impl Future<Output = Result<ContentRow, SqlStorageError>> + Send;
 
+    /// Insert a content, reserving its size against the user's storage quota
+    /// the way [`uploads_create`](Self::uploads_create) does.
+    ///
+    /// Fails with [`SqlStorageError::QuotaExceeded`] if it does not fit.
+    fn contents_insert_within_quota(
+        &self,
+        input: ContentsInsert,
+    ) -> impl Future<Output = Result<ContentRow, SqlStorageError>> + Send;
+
     fn contents_get(
         &self,
         id: uuid::Uuid,
@@ -47,6 +56,14 @@ pub trait SqlStorage: Clone + Send + Sync + 'static {
         params: ContentsListParams,
     ) -> impl Future<Output = Result<Vec<ContentRow>, SqlStorageError>> + Send;
 
+    /// Full-text search over the title, description and text body of a
+    /// user's contents, ranked by relevance.
+    fn contents_search(
+        &self,
+        user_id: uuid::Uuid,
+        params: ContentsSearchParams,
+    ) -> impl Future<Output = Result<Vec<ContentSearchHit>, SqlStorageError>> + Send;
+
     fn contents_update_metadata(
         &self,
         id: uuid::Uuid,
@@ -62,6 +79,52 @@ pub trait SqlStorage: Clone + Send + Sync + 'static {
         now: chrono::DateTime<chrono::Utc>,
     ) -> impl Future<Output = Result<Option<ContentRow>, SqlStorageError>> + Send;
 
+    /// Apply `op` to the contents among `ids` owned by `user_id`, in one
+    /// transaction.
+    ///
+    /// Returns the ids the operation was applied to; ids that do not exist or
+    /// belong to another user are skipped. Fails with
+    /// [`SqlStorageError::NotFound`] if the target tag or group is not the
+    /// user's.
+    fn contents_batch(
+        &self,
+        user_id: uuid::Uuid,
+        ids: &[uuid::Uuid],
+        op: ContentsBatchOp,
+        now: chrono::DateTime<chrono::Utc>,
+    ) -> impl Future<Output = Result<Vec<uuid::Uuid>, SqlStorageError>> + Send;
+
+    /// Store the media metadata extracted from a content's file.
+    ///
+    /// Returns `false` if the content no longer exists.
+    fn contents_set_media_metadata(
+        &self,
+        id: uuid::Uuid,
+        metadata: serde_json::Value,
+    ) -> impl Future<Output = Result<bool, SqlStorageError>> + Send;
+
+    /// Permanently delete trashed contents matching `params`.
+    ///
+    /// Group memberships, tags and shares of the contents are removed with
+    /// them (`ON DELETE CASCADE`). Returns the deleted rows so their storage
+    /// objects can be released. Rows locked by a concurrent purge are skipped.
+    fn contents_purge_trashed(
+        &self,
+        params: TrashPurgeParams,
+    ) -> impl Future<Output = Result<Vec<ContentRow>, SqlStorageError>> + Send;
+
+    /// Earlier versions of a text content, newest first.
+    fn content_revisions_list(
+        &self,
+        content_id: uuid::Uuid,
+    ) -> impl Future<Output = Result<Vec<ContentRevisionRow>, SqlStorageError>> + Send;
+
+    fn content_revisions_get(
+        &self,
+        content_id: uuid::Uuid,
+        revision: i32,
+    ) -> impl Future<Output = Result<Option<ContentRevisionRow>, SqlStorageError>> + Send;
+
     // -------------------------------------------------------------------------
     // Content groups + join table
     // -------------------------------------------------------------------------
@@ -96,6 +159,15 @@ pub trait SqlStorage: Clone + Send + Sync + 'static {
         now: chrono::DateTime<chrono::Utc>,
     ) -> impl Future<Output = Result<Option<ContentGroupRow>, SqlStorageError>> + Send;
 
+    /// Permanently delete trashed groups matching `params`.
+    ///
+    /// Group items and shares are removed with them; the contents themselves
+    /// are kept.
+    fn groups_purge_trashed(
+        &self,
+        params: TrashPurgeParams,
+    ) -> impl Future<Output = Result<Vec<ContentGroupRow>, SqlStorageError>> + Send;
+
     fn group_items_add(
         &self,
         group_id: uuid::Uuid,
@@ -136,10 +208,18 @@ pub trait SqlStorage: Clone + Send + Sync + 'static {
         user_id: uuid::Uuid,
     ) -> impl Future<Output = Result<Vec<TagRow>, SqlStorageError>> + Send;
 
+    fn tags_get(
+        &self,
+        user_id: uuid::Uuid,
+        tag_id: uuid::Uuid,
+    ) -> impl Future<Output = Result<Option<TagRow>, SqlStorageError>> + Send;
+
+    /// Delete a tag; with `expected_updated_at`, only while it is unchanged.
     fn tags_delete(
         &self,
         user_id: uuid::Uuid,
         tag_id: uuid::Uuid,
+        expected_updated_at: Option<chrono::DateTime<chrono::Utc>>,
     ) -> impl Future<Output = Result<bool, SqlStorageError>> + Send;
 
     fn tags_update(
@@ -203,10 +283,13 @@ pub trait SqlStorage: Clone + Send + Sync + 'static {
         input: ShareLinkUpdate,
     ) -> impl Future<Output = Result<Option<ShareLinkRow>, SqlStorageError>> + Send;
 
+    /// Delete a share link; with `expected_updated_at`, only while it is
+    /// unchanged.
     fn share_links_delete(
         &self,
         id: uuid::Uuid,
         owner_id: uuid::Uuid,
+        expected_updated_at: Option<chrono::DateTime<chrono::Utc>>,
     ) -> impl Future<Output = Result<bool, SqlStorageError>> + Send;
 
     fn share_links_increment_access(
@@ -214,6 +297,50 @@ pub trait SqlStorage: Clone + Send + Sync + 'static {
         id: uuid::Uuid,
     ) -> impl Future<Output = Result<(), SqlStorageError>> + Send;
 
+    /// Replace a share link's password hash with `new_hash`, if it still is
+    /// `current_hash`. Unlike `share_links_update`, this leaves `updated_at`
+    /// (and so the ETag) alone: the password itself does not change.
+    fn share_links_rehash_password(
+        &self,
+        id: uuid::Uuid,
+        current_hash: &str,
+        new_hash: &str,
+    ) -> impl Future<Output = Result<(), SqlStorageError>> + Send;
+
+    /// Record a public access to a share link.
+    fn share_link_accesses_insert(
+        &self,
+        input: ShareLinkAccessInsert,
+    ) -> impl Future<Output = Result<(), SqlStorageError>> + Send;
+
+    /// Access statistics of a share link, with daily counts from `since` on.
+    fn share_link_accesses_stats(
+        &self,
+        share_link_id: uuid::Uuid,
+        since: chrono::DateTime<chrono::Utc>,
+    ) -> impl Future<Output = Result<ShareLinkAccessStats, SqlStorageError>> + Send;
+
+    /// Reserve a password attempt on a share link, before the password is
+    /// verified.
+    ///
+    /// Unless the link or the IP address already has too many failed
+    /// attempts within the window, the attempt is recorded as failed and its
+    /// id returned. Checking the limits and recording the attempt are atomic,
+    /// so concurrent guesses cannot get past the limits. Returns `None` if the
+    /// attempt should be blocked.
+    fn share_link_password_reserve_attempt(
+        &self,
+        share_link_id: uuid::Uuid,
+        ip_address: Option<IpAddr>,
+        config: &ShareLinkPasswordRateLimitConfig,
+    ) -> impl Future<Output = Result<Option<uuid::Uuid>, SqlStorageError>> + Send;
+
+    /// Mark a reserved password attempt as successful.
+    fn share_link_password_attempt_succeeded(
+        &self,
+        attempt_id: uuid::Uuid,
+    ) -> impl Future<Output = Result<(), SqlStorageError>> + Send;
+
     fn content_shares_attach_link(
         &self,
         content_id: uuid::Uuid,
@@ -228,6 +355,12 @@ pub trait SqlStorage: Clone + Send + Sync + 'static {
         created_by: uuid::Uuid,
     ) -> impl Future<Output = Result<(), SqlStorageError>> + Send;
 
+    /// The contents and groups the owner's share links are attached to.
+    fn share_links_targets_for_owner(
+        &self,
+        owner_id: uuid::Uuid,
+    ) -> impl Future<Output = Result<Vec<ShareLinkTargetRow>, SqlStorageError>> + Send;
+
     fn contents_get_by_share_token(
         &self,
         token: &str,
@@ -258,11 +391,60 @@ pub trait SqlStorage: Clone + Send + Sync + 'static {
         input: GroupShareCreateForLink,
     ) -> impl Future<Output = Result<ContentGroupShareRow, SqlStorageError>> + Send;
 
+    /// Direct shares of a content with users, oldest first.
+    fn content_shares_list_for_users(
+        &self,
+        content_id: uuid::Uuid,
+    ) -> impl Future<Output = Result<Vec<UserShareRow>, SqlStorageError>> + Send;
+
+    /// Direct shares of a group with users, oldest first.
+    fn group_shares_list_for_users(
+        &self,
+        group_id: uuid::Uuid,
+    ) -> impl Future<Output = Result<Vec<UserShareRow>, SqlStorageError>> + Send;
+
+    /// Revoke the direct share of a content with a user.
+    /// Returns whether there was one.
+    fn content_shares_delete_for_user(
+        &self,
+        content_id: uuid::Uuid,
+        shared_with_user_id: uuid::Uuid,
+    ) -> impl Future<Output = Result<bool, SqlStorageError>> + Send;
+
+    /// Revoke the direct share of a group with a user.
+    /// Returns whether there was one.
+    fn group_shares_delete_for_user(
+        &self,
+        group_id: uuid::Uuid,
+        shared_with_user_id: uuid::Uuid,
+    ) -> impl Future<Output = Result<bool, SqlStorageError>> + Send;
+
+    /// Contents and groups shared directly with a user, most recently shared
+    /// first. Trashed items are left out.
+    fn shares_list_shared_with_user(
+        &self,
+        user_id: uuid::Uuid,
+    ) -> impl Future<Output = Result<Vec<SharedWithUserRow>, SqlStorageError>> + Send;
+
+    /// The permission a user has on a content through direct shares, either of
+    /// the content or of a (non-trashed) group containing it. "download" wins
+    /// over "view"; `None` without any share.
+    fn content_shares_permission_for_user(
+        &self,
+        content_id: uuid::Uuid,
+        user_id: uuid::Uuid,
+    ) -> impl Future<Output = Result<Option<String>, SqlStorageError>> + Send;
+
     // -------------------------------------------------------------------------
     // Uploads
     // -------------------------------------------------------------------------
 
-    /// Create a new upload record.
+    /// Create a new upload record, reserving its declared size against the
+    /// user's storage quota.
+    ///
+    /// The user row is locked while usage is summed, so concurrent uploads
+    /// cannot together overshoot the quota. Fails with
+    /// [`SqlStorageError::QuotaExceeded`] if the upload does not fit.
     fn uploads_create(
         &self,
         input: UploadInsert,
@@ -281,6 +463,160 @@ pub trait SqlStorage: Clone + Send + Sync + 'static {
         user_id: uuid::Uuid,
     ) -> impl Future<Output = Result<Option<UploadRow>, SqlStorageError>> + Send;
 
+    /// Mark an upload as aborted.
+    ///
+    /// Same ownership/status checks as [`Self::uploads_complete`].
+    fn uploads_abort(
+        &self,
+        id: uuid::Uuid,
+        user_id: uuid::Uuid,
+    ) -> impl Future<Output = Result<Option<UploadRow>, SqlStorageError>> + Send;
+
+    /// Mark up to `limit` `initiated` uploads that expired before `now` as `expired`.
+    ///
+    /// Returns the updated rows so their storage objects can be cleaned up.
+    /// Rows locked by a concurrent sweep are skipped.
+    fn uploads_expire_stale(
+        &self,
+        now: chrono::DateTime<chrono::Utc>,
+        limit: i64,
+    ) -> impl Future<Output = Result<Vec<UploadRow>, SqlStorageError>> + Send;
+
+    /// Create the (pending) part records of a multipart upload.
+    fn upload_parts_create(
+        &self,
+        upload_id: uuid::Uuid,
+        parts: Vec<UploadPartInsert>,
+    ) -> impl Future<Output = Result<(), SqlStorageError>> + Send;
+
+    /// List the parts of a multipart upload ordered by part number.
+    fn upload_parts_list(
+        &self,
+        upload_id: uuid::Uuid,
+    ) -> impl Future<Output = Result<Vec<UploadPartRow>, SqlStorageError>> + Send;
+
+    /// Record that a part has been uploaded with the given ETag.
+    fn upload_parts_mark_uploaded(
+        &self,
+        upload_id: uuid::Uuid,
+        part_number: i32,
+        etag: String,
+    ) -> impl Future<Output = Result<Option<UploadPartRow>, SqlStorageError>> + Send;
+
+    // -------------------------------------------------------------------------
+    // Storage objects (deduplicated uploads)
+    // -------------------------------------------------------------------------
+
+    /// Take a reference on the user's stored object with `input.sha256` for
+    /// the content `content_id` and point the content at it, in one
+    /// transaction.
+    ///
+    /// If the user has no object with that digest yet, one is recorded at
+    /// `input`'s location with a single reference. Otherwise the existing
+    /// object's reference count is incremented and it is returned unchanged,
+    /// so a duplicate upload is recognized by its differing `storage_key`.
+    /// The content's `sha256` is set with its new location.
+    ///
+    /// Returns `None` without taking a reference if the content does not
+    /// exist or already has a digest.
+    fn contents_share_storage_object(
+        &self,
+        content_id: uuid::Uuid,
+        input: StorageObjectInsert,
+    ) -> impl Future<Output = Result<Option<(StorageObjectRow, ContentRow)>, SqlStorageError>> + Send;
+
+    /// Drop a reference on the user's stored object with `sha256`.
+    ///
+    /// Returns the object with its remaining reference count, or `None` if the
+    /// user has no object with that digest. The record is removed once no
+    /// references are left; the caller must then delete the stored object.
+    fn storage_objects_release(
+        &self,
+        user_id: uuid::Uuid,
+        sha256: &str,
+    ) -> impl Future<Output = Result<Option<StorageObjectRow>, SqlStorageError>> + Send;
+
+    // -------------------------------------------------------------------------
+    // Storage quotas
+    // -------------------------------------------------------------------------
+
+    /// Total size of the user's stored objects by content status, plus the
+    /// declared size of their uploads still `initiated` and not expired at
+    /// `now`.
+    ///
+    /// Contents sharing a deduplicated object count it once, under the most
+    /// live of their statuses (active, archived, trashed).
+    fn users_storage_usage(
+        &self,
+        user_id: uuid::Uuid,
+        now: chrono::DateTime<chrono::Utc>,
+    ) -> impl Future<Output = Result<StorageUsage, SqlStorageError>> + Send;
+
+    /// The user's storage quota in bytes, `None` when unlimited.
+    fn users_get_storage_quota(
+        &self,
+        user_id: uuid::Uuid,
+    ) -> impl Future<Output = Result<Option<i64>, SqlStorageError>> + Send;
+
+    /// Set (or with `None`, remove) the user's storage quota in bytes.
+    ///
+    /// Returns `false` if the user does not exist.
+    fn users_set_storage_quota(
+        &self,
+        user_id: uuid::Uuid,
+        quota_bytes: Option<i64>,
+    ) -> impl Future<Output = Result<bool, SqlStorageError>> + Send;
+
+    // -------------------------------------------------------------------------
+    // Account exports
+    // -------------------------------------------------------------------------
+
+    /// Record a pending export job for the user.
+    fn account_exports_create(
+        &self,
+        user_id: uuid::Uuid,
+        expires_at: chrono::DateTime<chrono::Utc>,
+    ) -> impl Future<Output = Result<AccountExportRow, SqlStorageError>> + Send;
+
+    fn account_exports_get(
+        &self,
+        id: uuid::Uuid,
+    ) -> impl Future<Output = Result<Option<AccountExportRow>, SqlStorageError>> + Send;
+
+    /// Store the manifest of a pending export and mark it completed.
+    ///
+    /// Returns `None` if the export does not exist or is no longer pending.
+    fn account_exports_complete(
+        &self,
+        id: uuid::Uuid,
+        manifest: serde_json::Value,
+    ) -> impl Future<Output = Result<Option<AccountExportRow>, SqlStorageError>> + Send;
+
+    /// Mark a pending export failed with `error`.
+    ///
+    /// Returns `None` if the export does not exist or is no longer pending.
+    fn account_exports_fail(
+        &self,
+        id: uuid::Uuid,
+        error: &str,
+    ) -> impl Future<Output = Result<Option<AccountExportRow>, SqlStorageError>> + Send;
+
+    // -------------------------------------------------------------------------
+    // Audit log
+    // -------------------------------------------------------------------------
+
+    /// Append an entry to the audit log.
+    fn audit_logs_insert(
+        &self,
+        entry: AuditLogInsert,
+    ) -> impl Future<Output = Result<(), SqlStorageError>> + Send;
+
+    /// Audit log entries matching `params`, newest first.
+    fn audit_logs_list(
+        &self,
+        params: AuditLogsListParams,
+    ) -> impl Future<Output = Result<Vec<AuditLogRow>, SqlStorageError>> + Send;
+
     // -------------------------------------------------------------------------
     // OTP rate limiting
     // -------------------------------------------------------------------------
@@ -343,6 +679,8 @@ pub enum SqlStorageError {
     Unauthorized,
     #[error("invalid input: {0}")]
     Invalid(String),
+    #[error("storage quota exceeded: {used} of {quota} bytes used")]
+    QuotaExceeded { used: i64, quota: i64 },
     #[error("database error: {0}")]
     Db(String),
 }
@@ -356,6 +694,137 @@ impl PgStorage {
     pub fn new(pool: PgPool) -> Self {
         Self { pool }
     }
+
+    /// Lock user `user_id` and check that `size` more bytes fit their storage
+    /// quota. The lock is held until `tx` ends, so concurrent reservations
+    /// are checked one at a time.
+    async fn reserve_quota(
+        tx: &mut PgConnection,
+        user_id: uuid::Uuid,
+        size: i64,
+    ) -> Result<(), SqlStorageError> {
+        let quota = sqlx::query_scalar!(
+            "SELECT storage_quota_bytes FROM users WHERE id = $1 FOR UPDATE",
+            user_id
+        )
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(|e| SqlStorageError::Db(e.to_string()))?
+        .flatten();
+        if let Some(quota) = quota {
+            let used = Self::storage_usage(tx, user_id, chrono::Utc::now())
+                .await?
+                .total_bytes();
+            if used.saturating_add(size) > quota {
+                return Err(SqlStorageError::QuotaExceeded { used, quota });
+            }
+        }
+        Ok(())
+    }
+
+    /// [`SqlStorage::contents_insert`] on `conn`, so it can run inside a
+    /// transaction.
+    async fn insert_content(
+        conn: &mut PgConnection,
+        input: ContentsInsert,
+    ) -> Result<ContentRow, SqlStorageError> {
+        let kind = input.kind.unwrap_or_else(|| "file".to_owned());
+        let rec = sqlx::query!(
+            r#"
+            INSERT INTO contents (
+                user_id, title, description,
+                storage_backend, storage_profile, storage_key,
+                content_type, file_size, visibility, kind, body, sha256
+            )
+            VALUES ($1,$2,$3,$4,$5,$6,$7,$8,$9,$10,$11,$12)
+            RETURNING
+                id, user_id, title, description,
+                storage_backend, storage_profile, storage_key,
+                content_type, file_size, status, visibility,
+                kind, body, sha256, metadata,
+                trashed_at, archived_at, created_at, updated_at
+            "#,
+            input.user_id,
+            input.title,
+            input.description,
+            input.storage_backend,
+            input.storage_profile,
+            input.storage_key,
+            input.content_type,
+            input.file_size,
+            input.visibility.as_db_str(),
+            kind,
+            input.body,
+            input.sha256,
+        )
+        .fetch_one(conn)
+        .await
+        .map_err(|e| SqlStorageError::Db(e.to_string()))?;
+
+        Ok(ContentRow {
+            id: rec.id,
+            user_id: rec.user_id,
+            title: rec.title,
+            description: rec.description,
+            storage_backend: rec.storage_backend,
+            storage_profile: rec.storage_profile,
+            storage_key: rec.storage_key,
+            content_type: rec.content_type,
+            file_size: rec.file_size,
+            status: rec.status,
+            visibility: rec.visibility,
+            kind: rec.kind,
+            body: rec.body,
+            sha256: rec.sha256,
+            metadata: rec.metadata,
+            trashed_at: rec.trashed_at,
+            archived_at: rec.archived_at,
+            created_at: rec.created_at,
+            updated_at: rec.updated_at,
+        })
+    }
+
+    /// [`SqlStorage::users_storage_usage`] on `conn`, so it can run inside a
+    /// transaction.
+    async fn storage_usage(
+        conn: &mut PgConnection,
+        user_id: uuid::Uuid,
+        now: chrono::DateTime<chrono::Utc>,
+    ) -> Result<StorageUsage, SqlStorageError> {
+        let rec = sqlx::query!(
+            r#"
+            WITH stored AS (
+                SELECT DISTINCT ON (COALESCE(sha256, id::text)) status, file_size
+                FROM contents
+                WHERE user_id = $1
+                ORDER BY COALESCE(sha256, id::text),
+                    CASE status WHEN 'active' THEN 0 WHEN 'archived' THEN 1 ELSE 2 END
+            )
+            SELECT
+                (SELECT COALESCE(SUM(file_size), 0) FROM stored
+                 WHERE status = 'active')::BIGINT AS "active_bytes!",
+                (SELECT COALESCE(SUM(file_size), 0) FROM stored
+                 WHERE status = 'archived')::BIGINT AS "archived_bytes!",
+                (SELECT COALESCE(SUM(file_size), 0) FROM stored
+                 WHERE status = 'trashed')::BIGINT AS "trashed_bytes!",
+                (SELECT COALESCE(SUM(file_size), 0) FROM uploads
+                 WHERE user_id = $1 AND status = 'initiated' AND expires_at > $2)::BIGINT
+                    AS "pending_bytes!"
+            "#,
+            user_id,
+            now
+        )
+        .fetch_one(conn)
+        .await
+        .map_err(|e| SqlStorageError::Db(e.to_string()))?;
+
+        Ok(StorageUsage {
+            active_bytes: rec.active_bytes,
+            archived_bytes: rec.archived_bytes,
+            trashed_bytes: rec.trashed_bytes,
+            pending_bytes: rec.pending_bytes,
+        })
+    }
 }
 
 // -----------------------------------------------------------------------------
@@ -430,6 +899,10 @@ pub struct ContentRow {
     pub kind: String,
     /// Inline text content (only present when kind="text").
     pub body: Option<String>,
+    /// Hex SHA-256 of the stored object, when the uploader declared one.
+    pub sha256: Option<String>,
+    /// Media metadata extracted from the file; an empty object when none.
+    pub metadata: serde_json::Value,
     pub trashed_at: Option<chrono::DateTime<chrono::Utc>>,
     pub archived_at: Option<chrono::DateTime<chrono::Utc>>,
     pub created_at: chrono::DateTime<chrono::Utc>,
@@ -451,6 +924,18 @@ pub struct ContentsInsert {
     pub kind: Option<String>,
     /// Inline text content (required when kind="text").
     pub body: Option<String>,
+    /// Verified hex SHA-256 of the stored object.
+    pub sha256: Option<String>,
+}
+
+/// Operation applied to many contents at once by
+/// [`SqlStorage::contents_batch`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ContentsBatchOp {
+    SetStatus(ContentStatus),
+    AttachTag(uuid::Uuid),
+    /// Appends the contents to the group, after its current last item.
+    AddToGroup(uuid::Uuid),
 }
 
 #[derive(Debug, Clone, Default)]
@@ -458,36 +943,188 @@ pub struct ContentsListParams {
     pub limit: i64,
     pub offset: i64,
     pub status: Option<ContentStatus>,
-}
-
-#[derive(Debug, Clone, Default)]
-pub struct ContentsUpdate {
-    pub title: Option<String>,
-    /// `None` => no change; `Some(None)` => clear; `Some(Some(v))` => set
-    pub description: Option<Option<String>>,
+    /// Content kind: "file" or "text".
+    pub kind: Option<String>,
+    /// Only contents whose MIME type starts with this, e.g. `image/`.
+    pub content_type_prefix: Option<String>,
     pub visibility: Option<Visibility>,
-    /// Update body content (only allowed when kind="text").
-    /// `None` => no change; `Some(value)` => set new body.
-    pub body: Option<String>,
+    /// Only contents carrying all of these tags.
+    pub tag_ids: Vec<uuid::Uuid>,
+    /// Inclusive lower bound on `created_at`.
+    pub created_after: Option<chrono::DateTime<chrono::Utc>>,
+    /// Exclusive upper bound on `created_at`.
+    pub created_before: Option<chrono::DateTime<chrono::Utc>>,
+    /// Inclusive lower bound on `updated_at`.
+    pub updated_after: Option<chrono::DateTime<chrono::Utc>>,
+    /// Exclusive upper bound on `updated_at`.
+    pub updated_before: Option<chrono::DateTime<chrono::Utc>>,
+    pub sort: ContentsSort,
+    /// Keyset position: only contents after this one in `sort` order.
+    pub after: Option<ContentsCursor>,
 }
 
-#[derive(Debug, Clone)]
-pub struct ContentGroupRow {
-    pub id: uuid::Uuid,
-    pub user_id: uuid::Uuid,
-    pub name: String,
-    pub description: Option<String>,
-    pub visibility: String,
-    pub status: String,
-    pub trashed_at: Option<chrono::DateTime<chrono::Utc>>,
-    pub archived_at: Option<chrono::DateTime<chrono::Utc>>,
-    pub created_at: chrono::DateTime<chrono::Utc>,
-    pub updated_at: chrono::DateTime<chrono::Utc>,
+/// Sort order of listed contents. Ties are broken by id in the same direction.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ContentsSort {
+    /// Newest first.
+    #[default]
+    CreatedDesc,
+    CreatedAsc,
+    UpdatedDesc,
+    UpdatedAsc,
+    TitleAsc,
+    TitleDesc,
+    /// EXIF capture time (`metadata.captured_at`), falling back to the UTC
+    /// creation time for contents without one.
+    CapturedDesc,
+    CapturedAsc,
 }
 
-#[derive(Debug, Clone)]
-pub struct ContentGroupItemRow {
-    pub id: uuid::Uuid,
+impl ContentsSort {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::CreatedDesc => "created_at_desc",
+            Self::CreatedAsc => "created_at_asc",
+            Self::UpdatedDesc => "updated_at_desc",
+            Self::UpdatedAsc => "updated_at_asc",
+            Self::TitleAsc => "title_asc",
+            Self::TitleDesc => "title_desc",
+            Self::CapturedDesc => "captured_at_desc",
+            Self::CapturedAsc => "captured_at_asc",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        [
+            Self::CreatedDesc,
+            Self::CreatedAsc,
+            Self::UpdatedDesc,
+            Self::UpdatedAsc,
+            Self::TitleAsc,
+            Self::TitleDesc,
+            Self::CapturedDesc,
+            Self::CapturedAsc,
+        ]
+        .into_iter()
+        .find(|sort| sort.as_str() == s)
+    }
+
+    pub fn is_descending(&self) -> bool {
+        matches!(
+            self,
+            Self::CreatedDesc | Self::UpdatedDesc | Self::TitleDesc | Self::CapturedDesc
+        )
+    }
+
+    /// Keyset position of `row` in this order.
+    pub fn cursor(&self, row: &ContentRow) -> ContentsCursor {
+        let value = match self {
+            Self::CreatedDesc | Self::CreatedAsc => row
+                .created_at
+                .to_rfc3339_opts(chrono::SecondsFormat::Micros, true),
+            Self::UpdatedDesc | Self::UpdatedAsc => row
+                .updated_at
+                .to_rfc3339_opts(chrono::SecondsFormat::Micros, true),
+            Self::TitleAsc | Self::TitleDesc => row.title.clone(),
+            Self::CapturedDesc | Self::CapturedAsc => row
+                .metadata
+                .get("captured_at")
+                .and_then(serde_json::Value::as_str)
+                .map(str::to_owned)
+                .unwrap_or_else(|| row.created_at.format("%Y-%m-%dT%H:%M:%S").to_string()),
+        };
+        ContentsCursor { value, id: row.id }
+    }
+}
+
+/// Position of a content in a [`ContentsSort`] order: its sort value and id.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ContentsCursor {
+    /// The sort value as text.
+    pub value: String,
+    pub id: uuid::Uuid,
+}
+
+/// Marks the start of a matched term in [`ContentSearchHit`] highlights.
+pub const SEARCH_HIGHLIGHT_START: char = '\u{2}';
+/// Marks the end of a matched term in [`ContentSearchHit`] highlights.
+pub const SEARCH_HIGHLIGHT_END: char = '\u{3}';
+
+#[derive(Debug, Clone, Default)]
+pub struct ContentsSearchParams {
+    /// Search terms, in `websearch_to_tsquery` syntax (`"a phrase"`, `-word`, `or`).
+    pub query: String,
+    pub limit: i64,
+    pub offset: i64,
+    pub status: Option<ContentStatus>,
+    /// Content kind: "file" or "text".
+    pub kind: Option<String>,
+    /// Only contents carrying all of these tags.
+    pub tag_ids: Vec<uuid::Uuid>,
+}
+
+/// A content matching a search, best match first.
+#[derive(Debug, Clone)]
+pub struct ContentSearchHit {
+    pub content: ContentRow,
+    pub rank: f32,
+    /// The title with matched terms wrapped in [`SEARCH_HIGHLIGHT_START`] and
+    /// [`SEARCH_HIGHLIGHT_END`].
+    pub title_highlight: String,
+    /// Fragments of the description and body around matched terms, marked the
+    /// same way. `None` when the content has neither.
+    pub snippet: Option<String>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ContentsUpdate {
+    pub title: Option<String>,
+    /// `None` => no change; `Some(None)` => clear; `Some(Some(v))` => set
+    pub description: Option<Option<String>>,
+    pub visibility: Option<Visibility>,
+    /// Update body content (only allowed when kind="text").
+    /// `None` => no change; `Some(value)` => set new body.
+    pub body: Option<String>,
+    /// For text contents, how many earlier versions to keep in
+    /// `content_revisions` when the title, description or body change.
+    /// 0 records no revision.
+    pub max_revisions: u32,
+    /// Only apply the change while the row's `updated_at` still equals this
+    /// (an `If-Match` precondition); otherwise nothing is updated.
+    pub expected_updated_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+/// An earlier version of a text content.
+#[derive(Debug, Clone)]
+pub struct ContentRevisionRow {
+    pub id: uuid::Uuid,
+    pub content_id: uuid::Uuid,
+    /// Increases by one with every revision of the content, starting at 1.
+    pub revision: i32,
+    pub title: String,
+    pub description: Option<String>,
+    pub body: String,
+    /// When the version was replaced.
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ContentGroupRow {
+    pub id: uuid::Uuid,
+    pub user_id: uuid::Uuid,
+    pub name: String,
+    pub description: Option<String>,
+    pub visibility: String,
+    pub status: String,
+    pub trashed_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub archived_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub updated_at: chrono::DateTime<chrono::Utc>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ContentGroupItemRow {
+    pub id: uuid::Uuid,
     pub group_id: uuid::Uuid,
     pub content_id: uuid::Uuid,
     pub sort_order: i32,
@@ -509,12 +1146,28 @@ pub struct GroupsListParams {
     pub status: Option<GroupStatus>,
 }
 
+/// Which trashed contents or groups to purge.
+#[derive(Debug, Clone, Default)]
+pub struct TrashPurgeParams {
+    /// Only items of this user.
+    pub user_id: Option<uuid::Uuid>,
+    /// Only items trashed before this instant.
+    pub trashed_before: Option<chrono::DateTime<chrono::Utc>>,
+    /// Only items with these ids.
+    pub ids: Option<Vec<uuid::Uuid>>,
+    /// Maximum number of items deleted.
+    pub limit: i64,
+}
+
 #[derive(Debug, Clone, Default)]
 pub struct GroupUpdate {
     pub name: Option<String>,
     /// `None` => no change; `Some(None)` => clear; `Some(Some(v))` => set
     pub description: Option<Option<String>>,
     pub visibility: Option<Visibility>,
+    /// Only apply the change while the row's `updated_at` still equals this
+    /// (an `If-Match` precondition); otherwise nothing is updated.
+    pub expected_updated_at: Option<chrono::DateTime<chrono::Utc>>,
 }
 
 #[derive(Debug, Clone)]
@@ -524,6 +1177,7 @@ pub struct TagRow {
     pub name: String,
     pub color: Option<String>,
     pub created_at: chrono::DateTime<chrono::Utc>,
+    pub updated_at: chrono::DateTime<chrono::Utc>,
 }
 
 #[derive(Debug, Clone)]
@@ -537,6 +1191,9 @@ pub struct TagCreate {
 pub struct TagUpdate {
     pub name: Option<String>,
     pub color: Option<Option<String>>,
+    /// Only apply the change while the row's `updated_at` still equals this
+    /// (an `If-Match` precondition); otherwise nothing is updated.
+    pub expected_updated_at: Option<chrono::DateTime<chrono::Utc>>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -567,6 +1224,8 @@ pub struct ShareLinkRow {
     pub expires_at: Option<chrono::DateTime<chrono::Utc>>,
     pub is_active: bool,
     pub created_at: chrono::DateTime<chrono::Utc>,
+    /// When the link's settings last changed; access counting leaves it alone.
+    pub updated_at: chrono::DateTime<chrono::Utc>,
 }
 
 #[derive(Debug, Clone)]
@@ -590,6 +1249,62 @@ pub struct ShareLinkUpdate {
     pub expires_at: Option<Option<chrono::DateTime<chrono::Utc>>>,
     pub max_access_count: Option<Option<i32>>,
     pub is_active: Option<bool>,
+    /// Only apply the change while the row's `updated_at` still equals this
+    /// (an `If-Match` precondition); otherwise nothing is updated.
+    pub expected_updated_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+/// A public access to a share link.
+#[derive(Debug, Clone)]
+pub struct ShareLinkAccessInsert {
+    pub share_link_id: uuid::Uuid,
+    pub ip_address: Option<IpAddr>,
+    pub user_agent: Option<String>,
+    /// Whether the visitor sent a password, right or wrong.
+    pub password_supplied: bool,
+}
+
+/// Configuration for share link password rate limiting.
+#[derive(Debug, Clone)]
+pub struct ShareLinkPasswordRateLimitConfig {
+    /// Maximum failed attempts per share link within the time window.
+    pub max_attempts_per_link: i64,
+    /// Maximum failed attempts per IP address within the time window.
+    pub max_attempts_per_ip: i64,
+    /// Time window in seconds for counting attempts.
+    pub window_seconds: i64,
+}
+
+impl Default for ShareLinkPasswordRateLimitConfig {
+    fn default() -> Self {
+        Self {
+            // 10 failed attempts per link in 15 minutes
+            max_attempts_per_link: 10,
+            // 20 failed attempts per IP in 15 minutes, across links
+            max_attempts_per_ip: 20,
+            // 15-minute window
+            window_seconds: 900,
+        }
+    }
+}
+
+/// Access statistics of a share link.
+///
+/// A visitor is a distinct (IP address, user agent) pair.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ShareLinkAccessStats {
+    pub total_accesses: i64,
+    pub unique_visitors: i64,
+    pub last_accessed_at: Option<chrono::DateTime<chrono::Utc>>,
+    /// Days (UTC) with at least one access, oldest first.
+    pub daily: Vec<ShareLinkAccessDay>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShareLinkAccessDay {
+    pub day: chrono::NaiveDate,
+    pub accesses: i64,
+    pub unique_visitors: i64,
 }
 
 #[derive(Debug, Clone)]
@@ -603,6 +1318,30 @@ pub struct ContentShareRow {
     pub created_by: uuid::Uuid,
 }
 
+/// A direct share of a content or group with a user.
+#[derive(Debug, Clone)]
+pub struct UserShareRow {
+    pub id: uuid::Uuid,
+    pub shared_with_user_id: uuid::Uuid,
+    pub shared_with_username: String,
+    pub permission: String,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// A content or group shared directly with a user.
+#[derive(Debug, Clone)]
+pub struct SharedWithUserRow {
+    /// "content" or "group".
+    pub item_type: String,
+    pub item_id: uuid::Uuid,
+    /// Content title or group name.
+    pub title: String,
+    pub owner_id: uuid::Uuid,
+    pub owner_username: String,
+    pub permission: String,
+    pub shared_at: chrono::DateTime<chrono::Utc>,
+}
+
 #[derive(Debug, Clone)]
 pub struct ContentShareCreateForUser {
     pub content_id: uuid::Uuid,
@@ -646,6 +1385,50 @@ pub struct GroupShareCreateForLink {
     pub created_by: uuid::Uuid,
 }
 
+// -----------------------------------------------------------------------------
+// Audit Log Types
+// -----------------------------------------------------------------------------
+
+/// Entry appended to the `audit_logs` table.
+#[derive(Debug, Clone, Default)]
+pub struct AuditLogInsert {
+    /// The acting user; `None` for internal (operator) and system actions.
+    pub user_id: Option<uuid::Uuid>,
+    /// What happened, as `<entity type>.<verb>`, e.g. `content.trash`.
+    pub action: String,
+    pub entity_type: String,
+    pub entity_id: Option<uuid::Uuid>,
+    pub details: Option<serde_json::Value>,
+    pub ip_address: Option<IpAddr>,
+}
+
+/// Row from the `audit_logs` table.
+#[derive(Debug, Clone)]
+pub struct AuditLogRow {
+    pub id: uuid::Uuid,
+    pub user_id: Option<uuid::Uuid>,
+    pub action: String,
+    pub entity_type: String,
+    pub entity_id: Option<uuid::Uuid>,
+    pub details: Option<serde_json::Value>,
+    pub ip_address: Option<IpAddr>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct AuditLogsListParams {
+    pub limit: i64,
+    pub offset: i64,
+    pub user_id: Option<uuid::Uuid>,
+    pub action: Option<String>,
+    pub entity_type: Option<String>,
+    pub entity_id: Option<uuid::Uuid>,
+    /// Inclusive lower bound on `created_at`.
+    pub created_after: Option<chrono::DateTime<chrono::Utc>>,
+    /// Exclusive upper bound on `created_at`.
+    pub created_before: Option<chrono::DateTime<chrono::Utc>>,
+}
+
 // -----------------------------------------------------------------------------
 // OTP Rate Limiting Types
 // -----------------------------------------------------------------------------
@@ -733,6 +1516,12 @@ pub struct UploadRow {
     pub expires_at: chrono::DateTime<chrono::Utc>,
     pub created_at: chrono::DateTime<chrono::Utc>,
     pub completed_at: Option<chrono::DateTime<chrono::Utc>>,
+    /// Backend upload ID for multipart uploads.
+    pub multipart_upload_id: Option<String>,
+    /// Size of every part except the last, for multipart uploads.
+    pub part_size: Option<i64>,
+    /// Hex SHA-256 the client declared for the file.
+    pub sha256: Option<String>,
 }
 
 /// Input for creating an upload record.
@@ -745,6 +1534,125 @@ pub struct UploadInsert {
     pub content_type: String,
     pub file_size: i64,
     pub expires_at: chrono::DateTime<chrono::Utc>,
+    pub multipart_upload_id: Option<String>,
+    pub part_size: Option<i64>,
+    pub sha256: Option<String>,
+}
+
+/// Row from the `storage_objects` table: one stored object shared by every
+/// content of a user with the same SHA-256.
+#[derive(Debug, Clone)]
+pub struct StorageObjectRow {
+    pub id: uuid::Uuid,
+    pub user_id: uuid::Uuid,
+    pub sha256: String,
+    pub storage_backend: String,
+    pub storage_profile: String,
+    pub storage_key: String,
+    pub file_size: i64,
+    /// Number of contents pointing at the object.
+    pub ref_count: i32,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub updated_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// Input for taking a reference on a stored object.
+#[derive(Debug, Clone)]
+pub struct StorageObjectInsert {
+    pub user_id: uuid::Uuid,
+    pub sha256: String,
+    pub storage_backend: String,
+    pub storage_profile: String,
+    pub storage_key: String,
+    pub file_size: i64,
+}
+
+/// A content or group a share link is attached to; exactly one is set.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShareLinkTargetRow {
+    pub share_link_id: uuid::Uuid,
+    pub content_id: Option<uuid::Uuid>,
+    pub group_id: Option<uuid::Uuid>,
+}
+
+/// Row from the `account_exports` table.
+#[derive(Debug, Clone)]
+pub struct AccountExportRow {
+    pub id: uuid::Uuid,
+    pub user_id: uuid::Uuid,
+    /// "pending", "completed" or "failed".
+    pub status: String,
+    /// The export manifest, once completed.
+    pub manifest: Option<serde_json::Value>,
+    /// Why the export failed.
+    pub error: Option<String>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub completed_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub expires_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// Bytes a user stores, as counted against their storage quota.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct StorageUsage {
+    pub active_bytes: i64,
+    pub archived_bytes: i64,
+    pub trashed_bytes: i64,
+    /// Declared size of uploads initialized but not yet completed.
+    pub pending_bytes: i64,
+}
+
+impl StorageUsage {
+    /// Bytes counted against the quota: every content, whatever its status,
+    /// and pending uploads.
+    pub fn total_bytes(&self) -> i64 {
+        self.active_bytes
+            .saturating_add(self.archived_bytes)
+            .saturating_add(self.trashed_bytes)
+            .saturating_add(self.pending_bytes)
+    }
+}
+
+/// Status of one part of a multipart upload.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UploadPartStatus {
+    Pending,
+    Uploaded,
+}
+
+impl UploadPartStatus {
+    pub fn as_db_str(&self) -> &'static str {
+        match self {
+            Self::Pending => "pending",
+            Self::Uploaded => "uploaded",
+        }
+    }
+
+    pub fn from_db_str(s: &str) -> Option<Self> {
+        match s {
+            "pending" => Some(Self::Pending),
+            "uploaded" => Some(Self::Uploaded),
+            _ => None,
+        }
+    }
+}
+
+/// Row from the `upload_parts` table.
+#[derive(Debug, Clone)]
+pub struct UploadPartRow {
+    pub upload_id: uuid::Uuid,
+    pub part_number: i32,
+    pub size: i64,
+    pub etag: Option<String>,
+    pub status: String,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub updated_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// Input for creating an upload part record.
+#[derive(Debug, Clone)]
+pub struct UploadPartInsert {
+    pub part_number: i32,
+    pub size: i64,
 }
 
 // -----------------------------------------------------------------------------
@@ -757,57 +1665,29 @@ impl SqlStorage for PgStorage {
     }
 
     async fn contents_insert(&self, input: ContentsInsert) -> Result<ContentRow, SqlStorageError> {
-        let kind = input.kind.unwrap_or_else(|| "file".to_owned());
-        let rec = sqlx::query!(
-            r#"
-            INSERT INTO contents (
-                user_id, title, description,
-                storage_backend, storage_profile, storage_key,
-                content_type, file_size, visibility, kind, body
-            )
-            VALUES ($1,$2,$3,$4,$5,$6,$7,$8,$9,$10,$11)
-            RETURNING
-                id, user_id, title, description,
-                storage_backend, storage_profile, storage_key,
-                content_type, file_size, status, visibility,
-                kind, body,
-                trashed_at, archived_at, created_at, updated_at
-            "#,
-            input.user_id,
-            input.title,
-            input.description,
-            input.storage_backend,
-            input.storage_profile,
-            input.storage_key,
-            input.content_type,
-            input.file_size,
-            input.visibility.as_db_str(),
-            kind,
-            input.body,
-        )
-        .fetch_one(&self.pool)
-        .await
-        .map_err(|e| SqlStorageError::Db(e.to_string()))?;
+        let mut conn = self
+            .pool
+            .acquire()
+            .await
+            .map_err(|e| SqlStorageError::Db(e.to_string()))?;
+        Self::insert_content(&mut conn, input).await
+    }
 
-        Ok(ContentRow {
-            id: rec.id,
-            user_id: rec.user_id,
-            title: rec.title,
-            description: rec.description,
-            storage_backend: rec.storage_backend,
-            storage_profile: rec.storage_profile,
-            storage_key: rec.storage_key,
-            content_type: rec.content_type,
-            file_size: rec.file_size,
-            status: rec.status,
-            visibility: rec.visibility,
-            kind: rec.kind,
-            body: rec.body,
-            trashed_at: rec.trashed_at,
-            archived_at: rec.archived_at,
-            created_at: rec.created_at,
-            updated_at: rec.updated_at,
-        })
+    async fn contents_insert_within_quota(
+        &self,
+        input: ContentsInsert,
+    ) -> Result<ContentRow, SqlStorageError> {
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .map_err(|e| SqlStorageError::Db(e.to_string()))?;
+        Self::reserve_quota(&mut tx, input.user_id, input.file_size).await?;
+        let row = Self::insert_content(&mut tx, input).await?;
+        tx.commit()
+            .await
+            .map_err(|e| SqlStorageError::Db(e.to_string()))?;
+        Ok(row)
     }
 
     async fn contents_get(&self, id: uuid::Uuid) -> Result<Option<ContentRow>, SqlStorageError> {
@@ -817,7 +1697,7 @@ impl SqlStorage for PgStorage {
                 id, user_id, title, description,
                 storage_backend, storage_profile, storage_key,
                 content_type, file_size, status, visibility,
-                kind, body,
+                kind, body, sha256, metadata,
                 trashed_at, archived_at, created_at, updated_at
             FROM contents
             WHERE id = $1
@@ -842,6 +1722,8 @@ impl SqlStorage for PgStorage {
             visibility: rec.visibility,
             kind: rec.kind,
             body: rec.body,
+            sha256: rec.sha256,
+            metadata: rec.metadata,
             trashed_at: rec.trashed_at,
             archived_at: rec.archived_at,
             created_at: rec.created_at,
@@ -857,25 +1739,159 @@ impl SqlStorage for PgStorage {
         let limit = if params.limit <= 0 { 50 } else { params.limit };
         let offset = if params.offset < 0 { 0 } else { params.offset };
         let status = params.status.map(|s| s.as_db_str().to_owned());
+        let visibility = params.visibility.map(|v| v.as_db_str().to_owned());
+        let (after_value, after_id) = match params.after {
+            Some(cursor) => (Some(cursor.value), Some(cursor.id)),
+            None => (None, None),
+        };
 
-        let recs = sqlx::query!(
-            r#"
-            SELECT
-                id, user_id, title, description,
-                storage_backend, storage_profile, storage_key,
-                content_type, file_size, status, visibility,
-                kind, body,
-                trashed_at, archived_at, created_at, updated_at
-            FROM contents
-            WHERE user_id = $1
-              AND ($2::text IS NULL OR status = $2)
-            ORDER BY created_at DESC
-            LIMIT $3 OFFSET $4
+        // One statement per sort order, so each can use the
+        // `(user_id, ...)` indexes; `$11`/`$12` are the keyset cursor.
+        macro_rules! list_query {
+            ($keyset:tt, $order:tt) => {
+                sqlx::query_as!(
+                    ContentRow,
+                    r#"
+                    SELECT
+                        c.id, c.user_id, c.title, c.description,
+                        c.storage_backend, c.storage_profile, c.storage_key,
+                        c.content_type, c.file_size, c.status, c.visibility,
+                        c.kind, c.body, c.sha256, c.metadata,
+                        c.trashed_at, c.archived_at, c.created_at, c.updated_at
+                    FROM contents c
+                    WHERE c.user_id = $1
+                      AND ($2::text IS NULL OR c.status = $2)
+                      AND ($3::text IS NULL OR c.kind = $3)
+                      AND ($4::text IS NULL OR starts_with(c.content_type, $4))
+                      AND ($5::text IS NULL OR c.visibility = $5)
+                      AND (cardinality($6::uuid[]) = 0 OR (
+                          SELECT count(*) FROM content_tags ct
+                          WHERE ct.content_id = c.id AND ct.tag_id = ANY($6)
+                      ) = cardinality($6))
+                      AND ($7::timestamptz IS NULL OR c.created_at >= $7)
+                      AND ($8::timestamptz IS NULL OR c.created_at < $8)
+                      AND ($9::timestamptz IS NULL OR c.updated_at >= $9)
+                      AND ($10::timestamptz IS NULL OR c.updated_at < $10)
+                      AND "#
+                        + $keyset
+                        + r#"
+                    ORDER BY "#
+                        + $order
+                        + r#"
+                    LIMIT $13 OFFSET $14
+                    "#,
+                    user_id,
+                    status,
+                    params.kind,
+                    params.content_type_prefix,
+                    visibility,
+                    &params.tag_ids,
+                    params.created_after,
+                    params.created_before,
+                    params.updated_after,
+                    params.updated_before,
+                    after_value,
+                    after_id,
+                    limit,
+                    offset
+                )
+                .fetch_all(&self.pool)
+                .await
+            };
+        }
+
+        let rows = match params.sort {
+            ContentsSort::CreatedDesc => list_query!(
+                "($11::text IS NULL OR (c.created_at, c.id) < ($11::text::timestamptz, $12))",
+                "c.created_at DESC, c.id DESC"
+            ),
+            ContentsSort::CreatedAsc => list_query!(
+                "($11::text IS NULL OR (c.created_at, c.id) > ($11::text::timestamptz, $12))",
+                "c.created_at ASC, c.id ASC"
+            ),
+            ContentsSort::UpdatedDesc => list_query!(
+                "($11::text IS NULL OR (c.updated_at, c.id) < ($11::text::timestamptz, $12))",
+                "c.updated_at DESC, c.id DESC"
+            ),
+            ContentsSort::UpdatedAsc => list_query!(
+                "($11::text IS NULL OR (c.updated_at, c.id) > ($11::text::timestamptz, $12))",
+                "c.updated_at ASC, c.id ASC"
+            ),
+            ContentsSort::TitleAsc => list_query!(
+                "($11::text IS NULL OR (c.title::text, c.id) > ($11::text, $12))",
+                "c.title ASC, c.id ASC"
+            ),
+            ContentsSort::TitleDesc => list_query!(
+                "($11::text IS NULL OR (c.title::text, c.id) < ($11::text, $12))",
+                "c.title DESC, c.id DESC"
+            ),
+            ContentsSort::CapturedDesc => list_query!(
+                "($11::text IS NULL OR (content_captured_at(c.metadata, c.created_at), c.id) < ($11::text, $12))",
+                "content_captured_at(c.metadata, c.created_at) DESC, c.id DESC"
+            ),
+            ContentsSort::CapturedAsc => list_query!(
+                "($11::text IS NULL OR (content_captured_at(c.metadata, c.created_at), c.id) > ($11::text, $12))",
+                "content_captured_at(c.metadata, c.created_at) ASC, c.id ASC"
+            ),
+        };
+
+        rows.map_err(|e| SqlStorageError::Db(e.to_string()))
+    }
+
+    async fn contents_search(
+        &self,
+        user_id: uuid::Uuid,
+        params: ContentsSearchParams,
+    ) -> Result<Vec<ContentSearchHit>, SqlStorageError> {
+        let limit = if params.limit <= 0 { 50 } else { params.limit };
+        let offset = if params.offset < 0 { 0 } else { params.offset };
+        let status = params.status.map(|s| s.as_db_str().to_owned());
+        let headline_options = format!(
+            "StartSel={SEARCH_HIGHLIGHT_START}, StopSel={SEARCH_HIGHLIGHT_END}, \
+             MaxWords=35, MinWords=15, MaxFragments=2, FragmentDelimiter=\" … \""
+        );
+
+        // ts_headline is costly, so Postgres evaluates it only for the rows
+        // left after the LIMIT.
+        let recs = sqlx::query!(
+            r#"
+            SELECT
+                c.id, c.user_id, c.title, c.description,
+                c.storage_backend, c.storage_profile, c.storage_key,
+                c.content_type, c.file_size, c.status, c.visibility,
+                c.kind, c.body, c.sha256, c.metadata,
+                c.trashed_at, c.archived_at, c.created_at, c.updated_at,
+                ts_rank_cd(c.search_vector, q.query) AS "rank!",
+                ts_headline(
+                    'english', c.title, q.query,
+                    'HighlightAll=true, ' || $8
+                ) AS "title_highlight!",
+                CASE
+                    WHEN c.description IS NULL AND c.body IS NULL THEN NULL
+                    ELSE ts_headline(
+                        'english', concat_ws(E'\n', c.description, c.body), q.query, $8
+                    )
+                END AS snippet
+            FROM contents c, websearch_to_tsquery('english', $2) AS q(query)
+            WHERE c.user_id = $1
+              AND c.search_vector @@ q.query
+              AND ($3::text IS NULL OR c.status = $3)
+              AND ($4::text IS NULL OR c.kind = $4)
+              AND (
+                  SELECT count(*) FROM content_tags ct
+                  WHERE ct.content_id = c.id AND ct.tag_id = ANY($5)
+              ) = cardinality($5)
+            ORDER BY ts_rank_cd(c.search_vector, q.query) DESC, c.updated_at DESC
+            LIMIT $6 OFFSET $7
             "#,
             user_id,
+            params.query,
             status,
+            params.kind,
+            &params.tag_ids,
             limit,
-            offset
+            offset,
+            headline_options,
         )
         .fetch_all(&self.pool)
         .await
@@ -883,24 +1899,31 @@ impl SqlStorage for PgStorage {
 
         Ok(recs
             .into_iter()
-            .map(|rec| ContentRow {
-                id: rec.id,
-                user_id: rec.user_id,
-                title: rec.title,
-                description: rec.description,
-                storage_backend: rec.storage_backend,
-                storage_profile: rec.storage_profile,
-                storage_key: rec.storage_key,
-                content_type: rec.content_type,
-                file_size: rec.file_size,
-                status: rec.status,
-                visibility: rec.visibility,
-                kind: rec.kind,
-                body: rec.body,
-                trashed_at: rec.trashed_at,
-                archived_at: rec.archived_at,
-                created_at: rec.created_at,
-                updated_at: rec.updated_at,
+            .map(|rec| ContentSearchHit {
+                content: ContentRow {
+                    id: rec.id,
+                    user_id: rec.user_id,
+                    title: rec.title,
+                    description: rec.description,
+                    storage_backend: rec.storage_backend,
+                    storage_profile: rec.storage_profile,
+                    storage_key: rec.storage_key,
+                    content_type: rec.content_type,
+                    file_size: rec.file_size,
+                    status: rec.status,
+                    visibility: rec.visibility,
+                    kind: rec.kind,
+                    body: rec.body,
+                    sha256: rec.sha256,
+                    metadata: rec.metadata,
+                    trashed_at: rec.trashed_at,
+                    archived_at: rec.archived_at,
+                    created_at: rec.created_at,
+                    updated_at: rec.updated_at,
+                },
+                rank: rec.rank,
+                title_highlight: rec.title_highlight,
+                snippet: rec.snippet,
             })
             .collect())
     }
@@ -917,6 +1940,77 @@ impl SqlStorage for PgStorage {
         let body_set = changes.body.is_some();
         let body_value = changes.body;
 
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .map_err(|e| SqlStorageError::Db(e.to_string()))?;
+
+        // Lock the row so concurrent updates number their revisions in turn.
+        let current = sqlx::query!(
+            r#"
+            SELECT title, description, body, kind, updated_at
+            FROM contents
+            WHERE id = $1 AND user_id = $2
+            FOR UPDATE
+            "#,
+            id,
+            user_id
+        )
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(|e| SqlStorageError::Db(e.to_string()))?;
+        let Some(current) = current else {
+            return Ok(None);
+        };
+        if changes
+            .expected_updated_at
+            .is_some_and(|expected| expected != current.updated_at)
+        {
+            return Ok(None);
+        }
+
+        let changed = changes.title.as_ref().is_some_and(|t| *t != current.title)
+            || (description_set && description_value != current.description)
+            || (body_set && body_value != current.body);
+        if current.kind == "text" && changed && changes.max_revisions > 0 {
+            sqlx::query!(
+                r#"
+                INSERT INTO content_revisions (content_id, revision, title, description, body)
+                SELECT
+                    $1,
+                    coalesce(max(revision), 0) + 1,
+                    $2,
+                    $3,
+                    $4
+                FROM content_revisions
+                WHERE content_id = $1
+                "#,
+                id,
+                current.title,
+                current.description,
+                current.body.unwrap_or_default(),
+            )
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| SqlStorageError::Db(e.to_string()))?;
+
+            sqlx::query!(
+                r#"
+                DELETE FROM content_revisions
+                WHERE content_id = $1
+                  AND revision <= (
+                      SELECT max(revision) FROM content_revisions WHERE content_id = $1
+                  ) - $2
+                "#,
+                id,
+                i32::try_from(changes.max_revisions).unwrap_or(i32::MAX),
+            )
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| SqlStorageError::Db(e.to_string()))?;
+        }
+
         // We need to handle "no change" vs "clear": COALESCE can't distinguish.
         // Strategy:
         // - If description was provided, use `$4` directly (even if NULL) via CASE when.
@@ -945,7 +2039,7 @@ impl SqlStorage for PgStorage {
                 id, user_id, title, description,
                 storage_backend, storage_profile, storage_key,
                 content_type, file_size, status, visibility,
-                kind, body,
+                kind, body, sha256, metadata,
                 trashed_at, archived_at, created_at, updated_at
             "#,
             id,
@@ -957,10 +2051,14 @@ impl SqlStorage for PgStorage {
             body_set,
             body_value,
         )
-        .fetch_optional(&self.pool)
+        .fetch_optional(&mut *tx)
         .await
         .map_err(|e| SqlStorageError::Db(e.to_string()))?;
 
+        tx.commit()
+            .await
+            .map_err(|e| SqlStorageError::Db(e.to_string()))?;
+
         Ok(rec.map(|rec| ContentRow {
             id: rec.id,
             user_id: rec.user_id,
@@ -975,6 +2073,8 @@ impl SqlStorage for PgStorage {
             visibility: rec.visibility,
             kind: rec.kind,
             body: rec.body,
+            sha256: rec.sha256,
+            metadata: rec.metadata,
             trashed_at: rec.trashed_at,
             archived_at: rec.archived_at,
             created_at: rec.created_at,
@@ -1007,7 +2107,7 @@ impl SqlStorage for PgStorage {
                 id, user_id, title, description,
                 storage_backend, storage_profile, storage_key,
                 content_type, file_size, status, visibility,
-                kind, body,
+                kind, body, sha256, metadata,
                 trashed_at, archived_at, created_at, updated_at
             "#,
             id,
@@ -1034,6 +2134,8 @@ impl SqlStorage for PgStorage {
             visibility: rec.visibility,
             kind: rec.kind,
             body: rec.body,
+            sha256: rec.sha256,
+            metadata: rec.metadata,
             trashed_at: rec.trashed_at,
             archived_at: rec.archived_at,
             created_at: rec.created_at,
@@ -1041,6 +2143,247 @@ impl SqlStorage for PgStorage {
         }))
     }
 
+    async fn contents_batch(
+        &self,
+        user_id: uuid::Uuid,
+        ids: &[uuid::Uuid],
+        op: ContentsBatchOp,
+        now: chrono::DateTime<chrono::Utc>,
+    ) -> Result<Vec<uuid::Uuid>, SqlStorageError> {
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .map_err(|e| SqlStorageError::Db(e.to_string()))?;
+
+        // Lock the user's rows so a concurrent batch sees a consistent set.
+        let owned: Vec<uuid::Uuid> = sqlx::query_scalar!(
+            r#"
+            SELECT id FROM contents
+            WHERE id = ANY($1) AND user_id = $2
+            FOR UPDATE
+            "#,
+            ids,
+            user_id
+        )
+        .fetch_all(&mut *tx)
+        .await
+        .map_err(|e| SqlStorageError::Db(e.to_string()))?;
+
+        match op {
+            ContentsBatchOp::SetStatus(new_status) => {
+                let (trashed_at, archived_at) = match new_status {
+                    ContentStatus::Trashed => (Some(now), None),
+                    ContentStatus::Archived => (None, Some(now)),
+                    ContentStatus::Active => (None, None),
+                };
+                sqlx::query!(
+                    r#"
+                    UPDATE contents
+                    SET status = $2, trashed_at = $3, archived_at = $4
+                    WHERE id = ANY($1)
+                    "#,
+                    &owned,
+                    new_status.as_db_str(),
+                    trashed_at,
+                    archived_at
+                )
+                .execute(&mut *tx)
+                .await
+                .map_err(|e| SqlStorageError::Db(e.to_string()))?;
+            }
+            ContentsBatchOp::AttachTag(tag_id) => {
+                let tag = sqlx::query_scalar!(
+                    "SELECT id FROM tags WHERE id = $1 AND user_id = $2",
+                    tag_id,
+                    user_id
+                )
+                .fetch_optional(&mut *tx)
+                .await
+                .map_err(|e| SqlStorageError::Db(e.to_string()))?;
+                if tag.is_none() {
+                    return Err(SqlStorageError::NotFound);
+                }
+
+                sqlx::query!(
+                    r#"
+                    INSERT INTO content_tags (content_id, tag_id)
+                    SELECT id, $2 FROM unnest($1::uuid[]) AS t(id)
+                    ON CONFLICT (content_id, tag_id) DO NOTHING
+                    "#,
+                    &owned,
+                    tag_id
+                )
+                .execute(&mut *tx)
+                .await
+                .map_err(|e| SqlStorageError::Db(e.to_string()))?;
+            }
+            ContentsBatchOp::AddToGroup(group_id) => {
+                let group = sqlx::query_scalar!(
+                    "SELECT id FROM content_groups WHERE id = $1 AND user_id = $2 FOR UPDATE",
+                    group_id,
+                    user_id
+                )
+                .fetch_optional(&mut *tx)
+                .await
+                .map_err(|e| SqlStorageError::Db(e.to_string()))?;
+                if group.is_none() {
+                    return Err(SqlStorageError::NotFound);
+                }
+
+                // Keep the request order; contents already in the group stay
+                // where they are.
+                let ordered: Vec<uuid::Uuid> = ids
+                    .iter()
+                    .copied()
+                    .filter(|id| owned.contains(id))
+                    .collect();
+                sqlx::query!(
+                    r#"
+                    INSERT INTO content_group_items (group_id, content_id, sort_order)
+                    SELECT
+                        $1,
+                        t.id,
+                        (
+                            SELECT coalesce(max(sort_order), -1)
+                            FROM content_group_items
+                            WHERE group_id = $1
+                        ) + t.position::int
+                    FROM unnest($2::uuid[]) WITH ORDINALITY AS t(id, position)
+                    ON CONFLICT (group_id, content_id) DO NOTHING
+                    "#,
+                    group_id,
+                    &ordered
+                )
+                .execute(&mut *tx)
+                .await
+                .map_err(|e| SqlStorageError::Db(e.to_string()))?;
+            }
+        }
+
+        tx.commit()
+            .await
+            .map_err(|e| SqlStorageError::Db(e.to_string()))?;
+
+        Ok(owned)
+    }
+
+    async fn contents_set_media_metadata(
+        &self,
+        id: uuid::Uuid,
+        metadata: serde_json::Value,
+    ) -> Result<bool, SqlStorageError> {
+        let result = sqlx::query!(
+            "UPDATE contents SET metadata = $2 WHERE id = $1",
+            id,
+            metadata,
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|e| SqlStorageError::Db(e.to_string()))?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn contents_purge_trashed(
+        &self,
+        params: TrashPurgeParams,
+    ) -> Result<Vec<ContentRow>, SqlStorageError> {
+        let recs = sqlx::query!(
+            r#"
+            DELETE FROM contents
+            WHERE id IN (
+                SELECT id
+                FROM contents
+                WHERE status = 'trashed'
+                  AND ($1::uuid IS NULL OR user_id = $1)
+                  AND ($2::timestamptz IS NULL OR trashed_at < $2)
+                  AND ($4::uuid[] IS NULL OR id = ANY($4))
+                ORDER BY trashed_at
+                LIMIT $3
+                FOR UPDATE SKIP LOCKED
+            )
+            RETURNING
+                id, user_id, title, description,
+                storage_backend, storage_profile, storage_key,
+                content_type, file_size, status, visibility,
+                kind, body, sha256, metadata,
+                trashed_at, archived_at, created_at, updated_at
+            "#,
+            params.user_id,
+            params.trashed_before,
+            params.limit,
+            params.ids.as_deref()
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| SqlStorageError::Db(e.to_string()))?;
+
+        Ok(recs
+            .into_iter()
+            .map(|rec| ContentRow {
+                id: rec.id,
+                user_id: rec.user_id,
+                title: rec.title,
+                description: rec.description,
+                storage_backend: rec.storage_backend,
+                storage_profile: rec.storage_profile,
+                storage_key: rec.storage_key,
+                content_type: rec.content_type,
+                file_size: rec.file_size,
+                status: rec.status,
+                visibility: rec.visibility,
+                kind: rec.kind,
+                body: rec.body,
+                sha256: rec.sha256,
+                metadata: rec.metadata,
+                trashed_at: rec.trashed_at,
+                archived_at: rec.archived_at,
+                created_at: rec.created_at,
+                updated_at: rec.updated_at,
+            })
+            .collect())
+    }
+
+    async fn content_revisions_list(
+        &self,
+        content_id: uuid::Uuid,
+    ) -> Result<Vec<ContentRevisionRow>, SqlStorageError> {
+        sqlx::query_as!(
+            ContentRevisionRow,
+            r#"
+            SELECT id, content_id, revision, title, description, body, created_at
+            FROM content_revisions
+            WHERE content_id = $1
+            ORDER BY revision DESC
+            "#,
+            content_id
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| SqlStorageError::Db(e.to_string()))
+    }
+
+    async fn content_revisions_get(
+        &self,
+        content_id: uuid::Uuid,
+        revision: i32,
+    ) -> Result<Option<ContentRevisionRow>, SqlStorageError> {
+        sqlx::query_as!(
+            ContentRevisionRow,
+            r#"
+            SELECT id, content_id, revision, title, description, body, created_at
+            FROM content_revisions
+            WHERE content_id = $1 AND revision = $2
+            "#,
+            content_id,
+            revision
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| SqlStorageError::Db(e.to_string()))
+    }
+
     async fn groups_create(&self, input: GroupCreate) -> Result<ContentGroupRow, SqlStorageError> {
         let rec = sqlx::query!(
             r#"
@@ -1172,6 +2515,7 @@ impl SqlStorage for PgStorage {
                 END,
                 visibility = COALESCE($6, visibility)
             WHERE id = $1 AND user_id = $2
+              AND ($7::timestamptz IS NULL OR updated_at = $7)
             RETURNING
                 id, user_id, name, description,
                 visibility, status,
@@ -1183,6 +2527,7 @@ impl SqlStorage for PgStorage {
             description_set,
             description_value,
             visibility,
+            changes.expected_updated_at,
         )
         .fetch_optional(&self.pool)
         .await
@@ -1252,6 +2597,55 @@ impl SqlStorage for PgStorage {
         }))
     }
 
+    async fn groups_purge_trashed(
+        &self,
+        params: TrashPurgeParams,
+    ) -> Result<Vec<ContentGroupRow>, SqlStorageError> {
+        let recs = sqlx::query!(
+            r#"
+            DELETE FROM content_groups
+            WHERE id IN (
+                SELECT id
+                FROM content_groups
+                WHERE status = 'trashed'
+                  AND ($1::uuid IS NULL OR user_id = $1)
+                  AND ($2::timestamptz IS NULL OR trashed_at < $2)
+                  AND ($4::uuid[] IS NULL OR id = ANY($4))
+                ORDER BY trashed_at
+                LIMIT $3
+                FOR UPDATE SKIP LOCKED
+            )
+            RETURNING
+                id, user_id, name, description,
+                visibility, status,
+                trashed_at, archived_at, created_at, updated_at
+            "#,
+            params.user_id,
+            params.trashed_before,
+            params.limit,
+            params.ids.as_deref()
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| SqlStorageError::Db(e.to_string()))?;
+
+        Ok(recs
+            .into_iter()
+            .map(|rec| ContentGroupRow {
+                id: rec.id,
+                user_id: rec.user_id,
+                name: rec.name,
+                description: rec.description,
+                visibility: rec.visibility,
+                status: rec.status,
+                trashed_at: rec.trashed_at,
+                archived_at: rec.archived_at,
+                created_at: rec.created_at,
+                updated_at: rec.updated_at,
+            })
+            .collect())
+    }
+
     async fn group_items_add(
         &self,
         group_id: uuid::Uuid,
@@ -1372,7 +2766,7 @@ impl SqlStorage for PgStorage {
             r#"
             INSERT INTO tags (user_id, name, color)
             VALUES ($1,$2,$3)
-            RETURNING id, user_id, name, color, created_at
+            RETURNING id, user_id, name, color, created_at, updated_at
             "#,
             input.user_id,
             input.name,
@@ -1395,6 +2789,7 @@ impl SqlStorage for PgStorage {
             name: rec.name,
             color: rec.color,
             created_at: rec.created_at,
+            updated_at: rec.updated_at,
         })
     }
 
@@ -1404,7 +2799,7 @@ impl SqlStorage for PgStorage {
     ) -> Result<Vec<TagRow>, SqlStorageError> {
         let recs = sqlx::query!(
             r#"
-            SELECT id, user_id, name, color, created_at
+            SELECT id, user_id, name, color, created_at, updated_at
             FROM tags
             WHERE user_id = $1
             ORDER BY created_at DESC
@@ -1423,22 +2818,54 @@ impl SqlStorage for PgStorage {
                 name: rec.name,
                 color: rec.color,
                 created_at: rec.created_at,
+                updated_at: rec.updated_at,
             })
             .collect())
     }
 
+    async fn tags_get(
+        &self,
+        user_id: uuid::Uuid,
+        tag_id: uuid::Uuid,
+    ) -> Result<Option<TagRow>, SqlStorageError> {
+        let rec = sqlx::query!(
+            r#"
+            SELECT id, user_id, name, color, created_at, updated_at
+            FROM tags
+            WHERE id = $1 AND user_id = $2
+            "#,
+            tag_id,
+            user_id
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| SqlStorageError::Db(e.to_string()))?;
+
+        Ok(rec.map(|rec| TagRow {
+            id: rec.id,
+            user_id: rec.user_id,
+            name: rec.name,
+            color: rec.color,
+            created_at: rec.created_at,
+            updated_at: rec.updated_at,
+        }))
+    }
+
     async fn tags_delete(
         &self,
         user_id: uuid::Uuid,
         tag_id: uuid::Uuid,
+        expected_updated_at: Option<chrono::DateTime<chrono::Utc>>,
     ) -> Result<bool, SqlStorageError> {
         let res = sqlx::query!(
             r#"
             DELETE FROM tags
             WHERE id = $1 AND user_id = $2
+              AND ($3::timestamptz IS NULL OR updated_at = $3)
             "#,
             tag_id,
-            user_id
+            user_id,
+            expected_updated_at
         )
         .execute(&self.pool)
         .await
@@ -1461,13 +2888,15 @@ impl SqlStorage for PgStorage {
                 name = COALESCE($3, name),
                 color = CASE WHEN $4 THEN $5 ELSE color END
             WHERE id = $1 AND user_id = $2
-            RETURNING id, user_id, name, color, created_at
+              AND ($6::timestamptz IS NULL OR updated_at = $6)
+            RETURNING id, user_id, name, color, created_at, updated_at
             "#,
             tag_id,
             user_id,
             input.name,
             input.color.is_some(), // $4: whether to update color
             input.color.flatten(), // $5: the new color value (can be NULL)
+            input.expected_updated_at,
         )
         .fetch_optional(&self.pool)
         .await
@@ -1486,6 +2915,7 @@ impl SqlStorage for PgStorage {
             name: rec.name,
             color: rec.color,
             created_at: rec.created_at,
+            updated_at: rec.updated_at,
         }))
     }
 
@@ -1536,7 +2966,7 @@ impl SqlStorage for PgStorage {
     ) -> Result<Vec<TagRow>, SqlStorageError> {
         let recs = sqlx::query!(
             r#"
-            SELECT t.id, t.user_id, t.name, t.color, t.created_at
+            SELECT t.id, t.user_id, t.name, t.color, t.created_at, t.updated_at
             FROM content_tags ct
             JOIN tags t ON t.id = ct.tag_id
             WHERE ct.content_id = $1
@@ -1556,6 +2986,7 @@ impl SqlStorage for PgStorage {
                 name: rec.name,
                 color: rec.color,
                 created_at: rec.created_at,
+                updated_at: rec.updated_at,
             })
             .collect())
     }
@@ -1573,7 +3004,7 @@ impl SqlStorage for PgStorage {
             VALUES ($1,$2,$3,$4,$5,$6,$7,true)
             RETURNING
                 id, owner_id, token, name, permission, password_hash,
-                max_access_count, access_count, expires_at, is_active, created_at
+                max_access_count, access_count, expires_at, is_active, created_at, updated_at
             "#,
             input.owner_id,
             input.token,
@@ -1599,6 +3030,7 @@ impl SqlStorage for PgStorage {
             expires_at: rec.expires_at,
             is_active: rec.is_active,
             created_at: rec.created_at,
+            updated_at: rec.updated_at,
         })
     }
 
@@ -1610,7 +3042,7 @@ impl SqlStorage for PgStorage {
             r#"
             SELECT
                 id, owner_id, token, name, permission, password_hash,
-                max_access_count, access_count, expires_at, is_active, created_at
+                max_access_count, access_count, expires_at, is_active, created_at, updated_at
             FROM share_links
             WHERE token = $1
             "#,
@@ -1632,6 +3064,7 @@ impl SqlStorage for PgStorage {
             expires_at: rec.expires_at,
             is_active: rec.is_active,
             created_at: rec.created_at,
+            updated_at: rec.updated_at,
         }))
     }
 
@@ -1643,7 +3076,7 @@ impl SqlStorage for PgStorage {
             r#"
             SELECT
                 id, owner_id, token, name, permission, password_hash,
-                max_access_count, access_count, expires_at, is_active, created_at
+                max_access_count, access_count, expires_at, is_active, created_at, updated_at
             FROM share_links
             WHERE owner_id = $1
             ORDER BY created_at DESC
@@ -1668,6 +3101,7 @@ impl SqlStorage for PgStorage {
                 expires_at: rec.expires_at,
                 is_active: rec.is_active,
                 created_at: rec.created_at,
+                updated_at: rec.updated_at,
             })
             .collect())
     }
@@ -1680,7 +3114,7 @@ impl SqlStorage for PgStorage {
         let res = sqlx::query!(
             r#"
             UPDATE share_links
-            SET is_active = false
+            SET is_active = false, updated_at = now()
             WHERE id = $1 AND owner_id = $2
             "#,
             share_link_id,
@@ -1702,7 +3136,7 @@ impl SqlStorage for PgStorage {
             r#"
             SELECT
                 id, owner_id, token, name, permission, password_hash,
-                max_access_count, access_count, expires_at, is_active, created_at
+                max_access_count, access_count, expires_at, is_active, created_at, updated_at
             FROM share_links
             WHERE id = $1 AND owner_id = $2
             "#,
@@ -1725,6 +3159,7 @@ impl SqlStorage for PgStorage {
             expires_at: rec.expires_at,
             is_active: rec.is_active,
             created_at: rec.created_at,
+            updated_at: rec.updated_at,
         }))
     }
 
@@ -1745,11 +3180,13 @@ impl SqlStorage for PgStorage {
                 password_hash = CASE WHEN $7 THEN $8 ELSE password_hash END,
                 expires_at = CASE WHEN $9 THEN $10 ELSE expires_at END,
                 max_access_count = CASE WHEN $11 THEN $12 ELSE max_access_count END,
-                is_active = CASE WHEN $13 THEN $14 ELSE is_active END
+                is_active = CASE WHEN $13 THEN $14 ELSE is_active END,
+                updated_at = now()
             WHERE id = $1 AND owner_id = $2
+              AND ($15::timestamptz IS NULL OR updated_at = $15)
             RETURNING
                 id, owner_id, token, name, permission, password_hash,
-                max_access_count, access_count, expires_at, is_active, created_at
+                max_access_count, access_count, expires_at, is_active, created_at, updated_at
             "#,
             id,
             owner_id,
@@ -1765,6 +3202,7 @@ impl SqlStorage for PgStorage {
             input.max_access_count.flatten(),
             input.is_active.is_some(),
             input.is_active.unwrap_or(true),
+            input.expected_updated_at,
         )
         .fetch_optional(&self.pool)
         .await
@@ -1782,6 +3220,7 @@ impl SqlStorage for PgStorage {
             expires_at: rec.expires_at,
             is_active: rec.is_active,
             created_at: rec.created_at,
+            updated_at: rec.updated_at,
         }))
     }
 
@@ -1789,14 +3228,17 @@ impl SqlStorage for PgStorage {
         &self,
         id: uuid::Uuid,
         owner_id: uuid::Uuid,
+        expected_updated_at: Option<chrono::DateTime<chrono::Utc>>,
     ) -> Result<bool, SqlStorageError> {
         let res = sqlx::query!(
             r#"
             DELETE FROM share_links
             WHERE id = $1 AND owner_id = $2
+              AND ($3::timestamptz IS NULL OR updated_at = $3)
             "#,
             id,
-            owner_id
+            owner_id,
+            expected_updated_at
         )
         .execute(&self.pool)
         .await
@@ -1821,25 +3263,232 @@ impl SqlStorage for PgStorage {
         Ok(())
     }
 
-    async fn content_shares_attach_link(
+    async fn share_links_rehash_password(
         &self,
-        content_id: uuid::Uuid,
-        share_link_id: uuid::Uuid,
-        created_by: uuid::Uuid,
+        id: uuid::Uuid,
+        current_hash: &str,
+        new_hash: &str,
     ) -> Result<(), SqlStorageError> {
-        // Get the permission from the share link
-        let share_link = sqlx::query!(
+        sqlx::query!(
             r#"
-            SELECT permission FROM share_links WHERE id = $1
+            UPDATE share_links
+            SET password_hash = $3
+            WHERE id = $1 AND password_hash = $2
             "#,
-            share_link_id
+            id,
+            current_hash,
+            new_hash
         )
-        .fetch_optional(&self.pool)
+        .execute(&self.pool)
         .await
-        .map_err(|e| SqlStorageError::Db(e.to_string()))?
-        .ok_or(SqlStorageError::NotFound)?;
+        .map_err(|e| SqlStorageError::Db(e.to_string()))?;
 
-        sqlx::query!(
+        Ok(())
+    }
+
+    async fn share_link_accesses_insert(
+        &self,
+        input: ShareLinkAccessInsert,
+    ) -> Result<(), SqlStorageError> {
+        let ip_net: Option<IpNet> = input.ip_address.map(IpNet::from);
+
+        sqlx::query!(
+            r#"
+            INSERT INTO share_link_accesses (share_link_id, ip_address, user_agent, password_supplied)
+            VALUES ($1, $2, $3, $4)
+            "#,
+            input.share_link_id,
+            ip_net as Option<IpNet>,
+            input.user_agent,
+            input.password_supplied
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|e| SqlStorageError::Db(e.to_string()))?;
+
+        Ok(())
+    }
+
+    async fn share_link_accesses_stats(
+        &self,
+        share_link_id: uuid::Uuid,
+        since: chrono::DateTime<chrono::Utc>,
+    ) -> Result<ShareLinkAccessStats, SqlStorageError> {
+        let totals = sqlx::query!(
+            r#"
+            SELECT COUNT(*) as "total_accesses!",
+                   COUNT(DISTINCT (ip_address, user_agent)) as "unique_visitors!",
+                   MAX(accessed_at) as last_accessed_at
+            FROM share_link_accesses
+            WHERE share_link_id = $1
+            "#,
+            share_link_id
+        )
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| SqlStorageError::Db(e.to_string()))?;
+
+        let daily = sqlx::query!(
+            r#"
+            SELECT (accessed_at AT TIME ZONE 'UTC')::date as "day!",
+                   COUNT(*) as "accesses!",
+                   COUNT(DISTINCT (ip_address, user_agent)) as "unique_visitors!"
+            FROM share_link_accesses
+            WHERE share_link_id = $1 AND accessed_at >= $2
+            GROUP BY 1
+            ORDER BY 1
+            "#,
+            share_link_id,
+            since
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| SqlStorageError::Db(e.to_string()))?;
+
+        Ok(ShareLinkAccessStats {
+            total_accesses: totals.total_accesses,
+            unique_visitors: totals.unique_visitors,
+            last_accessed_at: totals.last_accessed_at,
+            daily: daily
+                .into_iter()
+                .map(|rec| ShareLinkAccessDay {
+                    day: rec.day,
+                    accesses: rec.accesses,
+                    unique_visitors: rec.unique_visitors,
+                })
+                .collect(),
+        })
+    }
+
+    async fn share_link_password_reserve_attempt(
+        &self,
+        share_link_id: uuid::Uuid,
+        ip_address: Option<IpAddr>,
+        config: &ShareLinkPasswordRateLimitConfig,
+    ) -> Result<Option<uuid::Uuid>, SqlStorageError> {
+        let ip_net: Option<IpNet> = ip_address.map(IpNet::from);
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .map_err(|e| SqlStorageError::Db(e.to_string()))?;
+
+        // Serialize attempts on the link, then on the IP address; the locks
+        // are always taken in this order
+        let locked = sqlx::query_scalar!(
+            r#"SELECT id FROM share_links WHERE id = $1 FOR UPDATE"#,
+            share_link_id
+        )
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(|e| SqlStorageError::Db(e.to_string()))?;
+        if locked.is_none() {
+            return Err(SqlStorageError::NotFound);
+        }
+        if let Some(ip_net) = &ip_net {
+            sqlx::query!(
+                r#"
+                SELECT pg_advisory_xact_lock(
+                    hashtextextended('share_link_password:' || $1::inet::text, 0)
+                )
+                "#,
+                ip_net as &IpNet
+            )
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| SqlStorageError::Db(e.to_string()))?;
+        }
+
+        let counts = sqlx::query!(
+            r#"
+            SELECT
+                COUNT(*) FILTER (WHERE share_link_id = $1) AS "link!",
+                COUNT(*) FILTER (WHERE ip_address = $2) AS "ip!"
+            FROM share_link_password_attempts
+            WHERE (share_link_id = $1 OR ip_address = $2)
+              AND success = false
+              AND attempted_at > now() - ($3 || ' seconds')::INTERVAL
+            "#,
+            share_link_id,
+            ip_net as Option<IpNet>,
+            config.window_seconds.to_string()
+        )
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(|e| SqlStorageError::Db(e.to_string()))?;
+
+        if counts.link >= config.max_attempts_per_link {
+            tracing::warn!(
+                share_link_id = %share_link_id,
+                count = %counts.link,
+                max = %config.max_attempts_per_link,
+                "Share link password rate limit exceeded for link"
+            );
+            return Ok(None);
+        }
+        if ip_address.is_some() && counts.ip >= config.max_attempts_per_ip {
+            tracing::warn!(
+                ip = ?ip_address,
+                count = %counts.ip,
+                max = %config.max_attempts_per_ip,
+                "Share link password rate limit exceeded for IP address"
+            );
+            return Ok(None);
+        }
+
+        let id = sqlx::query_scalar!(
+            r#"
+            INSERT INTO share_link_password_attempts (share_link_id, success, ip_address)
+            VALUES ($1, false, $2)
+            RETURNING id
+            "#,
+            share_link_id,
+            ip_net as Option<IpNet>
+        )
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(|e| SqlStorageError::Db(e.to_string()))?;
+
+        tx.commit()
+            .await
+            .map_err(|e| SqlStorageError::Db(e.to_string()))?;
+        Ok(Some(id))
+    }
+
+    async fn share_link_password_attempt_succeeded(
+        &self,
+        attempt_id: uuid::Uuid,
+    ) -> Result<(), SqlStorageError> {
+        sqlx::query!(
+            r#"UPDATE share_link_password_attempts SET success = true WHERE id = $1"#,
+            attempt_id
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|e| SqlStorageError::Db(e.to_string()))?;
+
+        Ok(())
+    }
+
+    async fn content_shares_attach_link(
+        &self,
+        content_id: uuid::Uuid,
+        share_link_id: uuid::Uuid,
+        created_by: uuid::Uuid,
+    ) -> Result<(), SqlStorageError> {
+        // Get the permission from the share link
+        let share_link = sqlx::query!(
+            r#"
+            SELECT permission FROM share_links WHERE id = $1
+            "#,
+            share_link_id
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| SqlStorageError::Db(e.to_string()))?
+        .ok_or(SqlStorageError::NotFound)?;
+
+        sqlx::query!(
             r#"
             INSERT INTO content_shares (content_id, share_link_id, permission, created_by)
             VALUES ($1, $2, $3, $4)
@@ -1891,6 +3540,39 @@ impl SqlStorage for PgStorage {
         Ok(())
     }
 
+    async fn share_links_targets_for_owner(
+        &self,
+        owner_id: uuid::Uuid,
+    ) -> Result<Vec<ShareLinkTargetRow>, SqlStorageError> {
+        let recs = sqlx::query!(
+            r#"
+            SELECT cs.share_link_id as "share_link_id!", cs.content_id as "content_id?",
+                   NULL::uuid as group_id
+            FROM content_shares cs
+            JOIN share_links sl ON sl.id = cs.share_link_id
+            WHERE sl.owner_id = $1
+            UNION ALL
+            SELECT cgs.share_link_id, NULL::uuid, cgs.group_id
+            FROM content_group_shares cgs
+            JOIN share_links sl ON sl.id = cgs.share_link_id
+            WHERE sl.owner_id = $1
+            "#,
+            owner_id
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| SqlStorageError::Db(e.to_string()))?;
+
+        Ok(recs
+            .into_iter()
+            .map(|rec| ShareLinkTargetRow {
+                share_link_id: rec.share_link_id,
+                content_id: rec.content_id,
+                group_id: rec.group_id,
+            })
+            .collect())
+    }
+
     async fn contents_get_by_share_token(
         &self,
         token: &str,
@@ -1911,6 +3593,8 @@ impl SqlStorage for PgStorage {
                 c.visibility,
                 c.kind,
                 c.body,
+                c.sha256,
+                c.metadata,
                 c.trashed_at,
                 c.archived_at,
                 c.created_at as content_created_at,
@@ -1925,7 +3609,8 @@ impl SqlStorage for PgStorage {
                 sl.access_count,
                 sl.expires_at,
                 sl.is_active,
-                sl.created_at as share_link_created_at
+                sl.created_at as share_link_created_at,
+                sl.updated_at as share_link_updated_at
             FROM share_links sl
             JOIN content_shares cs ON cs.share_link_id = sl.id
             JOIN contents c ON c.id = cs.content_id
@@ -1952,6 +3637,8 @@ impl SqlStorage for PgStorage {
                 visibility: rec.visibility,
                 kind: rec.kind,
                 body: rec.body,
+                sha256: rec.sha256,
+                metadata: rec.metadata,
                 trashed_at: rec.trashed_at,
                 archived_at: rec.archived_at,
                 created_at: rec.content_created_at,
@@ -1969,6 +3656,7 @@ impl SqlStorage for PgStorage {
                 expires_at: rec.expires_at,
                 is_active: rec.is_active,
                 created_at: rec.share_link_created_at,
+                updated_at: rec.share_link_updated_at,
             };
             (content, share_link)
         }))
@@ -2002,6 +3690,7 @@ impl SqlStorage for PgStorage {
                 sl.expires_at,
                 sl.is_active,
                 sl.created_at as share_link_created_at,
+                sl.updated_at as share_link_updated_at,
                 (SELECT COUNT(*) FROM content_group_items WHERE group_id = g.id) as "file_count!"
             FROM share_links sl
             JOIN content_group_shares cgs ON cgs.share_link_id = sl.id
@@ -2039,6 +3728,7 @@ impl SqlStorage for PgStorage {
                 expires_at: rec.expires_at,
                 is_active: rec.is_active,
                 created_at: rec.share_link_created_at,
+                updated_at: rec.share_link_updated_at,
             };
             (group, share_link, rec.file_count)
         }))
@@ -2054,6 +3744,8 @@ impl SqlStorage for PgStorage {
                 content_id, shared_with_user_id, share_link_id, permission, created_by
             )
             VALUES ($1,$2,NULL,$3,$4)
+            ON CONFLICT (content_id, shared_with_user_id) WHERE shared_with_user_id IS NOT NULL
+            DO UPDATE SET permission = EXCLUDED.permission
             RETURNING id, content_id, shared_with_user_id, share_link_id, permission, created_at, created_by
             "#,
             input.content_id,
@@ -2118,6 +3810,8 @@ impl SqlStorage for PgStorage {
                 group_id, shared_with_user_id, share_link_id, permission, created_by
             )
             VALUES ($1,$2,NULL,$3,$4)
+            ON CONFLICT (group_id, shared_with_user_id) WHERE shared_with_user_id IS NOT NULL
+            DO UPDATE SET permission = EXCLUDED.permission
             RETURNING id, group_id, shared_with_user_id, share_link_id, permission, created_at, created_by
             "#,
             input.group_id,
@@ -2172,17 +3866,207 @@ impl SqlStorage for PgStorage {
         })
     }
 
+    async fn content_shares_list_for_users(
+        &self,
+        content_id: uuid::Uuid,
+    ) -> Result<Vec<UserShareRow>, SqlStorageError> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT s.id, s.shared_with_user_id AS "shared_with_user_id!", u.username,
+                   s.permission, s.created_at
+            FROM content_shares s
+            JOIN users u ON u.id = s.shared_with_user_id
+            WHERE s.content_id = $1
+            ORDER BY s.created_at, s.id
+            "#,
+            content_id
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| SqlStorageError::Db(e.to_string()))?;
+
+        Ok(rows
+            .into_iter()
+            .map(|rec| UserShareRow {
+                id: rec.id,
+                shared_with_user_id: rec.shared_with_user_id,
+                shared_with_username: rec.username,
+                permission: rec.permission,
+                created_at: rec.created_at,
+            })
+            .collect())
+    }
+
+    async fn group_shares_list_for_users(
+        &self,
+        group_id: uuid::Uuid,
+    ) -> Result<Vec<UserShareRow>, SqlStorageError> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT s.id, s.shared_with_user_id AS "shared_with_user_id!", u.username,
+                   s.permission, s.created_at
+            FROM content_group_shares s
+            JOIN users u ON u.id = s.shared_with_user_id
+            WHERE s.group_id = $1
+            ORDER BY s.created_at, s.id
+            "#,
+            group_id
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| SqlStorageError::Db(e.to_string()))?;
+
+        Ok(rows
+            .into_iter()
+            .map(|rec| UserShareRow {
+                id: rec.id,
+                shared_with_user_id: rec.shared_with_user_id,
+                shared_with_username: rec.username,
+                permission: rec.permission,
+                created_at: rec.created_at,
+            })
+            .collect())
+    }
+
+    async fn content_shares_delete_for_user(
+        &self,
+        content_id: uuid::Uuid,
+        shared_with_user_id: uuid::Uuid,
+    ) -> Result<bool, SqlStorageError> {
+        let result = sqlx::query!(
+            r#"
+            DELETE FROM content_shares
+            WHERE content_id = $1 AND shared_with_user_id = $2
+            "#,
+            content_id,
+            shared_with_user_id
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|e| SqlStorageError::Db(e.to_string()))?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn group_shares_delete_for_user(
+        &self,
+        group_id: uuid::Uuid,
+        shared_with_user_id: uuid::Uuid,
+    ) -> Result<bool, SqlStorageError> {
+        let result = sqlx::query!(
+            r#"
+            DELETE FROM content_group_shares
+            WHERE group_id = $1 AND shared_with_user_id = $2
+            "#,
+            group_id,
+            shared_with_user_id
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|e| SqlStorageError::Db(e.to_string()))?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn shares_list_shared_with_user(
+        &self,
+        user_id: uuid::Uuid,
+    ) -> Result<Vec<SharedWithUserRow>, SqlStorageError> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT
+                'content' AS "item_type!",
+                c.id AS "item_id!",
+                c.title AS "title!",
+                c.user_id AS "owner_id!",
+                u.username AS "owner_username!",
+                s.permission AS "permission!",
+                s.created_at AS "shared_at!"
+            FROM content_shares s
+            JOIN contents c ON c.id = s.content_id
+            JOIN users u ON u.id = c.user_id
+            WHERE s.shared_with_user_id = $1 AND c.status <> 'trashed'
+            UNION ALL
+            SELECT 'group', g.id, g.name, g.user_id, u.username, s.permission, s.created_at
+            FROM content_group_shares s
+            JOIN content_groups g ON g.id = s.group_id
+            JOIN users u ON u.id = g.user_id
+            WHERE s.shared_with_user_id = $1 AND g.status <> 'trashed'
+            ORDER BY 7 DESC, 2
+            "#,
+            user_id
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| SqlStorageError::Db(e.to_string()))?;
+
+        Ok(rows
+            .into_iter()
+            .map(|rec| SharedWithUserRow {
+                item_type: rec.item_type,
+                item_id: rec.item_id,
+                title: rec.title,
+                owner_id: rec.owner_id,
+                owner_username: rec.owner_username,
+                permission: rec.permission,
+                shared_at: rec.shared_at,
+            })
+            .collect())
+    }
+
+    async fn content_shares_permission_for_user(
+        &self,
+        content_id: uuid::Uuid,
+        user_id: uuid::Uuid,
+    ) -> Result<Option<String>, SqlStorageError> {
+        let permission = sqlx::query_scalar!(
+            r#"
+            SELECT p.permission AS "permission!"
+            FROM (
+                SELECT permission
+                FROM content_shares
+                WHERE content_id = $1 AND shared_with_user_id = $2
+                UNION ALL
+                SELECT gs.permission
+                FROM content_group_shares gs
+                JOIN content_group_items gi ON gi.group_id = gs.group_id
+                JOIN content_groups g ON g.id = gs.group_id
+                WHERE gi.content_id = $1 AND gs.shared_with_user_id = $2
+                  AND g.status <> 'trashed'
+            ) p
+            ORDER BY p.permission = 'download' DESC
+            LIMIT 1
+            "#,
+            content_id,
+            user_id
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| SqlStorageError::Db(e.to_string()))?;
+
+        Ok(permission)
+    }
+
     async fn uploads_create(&self, input: UploadInsert) -> Result<UploadRow, SqlStorageError> {
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .map_err(|e| SqlStorageError::Db(e.to_string()))?;
+
+        Self::reserve_quota(&mut tx, input.user_id, input.file_size).await?;
+
         let rec = sqlx::query!(
             r#"
             INSERT INTO uploads (
                 user_id, storage_backend, storage_profile, storage_key,
-                content_type, file_size, expires_at
+                content_type, file_size, expires_at, multipart_upload_id, part_size, sha256
             )
-            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
             RETURNING
                 id, user_id, storage_backend, storage_profile, storage_key,
-                content_type, file_size, status, expires_at, created_at, completed_at
+                content_type, file_size, status, expires_at, created_at, completed_at,
+                multipart_upload_id, part_size, sha256
             "#,
             input.user_id,
             input.storage_backend,
@@ -2191,11 +4075,18 @@ impl SqlStorage for PgStorage {
             input.content_type,
             input.file_size,
             input.expires_at,
+            input.multipart_upload_id,
+            input.part_size,
+            input.sha256,
         )
-        .fetch_one(&self.pool)
+        .fetch_one(&mut *tx)
         .await
         .map_err(|e| SqlStorageError::Db(e.to_string()))?;
 
+        tx.commit()
+            .await
+            .map_err(|e| SqlStorageError::Db(e.to_string()))?;
+
         Ok(UploadRow {
             id: rec.id,
             user_id: rec.user_id,
@@ -2208,6 +4099,9 @@ impl SqlStorage for PgStorage {
             expires_at: rec.expires_at,
             created_at: rec.created_at,
             completed_at: rec.completed_at,
+            multipart_upload_id: rec.multipart_upload_id,
+            part_size: rec.part_size,
+            sha256: rec.sha256,
         })
     }
 
@@ -2216,7 +4110,8 @@ impl SqlStorage for PgStorage {
             r#"
             SELECT
                 id, user_id, storage_backend, storage_profile, storage_key,
-                content_type, file_size, status, expires_at, created_at, completed_at
+                content_type, file_size, status, expires_at, created_at, completed_at,
+                multipart_upload_id, part_size, sha256
             FROM uploads
             WHERE id = $1
             "#,
@@ -2238,6 +4133,9 @@ impl SqlStorage for PgStorage {
             expires_at: r.expires_at,
             created_at: r.created_at,
             completed_at: r.completed_at,
+            multipart_upload_id: r.multipart_upload_id,
+            part_size: r.part_size,
+            sha256: r.sha256,
         }))
     }
 
@@ -2282,7 +4180,8 @@ impl SqlStorage for PgStorage {
             WHERE id = $1 AND user_id = $2
             RETURNING
                 id, user_id, storage_backend, storage_profile, storage_key,
-                content_type, file_size, status, expires_at, created_at, completed_at
+                content_type, file_size, status, expires_at, created_at, completed_at,
+                multipart_upload_id, part_size, sha256
             "#,
             id,
             user_id
@@ -2303,9 +4202,589 @@ impl SqlStorage for PgStorage {
             expires_at: r.expires_at,
             created_at: r.created_at,
             completed_at: r.completed_at,
+            multipart_upload_id: r.multipart_upload_id,
+            part_size: r.part_size,
+            sha256: r.sha256,
         }))
     }
 
+    async fn uploads_abort(
+        &self,
+        id: uuid::Uuid,
+        user_id: uuid::Uuid,
+    ) -> Result<Option<UploadRow>, SqlStorageError> {
+        let existing = sqlx::query!(
+            r#"
+            SELECT user_id, status
+            FROM uploads
+            WHERE id = $1
+            "#,
+            id
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| SqlStorageError::Db(e.to_string()))?;
+
+        let Some(existing) = existing else {
+            return Ok(None);
+        };
+
+        if existing.user_id != user_id {
+            return Err(SqlStorageError::Unauthorized);
+        }
+
+        if existing.status != "initiated" {
+            return Err(SqlStorageError::Invalid(format!(
+                "Upload status is '{}', expected 'initiated'",
+                existing.status
+            )));
+        }
+
+        let rec = sqlx::query!(
+            r#"
+            UPDATE uploads
+            SET status = 'aborted'
+            WHERE id = $1 AND user_id = $2
+            RETURNING
+                id, user_id, storage_backend, storage_profile, storage_key,
+                content_type, file_size, status, expires_at, created_at, completed_at,
+                multipart_upload_id, part_size, sha256
+            "#,
+            id,
+            user_id
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| SqlStorageError::Db(e.to_string()))?;
+
+        Ok(rec.map(|r| UploadRow {
+            id: r.id,
+            user_id: r.user_id,
+            storage_backend: r.storage_backend,
+            storage_profile: r.storage_profile,
+            storage_key: r.storage_key,
+            content_type: r.content_type,
+            file_size: r.file_size,
+            status: r.status,
+            expires_at: r.expires_at,
+            created_at: r.created_at,
+            completed_at: r.completed_at,
+            multipart_upload_id: r.multipart_upload_id,
+            part_size: r.part_size,
+            sha256: r.sha256,
+        }))
+    }
+
+    async fn uploads_expire_stale(
+        &self,
+        now: chrono::DateTime<chrono::Utc>,
+        limit: i64,
+    ) -> Result<Vec<UploadRow>, SqlStorageError> {
+        let rows = sqlx::query!(
+            r#"
+            UPDATE uploads
+            SET status = 'expired'
+            WHERE id IN (
+                SELECT id
+                FROM uploads
+                WHERE status = 'initiated' AND expires_at < $1
+                ORDER BY expires_at
+                LIMIT $2
+                FOR UPDATE SKIP LOCKED
+            )
+            RETURNING
+                id, user_id, storage_backend, storage_profile, storage_key,
+                content_type, file_size, status, expires_at, created_at, completed_at,
+                multipart_upload_id, part_size, sha256
+            "#,
+            now,
+            limit
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| SqlStorageError::Db(e.to_string()))?;
+
+        Ok(rows
+            .into_iter()
+            .map(|r| UploadRow {
+                id: r.id,
+                user_id: r.user_id,
+                storage_backend: r.storage_backend,
+                storage_profile: r.storage_profile,
+                storage_key: r.storage_key,
+                content_type: r.content_type,
+                file_size: r.file_size,
+                status: r.status,
+                expires_at: r.expires_at,
+                created_at: r.created_at,
+                completed_at: r.completed_at,
+                multipart_upload_id: r.multipart_upload_id,
+                part_size: r.part_size,
+                sha256: r.sha256,
+            })
+            .collect())
+    }
+
+    async fn upload_parts_create(
+        &self,
+        upload_id: uuid::Uuid,
+        parts: Vec<UploadPartInsert>,
+    ) -> Result<(), SqlStorageError> {
+        let (part_numbers, sizes): (Vec<i32>, Vec<i64>) =
+            parts.into_iter().map(|p| (p.part_number, p.size)).unzip();
+
+        sqlx::query!(
+            r#"
+            INSERT INTO upload_parts (upload_id, part_number, size)
+            SELECT $1, part_number, size
+            FROM UNNEST($2::INTEGER[], $3::BIGINT[]) AS t(part_number, size)
+            "#,
+            upload_id,
+            &part_numbers,
+            &sizes,
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|e| SqlStorageError::Db(e.to_string()))?;
+
+        Ok(())
+    }
+
+    async fn upload_parts_list(
+        &self,
+        upload_id: uuid::Uuid,
+    ) -> Result<Vec<UploadPartRow>, SqlStorageError> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT upload_id, part_number, size, etag, status, created_at, updated_at
+            FROM upload_parts
+            WHERE upload_id = $1
+            ORDER BY part_number
+            "#,
+            upload_id
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| SqlStorageError::Db(e.to_string()))?;
+
+        Ok(rows
+            .into_iter()
+            .map(|r| UploadPartRow {
+                upload_id: r.upload_id,
+                part_number: r.part_number,
+                size: r.size,
+                etag: r.etag,
+                status: r.status,
+                created_at: r.created_at,
+                updated_at: r.updated_at,
+            })
+            .collect())
+    }
+
+    async fn upload_parts_mark_uploaded(
+        &self,
+        upload_id: uuid::Uuid,
+        part_number: i32,
+        etag: String,
+    ) -> Result<Option<UploadPartRow>, SqlStorageError> {
+        let rec = sqlx::query!(
+            r#"
+            UPDATE upload_parts
+            SET etag = $3, status = 'uploaded', updated_at = now()
+            WHERE upload_id = $1 AND part_number = $2
+            RETURNING upload_id, part_number, size, etag, status, created_at, updated_at
+            "#,
+            upload_id,
+            part_number,
+            etag
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| SqlStorageError::Db(e.to_string()))?;
+
+        Ok(rec.map(|r| UploadPartRow {
+            upload_id: r.upload_id,
+            part_number: r.part_number,
+            size: r.size,
+            etag: r.etag,
+            status: r.status,
+            created_at: r.created_at,
+            updated_at: r.updated_at,
+        }))
+    }
+
+    async fn contents_share_storage_object(
+        &self,
+        content_id: uuid::Uuid,
+        input: StorageObjectInsert,
+    ) -> Result<Option<(StorageObjectRow, ContentRow)>, SqlStorageError> {
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .map_err(|e| SqlStorageError::Db(e.to_string()))?;
+
+        // Lock the content so a concurrent call cannot take a second reference
+        let locked = sqlx::query_scalar!(
+            "SELECT id FROM contents WHERE id = $1 AND sha256 IS NULL FOR UPDATE",
+            content_id
+        )
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(|e| SqlStorageError::Db(e.to_string()))?;
+        if locked.is_none() {
+            return Ok(None);
+        }
+
+        let object = sqlx::query!(
+            r#"
+            INSERT INTO storage_objects (
+                user_id, sha256, storage_backend, storage_profile, storage_key, file_size
+            )
+            VALUES ($1, $2, $3, $4, $5, $6)
+            ON CONFLICT (user_id, sha256) DO UPDATE
+            SET ref_count = storage_objects.ref_count + 1, updated_at = now()
+            RETURNING
+                id, user_id, sha256, storage_backend, storage_profile, storage_key,
+                file_size, ref_count, created_at, updated_at
+            "#,
+            input.user_id,
+            input.sha256,
+            input.storage_backend,
+            input.storage_profile,
+            input.storage_key,
+            input.file_size,
+        )
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(|e| SqlStorageError::Db(e.to_string()))?;
+
+        let rec = sqlx::query!(
+            r#"
+            UPDATE contents
+            SET storage_backend = $2, storage_profile = $3, storage_key = $4,
+                sha256 = $5, updated_at = now()
+            WHERE id = $1
+            RETURNING
+                id, user_id, title, description,
+                storage_backend, storage_profile, storage_key,
+                content_type, file_size, status, visibility,
+                kind, body, sha256, metadata,
+                trashed_at, archived_at, created_at, updated_at
+            "#,
+            content_id,
+            object.storage_backend,
+            object.storage_profile,
+            object.storage_key,
+            object.sha256,
+        )
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(|e| SqlStorageError::Db(e.to_string()))?;
+
+        tx.commit()
+            .await
+            .map_err(|e| SqlStorageError::Db(e.to_string()))?;
+
+        Ok(Some((
+            StorageObjectRow {
+                id: object.id,
+                user_id: object.user_id,
+                sha256: object.sha256,
+                storage_backend: object.storage_backend,
+                storage_profile: object.storage_profile,
+                storage_key: object.storage_key,
+                file_size: object.file_size,
+                ref_count: object.ref_count,
+                created_at: object.created_at,
+                updated_at: object.updated_at,
+            },
+            ContentRow {
+                id: rec.id,
+                user_id: rec.user_id,
+                title: rec.title,
+                description: rec.description,
+                storage_backend: rec.storage_backend,
+                storage_profile: rec.storage_profile,
+                storage_key: rec.storage_key,
+                content_type: rec.content_type,
+                file_size: rec.file_size,
+                status: rec.status,
+                visibility: rec.visibility,
+                kind: rec.kind,
+                body: rec.body,
+                sha256: rec.sha256,
+                metadata: rec.metadata,
+                trashed_at: rec.trashed_at,
+                archived_at: rec.archived_at,
+                created_at: rec.created_at,
+                updated_at: rec.updated_at,
+            },
+        )))
+    }
+
+    async fn storage_objects_release(
+        &self,
+        user_id: uuid::Uuid,
+        sha256: &str,
+    ) -> Result<Option<StorageObjectRow>, SqlStorageError> {
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .map_err(|e| SqlStorageError::Db(e.to_string()))?;
+
+        let rec = sqlx::query!(
+            r#"
+            UPDATE storage_objects
+            SET ref_count = GREATEST(ref_count - 1, 0), updated_at = now()
+            WHERE user_id = $1 AND sha256 = $2
+            RETURNING
+                id, user_id, sha256, storage_backend, storage_profile, storage_key,
+                file_size, ref_count, created_at, updated_at
+            "#,
+            user_id,
+            sha256
+        )
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(|e| SqlStorageError::Db(e.to_string()))?;
+
+        if let Some(rec) = &rec
+            && rec.ref_count == 0
+        {
+            sqlx::query!("DELETE FROM storage_objects WHERE id = $1", rec.id)
+                .execute(&mut *tx)
+                .await
+                .map_err(|e| SqlStorageError::Db(e.to_string()))?;
+        }
+
+        tx.commit()
+            .await
+            .map_err(|e| SqlStorageError::Db(e.to_string()))?;
+
+        Ok(rec.map(|rec| StorageObjectRow {
+            id: rec.id,
+            user_id: rec.user_id,
+            sha256: rec.sha256,
+            storage_backend: rec.storage_backend,
+            storage_profile: rec.storage_profile,
+            storage_key: rec.storage_key,
+            file_size: rec.file_size,
+            ref_count: rec.ref_count,
+            created_at: rec.created_at,
+            updated_at: rec.updated_at,
+        }))
+    }
+
+    async fn users_storage_usage(
+        &self,
+        user_id: uuid::Uuid,
+        now: chrono::DateTime<chrono::Utc>,
+    ) -> Result<StorageUsage, SqlStorageError> {
+        let mut conn = self
+            .pool
+            .acquire()
+            .await
+            .map_err(|e| SqlStorageError::Db(e.to_string()))?;
+        Self::storage_usage(&mut conn, user_id, now).await
+    }
+
+    async fn users_get_storage_quota(
+        &self,
+        user_id: uuid::Uuid,
+    ) -> Result<Option<i64>, SqlStorageError> {
+        let quota = sqlx::query_scalar!(
+            "SELECT storage_quota_bytes FROM users WHERE id = $1",
+            user_id
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| SqlStorageError::Db(e.to_string()))?;
+
+        Ok(quota.flatten())
+    }
+
+    async fn users_set_storage_quota(
+        &self,
+        user_id: uuid::Uuid,
+        quota_bytes: Option<i64>,
+    ) -> Result<bool, SqlStorageError> {
+        if quota_bytes.is_some_and(|quota| quota < 0) {
+            return Err(SqlStorageError::Invalid(
+                "Storage quota cannot be negative".to_owned(),
+            ));
+        }
+
+        let result = sqlx::query!(
+            r#"
+            UPDATE users
+            SET storage_quota_bytes = $2, updated_at = now()
+            WHERE id = $1
+            "#,
+            user_id,
+            quota_bytes
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|e| SqlStorageError::Db(e.to_string()))?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn account_exports_create(
+        &self,
+        user_id: uuid::Uuid,
+        expires_at: chrono::DateTime<chrono::Utc>,
+    ) -> Result<AccountExportRow, SqlStorageError> {
+        let rec = sqlx::query_as!(
+            AccountExportRow,
+            r#"
+            INSERT INTO account_exports (user_id, expires_at)
+            VALUES ($1, $2)
+            RETURNING id, user_id, status, manifest, error, created_at, completed_at, expires_at
+            "#,
+            user_id,
+            expires_at
+        )
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| SqlStorageError::Db(e.to_string()))?;
+
+        Ok(rec)
+    }
+
+    async fn account_exports_get(
+        &self,
+        id: uuid::Uuid,
+    ) -> Result<Option<AccountExportRow>, SqlStorageError> {
+        sqlx::query_as!(
+            AccountExportRow,
+            r#"
+            SELECT id, user_id, status, manifest, error, created_at, completed_at, expires_at
+            FROM account_exports
+            WHERE id = $1
+            "#,
+            id
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| SqlStorageError::Db(e.to_string()))
+    }
+
+    async fn account_exports_complete(
+        &self,
+        id: uuid::Uuid,
+        manifest: serde_json::Value,
+    ) -> Result<Option<AccountExportRow>, SqlStorageError> {
+        sqlx::query_as!(
+            AccountExportRow,
+            r#"
+            UPDATE account_exports
+            SET status = 'completed', manifest = $2, completed_at = now()
+            WHERE id = $1 AND status = 'pending'
+            RETURNING id, user_id, status, manifest, error, created_at, completed_at, expires_at
+            "#,
+            id,
+            manifest
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| SqlStorageError::Db(e.to_string()))
+    }
+
+    async fn account_exports_fail(
+        &self,
+        id: uuid::Uuid,
+        error: &str,
+    ) -> Result<Option<AccountExportRow>, SqlStorageError> {
+        sqlx::query_as!(
+            AccountExportRow,
+            r#"
+            UPDATE account_exports
+            SET status = 'failed', error = $2, completed_at = now()
+            WHERE id = $1 AND status = 'pending'
+            RETURNING id, user_id, status, manifest, error, created_at, completed_at, expires_at
+            "#,
+            id,
+            error
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| SqlStorageError::Db(e.to_string()))
+    }
+
+    async fn audit_logs_insert(&self, entry: AuditLogInsert) -> Result<(), SqlStorageError> {
+        let ip_net: Option<IpNet> = entry.ip_address.map(IpNet::from);
+
+        sqlx::query!(
+            r#"
+            INSERT INTO audit_logs (user_id, action, entity_type, entity_id, details, ip_address)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            "#,
+            entry.user_id,
+            entry.action,
+            entry.entity_type,
+            entry.entity_id,
+            entry.details,
+            ip_net as Option<IpNet>
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|e| SqlStorageError::Db(e.to_string()))?;
+
+        Ok(())
+    }
+
+    async fn audit_logs_list(
+        &self,
+        params: AuditLogsListParams,
+    ) -> Result<Vec<AuditLogRow>, SqlStorageError> {
+        let limit = if params.limit <= 0 { 50 } else { params.limit };
+        let offset = if params.offset < 0 { 0 } else { params.offset };
+
+        let recs = sqlx::query!(
+            r#"
+            SELECT id, user_id, action, entity_type, entity_id, details,
+                   ip_address as "ip_address: IpNet", created_at
+            FROM audit_logs
+            WHERE ($1::uuid IS NULL OR user_id = $1)
+              AND ($2::text IS NULL OR action = $2)
+              AND ($3::text IS NULL OR entity_type = $3)
+              AND ($4::uuid IS NULL OR entity_id = $4)
+              AND ($5::timestamptz IS NULL OR created_at >= $5)
+              AND ($6::timestamptz IS NULL OR created_at < $6)
+            ORDER BY created_at DESC, id DESC
+            LIMIT $7 OFFSET $8
+            "#,
+            params.user_id,
+            params.action,
+            params.entity_type,
+            params.entity_id,
+            params.created_after,
+            params.created_before,
+            limit,
+            offset
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| SqlStorageError::Db(e.to_string()))?;
+
+        Ok(recs
+            .into_iter()
+            .map(|rec| AuditLogRow {
+                id: rec.id,
+                user_id: rec.user_id,
+                action: rec.action,
+                entity_type: rec.entity_type,
+                entity_id: rec.entity_id,
+                details: rec.details,
+                ip_address: rec.ip_address.map(|ip| ip.addr()),
+                created_at: rec.created_at,
+            })
+            .collect())
+    }
+
     async fn otp_record_attempt(&self, input: OtpAttemptRecord) -> Result<(), SqlStorageError> {
         // Convert IpAddr to IpNet for SQLx INET type
         let ip_net: Option<IpNet> = input.ip_address.map(IpNet::from);
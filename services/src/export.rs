@@ -0,0 +1,968 @@
+//! Account data export and import.
+//!
+//! An export is a ZIP archive holding `manifest.json`, a self-describing
+//! snapshot of a user's contents, tags, groups (with their ordering) and share
+//! link settings, followed by one `blobs/{content id}` entry per file content.
+//! [`AccountExporter`] builds the manifest in the background and stores it on
+//! the export job; the archive is streamed from it when downloaded.
+//! [`AccountImporter`] recreates the data of an archive under another
+//! account, on this server or another one.
+//!
+//! Manifests carry a `version`. Fields added later get defaults so older
+//! manifests still parse; any other change bumps the version, and
+//! [`ExportManifest::parse`] upgrades older versions so their archives stay
+//! importable.
+
+use std::collections::{HashMap, HashSet};
+use std::io;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use futures_util::TryStreamExt as _;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest as _, Sha256};
+use tokio::io::AsyncRead;
+use uuid::Uuid;
+
+use crate::database::{
+    AccountExportRow, ContentRow, ContentStatus, ContentsInsert, ContentsListParams, ContentsSort,
+    GroupCreate, GroupStatus, GroupsListParams, ShareLinkCreate, SharePermission, SqlStorage,
+    SqlStorageError, StorageObjectInsert, TagCreate, Visibility,
+};
+use crate::purge::TrashPurger;
+use crate::storage::{FileStorage, StorageRouter, StorageSelectionError, ZipReader};
+use crate::v1::share_links::generate_share_token;
+use crate::v1::types::{parse_share_permission, parse_visibility};
+
+/// Value of the manifest's `format` field.
+pub const MANIFEST_FORMAT: &str = "collects-export";
+
+/// Current manifest version.
+pub const MANIFEST_VERSION: u32 = 1;
+
+/// Name of the manifest entry, the first entry of every export archive.
+pub const MANIFEST_ENTRY: &str = "manifest.json";
+
+/// Days a completed export can be downloaded.
+pub const EXPORT_RETENTION_DAYS: i64 = 7;
+
+/// Largest manifest an import accepts.
+const MAX_MANIFEST_SIZE: u64 = 64 * 1024 * 1024;
+
+/// Rows fetched per database round trip while building a manifest.
+const EXPORT_PAGE_SIZE: i64 = 100;
+
+/// Name of the archive entry holding the file of content `id`.
+pub fn blob_entry(id: Uuid) -> String {
+    format!("blobs/{id}")
+}
+
+/// The `manifest.json` of an export archive.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExportManifest {
+    /// Always [`MANIFEST_FORMAT`].
+    pub format: String,
+    /// Layout version, see [`MANIFEST_VERSION`].
+    pub version: u32,
+    pub exported_at: DateTime<Utc>,
+    #[serde(default)]
+    pub contents: Vec<ExportedContent>,
+    #[serde(default)]
+    pub tags: Vec<ExportedTag>,
+    #[serde(default)]
+    pub groups: Vec<ExportedGroup>,
+    #[serde(default)]
+    pub share_links: Vec<ExportedShareLink>,
+}
+
+/// A content. Ids are those of the exporting server and only link the
+/// entries of a manifest together.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExportedContent {
+    pub id: Uuid,
+    /// "file" or "text".
+    pub kind: String,
+    pub title: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    pub content_type: String,
+    pub visibility: String,
+    /// "active" or "archived"; trashed contents are not exported.
+    pub status: String,
+    pub file_size: i64,
+    #[serde(default)]
+    pub sha256: Option<String>,
+    /// Body of a text content.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub body: Option<String>,
+    /// Name the file was uploaded as.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub filename: Option<String>,
+    /// Archive entry holding the file, see [`blob_entry`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub blob: Option<String>,
+    /// Ids of the content's tags.
+    #[serde(default)]
+    pub tags: Vec<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExportedTag {
+    pub id: Uuid,
+    pub name: String,
+    #[serde(default)]
+    pub color: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExportedGroup {
+    pub id: Uuid,
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    pub visibility: String,
+    /// "active" or "archived"; trashed groups are not exported.
+    pub status: String,
+    /// Ids of the group's contents, in group order.
+    #[serde(default)]
+    pub contents: Vec<Uuid>,
+}
+
+/// Settings of a share link. Tokens are not exported: an imported link gets
+/// a new one.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExportedShareLink {
+    #[serde(default)]
+    pub name: Option<String>,
+    /// "view" or "download".
+    pub permission: String,
+    #[serde(default)]
+    pub password_hash: Option<String>,
+    #[serde(default)]
+    pub max_access_count: Option<i32>,
+    #[serde(default)]
+    pub expires_at: Option<DateTime<Utc>>,
+    pub is_active: bool,
+    /// Ids of the shared contents.
+    #[serde(default)]
+    pub contents: Vec<Uuid>,
+    /// Ids of the shared groups.
+    #[serde(default)]
+    pub groups: Vec<Uuid>,
+}
+
+/// Why a manifest could not be read.
+#[derive(Debug, thiserror::Error)]
+pub enum ManifestError {
+    #[error("Invalid export manifest: {0}")]
+    Json(#[from] serde_json::Error),
+
+    #[error("Not an export manifest (format '{0}')")]
+    Format(String),
+
+    #[error("Export manifest version {0} is not supported (latest is {MANIFEST_VERSION})")]
+    Version(u64),
+}
+
+impl ExportManifest {
+    /// Parse a manifest of any supported version.
+    pub fn parse(data: &[u8]) -> Result<Self, ManifestError> {
+        let value: serde_json::Value = serde_json::from_slice(data)?;
+        let format = value
+            .get("format")
+            .and_then(serde_json::Value::as_str)
+            .unwrap_or_default();
+        if format != MANIFEST_FORMAT {
+            return Err(ManifestError::Format(format.to_owned()));
+        }
+        let version = value
+            .get("version")
+            .and_then(serde_json::Value::as_u64)
+            .unwrap_or_default();
+        Ok(serde_json::from_value(upgrade(value, version)?)?)
+    }
+
+    /// Total size of the manifest's contents: files and text bodies.
+    pub fn total_bytes(&self) -> u64 {
+        self.contents
+            .iter()
+            .map(|content| match (&content.blob, &content.body) {
+                (Some(_), _) => u64::try_from(content.file_size).unwrap_or(0),
+                (None, Some(body)) => body.len() as u64,
+                (None, None) => 0,
+            })
+            .sum()
+    }
+}
+
+/// Bring a manifest of `version` to the current layout.
+///
+/// Each format change adds a step here, from the version before it.
+fn upgrade(value: serde_json::Value, version: u64) -> Result<serde_json::Value, ManifestError> {
+    match version {
+        v if v == u64::from(MANIFEST_VERSION) => Ok(value),
+        v => Err(ManifestError::Version(v)),
+    }
+}
+
+/// Builds export manifests and records them on export jobs.
+#[derive(Clone)]
+pub struct AccountExporter<S> {
+    sql_storage: S,
+}
+
+impl<S: SqlStorage> AccountExporter<S> {
+    pub fn new(sql_storage: S) -> Self {
+        Self { sql_storage }
+    }
+
+    /// Build the manifest of `export` and complete the job with it, or fail
+    /// the job.
+    pub async fn run(&self, export: &AccountExportRow) -> Result<(), SqlStorageError> {
+        let manifest = self
+            .manifest(export.user_id)
+            .await
+            .map_err(|e| e.to_string())
+            .and_then(|manifest| serde_json::to_value(manifest).map_err(|e| e.to_string()));
+        match manifest {
+            Ok(manifest) => {
+                self.sql_storage
+                    .account_exports_complete(export.id, manifest)
+                    .await?;
+            }
+            Err(error) => {
+                tracing::error!(export_id = %export.id, "Account export failed: {}", error);
+                self.sql_storage
+                    .account_exports_fail(export.id, &error)
+                    .await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Snapshot the active and archived data of a user.
+    pub async fn manifest(&self, user_id: Uuid) -> Result<ExportManifest, SqlStorageError> {
+        let mut contents = Vec::new();
+        for status in [ContentStatus::Active, ContentStatus::Archived] {
+            let mut offset = 0;
+            loop {
+                let page = self
+                    .sql_storage
+                    .contents_list_for_user(
+                        user_id,
+                        ContentsListParams {
+                            limit: EXPORT_PAGE_SIZE,
+                            offset,
+                            status: Some(status.clone()),
+                            sort: ContentsSort::CreatedAsc,
+                            ..Default::default()
+                        },
+                    )
+                    .await?;
+                let done = (page.len() as i64) < EXPORT_PAGE_SIZE;
+                offset += page.len() as i64;
+                for content in page {
+                    let tags = self
+                        .sql_storage
+                        .content_tags_list_for_content(content.id)
+                        .await?
+                        .into_iter()
+                        .map(|tag| tag.id)
+                        .collect();
+                    let file = content.kind != "text";
+                    contents.push(ExportedContent {
+                        id: content.id,
+                        kind: content.kind,
+                        title: content.title,
+                        description: content.description,
+                        content_type: content.content_type,
+                        visibility: content.visibility,
+                        status: content.status,
+                        file_size: content.file_size,
+                        sha256: content.sha256,
+                        body: content.body,
+                        filename: file.then(|| {
+                            content
+                                .storage_key
+                                .rsplit('/')
+                                .next()
+                                .unwrap_or_default()
+                                .to_owned()
+                        }),
+                        blob: file.then(|| blob_entry(content.id)),
+                        tags,
+                        created_at: content.created_at,
+                        updated_at: content.updated_at,
+                    });
+                }
+                if done {
+                    break;
+                }
+            }
+        }
+        let exported: HashSet<Uuid> = contents.iter().map(|content| content.id).collect();
+
+        let tags = self
+            .sql_storage
+            .tags_list_for_user(user_id)
+            .await?
+            .into_iter()
+            .map(|tag| ExportedTag {
+                id: tag.id,
+                name: tag.name,
+                color: tag.color,
+            })
+            .collect();
+
+        let mut groups = Vec::new();
+        for status in [GroupStatus::Active, GroupStatus::Archived] {
+            let mut offset = 0;
+            loop {
+                let page = self
+                    .sql_storage
+                    .groups_list_for_user(
+                        user_id,
+                        GroupsListParams {
+                            limit: EXPORT_PAGE_SIZE,
+                            offset,
+                            status: Some(status.clone()),
+                        },
+                    )
+                    .await?;
+                let done = (page.len() as i64) < EXPORT_PAGE_SIZE;
+                offset += page.len() as i64;
+                for group in page {
+                    let items = self.sql_storage.group_items_list(group.id).await?;
+                    groups.push(ExportedGroup {
+                        id: group.id,
+                        name: group.name,
+                        description: group.description,
+                        visibility: group.visibility,
+                        status: group.status,
+                        contents: items
+                            .into_iter()
+                            .map(|item| item.content_id)
+                            .filter(|id| exported.contains(id))
+                            .collect(),
+                    });
+                }
+                if done {
+                    break;
+                }
+            }
+        }
+        let exported_groups: HashSet<Uuid> = groups.iter().map(|group| group.id).collect();
+
+        let mut targets: HashMap<Uuid, (Vec<Uuid>, Vec<Uuid>)> = HashMap::new();
+        for target in self
+            .sql_storage
+            .share_links_targets_for_owner(user_id)
+            .await?
+        {
+            let (link_contents, link_groups) = targets.entry(target.share_link_id).or_default();
+            if let Some(id) = target.content_id.filter(|id| exported.contains(id)) {
+                link_contents.push(id);
+            }
+            if let Some(id) = target.group_id.filter(|id| exported_groups.contains(id)) {
+                link_groups.push(id);
+            }
+        }
+        let share_links = self
+            .sql_storage
+            .share_links_list_for_owner(user_id)
+            .await?
+            .into_iter()
+            .filter_map(|link| {
+                let (contents, groups) = targets.remove(&link.id)?;
+                if contents.is_empty() && groups.is_empty() {
+                    return None;
+                }
+                Some(ExportedShareLink {
+                    name: link.name,
+                    permission: link.permission,
+                    password_hash: link.password_hash,
+                    max_access_count: link.max_access_count,
+                    expires_at: link.expires_at,
+                    is_active: link.is_active,
+                    contents,
+                    groups,
+                })
+            })
+            .collect();
+
+        Ok(ExportManifest {
+            format: MANIFEST_FORMAT.to_owned(),
+            version: MANIFEST_VERSION,
+            exported_at: Utc::now(),
+            contents,
+            tags,
+            groups,
+            share_links,
+        })
+    }
+}
+
+/// What an import created.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ImportReport {
+    pub contents: usize,
+    pub tags: usize,
+    pub groups: usize,
+    pub share_links: usize,
+    /// Manifest ids of file contents whose file was not in the archive; they
+    /// are not imported.
+    pub missing_blobs: Vec<Uuid>,
+}
+
+/// Why an import stopped. Data imported before the failure is removed.
+#[derive(Debug, thiserror::Error)]
+pub enum ImportError {
+    #[error("Import of {size} bytes exceeds the storage quota ({used} of {quota} bytes used)")]
+    QuotaExceeded { size: u64, used: i64, quota: i64 },
+
+    #[error(transparent)]
+    Manifest(#[from] ManifestError),
+
+    #[error("Invalid export archive: {0}")]
+    Archive(#[from] io::Error),
+
+    #[error("Invalid export archive: {0}")]
+    Invalid(String),
+
+    #[error(transparent)]
+    Storage(#[from] StorageSelectionError),
+
+    #[error("Failed to store file: {0}")]
+    Upload(String),
+
+    #[error(transparent)]
+    Db(#[from] SqlStorageError),
+}
+
+/// Read the manifest, which must be the first entry of `archive`.
+pub async fn read_manifest<R: AsyncRead + Unpin>(
+    archive: &mut ZipReader<R>,
+) -> Result<ExportManifest, ImportError> {
+    match archive.next_entry().await? {
+        Some(entry) if entry.name == MANIFEST_ENTRY => {
+            let data = archive.read_entry(MAX_MANIFEST_SIZE).await?;
+            Ok(ExportManifest::parse(&data)?)
+        }
+        _ => Err(ImportError::Invalid(format!(
+            "'{MANIFEST_ENTRY}' must be the first entry"
+        ))),
+    }
+}
+
+/// Recreates the data of an export archive for one user.
+pub struct AccountImporter<S> {
+    sql_storage: S,
+    storage: StorageRouter,
+    user_id: Uuid,
+    username: String,
+    /// Manifest content id -> imported content id.
+    contents: HashMap<Uuid, Uuid>,
+    /// Rows created so far, removed if the import fails.
+    created: CreatedRows,
+    report: ImportReport,
+}
+
+#[derive(Default)]
+struct CreatedRows {
+    contents: Vec<Uuid>,
+    tags: Vec<Uuid>,
+    groups: Vec<Uuid>,
+    share_links: Vec<Uuid>,
+}
+
+impl<S: SqlStorage> AccountImporter<S> {
+    pub fn new(sql_storage: S, storage: StorageRouter, user_id: Uuid, username: &str) -> Self {
+        Self {
+            sql_storage,
+            storage,
+            user_id,
+            username: username.to_owned(),
+            contents: HashMap::new(),
+            created: CreatedRows::default(),
+            report: ImportReport::default(),
+        }
+    }
+
+    /// Import `manifest` and the files that follow it in `archive`.
+    ///
+    /// Files are streamed into storage as their entries are read; tags,
+    /// groups and share links are created once the whole archive has been
+    /// read. The manifest's total size must fit the user's storage quota, and
+    /// each content reserves its size as it is created. If the import fails,
+    /// everything it created is deleted again.
+    pub async fn import<R: AsyncRead + Unpin + Send + 'static>(
+        mut self,
+        manifest: ExportManifest,
+        archive: ZipReader<R>,
+    ) -> Result<ImportReport, ImportError> {
+        // Runs as its own task, so a failed import is still rolled back when
+        // the client disconnects and the caller is dropped
+        tokio::spawn(async move {
+            self.check_quota(&manifest).await?;
+            match self.import_all(&manifest, archive).await {
+                Ok(()) => Ok(self.report),
+                Err(e) => {
+                    self.roll_back().await;
+                    Err(e)
+                }
+            }
+        })
+        .await
+        .expect("import task panicked")
+    }
+
+    /// Reject manifests whose contents do not fit the user's storage quota.
+    ///
+    /// Nothing is reserved: this only fails early, before any file is read.
+    /// Contents reserve their size as they are created, in
+    /// [`insert_content`](Self::insert_content).
+    async fn check_quota(&self, manifest: &ExportManifest) -> Result<(), ImportError> {
+        let Some(quota) = self
+            .sql_storage
+            .users_get_storage_quota(self.user_id)
+            .await?
+        else {
+            return Ok(());
+        };
+        let used = self
+            .sql_storage
+            .users_storage_usage(self.user_id, Utc::now())
+            .await?
+            .total_bytes();
+
+        let size = manifest.total_bytes();
+        if used.saturating_add(i64::try_from(size).unwrap_or(i64::MAX)) > quota {
+            return Err(ImportError::QuotaExceeded { size, used, quota });
+        }
+        Ok(())
+    }
+
+    async fn import_all<R: AsyncRead + Unpin + Send>(
+        &mut self,
+        manifest: &ExportManifest,
+        mut archive: ZipReader<R>,
+    ) -> Result<(), ImportError> {
+        let mut files = HashMap::new();
+        for content in &manifest.contents {
+            match (&content.blob, &content.body) {
+                (Some(blob), _) => {
+                    files.insert(blob.as_str(), content);
+                }
+                (None, Some(body)) => self.import_text(content, body).await?,
+                (None, None) => {}
+            }
+        }
+
+        while let Some(entry) = archive.next_entry().await? {
+            let Some(content) = files.remove(entry.name.as_str()) else {
+                continue;
+            };
+            if Ok(entry.size) != u64::try_from(content.file_size) {
+                return Err(ImportError::Invalid(format!(
+                    "'{}' is {} bytes, the manifest says {}",
+                    entry.name, entry.size, content.file_size
+                )));
+            }
+            self.import_file(content, entry.size, &mut archive).await?;
+        }
+        self.report.missing_blobs = files.values().map(|content| content.id).collect();
+
+        self.import_tags(manifest).await?;
+        let groups = self.import_groups(manifest).await?;
+        self.import_share_links(manifest, &groups).await?;
+        Ok(())
+    }
+
+    /// Delete the rows created by a failed import, and the files of its
+    /// contents once nothing else references them.
+    async fn roll_back(&mut self) {
+        let created = std::mem::take(&mut self.created);
+        for id in created.share_links {
+            if let Err(e) = self
+                .sql_storage
+                .share_links_delete(id, self.user_id, None)
+                .await
+            {
+                tracing::warn!(share_link_id = %id, "Failed to delete imported share link: {e:?}");
+            }
+        }
+        for id in created.tags {
+            if let Err(e) = self.sql_storage.tags_delete(self.user_id, id, None).await {
+                tracing::warn!(tag_id = %id, "Failed to delete imported tag: {e:?}");
+            }
+        }
+
+        // Purging only removes trashed rows, so trash them first
+        let now = Utc::now();
+        for id in &created.groups {
+            if let Err(e) = self
+                .sql_storage
+                .groups_set_status(*id, self.user_id, GroupStatus::Trashed, now)
+                .await
+            {
+                tracing::warn!(group_id = %id, "Failed to trash imported group: {e:?}");
+            }
+        }
+        for id in &created.contents {
+            if let Err(e) = self
+                .sql_storage
+                .contents_set_status(*id, self.user_id, ContentStatus::Trashed, now)
+                .await
+            {
+                tracing::warn!(content_id = %id, "Failed to trash imported content: {e:?}");
+            }
+        }
+
+        let ids = created.groups.into_iter().chain(created.contents).collect();
+        let purger = TrashPurger::new(
+            self.sql_storage.clone(),
+            self.storage.clone(),
+            Duration::ZERO,
+        );
+        match purger.purge_items(self.user_id, ids).await {
+            Ok(report) if report.failed_cleanups > 0 => {
+                tracing::warn!(
+                    failed_cleanups = report.failed_cleanups,
+                    "Some files of a failed import were not deleted"
+                );
+            }
+            Ok(_) => {}
+            Err(e) => tracing::warn!("Failed to delete the contents of a failed import: {e:?}"),
+        }
+    }
+
+    async fn import_text(
+        &mut self,
+        content: &ExportedContent,
+        body: &str,
+    ) -> Result<(), ImportError> {
+        let row = self
+            .insert_content(ContentsInsert {
+                user_id: self.user_id,
+                title: content.title.clone(),
+                description: content.description.clone(),
+                storage_backend: "inline".to_owned(),
+                storage_profile: "inline".to_owned(),
+                storage_key: "".to_owned(),
+                content_type: content.content_type.clone(),
+                file_size: body.len() as i64,
+                visibility: parse_visibility(&content.visibility).unwrap_or(Visibility::Private),
+                kind: Some("text".to_owned()),
+                body: Some(body.to_owned()),
+                sha256: None,
+            })
+            .await?;
+        self.imported(content, row.id).await
+    }
+
+    /// Import the file content `content`, whose `size` bytes are the current
+    /// entry of `archive`.
+    ///
+    /// The content is created first, reserving its size, and the entry is
+    /// then streamed into storage while its SHA-256 is computed.
+    async fn import_file<R: AsyncRead + Unpin + Send>(
+        &mut self,
+        content: &ExportedContent,
+        size: u64,
+        archive: &mut ZipReader<R>,
+    ) -> Result<(), ImportError> {
+        // Checked against the header before any of the file is read
+        let target = self
+            .storage
+            .select_upload_target(None, &self.username, &content.content_type, size)?
+            .clone();
+        let filename = content
+            .filename
+            .as_deref()
+            .map(|name| name.replace(['/', '\\'], "_"))
+            .filter(|name| !name.is_empty())
+            .unwrap_or_else(|| "file".to_owned());
+        let storage_key = format!("{}/{}/{}", self.user_id, Uuid::new_v4(), filename);
+        let row = self
+            .insert_content(ContentsInsert {
+                user_id: self.user_id,
+                title: content.title.clone(),
+                description: content.description.clone(),
+                storage_backend: target.backend().to_owned(),
+                storage_profile: target.profile().to_owned(),
+                storage_key: storage_key.clone(),
+                content_type: content.content_type.clone(),
+                file_size: size as i64,
+                visibility: parse_visibility(&content.visibility).unwrap_or(Visibility::Private),
+                kind: Some("file".to_owned()),
+                body: None,
+                sha256: None,
+            })
+            .await?;
+
+        let mut hasher = Sha256::new();
+        let mut archive_error = None;
+        let chunks = futures_util::stream::try_unfold(archive, |archive| async move {
+            let chunk = archive.read_chunk().await?;
+            Ok::<_, io::Error>(chunk.map(|chunk| (chunk, archive)))
+        })
+        .inspect_ok(|chunk| hasher.update(chunk))
+        .inspect_err(|e| archive_error = Some(io::Error::new(e.kind(), e.to_string())));
+        let stored = target
+            .file_storage()
+            .upload_stream(&storage_key, &content.content_type, chunks)
+            .await;
+        if let Err(e) = stored {
+            // A broken archive is the client's fault, not the storage's
+            return Err(match archive_error {
+                Some(e) => ImportError::Archive(e),
+                None => ImportError::Upload(e.to_string()),
+            });
+        }
+
+        let sha256 = hex::encode(hasher.finalize());
+        if content
+            .sha256
+            .as_deref()
+            .is_some_and(|expected| !expected.eq_ignore_ascii_case(&sha256))
+        {
+            return Err(ImportError::Invalid(format!(
+                "the file of content {} does not match its SHA-256",
+                content.id
+            )));
+        }
+
+        let shared = self
+            .sql_storage
+            .contents_share_storage_object(
+                row.id,
+                StorageObjectInsert {
+                    user_id: self.user_id,
+                    sha256,
+                    storage_backend: row.storage_backend.clone(),
+                    storage_profile: row.storage_profile.clone(),
+                    storage_key: storage_key.clone(),
+                    file_size: size as i64,
+                },
+            )
+            .await?;
+        if let Some((object, _)) = shared
+            && object.storage_key != storage_key
+            && let Err(e) = target.file_storage().delete_file(&storage_key).await
+        {
+            tracing::warn!(%storage_key, "Failed to delete duplicate imported file: {e}");
+        }
+
+        self.imported(content, row.id).await
+    }
+
+    /// Create a content, reserving its size against the user's storage quota,
+    /// and record it for roll back.
+    async fn insert_content(&mut self, input: ContentsInsert) -> Result<ContentRow, ImportError> {
+        let size = input.file_size as u64;
+        let row = match self.sql_storage.contents_insert_within_quota(input).await {
+            Ok(row) => row,
+            Err(SqlStorageError::QuotaExceeded { used, quota }) => {
+                return Err(ImportError::QuotaExceeded { size, used, quota });
+            }
+            Err(e) => return Err(e.into()),
+        };
+        self.created.contents.push(row.id);
+        Ok(row)
+    }
+
+    /// Record the imported content `id` and restore its status.
+    async fn imported(&mut self, content: &ExportedContent, id: Uuid) -> Result<(), ImportError> {
+        if content.status == ContentStatus::Archived.as_db_str() {
+            self.sql_storage
+                .contents_set_status(id, self.user_id, ContentStatus::Archived, Utc::now())
+                .await?;
+        }
+        self.contents.insert(content.id, id);
+        self.report.contents += 1;
+        Ok(())
+    }
+
+    /// Create the manifest's tags, reusing the user's tags of the same name,
+    /// and attach them.
+    async fn import_tags(&mut self, manifest: &ExportManifest) -> Result<(), ImportError> {
+        let mut existing: HashMap<String, Uuid> = self
+            .sql_storage
+            .tags_list_for_user(self.user_id)
+            .await?
+            .into_iter()
+            .map(|tag| (tag.name, tag.id))
+            .collect();
+        let mut tags = HashMap::new();
+        for tag in &manifest.tags {
+            let id = match existing.get(&tag.name) {
+                Some(id) => *id,
+                None => {
+                    let row = self
+                        .sql_storage
+                        .tags_create(TagCreate {
+                            user_id: self.user_id,
+                            name: tag.name.clone(),
+                            color: tag.color.clone(),
+                        })
+                        .await?;
+                    self.report.tags += 1;
+                    self.created.tags.push(row.id);
+                    existing.insert(row.name, row.id);
+                    row.id
+                }
+            };
+            tags.insert(tag.id, id);
+        }
+
+        for content in &manifest.contents {
+            let Some(content_id) = self.contents.get(&content.id) else {
+                continue;
+            };
+            for tag_id in content.tags.iter().filter_map(|id| tags.get(id)) {
+                self.sql_storage
+                    .content_tags_attach(*content_id, *tag_id)
+                    .await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Create the manifest's groups with their contents in order. Returns
+    /// manifest group id -> imported group id.
+    async fn import_groups(
+        &mut self,
+        manifest: &ExportManifest,
+    ) -> Result<HashMap<Uuid, Uuid>, ImportError> {
+        let mut groups = HashMap::new();
+        for group in &manifest.groups {
+            let row = self
+                .sql_storage
+                .groups_create(GroupCreate {
+                    user_id: self.user_id,
+                    name: group.name.clone(),
+                    description: group.description.clone(),
+                    visibility: parse_visibility(&group.visibility).unwrap_or(Visibility::Private),
+                })
+                .await?;
+            self.created.groups.push(row.id);
+            let content_ids = group.contents.iter().filter_map(|id| self.contents.get(id));
+            for (sort_order, content_id) in content_ids.enumerate() {
+                self.sql_storage
+                    .group_items_add(row.id, *content_id, sort_order as i32)
+                    .await?;
+            }
+            if group.status == GroupStatus::Archived.as_db_str() {
+                self.sql_storage
+                    .groups_set_status(row.id, self.user_id, GroupStatus::Archived, Utc::now())
+                    .await?;
+            }
+            groups.insert(group.id, row.id);
+            self.report.groups += 1;
+        }
+        Ok(groups)
+    }
+
+    /// Recreate the manifest's share links, with new tokens, on the imported
+    /// contents and groups.
+    async fn import_share_links(
+        &mut self,
+        manifest: &ExportManifest,
+        groups: &HashMap<Uuid, Uuid>,
+    ) -> Result<(), ImportError> {
+        for link in &manifest.share_links {
+            let content_ids: Vec<Uuid> = link
+                .contents
+                .iter()
+                .filter_map(|id| self.contents.get(id).copied())
+                .collect();
+            let group_ids: Vec<Uuid> = link
+                .groups
+                .iter()
+                .filter_map(|id| groups.get(id).copied())
+                .collect();
+            if content_ids.is_empty() && group_ids.is_empty() {
+                continue;
+            }
+
+            let row = self
+                .sql_storage
+                .share_links_create(ShareLinkCreate {
+                    owner_id: self.user_id,
+                    token: generate_share_token(),
+                    name: link.name.clone(),
+                    permission: parse_share_permission(&link.permission)
+                        .unwrap_or(SharePermission::View),
+                    password_hash: link.password_hash.clone(),
+                    max_access_count: link.max_access_count,
+                    expires_at: link.expires_at,
+                })
+                .await?;
+            self.created.share_links.push(row.id);
+            for content_id in content_ids {
+                self.sql_storage
+                    .content_shares_attach_link(content_id, row.id, self.user_id)
+                    .await?;
+            }
+            for group_id in group_ids {
+                self.sql_storage
+                    .group_shares_attach_link(group_id, row.id, self.user_id)
+                    .await?;
+            }
+            if !link.is_active {
+                self.sql_storage
+                    .share_links_deactivate(self.user_id, row.id)
+                    .await?;
+            }
+            self.report.share_links += 1;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_manifest_parse_checks_format_and_version() {
+        let manifest = ExportManifest {
+            format: MANIFEST_FORMAT.to_owned(),
+            version: MANIFEST_VERSION,
+            exported_at: Utc::now(),
+            contents: vec![],
+            tags: vec![ExportedTag {
+                id: Uuid::new_v4(),
+                name: "travel".to_owned(),
+                color: None,
+            }],
+            groups: vec![],
+            share_links: vec![],
+        };
+        let data = serde_json::to_vec(&manifest).unwrap();
+        assert_eq!(ExportManifest::parse(&data).unwrap(), manifest);
+
+        // Lists may be left out
+        let minimal = serde_json::json!({
+            "format": MANIFEST_FORMAT,
+            "version": MANIFEST_VERSION,
+            "exported_at": "2026-10-17T12:00:00Z",
+        });
+        let parsed = ExportManifest::parse(minimal.to_string().as_bytes()).unwrap();
+        assert!(parsed.contents.is_empty());
+
+        let other = serde_json::json!({ "format": "zip", "version": 1 });
+        assert!(matches!(
+            ExportManifest::parse(other.to_string().as_bytes()),
+            Err(ManifestError::Format(_))
+        ));
+        let newer =
+            serde_json::json!({ "format": MANIFEST_FORMAT, "version": MANIFEST_VERSION + 1 });
+        assert!(matches!(
+            ExportManifest::parse(newer.to_string().as_bytes()),
+            Err(ManifestError::Version(_))
+        ));
+    }
+}
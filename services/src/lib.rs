@@ -1,6 +1,12 @@
-use crate::config::Config;
+use crate::config::{
+    Config, GcsConfig, LocalStorageConfig, R2Config, StorageBackend, StorageProfileBackend,
+};
 use crate::database::SqlStorage;
-use crate::storage::{CFDiskConfig, R2Presigner};
+use crate::storage::{
+    BackendFileStorage, CFDiskConfig, CFFileStorage, DEFAULT_STORAGE_PROFILE, GcsDiskConfig,
+    GcsFileStorage, GcsPresigner, LocalFileStorage, LocalPresigner, Presigner, R2Presigner,
+    StorageRouter, StorageTarget,
+};
 use crate::users::routes::AppState;
 use crate::users::storage::UserStorage;
 use axum::{
@@ -17,14 +23,19 @@ use tracing_opentelemetry::OpenTelemetrySpanExt as _;
 
 use axum::http::header;
 
+pub mod audit;
 pub mod auth;
 pub mod collect_files;
 pub mod collects;
 pub mod config;
 pub mod database;
+pub mod export;
 pub mod internal;
 pub mod openapi;
+pub mod purge;
+pub mod share_page;
 pub mod storage;
+pub mod sweeper;
 pub mod telemetry;
 pub mod users;
 pub mod v1;
@@ -65,23 +76,17 @@ where
         .nest("/v1", v1_routes)
         .nest("/v1", v1_public_routes)
         .nest("/internal", internal_routes)
-        .nest("/auth", users::auth_routes::<S, U>());
+        .nest("/auth", users::auth_routes::<S, U>())
+        .merge(share_page::routes::<S, U>())
+        .merge(storage::local_storage_routes());
 
     // Add OpenAPI documentation routes for internal environments (protected by Zero Trust)
     if let Some(openapi_routes) = openapi::create_openapi_routes::<S, U>(&config) {
         router = router.merge(openapi_routes);
     }
 
-    // Add R2 presigner extension if R2 is configured
-    if let Some(r2_config) = config.r2() {
-        let presigner = R2Presigner::new(CFDiskConfig {
-            account_id: r2_config.account_id().to_owned(),
-            access_key_id: r2_config.access_key_id().to_owned(),
-            secret_access_key: r2_config.secret_access_key().to_owned(),
-            bucket: r2_config.bucket().to_owned(),
-        });
-        router = router.layer(Extension(presigner));
-    }
+    // Add the storage router resolving (storage_backend, storage_profile) to presigners
+    router = router.layer(Extension(storage_router(&config)));
 
     router
         .fallback(any(catch_all))
@@ -112,6 +117,112 @@ where
         .with_state(state)
 }
 
+/// Builds the storage router from every configured backend and storage profile.
+///
+/// Each top-level backend is registered under the default profile, where
+/// `STORAGE_BACKEND` selects the one that receives new uploads. Named profiles
+/// from `STORAGE_PROFILES` are registered with their size and MIME limits.
+pub fn storage_router(config: &Config) -> StorageRouter {
+    let mut router = StorageRouter::new();
+
+    let default_targets = [
+        config
+            .r2()
+            .map(|r2| (StorageBackend::R2, r2_target(DEFAULT_STORAGE_PROFILE, r2))),
+        config.gcs().map(|gcs| {
+            (
+                StorageBackend::Gcs,
+                gcs_target(DEFAULT_STORAGE_PROFILE, gcs),
+            )
+        }),
+        config.local_storage().map(|local| {
+            (
+                StorageBackend::Local,
+                local_target(DEFAULT_STORAGE_PROFILE, local),
+            )
+        }),
+    ];
+    for (backend, target) in default_targets.into_iter().flatten() {
+        router = if backend == config.storage_backend() {
+            router.with_upload_profile(target)
+        } else {
+            router.with_target(target)
+        };
+    }
+
+    for profile in config.storage_profiles() {
+        let target = match profile.backend() {
+            StorageProfileBackend::R2(r2) => r2_target(profile.name(), r2),
+            StorageProfileBackend::Gcs(gcs) => gcs_target(profile.name(), gcs),
+            StorageProfileBackend::Local(local) => local_target(profile.name(), local),
+        };
+        router = router.with_upload_profile(target.with_limits(
+            profile.max_object_size(),
+            profile.allowed_content_types().to_vec(),
+        ));
+    }
+
+    for (username, profile) in config.storage_user_profiles() {
+        router = router.with_user_profile(username, profile);
+    }
+
+    let default_backend = config
+        .storage_profiles()
+        .iter()
+        .find(|profile| profile.name() == config.storage_default_profile())
+        .map_or(config.storage_backend(), |profile| {
+            profile.storage_backend()
+        });
+    router.with_default(
+        default_backend.to_string(),
+        config.storage_default_profile(),
+    )
+}
+
+fn r2_target(profile: &str, r2_config: &R2Config) -> StorageTarget {
+    let disk_config = CFDiskConfig {
+        account_id: r2_config.account_id().to_owned(),
+        access_key_id: r2_config.access_key_id().to_owned(),
+        secret_access_key: r2_config.secret_access_key().to_owned(),
+        bucket: r2_config.bucket().to_owned(),
+    };
+    StorageTarget::new(
+        profile,
+        Presigner::R2(R2Presigner::new(disk_config.clone())),
+        BackendFileStorage::R2(CFFileStorage::new(disk_config)),
+    )
+}
+
+fn gcs_target(profile: &str, gcs_config: &GcsConfig) -> StorageTarget {
+    let disk_config = GcsDiskConfig {
+        bucket: gcs_config.bucket().to_owned(),
+        credential: gcs_config.credential().map(str::to_owned),
+        endpoint: gcs_config.endpoint().map(str::to_owned),
+        token: gcs_config.token().map(str::to_owned),
+    };
+    StorageTarget::new(
+        profile,
+        Presigner::Gcs(GcsPresigner::new(disk_config.clone())),
+        BackendFileStorage::Gcs(GcsFileStorage::new(disk_config)),
+    )
+}
+
+fn local_target(profile: &str, local_config: &LocalStorageConfig) -> StorageTarget {
+    let storage = LocalFileStorage::new(local_config.root());
+    StorageTarget::new(
+        profile,
+        Presigner::Local(
+            LocalPresigner::new(
+                storage.clone(),
+                local_config.base_url(),
+                local_config.signing_secret(),
+            )
+            .with_profile(profile),
+        ),
+        BackendFileStorage::Local(storage),
+    )
+}
+
 async fn health_check<S, U>(
     State(state): State<AppState<S, U>>,
     Extension(config): Extension<Config>,
@@ -165,6 +276,8 @@ mod tests {
         body::Body,
         http::{Request, StatusCode},
     };
+    use std::collections::HashMap;
+    use std::sync::{Arc, Mutex};
     use tower::ServiceExt;
 
     /// A fixed UUID for test scenarios to coordinate between MockSqlStorage and MockUserStorage.
@@ -179,6 +292,30 @@ mod tests {
         is_connected: bool,
         /// When set, mock methods will use this user ID for ownership checks.
         mock_user_id: Option<uuid::Uuid>,
+        /// Upload sessions and parts, kept so multi-step upload flows can be tested.
+        uploads: Arc<Mutex<HashMap<uuid::Uuid, crate::database::UploadRow>>>,
+        upload_parts: Arc<Mutex<Vec<crate::database::UploadPartRow>>>,
+        /// Deduplicated storage objects keyed by owner and SHA-256.
+        storage_objects:
+            Arc<Mutex<HashMap<(uuid::Uuid, String), crate::database::StorageObjectRow>>>,
+        /// Contents created through `contents_insert`, returned by `contents_get`.
+        contents: Arc<Mutex<HashMap<uuid::Uuid, crate::database::ContentRow>>>,
+        /// Trashed contents, listed by the trash endpoints and removed by purges.
+        trashed_contents: Arc<Mutex<Vec<crate::database::ContentRow>>>,
+        /// Storage quotas in bytes by user.
+        storage_quotas: Arc<Mutex<HashMap<uuid::Uuid, i64>>>,
+        /// Revisions recorded by `contents_update_metadata`.
+        content_revisions: Arc<Mutex<Vec<crate::database::ContentRevisionRow>>>,
+        /// Groups returned by `groups_get`.
+        groups: Arc<Mutex<HashMap<uuid::Uuid, crate::database::ContentGroupRow>>>,
+        /// Group items returned by `group_items_list`.
+        group_items: Arc<Mutex<Vec<crate::database::ContentGroupItemRow>>>,
+        /// Share links to groups by token, returned by `groups_get_by_share_token`.
+        group_share_links: Arc<Mutex<HashMap<String, (uuid::Uuid, crate::database::ShareLinkRow)>>>,
+        /// Export jobs, completed or failed by the exporter.
+        account_exports: Arc<Mutex<HashMap<uuid::Uuid, crate::database::AccountExportRow>>>,
+        /// Audit log entries appended by `audit_logs_insert`.
+        audit_logs: Arc<Mutex<Vec<crate::database::AuditLogRow>>>,
     }
 
     impl MockSqlStorage {
@@ -187,6 +324,18 @@ mod tests {
             Self {
                 is_connected: true,
                 mock_user_id: None,
+                uploads: Arc::default(),
+                upload_parts: Arc::default(),
+                storage_objects: Arc::default(),
+                contents: Arc::default(),
+                trashed_contents: Arc::default(),
+                storage_quotas: Arc::default(),
+                content_revisions: Arc::default(),
+                groups: Arc::default(),
+                group_items: Arc::default(),
+                group_share_links: Arc::default(),
+                account_exports: Arc::default(),
+                audit_logs: Arc::default(),
             }
         }
 
@@ -195,6 +344,18 @@ mod tests {
             Self {
                 is_connected: true,
                 mock_user_id: Some(user_id),
+                uploads: Arc::default(),
+                upload_parts: Arc::default(),
+                storage_objects: Arc::default(),
+                contents: Arc::default(),
+                trashed_contents: Arc::default(),
+                storage_quotas: Arc::default(),
+                content_revisions: Arc::default(),
+                groups: Arc::default(),
+                group_items: Arc::default(),
+                group_share_links: Arc::default(),
+                account_exports: Arc::default(),
+                audit_logs: Arc::default(),
             }
         }
 
@@ -203,7 +364,44 @@ mod tests {
             Self {
                 is_connected: false,
                 mock_user_id: None,
+                uploads: Arc::default(),
+                upload_parts: Arc::default(),
+                storage_objects: Arc::default(),
+                contents: Arc::default(),
+                trashed_contents: Arc::default(),
+                storage_quotas: Arc::default(),
+                content_revisions: Arc::default(),
+                groups: Arc::default(),
+                group_items: Arc::default(),
+                group_share_links: Arc::default(),
+                account_exports: Arc::default(),
+                audit_logs: Arc::default(),
+            }
+        }
+    }
+
+    impl MockSqlStorage {
+        fn set_upload_status(
+            &self,
+            id: uuid::Uuid,
+            user_id: uuid::Uuid,
+            status: &str,
+        ) -> Result<Option<crate::database::UploadRow>, crate::database::SqlStorageError> {
+            let mut uploads = self.uploads.lock().expect("lock poisoned");
+            let Some(upload) = uploads.get_mut(&id) else {
+                return Ok(None);
+            };
+            if upload.user_id != user_id {
+                return Err(crate::database::SqlStorageError::Unauthorized);
+            }
+            if upload.status != "initiated" {
+                return Err(crate::database::SqlStorageError::Invalid(format!(
+                    "Upload status is '{}', expected 'initiated'",
+                    upload.status
+                )));
             }
+            upload.status = status.to_owned();
+            Ok(Some(upload.clone()))
         }
     }
 
@@ -230,8 +428,8 @@ mod tests {
             input: crate::database::ContentsInsert,
         ) -> Result<crate::database::ContentRow, crate::database::SqlStorageError> {
             // Return a mock content row based on the input
-            Ok(crate::database::ContentRow {
-                id: uuid::Uuid::new_v4(),
+            let content = crate::database::ContentRow {
+                id: uuid::Uuid::now_v7(),
                 user_id: input.user_id,
                 title: input.title,
                 description: input.description,
@@ -244,11 +442,45 @@ mod tests {
                 visibility: input.visibility.as_db_str().to_owned(),
                 kind: input.kind.unwrap_or_else(|| "file".to_owned()),
                 body: input.body,
+                sha256: input.sha256,
+                metadata: serde_json::json!({}),
                 trashed_at: None,
                 archived_at: None,
                 created_at: chrono::Utc::now(),
                 updated_at: chrono::Utc::now(),
-            })
+            };
+            self.contents
+                .lock()
+                .expect("lock poisoned")
+                .insert(content.id, content.clone());
+            Ok(content)
+        }
+
+        async fn contents_insert_within_quota(
+            &self,
+            input: crate::database::ContentsInsert,
+        ) -> Result<crate::database::ContentRow, crate::database::SqlStorageError> {
+            let quota = self.users_get_storage_quota(input.user_id).await?;
+            if let Some(quota) = quota {
+                // `users_storage_usage` leaves out contents, so add them here
+                let stored: i64 = self
+                    .contents
+                    .lock()
+                    .expect("lock poisoned")
+                    .values()
+                    .filter(|c| c.user_id == input.user_id)
+                    .map(|c| c.file_size)
+                    .sum();
+                let used = self
+                    .users_storage_usage(input.user_id, chrono::Utc::now())
+                    .await?
+                    .total_bytes()
+                    + stored;
+                if used + input.file_size > quota {
+                    return Err(crate::database::SqlStorageError::QuotaExceeded { used, quota });
+                }
+            }
+            self.contents_insert(input).await
         }
 
         async fn contents_get(
@@ -273,40 +505,338 @@ mod tests {
                     visibility: "private".to_owned(),
                     kind: "file".to_owned(),
                     body: None,
+                    sha256: None,
+                    metadata: serde_json::json!({}),
                     trashed_at: None,
                     archived_at: None,
                     created_at: chrono::Utc::now(),
                     updated_at: chrono::Utc::now(),
                 }));
             }
-            Ok(None)
+            Ok(self
+                .contents
+                .lock()
+                .expect("lock poisoned")
+                .get(&id)
+                .cloned())
         }
 
         async fn contents_list_for_user(
             &self,
-            _user_id: uuid::Uuid,
-            _params: crate::database::ContentsListParams,
+            user_id: uuid::Uuid,
+            params: crate::database::ContentsListParams,
         ) -> Result<Vec<crate::database::ContentRow>, crate::database::SqlStorageError> {
-            Ok(vec![])
+            if params.status == Some(crate::database::ContentStatus::Trashed) {
+                let trashed = self.trashed_contents.lock().expect("lock poisoned");
+                return Ok(trashed
+                    .iter()
+                    .filter(|c| c.user_id == user_id)
+                    .cloned()
+                    .collect());
+            }
+            let sort = params.sort;
+            let position = |c: &crate::database::ContentRow| {
+                let cursor = sort.cursor(c);
+                (cursor.value, cursor.id)
+            };
+            let contents = self.contents.lock().expect("lock poisoned");
+            let mut rows: Vec<_> = contents
+                .values()
+                .filter(|c| c.user_id == user_id)
+                .filter(|c| {
+                    params
+                        .status
+                        .as_ref()
+                        .is_none_or(|s| c.status == s.as_db_str())
+                })
+                .filter(|c| params.kind.as_ref().is_none_or(|k| &c.kind == k))
+                .filter(|c| {
+                    params
+                        .content_type_prefix
+                        .as_ref()
+                        .is_none_or(|p| c.content_type.starts_with(p.as_str()))
+                })
+                .filter(|c| {
+                    params
+                        .visibility
+                        .as_ref()
+                        .is_none_or(|v| c.visibility == v.as_db_str())
+                })
+                .filter(|_| params.tag_ids.is_empty())
+                .filter(|c| {
+                    params.after.as_ref().is_none_or(|after| {
+                        let after = (after.value.clone(), after.id);
+                        if sort.is_descending() {
+                            position(c) < after
+                        } else {
+                            position(c) > after
+                        }
+                    })
+                })
+                .cloned()
+                .collect();
+            rows.sort_by_key(position);
+            if sort.is_descending() {
+                rows.reverse();
+            }
+            Ok(rows
+                .into_iter()
+                .skip(usize::try_from(params.offset).unwrap_or(0))
+                .take(usize::try_from(params.limit).unwrap_or(0))
+                .collect())
+        }
+
+        async fn contents_search(
+            &self,
+            user_id: uuid::Uuid,
+            params: crate::database::ContentsSearchParams,
+        ) -> Result<Vec<crate::database::ContentSearchHit>, crate::database::SqlStorageError>
+        {
+            if !params.tag_ids.is_empty() {
+                return Ok(vec![]);
+            }
+            let terms: Vec<String> = params
+                .query
+                .split_whitespace()
+                .map(str::to_lowercase)
+                .collect();
+            let mark = |text: &str| {
+                let mut marked = text.to_owned();
+                for term in &terms {
+                    if let Some(at) = marked.to_lowercase().find(term.as_str()) {
+                        marked.insert(at + term.len(), crate::database::SEARCH_HIGHLIGHT_END);
+                        marked.insert(at, crate::database::SEARCH_HIGHLIGHT_START);
+                    }
+                }
+                marked
+            };
+            let contents = self.contents.lock().expect("lock poisoned");
+            let mut hits: Vec<_> = contents
+                .values()
+                .filter(|c| c.user_id == user_id)
+                .filter(|c| {
+                    params
+                        .status
+                        .as_ref()
+                        .is_none_or(|s| c.status == s.as_db_str())
+                })
+                .filter(|c| params.kind.as_ref().is_none_or(|k| &c.kind == k))
+                .filter_map(|c| {
+                    let text = [Some(&c.title), c.description.as_ref(), c.body.as_ref()]
+                        .into_iter()
+                        .flatten()
+                        .map(|t| t.to_lowercase())
+                        .collect::<Vec<_>>()
+                        .join("\n");
+                    if terms.is_empty() || !terms.iter().all(|t| text.contains(t.as_str())) {
+                        return None;
+                    }
+                    let rank = terms
+                        .iter()
+                        .map(|t| text.matches(t.as_str()).count())
+                        .sum::<usize>();
+                    let snippet = match (&c.description, &c.body) {
+                        (None, None) => None,
+                        (description, body) => Some(mark(
+                            &[description.as_deref(), body.as_deref()]
+                                .into_iter()
+                                .flatten()
+                                .collect::<Vec<_>>()
+                                .join("\n"),
+                        )),
+                    };
+                    Some(crate::database::ContentSearchHit {
+                        content: c.clone(),
+                        rank: rank as f32,
+                        title_highlight: mark(&c.title),
+                        snippet,
+                    })
+                })
+                .collect();
+            hits.sort_by(|a, b| b.rank.total_cmp(&a.rank));
+            Ok(hits
+                .into_iter()
+                .skip(usize::try_from(params.offset).unwrap_or(0))
+                .take(usize::try_from(params.limit).unwrap_or(0))
+                .collect())
         }
 
         async fn contents_update_metadata(
             &self,
-            _id: uuid::Uuid,
-            _user_id: uuid::Uuid,
-            _changes: crate::database::ContentsUpdate,
+            id: uuid::Uuid,
+            user_id: uuid::Uuid,
+            changes: crate::database::ContentsUpdate,
         ) -> Result<Option<crate::database::ContentRow>, crate::database::SqlStorageError> {
-            Ok(None)
+            let mut contents = self.contents.lock().expect("lock poisoned");
+            let Some(content) = contents.get_mut(&id).filter(|c| {
+                c.user_id == user_id
+                    && changes
+                        .expected_updated_at
+                        .is_none_or(|expected| expected == c.updated_at)
+            }) else {
+                return Ok(None);
+            };
+            let previous = content.clone();
+            if let Some(title) = changes.title {
+                content.title = title;
+            }
+            if let Some(description) = changes.description {
+                content.description = description;
+            }
+            if let Some(visibility) = changes.visibility {
+                content.visibility = visibility.as_db_str().to_owned();
+            }
+            if content.kind == "text"
+                && let Some(body) = changes.body
+            {
+                content.body = Some(body);
+            }
+            let changed = content.title != previous.title
+                || content.description != previous.description
+                || content.body != previous.body;
+            if content.kind == "text" && changed && changes.max_revisions > 0 {
+                let mut revisions = self.content_revisions.lock().expect("lock poisoned");
+                let revision = revisions
+                    .iter()
+                    .filter(|r| r.content_id == id)
+                    .map(|r| r.revision)
+                    .max()
+                    .unwrap_or(0)
+                    + 1;
+                revisions.push(crate::database::ContentRevisionRow {
+                    id: uuid::Uuid::new_v4(),
+                    content_id: id,
+                    revision,
+                    title: previous.title,
+                    description: previous.description,
+                    body: previous.body.unwrap_or_default(),
+                    created_at: chrono::Utc::now(),
+                });
+                let oldest_kept = revision - i32::try_from(changes.max_revisions).unwrap_or(0);
+                revisions.retain(|r| r.content_id != id || r.revision > oldest_kept);
+            }
+            content.updated_at = chrono::Utc::now();
+            Ok(Some(content.clone()))
         }
 
         async fn contents_set_status(
             &self,
-            _id: uuid::Uuid,
-            _user_id: uuid::Uuid,
-            _new_status: crate::database::ContentStatus,
-            _now: chrono::DateTime<chrono::Utc>,
+            id: uuid::Uuid,
+            user_id: uuid::Uuid,
+            new_status: crate::database::ContentStatus,
+            now: chrono::DateTime<chrono::Utc>,
         ) -> Result<Option<crate::database::ContentRow>, crate::database::SqlStorageError> {
-            Ok(None)
+            let mut contents = self.contents.lock().expect("lock poisoned");
+            let Some(content) = contents.get_mut(&id).filter(|c| c.user_id == user_id) else {
+                return Ok(None);
+            };
+            content.status = new_status.as_db_str().to_owned();
+            content.trashed_at =
+                (new_status == crate::database::ContentStatus::Trashed).then_some(now);
+            content.archived_at =
+                (new_status == crate::database::ContentStatus::Archived).then_some(now);
+            content.updated_at = now;
+            let mut trashed = self.trashed_contents.lock().expect("lock poisoned");
+            trashed.retain(|c| c.id != id);
+            if new_status == crate::database::ContentStatus::Trashed {
+                trashed.push(content.clone());
+            }
+            Ok(Some(content.clone()))
+        }
+
+        async fn contents_batch(
+            &self,
+            user_id: uuid::Uuid,
+            ids: &[uuid::Uuid],
+            op: crate::database::ContentsBatchOp,
+            now: chrono::DateTime<chrono::Utc>,
+        ) -> Result<Vec<uuid::Uuid>, crate::database::SqlStorageError> {
+            let mut contents = self.contents.lock().expect("lock poisoned");
+            let mut trashed = self.trashed_contents.lock().expect("lock poisoned");
+            let mut applied = Vec::new();
+            for id in ids {
+                let Some(content) = contents.get_mut(id).filter(|c| c.user_id == user_id) else {
+                    continue;
+                };
+                if let crate::database::ContentsBatchOp::SetStatus(status) = &op {
+                    content.status = status.as_db_str().to_owned();
+                    content.trashed_at =
+                        (*status == crate::database::ContentStatus::Trashed).then_some(now);
+                    content.archived_at =
+                        (*status == crate::database::ContentStatus::Archived).then_some(now);
+                    trashed.retain(|c| c.id != *id);
+                    if *status == crate::database::ContentStatus::Trashed {
+                        trashed.push(content.clone());
+                    }
+                }
+                applied.push(*id);
+            }
+            Ok(applied)
+        }
+
+        async fn contents_set_media_metadata(
+            &self,
+            id: uuid::Uuid,
+            metadata: serde_json::Value,
+        ) -> Result<bool, crate::database::SqlStorageError> {
+            let mut contents = self.contents.lock().expect("lock poisoned");
+            Ok(match contents.get_mut(&id) {
+                Some(content) => {
+                    content.metadata = metadata;
+                    true
+                }
+                None => false,
+            })
+        }
+
+        async fn content_revisions_list(
+            &self,
+            content_id: uuid::Uuid,
+        ) -> Result<Vec<crate::database::ContentRevisionRow>, crate::database::SqlStorageError>
+        {
+            let revisions = self.content_revisions.lock().expect("lock poisoned");
+            let mut rows: Vec<_> = revisions
+                .iter()
+                .filter(|r| r.content_id == content_id)
+                .cloned()
+                .collect();
+            rows.sort_by_key(|r| std::cmp::Reverse(r.revision));
+            Ok(rows)
+        }
+
+        async fn content_revisions_get(
+            &self,
+            content_id: uuid::Uuid,
+            revision: i32,
+        ) -> Result<Option<crate::database::ContentRevisionRow>, crate::database::SqlStorageError>
+        {
+            let revisions = self.content_revisions.lock().expect("lock poisoned");
+            Ok(revisions
+                .iter()
+                .find(|r| r.content_id == content_id && r.revision == revision)
+                .cloned())
+        }
+
+        async fn contents_purge_trashed(
+            &self,
+            params: crate::database::TrashPurgeParams,
+        ) -> Result<Vec<crate::database::ContentRow>, crate::database::SqlStorageError> {
+            let mut trashed = self.trashed_contents.lock().expect("lock poisoned");
+            let (mut purged, kept): (Vec<_>, Vec<_>) = trashed.drain(..).partition(|c| {
+                params.user_id.is_none_or(|user_id| c.user_id == user_id)
+                    && params
+                        .trashed_before
+                        .is_none_or(|before| c.trashed_at.is_some_and(|at| at < before))
+                    && params.ids.as_ref().is_none_or(|ids| ids.contains(&c.id))
+            });
+            let limit = usize::try_from(params.limit).unwrap_or(0);
+            trashed.extend(kept);
+            trashed.extend(purged.drain(limit.min(purged.len())..));
+            let mut contents = self.contents.lock().expect("lock poisoned");
+            for content in &purged {
+                contents.remove(&content.id);
+            }
+            Ok(purged)
         }
 
         async fn groups_create(
@@ -320,10 +850,10 @@ mod tests {
 
         async fn groups_get(
             &self,
-            _id: uuid::Uuid,
+            id: uuid::Uuid,
         ) -> Result<Option<crate::database::ContentGroupRow>, crate::database::SqlStorageError>
         {
-            Ok(None)
+            Ok(self.groups.lock().unwrap().get(&id).cloned())
         }
 
         async fn groups_list_for_user(
@@ -356,6 +886,14 @@ mod tests {
             Ok(None)
         }
 
+        async fn groups_purge_trashed(
+            &self,
+            _params: crate::database::TrashPurgeParams,
+        ) -> Result<Vec<crate::database::ContentGroupRow>, crate::database::SqlStorageError>
+        {
+            Ok(vec![])
+        }
+
         async fn group_items_add(
             &self,
             _group_id: uuid::Uuid,
@@ -375,10 +913,19 @@ mod tests {
 
         async fn group_items_list(
             &self,
-            _group_id: uuid::Uuid,
+            group_id: uuid::Uuid,
         ) -> Result<Vec<crate::database::ContentGroupItemRow>, crate::database::SqlStorageError>
         {
-            Ok(vec![])
+            let mut items: Vec<_> = self
+                .group_items
+                .lock()
+                .unwrap()
+                .iter()
+                .filter(|item| item.group_id == group_id)
+                .cloned()
+                .collect();
+            items.sort_by_key(|item| (item.sort_order, item.added_at));
+            Ok(items)
         }
 
         async fn group_items_reorder(
@@ -406,10 +953,19 @@ mod tests {
             Ok(vec![])
         }
 
+        async fn tags_get(
+            &self,
+            _user_id: uuid::Uuid,
+            _tag_id: uuid::Uuid,
+        ) -> Result<Option<crate::database::TagRow>, crate::database::SqlStorageError> {
+            Ok(None)
+        }
+
         async fn tags_delete(
             &self,
             _user_id: uuid::Uuid,
             _tag_id: uuid::Uuid,
+            _expected_updated_at: Option<chrono::DateTime<chrono::Utc>>,
         ) -> Result<bool, crate::database::SqlStorageError> {
             Ok(false)
         }
@@ -501,6 +1057,7 @@ mod tests {
             &self,
             _id: uuid::Uuid,
             _owner_id: uuid::Uuid,
+            _expected_updated_at: Option<chrono::DateTime<chrono::Utc>>,
         ) -> Result<bool, crate::database::SqlStorageError> {
             Ok(false)
         }
@@ -512,6 +1069,47 @@ mod tests {
             Ok(())
         }
 
+        async fn share_links_rehash_password(
+            &self,
+            _id: uuid::Uuid,
+            _current_hash: &str,
+            _new_hash: &str,
+        ) -> Result<(), crate::database::SqlStorageError> {
+            Ok(())
+        }
+
+        async fn share_link_accesses_insert(
+            &self,
+            _input: crate::database::ShareLinkAccessInsert,
+        ) -> Result<(), crate::database::SqlStorageError> {
+            Ok(())
+        }
+
+        async fn share_link_accesses_stats(
+            &self,
+            _share_link_id: uuid::Uuid,
+            _since: chrono::DateTime<chrono::Utc>,
+        ) -> Result<crate::database::ShareLinkAccessStats, crate::database::SqlStorageError>
+        {
+            Ok(crate::database::ShareLinkAccessStats::default())
+        }
+
+        async fn share_link_password_reserve_attempt(
+            &self,
+            _share_link_id: uuid::Uuid,
+            _ip_address: Option<std::net::IpAddr>,
+            _config: &crate::database::ShareLinkPasswordRateLimitConfig,
+        ) -> Result<Option<uuid::Uuid>, crate::database::SqlStorageError> {
+            Ok(Some(uuid::Uuid::new_v4()))
+        }
+
+        async fn share_link_password_attempt_succeeded(
+            &self,
+            _attempt_id: uuid::Uuid,
+        ) -> Result<(), crate::database::SqlStorageError> {
+            Ok(())
+        }
+
         async fn content_shares_attach_link(
             &self,
             _content_id: uuid::Uuid,
@@ -542,7 +1140,7 @@ mod tests {
 
         async fn groups_get_by_share_token(
             &self,
-            _token: &str,
+            token: &str,
         ) -> Result<
             Option<(
                 crate::database::ContentGroupRow,
@@ -551,7 +1149,22 @@ mod tests {
             )>,
             crate::database::SqlStorageError,
         > {
-            Ok(None)
+            let Some((group_id, share_link)) =
+                self.group_share_links.lock().unwrap().get(token).cloned()
+            else {
+                return Ok(None);
+            };
+            let Some(group) = self.groups.lock().unwrap().get(&group_id).cloned() else {
+                return Ok(None);
+            };
+            let file_count = self
+                .group_items
+                .lock()
+                .unwrap()
+                .iter()
+                .filter(|item| item.group_id == group_id)
+                .count() as i64;
+            Ok(Some((group, share_link, file_count)))
         }
 
         async fn content_shares_create_for_user(
@@ -592,6 +1205,98 @@ mod tests {
             ))
         }
 
+        async fn content_shares_list_for_users(
+            &self,
+            _content_id: uuid::Uuid,
+        ) -> Result<Vec<crate::database::UserShareRow>, crate::database::SqlStorageError> {
+            Ok(vec![])
+        }
+
+        async fn group_shares_list_for_users(
+            &self,
+            _group_id: uuid::Uuid,
+        ) -> Result<Vec<crate::database::UserShareRow>, crate::database::SqlStorageError> {
+            Ok(vec![])
+        }
+
+        async fn content_shares_delete_for_user(
+            &self,
+            _content_id: uuid::Uuid,
+            _shared_with_user_id: uuid::Uuid,
+        ) -> Result<bool, crate::database::SqlStorageError> {
+            Ok(false)
+        }
+
+        async fn group_shares_delete_for_user(
+            &self,
+            _group_id: uuid::Uuid,
+            _shared_with_user_id: uuid::Uuid,
+        ) -> Result<bool, crate::database::SqlStorageError> {
+            Ok(false)
+        }
+
+        async fn shares_list_shared_with_user(
+            &self,
+            _user_id: uuid::Uuid,
+        ) -> Result<Vec<crate::database::SharedWithUserRow>, crate::database::SqlStorageError>
+        {
+            Ok(vec![])
+        }
+
+        async fn content_shares_permission_for_user(
+            &self,
+            _content_id: uuid::Uuid,
+            _user_id: uuid::Uuid,
+        ) -> Result<Option<String>, crate::database::SqlStorageError> {
+            Ok(None)
+        }
+
+        async fn audit_logs_insert(
+            &self,
+            entry: crate::database::AuditLogInsert,
+        ) -> Result<(), crate::database::SqlStorageError> {
+            self.audit_logs
+                .lock()
+                .unwrap()
+                .push(crate::database::AuditLogRow {
+                    id: uuid::Uuid::new_v4(),
+                    user_id: entry.user_id,
+                    action: entry.action,
+                    entity_type: entry.entity_type,
+                    entity_id: entry.entity_id,
+                    details: entry.details,
+                    ip_address: entry.ip_address,
+                    created_at: chrono::Utc::now(),
+                });
+            Ok(())
+        }
+
+        async fn audit_logs_list(
+            &self,
+            params: crate::database::AuditLogsListParams,
+        ) -> Result<Vec<crate::database::AuditLogRow>, crate::database::SqlStorageError> {
+            let limit = if params.limit <= 0 { 50 } else { params.limit };
+            let logs = self.audit_logs.lock().unwrap();
+            Ok(logs
+                .iter()
+                .rev()
+                .filter(|row| params.user_id.is_none_or(|id| row.user_id == Some(id)))
+                .filter(|row| params.action.as_ref().is_none_or(|a| &row.action == a))
+                .filter(|row| {
+                    params
+                        .entity_type
+                        .as_ref()
+                        .is_none_or(|t| &row.entity_type == t)
+                })
+                .filter(|row| params.entity_id.is_none_or(|id| row.entity_id == Some(id)))
+                .filter(|row| params.created_after.is_none_or(|t| row.created_at >= t))
+                .filter(|row| params.created_before.is_none_or(|t| row.created_at < t))
+                .skip(params.offset.max(0) as usize)
+                .take(limit as usize)
+                .cloned()
+                .collect())
+        }
+
         async fn otp_record_attempt(
             &self,
             _input: crate::database::OtpAttemptRecord,
@@ -614,8 +1319,19 @@ mod tests {
             &self,
             input: crate::database::UploadInsert,
         ) -> Result<crate::database::UploadRow, crate::database::SqlStorageError> {
+            let quota = self.users_get_storage_quota(input.user_id).await?;
+            if let Some(quota) = quota {
+                let used = self
+                    .users_storage_usage(input.user_id, chrono::Utc::now())
+                    .await?
+                    .total_bytes();
+                if used + input.file_size > quota {
+                    return Err(crate::database::SqlStorageError::QuotaExceeded { used, quota });
+                }
+            }
+
             // Return a mock upload row based on the input
-            Ok(crate::database::UploadRow {
+            let row = crate::database::UploadRow {
                 id: uuid::Uuid::new_v4(),
                 user_id: input.user_id,
                 storage_backend: input.storage_backend,
@@ -627,67 +1343,334 @@ mod tests {
                 expires_at: input.expires_at,
                 created_at: chrono::Utc::now(),
                 completed_at: None,
-            })
+                multipart_upload_id: input.multipart_upload_id,
+                part_size: input.part_size,
+                sha256: input.sha256,
+            };
+            self.uploads
+                .lock()
+                .expect("lock poisoned")
+                .insert(row.id, row.clone());
+            Ok(row)
         }
 
         async fn uploads_get(
             &self,
-            _id: uuid::Uuid,
+            id: uuid::Uuid,
         ) -> Result<Option<crate::database::UploadRow>, crate::database::SqlStorageError> {
-            Ok(None)
+            Ok(self
+                .uploads
+                .lock()
+                .expect("lock poisoned")
+                .get(&id)
+                .cloned())
         }
 
         async fn uploads_complete(
             &self,
-            _id: uuid::Uuid,
-            _user_id: uuid::Uuid,
+            id: uuid::Uuid,
+            user_id: uuid::Uuid,
         ) -> Result<Option<crate::database::UploadRow>, crate::database::SqlStorageError> {
-            Ok(None)
+            self.set_upload_status(id, user_id, "completed")
         }
 
-        async fn revoked_tokens_add(
+        async fn uploads_abort(
             &self,
-            _token_hash: &str,
-            _username: &str,
-            _expires_at: chrono::DateTime<chrono::Utc>,
+            id: uuid::Uuid,
+            user_id: uuid::Uuid,
+        ) -> Result<Option<crate::database::UploadRow>, crate::database::SqlStorageError> {
+            self.set_upload_status(id, user_id, "aborted")
+        }
+
+        async fn uploads_expire_stale(
+            &self,
+            now: chrono::DateTime<chrono::Utc>,
+            limit: i64,
+        ) -> Result<Vec<crate::database::UploadRow>, crate::database::SqlStorageError> {
+            let mut uploads = self.uploads.lock().expect("lock poisoned");
+            Ok(uploads
+                .values_mut()
+                .filter(|u| u.status == "initiated" && u.expires_at < now)
+                .take(limit as usize)
+                .map(|u| {
+                    u.status = "expired".to_owned();
+                    u.clone()
+                })
+                .collect())
+        }
+
+        async fn upload_parts_create(
+            &self,
+            upload_id: uuid::Uuid,
+            parts: Vec<crate::database::UploadPartInsert>,
         ) -> Result<(), crate::database::SqlStorageError> {
-            // Mock: silently succeed
+            let now = chrono::Utc::now();
+            self.upload_parts
+                .lock()
+                .expect("lock poisoned")
+                .extend(parts.into_iter().map(|p| crate::database::UploadPartRow {
+                    upload_id,
+                    part_number: p.part_number,
+                    size: p.size,
+                    etag: None,
+                    status: "pending".to_owned(),
+                    created_at: now,
+                    updated_at: now,
+                }));
             Ok(())
         }
 
-        async fn revoked_tokens_is_revoked(
+        async fn upload_parts_list(
             &self,
-            _token_hash: &str,
-        ) -> Result<bool, crate::database::SqlStorageError> {
-            // Mock: tokens are never revoked
-            Ok(false)
+            upload_id: uuid::Uuid,
+        ) -> Result<Vec<crate::database::UploadPartRow>, crate::database::SqlStorageError> {
+            Ok(self
+                .upload_parts
+                .lock()
+                .expect("lock poisoned")
+                .iter()
+                .filter(|p| p.upload_id == upload_id)
+                .cloned()
+                .collect())
         }
-    }
 
-    #[tokio::test]
-    async fn test_health_check_connected() {
-        let sql_storage = MockSqlStorage::new();
-        let user_storage = MockUserStorage::new();
-        let config = Config::new_for_test();
-        let app = routes(sql_storage, user_storage, config).await;
+        async fn upload_parts_mark_uploaded(
+            &self,
+            upload_id: uuid::Uuid,
+            part_number: i32,
+            etag: String,
+        ) -> Result<Option<crate::database::UploadPartRow>, crate::database::SqlStorageError>
+        {
+            let mut parts = self.upload_parts.lock().expect("lock poisoned");
+            Ok(parts
+                .iter_mut()
+                .find(|p| p.upload_id == upload_id && p.part_number == part_number)
+                .map(|p| {
+                    p.etag = Some(etag);
+                    p.status = "uploaded".to_owned();
+                    p.clone()
+                }))
+        }
 
-        let response = app
-            .oneshot(
-                Request::builder()
-                    .uri("/is-health")
-                    .body(Body::empty())
-                    .unwrap(),
-            )
-            .await
-            .unwrap();
+        async fn contents_share_storage_object(
+            &self,
+            content_id: uuid::Uuid,
+            input: crate::database::StorageObjectInsert,
+        ) -> Result<
+            Option<(
+                crate::database::StorageObjectRow,
+                crate::database::ContentRow,
+            )>,
+            crate::database::SqlStorageError,
+        > {
+            let mut contents = self.contents.lock().expect("lock poisoned");
+            let Some(content) = contents.get_mut(&content_id).filter(|c| c.sha256.is_none()) else {
+                return Ok(None);
+            };
+            let now = chrono::Utc::now();
+            let mut objects = self.storage_objects.lock().expect("lock poisoned");
+            let object = objects
+                .entry((input.user_id, input.sha256.clone()))
+                .and_modify(|o| {
+                    o.ref_count += 1;
+                    o.updated_at = now;
+                })
+                .or_insert_with(|| crate::database::StorageObjectRow {
+                    id: uuid::Uuid::new_v4(),
+                    user_id: input.user_id,
+                    sha256: input.sha256,
+                    storage_backend: input.storage_backend,
+                    storage_profile: input.storage_profile,
+                    storage_key: input.storage_key,
+                    file_size: input.file_size,
+                    ref_count: 1,
+                    created_at: now,
+                    updated_at: now,
+                });
+            content.storage_backend = object.storage_backend.clone();
+            content.storage_profile = object.storage_profile.clone();
+            content.storage_key = object.storage_key.clone();
+            content.sha256 = Some(object.sha256.clone());
+            content.updated_at = now;
+            Ok(Some((object.clone(), content.clone())))
+        }
 
-        assert_eq!(response.status(), StatusCode::OK);
-    }
+        async fn storage_objects_release(
+            &self,
+            user_id: uuid::Uuid,
+            sha256: &str,
+        ) -> Result<Option<crate::database::StorageObjectRow>, crate::database::SqlStorageError>
+        {
+            let mut objects = self.storage_objects.lock().expect("lock poisoned");
+            let key = (user_id, sha256.to_owned());
+            let Some(object) = objects.get_mut(&key) else {
+                return Ok(None);
+            };
+            object.ref_count = (object.ref_count - 1).max(0);
+            let object = object.clone();
+            if object.ref_count == 0 {
+                objects.remove(&key);
+            }
+            Ok(Some(object))
+        }
 
-    // Helper to generate a valid test token
-    fn generate_test_token() -> String {
-        crate::users::otp::generate_session_token(
-            "testuser",
+        async fn users_storage_usage(
+            &self,
+            user_id: uuid::Uuid,
+            now: chrono::DateTime<chrono::Utc>,
+        ) -> Result<crate::database::StorageUsage, crate::database::SqlStorageError> {
+            let trashed = self.trashed_contents.lock().expect("lock poisoned");
+            let uploads = self.uploads.lock().expect("lock poisoned");
+            Ok(crate::database::StorageUsage {
+                trashed_bytes: trashed
+                    .iter()
+                    .filter(|c| c.user_id == user_id)
+                    .map(|c| c.file_size)
+                    .sum(),
+                pending_bytes: uploads
+                    .values()
+                    .filter(|u| u.user_id == user_id && u.status == "initiated")
+                    .filter(|u| u.expires_at > now)
+                    .map(|u| u.file_size)
+                    .sum(),
+                ..Default::default()
+            })
+        }
+
+        async fn users_get_storage_quota(
+            &self,
+            user_id: uuid::Uuid,
+        ) -> Result<Option<i64>, crate::database::SqlStorageError> {
+            let quotas = self.storage_quotas.lock().expect("lock poisoned");
+            Ok(quotas.get(&user_id).copied())
+        }
+
+        async fn users_set_storage_quota(
+            &self,
+            user_id: uuid::Uuid,
+            quota_bytes: Option<i64>,
+        ) -> Result<bool, crate::database::SqlStorageError> {
+            let mut quotas = self.storage_quotas.lock().expect("lock poisoned");
+            match quota_bytes {
+                Some(quota) => quotas.insert(user_id, quota),
+                None => quotas.remove(&user_id),
+            };
+            Ok(true)
+        }
+
+        async fn share_links_targets_for_owner(
+            &self,
+            _owner_id: uuid::Uuid,
+        ) -> Result<Vec<crate::database::ShareLinkTargetRow>, crate::database::SqlStorageError>
+        {
+            Ok(vec![])
+        }
+
+        async fn account_exports_create(
+            &self,
+            user_id: uuid::Uuid,
+            expires_at: chrono::DateTime<chrono::Utc>,
+        ) -> Result<crate::database::AccountExportRow, crate::database::SqlStorageError> {
+            let export = crate::database::AccountExportRow {
+                id: uuid::Uuid::new_v4(),
+                user_id,
+                status: "pending".to_owned(),
+                manifest: None,
+                error: None,
+                created_at: chrono::Utc::now(),
+                completed_at: None,
+                expires_at,
+            };
+            self.account_exports
+                .lock()
+                .unwrap()
+                .insert(export.id, export.clone());
+            Ok(export)
+        }
+
+        async fn account_exports_get(
+            &self,
+            id: uuid::Uuid,
+        ) -> Result<Option<crate::database::AccountExportRow>, crate::database::SqlStorageError>
+        {
+            Ok(self.account_exports.lock().unwrap().get(&id).cloned())
+        }
+
+        async fn account_exports_complete(
+            &self,
+            id: uuid::Uuid,
+            manifest: serde_json::Value,
+        ) -> Result<Option<crate::database::AccountExportRow>, crate::database::SqlStorageError>
+        {
+            let mut exports = self.account_exports.lock().unwrap();
+            let Some(export) = exports.get_mut(&id).filter(|e| e.status == "pending") else {
+                return Ok(None);
+            };
+            export.status = "completed".to_owned();
+            export.manifest = Some(manifest);
+            export.completed_at = Some(chrono::Utc::now());
+            Ok(Some(export.clone()))
+        }
+
+        async fn account_exports_fail(
+            &self,
+            id: uuid::Uuid,
+            error: &str,
+        ) -> Result<Option<crate::database::AccountExportRow>, crate::database::SqlStorageError>
+        {
+            let mut exports = self.account_exports.lock().unwrap();
+            let Some(export) = exports.get_mut(&id).filter(|e| e.status == "pending") else {
+                return Ok(None);
+            };
+            export.status = "failed".to_owned();
+            export.error = Some(error.to_owned());
+            export.completed_at = Some(chrono::Utc::now());
+            Ok(Some(export.clone()))
+        }
+
+        async fn revoked_tokens_add(
+            &self,
+            _token_hash: &str,
+            _username: &str,
+            _expires_at: chrono::DateTime<chrono::Utc>,
+        ) -> Result<(), crate::database::SqlStorageError> {
+            // Mock: silently succeed
+            Ok(())
+        }
+
+        async fn revoked_tokens_is_revoked(
+            &self,
+            _token_hash: &str,
+        ) -> Result<bool, crate::database::SqlStorageError> {
+            // Mock: tokens are never revoked
+            Ok(false)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_health_check_connected() {
+        let sql_storage = MockSqlStorage::new();
+        let user_storage = MockUserStorage::new();
+        let config = Config::new_for_test();
+        let app = routes(sql_storage, user_storage, config).await;
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/is-health")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    // Helper to generate a valid test token
+    fn generate_test_token() -> String {
+        crate::users::otp::generate_session_token(
+            "testuser",
             "test-jwt-secret-key-for-local-development",
         )
         .unwrap()
@@ -719,7 +1702,7 @@ mod tests {
     #[tokio::test]
     async fn test_v1_me_with_valid_auth() {
         let sql_storage = MockSqlStorage::new();
-        let user_storage = MockUserStorage::new();
+        let user_storage = create_test_user_storage();
         let config = Config::new_for_test();
         let app = routes(sql_storage, user_storage, config).await;
 
@@ -743,43 +1726,1049 @@ mod tests {
             .await
             .unwrap();
         let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
-        assert_eq!(json["username"], "testuser");
-    }
+        assert_eq!(json["username"], "testuser");
+        assert_eq!(json["storage"]["used_bytes"], 0);
+        assert!(json["storage"]["quota_bytes"].is_null());
+    }
+
+    #[tokio::test]
+    async fn test_v1_uploads_init_without_auth_returns_401() {
+        let sql_storage = MockSqlStorage::new();
+        let user_storage = MockUserStorage::new();
+        let config = Config::new_for_test();
+        let app = routes(sql_storage, user_storage, config).await;
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/v1/uploads/init")
+                    .header("content-type", "application/json")
+                    .body(Body::from(
+                        r#"{"filename":"photo.jpg","content_type":"image/jpeg","file_size":1234}"#,
+                    ))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        // Without auth token, should return 401 Unauthorized
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn test_v1_uploads_init_with_valid_auth() {
+        let sql_storage = MockSqlStorage::with_user_id(TEST_USER_ID);
+        let user_storage = create_test_user_storage();
+        let config = Config::new_for_test();
+        let app = routes(sql_storage, user_storage, config).await;
+
+        let token = generate_test_token();
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/v1/uploads/init")
+                    .header("content-type", "application/json")
+                    .header("Authorization", format!("Bearer {}", token))
+                    .body(Body::from(
+                        r#"{"filename":"photo.jpg","content_type":"image/jpeg","file_size":1234}"#,
+                    ))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::CREATED);
+
+        // Verify we return a real R2 presigned URL (not a mock placeholder)
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        let upload_url = json["upload_url"].as_str().unwrap_or_default();
+        assert!(upload_url.contains("r2.cloudflarestorage.com"));
+        assert!(!upload_url.contains("test.r2.example.com"));
+        assert!(!upload_url.contains("mock=true"));
+    }
+
+    #[tokio::test]
+    async fn test_v1_uploads_init_with_local_storage() {
+        let dir = tempfile::tempdir().unwrap();
+        let sql_storage = MockSqlStorage::with_user_id(TEST_USER_ID);
+        let user_storage = create_test_user_storage();
+        let config = Config::new_for_test_local_storage(dir.path().to_string_lossy());
+        let app = routes(sql_storage, user_storage, config).await;
+
+        let token = generate_test_token();
+
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/v1/uploads/init")
+                    .header("content-type", "application/json")
+                    .header("Authorization", format!("Bearer {}", token))
+                    .body(Body::from(
+                        r#"{"filename":"notes.txt","content_type":"text/plain","file_size":5}"#,
+                    ))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::CREATED);
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        let upload_url = json["upload_url"].as_str().unwrap_or_default();
+        assert!(upload_url.starts_with("http://127.0.0.1:8080/storage/local/"));
+
+        // The signed URL is served by the same router
+        let path_and_query = upload_url.trim_start_matches("http://127.0.0.1:8080");
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method("PUT")
+                    .uri(path_and_query)
+                    .header("content-type", "text/plain")
+                    .body(Body::from("hello"))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let storage_key = json["storage_key"].as_str().unwrap();
+        assert!(dir.path().join(storage_key).exists());
+    }
+
+    /// Sends an authenticated request and returns the status and JSON body (if any).
+    async fn send_json(
+        app: &Router,
+        method: &str,
+        uri: &str,
+        body: serde_json::Value,
+    ) -> (StatusCode, serde_json::Value) {
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .method(method)
+                    .uri(uri)
+                    .header("content-type", "application/json")
+                    .header("Authorization", format!("Bearer {}", generate_test_token()))
+                    .body(Body::from(body.to_string()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        let status = response.status();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        (
+            status,
+            serde_json::from_slice(&body).unwrap_or(serde_json::Value::Null),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_v1_multipart_upload_with_local_storage() {
+        let dir = tempfile::tempdir().unwrap();
+        let sql_storage = MockSqlStorage::with_user_id(TEST_USER_ID);
+        let user_storage = create_test_user_storage();
+        let config = Config::new_for_test_local_storage(dir.path().to_string_lossy());
+        let app = routes(sql_storage, user_storage, config).await;
+
+        let part_size = crate::storage::MIN_PART_SIZE as usize;
+        let data: Vec<u8> = (0..part_size + 1024).map(|i| (i % 251) as u8).collect();
+
+        let (status, init) = send_json(
+            &app,
+            "POST",
+            "/v1/uploads/multipart/init",
+            serde_json::json!({
+                "filename": "big.bin",
+                "content_type": "application/octet-stream",
+                "file_size": data.len(),
+                "part_size": part_size,
+            }),
+        )
+        .await;
+        assert_eq!(status, StatusCode::CREATED);
+        assert_eq!(init["part_count"], 2);
+        let upload_id = init["upload_id"].as_str().unwrap();
+
+        let (status, parts) = send_json(
+            &app,
+            "POST",
+            &format!("/v1/uploads/{upload_id}/parts"),
+            serde_json::json!({}),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        let parts = parts["parts"].as_array().unwrap();
+        assert_eq!(parts.len(), 2);
+        assert_eq!(parts[1]["size"], 1024);
+
+        let mut etags = Vec::new();
+        for (part, chunk) in parts.iter().zip(data.chunks(part_size)) {
+            let url = part["upload_url"].as_str().unwrap();
+            let response = app
+                .clone()
+                .oneshot(
+                    Request::builder()
+                        .method("PUT")
+                        .uri(url.trim_start_matches("http://127.0.0.1:8080"))
+                        .body(Body::from(chunk.to_vec()))
+                        .unwrap(),
+                )
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+            etags.push(response.headers()["etag"].to_str().unwrap().to_owned());
+        }
+
+        // Record the first part now; the second is reported on completion
+        let (status, part) = send_json(
+            &app,
+            "PUT",
+            &format!("/v1/uploads/{upload_id}/parts/1"),
+            serde_json::json!({ "etag": etags[0] }),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(part["status"], "uploaded");
+
+        let (_, state) = send_json(
+            &app,
+            "GET",
+            &format!("/v1/uploads/{upload_id}"),
+            serde_json::Value::Null,
+        )
+        .await;
+        assert_eq!(state["multipart"], true);
+        assert_eq!(state["parts"][0]["status"], "uploaded");
+        assert_eq!(state["parts"][1]["status"], "pending");
+
+        // Completing with a part missing is rejected
+        let (status, _) = send_json(
+            &app,
+            "POST",
+            "/v1/uploads/complete",
+            serde_json::json!({ "upload_id": upload_id }),
+        )
+        .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+
+        let (status, completed) = send_json(
+            &app,
+            "POST",
+            "/v1/uploads/complete",
+            serde_json::json!({
+                "upload_id": upload_id,
+                "parts": [{ "part_number": 2, "etag": etags[1] }],
+            }),
+        )
+        .await;
+        assert_eq!(status, StatusCode::CREATED);
+        assert_eq!(completed["content"]["title"], "big.bin");
+
+        let storage_key = init["storage_key"].as_str().unwrap();
+        assert_eq!(std::fs::read(dir.path().join(storage_key)).unwrap(), data);
+    }
+
+    #[tokio::test]
+    async fn test_v1_multipart_upload_abort() {
+        let dir = tempfile::tempdir().unwrap();
+        let sql_storage = MockSqlStorage::with_user_id(TEST_USER_ID);
+        let user_storage = create_test_user_storage();
+        let config = Config::new_for_test_local_storage(dir.path().to_string_lossy());
+        let app = routes(sql_storage, user_storage, config).await;
+
+        let (status, init) = send_json(
+            &app,
+            "POST",
+            "/v1/uploads/multipart/init",
+            serde_json::json!({
+                "filename": "big.bin",
+                "content_type": "application/octet-stream",
+                "file_size": 100,
+            }),
+        )
+        .await;
+        assert_eq!(status, StatusCode::CREATED);
+        assert_eq!(init["part_count"], 1);
+        let upload_id = init["upload_id"].as_str().unwrap();
+
+        let (status, _) = send_json(
+            &app,
+            "POST",
+            &format!("/v1/uploads/{upload_id}/abort"),
+            serde_json::Value::Null,
+        )
+        .await;
+        assert_eq!(status, StatusCode::NO_CONTENT);
+
+        let (_, state) = send_json(
+            &app,
+            "GET",
+            &format!("/v1/uploads/{upload_id}"),
+            serde_json::Value::Null,
+        )
+        .await;
+        assert_eq!(state["status"], "aborted");
+
+        let (status, _) = send_json(
+            &app,
+            "POST",
+            &format!("/v1/uploads/{upload_id}/parts"),
+            serde_json::json!({}),
+        )
+        .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_v1_multipart_upload_rejects_invalid_part_size() {
+        let dir = tempfile::tempdir().unwrap();
+        let sql_storage = MockSqlStorage::with_user_id(TEST_USER_ID);
+        let user_storage = create_test_user_storage();
+        let config = Config::new_for_test_local_storage(dir.path().to_string_lossy());
+        let app = routes(sql_storage, user_storage, config).await;
+
+        let (status, _) = send_json(
+            &app,
+            "POST",
+            "/v1/uploads/multipart/init",
+            serde_json::json!({
+                "filename": "big.bin",
+                "content_type": "application/octet-stream",
+                "file_size": 100,
+                "part_size": 1024,
+            }),
+        )
+        .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+
+    /// SHA-256 of `hello`.
+    const HELLO_SHA256: &str = "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824";
+
+    /// Uploads `data` to local storage through init (with `init` as the
+    /// request body), PUT and complete.
+    ///
+    /// Returns the init response and the completion status and body.
+    async fn upload_local_file(
+        app: &Router,
+        init: serde_json::Value,
+        data: &[u8],
+    ) -> (serde_json::Value, StatusCode, serde_json::Value) {
+        let content_type = init["content_type"].as_str().unwrap().to_owned();
+        let (status, init) = send_json(app, "POST", "/v1/uploads/init", init).await;
+        assert_eq!(status, StatusCode::CREATED);
+
+        let upload_url = init["upload_url"].as_str().unwrap();
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .method("PUT")
+                    .uri(upload_url.trim_start_matches("http://127.0.0.1:8080"))
+                    .header("content-type", content_type)
+                    .body(Body::from(data.to_vec()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let (status, completed) = send_json(
+            app,
+            "POST",
+            "/v1/uploads/complete",
+            serde_json::json!({ "upload_id": init["upload_id"] }),
+        )
+        .await;
+        (init, status, completed)
+    }
+
+    /// Init request body for a text file holding `hello`.
+    fn hello_upload(filename: &str, sha256: &str) -> serde_json::Value {
+        serde_json::json!({
+            "filename": filename,
+            "content_type": "text/plain",
+            "file_size": 5,
+            "sha256": sha256,
+        })
+    }
+
+    #[tokio::test]
+    async fn test_v1_uploads_deduplicate_identical_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let sql_storage = MockSqlStorage::with_user_id(TEST_USER_ID);
+        let user_storage = create_test_user_storage();
+        let config = Config::new_for_test_local_storage(dir.path().to_string_lossy());
+        let app = routes(sql_storage.clone(), user_storage, config).await;
+
+        // Uppercase digests are accepted and normalized
+        let (first_init, status, first) = upload_local_file(
+            &app,
+            hello_upload("a.txt", &HELLO_SHA256.to_uppercase()),
+            b"hello",
+        )
+        .await;
+        assert_eq!(status, StatusCode::CREATED);
+        assert_eq!(first["content"]["sha256"], HELLO_SHA256);
+        assert_eq!(first["content"]["storage_key"], first_init["storage_key"]);
+
+        // The duplicate points at the shared object
+        let (second_init, status, second) =
+            upload_local_file(&app, hello_upload("b.txt", HELLO_SHA256), b"hello").await;
+        assert_eq!(status, StatusCode::CREATED);
+        assert_eq!(second["content"]["title"], "b.txt");
+        assert_eq!(second["content"]["storage_key"], first_init["storage_key"]);
+
+        // The duplicate copy is deleted and the shared object has two references
+        let first_key = first_init["storage_key"].as_str().unwrap();
+        let second_key = second_init["storage_key"].as_str().unwrap();
+        assert!(dir.path().join(first_key).exists());
+        assert!(!dir.path().join(second_key).exists());
+        let objects = sql_storage.storage_objects.lock().unwrap();
+        let object = &objects[&(TEST_USER_ID, HELLO_SHA256.to_owned())];
+        assert_eq!(object.ref_count, 2);
+        assert_eq!(object.storage_key, first_key);
+    }
+
+    #[tokio::test]
+    async fn test_v1_uploads_complete_rejects_checksum_mismatch() {
+        let dir = tempfile::tempdir().unwrap();
+        let sql_storage = MockSqlStorage::with_user_id(TEST_USER_ID);
+        let user_storage = create_test_user_storage();
+        let config = Config::new_for_test_local_storage(dir.path().to_string_lossy());
+        let app = routes(sql_storage.clone(), user_storage, config).await;
+
+        let (init, status, body) =
+            upload_local_file(&app, hello_upload("a.txt", HELLO_SHA256), b"hellO").await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["error"], "checksum_mismatch");
+
+        // The upload is aborted, its file deleted and no content created
+        let storage_key = init["storage_key"].as_str().unwrap();
+        assert!(!dir.path().join(storage_key).exists());
+        let upload_id: uuid::Uuid = init["upload_id"].as_str().unwrap().parse().unwrap();
+        assert_eq!(
+            sql_storage.uploads.lock().unwrap()[&upload_id].status,
+            "aborted"
+        );
+        assert!(sql_storage.contents.lock().unwrap().is_empty());
+        assert!(sql_storage.storage_objects.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_v1_uploads_complete_rejects_size_mismatch() {
+        let dir = tempfile::tempdir().unwrap();
+        let sql_storage = MockSqlStorage::with_user_id(TEST_USER_ID);
+        let uploads = sql_storage.uploads.clone();
+        let user_storage = create_test_user_storage();
+        let config = Config::new_for_test_local_storage(dir.path().to_string_lossy());
+        let app = routes(sql_storage, user_storage, config).await;
+
+        let init = serde_json::json!({
+            "filename": "a.txt",
+            "content_type": "text/plain",
+            "file_size": 4,
+        });
+        let (init, status, body) = upload_local_file(&app, init, b"hello").await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["error"], "size_mismatch");
+
+        // The upload is aborted and its file deleted
+        let storage_key = init["storage_key"].as_str().unwrap();
+        assert!(!dir.path().join(storage_key).exists());
+        let upload_id: uuid::Uuid = init["upload_id"].as_str().unwrap().parse().unwrap();
+        assert_eq!(uploads.lock().unwrap()[&upload_id].status, "aborted");
+    }
+
+    #[tokio::test]
+    async fn test_v1_uploads_complete_rejects_content_type_mismatch() {
+        let dir = tempfile::tempdir().unwrap();
+        let sql_storage = MockSqlStorage::with_user_id(TEST_USER_ID);
+        let user_storage = create_test_user_storage();
+        let config = Config::new_for_test_local_storage(dir.path().to_string_lossy());
+        let app = routes(sql_storage, user_storage, config).await;
+
+        // Text declared as PNG
+        let init = serde_json::json!({
+            "filename": "a.png",
+            "content_type": "image/png",
+            "file_size": 5,
+        });
+        let (init, status, body) = upload_local_file(&app, init, b"hello").await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["error"], "content_type_mismatch");
+        let storage_key = init["storage_key"].as_str().unwrap();
+        assert!(!dir.path().join(storage_key).exists());
+
+        // A real PNG signature is accepted
+        let png: &[u8] = b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR";
+        let init = serde_json::json!({
+            "filename": "b.png",
+            "content_type": "image/png",
+            "file_size": png.len(),
+        });
+        let (_, status, _) = upload_local_file(&app, init, png).await;
+        assert_eq!(status, StatusCode::CREATED);
+    }
+
+    #[tokio::test]
+    async fn test_v1_uploads_init_rejects_invalid_sha256() {
+        let dir = tempfile::tempdir().unwrap();
+        let sql_storage = MockSqlStorage::with_user_id(TEST_USER_ID);
+        let user_storage = create_test_user_storage();
+        let config = Config::new_for_test_local_storage(dir.path().to_string_lossy());
+        let app = routes(sql_storage, user_storage, config).await;
+
+        let (status, body) = send_json(
+            &app,
+            "POST",
+            "/v1/uploads/init",
+            serde_json::json!({
+                "filename": "a.txt",
+                "content_type": "text/plain",
+                "file_size": 5,
+                "sha256": "not-a-digest",
+            }),
+        )
+        .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["error"], "bad_request");
+    }
+
+    #[tokio::test]
+    async fn test_v1_uploads_init_enforces_storage_quota() {
+        let dir = tempfile::tempdir().unwrap();
+        let sql_storage = MockSqlStorage::with_user_id(TEST_USER_ID);
+        let user_storage = create_test_user_storage();
+        let config = Config::new_for_test_local_storage(dir.path().to_string_lossy());
+        sql_storage
+            .users_set_storage_quota(TEST_USER_ID, Some(8))
+            .await
+            .unwrap();
+        let app = routes(sql_storage, user_storage, config).await;
+
+        let init = |filename: &str| {
+            serde_json::json!({
+                "filename": filename,
+                "content_type": "text/plain",
+                "file_size": 5,
+            })
+        };
+        let (status, _) = send_json(&app, "POST", "/v1/uploads/init", init("a.txt")).await;
+        assert_eq!(status, StatusCode::CREATED);
+
+        // The pending upload counts against the quota
+        let (status, body) = send_json(&app, "POST", "/v1/uploads/init", init("b.txt")).await;
+        assert_eq!(status, StatusCode::PAYLOAD_TOO_LARGE);
+        assert_eq!(body["error"], "quota_exceeded");
+        let (status, body) =
+            send_json(&app, "POST", "/v1/uploads/multipart/init", init("c.txt")).await;
+        assert_eq!(status, StatusCode::PAYLOAD_TOO_LARGE);
+        assert_eq!(body["error"], "quota_exceeded");
+
+        let (status, me) = send_json(&app, "GET", "/v1/me", serde_json::Value::Null).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(me["storage"]["pending_bytes"], 5);
+        assert_eq!(me["storage"]["used_bytes"], 5);
+        assert_eq!(me["storage"]["quota_bytes"], 8);
+    }
+
+    #[tokio::test]
+    async fn test_v1_uploads_complete_processes_media() {
+        let dir = tempfile::tempdir().unwrap();
+        let sql_storage = MockSqlStorage::with_user_id(TEST_USER_ID);
+        let user_storage = create_test_user_storage();
+        let config = Config::new_for_test_local_storage(dir.path().to_string_lossy());
+        let app = routes(sql_storage, user_storage, config).await;
+
+        let mut png = std::io::Cursor::new(Vec::new());
+        image::DynamicImage::new_rgb8(600, 300)
+            .write_to(&mut png, image::ImageFormat::Png)
+            .unwrap();
+        let png = png.into_inner();
+        let init = serde_json::json!({
+            "filename": "photo.png",
+            "content_type": "image/png",
+            "file_size": png.len(),
+        });
+        let (init, status, body) = upload_local_file(&app, init, &png).await;
+        assert_eq!(status, StatusCode::CREATED);
+        let content_id = body["content"]["id"].as_str().unwrap().to_owned();
+
+        // Thumbnails are generated in the background, next to the original
+        let storage_key = init["storage_key"].as_str().unwrap();
+        let largest = dir.path().join(format!("{storage_key}.thumb-1024.png"));
+        for _ in 0..100 {
+            if largest.exists() {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+        }
+        assert!(largest.exists());
+        assert!(
+            dir.path()
+                .join(format!("{storage_key}.thumb-128.png"))
+                .exists()
+        );
+
+        // Metadata is stored before the thumbnails
+        let (status, body) = send_json(
+            &app,
+            "GET",
+            &format!("/v1/contents/{content_id}"),
+            serde_json::Value::Null,
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(
+            body["metadata"],
+            serde_json::json!({ "width": 600, "height": 300 })
+        );
+
+        let view_url = |query: &str| format!("/v1/contents/{content_id}/view-url{query}");
+        let disposition = serde_json::json!({ "disposition": "inline" });
+        let (status, body) =
+            send_json(&app, "POST", &view_url("?size=128"), disposition.clone()).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["thumbnail_size"], 128);
+        assert!(body["url"].as_str().unwrap().contains("thumb-128.png"));
+
+        let (status, body) = send_json(&app, "POST", &view_url(""), disposition.clone()).await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(body.get("thumbnail_size").is_none());
+
+        let (status, _) = send_json(&app, "POST", &view_url("?size=100"), disposition).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_upload_sweeper_expires_stale_uploads() {
+        let dir = tempfile::tempdir().unwrap();
+        let sql_storage = MockSqlStorage::with_user_id(TEST_USER_ID);
+        let config = Config::new_for_test_local_storage(dir.path().to_string_lossy());
+        let router = storage_router(&config);
+        let now = chrono::Utc::now();
+
+        let mut stale_keys = Vec::new();
+        for (name, expires_at) in [
+            ("stale.txt", now - chrono::Duration::minutes(1)),
+            ("missing.txt", now - chrono::Duration::minutes(1)),
+            ("fresh.txt", now + chrono::Duration::minutes(10)),
+        ] {
+            let upload = sql_storage
+                .uploads_create(crate::database::UploadInsert {
+                    user_id: TEST_USER_ID,
+                    storage_backend: "local".to_owned(),
+                    storage_profile: DEFAULT_STORAGE_PROFILE.to_owned(),
+                    storage_key: format!("{TEST_USER_ID}/{name}"),
+                    content_type: "text/plain".to_owned(),
+                    file_size: 5,
+                    expires_at,
+                    multipart_upload_id: None,
+                    part_size: None,
+                    sha256: None,
+                })
+                .await
+                .unwrap();
+            if name != "missing.txt" {
+                let path = dir.path().join(&upload.storage_key);
+                std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+                std::fs::write(path, "hello").unwrap();
+            }
+            stale_keys.push((upload.id, upload.storage_key));
+        }
+
+        let report = crate::sweeper::UploadSweeper::new(sql_storage.clone(), router)
+            .sweep(now)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            report,
+            crate::sweeper::SweepReport {
+                expired_uploads: 2,
+                deleted_objects: 1,
+                aborted_multipart_uploads: 0,
+                failed_cleanups: 0,
+            }
+        );
+        let status = |id| {
+            let uploads = sql_storage.uploads.lock().unwrap();
+            uploads[&id].status.clone()
+        };
+        assert_eq!(status(stale_keys[0].0), "expired");
+        assert_eq!(status(stale_keys[1].0), "expired");
+        assert_eq!(status(stale_keys[2].0), "initiated");
+        assert!(!dir.path().join(&stale_keys[0].1).exists());
+        assert!(dir.path().join(&stale_keys[2].1).exists());
+    }
+
+    /// A trashed file content stored under `key` in the local storage at `dir`.
+    fn trashed_file(
+        dir: &std::path::Path,
+        key: &str,
+        sha256: Option<&str>,
+        trashed_at: chrono::DateTime<chrono::Utc>,
+    ) -> crate::database::ContentRow {
+        let path = dir.join(key);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, "hello").unwrap();
+        crate::database::ContentRow {
+            id: uuid::Uuid::new_v4(),
+            user_id: TEST_USER_ID,
+            title: key.to_owned(),
+            description: None,
+            storage_backend: "local".to_owned(),
+            storage_profile: DEFAULT_STORAGE_PROFILE.to_owned(),
+            storage_key: key.to_owned(),
+            content_type: "text/plain".to_owned(),
+            file_size: 5,
+            status: "trashed".to_owned(),
+            visibility: "private".to_owned(),
+            kind: "file".to_owned(),
+            body: None,
+            sha256: sha256.map(str::to_owned),
+            metadata: serde_json::json!({}),
+            trashed_at: Some(trashed_at),
+            archived_at: None,
+            created_at: trashed_at,
+            updated_at: trashed_at,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_trash_purger_respects_retention() {
+        let dir = tempfile::tempdir().unwrap();
+        let sql_storage = MockSqlStorage::with_user_id(TEST_USER_ID);
+        let config = Config::new_for_test_local_storage(dir.path().to_string_lossy());
+        let now = chrono::Utc::now();
+        let expired = now - chrono::Duration::days(31);
+
+        // Two expired contents sharing one deduplicated object
+        let shared_key = format!("{TEST_USER_ID}/shared.txt");
+        sql_storage.storage_objects.lock().unwrap().insert(
+            (TEST_USER_ID, HELLO_SHA256.to_owned()),
+            crate::database::StorageObjectRow {
+                id: uuid::Uuid::new_v4(),
+                user_id: TEST_USER_ID,
+                sha256: HELLO_SHA256.to_owned(),
+                storage_backend: "local".to_owned(),
+                storage_profile: DEFAULT_STORAGE_PROFILE.to_owned(),
+                storage_key: shared_key.clone(),
+                file_size: 5,
+                ref_count: 2,
+                created_at: now,
+                updated_at: now,
+            },
+        );
+        let recent_key = format!("{TEST_USER_ID}/recent.txt");
+        sql_storage.trashed_contents.lock().unwrap().extend([
+            trashed_file(dir.path(), &shared_key, Some(HELLO_SHA256), expired),
+            trashed_file(dir.path(), &shared_key, Some(HELLO_SHA256), expired),
+            trashed_file(
+                dir.path(),
+                &recent_key,
+                None,
+                now - chrono::Duration::days(1),
+            ),
+        ]);
+
+        let report = crate::purge::TrashPurger::new(
+            sql_storage.clone(),
+            storage_router(&config),
+            config.trash_retention(),
+        )
+        .purge_expired(now)
+        .await
+        .unwrap();
+
+        assert_eq!(
+            report,
+            crate::purge::PurgeReport {
+                purged_contents: 2,
+                purged_groups: 0,
+                deleted_objects: 1,
+                failed_cleanups: 0,
+            }
+        );
+        assert!(!dir.path().join(&shared_key).exists());
+        assert!(dir.path().join(&recent_key).exists());
+        assert!(sql_storage.storage_objects.lock().unwrap().is_empty());
+        assert_eq!(sql_storage.trashed_contents.lock().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_v1_trash_list_and_empty() {
+        let dir = tempfile::tempdir().unwrap();
+        let sql_storage = MockSqlStorage::with_user_id(TEST_USER_ID);
+        let user_storage = create_test_user_storage();
+        let config = Config::new_for_test_local_storage(dir.path().to_string_lossy());
+        let key = format!("{TEST_USER_ID}/trashed.txt");
+        sql_storage
+            .trashed_contents
+            .lock()
+            .unwrap()
+            .push(trashed_file(dir.path(), &key, None, chrono::Utc::now()));
+        let app = routes(sql_storage, user_storage, config).await;
+
+        let (status, body) = send_json(&app, "GET", "/v1/trash", serde_json::Value::Null).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["contents"].as_array().unwrap().len(), 1);
+        assert_eq!(body["contents"][0]["storage_key"], key.as_str());
+        assert_eq!(body["retention_days"], 30);
+
+        let (status, body) =
+            send_json(&app, "POST", "/v1/trash/empty", serde_json::Value::Null).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["purged_contents"], 1);
+        assert_eq!(body["purged_groups"], 0);
+        assert!(!dir.path().join(&key).exists());
+
+        let (_, body) = send_json(&app, "GET", "/v1/trash", serde_json::Value::Null).await;
+        assert!(body["contents"].as_array().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_upload_sweeper_aborts_multipart_uploads() {
+        let dir = tempfile::tempdir().unwrap();
+        let sql_storage = MockSqlStorage::with_user_id(TEST_USER_ID);
+        let user_storage = create_test_user_storage();
+        let config = Config::new_for_test_local_storage(dir.path().to_string_lossy());
+        let router = storage_router(&config);
+        let app = routes(sql_storage.clone(), user_storage, config).await;
+
+        let (status, init) = send_json(
+            &app,
+            "POST",
+            "/v1/uploads/multipart/init",
+            serde_json::json!({
+                "filename": "big.bin",
+                "content_type": "application/octet-stream",
+                "file_size": 100,
+            }),
+        )
+        .await;
+        assert_eq!(status, StatusCode::CREATED);
+        assert_eq!(
+            std::fs::read_dir(dir.path().join(".parts"))
+                .unwrap()
+                .count(),
+            1
+        );
+
+        // Sweep as if the session had expired
+        let report = crate::sweeper::UploadSweeper::new(sql_storage, router)
+            .sweep(chrono::Utc::now() + chrono::Duration::days(2))
+            .await
+            .unwrap();
+
+        assert_eq!(report.expired_uploads, 1);
+        assert_eq!(report.aborted_multipart_uploads, 1);
+        assert_eq!(report.failed_cleanups, 0);
+        assert_eq!(
+            std::fs::read_dir(dir.path().join(".parts"))
+                .unwrap()
+                .count(),
+            0
+        );
+
+        let (_, state) = send_json(
+            &app,
+            "GET",
+            &format!("/v1/uploads/{}", init["upload_id"].as_str().unwrap()),
+            serde_json::Value::Null,
+        )
+        .await;
+        assert_eq!(state["status"], "expired");
+    }
+
+    #[tokio::test]
+    async fn test_storage_routing_with_gcs_default_backend() {
+        let sql_storage = MockSqlStorage::with_user_id(TEST_USER_ID);
+        let user_storage = create_test_user_storage();
+        let config = Config::new_for_test_gcs("http://127.0.0.1:4443");
+        let app = routes(sql_storage, user_storage, config).await;
+
+        let token = generate_test_token();
+
+        // New uploads go to the default (GCS) backend
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/v1/uploads/init")
+                    .header("content-type", "application/json")
+                    .header("Authorization", format!("Bearer {}", token))
+                    .body(Body::from(
+                        r#"{"filename":"photo.jpg","content_type":"image/jpeg","file_size":1234}"#,
+                    ))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::CREATED);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        let upload_url = json["upload_url"].as_str().unwrap_or_default();
+        assert!(upload_url.starts_with("http://127.0.0.1:4443/test-bucket/"));
+
+        // Existing content recorded on R2 is still served by R2
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri(format!("/v1/contents/{}/view-url", TEST_CONTENT_ID))
+                    .header("content-type", "application/json")
+                    .header("Authorization", format!("Bearer {}", token))
+                    .body(Body::from(r#"{"disposition":"inline"}"#))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        let url = json["url"].as_str().unwrap_or_default();
+        assert!(url.contains("r2.cloudflarestorage.com"));
+    }
+
+    #[tokio::test]
+    async fn test_v1_uploads_init_selects_storage_profile() {
+        let dir = tempfile::tempdir().unwrap();
+        let sql_storage = MockSqlStorage::with_user_id(TEST_USER_ID);
+        let user_storage = create_test_user_storage();
+        let config = Config::new_for_test_storage_profiles(
+            dir.path(),
+            &[
+                ("images", Some(1024), &["image/*"]),
+                ("archives", None, &["application/zip"]),
+            ],
+            &[],
+        );
+        let app = routes(sql_storage, user_storage, config).await;
+
+        let token = generate_test_token();
+        let init = |body: &'static str| {
+            Request::builder()
+                .method("POST")
+                .uri("/v1/uploads/init")
+                .header("content-type", "application/json")
+                .header("Authorization", format!("Bearer {}", token))
+                .body(Body::from(body))
+                .unwrap()
+        };
+
+        // Images land in the images profile and are served from its directory
+        let response = app
+            .clone()
+            .oneshot(init(
+                r#"{"filename":"photo.png","content_type":"image/png","file_size":9}"#,
+            ))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::CREATED);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(json["storage_profile"], "images");
+        let upload_url = json["upload_url"].as_str().unwrap_or_default();
+        assert!(upload_url.starts_with("http://127.0.0.1:8080/storage/local/images/"));
+
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .method("PUT")
+                    .uri(upload_url.trim_start_matches("http://127.0.0.1:8080"))
+                    .header("content-type", "image/png")
+                    .body(Body::from("png bytes"))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let storage_key = json["storage_key"].as_str().unwrap();
+        assert!(dir.path().join("images").join(storage_key).exists());
+
+        // Other types fall back to the default profile
+        let response = app
+            .clone()
+            .oneshot(init(
+                r#"{"filename":"notes.txt","content_type":"text/plain","file_size":5}"#,
+            ))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::CREATED);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(json["storage_profile"], "default");
 
-    #[tokio::test]
-    async fn test_v1_uploads_init_without_auth_returns_401() {
-        let sql_storage = MockSqlStorage::new();
-        let user_storage = MockUserStorage::new();
-        let config = Config::new_for_test();
-        let app = routes(sql_storage, user_storage, config).await;
+        // Explicit profiles are checked against their limits
+        let response = app
+            .clone()
+            .oneshot(init(
+                r#"{"filename":"big.png","content_type":"image/png","file_size":4096,"storage_profile":"images"}"#,
+            ))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
 
         let response = app
-            .oneshot(
-                Request::builder()
-                    .method("POST")
-                    .uri("/v1/uploads/init")
-                    .header("content-type", "application/json")
-                    .body(Body::from(
-                        r#"{"filename":"photo.jpg","content_type":"image/jpeg","file_size":1234}"#,
-                    ))
-                    .unwrap(),
-            )
+            .clone()
+            .oneshot(init(
+                r#"{"filename":"notes.txt","content_type":"text/plain","file_size":5,"storage_profile":"archives"}"#,
+            ))
             .await
             .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
 
-        // Without auth token, should return 401 Unauthorized
-        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        let response = app
+            .oneshot(init(
+                r#"{"filename":"notes.txt","content_type":"text/plain","file_size":5,"storage_profile":"missing"}"#,
+            ))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
     }
 
     #[tokio::test]
-    async fn test_v1_uploads_init_with_valid_auth() {
+    async fn test_v1_uploads_init_uses_user_storage_profile() {
+        let dir = tempfile::tempdir().unwrap();
         let sql_storage = MockSqlStorage::with_user_id(TEST_USER_ID);
         let user_storage = create_test_user_storage();
-        let config = Config::new_for_test();
+        let config = Config::new_for_test_storage_profiles(
+            dir.path(),
+            &[("archives", None, &[])],
+            &[("testuser", "archives")],
+        );
         let app = routes(sql_storage, user_storage, config).await;
 
         let token = generate_test_token();
-
         let response = app
             .oneshot(
                 Request::builder()
@@ -788,7 +2777,7 @@ mod tests {
                     .header("content-type", "application/json")
                     .header("Authorization", format!("Bearer {}", token))
                     .body(Body::from(
-                        r#"{"filename":"photo.jpg","content_type":"image/jpeg","file_size":1234}"#,
+                        r#"{"filename":"notes.txt","content_type":"text/plain","file_size":5}"#,
                     ))
                     .unwrap(),
             )
@@ -796,16 +2785,11 @@ mod tests {
             .unwrap();
 
         assert_eq!(response.status(), StatusCode::CREATED);
-
-        // Verify we return a real R2 presigned URL (not a mock placeholder)
         let body = axum::body::to_bytes(response.into_body(), usize::MAX)
             .await
             .unwrap();
         let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
-        let upload_url = json["upload_url"].as_str().unwrap_or_default();
-        assert!(upload_url.contains("r2.cloudflarestorage.com"));
-        assert!(!upload_url.contains("test.r2.example.com"));
-        assert!(!upload_url.contains("mock=true"));
+        assert_eq!(json["storage_profile"], "archives");
     }
 
     #[tokio::test]
@@ -1003,6 +2987,1008 @@ mod tests {
         assert_eq!(RuntimeEnv::from(&config::Env::Nightly), RuntimeEnv::Nightly);
     }
 
+    // =========================================================================
+    // Search API Tests
+    // =========================================================================
+
+    #[tokio::test]
+    async fn test_v1_search_ranks_and_highlights() {
+        let sql_storage = MockSqlStorage::new();
+        let user_storage = create_test_user_storage();
+        let config = Config::new_for_test();
+        let app = routes(sql_storage, user_storage, config).await;
+
+        for (title, body) in [
+            ("Pasta", "Boil <pasta> for ten minutes"),
+            ("Shopping", "Milk, eggs"),
+            ("Pasta pasta", "More pasta"),
+        ] {
+            let note = serde_json::json!({
+                "title": title,
+                "body": body,
+                "content_type": "text/plain",
+            });
+            let (status, _) = send_json(&app, "POST", "/v1/contents", note).await;
+            assert_eq!(status, StatusCode::CREATED);
+        }
+
+        let (status, body) = send_json(
+            &app,
+            "GET",
+            "/v1/search?q=pasta&kind=text",
+            serde_json::Value::Null,
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["total"], 2);
+        assert_eq!(body["items"][0]["content"]["title"], "Pasta pasta");
+        assert_eq!(body["items"][1]["title_highlight"], "<mark>Pasta</mark>");
+        assert_eq!(
+            body["items"][1]["snippet"],
+            "Boil &lt;<mark>pasta</mark>&gt; for ten minutes"
+        );
+
+        for query in [
+            "q=",
+            "q=pasta&kind=image",
+            "q=pasta&status=gone",
+            "q=pasta&tags=nope",
+        ] {
+            let (status, _) = send_json(
+                &app,
+                "GET",
+                &format!("/v1/search?{query}"),
+                serde_json::Value::Null,
+            )
+            .await;
+            assert_eq!(status, StatusCode::BAD_REQUEST, "{query}");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_v1_contents_list_filters_sorts_and_pages() {
+        let sql_storage = MockSqlStorage::new();
+        let user_storage = create_test_user_storage();
+        let config = Config::new_for_test();
+        let app = routes(sql_storage, user_storage, config).await;
+
+        for title in ["Charlie", "Alpha", "Bravo"] {
+            let note = serde_json::json!({
+                "title": title,
+                "body": "text",
+                "content_type": "text/markdown",
+            });
+            let (status, _) = send_json(&app, "POST", "/v1/contents", note).await;
+            assert_eq!(status, StatusCode::CREATED);
+        }
+
+        let (status, body) = send_json(
+            &app,
+            "GET",
+            "/v1/contents?kind=text&content_type=text/&sort=title_asc&limit=2",
+            serde_json::Value::Null,
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["items"][0]["title"], "Alpha");
+        assert_eq!(body["items"][1]["title"], "Bravo");
+        let cursor = body["next_cursor"]
+            .as_str()
+            .expect("next_cursor")
+            .to_owned();
+
+        let (status, body) = send_json(
+            &app,
+            "GET",
+            &format!("/v1/contents?kind=text&sort=title_asc&limit=2&cursor={cursor}"),
+            serde_json::Value::Null,
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["total"], 1);
+        assert_eq!(body["items"][0]["title"], "Charlie");
+        assert!(body.get("next_cursor").is_none());
+
+        let (status, body) = send_json(
+            &app,
+            "GET",
+            "/v1/contents?content_type=image/",
+            serde_json::Value::Null,
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["total"], 0);
+
+        for query in [
+            "sort=size".to_owned(),
+            "kind=image".to_owned(),
+            "visibility=secret".to_owned(),
+            "tags=nope".to_owned(),
+            "cursor=garbage".to_owned(),
+            format!("sort=title_desc&cursor={cursor}"),
+        ] {
+            let (status, _) = send_json(
+                &app,
+                "GET",
+                &format!("/v1/contents?{query}"),
+                serde_json::Value::Null,
+            )
+            .await;
+            assert_eq!(status, StatusCode::BAD_REQUEST, "{query}");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_v1_contents_list_pages_in_creation_order() {
+        let sql_storage = MockSqlStorage::new();
+        let user_storage = create_test_user_storage();
+        let config = Config::new_for_test();
+        let app = routes(sql_storage.clone(), user_storage, config).await;
+
+        // Contents created before UUIDv7 ids have random ids
+        let now = chrono::Utc::now();
+        for (age, id) in [
+            "ffffffff-0000-4000-8000-000000000000",
+            "00000000-0000-4000-8000-000000000000",
+            "77777777-0000-4000-8000-000000000000",
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            let created_at = now - chrono::Duration::days(age as i64);
+            let content = crate::database::ContentRow {
+                id: id.parse().unwrap(),
+                user_id: TEST_USER_ID,
+                title: format!("{age} days old"),
+                description: None,
+                storage_backend: "local".to_owned(),
+                storage_profile: DEFAULT_STORAGE_PROFILE.to_owned(),
+                storage_key: String::new(),
+                content_type: "text/plain".to_owned(),
+                file_size: 4,
+                status: "active".to_owned(),
+                visibility: "private".to_owned(),
+                kind: "text".to_owned(),
+                body: Some("text".to_owned()),
+                sha256: None,
+                metadata: serde_json::json!({}),
+                trashed_at: None,
+                archived_at: None,
+                created_at,
+                updated_at: created_at,
+            };
+            sql_storage
+                .contents
+                .lock()
+                .unwrap()
+                .insert(content.id, content);
+        }
+
+        for (sort, expected) in [
+            (
+                "created_at_desc",
+                ["0 days old", "1 days old", "2 days old"],
+            ),
+            ("created_at_asc", ["2 days old", "1 days old", "0 days old"]),
+        ] {
+            let mut titles = Vec::new();
+            let mut cursor = String::new();
+            loop {
+                let (status, body) = send_json(
+                    &app,
+                    "GET",
+                    &format!("/v1/contents?sort={sort}&limit=2{cursor}"),
+                    serde_json::Value::Null,
+                )
+                .await;
+                assert_eq!(status, StatusCode::OK);
+                for item in body["items"].as_array().unwrap() {
+                    titles.push(item["title"].as_str().unwrap().to_owned());
+                }
+                match body["next_cursor"].as_str() {
+                    Some(next) => cursor = format!("&cursor={next}"),
+                    None => break,
+                }
+            }
+            assert_eq!(titles, expected, "{sort}");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_v1_contents_batch_reports_per_item_results() {
+        let sql_storage = MockSqlStorage::new();
+        let user_storage = create_test_user_storage();
+        let config = Config::new_for_test();
+        let app = routes(sql_storage, user_storage, config).await;
+
+        let mut ids = Vec::new();
+        for title in ["One", "Two"] {
+            let note = serde_json::json!({
+                "title": title,
+                "body": "text",
+                "content_type": "text/plain",
+            });
+            let (status, body) = send_json(&app, "POST", "/v1/contents", note).await;
+            assert_eq!(status, StatusCode::CREATED);
+            ids.push(
+                body["content"]["id"]
+                    .as_str()
+                    .expect("content id")
+                    .to_owned(),
+            );
+        }
+        let missing = uuid::Uuid::now_v7().to_string();
+
+        let request = serde_json::json!({
+            "ids": [ids[0], ids[1], missing, "not-a-uuid"],
+            "op": "trash",
+        });
+        let (status, body) = send_json(&app, "POST", "/v1/contents/batch", request).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["succeeded"], 2);
+        assert_eq!(body["failed"], 2);
+        let statuses: Vec<&str> = body["results"]
+            .as_array()
+            .expect("results")
+            .iter()
+            .map(|r| r["status"].as_str().expect("status"))
+            .collect();
+        assert_eq!(statuses, ["ok", "ok", "not_found", "invalid_id"]);
+
+        let (status, body) = send_json(&app, "GET", "/v1/trash", serde_json::Value::Null).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["contents"].as_array().map(Vec::len), Some(2));
+
+        for request in [
+            serde_json::json!({ "ids": [], "op": "trash" }),
+            serde_json::json!({ "ids": [ids[0]], "op": "delete" }),
+            serde_json::json!({ "ids": [ids[0]], "op": "tag" }),
+            serde_json::json!({ "ids": [ids[0]], "op": "add_to_group", "group_id": "x" }),
+        ] {
+            let (status, _) = send_json(&app, "POST", "/v1/contents/batch", request.clone()).await;
+            assert_eq!(status, StatusCode::BAD_REQUEST, "{request}");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_v1_content_revisions_list_diff_and_restore() {
+        let sql_storage = MockSqlStorage::new();
+        let user_storage = create_test_user_storage();
+        let config = Config::new_for_test();
+        let app = routes(sql_storage, user_storage, config).await;
+
+        let note = serde_json::json!({
+            "title": "Recipe",
+            "body": "flour\nwater",
+            "content_type": "text/plain",
+        });
+        let (status, body) = send_json(&app, "POST", "/v1/contents", note).await;
+        assert_eq!(status, StatusCode::CREATED);
+        let id = body["content"]["id"]
+            .as_str()
+            .expect("content id")
+            .to_owned();
+
+        for update in ["flour\nwater\nsalt", "flour\nsalt"] {
+            let (status, _) = send_json(
+                &app,
+                "PATCH",
+                &format!("/v1/contents/{id}"),
+                serde_json::json!({ "body": update }),
+            )
+            .await;
+            assert_eq!(status, StatusCode::OK);
+        }
+
+        let uri = format!("/v1/contents/{id}/revisions");
+        let (status, body) = send_json(&app, "GET", &uri, serde_json::Value::Null).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["total"], 2);
+        assert_eq!(body["items"][0]["revision"], 2);
+        assert_eq!(body["items"][0]["body"], "flour\nwater\nsalt");
+        assert_eq!(body["items"][1]["body"], "flour\nwater");
+
+        let uri = format!("/v1/contents/{id}/revisions/diff?from=1");
+        let (status, body) = send_json(&app, "GET", &uri, serde_json::Value::Null).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(
+            body["lines"],
+            serde_json::json!([
+                { "op": "equal", "text": "flour" },
+                { "op": "delete", "text": "water" },
+                { "op": "insert", "text": "salt" },
+            ])
+        );
+
+        let uri = format!("/v1/contents/{id}/revisions/1/restore");
+        let (status, body) = send_json(&app, "POST", &uri, serde_json::Value::Null).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["body"], "flour\nwater");
+
+        let uri = format!("/v1/contents/{id}/revisions");
+        let (_, body) = send_json(&app, "GET", &uri, serde_json::Value::Null).await;
+        assert_eq!(body["total"], 3);
+        assert_eq!(body["items"][0]["body"], "flour\nsalt");
+
+        let uri = format!("/v1/contents/{id}/revisions/9/restore");
+        let (status, _) = send_json(&app, "POST", &uri, serde_json::Value::Null).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_v1_contents_update_honors_if_match() {
+        let sql_storage = MockSqlStorage::new();
+        let user_storage = create_test_user_storage();
+        let config = Config::new_for_test();
+        let app = routes(sql_storage, user_storage, config).await;
+
+        let note = serde_json::json!({
+            "title": "Draft",
+            "body": "hello",
+            "content_type": "text/plain",
+        });
+        let (status, body) = send_json(&app, "POST", "/v1/contents", note).await;
+        assert_eq!(status, StatusCode::CREATED);
+        let uri = format!("/v1/contents/{}", body["content"]["id"].as_str().unwrap());
+
+        let request = |method: &str, if_match: Option<&str>, body: serde_json::Value| {
+            let mut builder = Request::builder()
+                .method(method)
+                .uri(&uri)
+                .header("content-type", "application/json")
+                .header("Authorization", format!("Bearer {}", generate_test_token()));
+            if let Some(if_match) = if_match {
+                builder = builder.header("If-Match", if_match);
+            }
+            builder.body(Body::from(body.to_string())).unwrap()
+        };
+
+        let response = app
+            .clone()
+            .oneshot(request("GET", None, serde_json::Value::Null))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let etag = response.headers()["etag"].to_str().unwrap().to_owned();
+
+        let response = app
+            .clone()
+            .oneshot(request(
+                "PATCH",
+                Some(&etag),
+                serde_json::json!({ "title": "First" }),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let new_etag = response.headers()["etag"].to_str().unwrap().to_owned();
+        assert_ne!(new_etag, etag);
+
+        // A second writer still holding the old ETag loses.
+        let response = app
+            .clone()
+            .oneshot(request(
+                "PATCH",
+                Some(&etag),
+                serde_json::json!({ "title": "Second" }),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::PRECONDITION_FAILED);
+
+        let (status, body) = send_json(&app, "GET", &uri, serde_json::Value::Null).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["title"], "First");
+    }
+
+    #[tokio::test]
+    async fn test_v1_groups_archive_zip_streams_contents_in_order() {
+        let dir = tempfile::tempdir().unwrap();
+        let sql_storage = MockSqlStorage::new();
+        let mut file = trashed_file(
+            dir.path(),
+            &format!("{TEST_USER_ID}/greeting.txt"),
+            Some(HELLO_SHA256),
+            chrono::Utc::now(),
+        );
+        file.status = "active".to_owned();
+        file.trashed_at = None;
+        let trashed = trashed_file(
+            dir.path(),
+            &format!("{TEST_USER_ID}/old.txt"),
+            Some(HELLO_SHA256),
+            chrono::Utc::now(),
+        );
+        for content in [&file, &trashed] {
+            sql_storage
+                .contents
+                .lock()
+                .unwrap()
+                .insert(content.id, content.clone());
+        }
+        let user_storage = create_test_user_storage();
+        let config = Config::new_for_test_local_storage(dir.path().to_string_lossy());
+        let app = routes(sql_storage.clone(), user_storage, config).await;
+
+        let note = serde_json::json!({
+            "title": "Packing list",
+            "body": "- tent",
+            "content_type": "text/markdown",
+        });
+        let (status, body) = send_json(&app, "POST", "/v1/contents", note).await;
+        assert_eq!(status, StatusCode::CREATED);
+        let note_id = uuid::Uuid::parse_str(body["content"]["id"].as_str().unwrap()).unwrap();
+
+        let now = chrono::Utc::now();
+        let group = crate::database::ContentGroupRow {
+            id: uuid::Uuid::new_v4(),
+            user_id: file.user_id,
+            name: "Trip".to_owned(),
+            description: None,
+            visibility: "private".to_owned(),
+            status: "active".to_owned(),
+            trashed_at: None,
+            archived_at: None,
+            created_at: now,
+            updated_at: now,
+        };
+        sql_storage
+            .groups
+            .lock()
+            .unwrap()
+            .insert(group.id, group.clone());
+        for (sort_order, content_id) in [(2, file.id), (0, note_id), (1, trashed.id)] {
+            sql_storage
+                .group_items
+                .lock()
+                .unwrap()
+                .push(crate::database::ContentGroupItemRow {
+                    id: uuid::Uuid::new_v4(),
+                    group_id: group.id,
+                    content_id,
+                    sort_order,
+                    added_at: now,
+                });
+        }
+        for (token, permission) in [("view-token", "view"), ("download-token", "download")] {
+            sql_storage.group_share_links.lock().unwrap().insert(
+                token.to_owned(),
+                (
+                    group.id,
+                    crate::database::ShareLinkRow {
+                        id: uuid::Uuid::new_v4(),
+                        owner_id: group.user_id,
+                        token: token.to_owned(),
+                        name: None,
+                        permission: permission.to_owned(),
+                        password_hash: None,
+                        max_access_count: None,
+                        access_count: 0,
+                        expires_at: None,
+                        is_active: true,
+                        created_at: now,
+                        updated_at: now,
+                    },
+                ),
+            );
+        }
+
+        let get = |uri: String, authorized: bool| {
+            let mut builder = Request::builder().uri(uri);
+            if authorized {
+                builder =
+                    builder.header("Authorization", format!("Bearer {}", generate_test_token()));
+            }
+            let app = app.clone();
+            let request = builder.body(Body::empty()).unwrap();
+            async move {
+                let response = app.oneshot(request).await.unwrap();
+                let status = response.status();
+                let headers = response.headers().clone();
+                let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+                    .await
+                    .unwrap();
+                (status, headers, body)
+            }
+        };
+        let find = |archive: &[u8], needle: &[u8]| {
+            archive
+                .windows(needle.len())
+                .position(|window| window == needle)
+        };
+
+        let (status, headers, archive) =
+            get(format!("/v1/groups/{}/archive.zip", group.id), true).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(headers["content-type"], "application/zip");
+        assert!(
+            headers["content-disposition"]
+                .to_str()
+                .unwrap()
+                .starts_with("attachment; filename=\"Trip.zip\"")
+        );
+        // Local header of the first entry, in group order
+        assert_eq!(&archive[..4], b"PK\x03\x04");
+        assert_eq!(&archive[30..45], b"Packing list.md");
+        let note_at = find(&archive, b"- tent").unwrap();
+        let file_at = find(&archive, b"greeting.txt").unwrap();
+        assert!(note_at < file_at);
+        assert!(find(&archive, b"hello").unwrap() > file_at);
+        assert!(find(&archive, b"old.txt").is_none());
+        // End of central directory record with two entries
+        let end = archive.len() - 22;
+        assert_eq!(&archive[end..end + 4], b"PK\x05\x06");
+        assert_eq!(
+            u16::from_le_bytes([archive[end + 10], archive[end + 11]]),
+            2
+        );
+
+        let (status, _, _) = get("/v1/public/share/view-token/archive.zip".to_owned(), false).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+
+        let (status, _, shared) = get(
+            "/v1/public/share/download-token/archive.zip".to_owned(),
+            false,
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(shared.len(), archive.len());
+
+        let (status, _, _) = get(format!("/v1/groups/{}/archive.zip", group.id), false).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn test_v1_exports_round_trip_through_import() {
+        let dir = tempfile::tempdir().unwrap();
+        let sql_storage = MockSqlStorage::new();
+        let mut file = trashed_file(
+            dir.path(),
+            &format!("{TEST_USER_ID}/upload/greeting.txt"),
+            Some(HELLO_SHA256),
+            chrono::Utc::now(),
+        );
+        file.status = "active".to_owned();
+        file.trashed_at = None;
+        sql_storage
+            .contents
+            .lock()
+            .unwrap()
+            .insert(file.id, file.clone());
+        let user_storage = create_test_user_storage();
+        let config = Config::new_for_test_local_storage(dir.path().to_string_lossy());
+        let app = routes(sql_storage.clone(), user_storage, config).await;
+
+        let note = serde_json::json!({
+            "title": "Packing list",
+            "body": "- tent",
+            "content_type": "text/markdown",
+        });
+        let (status, _) = send_json(&app, "POST", "/v1/contents", note).await;
+        assert_eq!(status, StatusCode::CREATED);
+
+        let (status, body) = send_json(&app, "POST", "/v1/exports", serde_json::json!({})).await;
+        assert_eq!(status, StatusCode::ACCEPTED);
+        assert_eq!(body["status"], "pending");
+        let export_id = body["id"].as_str().unwrap().to_owned();
+        let mut export = body;
+        for _ in 0..100 {
+            if export["status"] != "pending" {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+            (_, export) = send_json(
+                &app,
+                "GET",
+                &format!("/v1/exports/{export_id}"),
+                serde_json::Value::Null,
+            )
+            .await;
+        }
+        assert_eq!(export["status"], "completed");
+
+        let send = |method: &str, uri: String, body: Vec<u8>| {
+            let request = Request::builder()
+                .method(method)
+                .uri(uri)
+                .header("Authorization", format!("Bearer {}", generate_test_token()))
+                .body(Body::from(body))
+                .unwrap();
+            let app = app.clone();
+            async move {
+                let response = app.oneshot(request).await.unwrap();
+                let status = response.status();
+                let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+                    .await
+                    .unwrap();
+                (status, body)
+            }
+        };
+
+        let (status, archive) = send(
+            "GET",
+            format!("/v1/exports/{export_id}/archive.zip"),
+            vec![],
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(&archive[30..43], b"manifest.json");
+        let blob = format!("blobs/{}", file.id);
+        assert!(
+            archive
+                .windows(blob.len())
+                .any(|window| window == blob.as_bytes())
+        );
+
+        let (status, body) = send("POST", "/v1/imports".to_owned(), archive.to_vec()).await;
+        assert_eq!(status, StatusCode::CREATED);
+        let report: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(report["contents"], 2);
+        assert_eq!(report["missing_files"], serde_json::json!([]));
+
+        let contents = sql_storage.contents.lock().unwrap().clone();
+        assert_eq!(contents.len(), 4);
+        let imported = contents
+            .values()
+            .find(|c| c.kind == "file" && c.id != file.id)
+            .unwrap();
+        assert_eq!(imported.sha256.as_deref(), Some(HELLO_SHA256));
+        assert_ne!(imported.storage_key, file.storage_key);
+        assert_eq!(
+            std::fs::read(dir.path().join(&imported.storage_key)).unwrap(),
+            b"hello"
+        );
+        assert!(
+            contents
+                .values()
+                .filter(|c| c.kind == "text")
+                .all(|c| c.body.as_deref() == Some("- tent"))
+        );
+
+        let (status, _) = send("POST", "/v1/imports".to_owned(), b"not a zip".to_vec()).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+
+        // A file that does not match the manifest undoes the whole import
+        let manifest = serde_json::json!({
+            "format": "collects-export",
+            "version": 1,
+            "exported_at": "2026-10-17T12:00:00Z",
+            "contents": [
+                {
+                    "id": uuid::Uuid::new_v4(),
+                    "kind": "text",
+                    "title": "Packing list",
+                    "content_type": "text/plain",
+                    "visibility": "private",
+                    "status": "active",
+                    "file_size": 6,
+                    "body": "- tent",
+                    "created_at": "2026-10-17T12:00:00Z",
+                    "updated_at": "2026-10-17T12:00:00Z",
+                },
+                {
+                    "id": file.id,
+                    "kind": "file",
+                    "title": "greeting.txt",
+                    "content_type": "text/plain",
+                    "visibility": "private",
+                    "status": "active",
+                    "file_size": 5,
+                    "blob": blob,
+                    "created_at": "2026-10-17T12:00:00Z",
+                    "updated_at": "2026-10-17T12:00:00Z",
+                },
+            ],
+        })
+        .to_string();
+        let now = chrono::Utc::now();
+        let mut writer = crate::storage::ZipWriter::new();
+        let mut invalid = Vec::new();
+        for (name, data) in [
+            ("manifest.json", manifest.as_bytes()),
+            (blob.as_str(), b"bye".as_slice()),
+        ] {
+            invalid.extend(writer.start_entry(name, data.len() as u64, now));
+            writer.write(data);
+            invalid.extend(data);
+            invalid.extend(writer.finish_entry().unwrap());
+        }
+        invalid.extend(writer.finish());
+        let (status, _) = send("POST", "/v1/imports".to_owned(), invalid).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(sql_storage.contents.lock().unwrap().len(), 4);
+    }
+
+    /// An export archive of one file content holding `data`.
+    fn single_file_archive(data: &[u8]) -> Vec<u8> {
+        let id = uuid::Uuid::new_v4();
+        let blob = crate::export::blob_entry(id);
+        let manifest = serde_json::json!({
+            "format": "collects-export",
+            "version": 1,
+            "exported_at": "2026-10-17T12:00:00Z",
+            "contents": [{
+                "id": id,
+                "kind": "file",
+                "title": "greeting.txt",
+                "content_type": "text/plain",
+                "visibility": "private",
+                "status": "active",
+                "file_size": data.len(),
+                "blob": blob,
+                "created_at": "2026-10-17T12:00:00Z",
+                "updated_at": "2026-10-17T12:00:00Z",
+            }],
+        })
+        .to_string();
+        let now = chrono::Utc::now();
+        let mut writer = crate::storage::ZipWriter::new();
+        let mut archive = Vec::new();
+        for (name, data) in [
+            ("manifest.json", manifest.as_bytes()),
+            (blob.as_str(), data),
+        ] {
+            archive.extend(writer.start_entry(name, data.len() as u64, now));
+            writer.write(data);
+            archive.extend(data);
+            archive.extend(writer.finish_entry().unwrap());
+        }
+        archive.extend(writer.finish());
+        archive
+    }
+
+    #[tokio::test]
+    async fn test_v1_imports_reserve_quota_per_content() {
+        let dir = tempfile::tempdir().unwrap();
+        let sql_storage = MockSqlStorage::new();
+        sql_storage
+            .users_set_storage_quota(TEST_USER_ID, Some(8))
+            .await
+            .unwrap();
+        let config = Config::new_for_test_local_storage(dir.path().to_string_lossy());
+        let app = routes(sql_storage.clone(), create_test_user_storage(), config).await;
+
+        let import = || {
+            let request = Request::builder()
+                .method("POST")
+                .uri("/v1/imports")
+                .header("Authorization", format!("Bearer {}", generate_test_token()))
+                .body(Body::from(single_file_archive(b"hello")))
+                .unwrap();
+            app.clone().oneshot(request)
+        };
+        // Each fits the quota on its own, but only one can reserve its size
+        let (first, second) = tokio::join!(import(), import());
+        let mut statuses = [first.unwrap().status(), second.unwrap().status()];
+        statuses.sort();
+        assert_eq!(
+            statuses,
+            [StatusCode::CREATED, StatusCode::PAYLOAD_TOO_LARGE]
+        );
+        assert_eq!(sql_storage.contents.lock().unwrap().len(), 1);
+        // The rejected import never stored its file
+        let stored = std::fs::read_dir(dir.path().join(TEST_USER_ID.to_string()))
+            .unwrap()
+            .count();
+        assert_eq!(stored, 1);
+    }
+
+    #[tokio::test]
+    async fn test_v1_imports_roll_back_when_client_disconnects() {
+        let dir = tempfile::tempdir().unwrap();
+        let sql_storage = MockSqlStorage::new();
+        let config = Config::new_for_test_local_storage(dir.path().to_string_lossy());
+        let app = routes(sql_storage.clone(), create_test_user_storage(), config).await;
+
+        // Send the archive up to the middle of the file, then hang
+        let archive = single_file_archive(b"hello");
+        let cut = archive.windows(5).position(|w| w == b"hello").unwrap() + 2;
+        let (tx, rx) = tokio::sync::mpsc::channel::<std::io::Result<axum::body::Bytes>>(1);
+        tx.send(Ok(axum::body::Bytes::copy_from_slice(&archive[..cut])))
+            .await
+            .unwrap();
+        let body = futures_util::stream::unfold(rx, |mut rx| async move {
+            rx.recv().await.map(|chunk| (chunk, rx))
+        });
+        let request = Request::builder()
+            .method("POST")
+            .uri("/v1/imports")
+            .header("Authorization", format!("Bearer {}", generate_test_token()))
+            .body(Body::from_stream(body))
+            .unwrap();
+        let handler = tokio::spawn(app.oneshot(request));
+
+        // The content is created before its file is read
+        for _ in 0..100 {
+            if !sql_storage.contents.lock().unwrap().is_empty() {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+        assert_eq!(sql_storage.contents.lock().unwrap().len(), 1);
+
+        // The client goes away: the handler is dropped and the body ends
+        handler.abort();
+        drop(tx);
+        for _ in 0..100 {
+            if sql_storage.contents.lock().unwrap().is_empty() {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+        assert!(sql_storage.contents.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_audit_logs_record_mutations() {
+        let dir = tempfile::tempdir().unwrap();
+        let sql_storage = MockSqlStorage::new();
+        let config = Config::new_for_test_local_storage(dir.path().to_string_lossy());
+        let app = routes(sql_storage.clone(), create_test_user_storage(), config).await;
+
+        let note = serde_json::json!({
+            "title": "Packing list",
+            "body": "- tent",
+            "content_type": "text/plain",
+        });
+        let (status, body) = send_json(&app, "POST", "/v1/contents", note).await;
+        assert_eq!(status, StatusCode::CREATED);
+        let content_id = body["content"]["id"].as_str().unwrap().to_owned();
+        let (status, _) = send_json(
+            &app,
+            "PATCH",
+            &format!("/v1/contents/{content_id}"),
+            serde_json::json!({ "title": "Camping list" }),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+
+        let (status, body) = send_json(
+            &app,
+            "GET",
+            "/internal/audit-logs?username=testuser",
+            serde_json::Value::Null,
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        let actions: Vec<&str> = body["entries"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|entry| entry["action"].as_str().unwrap())
+            .collect();
+        assert_eq!(actions, ["content.update", "content.create"]);
+        assert_eq!(body["entries"][0]["entity_type"], "content");
+        assert_eq!(body["entries"][0]["entity_id"], content_id.as_str());
+        assert_eq!(body["entries"][0]["user_id"], TEST_USER_ID.to_string());
+
+        let (_, body) = send_json(
+            &app,
+            "GET",
+            &format!("/internal/audit-logs?action=content.create&entity_id={content_id}&limit=1"),
+            serde_json::Value::Null,
+        )
+        .await;
+        assert_eq!(body["entries"].as_array().unwrap().len(), 1);
+        assert_eq!(body["limit"], 1);
+
+        let (status, _) = send_json(
+            &app,
+            "DELETE",
+            "/internal/users/testuser",
+            serde_json::Value::Null,
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        let (_, body) = send_json(
+            &app,
+            "GET",
+            "/internal/audit-logs?action=user.delete",
+            serde_json::Value::Null,
+        )
+        .await;
+        let entry = &body["entries"][0];
+        assert_eq!(entry["user_id"], serde_json::Value::Null);
+        assert_eq!(entry["entity_id"], TEST_USER_ID.to_string());
+        assert_eq!(entry["details"]["username"], "testuser");
+    }
+
+    #[tokio::test]
+    async fn test_v1_contents_raw_streams_ranges() {
+        let dir = tempfile::tempdir().unwrap();
+        let sql_storage = MockSqlStorage::new();
+        let mut file = trashed_file(
+            dir.path(),
+            &format!("{TEST_USER_ID}/greeting.txt"),
+            Some(HELLO_SHA256),
+            chrono::Utc::now(),
+        );
+        file.status = "active".to_owned();
+        file.trashed_at = None;
+        sql_storage
+            .contents
+            .lock()
+            .unwrap()
+            .insert(file.id, file.clone());
+        let user_storage = create_test_user_storage();
+        let config = Config::new_for_test_local_storage(dir.path().to_string_lossy());
+        let app = routes(sql_storage, user_storage, config).await;
+
+        let get = |uri: String, headers: &[(&str, &str)]| {
+            let mut builder = Request::builder()
+                .uri(uri)
+                .header("Authorization", format!("Bearer {}", generate_test_token()));
+            for (name, value) in headers {
+                builder = builder.header(*name, *value);
+            }
+            let app = app.clone();
+            let request = builder.body(Body::empty()).unwrap();
+            async move {
+                let response = app.oneshot(request).await.unwrap();
+                let status = response.status();
+                let headers = response.headers().clone();
+                let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+                    .await
+                    .unwrap();
+                (status, headers, body)
+            }
+        };
+
+        let uri = format!("/v1/contents/{}/raw", file.id);
+        let (status, headers, body) = get(uri.clone(), &[]).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(&body[..], b"hello");
+        assert_eq!(headers["etag"], format!("\"{HELLO_SHA256}\""));
+        assert_eq!(headers["accept-ranges"], "bytes");
+
+        let (status, headers, body) = get(
+            format!("{uri}?disposition=attachment"),
+            &[("Range", "bytes=1-3")],
+        )
+        .await;
+        assert_eq!(status, StatusCode::PARTIAL_CONTENT);
+        assert_eq!(&body[..], b"ell");
+        assert_eq!(headers["content-range"], "bytes 1-3/5");
+        assert!(
+            headers["content-disposition"]
+                .to_str()
+                .unwrap()
+                .starts_with("attachment; filename=\"greeting.txt\"")
+        );
+
+        let etag = format!("\"{HELLO_SHA256}\"");
+        let (status, _, body) = get(uri.clone(), &[("If-None-Match", &etag)]).await;
+        assert_eq!(status, StatusCode::NOT_MODIFIED);
+        assert!(body.is_empty());
+
+        let (status, headers, _) = get(uri, &[("Range", "bytes=9-")]).await;
+        assert_eq!(status, StatusCode::RANGE_NOT_SATISFIABLE);
+        assert_eq!(headers["content-range"], "bytes */5");
+
+        // Text contents are served from their inline body
+        let note = serde_json::json!({
+            "title": "Shopping",
+            "body": "- milk\n- eggs",
+            "content_type": "text/markdown",
+        });
+        let (status, body) = send_json(&app, "POST", "/v1/contents", note).await;
+        assert_eq!(status, StatusCode::CREATED);
+        let uri = format!(
+            "/v1/contents/{}/raw",
+            body["content"]["id"].as_str().unwrap()
+        );
+        let (status, headers, body) = get(uri, &[("Range", "bytes=-4")]).await;
+        assert_eq!(status, StatusCode::PARTIAL_CONTENT);
+        assert_eq!(&body[..], b"eggs");
+        assert_eq!(headers["content-type"], "text/markdown; charset=utf-8");
+        assert!(
+            headers["content-disposition"]
+                .to_str()
+                .unwrap()
+                .starts_with("inline; filename=\"Shopping.md\"")
+        );
+    }
+
     // =========================================================================
     // Contents API Tests
     // =========================================================================
@@ -1,8 +1,14 @@
 use collects_services::{
-    config::Config,
+    config::{Config, StorageBackend, StorageProfileBackend},
     database::{self, PgStorage},
+    purge::TrashPurger,
     routes,
-    storage::{CFDisk, CFDiskConfig, OpenDALDisk},
+    storage::{
+        CFDisk, CFDiskConfig, DEFAULT_STORAGE_PROFILE, GcsDisk, GcsDiskConfig, LocalFileStorage,
+        OpenDALDisk,
+    },
+    storage_router,
+    sweeper::UploadSweeper,
     telemetry,
     users::PgUserStorage,
 };
@@ -54,6 +60,27 @@ async fn main() -> anyhow::Result<()> {
     let pool = database::create_pool(&config).await?;
     let sql_storage = PgStorage::new(pool);
 
+    // Periodically expire abandoned uploads and delete their objects
+    if let Some(interval) = config.upload_sweep_interval() {
+        UploadSweeper::new(sql_storage.clone(), storage_router(&config)).spawn(interval);
+        info!(interval_secs = interval.as_secs(), "Upload sweeper started");
+    }
+
+    // Periodically delete contents and groups trashed longer than the retention period
+    if let Some(interval) = config.trash_purge_interval() {
+        TrashPurger::new(
+            sql_storage.clone(),
+            storage_router(&config),
+            config.trash_retention(),
+        )
+        .spawn(interval);
+        info!(
+            interval_secs = interval.as_secs(),
+            retention_days = config.trash_retention().as_secs() / (24 * 60 * 60),
+            "Trash purger started"
+        );
+    }
+
     // Create user storage backed by PostgreSQL
     let user_storage = PgUserStorage::new(sql_storage.clone());
 
@@ -67,6 +94,90 @@ async fn main() -> anyhow::Result<()> {
 }
 
 async fn validate_storage_backends(config: &Config) -> anyhow::Result<()> {
+    // STORAGE_BACKEND only receives uploads when no named profile is the default
+    if config.storage_default_profile() == DEFAULT_STORAGE_PROFILE {
+        validate_default_storage_backend(config).await?;
+    }
+
+    for profile in config.storage_profiles() {
+        match profile.backend() {
+            StorageProfileBackend::Local(local) => {
+                LocalFileStorage::new(local.root())
+                    .ensure_root()
+                    .await
+                    .map_err(|e| {
+                        anyhow::anyhow!(
+                            "Storage profile '{}': local root is not writable: {e}",
+                            profile.name()
+                        )
+                    })?;
+            }
+            StorageProfileBackend::Gcs(gcs) => {
+                let disk = GcsDisk::new(GcsDiskConfig {
+                    bucket: gcs.bucket().to_owned(),
+                    credential: gcs.credential().map(str::to_owned),
+                    endpoint: gcs.endpoint().map(str::to_owned),
+                    token: gcs.token().map(str::to_owned),
+                });
+                if !disk.could_connected().await {
+                    anyhow::bail!(
+                        "Storage profile '{}': GCS connectivity check failed",
+                        profile.name()
+                    );
+                }
+            }
+            StorageProfileBackend::R2(r2) => {
+                let disk = CFDisk::new(CFDiskConfig {
+                    account_id: r2.account_id().to_owned(),
+                    access_key_id: r2.access_key_id().to_owned(),
+                    secret_access_key: r2.secret_access_key().to_owned(),
+                    bucket: r2.bucket().to_owned(),
+                });
+                if !disk.could_connected().await {
+                    anyhow::bail!(
+                        "Storage profile '{}': R2 connectivity check failed",
+                        profile.name()
+                    );
+                }
+            }
+        }
+        info!(profile = %profile.name(), backend = %profile.storage_backend(), "Storage profile ready");
+    }
+
+    Ok(())
+}
+
+async fn validate_default_storage_backend(config: &Config) -> anyhow::Result<()> {
+    if config.storage_backend() == StorageBackend::Local {
+        let local = config.local_storage().ok_or_else(|| {
+            anyhow::anyhow!("No storage backend configured. Set LOCAL_STORAGE_ROOT.")
+        })?;
+        LocalFileStorage::new(local.root())
+            .ensure_root()
+            .await
+            .map_err(|e| anyhow::anyhow!("Local storage root is not writable: {e}"))?;
+        info!(root = %local.root(), "Local storage backend ready");
+        return Ok(());
+    }
+
+    if config.storage_backend() == StorageBackend::Gcs {
+        let gcs = config
+            .gcs()
+            .ok_or_else(|| anyhow::anyhow!("No storage backend configured. Set GCS_BUCKET."))?;
+
+        let disk = GcsDisk::new(GcsDiskConfig {
+            bucket: gcs.bucket().to_owned(),
+            credential: gcs.credential().map(str::to_owned),
+            endpoint: gcs.endpoint().map(str::to_owned),
+            token: gcs.token().map(str::to_owned),
+        });
+
+        if !disk.could_connected().await {
+            anyhow::bail!("GCS storage is configured but the connectivity check failed");
+        }
+        return Ok(());
+    }
+
     let r2 = config.r2().ok_or_else(|| {
         anyhow::anyhow!("No storage backend configured. Set R2 (CF_*) credentials.")
     })?;
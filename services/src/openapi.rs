@@ -15,7 +15,10 @@ use crate::config::{Config, Env};
 use crate::database::SqlStorage;
 use crate::users::routes::AppState;
 use crate::users::storage::UserStorage;
-use crate::v1::{content_tags, contents, groups, me, public, share_links, tags, types, uploads};
+use crate::v1::{
+    archive, content_tags, contents, exports, groups, me, public, raw, revisions, search,
+    share_links, shares, tags, trash, types, uploads,
+};
 
 /// `OpenAPI` documentation structure.
 #[allow(clippy::needless_for_each)] // Triggered by OpenApi derive macro
@@ -42,10 +45,15 @@ use crate::v1::{content_tags, contents, groups, me, public, share_links, tags, t
         (name = "me", description = "Current user information"),
         (name = "contents", description = "Content management endpoints"),
         (name = "content-tags", description = "Content-tag relationship endpoints"),
+        (name = "content-revisions", description = "Text content revision history endpoints"),
         (name = "tags", description = "Tag management endpoints"),
         (name = "groups", description = "Group management endpoints"),
+        (name = "search", description = "Full-text search endpoints"),
+        (name = "trash", description = "Trash endpoints"),
+        (name = "exports", description = "Account data export and import endpoints"),
         (name = "uploads", description = "File upload endpoints"),
         (name = "share-links", description = "Share link management endpoints"),
+        (name = "shares", description = "Direct sharing with other users"),
         (name = "public", description = "Public share access endpoints (unauthenticated)"),
     ),
     paths(
@@ -56,11 +64,17 @@ use crate::v1::{content_tags, contents, groups, me, public, share_links, tags, t
         contents::v1_contents_create,
         contents::v1_contents_get,
         contents::v1_contents_update,
+        contents::v1_contents_batch,
         contents::v1_contents_trash,
         contents::v1_contents_restore,
         contents::v1_contents_archive,
         contents::v1_contents_unarchive,
         contents::v1_contents_view_url,
+        raw::v1_contents_raw,
+        // Content Revisions
+        revisions::v1_content_revisions_list,
+        revisions::v1_content_revisions_diff,
+        revisions::v1_content_revisions_restore,
         // Content Tags
         content_tags::v1_content_tags_list,
         content_tags::v1_content_tags_attach,
@@ -68,6 +82,7 @@ use crate::v1::{content_tags, contents, groups, me, public, share_links, tags, t
         // Tags
         tags::v1_tags_list,
         tags::v1_tags_create,
+        tags::v1_tags_get,
         tags::v1_tags_update,
         tags::v1_tags_delete,
         // Groups
@@ -83,33 +98,70 @@ use crate::v1::{content_tags, contents, groups, me, public, share_links, tags, t
         groups::v1_groups_contents_add,
         groups::v1_groups_contents_remove,
         groups::v1_groups_contents_reorder,
+        archive::v1_groups_archive_zip,
+        // Search
+        search::v1_search,
+        // Trash
+        trash::v1_trash_list,
+        trash::v1_trash_empty,
+        // Exports
+        exports::v1_exports_create,
+        exports::v1_exports_get,
+        exports::v1_exports_archive_zip,
+        exports::v1_imports_create,
         // Uploads
         uploads::v1_uploads_init,
         uploads::v1_uploads_complete,
+        uploads::v1_uploads_multipart_init,
+        uploads::v1_uploads_get,
+        uploads::v1_uploads_parts_presign,
+        uploads::v1_uploads_part_update,
+        uploads::v1_uploads_abort,
         // Share Links
         share_links::v1_share_links_list,
         share_links::v1_share_links_create,
         share_links::v1_share_links_get,
         share_links::v1_share_links_update,
         share_links::v1_share_links_delete,
+        share_links::v1_share_links_analytics,
         share_links::v1_contents_share_link_create,
         share_links::v1_groups_share_link_create,
+        // Direct Shares
+        shares::v1_contents_shares_list,
+        shares::v1_contents_shares_create,
+        shares::v1_contents_shares_delete,
+        shares::v1_groups_shares_list,
+        shares::v1_groups_shares_create,
+        shares::v1_groups_shares_delete,
+        shares::v1_shared_with_me,
         // Public
         public::v1_public_share_get,
         public::v1_public_share_view_url,
+        archive::v1_public_share_archive_zip,
     ),
     components(
         schemas(
             types::V1ErrorResponse,
             types::V1MeResponse,
+            types::V1StorageUsage,
             types::V1ContentItem,
+            types::V1MediaMetadata,
             types::V1ContentsListQuery,
             types::V1ContentsListResponse,
             types::V1ContentsUpdateRequest,
             types::V1ContentCreateRequest,
             types::V1ContentCreateResponse,
             types::V1ViewUrlRequest,
+            types::V1ViewUrlQuery,
+            types::V1ContentRawQuery,
             types::V1ViewUrlResponse,
+            types::V1ContentsBatchRequest,
+            types::V1ContentsBatchItemResult,
+            types::V1ContentsBatchResponse,
+            types::V1ContentRevisionItem,
+            types::V1ContentRevisionsListResponse,
+            types::V1ContentRevisionDiffResponse,
+            types::V1DiffLine,
             types::V1TagItem,
             types::V1TagsListResponse,
             types::V1TagCreateRequest,
@@ -125,16 +177,41 @@ use crate::v1::{content_tags, contents, groups, me, public, share_links, tags, t
             types::V1GroupAddContentRequest,
             types::V1GroupReorderRequest,
             types::V1GroupReorderItem,
+            types::V1SearchQuery,
+            types::V1SearchHit,
+            types::V1SearchResponse,
+            types::V1TrashListQuery,
+            types::V1TrashListResponse,
+            types::V1TrashEmptyResponse,
+            types::V1ExportItem,
+            types::V1ImportResponse,
             types::V1UploadsInitRequest,
             types::V1UploadsInitResponse,
             types::V1UploadsCompleteRequest,
             types::V1UploadsCompleteResponse,
+            types::V1MultipartUploadInitRequest,
+            types::V1MultipartUploadInitResponse,
+            types::V1UploadPartsRequest,
+            types::V1UploadPartUrl,
+            types::V1UploadPartsResponse,
+            types::V1UploadPartCompleted,
+            types::V1UploadPartUpdateRequest,
+            types::V1UploadPart,
+            types::V1UploadStatusResponse,
             types::V1ShareLinkCreateRequest,
             types::V1ShareLinkUpdateRequest,
             types::V1ShareLinkResponse,
             types::V1ShareLinksListResponse,
+            types::V1ShareLinkAnalyticsQuery,
+            types::V1ShareLinkAccessDay,
+            types::V1ShareLinkAnalyticsResponse,
             types::V1ContentShareLinkCreateRequest,
             types::V1GroupShareLinkCreateRequest,
+            types::V1UserShareCreateRequest,
+            types::V1UserShareResponse,
+            types::V1UserSharesListResponse,
+            types::V1SharedWithMeItem,
+            types::V1SharedWithMeResponse,
             types::V1PublicShareResponse,
             types::V1PublicViewUrlRequest,
             types::V1PublicViewUrlResponse,
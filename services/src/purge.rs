@@ -0,0 +1,231 @@
+//! Permanent deletion of trashed contents and groups.
+//!
+//! Trashed items are kept for a retention period (`TRASH_RETENTION_DAYS`) so
+//! they can be restored. [`TrashPurger`] deletes the rows of items trashed for
+//! longer than that, or a user's whole trash on request, and releases the
+//! storage objects and thumbnails of purged file contents. Group memberships,
+//! tags and shares are removed with the rows by `ON DELETE CASCADE`.
+
+use std::time::Duration;
+
+use crate::database::{ContentRow, SqlStorage, SqlStorageError, TrashPurgeParams};
+use crate::storage::{FileStorage, StorageRouter, delete_thumbnails};
+
+/// Maximum number of rows purged per database round trip.
+const PURGE_BATCH_SIZE: i64 = 100;
+
+/// What a purge deleted.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct PurgeReport {
+    /// Contents deleted.
+    pub purged_contents: usize,
+    /// Groups deleted.
+    pub purged_groups: usize,
+    /// Storage objects deleted.
+    pub deleted_objects: usize,
+    /// Contents whose storage could not be cleaned up (see logs).
+    pub failed_cleanups: usize,
+}
+
+impl PurgeReport {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// Deletes trashed contents and groups with their storage objects.
+#[derive(Clone)]
+pub struct TrashPurger<S> {
+    sql_storage: S,
+    storage: StorageRouter,
+    retention: Duration,
+}
+
+impl<S: SqlStorage> TrashPurger<S> {
+    pub fn new(sql_storage: S, storage: StorageRouter, retention: Duration) -> Self {
+        Self {
+            sql_storage,
+            storage,
+            retention,
+        }
+    }
+
+    /// Purge every item trashed longer than the retention period before `now`.
+    pub async fn purge_expired(
+        &self,
+        now: chrono::DateTime<chrono::Utc>,
+    ) -> Result<PurgeReport, SqlStorageError> {
+        let retention = chrono::Duration::from_std(self.retention).unwrap_or(chrono::Duration::MAX);
+        self.purge(TrashPurgeParams {
+            user_id: None,
+            trashed_before: Some(now.checked_sub_signed(retention).unwrap_or_default()),
+            ids: None,
+            limit: PURGE_BATCH_SIZE,
+        })
+        .await
+    }
+
+    /// Purge the whole trash of a user.
+    pub async fn purge_user(&self, user_id: uuid::Uuid) -> Result<PurgeReport, SqlStorageError> {
+        self.purge(TrashPurgeParams {
+            user_id: Some(user_id),
+            trashed_before: None,
+            ids: None,
+            limit: PURGE_BATCH_SIZE,
+        })
+        .await
+    }
+
+    /// Purge the trashed contents and groups among `ids` of a user.
+    pub async fn purge_items(
+        &self,
+        user_id: uuid::Uuid,
+        ids: Vec<uuid::Uuid>,
+    ) -> Result<PurgeReport, SqlStorageError> {
+        if ids.is_empty() {
+            return Ok(PurgeReport::default());
+        }
+        self.purge(TrashPurgeParams {
+            user_id: Some(user_id),
+            trashed_before: None,
+            ids: Some(ids),
+            limit: PURGE_BATCH_SIZE,
+        })
+        .await
+    }
+
+    async fn purge(&self, params: TrashPurgeParams) -> Result<PurgeReport, SqlStorageError> {
+        let mut report = PurgeReport::default();
+
+        loop {
+            let contents = self
+                .sql_storage
+                .contents_purge_trashed(params.clone())
+                .await?;
+            let done = (contents.len() as i64) < params.limit;
+
+            for content in &contents {
+                report.purged_contents += 1;
+                self.release_object(content, &mut report).await;
+            }
+
+            if done {
+                break;
+            }
+        }
+
+        loop {
+            let groups = self
+                .sql_storage
+                .groups_purge_trashed(params.clone())
+                .await?;
+            report.purged_groups += groups.len();
+            if (groups.len() as i64) < params.limit {
+                return Ok(report);
+            }
+        }
+    }
+
+    /// Delete the storage object of a purged content once nothing references it.
+    async fn release_object(&self, content: &ContentRow, report: &mut PurgeReport) {
+        if content.kind != "file" {
+            return;
+        }
+
+        // Deduplicated objects are shared by every content with the same digest
+        let (storage_backend, storage_profile, storage_key) = match &content.sha256 {
+            Some(sha256) => {
+                match self
+                    .sql_storage
+                    .storage_objects_release(content.user_id, sha256)
+                    .await
+                {
+                    Ok(Some(object)) if object.ref_count > 0 => return,
+                    Ok(Some(object)) => (
+                        object.storage_backend,
+                        object.storage_profile,
+                        object.storage_key,
+                    ),
+                    Ok(None) => (
+                        content.storage_backend.clone(),
+                        content.storage_profile.clone(),
+                        content.storage_key.clone(),
+                    ),
+                    Err(e) => {
+                        tracing::warn!(
+                            content_id = %content.id,
+                            "Failed to release storage object of purged content: {e:?}"
+                        );
+                        report.failed_cleanups += 1;
+                        return;
+                    }
+                }
+            }
+            None => (
+                content.storage_backend.clone(),
+                content.storage_profile.clone(),
+                content.storage_key.clone(),
+            ),
+        };
+
+        let Some(target) = self.storage.resolve(&storage_backend, &storage_profile) else {
+            tracing::warn!(
+                content_id = %content.id,
+                storage_backend = %storage_backend,
+                storage_profile = %storage_profile,
+                "Cannot delete object of purged content: storage is not configured"
+            );
+            report.failed_cleanups += 1;
+            return;
+        };
+
+        if let Err(e) =
+            delete_thumbnails(target.file_storage(), &storage_key, &content.content_type).await
+        {
+            tracing::warn!(
+                content_id = %content.id,
+                storage_key = %storage_key,
+                "Failed to delete thumbnails of purged content: {e}"
+            );
+        }
+
+        match target.file_storage().delete_file(&storage_key).await {
+            Ok(true) => report.deleted_objects += 1,
+            Ok(false) => {}
+            Err(e) => {
+                tracing::warn!(
+                    content_id = %content.id,
+                    storage_key = %storage_key,
+                    "Failed to delete object of purged content: {e}"
+                );
+                report.failed_cleanups += 1;
+            }
+        }
+    }
+
+    /// Spawn a task purging expired trash every `interval`.
+    pub fn spawn(self, interval: Duration) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            loop {
+                ticker.tick().await;
+                match self.purge_expired(chrono::Utc::now()).await {
+                    Ok(report) if report.is_empty() => {
+                        tracing::debug!("Trash purge found nothing to delete");
+                    }
+                    Ok(report) => {
+                        tracing::info!(
+                            purged_contents = report.purged_contents,
+                            purged_groups = report.purged_groups,
+                            deleted_objects = report.deleted_objects,
+                            failed_cleanups = report.failed_cleanups,
+                            "Trash purge finished"
+                        );
+                    }
+                    Err(e) => tracing::error!("Trash purge failed: {e:?}"),
+                }
+            }
+        })
+    }
+}
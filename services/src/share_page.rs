@@ -0,0 +1,678 @@
+//! Server-rendered share pages.
+//!
+//! Share links point at `/s/{token}` on [`Config::share_base_url`]. The page
+//! previews the shared content (or summarizes the shared group), offers a
+//! download button when the link allows downloads and carries Open Graph and
+//! Twitter meta tags, so that chat apps can unfurl the link.
+//!
+//! Rendering a page does not count as an access, so that link unfurlers do
+//! not use up a link's access limit. Fetching the shared bytes does: pages
+//! embed a content's file from `/s/{token}/raw`, and group pages link to the
+//! group archive. `og:image` points at `/s/{token}/thumbnail`, which serves a
+//! thumbnail of an image without counting an access.
+//!
+//! Password-protected links first render a password form, posted back to
+//! `POST /s/{token}`. Until it is unlocked, the page reveals nothing about
+//! what is shared, in its meta tags or otherwise. Unlocking sets the link's
+//! unlock cookie, which later visits, the raw file and the group archive
+//! download accept in place of the password.
+
+use std::fmt::Write as _;
+
+use axum::{
+    Extension, Form, Router,
+    extract::{Path, Query, State},
+    http::{HeaderMap, HeaderValue, StatusCode, header},
+    response::{Html, IntoResponse, Response},
+    routing::get,
+};
+use serde::Deserialize;
+
+use crate::audit::ClientIp;
+use crate::config::Config;
+use crate::database::{ContentGroupRow, ContentRow, ShareLinkRow, SqlStorage};
+use crate::storage::{
+    ContentDisposition, FileStorage, StorageRouter, THUMBNAIL_SIZES, ThumbnailFormat, thumbnail_key,
+};
+use crate::users::routes::AppState;
+use crate::users::storage::UserStorage;
+use crate::v1::public::{record_access, validate_share_link};
+use crate::v1::raw::{fetches_from_start, raw_response};
+use crate::v1::share_unlock::{
+    PasswordError, check_share_link_password, set_unlock_cookie, unlock_token_from_headers,
+};
+use crate::v1::types::V1ContentRawQuery;
+
+/// Name of the site in page titles and `og:site_name`.
+const SITE_NAME: &str = "Collects";
+
+/// Characters of a text content's body used as its description.
+const TEXT_DESCRIPTION_CHARS: usize = 200;
+
+/// Creates the share page routes.
+pub fn routes<S, U>() -> Router<AppState<S, U>>
+where
+    S: SqlStorage + Clone + Send + Sync + 'static,
+    U: UserStorage + Clone + Send + Sync + 'static,
+{
+    Router::new()
+        .route(
+            "/s/{token}",
+            get(share_page::<S, U>).post(share_page_unlock::<S, U>),
+        )
+        .route("/s/{token}/raw", get(share_page_raw::<S, U>))
+        .route("/s/{token}/thumbnail", get(share_page_thumbnail::<S, U>))
+}
+
+/// Password form of a protected share page.
+#[derive(Debug, Deserialize)]
+pub struct UnlockForm {
+    password: String,
+}
+
+/// Render the share page of `token`.
+pub async fn share_page<S, U>(
+    State(state): State<AppState<S, U>>,
+    Extension(config): Extension<Config>,
+    client_ip: ClientIp,
+    headers: HeaderMap,
+    Path(token): Path<String>,
+) -> Response
+where
+    S: SqlStorage,
+    U: UserStorage,
+{
+    render(&state, &config, client_ip, &headers, &token, None).await
+}
+
+/// Unlock a password-protected share page.
+pub async fn share_page_unlock<S, U>(
+    State(state): State<AppState<S, U>>,
+    Extension(config): Extension<Config>,
+    client_ip: ClientIp,
+    headers: HeaderMap,
+    Path(token): Path<String>,
+    Form(form): Form<UnlockForm>,
+) -> Response
+where
+    S: SqlStorage,
+    U: UserStorage,
+{
+    render(
+        &state,
+        &config,
+        client_ip,
+        &headers,
+        &token,
+        Some(&form.password),
+    )
+    .await
+}
+
+/// Stream the file of a shared content. Counts as an access, unless it only
+/// fetches a later part of the file.
+///
+/// Protected links need their unlock cookie. `disposition=attachment` is
+/// only honored by links that allow downloads.
+pub async fn share_page_raw<S, U>(
+    State(state): State<AppState<S, U>>,
+    storage: Option<Extension<StorageRouter>>,
+    Extension(config): Extension<Config>,
+    client_ip: ClientIp,
+    headers: HeaderMap,
+    Path(token): Path<String>,
+    Query(query): Query<V1ContentRawQuery>,
+) -> Response
+where
+    S: SqlStorage,
+    U: UserStorage,
+{
+    let (content, share_link) = match find_share(&state.sql_storage, &token).await {
+        Ok(Some((Shared::Content(content), share_link))) => (content, share_link),
+        Ok(_) => return not_found_page(),
+        Err(response) => return response,
+    };
+    if let Err((status, message)) = validate_share_link(&share_link) {
+        return message_page(status, "Share unavailable", message);
+    }
+    if let Err(e) = check_share_link_password(
+        &state.sql_storage,
+        &config,
+        &share_link,
+        None,
+        unlock_token_from_headers(&headers, &token),
+        client_ip,
+    )
+    .await
+    {
+        return e.into_response();
+    }
+
+    let disposition = match query.disposition.as_deref() {
+        Some("attachment") if share_link.permission == "download" => ContentDisposition::Attachment,
+        _ => ContentDisposition::Inline,
+    };
+
+    if fetches_from_start(&content, &headers) {
+        if let Err(e) = state
+            .sql_storage
+            .share_links_increment_access(share_link.id)
+            .await
+        {
+            tracing::warn!("Failed to increment share link access count: {:?}", e);
+        }
+        record_access(&state.sql_storage, &share_link, client_ip, &headers, false).await;
+    }
+
+    let storage = storage.as_ref().map(|Extension(router)| router);
+    raw_response(storage, content, disposition, &headers).await
+}
+
+/// Serve the `og:image` of a share page: the largest thumbnail of a shared
+/// image. Does not count as an access, and is not available for protected
+/// links.
+pub async fn share_page_thumbnail<S, U>(
+    State(state): State<AppState<S, U>>,
+    storage: Option<Extension<StorageRouter>>,
+    Path(token): Path<String>,
+) -> Response
+where
+    S: SqlStorage,
+    U: UserStorage,
+{
+    let (content, share_link) = match find_share(&state.sql_storage, &token).await {
+        Ok(Some((Shared::Content(content), share_link))) => (content, share_link),
+        Ok(_) => return not_found_page(),
+        Err(response) => return response,
+    };
+    if let Err((status, message)) = validate_share_link(&share_link) {
+        return message_page(status, "Share unavailable", message);
+    }
+    let Some(format) = ThumbnailFormat::for_source(&content.content_type)
+        .filter(|_| share_link.password_hash.is_none())
+    else {
+        return not_found_page();
+    };
+
+    let Some(target) = storage.as_ref().and_then(|Extension(router)| {
+        router.resolve(&content.storage_backend, &content.storage_profile)
+    }) else {
+        return not_found_page();
+    };
+    let size = THUMBNAIL_SIZES[THUMBNAIL_SIZES.len() - 1];
+    let key = thumbnail_key(&content.storage_key, size, format);
+    match target.file_storage().file_exists(&key).await {
+        Ok(true) => {}
+        // Not generated (yet)
+        Ok(false) => return not_found_page(),
+        Err(e) => {
+            tracing::warn!("Failed to look up thumbnail: {e}");
+            return internal_error_page();
+        }
+    }
+    match target.file_storage().download_file(&key).await {
+        Ok(data) => (
+            [
+                (
+                    header::CONTENT_TYPE,
+                    HeaderValue::from_static(format.content_type()),
+                ),
+                (
+                    header::CACHE_CONTROL,
+                    HeaderValue::from_static("public, max-age=3600"),
+                ),
+                (
+                    header::X_CONTENT_TYPE_OPTIONS,
+                    HeaderValue::from_static("nosniff"),
+                ),
+            ],
+            data,
+        )
+            .into_response(),
+        Err(e) => {
+            tracing::warn!("Failed to read thumbnail: {e}");
+            internal_error_page()
+        }
+    }
+}
+
+/// What a share link points to.
+enum Shared {
+    Content(ContentRow),
+    Group(ContentGroupRow, i64),
+}
+
+async fn render<S, U>(
+    state: &AppState<S, U>,
+    config: &Config,
+    client_ip: ClientIp,
+    headers: &HeaderMap,
+    token: &str,
+    password: Option<&str>,
+) -> Response
+where
+    S: SqlStorage,
+    U: UserStorage,
+{
+    let (shared, share_link) = match find_share(&state.sql_storage, token).await {
+        Ok(Some(share)) => share,
+        Ok(None) => return not_found_page(),
+        Err(response) => return response,
+    };
+
+    if let Err((status, message)) = validate_share_link(&share_link) {
+        return message_page(status, "Share unavailable", message);
+    }
+
+    let url = format!("{}/s/{}", config.share_base_url(), token);
+
+    let unlock = match check_share_link_password(
+        &state.sql_storage,
+        config,
+        &share_link,
+        password,
+        unlock_token_from_headers(headers, token),
+        client_ip,
+    )
+    .await
+    {
+        Ok(unlock) => unlock,
+        Err(PasswordError::Required) => return password_page(StatusCode::OK, &url, None),
+        Err(PasswordError::Incorrect) => {
+            return password_page(StatusCode::UNAUTHORIZED, &url, Some("Incorrect password"));
+        }
+        Err(e) => return password_page(e.status(), &url, Some(e.message())),
+    };
+
+    let mut response = match shared {
+        Shared::Content(content) => content_page(config, &share_link, &content, &url),
+        Shared::Group(group, file_count) => group_page(&share_link, &group, file_count, &url),
+    };
+    if let Some(unlock) = &unlock {
+        set_unlock_cookie(&mut response, &share_link, unlock, config);
+    }
+    response
+}
+
+/// Look up the content or group shared with `token`.
+async fn find_share<S: SqlStorage>(
+    storage: &S,
+    token: &str,
+) -> Result<Option<(Shared, ShareLinkRow)>, Response> {
+    match storage.contents_get_by_share_token(token).await {
+        Ok(Some((content, share_link))) => return Ok(Some((Shared::Content(content), share_link))),
+        Ok(None) => {}
+        Err(e) => {
+            tracing::error!("Failed to get content by share token: {:?}", e);
+            return Err(internal_error_page());
+        }
+    }
+
+    match storage.groups_get_by_share_token(token).await {
+        Ok(Some((group, share_link, file_count))) => {
+            Ok(Some((Shared::Group(group, file_count), share_link)))
+        }
+        Ok(None) => Ok(None),
+        Err(e) => {
+            tracing::error!("Failed to get group by share token: {:?}", e);
+            Err(internal_error_page())
+        }
+    }
+}
+
+fn content_page(
+    config: &Config,
+    share_link: &ShareLinkRow,
+    content: &ContentRow,
+    url: &str,
+) -> Response {
+    let is_text = content.kind == "text";
+    let media = if is_text {
+        None
+    } else {
+        content.content_type.split('/').next()
+    };
+
+    let raw_url = format!("/s/{}/raw", share_link.token);
+    let view_url = (!is_text).then(|| raw_url.clone());
+    let download_url = (share_link.permission == "download" && !is_text)
+        .then(|| format!("{raw_url}?disposition=attachment"));
+
+    let mut body = format!("<h1>{}</h1>\n", escape(&content.title));
+    if let Some(description) = &content.description {
+        let _ = writeln!(body, "<p class=\"description\">{}</p>", escape(description));
+    }
+    match (media, view_url.as_deref()) {
+        _ if is_text => {
+            let text = content.body.as_deref().unwrap_or_default();
+            let _ = writeln!(body, "<pre class=\"text\">{}</pre>", escape(text));
+        }
+        (Some("image"), Some(src)) => {
+            let _ = writeln!(
+                body,
+                "<img class=\"preview\" src=\"{}\" alt=\"{}\">",
+                escape(src),
+                escape(&content.title)
+            );
+        }
+        (Some("video"), Some(src)) => {
+            let _ = writeln!(
+                body,
+                "<video class=\"preview\" src=\"{}\" controls preload=\"metadata\"></video>",
+                escape(src)
+            );
+        }
+        (Some("audio"), Some(src)) => {
+            let _ = writeln!(
+                body,
+                "<audio class=\"preview\" src=\"{}\" controls preload=\"metadata\"></audio>",
+                escape(src)
+            );
+        }
+        _ => {
+            let _ = writeln!(
+                body,
+                "<p class=\"file\">{} &middot; {}</p>",
+                escape(&content.content_type),
+                format_size(content.file_size)
+            );
+        }
+    }
+    if let Some(href) = &download_url {
+        let _ = writeln!(
+            body,
+            "<p><a class=\"button\" href=\"{}\" download>Download</a></p>",
+            escape(href)
+        );
+    }
+
+    let description = match (&content.description, &content.body) {
+        (Some(description), _) => description.clone(),
+        (None, Some(text)) if is_text => excerpt(text),
+        _ => format!("Shared on {SITE_NAME}"),
+    };
+    // Unfurling a protected link must not leak the content
+    let image = (ThumbnailFormat::for_source(&content.content_type).is_some()
+        && share_link.password_hash.is_none())
+    .then(|| {
+        format!(
+            "{}/s/{}/thumbnail",
+            config.share_base_url(),
+            share_link.token
+        )
+    });
+
+    Page {
+        status: StatusCode::OK,
+        title: &content.title,
+        description: &description,
+        url: Some(url),
+        image: image.as_deref(),
+        body,
+    }
+    .into_response()
+}
+
+fn group_page(
+    share_link: &ShareLinkRow,
+    group: &ContentGroupRow,
+    file_count: i64,
+    url: &str,
+) -> Response {
+    let files = if file_count == 1 {
+        "1 file".to_owned()
+    } else {
+        format!("{file_count} files")
+    };
+
+    let mut body = format!("<h1>{}</h1>\n", escape(&group.name));
+    if let Some(description) = &group.description {
+        let _ = writeln!(body, "<p class=\"description\">{}</p>", escape(description));
+    }
+    let _ = writeln!(body, "<p class=\"file\">{files}</p>");
+    // Protected links reach this point unlocked, with the unlock cookie the
+    // archive endpoint accepts
+    if share_link.permission == "download" {
+        let _ = writeln!(
+            body,
+            "<p><a class=\"button\" href=\"/v1/public/share/{}/archive.zip\" download>Download all</a></p>",
+            escape(&share_link.token)
+        );
+    }
+
+    let description = group
+        .description
+        .clone()
+        .unwrap_or_else(|| format!("{files} shared on {SITE_NAME}"));
+
+    Page {
+        status: StatusCode::OK,
+        title: &group.name,
+        description: &description,
+        url: Some(url),
+        image: None,
+        body,
+    }
+    .into_response()
+}
+
+fn password_page(status: StatusCode, url: &str, error: Option<&str>) -> Response {
+    let mut body = String::from("<h1>Password required</h1>\n");
+    if let Some(error) = error {
+        let _ = writeln!(body, "<p class=\"error\">{}</p>", escape(error));
+    }
+    body.push_str(
+        "<form method=\"post\">\n\
+         <label for=\"password\">Enter the password to view this share.</label>\n\
+         <input id=\"password\" name=\"password\" type=\"password\" required autofocus>\n\
+         <button class=\"button\" type=\"submit\">Unlock</button>\n\
+         </form>\n",
+    );
+
+    Page {
+        status,
+        title: "Password-protected share",
+        description: "Enter the password to view this share.",
+        url: Some(url),
+        image: None,
+        body,
+    }
+    .into_response()
+}
+
+fn message_page(status: StatusCode, title: &str, message: &str) -> Response {
+    Page {
+        status,
+        title,
+        description: message,
+        url: None,
+        image: None,
+        body: format!("<h1>{}</h1>\n<p>{}</p>\n", escape(title), escape(message)),
+    }
+    .into_response()
+}
+
+fn not_found_page() -> Response {
+    message_page(
+        StatusCode::NOT_FOUND,
+        "Share not found",
+        "This share link does not exist.",
+    )
+}
+
+fn internal_error_page() -> Response {
+    message_page(
+        StatusCode::INTERNAL_SERVER_ERROR,
+        "Something went wrong",
+        "The share could not be loaded. Please try again later.",
+    )
+}
+
+/// An HTML page with Open Graph and Twitter meta tags.
+struct Page<'a> {
+    status: StatusCode,
+    title: &'a str,
+    description: &'a str,
+    /// Canonical URL (`og:url`).
+    url: Option<&'a str>,
+    /// Preview image (`og:image`), making the Twitter card a large image.
+    image: Option<&'a str>,
+    /// Escaped HTML of the page's main element.
+    body: String,
+}
+
+impl IntoResponse for Page<'_> {
+    fn into_response(self) -> Response {
+        let title = escape(self.title);
+        let description = escape(self.description);
+
+        let mut meta = String::new();
+        let _ = writeln!(
+            meta,
+            "<meta property=\"og:site_name\" content=\"{SITE_NAME}\">"
+        );
+        let _ = writeln!(meta, "<meta property=\"og:type\" content=\"website\">");
+        let _ = writeln!(meta, "<meta property=\"og:title\" content=\"{title}\">");
+        let _ = writeln!(
+            meta,
+            "<meta property=\"og:description\" content=\"{description}\">"
+        );
+        if let Some(url) = self.url {
+            let _ = writeln!(
+                meta,
+                "<meta property=\"og:url\" content=\"{}\">",
+                escape(url)
+            );
+        }
+        let card = if let Some(image) = self.image {
+            let image = escape(image);
+            let _ = writeln!(meta, "<meta property=\"og:image\" content=\"{image}\">");
+            let _ = writeln!(meta, "<meta name=\"twitter:image\" content=\"{image}\">");
+            "summary_large_image"
+        } else {
+            "summary"
+        };
+        let _ = writeln!(meta, "<meta name=\"twitter:card\" content=\"{card}\">");
+        let _ = writeln!(meta, "<meta name=\"twitter:title\" content=\"{title}\">");
+        let _ = writeln!(
+            meta,
+            "<meta name=\"twitter:description\" content=\"{description}\">"
+        );
+
+        let html = format!(
+            "<!DOCTYPE html>\n\
+             <html lang=\"en\">\n\
+             <head>\n\
+             <meta charset=\"utf-8\">\n\
+             <meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\n\
+             <meta name=\"robots\" content=\"noindex\">\n\
+             <title>{title} &middot; {SITE_NAME}</title>\n\
+             <meta name=\"description\" content=\"{description}\">\n\
+             {meta}\
+             <style>{STYLE}</style>\n\
+             </head>\n\
+             <body>\n\
+             <main>\n\
+             {body}\
+             </main>\n\
+             </body>\n\
+             </html>\n",
+            body = self.body,
+        );
+
+        let mut response = (self.status, Html(html)).into_response();
+        let headers = response.headers_mut();
+        // Pages depend on the link's state and unlock cookie
+        headers.insert(header::CACHE_CONTROL, HeaderValue::from_static("no-store"));
+        // Keep the share token out of the Referer sent to other sites
+        headers.insert(
+            header::REFERRER_POLICY,
+            HeaderValue::from_static("no-referrer"),
+        );
+        response
+    }
+}
+
+const STYLE: &str = "\
+body{margin:0;font-family:system-ui,sans-serif;background:#f6f6f4;color:#1c1c1c}\
+main{max-width:48rem;margin:0 auto;padding:2rem 1rem}\
+h1{font-size:1.5rem;overflow-wrap:anywhere}\
+.description,.file{color:#555}\
+.error{color:#b00020}\
+.preview{display:block;max-width:100%;max-height:75vh;margin:1rem 0}\
+.text{white-space:pre-wrap;overflow-wrap:anywhere;background:#fff;padding:1rem;border-radius:.5rem}\
+form{display:flex;flex-direction:column;gap:.75rem;max-width:20rem}\
+input{padding:.5rem;font-size:1rem}\
+.button{display:inline-block;padding:.6rem 1.2rem;border:0;border-radius:.5rem;background:#1c1c1c;color:#fff;font-size:1rem;text-decoration:none;cursor:pointer}";
+
+/// Escape text for HTML content and double-quoted attribute values.
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// The start of a text, on one line, as a description.
+fn excerpt(text: &str) -> String {
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    match text.char_indices().nth(TEXT_DESCRIPTION_CHARS) {
+        Some((end, _)) => format!("{}…", &text[..end]),
+        None => text,
+    }
+}
+
+/// Human-readable file size.
+fn format_size(bytes: i64) -> String {
+    const UNITS: [&str; 4] = ["KB", "MB", "GB", "TB"];
+
+    if bytes < 1024 {
+        return format!("{bytes} B");
+    }
+    #[allow(clippy::cast_precision_loss)] // Display only
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    format!("{size:.1} {}", UNITS[unit])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_escape() {
+        assert_eq!(
+            escape(r#"<a href="x">Tom & Jerry's</a>"#),
+            "&lt;a href=&quot;x&quot;&gt;Tom &amp; Jerry&#39;s&lt;/a&gt;"
+        );
+    }
+
+    #[test]
+    fn test_excerpt() {
+        assert_eq!(excerpt("  line one\n\nline   two "), "line one line two");
+
+        let long = "é".repeat(TEXT_DESCRIPTION_CHARS + 10);
+        let short = excerpt(&long);
+        assert_eq!(short.chars().count(), TEXT_DESCRIPTION_CHARS + 1);
+        assert!(short.ends_with('…'));
+    }
+
+    #[test]
+    fn test_format_size() {
+        assert_eq!(format_size(512), "512 B");
+        assert_eq!(format_size(1536), "1.5 KB");
+        assert_eq!(format_size(5 * 1024 * 1024), "5.0 MB");
+    }
+}
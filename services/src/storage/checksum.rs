@@ -0,0 +1,86 @@
+//! SHA-256 checksums of stored objects.
+
+use futures_util::TryStreamExt as _;
+use sha2::{Digest, Sha256};
+
+use super::stream::stream_range;
+use super::traits::FileStorage;
+
+/// Normalize a hex-encoded SHA-256 digest to lowercase.
+///
+/// Returns `None` unless `digest` is exactly 64 hex characters.
+pub fn normalize_sha256(digest: &str) -> Option<String> {
+    (digest.len() == 64 && digest.bytes().all(|b| b.is_ascii_hexdigit()))
+        .then(|| digest.to_ascii_lowercase())
+}
+
+/// Compute the hex-encoded SHA-256 of the object stored at `path`.
+///
+/// The object is read and hashed one chunk at a time, so large objects are
+/// never held in memory.
+pub async fn object_sha256<F: FileStorage>(storage: &F, path: &str) -> Result<String, F::Error> {
+    let hasher = stream_range(storage.clone(), path.to_owned(), 0..u64::MAX)
+        .try_fold(Sha256::new(), |mut hasher, chunk| async move {
+            hasher.update(&chunk);
+            Ok(hasher)
+        })
+        .await?;
+    Ok(hex::encode(hasher.finalize()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::{FileUploadRequest, MockFileStorage};
+
+    #[test]
+    fn test_normalize_sha256() {
+        let digest = "E3B0C44298FC1C149AFBF4C8996FB92427AE41E4649B934CA495991B7852B855";
+        assert_eq!(
+            normalize_sha256(digest).as_deref(),
+            Some("e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855")
+        );
+        assert_eq!(normalize_sha256("abc"), None);
+        assert_eq!(normalize_sha256(&"g".repeat(64)), None);
+    }
+
+    #[tokio::test]
+    async fn test_object_sha256() {
+        let storage = MockFileStorage::new();
+        storage
+            .upload_file(FileUploadRequest::new(
+                "a/b.txt",
+                b"hello".to_vec(),
+                "text/plain",
+            ))
+            .await
+            .unwrap();
+
+        assert_eq!(
+            object_sha256(&storage, "a/b.txt").await.unwrap(),
+            "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824"
+        );
+        assert!(object_sha256(&storage, "missing").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_object_sha256_spans_chunks() {
+        let storage = MockFileStorage::new();
+        let content: Vec<u8> = (0..crate::storage::STREAM_CHUNK_SIZE * 2 + 7)
+            .map(|i| (i % 251) as u8)
+            .collect();
+        storage
+            .upload_file(FileUploadRequest::new(
+                "a/big.bin",
+                content.clone(),
+                "application/octet-stream",
+            ))
+            .await
+            .unwrap();
+
+        assert_eq!(
+            object_sha256(&storage, "a/big.bin").await.unwrap(),
+            hex::encode(Sha256::digest(&content))
+        );
+    }
+}
@@ -1,5 +1,11 @@
 //! Cloudflare R2 storage implementations.
 
+use std::io;
+use std::ops::Range;
+
+use axum::body::Bytes;
+use futures_util::Stream;
+
 use super::mock::MockFileStorage;
 use super::traits::{FileStorage, OpenDALDisk};
 use super::types::{FileMetadata, FileStorageError, FileUploadRequest};
@@ -150,6 +156,31 @@ impl FileStorage for CFFileStorage {
         ))
     }
 
+    async fn upload_stream<C>(
+        &self,
+        path: &str,
+        content_type: &str,
+        chunks: C,
+    ) -> Result<u64, Self::Error>
+    where
+        C: Stream<Item = io::Result<Bytes>> + Send,
+    {
+        if let Some(mock) = &self.mock {
+            return mock.upload_stream(path, content_type, chunks).await;
+        }
+
+        #[cfg(not(test))]
+        {
+            let op = self.create_operator()?;
+            super::stream::write_stream(&op, path, content_type, chunks).await
+        }
+
+        #[cfg(test)]
+        Err(FileStorageError::ConnectionError(
+            "No mock storage configured for test".to_owned(),
+        ))
+    }
+
     async fn download_file(&self, path: &str) -> Result<Vec<u8>, Self::Error> {
         if let Some(mock) = &self.mock {
             return mock.download_file(path).await;
@@ -173,6 +204,33 @@ impl FileStorage for CFFileStorage {
         ))
     }
 
+    async fn download_range(&self, path: &str, range: Range<u64>) -> Result<Vec<u8>, Self::Error> {
+        if let Some(mock) = &self.mock {
+            return mock.download_range(path, range).await;
+        }
+
+        #[cfg(not(test))]
+        {
+            let op = self.create_operator()?;
+            op.read_with(path)
+                .range(range)
+                .await
+                .map(|buf| buf.to_vec())
+                .map_err(|e| {
+                    if e.kind() == opendal::ErrorKind::NotFound {
+                        FileStorageError::NotFound(path.to_owned())
+                    } else {
+                        FileStorageError::StorageError(e.to_string())
+                    }
+                })
+        }
+
+        #[cfg(test)]
+        Err(FileStorageError::ConnectionError(
+            "No mock storage configured for test".to_owned(),
+        ))
+    }
+
     async fn delete_file(&self, path: &str) -> Result<bool, Self::Error> {
         if let Some(mock) = &self.mock {
             return mock.delete_file(path).await;
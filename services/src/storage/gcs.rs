@@ -0,0 +1,414 @@
+//! Google Cloud Storage implementations.
+//!
+//! Uses OpenDAL's `services-gcs`. Setting an `endpoint` points the backend at
+//! a GCS emulator (e.g. `fake-gcs-server`) for local development and tests.
+
+use std::io;
+use std::ops::Range;
+use std::time::Duration;
+
+use axum::body::Bytes;
+use futures_util::Stream;
+
+use super::presign::{
+    ContentDisposition, FileMetadata as PresignFileMetadata, PresignError, PresignedUrl,
+};
+use super::stream::write_stream;
+use super::traits::{FileStorage, OpenDALDisk};
+use super::types::{FileMetadata, FileStorageError, FileUploadRequest};
+
+/// Configuration for Google Cloud Storage.
+#[derive(Clone, Debug, Default)]
+pub struct GcsDiskConfig {
+    pub bucket: String,
+    /// Base64 encoded service account JSON. Required for presigning.
+    pub credential: Option<String>,
+    /// Custom endpoint (defaults to `https://storage.googleapis.com`).
+    pub endpoint: Option<String>,
+    /// Static OAuth2 access token, used instead of exchanging the credential.
+    pub token: Option<String>,
+}
+
+impl GcsDiskConfig {
+    fn create_operator(&self) -> opendal::Result<opendal::Operator> {
+        let mut builder = opendal::services::Gcs::default().bucket(&self.bucket);
+
+        if let Some(endpoint) = &self.endpoint {
+            builder = builder.endpoint(endpoint);
+        }
+        if let Some(token) = &self.token {
+            builder = builder.token(token.clone());
+        }
+        match &self.credential {
+            Some(credential) => builder = builder.credential(credential),
+            // Emulators accept unauthenticated requests; never fall back to
+            // ambient credentials or the VM metadata server.
+            None => {
+                builder = builder
+                    .allow_anonymous()
+                    .disable_vm_metadata()
+                    .disable_config_load();
+            }
+        }
+
+        opendal::Operator::new(builder).map(|op| op.finish())
+    }
+}
+
+/// Google Cloud Storage connectivity checker.
+#[derive(Clone)]
+pub struct GcsDisk {
+    config: GcsDiskConfig,
+}
+
+impl GcsDisk {
+    pub fn new(config: GcsDiskConfig) -> Self {
+        Self { config }
+    }
+}
+
+impl OpenDALDisk for GcsDisk {
+    async fn could_connected(&self) -> bool {
+        match self.config.create_operator() {
+            Ok(op) => op.check().await.is_ok(),
+            Err(_) => false,
+        }
+    }
+}
+
+/// Google Cloud Storage file storage.
+#[derive(Clone)]
+pub struct GcsFileStorage {
+    config: GcsDiskConfig,
+}
+
+impl GcsFileStorage {
+    pub fn new(config: GcsDiskConfig) -> Self {
+        Self { config }
+    }
+
+    fn create_operator(&self) -> Result<opendal::Operator, FileStorageError> {
+        self.config
+            .create_operator()
+            .map_err(|e| FileStorageError::StorageError(e.to_string()))
+    }
+}
+
+fn filename_of(path: &str) -> String {
+    path.split('/').next_back().unwrap_or(path).to_owned()
+}
+
+impl FileStorage for GcsFileStorage {
+    type Error = FileStorageError;
+
+    async fn upload_file(&self, request: FileUploadRequest) -> Result<FileMetadata, Self::Error> {
+        let op = self.create_operator()?;
+        let size = request.content.len() as u64;
+        op.write_with(&request.path, request.content)
+            .content_type(&request.content_type)
+            .await
+            .map_err(|e| FileStorageError::StorageError(e.to_string()))?;
+
+        Ok(FileMetadata {
+            filename: filename_of(&request.path),
+            id: request.path,
+            content_type: request.content_type,
+            size,
+            description: request.description,
+        })
+    }
+
+    async fn upload_stream<C>(
+        &self,
+        path: &str,
+        content_type: &str,
+        chunks: C,
+    ) -> Result<u64, Self::Error>
+    where
+        C: Stream<Item = io::Result<Bytes>> + Send,
+    {
+        let op = self.create_operator()?;
+        write_stream(&op, path, content_type, chunks).await
+    }
+
+    async fn download_file(&self, path: &str) -> Result<Vec<u8>, Self::Error> {
+        let op = self.create_operator()?;
+        op.read(path).await.map(|buf| buf.to_vec()).map_err(|e| {
+            if e.kind() == opendal::ErrorKind::NotFound {
+                FileStorageError::NotFound(path.to_owned())
+            } else {
+                FileStorageError::StorageError(e.to_string())
+            }
+        })
+    }
+
+    async fn download_range(&self, path: &str, range: Range<u64>) -> Result<Vec<u8>, Self::Error> {
+        let op = self.create_operator()?;
+        op.read_with(path)
+            .range(range)
+            .await
+            .map(|buf| buf.to_vec())
+            .map_err(|e| {
+                if e.kind() == opendal::ErrorKind::NotFound {
+                    FileStorageError::NotFound(path.to_owned())
+                } else {
+                    FileStorageError::StorageError(e.to_string())
+                }
+            })
+    }
+
+    async fn delete_file(&self, path: &str) -> Result<bool, Self::Error> {
+        let op = self.create_operator()?;
+        let exists = op
+            .exists(path)
+            .await
+            .map_err(|e| FileStorageError::StorageError(e.to_string()))?;
+
+        if exists {
+            op.delete(path)
+                .await
+                .map_err(|e| FileStorageError::StorageError(e.to_string()))?;
+        }
+        Ok(exists)
+    }
+
+    async fn list_files(&self, prefix: &str) -> Result<Vec<FileMetadata>, Self::Error> {
+        let op = self.create_operator()?;
+        let entries = op
+            .list_with(prefix)
+            .recursive(true)
+            .await
+            .map_err(|e| FileStorageError::StorageError(e.to_string()))?;
+
+        Ok(entries
+            .into_iter()
+            .filter(|entry| entry.metadata().is_file())
+            .map(|entry| FileMetadata {
+                id: entry.path().to_owned(),
+                filename: filename_of(entry.path()),
+                content_type: entry
+                    .metadata()
+                    .content_type()
+                    .unwrap_or("application/octet-stream")
+                    .to_owned(),
+                size: entry.metadata().content_length(),
+                description: None,
+            })
+            .collect())
+    }
+
+    async fn file_exists(&self, path: &str) -> Result<bool, Self::Error> {
+        let op = self.create_operator()?;
+        op.exists(path)
+            .await
+            .map_err(|e| FileStorageError::StorageError(e.to_string()))
+    }
+
+    async fn get_file_metadata(&self, path: &str) -> Result<Option<FileMetadata>, Self::Error> {
+        let op = self.create_operator()?;
+        match op.stat(path).await {
+            Ok(meta) => Ok(Some(FileMetadata {
+                id: path.to_owned(),
+                filename: filename_of(path),
+                content_type: meta
+                    .content_type()
+                    .unwrap_or("application/octet-stream")
+                    .to_owned(),
+                size: meta.content_length(),
+                description: None,
+            })),
+            Err(e) if e.kind() == opendal::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(FileStorageError::StorageError(e.to_string())),
+        }
+    }
+}
+
+/// Presigner for Google Cloud Storage (V4 signed URLs over the XML API).
+#[derive(Clone)]
+pub struct GcsPresigner {
+    config: GcsDiskConfig,
+}
+
+impl GcsPresigner {
+    /// Create a new presigner with the given configuration.
+    pub fn new(config: GcsDiskConfig) -> Self {
+        Self { config }
+    }
+
+    fn create_operator(&self) -> Result<opendal::Operator, PresignError> {
+        self.config
+            .create_operator()
+            .map_err(|e| PresignError::StorageError(e.to_string()))
+    }
+
+    /// Generate a presigned PUT URL for uploading a file.
+    pub async fn presign_put(
+        &self,
+        storage_key: &str,
+        content_type: &str,
+        expires_in: Duration,
+    ) -> Result<PresignedUrl, PresignError> {
+        let op = self.create_operator()?;
+        let expires_at = chrono::Utc::now()
+            + chrono::Duration::from_std(expires_in)
+                .map_err(|e| PresignError::PresignFailed(e.to_string()))?;
+
+        let presigned = op
+            .presign_write_with(storage_key, expires_in)
+            .content_type(content_type)
+            .await
+            .map_err(|e| PresignError::PresignFailed(e.to_string()))?;
+
+        Ok(PresignedUrl {
+            url: presigned.uri().to_string(),
+            expires_at,
+        })
+    }
+
+    /// Generate a presigned GET URL for downloading/viewing a file.
+    pub async fn presign_get(
+        &self,
+        storage_key: &str,
+        disposition: ContentDisposition,
+        expires_in: Duration,
+    ) -> Result<PresignedUrl, PresignError> {
+        let op = self.create_operator()?;
+        let expires_at = chrono::Utc::now()
+            + chrono::Duration::from_std(expires_in)
+                .map_err(|e| PresignError::PresignFailed(e.to_string()))?;
+
+        let disposition_value = match disposition {
+            ContentDisposition::Inline => "inline",
+            ContentDisposition::Attachment => "attachment",
+        };
+        let presigned = op
+            .presign_read_with(storage_key, expires_in)
+            .override_content_disposition(disposition_value)
+            .await
+            .map_err(|e| PresignError::PresignFailed(e.to_string()))?;
+
+        Ok(PresignedUrl {
+            url: presigned.uri().to_string(),
+            expires_at,
+        })
+    }
+
+    /// Check if a file exists at the given storage key.
+    pub async fn file_exists(&self, storage_key: &str) -> Result<bool, PresignError> {
+        let op = self.create_operator()?;
+        op.exists(storage_key)
+            .await
+            .map_err(|e| PresignError::StorageError(e.to_string()))
+    }
+
+    /// Get metadata for a file at the given storage key.
+    ///
+    /// Returns None if the file doesn't exist.
+    pub async fn get_metadata(
+        &self,
+        storage_key: &str,
+    ) -> Result<Option<PresignFileMetadata>, PresignError> {
+        let op = self.create_operator()?;
+        match op.stat(storage_key).await {
+            Ok(meta) => Ok(Some(PresignFileMetadata {
+                content_type: meta
+                    .content_type()
+                    .unwrap_or("application/octet-stream")
+                    .to_owned(),
+                content_length: meta.content_length(),
+            })),
+            Err(e) if e.kind() == opendal::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(PresignError::StorageError(e.to_string())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn emulator_config(server: &MockServer) -> GcsDiskConfig {
+        GcsDiskConfig {
+            bucket: "test-bucket".to_owned(),
+            endpoint: Some(server.uri()),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_gcs_presigner_against_fake_server() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/storage/v1/b/test-bucket/o/user%2Fphoto.png"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "name": "user/photo.png",
+                "bucket": "test-bucket",
+                "size": "42",
+                "contentType": "image/png",
+                "updated": "2026-01-01T00:00:00.000Z",
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/storage/v1/b/test-bucket/o/user%2Fmissing.png"))
+            .respond_with(ResponseTemplate::new(404))
+            .mount(&server)
+            .await;
+
+        let presigner = GcsPresigner::new(emulator_config(&server));
+
+        assert!(presigner.file_exists("user/photo.png").await.unwrap());
+        assert!(!presigner.file_exists("user/missing.png").await.unwrap());
+
+        let metadata = presigner
+            .get_metadata("user/photo.png")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(metadata.content_type, "image/png");
+        assert_eq!(metadata.content_length, 42);
+        assert!(
+            presigner
+                .get_metadata("user/missing.png")
+                .await
+                .unwrap()
+                .is_none()
+        );
+
+        let put = presigner
+            .presign_put("user/photo.png", "image/png", Duration::from_secs(300))
+            .await
+            .unwrap();
+        assert_eq!(
+            put.url,
+            format!("{}/test-bucket/user/photo.png", server.uri())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_gcs_file_storage_against_fake_server() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/storage/v1/b/test-bucket/o/user%2Fdoc.txt"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "name": "user/doc.txt",
+                "size": "5",
+                "contentType": "text/plain",
+                "updated": "2026-01-01T00:00:00.000Z",
+            })))
+            .mount(&server)
+            .await;
+
+        let storage = GcsFileStorage::new(emulator_config(&server));
+        let metadata = storage
+            .get_file_metadata("user/doc.txt")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(metadata.filename, "doc.txt");
+        assert_eq!(metadata.size, 5);
+        assert_eq!(metadata.content_type, "text/plain");
+    }
+}
@@ -0,0 +1,1357 @@
+//! Local filesystem storage backend.
+//!
+//! Objects are stored under a root directory and exposed through HMAC-signed
+//! URLs served by [`local_storage_routes`], so the direct-upload flow works
+//! without an S3-compatible service (local development, self-hosting).
+
+use std::ops::Range;
+use std::path::{Component, Path, PathBuf};
+use std::time::Duration;
+
+use axum::{
+    Router,
+    body::{Body, Bytes},
+    extract::{Extension, Path as UrlPath, Query},
+    http::{HeaderMap, StatusCode, header},
+    response::{IntoResponse, Response},
+    routing::get,
+};
+use futures_util::{Stream, TryStreamExt as _};
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest as _, Sha256};
+use tokio::io::{AsyncRead, AsyncReadExt as _, AsyncSeekExt as _, AsyncWriteExt as _};
+
+use super::multipart::CompletedPart;
+use super::presign::{
+    ContentDisposition, FileMetadata as PresignFileMetadata, PresignError, PresignedUrl, Presigner,
+};
+use super::router::{DEFAULT_STORAGE_PROFILE, StorageRouter};
+use super::traits::FileStorage;
+use super::types::{FileMetadata, FileStorageError, FileUploadRequest};
+
+/// Route prefix under which signed local storage URLs are served.
+pub const LOCAL_STORAGE_ROUTE_PREFIX: &str = "/storage/local";
+
+/// Directory (relative to the storage root) holding per-object metadata sidecars.
+const META_DIR: &str = ".meta";
+
+/// Directory (relative to the storage root) holding in-progress multipart uploads.
+const PARTS_DIR: &str = ".parts";
+
+/// File in a multipart upload directory describing the upload.
+const MULTIPART_MANIFEST: &str = "upload.json";
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Metadata persisted next to each stored object.
+#[derive(Debug, Serialize, Deserialize)]
+struct StoredMeta {
+    content_type: String,
+    description: Option<String>,
+}
+
+/// Manifest of an in-progress multipart upload.
+#[derive(Debug, Serialize, Deserialize)]
+struct MultipartManifest {
+    storage_key: String,
+    content_type: String,
+}
+
+/// File storage backed by a directory on the local filesystem.
+#[derive(Clone, Debug)]
+pub struct LocalFileStorage {
+    root: PathBuf,
+}
+
+impl LocalFileStorage {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Create the root directory if it does not exist yet.
+    pub async fn ensure_root(&self) -> Result<(), FileStorageError> {
+        tokio::fs::create_dir_all(&self.root)
+            .await
+            .map_err(|e| FileStorageError::StorageError(e.to_string()))
+    }
+
+    /// Resolve a storage key to a path under the root.
+    ///
+    /// Rejects keys that are empty, absolute, contain `..`/`.` segments or
+    /// point into the metadata or multipart directories.
+    fn object_path(&self, key: &str) -> Result<PathBuf, FileStorageError> {
+        let relative = Path::new(key);
+        let valid = !key.is_empty()
+            && !key.contains('\\')
+            && relative
+                .components()
+                .all(|c| matches!(c, Component::Normal(_)))
+            && relative
+                .components()
+                .next()
+                .is_some_and(|c| c.as_os_str() != META_DIR && c.as_os_str() != PARTS_DIR);
+
+        if !valid {
+            return Err(FileStorageError::InvalidFileType(format!(
+                "Invalid storage key: {key}"
+            )));
+        }
+
+        Ok(self.root.join(relative))
+    }
+
+    fn meta_path(&self, key: &str) -> Result<PathBuf, FileStorageError> {
+        self.object_path(key)?;
+        Ok(self.root.join(META_DIR).join(format!("{key}.json")))
+    }
+
+    async fn read_meta(&self, key: &str) -> Result<Option<StoredMeta>, FileStorageError> {
+        match tokio::fs::read(self.meta_path(key)?).await {
+            Ok(bytes) => serde_json::from_slice(&bytes)
+                .map(Some)
+                .map_err(|e| FileStorageError::StorageError(e.to_string())),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(FileStorageError::StorageError(e.to_string())),
+        }
+    }
+
+    async fn write_meta(&self, key: &str, meta: &StoredMeta) -> Result<(), FileStorageError> {
+        let path = self.meta_path(key)?;
+        create_parent_dir(&path).await?;
+        let bytes =
+            serde_json::to_vec(meta).map_err(|e| FileStorageError::StorageError(e.to_string()))?;
+        tokio::fs::write(path, bytes)
+            .await
+            .map_err(|e| FileStorageError::StorageError(e.to_string()))
+    }
+
+    /// Write an object from an async reader without buffering it in memory.
+    ///
+    /// The data is first written to a temporary file and renamed into place,
+    /// so readers never observe a partially written object.
+    pub async fn write_from_reader<R>(
+        &self,
+        key: &str,
+        content_type: &str,
+        reader: &mut R,
+    ) -> Result<u64, FileStorageError>
+    where
+        R: AsyncRead + Unpin + ?Sized,
+    {
+        let path = self.object_path(key)?;
+        create_parent_dir(&path).await?;
+
+        let tmp_path = path.with_file_name(format!(
+            ".{}.{}.tmp",
+            path.file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+            uuid::Uuid::new_v4()
+        ));
+
+        let written = async {
+            let mut file = tokio::fs::File::create(&tmp_path).await?;
+            let written = tokio::io::copy(reader, &mut file).await?;
+            file.flush().await?;
+            tokio::fs::rename(&tmp_path, &path).await?;
+            Ok::<_, std::io::Error>(written)
+        }
+        .await;
+
+        let written = match written {
+            Ok(written) => written,
+            Err(e) => {
+                let _ = tokio::fs::remove_file(&tmp_path).await;
+                return Err(FileStorageError::StorageError(e.to_string()));
+            }
+        };
+
+        self.write_meta(
+            key,
+            &StoredMeta {
+                content_type: content_type.to_owned(),
+                description: None,
+            },
+        )
+        .await?;
+
+        Ok(written)
+    }
+
+    /// Open an object for streaming reads.
+    ///
+    /// Returns None if the object doesn't exist.
+    pub async fn open(
+        &self,
+        key: &str,
+    ) -> Result<Option<(tokio::fs::File, FileMetadata)>, FileStorageError> {
+        let Some(metadata) = self.get_file_metadata(key).await? else {
+            return Ok(None);
+        };
+
+        match tokio::fs::File::open(self.object_path(key)?).await {
+            Ok(file) => Ok(Some((file, metadata))),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(FileStorageError::StorageError(e.to_string())),
+        }
+    }
+}
+
+impl LocalFileStorage {
+    /// Directory holding the parts of a multipart upload.
+    ///
+    /// Upload IDs are generated by [`Self::create_multipart_upload`]; anything
+    /// else is rejected so IDs cannot escape the parts directory.
+    fn multipart_dir(&self, upload_id: &str) -> Result<PathBuf, FileStorageError> {
+        if upload_id.is_empty() || !upload_id.chars().all(|c| c.is_ascii_alphanumeric()) {
+            return Err(FileStorageError::InvalidFileType(format!(
+                "Invalid upload ID: {upload_id}"
+            )));
+        }
+        Ok(self.root.join(PARTS_DIR).join(upload_id))
+    }
+
+    fn part_path(&self, upload_id: &str, part_number: u32) -> Result<PathBuf, FileStorageError> {
+        Ok(self
+            .multipart_dir(upload_id)?
+            .join(format!("{part_number:05}")))
+    }
+
+    async fn read_manifest(&self, upload_id: &str) -> Result<MultipartManifest, FileStorageError> {
+        let path = self.multipart_dir(upload_id)?.join(MULTIPART_MANIFEST);
+        match tokio::fs::read(path).await {
+            Ok(bytes) => serde_json::from_slice(&bytes)
+                .map_err(|e| FileStorageError::StorageError(e.to_string())),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Err(FileStorageError::NotFound(
+                format!("Multipart upload {upload_id}"),
+            )),
+            Err(e) => Err(FileStorageError::StorageError(e.to_string())),
+        }
+    }
+
+    /// Start a multipart upload for `key` and return its upload ID.
+    pub async fn create_multipart_upload(
+        &self,
+        key: &str,
+        content_type: &str,
+    ) -> Result<String, FileStorageError> {
+        self.object_path(key)?;
+
+        let upload_id = uuid::Uuid::new_v4().simple().to_string();
+        let dir = self.multipart_dir(&upload_id)?;
+        tokio::fs::create_dir_all(&dir)
+            .await
+            .map_err(|e| FileStorageError::StorageError(e.to_string()))?;
+
+        let manifest = serde_json::to_vec(&MultipartManifest {
+            storage_key: key.to_owned(),
+            content_type: content_type.to_owned(),
+        })
+        .map_err(|e| FileStorageError::StorageError(e.to_string()))?;
+        tokio::fs::write(dir.join(MULTIPART_MANIFEST), manifest)
+            .await
+            .map_err(|e| FileStorageError::StorageError(e.to_string()))?;
+
+        Ok(upload_id)
+    }
+
+    /// Write one part of a multipart upload and return its ETag (hex SHA-256).
+    ///
+    /// Re-uploading a part replaces it.
+    pub async fn write_part<R>(
+        &self,
+        key: &str,
+        upload_id: &str,
+        part_number: u32,
+        reader: &mut R,
+    ) -> Result<String, FileStorageError>
+    where
+        R: AsyncRead + Unpin + ?Sized,
+    {
+        let manifest = self.read_manifest(upload_id).await?;
+        if manifest.storage_key != key {
+            return Err(FileStorageError::NotFound(format!(
+                "Multipart upload {upload_id}"
+            )));
+        }
+
+        let path = self.part_path(upload_id, part_number)?;
+        let tmp_path = path.with_extension(format!("{}.tmp", uuid::Uuid::new_v4().simple()));
+
+        let etag = async {
+            let mut file = tokio::fs::File::create(&tmp_path).await?;
+            let mut hasher = Sha256::new();
+            let mut buf = vec![0u8; 64 * 1024];
+            loop {
+                let n = reader.read(&mut buf).await?;
+                if n == 0 {
+                    break;
+                }
+                hasher.update(&buf[..n]);
+                file.write_all(&buf[..n]).await?;
+            }
+            file.flush().await?;
+            tokio::fs::rename(&tmp_path, &path).await?;
+            Ok::<_, std::io::Error>(hex::encode(hasher.finalize()))
+        }
+        .await;
+
+        match etag {
+            Ok(etag) => Ok(etag),
+            Err(e) => {
+                let _ = tokio::fs::remove_file(&tmp_path).await;
+                Err(FileStorageError::StorageError(e.to_string()))
+            }
+        }
+    }
+
+    /// Concatenate the uploaded parts into the object at `key`.
+    ///
+    /// `parts` must be in ascending part number order. ETags are compared
+    /// against the stored parts when they are not empty.
+    pub async fn complete_multipart_upload(
+        &self,
+        key: &str,
+        upload_id: &str,
+        parts: &[CompletedPart],
+    ) -> Result<u64, FileStorageError> {
+        let manifest = self.read_manifest(upload_id).await?;
+        if manifest.storage_key != key {
+            return Err(FileStorageError::NotFound(format!(
+                "Multipart upload {upload_id}"
+            )));
+        }
+        if parts.is_empty()
+            || parts
+                .windows(2)
+                .any(|pair| pair[0].part_number >= pair[1].part_number)
+        {
+            return Err(FileStorageError::InvalidFileType(
+                "Parts must be listed in ascending order".to_owned(),
+            ));
+        }
+
+        let path = self.object_path(key)?;
+        create_parent_dir(&path).await?;
+        let tmp_path = self
+            .multipart_dir(upload_id)?
+            .join(format!("{}.tmp", uuid::Uuid::new_v4().simple()));
+
+        let written = async {
+            let mut output = tokio::fs::File::create(&tmp_path).await?;
+            let mut written = 0;
+            for part in parts {
+                let part_path = self
+                    .part_path(upload_id, part.part_number)
+                    .map_err(std::io::Error::other)?;
+                let mut input = tokio::fs::File::open(&part_path).await.map_err(|e| {
+                    std::io::Error::new(
+                        e.kind(),
+                        format!("Part {} has not been uploaded", part.part_number),
+                    )
+                })?;
+
+                let etag = part.etag.trim_matches('"');
+                if !etag.is_empty() {
+                    let mut hasher = Sha256::new();
+                    let mut buf = vec![0u8; 64 * 1024];
+                    loop {
+                        let n = input.read(&mut buf).await?;
+                        if n == 0 {
+                            break;
+                        }
+                        hasher.update(&buf[..n]);
+                    }
+                    if hex::encode(hasher.finalize()) != etag {
+                        return Err(std::io::Error::new(
+                            std::io::ErrorKind::InvalidData,
+                            format!("ETag mismatch for part {}", part.part_number),
+                        ));
+                    }
+                    input = tokio::fs::File::open(&part_path).await?;
+                }
+
+                written += tokio::io::copy(&mut input, &mut output).await?;
+            }
+            output.flush().await?;
+            tokio::fs::rename(&tmp_path, &path).await?;
+            Ok::<_, std::io::Error>(written)
+        }
+        .await;
+
+        let written = match written {
+            Ok(written) => written,
+            Err(e) => {
+                let _ = tokio::fs::remove_file(&tmp_path).await;
+                return Err(match e.kind() {
+                    std::io::ErrorKind::NotFound | std::io::ErrorKind::InvalidData => {
+                        FileStorageError::InvalidFileType(e.to_string())
+                    }
+                    _ => FileStorageError::StorageError(e.to_string()),
+                });
+            }
+        };
+
+        self.write_meta(
+            key,
+            &StoredMeta {
+                content_type: manifest.content_type,
+                description: None,
+            },
+        )
+        .await?;
+        self.abort_multipart_upload(upload_id).await?;
+
+        Ok(written)
+    }
+
+    /// Discard the parts of a multipart upload. Unknown uploads are ignored.
+    pub async fn abort_multipart_upload(&self, upload_id: &str) -> Result<(), FileStorageError> {
+        match tokio::fs::remove_dir_all(self.multipart_dir(upload_id)?).await {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(FileStorageError::StorageError(e.to_string())),
+        }
+    }
+}
+
+async fn create_parent_dir(path: &Path) -> Result<(), FileStorageError> {
+    if let Some(parent) = path.parent() {
+        tokio::fs::create_dir_all(parent)
+            .await
+            .map_err(|e| FileStorageError::StorageError(e.to_string()))?;
+    }
+    Ok(())
+}
+
+fn filename_of(key: &str) -> String {
+    key.split('/').next_back().unwrap_or(key).to_owned()
+}
+
+impl FileStorage for LocalFileStorage {
+    type Error = FileStorageError;
+
+    async fn upload_file(&self, request: FileUploadRequest) -> Result<FileMetadata, Self::Error> {
+        let path = self.object_path(&request.path)?;
+        create_parent_dir(&path).await?;
+        tokio::fs::write(&path, &request.content)
+            .await
+            .map_err(|e| FileStorageError::StorageError(e.to_string()))?;
+
+        self.write_meta(
+            &request.path,
+            &StoredMeta {
+                content_type: request.content_type.clone(),
+                description: request.description.clone(),
+            },
+        )
+        .await?;
+
+        Ok(FileMetadata {
+            filename: filename_of(&request.path),
+            id: request.path,
+            content_type: request.content_type,
+            size: request.content.len() as u64,
+            description: request.description,
+        })
+    }
+
+    async fn upload_stream<C>(
+        &self,
+        path: &str,
+        content_type: &str,
+        chunks: C,
+    ) -> Result<u64, Self::Error>
+    where
+        C: Stream<Item = std::io::Result<Bytes>> + Send,
+    {
+        let chunks = std::pin::pin!(chunks);
+        let mut reader = tokio_util::io::StreamReader::new(chunks);
+        self.write_from_reader(path, content_type, &mut reader)
+            .await
+    }
+
+    async fn download_file(&self, path: &str) -> Result<Vec<u8>, Self::Error> {
+        match tokio::fs::read(self.object_path(path)?).await {
+            Ok(content) => Ok(content),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                Err(FileStorageError::NotFound(path.to_owned()))
+            }
+            Err(e) => Err(FileStorageError::StorageError(e.to_string())),
+        }
+    }
+
+    async fn download_range(&self, path: &str, range: Range<u64>) -> Result<Vec<u8>, Self::Error> {
+        let mut file = match tokio::fs::File::open(self.object_path(path)?).await {
+            Ok(file) => file,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                return Err(FileStorageError::NotFound(path.to_owned()));
+            }
+            Err(e) => return Err(FileStorageError::StorageError(e.to_string())),
+        };
+
+        let mut content = Vec::new();
+        file.seek(std::io::SeekFrom::Start(range.start))
+            .await
+            .map_err(|e| FileStorageError::StorageError(e.to_string()))?;
+        file.take(range.end.saturating_sub(range.start))
+            .read_to_end(&mut content)
+            .await
+            .map_err(|e| FileStorageError::StorageError(e.to_string()))?;
+        Ok(content)
+    }
+
+    async fn delete_file(&self, path: &str) -> Result<bool, Self::Error> {
+        let deleted = match tokio::fs::remove_file(self.object_path(path)?).await {
+            Ok(()) => true,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => false,
+            Err(e) => return Err(FileStorageError::StorageError(e.to_string())),
+        };
+
+        match tokio::fs::remove_file(self.meta_path(path)?).await {
+            Ok(()) => {}
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => return Err(FileStorageError::StorageError(e.to_string())),
+        }
+
+        Ok(deleted)
+    }
+
+    async fn list_files(&self, prefix: &str) -> Result<Vec<FileMetadata>, Self::Error> {
+        let mut files = Vec::new();
+        let mut pending = vec![self.root.clone()];
+
+        while let Some(dir) = pending.pop() {
+            let mut entries = match tokio::fs::read_dir(&dir).await {
+                Ok(entries) => entries,
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+                Err(e) => return Err(FileStorageError::StorageError(e.to_string())),
+            };
+
+            while let Some(entry) = entries
+                .next_entry()
+                .await
+                .map_err(|e| FileStorageError::StorageError(e.to_string()))?
+            {
+                let path = entry.path();
+                let file_type = entry
+                    .file_type()
+                    .await
+                    .map_err(|e| FileStorageError::StorageError(e.to_string()))?;
+
+                if file_type.is_dir() {
+                    if dir != self.root
+                        || (entry.file_name() != META_DIR && entry.file_name() != PARTS_DIR)
+                    {
+                        pending.push(path);
+                    }
+                    continue;
+                }
+
+                // Skip in-flight temporary files from `write_from_reader`.
+                if entry.file_name().to_string_lossy().starts_with('.') {
+                    continue;
+                }
+
+                let Ok(relative) = path.strip_prefix(&self.root) else {
+                    continue;
+                };
+                let key = relative
+                    .components()
+                    .map(|c| c.as_os_str().to_string_lossy())
+                    .collect::<Vec<_>>()
+                    .join("/");
+
+                if key.starts_with(prefix)
+                    && let Some(metadata) = self.get_file_metadata(&key).await?
+                {
+                    files.push(metadata);
+                }
+            }
+        }
+
+        Ok(files)
+    }
+
+    async fn file_exists(&self, path: &str) -> Result<bool, Self::Error> {
+        tokio::fs::try_exists(self.object_path(path)?)
+            .await
+            .map_err(|e| FileStorageError::StorageError(e.to_string()))
+    }
+
+    async fn get_file_metadata(&self, path: &str) -> Result<Option<FileMetadata>, Self::Error> {
+        let size = match tokio::fs::metadata(self.object_path(path)?).await {
+            Ok(meta) if meta.is_file() => meta.len(),
+            Ok(_) => return Ok(None),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(FileStorageError::StorageError(e.to_string())),
+        };
+
+        let meta = self.read_meta(path).await?;
+        Ok(Some(FileMetadata {
+            id: path.to_owned(),
+            filename: filename_of(path),
+            content_type: meta.as_ref().map_or_else(
+                || "application/octet-stream".to_owned(),
+                |m| m.content_type.clone(),
+            ),
+            size,
+            description: meta.and_then(|m| m.description),
+        }))
+    }
+}
+
+/// Presigner issuing HMAC-SHA256 signed URLs for [`LocalFileStorage`].
+///
+/// URLs point at `{LOCAL_STORAGE_ROUTE_PREFIX}/{profile}/{key}` on `base_url`
+/// and carry the expiry timestamp and signature as query parameters.
+#[derive(Clone)]
+pub struct LocalPresigner {
+    storage: LocalFileStorage,
+    profile: String,
+    base_url: String,
+    secret: Vec<u8>,
+}
+
+impl LocalPresigner {
+    /// Create a new presigner.
+    ///
+    /// # Arguments
+    /// * `storage` - The local storage the signed URLs read from / write to
+    /// * `base_url` - Public base URL of this service (e.g. `http://127.0.0.1:8080`)
+    /// * `secret` - Key used to sign URLs
+    pub fn new(
+        storage: LocalFileStorage,
+        base_url: impl Into<String>,
+        secret: impl AsRef<[u8]>,
+    ) -> Self {
+        Self {
+            storage,
+            profile: DEFAULT_STORAGE_PROFILE.to_owned(),
+            base_url: base_url.into().trim_end_matches('/').to_owned(),
+            secret: secret.as_ref().to_vec(),
+        }
+    }
+
+    /// Set the storage profile this presigner serves (defaults to [`DEFAULT_STORAGE_PROFILE`]).
+    pub fn with_profile(mut self, profile: impl Into<String>) -> Self {
+        self.profile = profile.into();
+        self
+    }
+
+    pub fn storage(&self) -> &LocalFileStorage {
+        &self.storage
+    }
+
+    pub fn profile(&self) -> &str {
+        &self.profile
+    }
+
+    /// Build the MAC for a request.
+    ///
+    /// `extra` binds the content type (PUT), upload ID and part number
+    /// (part PUT) or disposition (GET) into the signature so clients cannot
+    /// change them.
+    fn mac(&self, method: &str, key: &str, expires: i64, extra: &str) -> HmacSha256 {
+        let mut mac =
+            HmacSha256::new_from_slice(&self.secret).expect("HMAC accepts keys of any length");
+        mac.update(format!("{method}\n{}\n{key}\n{expires}\n{extra}", self.profile).as_bytes());
+        mac
+    }
+
+    /// Verify a signed request.
+    ///
+    /// Returns false if the signature is malformed, doesn't match, or the
+    /// URL has expired.
+    pub fn verify(&self, method: &str, key: &str, query: &LocalSignedQuery) -> bool {
+        if query.expires < chrono::Utc::now().timestamp() {
+            return false;
+        }
+
+        let Ok(signature) = hex::decode(&query.signature) else {
+            return false;
+        };
+
+        let extra = match (method, &query.upload_id, query.part_number) {
+            ("PUT", Some(upload_id), Some(part_number)) => format!("{upload_id}\n{part_number}"),
+            ("PUT", _, _) => query.content_type.clone().unwrap_or_default(),
+            _ => query.disposition.clone().unwrap_or_default(),
+        };
+
+        self.mac(method, key, query.expires, &extra)
+            .verify_slice(&signature)
+            .is_ok()
+    }
+
+    /// Build a signed URL; the values of `params` are bound into the signature.
+    fn signed_url(
+        &self,
+        method: &str,
+        storage_key: &str,
+        expires_in: Duration,
+        params: &[(&str, &str)],
+    ) -> Result<PresignedUrl, PresignError> {
+        self.storage
+            .object_path(storage_key)
+            .map_err(|e| PresignError::PresignFailed(e.to_string()))?;
+
+        let expires_at = chrono::Utc::now()
+            + chrono::Duration::from_std(expires_in)
+                .map_err(|e| PresignError::PresignFailed(e.to_string()))?;
+        let expires = expires_at.timestamp();
+        let extra = params
+            .iter()
+            .map(|(_, value)| *value)
+            .collect::<Vec<_>>()
+            .join("\n");
+        let signature = hex::encode(
+            self.mac(method, storage_key, expires, &extra)
+                .finalize()
+                .into_bytes(),
+        );
+
+        let mut url = reqwest::Url::parse(&self.base_url)
+            .map_err(|e| PresignError::PresignFailed(e.to_string()))?;
+        url.path_segments_mut()
+            .map_err(|()| PresignError::PresignFailed("Invalid base URL".to_owned()))?
+            .pop_if_empty()
+            .extend(
+                LOCAL_STORAGE_ROUTE_PREFIX
+                    .trim_start_matches('/')
+                    .split('/'),
+            )
+            .push(&self.profile)
+            .extend(storage_key.split('/'));
+        {
+            let mut query = url.query_pairs_mut();
+            query.append_pair("expires", &expires.to_string());
+            for (name, value) in params {
+                query.append_pair(name, value);
+            }
+            query.append_pair("signature", &signature);
+        }
+
+        Ok(PresignedUrl {
+            url: url.to_string(),
+            expires_at,
+        })
+    }
+
+    /// Generate a signed PUT URL for uploading a file.
+    pub async fn presign_put(
+        &self,
+        storage_key: &str,
+        content_type: &str,
+        expires_in: Duration,
+    ) -> Result<PresignedUrl, PresignError> {
+        self.signed_url(
+            "PUT",
+            storage_key,
+            expires_in,
+            &[("content_type", content_type)],
+        )
+    }
+
+    /// Generate a signed GET URL for downloading/viewing a file.
+    pub async fn presign_get(
+        &self,
+        storage_key: &str,
+        disposition: ContentDisposition,
+        expires_in: Duration,
+    ) -> Result<PresignedUrl, PresignError> {
+        let disposition = match disposition {
+            ContentDisposition::Inline => "inline",
+            ContentDisposition::Attachment => "attachment",
+        };
+        self.signed_url(
+            "GET",
+            storage_key,
+            expires_in,
+            &[("disposition", disposition)],
+        )
+    }
+
+    /// Start a multipart upload and return its upload ID.
+    pub async fn create_multipart_upload(
+        &self,
+        storage_key: &str,
+        content_type: &str,
+    ) -> Result<String, PresignError> {
+        self.storage
+            .create_multipart_upload(storage_key, content_type)
+            .await
+            .map_err(|e| PresignError::StorageError(e.to_string()))
+    }
+
+    /// Generate a signed PUT URL for one part of a multipart upload.
+    pub async fn presign_upload_part(
+        &self,
+        storage_key: &str,
+        upload_id: &str,
+        part_number: u32,
+        expires_in: Duration,
+    ) -> Result<PresignedUrl, PresignError> {
+        self.signed_url(
+            "PUT",
+            storage_key,
+            expires_in,
+            &[
+                ("upload_id", upload_id),
+                ("part_number", &part_number.to_string()),
+            ],
+        )
+    }
+
+    /// Assemble the uploaded parts into the final object.
+    pub async fn complete_multipart_upload(
+        &self,
+        storage_key: &str,
+        upload_id: &str,
+        parts: &[CompletedPart],
+    ) -> Result<(), PresignError> {
+        self.storage
+            .complete_multipart_upload(storage_key, upload_id, parts)
+            .await
+            .map(drop)
+            .map_err(|e| PresignError::StorageError(e.to_string()))
+    }
+
+    /// Discard the parts of a multipart upload.
+    pub async fn abort_multipart_upload(&self, upload_id: &str) -> Result<(), PresignError> {
+        self.storage
+            .abort_multipart_upload(upload_id)
+            .await
+            .map_err(|e| PresignError::StorageError(e.to_string()))
+    }
+
+    /// Check if a file exists at the given storage key.
+    pub async fn file_exists(&self, storage_key: &str) -> Result<bool, PresignError> {
+        self.storage
+            .file_exists(storage_key)
+            .await
+            .map_err(|e| PresignError::StorageError(e.to_string()))
+    }
+
+    /// Get metadata for a file at the given storage key.
+    ///
+    /// Returns None if the file doesn't exist.
+    pub async fn get_metadata(
+        &self,
+        storage_key: &str,
+    ) -> Result<Option<PresignFileMetadata>, PresignError> {
+        self.storage
+            .get_file_metadata(storage_key)
+            .await
+            .map(|meta| {
+                meta.map(|m| PresignFileMetadata {
+                    content_type: m.content_type,
+                    content_length: m.size,
+                })
+            })
+            .map_err(|e| PresignError::StorageError(e.to_string()))
+    }
+}
+
+/// Query parameters carried by a signed local storage URL.
+#[derive(Debug, Deserialize)]
+pub struct LocalSignedQuery {
+    pub expires: i64,
+    pub signature: String,
+    pub content_type: Option<String>,
+    pub disposition: Option<String>,
+    /// Set on multipart part uploads.
+    pub upload_id: Option<String>,
+    pub part_number: Option<u32>,
+}
+
+/// Creates the routes serving signed local storage URLs.
+///
+/// The routes look up the local profile in the [`StorageRouter`] extension;
+/// requests for profiles without a local target are answered with 404.
+pub fn local_storage_routes<T>() -> Router<T>
+where
+    T: Clone + Send + Sync + 'static,
+{
+    Router::new().route(
+        &format!("{LOCAL_STORAGE_ROUTE_PREFIX}/{{profile}}/{{*key}}"),
+        get(local_storage_get).put(local_storage_put),
+    )
+}
+
+/// Find the local presigner serving `profile`.
+fn local_presigner(
+    router: Option<Extension<StorageRouter>>,
+    profile: &str,
+) -> Option<LocalPresigner> {
+    let Extension(router) = router?;
+    match router.resolve("local", profile)?.presigner() {
+        Presigner::Local(presigner) => Some(presigner.clone()),
+        _ => None,
+    }
+}
+
+/// Handle a signed PUT, streaming the request body to disk.
+async fn local_storage_put(
+    router: Option<Extension<StorageRouter>>,
+    UrlPath((profile, key)): UrlPath<(String, String)>,
+    Query(query): Query<LocalSignedQuery>,
+    headers: HeaderMap,
+    body: Body,
+) -> Response {
+    let Some(presigner) = local_presigner(router, &profile) else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+
+    if !presigner.verify("PUT", &key, &query) {
+        return (StatusCode::FORBIDDEN, "Invalid or expired signature").into_response();
+    }
+
+    let stream = body.into_data_stream().map_err(std::io::Error::other);
+    let mut reader = tokio_util::io::StreamReader::new(stream);
+
+    if let (Some(upload_id), Some(part_number)) = (&query.upload_id, query.part_number) {
+        return match presigner
+            .storage()
+            .write_part(&key, upload_id, part_number, &mut reader)
+            .await
+        {
+            Ok(etag) => (StatusCode::OK, [(header::ETAG, format!("\"{etag}\""))]).into_response(),
+            Err(FileStorageError::NotFound(msg)) => (StatusCode::NOT_FOUND, msg).into_response(),
+            Err(FileStorageError::InvalidFileType(msg)) => {
+                (StatusCode::BAD_REQUEST, msg).into_response()
+            }
+            Err(e) => {
+                tracing::error!("Failed to write local storage part: {:?}", e);
+                StatusCode::INTERNAL_SERVER_ERROR.into_response()
+            }
+        };
+    }
+
+    let content_type = query.content_type.unwrap_or_default();
+    let header_content_type = headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or_default();
+    if !content_type.is_empty() && header_content_type != content_type {
+        return (
+            StatusCode::FORBIDDEN,
+            "Content-Type does not match the signed URL",
+        )
+            .into_response();
+    }
+
+    match presigner
+        .storage()
+        .write_from_reader(&key, &content_type, &mut reader)
+        .await
+    {
+        Ok(_) => StatusCode::OK.into_response(),
+        Err(FileStorageError::InvalidFileType(msg)) => {
+            (StatusCode::BAD_REQUEST, msg).into_response()
+        }
+        Err(e) => {
+            tracing::error!("Failed to write local storage object: {:?}", e);
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+    }
+}
+
+/// Handle a signed GET, streaming the object from disk.
+async fn local_storage_get(
+    router: Option<Extension<StorageRouter>>,
+    UrlPath((profile, key)): UrlPath<(String, String)>,
+    Query(query): Query<LocalSignedQuery>,
+) -> Response {
+    let Some(presigner) = local_presigner(router, &profile) else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+
+    if !presigner.verify("GET", &key, &query) {
+        return (StatusCode::FORBIDDEN, "Invalid or expired signature").into_response();
+    }
+
+    let (file, metadata) = match presigner.storage().open(&key).await {
+        Ok(Some(found)) => found,
+        Ok(None) => return StatusCode::NOT_FOUND.into_response(),
+        Err(FileStorageError::InvalidFileType(msg)) => {
+            return (StatusCode::BAD_REQUEST, msg).into_response();
+        }
+        Err(e) => {
+            tracing::error!("Failed to read local storage object: {:?}", e);
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+    };
+
+    let disposition = query.disposition.unwrap_or_else(|| "inline".to_owned());
+    (
+        [
+            (header::CONTENT_TYPE, metadata.content_type),
+            (header::CONTENT_LENGTH, metadata.size.to_string()),
+            (header::CONTENT_DISPOSITION, disposition),
+            // The declared type is served from the API origin: never let a
+            // browser sniff or run the object as an active document.
+            (header::X_CONTENT_TYPE_OPTIONS, "nosniff".to_owned()),
+            (header::CONTENT_SECURITY_POLICY, "sandbox".to_owned()),
+        ],
+        Body::from_stream(tokio_util::io::ReaderStream::new(file)),
+    )
+        .into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::{BackendFileStorage, StorageTarget};
+    use axum::http::Request;
+    use tower::ServiceExt;
+
+    fn make_presigner(dir: &tempfile::TempDir) -> LocalPresigner {
+        LocalPresigner::new(
+            LocalFileStorage::new(dir.path()),
+            "http://127.0.0.1:8080",
+            "test-secret",
+        )
+    }
+
+    fn routes_for(presigner: &LocalPresigner) -> Router {
+        let router = StorageRouter::new().with_target(StorageTarget::new(
+            presigner.profile(),
+            Presigner::Local(presigner.clone()),
+            BackendFileStorage::Local(presigner.storage().clone()),
+        ));
+        local_storage_routes().layer(Extension(router))
+    }
+
+    fn path_and_query(url: &str) -> String {
+        let url = reqwest::Url::parse(url).unwrap();
+        format!("{}?{}", url.path(), url.query().unwrap_or_default())
+    }
+
+    fn signed_query(url: &str) -> Option<LocalSignedQuery> {
+        let url = reqwest::Url::parse(url).ok()?;
+        let pairs: std::collections::HashMap<String, String> =
+            url.query_pairs().into_owned().collect();
+        Some(LocalSignedQuery {
+            expires: pairs.get("expires")?.parse().ok()?,
+            signature: pairs.get("signature")?.clone(),
+            content_type: pairs.get("content_type").cloned(),
+            disposition: pairs.get("disposition").cloned(),
+            upload_id: pairs.get("upload_id").cloned(),
+            part_number: pairs.get("part_number").and_then(|n| n.parse().ok()),
+        })
+    }
+
+    #[tokio::test]
+    async fn test_local_file_storage_roundtrip() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = LocalFileStorage::new(dir.path());
+
+        let request = FileUploadRequest::new("user/doc.txt", b"hello".to_vec(), "text/plain");
+        storage.upload_file(request).await.unwrap();
+
+        assert!(storage.file_exists("user/doc.txt").await.unwrap());
+        assert_eq!(
+            storage.download_file("user/doc.txt").await.unwrap(),
+            b"hello"
+        );
+        assert_eq!(
+            storage.download_range("user/doc.txt", 1..3).await.unwrap(),
+            b"el"
+        );
+        assert_eq!(
+            storage
+                .download_range("user/doc.txt", 3..100)
+                .await
+                .unwrap(),
+            b"lo"
+        );
+
+        let metadata = storage
+            .get_file_metadata("user/doc.txt")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(metadata.content_type, "text/plain");
+        assert_eq!(metadata.size, 5);
+
+        assert_eq!(storage.list_files("user/").await.unwrap().len(), 1);
+        assert!(storage.list_files("other/").await.unwrap().is_empty());
+
+        assert!(storage.delete_file("user/doc.txt").await.unwrap());
+        assert!(!storage.delete_file("user/doc.txt").await.unwrap());
+        assert!(!storage.file_exists("user/doc.txt").await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_local_file_storage_rejects_path_traversal() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = LocalFileStorage::new(dir.path());
+
+        for key in ["../escape.txt", "/etc/passwd", "a/../../b", ".meta/x", ""] {
+            let result = storage.file_exists(key).await;
+            assert!(
+                matches!(result, Err(FileStorageError::InvalidFileType(_))),
+                "key {key:?} should be rejected"
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_presigned_urls_verify() {
+        let dir = tempfile::tempdir().unwrap();
+        let presigner = make_presigner(&dir);
+
+        let put = presigner
+            .presign_put("user/a b.txt", "text/plain", Duration::from_secs(300))
+            .await
+            .unwrap();
+        assert!(
+            put.url
+                .starts_with("http://127.0.0.1:8080/storage/local/default/user/a%20b.txt?")
+        );
+
+        let query: LocalSignedQuery = signed_query(&put.url).expect("query should deserialize");
+        assert!(presigner.verify("PUT", "user/a b.txt", &query));
+        assert!(!presigner.verify("GET", "user/a b.txt", &query));
+        assert!(!presigner.verify("PUT", "user/other.txt", &query));
+
+        // Signatures are bound to the profile
+        let other_profile = make_presigner(&dir).with_profile("archives");
+        assert!(!other_profile.verify("PUT", "user/a b.txt", &query));
+
+        let expired = LocalSignedQuery {
+            expires: chrono::Utc::now().timestamp() - 1,
+            ..query
+        };
+        assert!(!presigner.verify("PUT", "user/a b.txt", &expired));
+    }
+
+    #[tokio::test]
+    async fn test_local_storage_routes_put_then_get() {
+        let dir = tempfile::tempdir().unwrap();
+        let presigner = make_presigner(&dir);
+        let app = routes_for(&presigner);
+
+        let put = presigner
+            .presign_put("user/photo.png", "image/png", Duration::from_secs(300))
+            .await
+            .unwrap();
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .method("PUT")
+                    .uri(path_and_query(&put.url))
+                    .header("content-type", "image/png")
+                    .body(Body::from("png bytes"))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(presigner.file_exists("user/photo.png").await.unwrap());
+
+        let get = presigner
+            .presign_get(
+                "user/photo.png",
+                ContentDisposition::Attachment,
+                Duration::from_secs(300),
+            )
+            .await
+            .unwrap();
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri(path_and_query(&get.url))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[header::CONTENT_TYPE], "image/png");
+        assert_eq!(
+            response.headers()[header::CONTENT_DISPOSITION],
+            "attachment"
+        );
+        assert_eq!(
+            response.headers()[header::X_CONTENT_TYPE_OPTIONS],
+            "nosniff"
+        );
+        assert_eq!(
+            response.headers()[header::CONTENT_SECURITY_POLICY],
+            "sandbox"
+        );
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        assert_eq!(&body[..], b"png bytes");
+    }
+
+    #[tokio::test]
+    async fn test_local_storage_routes_reject_tampered_signature() {
+        let dir = tempfile::tempdir().unwrap();
+        let presigner = make_presigner(&dir);
+        let app = routes_for(&presigner);
+
+        let put = presigner
+            .presign_put("user/file.txt", "text/plain", Duration::from_secs(300))
+            .await
+            .unwrap();
+        // Reuse the signature for a different key
+        let tampered = path_and_query(&put.url).replace("file.txt", "other.txt");
+
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .method("PUT")
+                    .uri(tampered)
+                    .header("content-type", "text/plain")
+                    .body(Body::from("data"))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+        assert!(!presigner.file_exists("user/other.txt").await.unwrap());
+
+        // Unknown profiles are not served
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method("PUT")
+                    .uri(path_and_query(&put.url).replace("/default/", "/archives/"))
+                    .header("content-type", "text/plain")
+                    .body(Body::from("data"))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_local_multipart_upload_through_routes() {
+        let dir = tempfile::tempdir().unwrap();
+        let presigner = make_presigner(&dir);
+        let app = routes_for(&presigner);
+
+        let upload_id = presigner
+            .create_multipart_upload("user/big.bin", "application/octet-stream")
+            .await
+            .unwrap();
+
+        let mut parts = Vec::new();
+        for (part_number, data) in [(1, "hello "), (2, "world")] {
+            let url = presigner
+                .presign_upload_part(
+                    "user/big.bin",
+                    &upload_id,
+                    part_number,
+                    Duration::from_secs(300),
+                )
+                .await
+                .unwrap();
+            let response = app
+                .clone()
+                .oneshot(
+                    Request::builder()
+                        .method("PUT")
+                        .uri(path_and_query(&url.url))
+                        .body(Body::from(data))
+                        .unwrap(),
+                )
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+            let etag = response.headers()[header::ETAG]
+                .to_str()
+                .unwrap()
+                .to_owned();
+            parts.push(CompletedPart { part_number, etag });
+        }
+
+        // Part URLs are bound to their upload and part number
+        let url = presigner
+            .presign_upload_part("user/big.bin", &upload_id, 1, Duration::from_secs(300))
+            .await
+            .unwrap();
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method("PUT")
+                    .uri(path_and_query(&url.url).replace("part_number=1", "part_number=3"))
+                    .body(Body::from("tampered"))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+        // A wrong ETag is rejected
+        let mut bad_parts = parts.clone();
+        bad_parts[0].etag = "\"0000\"".to_owned();
+        assert!(
+            presigner
+                .complete_multipart_upload("user/big.bin", &upload_id, &bad_parts)
+                .await
+                .is_err()
+        );
+
+        presigner
+            .complete_multipart_upload("user/big.bin", &upload_id, &parts)
+            .await
+            .unwrap();
+        assert_eq!(
+            presigner
+                .storage()
+                .download_file("user/big.bin")
+                .await
+                .unwrap(),
+            b"hello world"
+        );
+        assert_eq!(
+            presigner
+                .get_metadata("user/big.bin")
+                .await
+                .unwrap()
+                .unwrap()
+                .content_type,
+            "application/octet-stream"
+        );
+        assert!(!dir.path().join(PARTS_DIR).join(&upload_id).exists());
+        assert_eq!(presigner.storage().list_files("").await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_local_multipart_abort_discards_parts() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = LocalFileStorage::new(dir.path());
+
+        let upload_id = storage
+            .create_multipart_upload("user/big.bin", "text/plain")
+            .await
+            .unwrap();
+        storage
+            .write_part("user/big.bin", &upload_id, 1, &mut &b"data"[..])
+            .await
+            .unwrap();
+        assert!(storage.list_files("").await.unwrap().is_empty());
+
+        storage.abort_multipart_upload(&upload_id).await.unwrap();
+        assert!(!dir.path().join(PARTS_DIR).join(&upload_id).exists());
+        assert!(matches!(
+            storage
+                .write_part("user/big.bin", &upload_id, 1, &mut &b"data"[..])
+                .await,
+            Err(FileStorageError::NotFound(_))
+        ));
+        assert!(storage.multipart_dir("../escape").is_err());
+    }
+}
@@ -0,0 +1,376 @@
+//! Media metadata extracted from uploaded files.
+//!
+//! Images yield their dimensions and EXIF capture time and orientation, PDFs
+//! their page count, and audio/video files their duration. Anything that
+//! cannot be read is left out rather than failing the extraction.
+
+use std::io::Cursor;
+
+use exif::{In, Tag};
+use image::ImageReader;
+use serde::{Deserialize, Serialize};
+use symphonia::core::formats::FormatOptions;
+use symphonia::core::io::MediaSourceStream;
+use symphonia::core::meta::MetadataOptions;
+use symphonia::core::probe::Hint;
+
+use super::sniff::mime_essence;
+
+/// Originals larger than this are not inspected.
+pub const MAX_MEDIA_SOURCE_SIZE: u64 = 128 * 1024 * 1024;
+
+/// Metadata of a media file, stored as `contents.metadata`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct MediaMetadata {
+    /// Width in pixels, before applying `orientation`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub width: Option<u32>,
+    /// Height in pixels, before applying `orientation`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub height: Option<u32>,
+    /// EXIF orientation (1-8).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub orientation: Option<u16>,
+    /// EXIF capture time as `YYYY-MM-DDTHH:MM:SS` in the camera's local
+    /// time, followed by its UTC offset when recorded.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub captured_at: Option<String>,
+    /// Number of pages of a PDF.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub page_count: Option<u32>,
+    /// Duration of audio or video, in seconds.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duration_secs: Option<f64>,
+}
+
+impl MediaMetadata {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// Kind of media a content type is inspected as.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MediaKind {
+    Image,
+    Pdf,
+    AudioVideo,
+}
+
+impl MediaKind {
+    /// Media kind of `content_type`, `None` if no metadata is extracted from it.
+    pub fn for_content_type(content_type: &str) -> Option<Self> {
+        let essence = mime_essence(content_type);
+        match essence.as_str() {
+            "application/pdf" => Some(Self::Pdf),
+            _ if essence.starts_with("image/") => Some(Self::Image),
+            _ if essence.starts_with("audio/") || essence.starts_with("video/") => {
+                Some(Self::AudioVideo)
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Extract the metadata of a file of `content_type`.
+///
+/// CPU-bound; run it off the async runtime.
+pub fn extract_media_metadata(content_type: &str, data: &[u8]) -> MediaMetadata {
+    let mut metadata = MediaMetadata::default();
+    match MediaKind::for_content_type(content_type) {
+        Some(MediaKind::Image) => {
+            if let Some((width, height)) = image_dimensions(data) {
+                metadata.width = Some(width);
+                metadata.height = Some(height);
+            }
+            if let Ok(exif) = exif::Reader::new().read_from_container(&mut Cursor::new(data)) {
+                metadata.orientation = exif
+                    .get_field(Tag::Orientation, In::PRIMARY)
+                    .and_then(|field| field.value.get_uint(0))
+                    .and_then(|orientation| u16::try_from(orientation).ok())
+                    .filter(|orientation| (1..=8).contains(orientation));
+                metadata.captured_at = exif_captured_at(&exif);
+            }
+        }
+        Some(MediaKind::Pdf) => {
+            metadata.page_count = lopdf::Document::load_metadata_mem(data)
+                .ok()
+                .map(|pdf| pdf.page_count);
+        }
+        Some(MediaKind::AudioVideo) => {
+            metadata.duration_secs = duration_secs(content_type, data);
+        }
+        None => {}
+    }
+    metadata
+}
+
+fn image_dimensions(data: &[u8]) -> Option<(u32, u32)> {
+    ImageReader::new(Cursor::new(data))
+        .with_guessed_format()
+        .ok()?
+        .into_dimensions()
+        .ok()
+}
+
+fn exif_captured_at(exif: &exif::Exif) -> Option<String> {
+    let ascii = |tag| match exif.get_field(tag, In::PRIMARY).map(|field| &field.value) {
+        Some(exif::Value::Ascii(values)) => values.first().cloned(),
+        _ => None,
+    };
+    let (mut captured_at, offset_tag) = [
+        (Tag::DateTimeOriginal, Tag::OffsetTimeOriginal),
+        (Tag::DateTimeDigitized, Tag::OffsetTimeDigitized),
+        (Tag::DateTime, Tag::OffsetTime),
+    ]
+    .into_iter()
+    .find_map(|(tag, offset_tag)| {
+        let captured_at = exif::DateTime::from_ascii(&ascii(tag)?).ok()?;
+        Some((captured_at, offset_tag))
+    })?;
+    if !(1..=12).contains(&captured_at.month)
+        || !(1..=31).contains(&captured_at.day)
+        || captured_at.hour > 23
+        || captured_at.minute > 59
+        || captured_at.second > 60
+    {
+        return None;
+    }
+
+    let mut formatted = format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}",
+        captured_at.year,
+        captured_at.month,
+        captured_at.day,
+        captured_at.hour,
+        captured_at.minute,
+        captured_at.second
+    );
+    if let Some(offset) = ascii(offset_tag) {
+        captured_at.parse_offset(&offset).ok();
+    }
+    if let Some(offset) = captured_at.offset {
+        let sign = if offset < 0 { '-' } else { '+' };
+        let offset = offset.unsigned_abs();
+        formatted.push_str(&format!("{sign}{:02}:{:02}", offset / 60, offset % 60));
+    }
+    Some(formatted)
+}
+
+fn duration_secs(content_type: &str, data: &[u8]) -> Option<f64> {
+    let mut hint = Hint::new();
+    hint.mime_type(&mime_essence(content_type));
+    let source = MediaSourceStream::new(Box::new(Cursor::new(data.to_vec())), Default::default());
+    let probed = symphonia::default::get_probe()
+        .format(
+            &hint,
+            source,
+            &FormatOptions::default(),
+            &MetadataOptions::default(),
+        )
+        .ok()?;
+
+    probed
+        .format
+        .tracks()
+        .iter()
+        .filter_map(|track| {
+            let params = &track.codec_params;
+            let time = params.time_base?.calc_time(params.n_frames?);
+            Some(time.seconds as f64 + time.frac)
+        })
+        .max_by(f64::total_cmp)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{DynamicImage, ImageFormat};
+
+    fn png(width: u32, height: u32) -> Vec<u8> {
+        let image = DynamicImage::ImageRgb8(image::RgbImage::new(width, height));
+        let mut encoded = Cursor::new(Vec::new());
+        image.write_to(&mut encoded, ImageFormat::Png).unwrap();
+        encoded.into_inner()
+    }
+
+    /// A 4x2 JPEG with an APP1 segment holding the EXIF `tiff`.
+    fn jpeg_with_exif(tiff: &[u8]) -> Vec<u8> {
+        let image = DynamicImage::ImageRgb8(image::RgbImage::new(4, 2));
+        let mut encoded = Cursor::new(Vec::new());
+        image.write_to(&mut encoded, ImageFormat::Jpeg).unwrap();
+        let jpeg = encoded.into_inner();
+
+        let mut app1 = b"Exif\0\0".to_vec();
+        app1.extend_from_slice(tiff);
+        let mut out = jpeg[..2].to_vec();
+        out.extend_from_slice(&[0xFF, 0xE1]);
+        out.extend_from_slice(&((app1.len() + 2) as u16).to_be_bytes());
+        out.extend_from_slice(&app1);
+        out.extend_from_slice(&jpeg[2..]);
+        out
+    }
+
+    /// Little-endian TIFF with Orientation in IFD0 and DateTimeOriginal plus
+    /// OffsetTimeOriginal in the Exif IFD.
+    fn exif_tiff(orientation: u16, captured_at: &[u8; 20], offset: &[u8; 7]) -> Vec<u8> {
+        let entry = |tag: u16, typ: u16, count: u32, value: u32| {
+            let mut e = tag.to_le_bytes().to_vec();
+            e.extend_from_slice(&typ.to_le_bytes());
+            e.extend_from_slice(&count.to_le_bytes());
+            e.extend_from_slice(&value.to_le_bytes());
+            e
+        };
+        // Header (8) + IFD0 with 2 entries (2 + 24 + 4) = 38
+        let exif_ifd = 38u32;
+        // Exif IFD with 2 entries (2 + 24 + 4) = 30
+        let date_offset = exif_ifd + 30;
+        let offset_offset = date_offset + 20;
+
+        let mut tiff = b"II*\0".to_vec();
+        tiff.extend_from_slice(&8u32.to_le_bytes());
+        tiff.extend_from_slice(&2u16.to_le_bytes());
+        tiff.extend(entry(0x0112, 3, 1, u32::from(orientation)));
+        tiff.extend(entry(0x8769, 4, 1, exif_ifd));
+        tiff.extend_from_slice(&0u32.to_le_bytes());
+        tiff.extend_from_slice(&2u16.to_le_bytes());
+        tiff.extend(entry(0x9003, 2, 20, date_offset));
+        tiff.extend(entry(0x9011, 2, 7, offset_offset));
+        tiff.extend_from_slice(&0u32.to_le_bytes());
+        tiff.extend_from_slice(captured_at);
+        tiff.extend_from_slice(offset);
+        tiff
+    }
+
+    /// A 16-bit mono PCM WAV of `frames` samples at 8 kHz.
+    fn wav(frames: u32) -> Vec<u8> {
+        let data_len = frames * 2;
+        let mut wav = b"RIFF".to_vec();
+        wav.extend_from_slice(&(36 + data_len).to_le_bytes());
+        wav.extend_from_slice(b"WAVEfmt ");
+        wav.extend_from_slice(&16u32.to_le_bytes());
+        wav.extend_from_slice(&1u16.to_le_bytes());
+        wav.extend_from_slice(&1u16.to_le_bytes());
+        wav.extend_from_slice(&8000u32.to_le_bytes());
+        wav.extend_from_slice(&16000u32.to_le_bytes());
+        wav.extend_from_slice(&2u16.to_le_bytes());
+        wav.extend_from_slice(&16u16.to_le_bytes());
+        wav.extend_from_slice(b"data");
+        wav.extend_from_slice(&data_len.to_le_bytes());
+        wav.resize(wav.len() + data_len as usize, 0);
+        wav
+    }
+
+    fn pdf(pages: usize) -> Vec<u8> {
+        use lopdf::{Document, Object, dictionary};
+
+        let mut doc = Document::with_version("1.5");
+        let pages_id = doc.new_object_id();
+        let kids: Vec<Object> = (0..pages)
+            .map(|_| {
+                doc.add_object(dictionary! {
+                    "Type" => "Page",
+                    "Parent" => pages_id,
+                    "MediaBox" => vec![0.into(), 0.into(), 595.into(), 842.into()],
+                })
+                .into()
+            })
+            .collect();
+        doc.objects.insert(
+            pages_id,
+            Object::Dictionary(dictionary! {
+                "Type" => "Pages",
+                "Kids" => kids,
+                "Count" => pages as i64,
+            }),
+        );
+        let catalog_id = doc.add_object(dictionary! {
+            "Type" => "Catalog",
+            "Pages" => pages_id,
+        });
+        doc.trailer.set("Root", catalog_id);
+        let mut out = Vec::new();
+        doc.save_to(&mut out).unwrap();
+        out
+    }
+
+    #[test]
+    fn test_media_kind_for_content_type() {
+        assert_eq!(
+            MediaKind::for_content_type("image/JPG"),
+            Some(MediaKind::Image)
+        );
+        assert_eq!(
+            MediaKind::for_content_type("application/pdf"),
+            Some(MediaKind::Pdf)
+        );
+        assert_eq!(
+            MediaKind::for_content_type("video/mp4"),
+            Some(MediaKind::AudioVideo)
+        );
+        assert_eq!(MediaKind::for_content_type("text/plain"), None);
+    }
+
+    #[test]
+    fn test_extract_image_dimensions() {
+        let metadata = extract_media_metadata("image/png", &png(30, 20));
+        assert_eq!(
+            metadata,
+            MediaMetadata {
+                width: Some(30),
+                height: Some(20),
+                ..Default::default()
+            }
+        );
+    }
+
+    #[test]
+    fn test_extract_exif_capture_time_and_orientation() {
+        let jpeg = jpeg_with_exif(&exif_tiff(6, b"2024:05:01 10:20:30\0", b"+09:00\0"));
+        let metadata = extract_media_metadata("image/jpeg", &jpeg);
+        assert_eq!(metadata.width, Some(4));
+        assert_eq!(metadata.height, Some(2));
+        assert_eq!(metadata.orientation, Some(6));
+        assert_eq!(
+            metadata.captured_at.as_deref(),
+            Some("2024-05-01T10:20:30+09:00")
+        );
+
+        // Blank offsets are ignored
+        let jpeg = jpeg_with_exif(&exif_tiff(1, b"2024:05:01 10:20:30\0", b"   :  \0"));
+        let metadata = extract_media_metadata("image/jpeg", &jpeg);
+        assert_eq!(metadata.captured_at.as_deref(), Some("2024-05-01T10:20:30"));
+    }
+
+    #[test]
+    fn test_extract_pdf_page_count() {
+        let metadata = extract_media_metadata("application/pdf", &pdf(3));
+        assert_eq!(metadata.page_count, Some(3));
+    }
+
+    #[test]
+    fn test_extract_audio_duration() {
+        let metadata = extract_media_metadata("audio/wav", &wav(12_000));
+        assert_eq!(metadata.duration_secs, Some(1.5));
+    }
+
+    #[test]
+    fn test_extract_unreadable_media_is_empty() {
+        assert!(extract_media_metadata("image/png", b"not an image").is_empty());
+        assert!(extract_media_metadata("application/pdf", b"%PDF-").is_empty());
+        assert!(extract_media_metadata("video/mp4", b"not a video").is_empty());
+        assert!(extract_media_metadata("text/plain", b"hello").is_empty());
+    }
+
+    #[test]
+    fn test_media_metadata_serializes_present_fields_only() {
+        let metadata = MediaMetadata {
+            page_count: Some(2),
+            ..Default::default()
+        };
+        assert_eq!(
+            serde_json::to_value(&metadata).unwrap(),
+            serde_json::json!({ "page_count": 2 })
+        );
+    }
+}
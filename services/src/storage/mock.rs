@@ -1,5 +1,11 @@
 //! Mock file storage for testing.
 
+use std::io;
+use std::ops::Range;
+
+use axum::body::Bytes;
+use futures_util::{Stream, TryStreamExt as _};
+
 use super::traits::FileStorage;
 use super::types::{FileMetadata, FileStorageError, FileUploadRequest};
 
@@ -63,6 +69,25 @@ impl FileStorage for MockFileStorage {
         Ok(metadata)
     }
 
+    async fn upload_stream<C>(
+        &self,
+        path: &str,
+        content_type: &str,
+        chunks: C,
+    ) -> Result<u64, Self::Error>
+    where
+        C: Stream<Item = io::Result<Bytes>> + Send,
+    {
+        let chunks: Vec<Bytes> = chunks
+            .try_collect()
+            .await
+            .map_err(|e| FileStorageError::StorageError(e.to_string()))?;
+        let metadata = self
+            .upload_file(FileUploadRequest::new(path, chunks.concat(), content_type))
+            .await?;
+        Ok(metadata.size)
+    }
+
     async fn download_file(&self, path: &str) -> Result<Vec<u8>, Self::Error> {
         let files = self.files.read().expect("lock poisoned");
         files
@@ -71,6 +96,17 @@ impl FileStorage for MockFileStorage {
             .ok_or_else(|| FileStorageError::NotFound(path.to_owned()))
     }
 
+    async fn download_range(&self, path: &str, range: Range<u64>) -> Result<Vec<u8>, Self::Error> {
+        let files = self.files.read().expect("lock poisoned");
+        let file = files
+            .get(path)
+            .ok_or_else(|| FileStorageError::NotFound(path.to_owned()))?;
+        let len = file.content.len();
+        let start = (range.start as usize).min(len);
+        let end = (range.end as usize).clamp(start, len);
+        Ok(file.content[start..end].to_vec())
+    }
+
     async fn delete_file(&self, path: &str) -> Result<bool, Self::Error> {
         let mut files = self.files.write().expect("lock poisoned");
         Ok(files.remove(path).is_some())
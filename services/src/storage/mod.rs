@@ -1,22 +1,55 @@
 //! Remote storage service using OpenDAL.
 //!
-//! Provides trait-based abstractions for file storage backends (Cloudflare R2).
+//! Provides trait-based abstractions for file storage backends (Cloudflare R2,
+//! Google Cloud Storage, local filesystem).
 //! See `docs/storage.md` for detailed documentation.
 
+mod checksum;
 mod cloudflare;
+mod gcs;
+mod local;
+mod media;
 mod mock;
+mod multipart;
 mod presign;
+mod router;
+mod sigv4;
+mod sniff;
+mod stream;
+mod thumbnail;
 mod traits;
 mod types;
+mod zip;
 
+pub use checksum::{normalize_sha256, object_sha256};
 pub use cloudflare::{CFDisk, CFDiskConfig, CFFileStorage};
+pub use gcs::{GcsDisk, GcsDiskConfig, GcsFileStorage, GcsPresigner};
+pub use local::{
+    LOCAL_STORAGE_ROUTE_PREFIX, LocalFileStorage, LocalPresigner, LocalSignedQuery,
+    local_storage_routes,
+};
+pub use media::{MAX_MEDIA_SOURCE_SIZE, MediaKind, MediaMetadata, extract_media_metadata};
 pub use mock::MockFileStorage;
+pub use multipart::{
+    CompletedPart, DEFAULT_PART_SIZE, MAX_PART_SIZE, MAX_PARTS, MIN_PART_SIZE, PartPlan,
+};
 pub use presign::{
     ContentDisposition, DEFAULT_PRESIGN_EXPIRY, FileMetadata as PresignFileMetadata,
-    MAX_PRESIGN_EXPIRY, PresignError, PresignedUrl, R2Presigner,
+    MAX_PRESIGN_EXPIRY, PresignError, PresignedUrl, Presigner, R2Presigner,
+};
+pub use router::{
+    BackendFileStorage, DEFAULT_STORAGE_PROFILE, StorageRouter, StorageSelectionError,
+    StorageTarget,
+};
+pub use sniff::{SNIFF_LEN, mime_essence, sniff_mismatch};
+pub use stream::{STREAM_CHUNK_SIZE, stream_range};
+pub use thumbnail::{
+    MAX_THUMBNAIL_SOURCE_SIZE, THUMBNAIL_SIZES, ThumbnailError, ThumbnailFormat, delete_thumbnails,
+    generate_thumbnails, render_thumbnails, thumbnail_key,
 };
 pub use traits::{FileStorage, OpenDALDisk};
 pub use types::{FileMetadata, FileStorageError, FileUploadRequest};
+pub use zip::{ZipEntry, ZipReader, ZipWriter};
 
 #[cfg(test)]
 mod tests {
@@ -0,0 +1,107 @@
+//! Multipart upload types shared by the storage backends.
+//!
+//! Large files are uploaded in parts, each with its own presigned URL. The
+//! limits follow S3/R2: every part except the last must be at least
+//! [`MIN_PART_SIZE`], and an upload has at most [`MAX_PARTS`] parts.
+
+/// Smallest allowed part size (except for the last part).
+pub const MIN_PART_SIZE: u64 = 5 * 1024 * 1024;
+
+/// Largest allowed part size.
+pub const MAX_PART_SIZE: u64 = 5 * 1024 * 1024 * 1024;
+
+/// Part size used when the client does not request one.
+pub const DEFAULT_PART_SIZE: u64 = 16 * 1024 * 1024;
+
+/// Maximum number of parts in one upload.
+pub const MAX_PARTS: u64 = 10_000;
+
+/// A part that has been uploaded, as reported back to the backend on completion.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CompletedPart {
+    /// 1-based part number.
+    pub part_number: u32,
+    /// ETag returned by the backend for the part.
+    pub etag: String,
+}
+
+/// How a file is split into parts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PartPlan {
+    pub part_size: u64,
+    pub part_count: u32,
+}
+
+impl PartPlan {
+    /// Split `file_size` bytes into parts of `requested_part_size` (or [`DEFAULT_PART_SIZE`]).
+    ///
+    /// The part size is raised when the file would otherwise need more than
+    /// [`MAX_PARTS`] parts. Returns an error if the requested size is out of
+    /// range or the file is too large to upload.
+    pub fn new(file_size: u64, requested_part_size: Option<u64>) -> Result<Self, String> {
+        if file_size == 0 {
+            return Err("Multipart uploads require a non-empty file".to_owned());
+        }
+
+        let requested = requested_part_size.unwrap_or(DEFAULT_PART_SIZE);
+        if !(MIN_PART_SIZE..=MAX_PART_SIZE).contains(&requested) {
+            return Err(format!(
+                "Part size must be between {MIN_PART_SIZE} and {MAX_PART_SIZE} bytes"
+            ));
+        }
+
+        let part_size = requested.max(file_size.div_ceil(MAX_PARTS));
+        if part_size > MAX_PART_SIZE {
+            return Err(format!(
+                "File size exceeds the multipart limit of {} bytes",
+                MAX_PART_SIZE * MAX_PARTS
+            ));
+        }
+
+        Ok(Self {
+            part_size,
+            part_count: file_size.div_ceil(part_size) as u32,
+        })
+    }
+
+    /// Size in bytes of the 1-based `part_number` for a file of `file_size` bytes.
+    pub fn part_len(&self, file_size: u64, part_number: u32) -> u64 {
+        let offset = u64::from(part_number - 1) * self.part_size;
+        self.part_size.min(file_size.saturating_sub(offset))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_part_plan_splits_file() {
+        let plan = PartPlan::new(MIN_PART_SIZE * 2 + 10, Some(MIN_PART_SIZE)).unwrap();
+        assert_eq!(plan.part_size, MIN_PART_SIZE);
+        assert_eq!(plan.part_count, 3);
+        assert_eq!(plan.part_len(MIN_PART_SIZE * 2 + 10, 1), MIN_PART_SIZE);
+        assert_eq!(plan.part_len(MIN_PART_SIZE * 2 + 10, 3), 10);
+
+        let plan = PartPlan::new(100, None).unwrap();
+        assert_eq!(plan.part_size, DEFAULT_PART_SIZE);
+        assert_eq!(plan.part_count, 1);
+        assert_eq!(plan.part_len(100, 1), 100);
+    }
+
+    #[test]
+    fn test_part_plan_grows_part_size_for_huge_files() {
+        let file_size = MIN_PART_SIZE * MAX_PARTS * 3;
+        let plan = PartPlan::new(file_size, Some(MIN_PART_SIZE)).unwrap();
+        assert_eq!(plan.part_size, MIN_PART_SIZE * 3);
+        assert_eq!(u64::from(plan.part_count), MAX_PARTS);
+    }
+
+    #[test]
+    fn test_part_plan_rejects_invalid_input() {
+        assert!(PartPlan::new(0, None).is_err());
+        assert!(PartPlan::new(100, Some(1024)).is_err());
+        assert!(PartPlan::new(100, Some(MAX_PART_SIZE + 1)).is_err());
+        assert!(PartPlan::new(MAX_PART_SIZE * MAX_PARTS + 1, None).is_err());
+    }
+}
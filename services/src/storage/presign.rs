@@ -1,10 +1,15 @@
 //! Presigned URL generation for storage backends.
 //!
-//! Provides S3 SigV4-compatible presigning for Cloudflare R2 using OpenDAL.
+//! Provides S3 SigV4-compatible presigning for Cloudflare R2 using OpenDAL,
+//! and a [`Presigner`] that dispatches to the backend an object lives on.
 
 use std::time::Duration;
 
 use super::cloudflare::CFDiskConfig;
+use super::gcs::GcsPresigner;
+use super::local::LocalPresigner;
+use super::multipart::CompletedPart;
+use super::sigv4::{self, SigV4Credentials};
 
 /// Result of a presign operation.
 #[derive(Debug, Clone)]
@@ -23,6 +28,9 @@ pub enum PresignError {
 
     #[error("Storage error: {0}")]
     StorageError(String),
+
+    #[error("Unsupported operation: {0}")]
+    Unsupported(String),
 }
 
 /// Content disposition for GET presigned URLs.
@@ -46,16 +54,81 @@ impl TryFrom<&str> for ContentDisposition {
     }
 }
 
+impl ContentDisposition {
+    /// The disposition type, `inline` or `attachment`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Inline => "inline",
+            Self::Attachment => "attachment",
+        }
+    }
+
+    /// A `Content-Disposition` header value suggesting `filename`.
+    ///
+    /// Carries an ASCII-only `filename` for old clients and the exact name as
+    /// an RFC 5987 `filename*`.
+    pub fn header_value(&self, filename: &str) -> String {
+        let fallback: String = filename
+            .chars()
+            .map(|c| {
+                if c.is_ascii() && !c.is_ascii_control() && c != '"' && c != '\\' {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect();
+        let mut encoded = String::with_capacity(filename.len());
+        for byte in filename.bytes() {
+            if byte.is_ascii_alphanumeric() || b"!#$&+-.^_`|~".contains(&byte) {
+                encoded.push(byte as char);
+            } else {
+                encoded.push_str(&format!("%{byte:02X}"));
+            }
+        }
+        format!(
+            "{}; filename=\"{fallback}\"; filename*=UTF-8''{encoded}",
+            self.as_str()
+        )
+    }
+}
+
+/// Expiry of the presigned URLs the server itself uses for multipart requests.
+const MULTIPART_REQUEST_EXPIRY: Duration = Duration::from_secs(60);
+
+/// Extract the text of the first `<tag>` element from an S3 XML response.
+fn xml_tag<'a>(xml: &'a str, tag: &str) -> Option<&'a str> {
+    let start = xml.find(&format!("<{tag}>"))? + tag.len() + 2;
+    let end = xml[start..].find(&format!("</{tag}>"))? + start;
+    Some(&xml[start..end])
+}
+
+fn xml_escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
 /// Presigner for Cloudflare R2 storage.
 #[derive(Clone)]
 pub struct R2Presigner {
     config: CFDiskConfig,
+    endpoint: String,
 }
 
 impl R2Presigner {
     /// Create a new presigner with the given configuration.
     pub fn new(config: CFDiskConfig) -> Self {
-        Self { config }
+        let endpoint = format!("https://{}.r2.cloudflarestorage.com", config.account_id);
+        Self { config, endpoint }
+    }
+
+    /// Point the presigner at another S3-compatible endpoint.
+    #[cfg(test)]
+    fn with_endpoint(mut self, endpoint: impl Into<String>) -> Self {
+        self.endpoint = endpoint.into();
+        self
     }
 
     /// Create an OpenDAL operator for R2.
@@ -66,10 +139,7 @@ impl R2Presigner {
             .region("auto")
             .access_key_id(&config.access_key_id)
             .secret_access_key(&config.secret_access_key)
-            .endpoint(&format!(
-                "https://{}.r2.cloudflarestorage.com",
-                config.account_id
-            ));
+            .endpoint(&self.endpoint);
 
         opendal::Operator::new(builder)
             .map(|op| op.finish())
@@ -175,6 +245,314 @@ impl R2Presigner {
             Err(e) => Err(PresignError::StorageError(e.to_string())),
         }
     }
+
+    /// Sign an S3 request for `storage_key` with SigV4 query parameters.
+    fn sign(
+        &self,
+        method: &str,
+        storage_key: &str,
+        query: &[(&str, &str)],
+        expires_in: Duration,
+    ) -> Result<String, PresignError> {
+        sigv4::presign_url(
+            method,
+            &self.endpoint,
+            &[&self.config.bucket, storage_key],
+            query,
+            &SigV4Credentials {
+                access_key_id: &self.config.access_key_id,
+                secret_access_key: &self.config.secret_access_key,
+                region: "auto",
+            },
+            chrono::Utc::now(),
+            expires_in,
+        )
+    }
+
+    /// Send a signed multipart request and return the response body.
+    async fn send_multipart_request(
+        &self,
+        operation: &str,
+        request: reqwest::RequestBuilder,
+    ) -> Result<String, PresignError> {
+        let response = request
+            .send()
+            .await
+            .map_err(|e| PresignError::StorageError(format!("{operation} failed: {e}")))?;
+        let status = response.status();
+        let body = response
+            .text()
+            .await
+            .map_err(|e| PresignError::StorageError(format!("{operation} failed: {e}")))?;
+
+        // CompleteMultipartUpload can report errors with a 200 status
+        if !status.is_success() || body.contains("<Error>") {
+            return Err(PresignError::StorageError(format!(
+                "{operation} failed with status {status}: {}",
+                xml_tag(&body, "Message").unwrap_or(&body)
+            )));
+        }
+        Ok(body)
+    }
+
+    /// Start a multipart upload and return the backend's upload ID.
+    pub async fn create_multipart_upload(
+        &self,
+        storage_key: &str,
+        content_type: &str,
+    ) -> Result<String, PresignError> {
+        let url = self.sign(
+            "POST",
+            storage_key,
+            &[("uploads", "")],
+            MULTIPART_REQUEST_EXPIRY,
+        )?;
+        let body = self
+            .send_multipart_request(
+                "CreateMultipartUpload",
+                reqwest::Client::new()
+                    .post(url)
+                    .header(reqwest::header::CONTENT_TYPE, content_type),
+            )
+            .await?;
+
+        xml_tag(&body, "UploadId")
+            .map(str::to_owned)
+            .ok_or_else(|| {
+                PresignError::StorageError("CreateMultipartUpload returned no UploadId".to_owned())
+            })
+    }
+
+    /// Generate a presigned PUT URL for one part of a multipart upload.
+    pub async fn presign_upload_part(
+        &self,
+        storage_key: &str,
+        upload_id: &str,
+        part_number: u32,
+        expires_in: Duration,
+    ) -> Result<PresignedUrl, PresignError> {
+        let expires_at = chrono::Utc::now()
+            + chrono::Duration::from_std(expires_in)
+                .map_err(|e| PresignError::PresignFailed(e.to_string()))?;
+        let url = self.sign(
+            "PUT",
+            storage_key,
+            &[
+                ("partNumber", &part_number.to_string()),
+                ("uploadId", upload_id),
+            ],
+            expires_in,
+        )?;
+
+        Ok(PresignedUrl { url, expires_at })
+    }
+
+    /// Assemble the uploaded parts into the final object.
+    pub async fn complete_multipart_upload(
+        &self,
+        storage_key: &str,
+        upload_id: &str,
+        parts: &[CompletedPart],
+    ) -> Result<(), PresignError> {
+        let url = self.sign(
+            "POST",
+            storage_key,
+            &[("uploadId", upload_id)],
+            MULTIPART_REQUEST_EXPIRY,
+        )?;
+        let body = format!(
+            "<CompleteMultipartUpload>{}</CompleteMultipartUpload>",
+            parts
+                .iter()
+                .map(|part| format!(
+                    "<Part><PartNumber>{}</PartNumber><ETag>{}</ETag></Part>",
+                    part.part_number,
+                    xml_escape(&part.etag)
+                ))
+                .collect::<String>()
+        );
+        self.send_multipart_request(
+            "CompleteMultipartUpload",
+            reqwest::Client::new()
+                .post(url)
+                .header(reqwest::header::CONTENT_TYPE, "application/xml")
+                .body(body),
+        )
+        .await
+        .map(drop)
+    }
+
+    /// Cancel a multipart upload and discard its parts.
+    pub async fn abort_multipart_upload(
+        &self,
+        storage_key: &str,
+        upload_id: &str,
+    ) -> Result<(), PresignError> {
+        let url = self.sign(
+            "DELETE",
+            storage_key,
+            &[("uploadId", upload_id)],
+            MULTIPART_REQUEST_EXPIRY,
+        )?;
+        match self
+            .send_multipart_request("AbortMultipartUpload", reqwest::Client::new().delete(url))
+            .await
+        {
+            Ok(_) => Ok(()),
+            // Already aborted or completed
+            Err(PresignError::StorageError(msg)) if msg.contains("404") => Ok(()),
+            Err(e) => Err(e),
+        }
+    }
+}
+
+/// Presigner for a storage backend.
+///
+/// [`Presigner::backend`] is the value recorded in `uploads.storage_backend` /
+/// `contents.storage_backend`; see [`StorageRouter`](super::StorageRouter).
+#[derive(Clone)]
+pub enum Presigner {
+    /// Cloudflare R2 (S3-compatible) presigning.
+    R2(R2Presigner),
+    /// Google Cloud Storage V4 signed URLs.
+    Gcs(GcsPresigner),
+    /// HMAC-signed URLs served by this service from the local filesystem.
+    Local(LocalPresigner),
+}
+
+impl Presigner {
+    /// Backend name stored in the database for objects handled by this presigner.
+    pub fn backend(&self) -> &'static str {
+        match self {
+            Self::R2(_) => "r2",
+            Self::Gcs(_) => "gcs",
+            Self::Local(_) => "local",
+        }
+    }
+
+    /// Generate a presigned PUT URL for uploading a file.
+    pub async fn presign_put(
+        &self,
+        storage_key: &str,
+        content_type: &str,
+        expires_in: Duration,
+    ) -> Result<PresignedUrl, PresignError> {
+        match self {
+            Self::R2(p) => p.presign_put(storage_key, content_type, expires_in).await,
+            Self::Gcs(p) => p.presign_put(storage_key, content_type, expires_in).await,
+            Self::Local(p) => p.presign_put(storage_key, content_type, expires_in).await,
+        }
+    }
+
+    /// Generate a presigned GET URL for downloading/viewing a file.
+    pub async fn presign_get(
+        &self,
+        storage_key: &str,
+        disposition: ContentDisposition,
+        expires_in: Duration,
+    ) -> Result<PresignedUrl, PresignError> {
+        match self {
+            Self::R2(p) => p.presign_get(storage_key, disposition, expires_in).await,
+            Self::Gcs(p) => p.presign_get(storage_key, disposition, expires_in).await,
+            Self::Local(p) => p.presign_get(storage_key, disposition, expires_in).await,
+        }
+    }
+
+    /// Check if a file exists at the given storage key.
+    pub async fn file_exists(&self, storage_key: &str) -> Result<bool, PresignError> {
+        match self {
+            Self::R2(p) => p.file_exists(storage_key).await,
+            Self::Gcs(p) => p.file_exists(storage_key).await,
+            Self::Local(p) => p.file_exists(storage_key).await,
+        }
+    }
+
+    /// Get metadata for a file at the given storage key.
+    ///
+    /// Returns None if the file doesn't exist.
+    pub async fn get_metadata(
+        &self,
+        storage_key: &str,
+    ) -> Result<Option<FileMetadata>, PresignError> {
+        match self {
+            Self::R2(p) => p.get_metadata(storage_key).await,
+            Self::Gcs(p) => p.get_metadata(storage_key).await,
+            Self::Local(p) => p.get_metadata(storage_key).await,
+        }
+    }
+
+    /// Start a multipart upload and return the backend's upload ID.
+    pub async fn create_multipart_upload(
+        &self,
+        storage_key: &str,
+        content_type: &str,
+    ) -> Result<String, PresignError> {
+        match self {
+            Self::R2(p) => p.create_multipart_upload(storage_key, content_type).await,
+            Self::Gcs(_) => Err(multipart_unsupported("gcs")),
+            Self::Local(p) => p.create_multipart_upload(storage_key, content_type).await,
+        }
+    }
+
+    /// Generate a presigned PUT URL for one part of a multipart upload.
+    pub async fn presign_upload_part(
+        &self,
+        storage_key: &str,
+        upload_id: &str,
+        part_number: u32,
+        expires_in: Duration,
+    ) -> Result<PresignedUrl, PresignError> {
+        match self {
+            Self::R2(p) => {
+                p.presign_upload_part(storage_key, upload_id, part_number, expires_in)
+                    .await
+            }
+            Self::Gcs(_) => Err(multipart_unsupported("gcs")),
+            Self::Local(p) => {
+                p.presign_upload_part(storage_key, upload_id, part_number, expires_in)
+                    .await
+            }
+        }
+    }
+
+    /// Assemble the uploaded parts into the final object.
+    pub async fn complete_multipart_upload(
+        &self,
+        storage_key: &str,
+        upload_id: &str,
+        parts: &[CompletedPart],
+    ) -> Result<(), PresignError> {
+        match self {
+            Self::R2(p) => {
+                p.complete_multipart_upload(storage_key, upload_id, parts)
+                    .await
+            }
+            Self::Gcs(_) => Err(multipart_unsupported("gcs")),
+            Self::Local(p) => {
+                p.complete_multipart_upload(storage_key, upload_id, parts)
+                    .await
+            }
+        }
+    }
+
+    /// Cancel a multipart upload and discard its parts.
+    pub async fn abort_multipart_upload(
+        &self,
+        storage_key: &str,
+        upload_id: &str,
+    ) -> Result<(), PresignError> {
+        match self {
+            Self::R2(p) => p.abort_multipart_upload(storage_key, upload_id).await,
+            Self::Gcs(_) => Err(multipart_unsupported("gcs")),
+            Self::Local(p) => p.abort_multipart_upload(upload_id).await,
+        }
+    }
+}
+
+fn multipart_unsupported(backend: &str) -> PresignError {
+    PresignError::Unsupported(format!(
+        "Multipart uploads are not supported by the {backend} backend"
+    ))
 }
 
 /// Metadata about a stored file.
@@ -255,4 +633,127 @@ mod tests {
         );
         assert_eq!(ContentDisposition::try_from("invalid"), Err(()));
     }
+
+    #[test]
+    fn test_content_disposition_header_value() {
+        assert_eq!(
+            ContentDisposition::Attachment.header_value("report.pdf"),
+            "attachment; filename=\"report.pdf\"; filename*=UTF-8''report.pdf"
+        );
+        assert_eq!(
+            ContentDisposition::Inline.header_value("naïve \"notes\".md"),
+            "inline; filename=\"na_ve _notes_.md\"; filename*=UTF-8''na%C3%AFve%20%22notes%22.md"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_r2_multipart_upload_against_fake_server() {
+        use wiremock::matchers::{body_string_contains, method, path, query_param};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/test-bucket/user/big.bin"))
+            .and(query_param("uploads", ""))
+            .respond_with(ResponseTemplate::new(200).set_body_string(
+                "<InitiateMultipartUploadResult><Bucket>test-bucket</Bucket>\
+                 <Key>user/big.bin</Key><UploadId>upload-123</UploadId>\
+                 </InitiateMultipartUploadResult>",
+            ))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/test-bucket/user/big.bin"))
+            .and(query_param("uploadId", "upload-123"))
+            .and(body_string_contains(
+                "<Part><PartNumber>1</PartNumber><ETag>\"etag-1\"</ETag></Part>",
+            ))
+            .respond_with(ResponseTemplate::new(200).set_body_string(
+                "<CompleteMultipartUploadResult><Key>user/big.bin</Key></CompleteMultipartUploadResult>",
+            ))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("DELETE"))
+            .and(path("/test-bucket/user/big.bin"))
+            .and(query_param("uploadId", "upload-123"))
+            .respond_with(ResponseTemplate::new(204))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let presigner = R2Presigner::new(CFDiskConfig {
+            account_id: "test-account".to_owned(),
+            access_key_id: "test-access-key".to_owned(),
+            secret_access_key: "test-secret".to_owned(),
+            bucket: "test-bucket".to_owned(),
+        })
+        .with_endpoint(server.uri());
+
+        let upload_id = presigner
+            .create_multipart_upload("user/big.bin", "application/octet-stream")
+            .await
+            .unwrap();
+        assert_eq!(upload_id, "upload-123");
+
+        let part = presigner
+            .presign_upload_part("user/big.bin", &upload_id, 1, Duration::from_secs(300))
+            .await
+            .unwrap();
+        assert!(
+            part.url
+                .starts_with(&format!("{}/test-bucket/user/big.bin?", server.uri()))
+        );
+        assert!(part.url.contains("partNumber=1"));
+        assert!(part.url.contains("uploadId=upload-123"));
+        assert!(part.url.contains("X-Amz-Signature="));
+
+        presigner
+            .complete_multipart_upload(
+                "user/big.bin",
+                &upload_id,
+                &[CompletedPart {
+                    part_number: 1,
+                    etag: "\"etag-1\"".to_owned(),
+                }],
+            )
+            .await
+            .unwrap();
+        presigner
+            .abort_multipart_upload("user/big.bin", &upload_id)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_r2_complete_multipart_reports_embedded_error() {
+        use wiremock::matchers::method;
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(
+                "<Error><Code>InvalidPart</Code><Message>One or more parts could not be found</Message></Error>",
+            ))
+            .mount(&server)
+            .await;
+
+        let presigner = R2Presigner::new(CFDiskConfig {
+            account_id: "test-account".to_owned(),
+            access_key_id: "test-access-key".to_owned(),
+            secret_access_key: "test-secret".to_owned(),
+            bucket: "test-bucket".to_owned(),
+        })
+        .with_endpoint(server.uri());
+
+        let err = presigner
+            .complete_multipart_upload("user/big.bin", "upload-123", &[])
+            .await
+            .unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("One or more parts could not be found")
+        );
+    }
 }
@@ -0,0 +1,550 @@
+//! Storage routing.
+//!
+//! Uploads and contents record the `storage_backend` and `storage_profile`
+//! they were written to. [`StorageRouter`] resolves that pair back to the
+//! presigner and [`FileStorage`] serving it, so objects stay reachable after
+//! the default backend for new uploads changes.
+//!
+//! New uploads are placed by [`StorageRouter::select_upload_target`]:
+//!
+//! 1. the profile named in the request,
+//! 2. otherwise the profile assigned to the user,
+//! 3. otherwise the first profile (in configuration order) whose
+//!    `allowed_content_types` list matches the upload,
+//! 4. otherwise the default profile.
+//!
+//! The chosen profile's size and MIME limits are always enforced.
+
+use std::collections::HashMap;
+use std::io;
+use std::ops::Range;
+use std::sync::Arc;
+
+use axum::body::Bytes;
+use futures_util::Stream;
+
+use super::cloudflare::CFFileStorage;
+use super::gcs::GcsFileStorage;
+use super::local::LocalFileStorage;
+use super::presign::Presigner;
+use super::traits::FileStorage;
+use super::types::{FileMetadata, FileStorageError, FileUploadRequest};
+
+/// Profile name used when no named profiles are configured.
+pub const DEFAULT_STORAGE_PROFILE: &str = "default";
+
+/// [`FileStorage`] implementation for any supported backend.
+#[derive(Clone)]
+pub enum BackendFileStorage {
+    R2(CFFileStorage),
+    Gcs(GcsFileStorage),
+    Local(LocalFileStorage),
+}
+
+impl FileStorage for BackendFileStorage {
+    type Error = FileStorageError;
+
+    async fn upload_file(&self, request: FileUploadRequest) -> Result<FileMetadata, Self::Error> {
+        match self {
+            Self::R2(s) => s.upload_file(request).await,
+            Self::Gcs(s) => s.upload_file(request).await,
+            Self::Local(s) => s.upload_file(request).await,
+        }
+    }
+
+    async fn upload_stream<C>(
+        &self,
+        path: &str,
+        content_type: &str,
+        chunks: C,
+    ) -> Result<u64, Self::Error>
+    where
+        C: Stream<Item = io::Result<Bytes>> + Send,
+    {
+        match self {
+            Self::R2(s) => s.upload_stream(path, content_type, chunks).await,
+            Self::Gcs(s) => s.upload_stream(path, content_type, chunks).await,
+            Self::Local(s) => s.upload_stream(path, content_type, chunks).await,
+        }
+    }
+
+    async fn download_file(&self, path: &str) -> Result<Vec<u8>, Self::Error> {
+        match self {
+            Self::R2(s) => s.download_file(path).await,
+            Self::Gcs(s) => s.download_file(path).await,
+            Self::Local(s) => s.download_file(path).await,
+        }
+    }
+
+    async fn download_range(&self, path: &str, range: Range<u64>) -> Result<Vec<u8>, Self::Error> {
+        match self {
+            Self::R2(s) => s.download_range(path, range).await,
+            Self::Gcs(s) => s.download_range(path, range).await,
+            Self::Local(s) => s.download_range(path, range).await,
+        }
+    }
+
+    async fn delete_file(&self, path: &str) -> Result<bool, Self::Error> {
+        match self {
+            Self::R2(s) => s.delete_file(path).await,
+            Self::Gcs(s) => s.delete_file(path).await,
+            Self::Local(s) => s.delete_file(path).await,
+        }
+    }
+
+    async fn list_files(&self, prefix: &str) -> Result<Vec<FileMetadata>, Self::Error> {
+        match self {
+            Self::R2(s) => s.list_files(prefix).await,
+            Self::Gcs(s) => s.list_files(prefix).await,
+            Self::Local(s) => s.list_files(prefix).await,
+        }
+    }
+
+    async fn file_exists(&self, path: &str) -> Result<bool, Self::Error> {
+        match self {
+            Self::R2(s) => s.file_exists(path).await,
+            Self::Gcs(s) => s.file_exists(path).await,
+            Self::Local(s) => s.file_exists(path).await,
+        }
+    }
+
+    async fn get_file_metadata(&self, path: &str) -> Result<Option<FileMetadata>, Self::Error> {
+        match self {
+            Self::R2(s) => s.get_file_metadata(path).await,
+            Self::Gcs(s) => s.get_file_metadata(path).await,
+            Self::Local(s) => s.get_file_metadata(path).await,
+        }
+    }
+}
+
+/// Why an upload could not be placed in a storage profile.
+#[derive(Debug, thiserror::Error, PartialEq, Eq)]
+pub enum StorageSelectionError {
+    #[error("Storage is not configured")]
+    NotConfigured,
+
+    #[error("Unknown storage profile '{0}'")]
+    UnknownProfile(String),
+
+    #[error(
+        "File size {size} exceeds the {max_object_size} byte limit of storage profile '{profile}'"
+    )]
+    TooLarge {
+        profile: String,
+        size: u64,
+        max_object_size: u64,
+    },
+
+    #[error("Content type '{content_type}' is not allowed in storage profile '{profile}'")]
+    ContentTypeNotAllowed {
+        profile: String,
+        content_type: String,
+    },
+}
+
+/// Returns true if `content_type` matches `pattern` (`*/*`, `image/*` or an exact type).
+///
+/// Parameters such as `; charset=utf-8` are ignored and matching is case-insensitive.
+fn content_type_matches(pattern: &str, content_type: &str) -> bool {
+    let essence = |value: &str| {
+        value
+            .split(';')
+            .next()
+            .unwrap_or_default()
+            .trim()
+            .to_ascii_lowercase()
+    };
+    let (pattern, content_type) = (essence(pattern), essence(content_type));
+
+    match pattern.strip_suffix("/*") {
+        Some("*") => true,
+        Some(top_level) => content_type
+            .split_once('/')
+            .is_some_and(|(ty, _)| ty == top_level),
+        None => pattern == content_type,
+    }
+}
+
+/// A configured storage destination: one backend under one profile name.
+#[derive(Clone)]
+pub struct StorageTarget {
+    profile: String,
+    presigner: Presigner,
+    file_storage: BackendFileStorage,
+    max_object_size: Option<u64>,
+    allowed_content_types: Vec<String>,
+}
+
+impl StorageTarget {
+    pub fn new(
+        profile: impl Into<String>,
+        presigner: Presigner,
+        file_storage: BackendFileStorage,
+    ) -> Self {
+        Self {
+            profile: profile.into(),
+            presigner,
+            file_storage,
+            max_object_size: None,
+            allowed_content_types: Vec::new(),
+        }
+    }
+
+    /// Restrict uploads to this target by size and MIME type.
+    ///
+    /// An empty `allowed_content_types` list accepts any type.
+    pub fn with_limits(
+        mut self,
+        max_object_size: Option<u64>,
+        allowed_content_types: Vec<String>,
+    ) -> Self {
+        self.max_object_size = max_object_size;
+        self.allowed_content_types = allowed_content_types;
+        self
+    }
+
+    /// Backend name recorded in `storage_backend`.
+    pub fn backend(&self) -> &'static str {
+        self.presigner.backend()
+    }
+
+    /// Profile name recorded in `storage_profile`.
+    pub fn profile(&self) -> &str {
+        &self.profile
+    }
+
+    pub fn presigner(&self) -> &Presigner {
+        &self.presigner
+    }
+
+    pub fn file_storage(&self) -> &BackendFileStorage {
+        &self.file_storage
+    }
+
+    pub fn max_object_size(&self) -> Option<u64> {
+        self.max_object_size
+    }
+
+    pub fn allowed_content_types(&self) -> &[String] {
+        &self.allowed_content_types
+    }
+
+    /// Check an upload against this target's limits.
+    pub fn check_upload(&self, content_type: &str, size: u64) -> Result<(), StorageSelectionError> {
+        if let Some(max_object_size) = self.max_object_size
+            && size > max_object_size
+        {
+            return Err(StorageSelectionError::TooLarge {
+                profile: self.profile.clone(),
+                size,
+                max_object_size,
+            });
+        }
+
+        if !self.allowed_content_types.is_empty()
+            && !self
+                .allowed_content_types
+                .iter()
+                .any(|pattern| content_type_matches(pattern, content_type))
+        {
+            return Err(StorageSelectionError::ContentTypeNotAllowed {
+                profile: self.profile.clone(),
+                content_type: content_type.to_owned(),
+            });
+        }
+
+        Ok(())
+    }
+}
+
+/// Resolves `(storage_backend, storage_profile)` pairs to storage targets.
+///
+/// Handlers receive this as an extension. New uploads go to
+/// [`StorageRouter::default_target`]; existing objects are looked up with
+/// [`StorageRouter::resolve`] using the values stored on their row.
+#[derive(Clone, Default)]
+pub struct StorageRouter {
+    targets: Arc<HashMap<(String, String), StorageTarget>>,
+    /// Profiles new uploads can be placed in, in configuration order.
+    upload_profiles: Arc<Vec<(String, String)>>,
+    /// Username -> profile name assignments.
+    user_profiles: Arc<HashMap<String, String>>,
+    default: Option<(String, String)>,
+}
+
+impl StorageRouter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a target. A later target with the same backend and profile replaces the earlier one.
+    pub fn with_target(mut self, target: StorageTarget) -> Self {
+        let key = (target.backend().to_owned(), target.profile().to_owned());
+        Arc::make_mut(&mut self.targets).insert(key, target);
+        self
+    }
+
+    /// Register a target that new uploads can be placed in by profile name.
+    ///
+    /// Profile names are unique among upload profiles; a later registration
+    /// with the same name replaces the earlier one.
+    pub fn with_upload_profile(mut self, target: StorageTarget) -> Self {
+        let key = (target.backend().to_owned(), target.profile().to_owned());
+        let profiles = Arc::make_mut(&mut self.upload_profiles);
+        profiles.retain(|(_, profile)| profile != &key.1);
+        profiles.push(key);
+        self.with_target(target)
+    }
+
+    /// Assign a user's uploads to a profile.
+    pub fn with_user_profile(
+        mut self,
+        username: impl Into<String>,
+        profile: impl Into<String>,
+    ) -> Self {
+        Arc::make_mut(&mut self.user_profiles).insert(username.into(), profile.into());
+        self
+    }
+
+    /// Select the target used for new uploads.
+    pub fn with_default(mut self, backend: impl Into<String>, profile: impl Into<String>) -> Self {
+        self.default = Some((backend.into(), profile.into()));
+        self
+    }
+
+    /// Look up the target for a stored object.
+    pub fn resolve(&self, backend: &str, profile: &str) -> Option<&StorageTarget> {
+        self.targets.get(&(backend.to_owned(), profile.to_owned()))
+    }
+
+    /// Target used for new uploads, if it is configured.
+    pub fn default_target(&self) -> Option<&StorageTarget> {
+        let (backend, profile) = self.default.as_ref()?;
+        self.resolve(backend, profile)
+    }
+
+    /// Look up an upload profile by name.
+    pub fn profile_target(&self, profile: &str) -> Option<&StorageTarget> {
+        if let Some((backend, default_profile)) = &self.default
+            && default_profile == profile
+        {
+            return self.resolve(backend, profile);
+        }
+        let (backend, profile) = self.upload_profiles.iter().find(|(_, p)| p == profile)?;
+        self.resolve(backend, profile)
+    }
+
+    /// Pick the target a new upload lands in and check it against the target's limits.
+    ///
+    /// `requested_profile` comes from the request; `username` is used for
+    /// per-user assignments. See the module docs for the order of precedence.
+    pub fn select_upload_target(
+        &self,
+        requested_profile: Option<&str>,
+        username: &str,
+        content_type: &str,
+        size: u64,
+    ) -> Result<&StorageTarget, StorageSelectionError> {
+        let assigned =
+            requested_profile.or_else(|| self.user_profiles.get(username).map(String::as_str));
+
+        let target = match assigned {
+            Some(profile) => self
+                .profile_target(profile)
+                .ok_or_else(|| StorageSelectionError::UnknownProfile(profile.to_owned()))?,
+            None => {
+                let matched = self
+                    .upload_profiles
+                    .iter()
+                    .filter_map(|(backend, profile)| self.resolve(backend, profile))
+                    .find(|target| {
+                        !target.allowed_content_types.is_empty()
+                            && target.check_upload(content_type, size).is_ok()
+                    });
+                match matched {
+                    Some(target) => target,
+                    None => self
+                        .default_target()
+                        .ok_or(StorageSelectionError::NotConfigured)?,
+                }
+            }
+        };
+
+        target.check_upload(content_type, size)?;
+        Ok(target)
+    }
+
+    /// All registered targets.
+    pub fn targets(&self) -> impl Iterator<Item = &StorageTarget> {
+        self.targets.values()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.targets.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::{CFDiskConfig, GcsDiskConfig, GcsPresigner, R2Presigner};
+
+    fn r2_target(profile: &str) -> StorageTarget {
+        let config = CFDiskConfig {
+            account_id: "test-account".to_owned(),
+            access_key_id: "test-access-key".to_owned(),
+            secret_access_key: "test-secret".to_owned(),
+            bucket: "test-bucket".to_owned(),
+        };
+        StorageTarget::new(
+            profile,
+            Presigner::R2(R2Presigner::new(config.clone())),
+            BackendFileStorage::R2(CFFileStorage::new(config)),
+        )
+    }
+
+    fn local_target(profile: &str, dir: &tempfile::TempDir) -> StorageTarget {
+        let storage = LocalFileStorage::new(dir.path().join(profile));
+        StorageTarget::new(
+            profile,
+            Presigner::Local(crate::storage::LocalPresigner::new(
+                storage.clone(),
+                "http://127.0.0.1:8080",
+                "test-secret",
+            )),
+            BackendFileStorage::Local(storage),
+        )
+    }
+
+    fn gcs_target(profile: &str) -> StorageTarget {
+        let config = GcsDiskConfig {
+            bucket: "test-bucket".to_owned(),
+            ..Default::default()
+        };
+        StorageTarget::new(
+            profile,
+            Presigner::Gcs(GcsPresigner::new(config.clone())),
+            BackendFileStorage::Gcs(GcsFileStorage::new(config)),
+        )
+    }
+
+    #[test]
+    fn test_router_resolves_backend_and_profile() {
+        let router = StorageRouter::new()
+            .with_target(r2_target(DEFAULT_STORAGE_PROFILE))
+            .with_target(gcs_target(DEFAULT_STORAGE_PROFILE))
+            .with_default("gcs", DEFAULT_STORAGE_PROFILE);
+
+        let default = router.default_target().expect("default target");
+        assert_eq!(default.backend(), "gcs");
+
+        let r2 = router
+            .resolve("r2", DEFAULT_STORAGE_PROFILE)
+            .expect("r2 target");
+        assert_eq!(r2.backend(), "r2");
+
+        assert!(router.resolve("r2", "archives").is_none());
+        assert!(router.resolve("local", DEFAULT_STORAGE_PROFILE).is_none());
+    }
+
+    #[test]
+    fn test_router_default_must_be_registered() {
+        let router = StorageRouter::new()
+            .with_target(r2_target(DEFAULT_STORAGE_PROFILE))
+            .with_default("gcs", DEFAULT_STORAGE_PROFILE);
+
+        assert!(router.default_target().is_none());
+        assert!(!router.is_empty());
+    }
+
+    #[test]
+    fn test_content_type_patterns() {
+        assert!(content_type_matches("image/*", "image/png"));
+        assert!(content_type_matches("image/*", "IMAGE/WebP"));
+        assert!(content_type_matches("*/*", "application/zip"));
+        assert!(content_type_matches(
+            "text/plain",
+            "text/plain; charset=utf-8"
+        ));
+        assert!(!content_type_matches("image/*", "application/zip"));
+        assert!(!content_type_matches("application/zip", "application/zip2"));
+    }
+
+    #[test]
+    fn test_select_upload_target_policy() {
+        let dir = tempfile::tempdir().unwrap();
+        let router = StorageRouter::new()
+            .with_upload_profile(r2_target(DEFAULT_STORAGE_PROFILE))
+            .with_upload_profile(
+                local_target("images", &dir).with_limits(Some(1024), vec!["image/*".to_owned()]),
+            )
+            .with_upload_profile(
+                local_target("archives", &dir)
+                    .with_limits(Some(1 << 30), vec!["application/zip".to_owned()]),
+            )
+            .with_user_profile("archivist", "archives")
+            .with_default("r2", DEFAULT_STORAGE_PROFILE);
+
+        // Matched by content type
+        let target = router
+            .select_upload_target(None, "alice", "image/png", 100)
+            .unwrap();
+        assert_eq!(target.profile(), "images");
+        let target = router
+            .select_upload_target(None, "alice", "application/zip", 10 << 20)
+            .unwrap();
+        assert_eq!(target.profile(), "archives");
+
+        // Too large for the images profile, falls back to the default profile
+        let target = router
+            .select_upload_target(None, "alice", "image/png", 4096)
+            .unwrap();
+        assert_eq!(target.profile(), DEFAULT_STORAGE_PROFILE);
+        assert_eq!(target.backend(), "r2");
+
+        // Explicit request wins and is checked against the profile's limits
+        let target = router
+            .select_upload_target(Some("default"), "alice", "image/png", 100)
+            .unwrap();
+        assert_eq!(target.profile(), DEFAULT_STORAGE_PROFILE);
+        assert_eq!(
+            router
+                .select_upload_target(Some("images"), "alice", "text/plain", 10)
+                .map(StorageTarget::profile)
+                .unwrap_err(),
+            StorageSelectionError::ContentTypeNotAllowed {
+                profile: "images".to_owned(),
+                content_type: "text/plain".to_owned(),
+            }
+        );
+        assert_eq!(
+            router
+                .select_upload_target(Some("images"), "alice", "image/png", 2048)
+                .map(StorageTarget::profile)
+                .unwrap_err(),
+            StorageSelectionError::TooLarge {
+                profile: "images".to_owned(),
+                size: 2048,
+                max_object_size: 1024,
+            }
+        );
+        assert_eq!(
+            router
+                .select_upload_target(Some("videos"), "alice", "video/mp4", 10)
+                .map(StorageTarget::profile)
+                .unwrap_err(),
+            StorageSelectionError::UnknownProfile("videos".to_owned())
+        );
+
+        // Per-user assignment
+        let target = router
+            .select_upload_target(None, "archivist", "application/zip", 10)
+            .unwrap();
+        assert_eq!(target.profile(), "archives");
+        assert!(
+            router
+                .select_upload_target(None, "archivist", "image/png", 10)
+                .is_err()
+        );
+    }
+}
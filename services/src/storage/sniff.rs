@@ -0,0 +1,129 @@
+//! Content type sniffing.
+//!
+//! Uploaded files are checked against their declared MIME type by their
+//! leading "magic" bytes. Only mismatches that can be detected reliably are
+//! reported:
+//!
+//! - a type with a known signature (images, PDF, archives, media, ...) must
+//!   carry that signature,
+//! - a `text/*` type must not carry the signature of a binary format,
+//! - anything else (`application/octet-stream`, JSON, unknown vendor types)
+//!   is accepted as is.
+
+/// Number of leading bytes read for sniffing.
+pub const SNIFF_LEN: u64 = 8 * 1024;
+
+/// Types stored in a ZIP container whose own signature may lie beyond the
+/// sniffed bytes. A plain ZIP signature is accepted for them.
+const ZIP_CONTAINER_TYPES: &[&str] = &[
+    "application/epub+zip",
+    "application/java-archive",
+    "application/vnd.android.package-archive",
+    "application/vnd.oasis.opendocument.presentation",
+    "application/vnd.oasis.opendocument.spreadsheet",
+    "application/vnd.oasis.opendocument.text",
+    "application/vnd.openxmlformats-officedocument.presentationml.presentation",
+    "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet",
+    "application/vnd.openxmlformats-officedocument.wordprocessingml.document",
+];
+
+/// The lowercase `type/subtype` of a MIME type, with common aliases resolved.
+pub fn mime_essence(content_type: &str) -> String {
+    let essence = content_type
+        .split(';')
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_ascii_lowercase();
+    match essence.as_str() {
+        "image/jpg" | "image/pjpeg" => "image/jpeg".to_owned(),
+        "audio/mp3" => "audio/mpeg".to_owned(),
+        "audio/wav" | "audio/wave" => "audio/x-wav".to_owned(),
+        "application/x-zip-compressed" => "application/zip".to_owned(),
+        _ => essence,
+    }
+}
+
+/// The MIME type detected from `head`, if it does not match `declared`.
+///
+/// Returns `None` when the bytes are consistent with the declared type.
+pub fn sniff_mismatch(declared: &str, head: &[u8]) -> Option<String> {
+    let declared = mime_essence(declared);
+    if head.is_empty() || declared == "application/octet-stream" {
+        return None;
+    }
+
+    let detected = infer::get(head);
+    let detected_mime = || {
+        detected
+            .map(|kind| kind.mime_type().to_owned())
+            .unwrap_or_else(|| "unknown".to_owned())
+    };
+
+    if !declared.starts_with("text/") && infer::is_mime_supported(&declared) {
+        let matches = infer::is_mime(head, &declared)
+            || (ZIP_CONTAINER_TYPES.contains(&declared.as_str())
+                && infer::is_mime(head, "application/zip"));
+        return (!matches).then(detected_mime);
+    }
+
+    match detected {
+        Some(kind)
+            if declared.starts_with("text/") && kind.matcher_type() != infer::MatcherType::Text =>
+        {
+            Some(detected_mime())
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PNG: &[u8] = b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR";
+    const ZIP: &[u8] = b"PK\x03\x04\x14\0\0\0\x08\0";
+
+    #[test]
+    fn test_mime_essence() {
+        assert_eq!(mime_essence("Text/Plain; charset=utf-8"), "text/plain");
+        assert_eq!(mime_essence("image/jpg"), "image/jpeg");
+    }
+
+    #[test]
+    fn test_sniff_mismatch_known_signature() {
+        assert_eq!(sniff_mismatch("image/png", PNG), None);
+        assert_eq!(
+            sniff_mismatch("image/jpeg", PNG).as_deref(),
+            Some("image/png")
+        );
+        assert_eq!(
+            sniff_mismatch("image/png", b"hello").as_deref(),
+            Some("unknown")
+        );
+    }
+
+    #[test]
+    fn test_sniff_mismatch_text() {
+        assert_eq!(sniff_mismatch("text/plain", b"hello"), None);
+        assert_eq!(sniff_mismatch("text/markdown", b"<html></html>"), None);
+        assert_eq!(
+            sniff_mismatch("text/plain", PNG).as_deref(),
+            Some("image/png")
+        );
+    }
+
+    #[test]
+    fn test_sniff_mismatch_lenient_types() {
+        assert_eq!(sniff_mismatch("application/octet-stream", PNG), None);
+        assert_eq!(sniff_mismatch("application/json", b"{}"), None);
+        assert_eq!(sniff_mismatch("application/x-custom", PNG), None);
+        assert_eq!(
+            sniff_mismatch(
+                "application/vnd.openxmlformats-officedocument.wordprocessingml.document",
+                ZIP
+            ),
+            None
+        );
+    }
+}
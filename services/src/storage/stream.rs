@@ -0,0 +1,121 @@
+//! Streaming reads and writes.
+//!
+//! [`FileStorage`] only reads whole objects or byte ranges into memory.
+//! [`stream_range`] turns that into a stream of bounded chunks, so large
+//! objects can be proxied to clients without holding them in memory.
+//! [`write_stream`] is the other direction for the OpenDAL backends.
+
+use std::io;
+use std::ops::Range;
+
+use axum::body::Bytes;
+use futures_util::{Stream, StreamExt as _};
+
+use super::traits::FileStorage;
+use super::types::FileStorageError;
+
+/// Size of each read issued by [`stream_range`].
+pub const STREAM_CHUNK_SIZE: u64 = 1024 * 1024;
+
+/// Stream bytes `range` of the object at `path`, reading it
+/// [`STREAM_CHUNK_SIZE`] bytes at a time.
+///
+/// The stream ends early if the object is shorter than `range`.
+pub fn stream_range<S: FileStorage>(
+    storage: S,
+    path: String,
+    range: Range<u64>,
+) -> impl Stream<Item = Result<Bytes, S::Error>> + Send {
+    futures_util::stream::try_unfold(
+        (storage, path, range),
+        |(storage, path, range)| async move {
+            if range.is_empty() {
+                return Ok(None);
+            }
+            let end = range.end.min(range.start.saturating_add(STREAM_CHUNK_SIZE));
+            let chunk = storage.download_range(&path, range.start..end).await?;
+            if chunk.is_empty() {
+                return Ok(None);
+            }
+            let next = range.start + chunk.len() as u64..range.end;
+            Ok(Some((Bytes::from(chunk), (storage, path, next))))
+        },
+    )
+}
+
+/// Write `chunks` to `path` through `op` as they arrive, and return the
+/// number of bytes written.
+///
+/// The write is aborted if a chunk fails, so nothing is stored.
+pub(super) async fn write_stream<C>(
+    op: &opendal::Operator,
+    path: &str,
+    content_type: &str,
+    chunks: C,
+) -> Result<u64, FileStorageError>
+where
+    C: Stream<Item = io::Result<Bytes>> + Send,
+{
+    let storage_error = |e: opendal::Error| FileStorageError::StorageError(e.to_string());
+    let mut writer = op
+        .writer_with(path)
+        .content_type(content_type)
+        .await
+        .map_err(storage_error)?;
+
+    let mut chunks = std::pin::pin!(chunks);
+    let mut written = 0;
+    while let Some(chunk) = chunks.next().await {
+        let result = match chunk {
+            Ok(chunk) => {
+                written += chunk.len() as u64;
+                writer.write(chunk).await.map_err(storage_error)
+            }
+            Err(e) => Err(FileStorageError::StorageError(e.to_string())),
+        };
+        if let Err(e) = result {
+            if let Err(e) = writer.abort().await {
+                tracing::warn!(%path, "Failed to abort storage write: {e}");
+            }
+            return Err(e);
+        }
+    }
+    writer.close().await.map_err(storage_error)?;
+    Ok(written)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::{FileUploadRequest, MockFileStorage};
+    use futures_util::TryStreamExt as _;
+
+    #[tokio::test]
+    async fn test_stream_range_reads_in_chunks() {
+        let storage = MockFileStorage::new();
+        let content: Vec<u8> = (0..STREAM_CHUNK_SIZE * 2 + 10)
+            .map(|i| (i % 251) as u8)
+            .collect();
+        storage
+            .upload_file(FileUploadRequest::new(
+                "a/b.bin",
+                content.clone(),
+                "application/octet-stream",
+            ))
+            .await
+            .unwrap();
+
+        let chunks: Vec<Bytes> = stream_range(storage.clone(), "a/b.bin".to_owned(), 5..u64::MAX)
+            .try_collect()
+            .await
+            .unwrap();
+        assert_eq!(chunks.len(), 3);
+        assert_eq!(chunks.concat(), content[5..]);
+
+        let chunks: Vec<Bytes> = stream_range(storage, "a/b.bin".to_owned(), 3..7)
+            .try_collect()
+            .await
+            .unwrap();
+        assert_eq!(chunks.concat(), content[3..7]);
+    }
+}
@@ -0,0 +1,272 @@
+//! Downscaled thumbnails of image contents.
+//!
+//! Thumbnails are stored next to the original, under its storage key:
+//! `{storage_key}.thumb-{size}.{webp|png}`. PNG and GIF sources get PNG
+//! thumbnails, other images WebP. Both encoders are lossless.
+
+use std::io::Cursor;
+
+use image::{DynamicImage, ImageFormat, ImageReader, Limits};
+
+use super::sniff::mime_essence;
+use super::traits::FileStorage;
+use super::types::FileUploadRequest;
+
+/// Thumbnail sizes, as the maximum width and height in pixels.
+pub const THUMBNAIL_SIZES: [u32; 3] = [128, 512, 1024];
+
+/// Originals larger than this are not thumbnailed.
+pub const MAX_THUMBNAIL_SOURCE_SIZE: u64 = 64 * 1024 * 1024;
+
+/// Decoded images larger than this in either dimension are not thumbnailed.
+const MAX_THUMBNAIL_SOURCE_DIMENSION: u32 = 16_384;
+
+/// Errors from generating thumbnails.
+#[derive(Debug, thiserror::Error)]
+pub enum ThumbnailError {
+    #[error("storage error: {0}")]
+    Storage(String),
+    #[error("image error: {0}")]
+    Image(#[from] image::ImageError),
+    #[error("thumbnail task failed: {0}")]
+    Task(#[from] tokio::task::JoinError),
+}
+
+/// Encoding of the thumbnails of a content.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ThumbnailFormat {
+    Webp,
+    Png,
+}
+
+impl ThumbnailFormat {
+    /// Thumbnail format for an original of `content_type`, `None` if it
+    /// cannot be thumbnailed.
+    pub fn for_source(content_type: &str) -> Option<Self> {
+        match mime_essence(content_type).as_str() {
+            "image/png" | "image/gif" => Some(Self::Png),
+            "image/jpeg" | "image/webp" => Some(Self::Webp),
+            _ => None,
+        }
+    }
+
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            Self::Webp => "image/webp",
+            Self::Png => "image/png",
+        }
+    }
+
+    fn extension(&self) -> &'static str {
+        match self {
+            Self::Webp => "webp",
+            Self::Png => "png",
+        }
+    }
+
+    fn image_format(&self) -> ImageFormat {
+        match self {
+            Self::Webp => ImageFormat::WebP,
+            Self::Png => ImageFormat::Png,
+        }
+    }
+}
+
+/// Storage key of the `size` thumbnail of the original at `storage_key`.
+pub fn thumbnail_key(storage_key: &str, size: u32, format: ThumbnailFormat) -> String {
+    format!("{storage_key}.thumb-{size}.{}", format.extension())
+}
+
+/// Decode `source` and encode it at every [`THUMBNAIL_SIZES`].
+///
+/// Images are never upscaled: sizes larger than the original keep its
+/// dimensions. CPU-bound; run it off the async runtime.
+pub fn render_thumbnails(
+    source: &[u8],
+    format: ThumbnailFormat,
+) -> Result<Vec<(u32, Vec<u8>)>, image::ImageError> {
+    let mut reader = ImageReader::new(Cursor::new(source)).with_guessed_format()?;
+    let mut limits = Limits::default();
+    limits.max_image_width = Some(MAX_THUMBNAIL_SOURCE_DIMENSION);
+    limits.max_image_height = Some(MAX_THUMBNAIL_SOURCE_DIMENSION);
+    reader.limits(limits);
+    let original = reader.decode()?;
+
+    THUMBNAIL_SIZES
+        .iter()
+        .map(|&size| {
+            let resized = if original.width() > size || original.height() > size {
+                original.thumbnail(size, size)
+            } else {
+                original.clone()
+            };
+            // The encoders only accept 8-bit RGB(A)
+            let resized = if resized.color().has_alpha() {
+                DynamicImage::ImageRgba8(resized.to_rgba8())
+            } else {
+                DynamicImage::ImageRgb8(resized.to_rgb8())
+            };
+            let mut encoded = Cursor::new(Vec::new());
+            resized.write_to(&mut encoded, format.image_format())?;
+            Ok((size, encoded.into_inner()))
+        })
+        .collect()
+}
+
+/// Generate and store the thumbnails of the original at `storage_key`, whose
+/// contents are `source`.
+///
+/// Returns the generated sizes, empty if the original is not an image that
+/// can be thumbnailed. Thumbnails that already exist (the object is shared by
+/// deduplicated contents) are not generated again.
+pub async fn generate_thumbnails<F: FileStorage>(
+    storage: &F,
+    storage_key: &str,
+    content_type: &str,
+    source: Vec<u8>,
+) -> Result<Vec<u32>, ThumbnailError> {
+    let Some(format) = ThumbnailFormat::for_source(content_type) else {
+        return Ok(vec![]);
+    };
+    if source.len() as u64 > MAX_THUMBNAIL_SOURCE_SIZE {
+        return Ok(vec![]);
+    }
+
+    let largest = thumbnail_key(
+        storage_key,
+        THUMBNAIL_SIZES[THUMBNAIL_SIZES.len() - 1],
+        format,
+    );
+    if storage
+        .file_exists(&largest)
+        .await
+        .map_err(|e| ThumbnailError::Storage(e.to_string()))?
+    {
+        return Ok(THUMBNAIL_SIZES.to_vec());
+    }
+
+    let thumbnails =
+        tokio::task::spawn_blocking(move || render_thumbnails(&source, format)).await??;
+
+    let mut sizes = Vec::with_capacity(thumbnails.len());
+    // Largest last: its presence marks the set as complete
+    for (size, data) in thumbnails {
+        storage
+            .upload_file(FileUploadRequest::new(
+                thumbnail_key(storage_key, size, format),
+                data,
+                format.content_type(),
+            ))
+            .await
+            .map_err(|e| ThumbnailError::Storage(e.to_string()))?;
+        sizes.push(size);
+    }
+    Ok(sizes)
+}
+
+/// Delete the thumbnails of the original at `storage_key`.
+///
+/// Returns how many thumbnails were deleted.
+pub async fn delete_thumbnails<F: FileStorage>(
+    storage: &F,
+    storage_key: &str,
+    content_type: &str,
+) -> Result<usize, F::Error> {
+    let Some(format) = ThumbnailFormat::for_source(content_type) else {
+        return Ok(0);
+    };
+    let mut deleted = 0;
+    for size in THUMBNAIL_SIZES {
+        if storage
+            .delete_file(&thumbnail_key(storage_key, size, format))
+            .await?
+        {
+            deleted += 1;
+        }
+    }
+    Ok(deleted)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::MockFileStorage;
+
+    fn png(width: u32, height: u32) -> Vec<u8> {
+        let image = DynamicImage::ImageRgba8(image::RgbaImage::new(width, height));
+        let mut encoded = Cursor::new(Vec::new());
+        image.write_to(&mut encoded, ImageFormat::Png).unwrap();
+        encoded.into_inner()
+    }
+
+    #[test]
+    fn test_thumbnail_format_for_source() {
+        assert_eq!(
+            ThumbnailFormat::for_source("image/PNG"),
+            Some(ThumbnailFormat::Png)
+        );
+        assert_eq!(
+            ThumbnailFormat::for_source("image/jpeg; charset=binary"),
+            Some(ThumbnailFormat::Webp)
+        );
+        assert_eq!(ThumbnailFormat::for_source("image/svg+xml"), None);
+        assert_eq!(ThumbnailFormat::for_source("text/plain"), None);
+    }
+
+    #[test]
+    fn test_render_thumbnails_downscales_without_upscaling() {
+        let thumbnails = render_thumbnails(&png(800, 400), ThumbnailFormat::Webp).unwrap();
+        let dimensions: Vec<_> = thumbnails
+            .iter()
+            .map(|(size, data)| {
+                let image = image::load_from_memory_with_format(data, ImageFormat::WebP).unwrap();
+                (*size, image.width(), image.height())
+            })
+            .collect();
+        assert_eq!(
+            dimensions,
+            vec![(128, 128, 64), (512, 512, 256), (1024, 800, 400)]
+        );
+    }
+
+    #[test]
+    fn test_render_thumbnails_rejects_invalid_image() {
+        assert!(render_thumbnails(b"not an image", ThumbnailFormat::Png).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_generate_and_delete_thumbnails() {
+        let storage = MockFileStorage::new();
+        let data = png(300, 300);
+        storage
+            .upload_file(FileUploadRequest::new(
+                "u/1/a.png",
+                data.clone(),
+                "image/png",
+            ))
+            .await
+            .unwrap();
+
+        let sizes = generate_thumbnails(&storage, "u/1/a.png", "image/png", data.clone())
+            .await
+            .unwrap();
+        assert_eq!(sizes, THUMBNAIL_SIZES.to_vec());
+        let key = thumbnail_key("u/1/a.png", 128, ThumbnailFormat::Png);
+        assert_eq!(key, "u/1/a.png.thumb-128.png");
+        assert!(storage.file_exists(&key).await.unwrap());
+
+        // Not an image
+        let sizes = generate_thumbnails(&storage, "u/1/a.png", "text/plain", data)
+            .await
+            .unwrap();
+        assert!(sizes.is_empty());
+
+        assert_eq!(
+            delete_thumbnails(&storage, "u/1/a.png", "image/png")
+                .await
+                .unwrap(),
+            3
+        );
+        assert!(!storage.file_exists(&key).await.unwrap());
+    }
+}
@@ -1,7 +1,11 @@
 //! Storage trait definitions.
 
 use super::types::{FileMetadata, FileUploadRequest};
+use axum::body::Bytes;
+use futures_util::Stream;
 use std::future::Future;
+use std::io;
+use std::ops::Range;
 
 /// Generic interface for file storage operations.
 ///
@@ -14,11 +18,31 @@ pub trait FileStorage: Clone + Send + Sync + 'static {
         request: FileUploadRequest,
     ) -> impl Future<Output = Result<FileMetadata, Self::Error>> + Send;
 
+    /// Store the object at `path` from `chunks` as they arrive, without
+    /// holding it in memory, and return its size.
+    ///
+    /// Nothing is stored if `chunks` fails.
+    fn upload_stream<C>(
+        &self,
+        path: &str,
+        content_type: &str,
+        chunks: C,
+    ) -> impl Future<Output = Result<u64, Self::Error>> + Send
+    where
+        C: Stream<Item = io::Result<Bytes>> + Send;
+
     fn download_file(
         &self,
         path: &str,
     ) -> impl Future<Output = Result<Vec<u8>, Self::Error>> + Send;
 
+    /// Download the bytes of `range`, truncated at the end of the file.
+    fn download_range(
+        &self,
+        path: &str,
+        range: Range<u64>,
+    ) -> impl Future<Output = Result<Vec<u8>, Self::Error>> + Send;
+
     fn delete_file(&self, path: &str) -> impl Future<Output = Result<bool, Self::Error>> + Send;
 
     fn list_files(
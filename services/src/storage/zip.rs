@@ -0,0 +1,628 @@
+//! Streaming ZIP writer and reader.
+//!
+//! [`ZipWriter`] produces an archive front to back, one entry at a time,
+//! without seeking or holding entries in memory. Entries are stored
+//! uncompressed; their CRC-32 is only known once their bytes have gone out,
+//! so it follows each entry in a data descriptor. ZIP64 records are used
+//! where sizes, offsets or the entry count outgrow the classic format.
+//!
+//! [`ZipReader`] reads such an archive back in the same order, from a stream
+//! rather than a file: it walks the local headers and stops at the central
+//! directory. It only handles stored entries whose local header gives their
+//! size, as [`ZipWriter`] writes them.
+
+use std::io;
+
+use axum::body::Bytes;
+use chrono::{DateTime, Datelike, Timelike, Utc};
+use tokio::io::{AsyncRead, AsyncReadExt as _};
+
+const LOCAL_HEADER_SIGNATURE: u32 = 0x0403_4b50;
+const DATA_DESCRIPTOR_SIGNATURE: u32 = 0x0807_4b50;
+const CENTRAL_HEADER_SIGNATURE: u32 = 0x0201_4b50;
+const ZIP64_END_SIGNATURE: u32 = 0x0606_4b50;
+const ZIP64_LOCATOR_SIGNATURE: u32 = 0x0706_4b50;
+const END_SIGNATURE: u32 = 0x0605_4b50;
+
+/// General purpose flags: sizes and CRC follow in a data descriptor (bit 3),
+/// names are UTF-8 (bit 11).
+const FLAGS: u16 = 0x0808;
+const VERSION: u16 = 20;
+const VERSION_ZIP64: u16 = 45;
+const ZIP64_EXTRA_ID: u16 = 0x0001;
+
+/// General purpose flag: the entry is encrypted.
+const FLAG_ENCRYPTED: u16 = 0x0001;
+/// General purpose flag: a data descriptor follows the entry data.
+const FLAG_DATA_DESCRIPTOR: u16 = 0x0008;
+
+/// Bytes read from storage per step while reading an entry.
+const READ_CHUNK_SIZE: usize = 64 * 1024;
+
+/// Field values at or above this are moved to ZIP64 records.
+const MAX_U32: u64 = 0xFFFF_FFFF;
+const MAX_U16: usize = 0xFFFF;
+
+/// Writes a ZIP archive as a sequence of byte chunks.
+///
+/// For each entry call [`start_entry`](Self::start_entry), send its bytes
+/// while passing them to [`write`](Self::write), then call
+/// [`finish_entry`](Self::finish_entry). [`finish`](Self::finish) returns
+/// the central directory that ends the archive.
+#[derive(Debug, Default)]
+pub struct ZipWriter {
+    offset: u64,
+    entries: Vec<Entry>,
+    current: Option<Current>,
+}
+
+#[derive(Debug)]
+struct Entry {
+    name: String,
+    size: u64,
+    crc: u32,
+    offset: u64,
+    time: u16,
+    date: u16,
+}
+
+#[derive(Debug)]
+struct Current {
+    entry: Entry,
+    hasher: crc32fast::Hasher,
+    written: u64,
+}
+
+impl ZipWriter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start an entry named `name` that will hold exactly `size` bytes, and
+    /// return its local header.
+    ///
+    /// # Panics
+    ///
+    /// If the previous entry has not been finished.
+    pub fn start_entry(&mut self, name: &str, size: u64, modified: DateTime<Utc>) -> Bytes {
+        assert!(self.current.is_none(), "previous ZIP entry not finished");
+
+        let (time, date) = dos_date_time(modified);
+        let entry = Entry {
+            name: name.to_owned(),
+            size,
+            crc: 0,
+            offset: self.offset,
+            time,
+            date,
+        };
+
+        // The sizes are known up front, so they are given here as well as in
+        // the data descriptor; streaming readers use them to find the end of
+        // the stored data.
+        let zip64 = size >= MAX_U32;
+        let mut header = Vec::with_capacity(30 + name.len() + 20);
+        put_u32(&mut header, LOCAL_HEADER_SIGNATURE);
+        put_u16(&mut header, if zip64 { VERSION_ZIP64 } else { VERSION });
+        put_u16(&mut header, FLAGS);
+        put_u16(&mut header, 0); // stored
+        put_u16(&mut header, time);
+        put_u16(&mut header, date);
+        put_u32(&mut header, 0); // CRC-32, in the data descriptor
+        put_u32(&mut header, clamp_u32(size));
+        put_u32(&mut header, clamp_u32(size));
+        put_u16(&mut header, name.len() as u16);
+        put_u16(&mut header, if zip64 { 20 } else { 0 });
+        header.extend_from_slice(name.as_bytes());
+        if zip64 {
+            put_u16(&mut header, ZIP64_EXTRA_ID);
+            put_u16(&mut header, 16);
+            put_u64(&mut header, size);
+            put_u64(&mut header, size);
+        }
+
+        self.offset += header.len() as u64;
+        self.current = Some(Current {
+            entry,
+            hasher: crc32fast::Hasher::new(),
+            written: 0,
+        });
+        header.into()
+    }
+
+    /// Account for `data`, the next bytes of the current entry.
+    ///
+    /// # Panics
+    ///
+    /// If no entry has been started.
+    pub fn write(&mut self, data: &[u8]) {
+        let current = self.current.as_mut().expect("no ZIP entry started");
+        current.hasher.update(data);
+        current.written += data.len() as u64;
+        self.offset += data.len() as u64;
+    }
+
+    /// Finish the current entry and return its data descriptor.
+    ///
+    /// Fails if the entry did not get the number of bytes given to
+    /// [`start_entry`](Self::start_entry).
+    ///
+    /// # Panics
+    ///
+    /// If no entry has been started.
+    pub fn finish_entry(&mut self) -> io::Result<Bytes> {
+        let Current {
+            mut entry,
+            hasher,
+            written,
+        } = self.current.take().expect("no ZIP entry started");
+        if written != entry.size {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                format!(
+                    "ZIP entry '{}' is {written} bytes, expected {}",
+                    entry.name, entry.size
+                ),
+            ));
+        }
+        entry.crc = hasher.finalize();
+
+        let mut descriptor = Vec::with_capacity(24);
+        put_u32(&mut descriptor, DATA_DESCRIPTOR_SIGNATURE);
+        put_u32(&mut descriptor, entry.crc);
+        if entry.size >= MAX_U32 {
+            put_u64(&mut descriptor, entry.size);
+            put_u64(&mut descriptor, entry.size);
+        } else {
+            put_u32(&mut descriptor, entry.size as u32);
+            put_u32(&mut descriptor, entry.size as u32);
+        }
+
+        self.offset += descriptor.len() as u64;
+        self.entries.push(entry);
+        Ok(descriptor.into())
+    }
+
+    /// Return the central directory and end records that close the archive.
+    ///
+    /// # Panics
+    ///
+    /// If the last entry has not been finished.
+    pub fn finish(self) -> Bytes {
+        assert!(self.current.is_none(), "last ZIP entry not finished");
+
+        let mut out = Vec::new();
+        for entry in &self.entries {
+            let mut extra = Vec::new();
+            if entry.size >= MAX_U32 {
+                put_u64(&mut extra, entry.size);
+                put_u64(&mut extra, entry.size);
+            }
+            if entry.offset >= MAX_U32 {
+                put_u64(&mut extra, entry.offset);
+            }
+            let zip64 = !extra.is_empty();
+
+            put_u32(&mut out, CENTRAL_HEADER_SIGNATURE);
+            put_u16(&mut out, VERSION_ZIP64); // version made by
+            put_u16(&mut out, if zip64 { VERSION_ZIP64 } else { VERSION });
+            put_u16(&mut out, FLAGS);
+            put_u16(&mut out, 0); // stored
+            put_u16(&mut out, entry.time);
+            put_u16(&mut out, entry.date);
+            put_u32(&mut out, entry.crc);
+            put_u32(&mut out, clamp_u32(entry.size));
+            put_u32(&mut out, clamp_u32(entry.size));
+            put_u16(&mut out, entry.name.len() as u16);
+            put_u16(&mut out, if zip64 { extra.len() as u16 + 4 } else { 0 });
+            put_u16(&mut out, 0); // comment length
+            put_u16(&mut out, 0); // disk number
+            put_u16(&mut out, 0); // internal attributes
+            put_u32(&mut out, 0); // external attributes
+            put_u32(&mut out, clamp_u32(entry.offset));
+            out.extend_from_slice(entry.name.as_bytes());
+            if zip64 {
+                put_u16(&mut out, ZIP64_EXTRA_ID);
+                put_u16(&mut out, extra.len() as u16);
+                out.extend_from_slice(&extra);
+            }
+        }
+
+        let directory_offset = self.offset;
+        let directory_size = out.len() as u64;
+        let count = self.entries.len();
+
+        if count >= MAX_U16 || directory_offset >= MAX_U32 || directory_size >= MAX_U32 {
+            let zip64_end_offset = directory_offset + directory_size;
+            put_u32(&mut out, ZIP64_END_SIGNATURE);
+            put_u64(&mut out, 44); // size of the rest of this record
+            put_u16(&mut out, VERSION_ZIP64);
+            put_u16(&mut out, VERSION_ZIP64);
+            put_u32(&mut out, 0); // this disk
+            put_u32(&mut out, 0); // disk with the central directory
+            put_u64(&mut out, count as u64);
+            put_u64(&mut out, count as u64);
+            put_u64(&mut out, directory_size);
+            put_u64(&mut out, directory_offset);
+
+            put_u32(&mut out, ZIP64_LOCATOR_SIGNATURE);
+            put_u32(&mut out, 0); // disk with the ZIP64 end record
+            put_u64(&mut out, zip64_end_offset);
+            put_u32(&mut out, 1); // total disks
+        }
+
+        put_u32(&mut out, END_SIGNATURE);
+        put_u16(&mut out, 0); // this disk
+        put_u16(&mut out, 0); // disk with the central directory
+        put_u16(&mut out, count.min(MAX_U16) as u16);
+        put_u16(&mut out, count.min(MAX_U16) as u16);
+        put_u32(&mut out, clamp_u32(directory_size));
+        put_u32(&mut out, clamp_u32(directory_offset));
+        put_u16(&mut out, 0); // comment length
+        out.into()
+    }
+}
+
+/// An entry found by [`ZipReader::next_entry`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ZipEntry {
+    pub name: String,
+    pub size: u64,
+}
+
+/// Reads the entries of a ZIP archive in order, from a stream.
+///
+/// Call [`next_entry`](Self::next_entry) to move to the next entry, then
+/// [`read_entry`](Self::read_entry) to get its bytes, or
+/// [`read_chunk`](Self::read_chunk) to stream them. Entries that are not
+/// read are skipped. The CRC-32 of every entry is checked.
+pub struct ZipReader<R> {
+    reader: R,
+    current: Option<Pending>,
+    done: bool,
+}
+
+#[derive(Debug)]
+struct Pending {
+    entry: ZipEntry,
+    crc: u32,
+    descriptor: bool,
+    zip64: bool,
+    /// Bytes of the entry not read yet.
+    remaining: u64,
+    hasher: crc32fast::Hasher,
+}
+
+impl<R: AsyncRead + Unpin> ZipReader<R> {
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader,
+            current: None,
+            done: false,
+        }
+    }
+
+    /// Move to the next entry, skipping the rest of the current one.
+    ///
+    /// Returns `None` once the central directory is reached.
+    pub async fn next_entry(&mut self) -> io::Result<Option<ZipEntry>> {
+        if self.current.is_some() {
+            while self.read_chunk().await?.is_some() {}
+        }
+        if self.done {
+            return Ok(None);
+        }
+
+        let signature = self.reader.read_u32_le().await?;
+        if signature == CENTRAL_HEADER_SIGNATURE || signature == END_SIGNATURE {
+            self.done = true;
+            return Ok(None);
+        }
+        if signature != LOCAL_HEADER_SIGNATURE {
+            return Err(invalid_data("not a ZIP local file header"));
+        }
+
+        let mut header = [0u8; 26];
+        self.reader.read_exact(&mut header).await?;
+        let flags = u16_at(&header, 2);
+        let method = u16_at(&header, 4);
+        let crc = u32_at(&header, 10);
+        let compressed_size = u32_at(&header, 14);
+        let size = u32_at(&header, 18);
+        let name_len = u16_at(&header, 22) as usize;
+        let extra_len = u16_at(&header, 24) as usize;
+
+        let mut name = vec![0u8; name_len];
+        self.reader.read_exact(&mut name).await?;
+        let name =
+            String::from_utf8(name).map_err(|_| invalid_data("ZIP entry name is not UTF-8"))?;
+        let mut extra = vec![0u8; extra_len];
+        self.reader.read_exact(&mut extra).await?;
+
+        if flags & FLAG_ENCRYPTED != 0 {
+            return Err(unsupported(format!("ZIP entry '{name}' is encrypted")));
+        }
+        if method != 0 {
+            return Err(unsupported(format!("ZIP entry '{name}' is compressed")));
+        }
+
+        let mut size = u64::from(size);
+        let mut zip64 = false;
+        if size == MAX_U32 || u64::from(compressed_size) == MAX_U32 {
+            size = zip64_size(&extra)
+                .ok_or_else(|| invalid_data(format!("ZIP entry '{name}' lacks its ZIP64 sizes")))?;
+            zip64 = true;
+        }
+
+        let entry = ZipEntry { name, size };
+        self.current = Some(Pending {
+            entry: entry.clone(),
+            crc,
+            descriptor: flags & FLAG_DATA_DESCRIPTOR != 0,
+            zip64,
+            remaining: size,
+            hasher: crc32fast::Hasher::new(),
+        });
+        Ok(Some(entry))
+    }
+
+    /// Read the data of the current entry.
+    ///
+    /// Fails without reading if the entry is larger than `max_size`.
+    ///
+    /// # Panics
+    ///
+    /// If there is no current entry.
+    pub async fn read_entry(&mut self, max_size: u64) -> io::Result<Vec<u8>> {
+        let current = self.current.as_ref().expect("no current ZIP entry");
+        if current.entry.size > max_size {
+            return Err(invalid_data(format!(
+                "ZIP entry '{}' is larger than {max_size} bytes",
+                current.entry.name
+            )));
+        }
+        // Grown as the bytes arrive rather than trusting the header's size
+        let mut data = Vec::with_capacity(current.entry.size.min(READ_CHUNK_SIZE as u64) as usize);
+        while let Some(chunk) = self.read_chunk().await? {
+            data.extend_from_slice(&chunk);
+        }
+        Ok(data)
+    }
+
+    /// Read the next chunk of the current entry, or `None` once all of it
+    /// has been read.
+    ///
+    /// Before returning `None`, the entry's data descriptor is read and its
+    /// CRC-32 checked; the entry is then done with.
+    ///
+    /// # Panics
+    ///
+    /// If there is no current entry.
+    pub async fn read_chunk(&mut self) -> io::Result<Option<Bytes>> {
+        let current = self.current.as_mut().expect("no current ZIP entry");
+        if current.remaining == 0 {
+            self.finish_entry().await?;
+            return Ok(None);
+        }
+
+        let len = current.remaining.min(READ_CHUNK_SIZE as u64) as usize;
+        let mut chunk = vec![0u8; len];
+        self.reader.read_exact(&mut chunk).await?;
+        current.hasher.update(&chunk);
+        current.remaining -= len as u64;
+        Ok(Some(chunk.into()))
+    }
+
+    /// Read the data descriptor of the current entry, whose data has been
+    /// read, and check its CRC-32.
+    async fn finish_entry(&mut self) -> io::Result<()> {
+        let Pending {
+            entry,
+            mut crc,
+            descriptor,
+            zip64,
+            hasher,
+            ..
+        } = self.current.take().expect("no current ZIP entry");
+
+        if descriptor {
+            // The descriptor signature is optional
+            let mut value = self.reader.read_u32_le().await?;
+            if value == DATA_DESCRIPTOR_SIGNATURE {
+                value = self.reader.read_u32_le().await?;
+            }
+            crc = value;
+            let (compressed_size, size) = if zip64 {
+                (
+                    self.reader.read_u64_le().await?,
+                    self.reader.read_u64_le().await?,
+                )
+            } else {
+                (
+                    u64::from(self.reader.read_u32_le().await?),
+                    u64::from(self.reader.read_u32_le().await?),
+                )
+            };
+            if compressed_size != entry.size || size != entry.size {
+                return Err(invalid_data(format!(
+                    "ZIP entry '{}' has no size in its local header",
+                    entry.name
+                )));
+            }
+        }
+
+        if hasher.finalize() != crc {
+            return Err(invalid_data(format!(
+                "ZIP entry '{}' failed its CRC-32 check",
+                entry.name
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// The uncompressed size from a ZIP64 extended information extra field.
+fn zip64_size(mut extra: &[u8]) -> Option<u64> {
+    while extra.len() >= 4 {
+        let id = u16_at(extra, 0);
+        let len = u16_at(extra, 2) as usize;
+        let data = extra.get(4..4 + len)?;
+        if id == ZIP64_EXTRA_ID && data.len() >= 8 {
+            return Some(u64::from_le_bytes(data[..8].try_into().ok()?));
+        }
+        extra = &extra[4 + len..];
+    }
+    None
+}
+
+fn invalid_data(message: impl Into<String>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.into())
+}
+
+fn unsupported(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::Unsupported, message)
+}
+
+fn u16_at(data: &[u8], at: usize) -> u16 {
+    u16::from_le_bytes([data[at], data[at + 1]])
+}
+
+fn u32_at(data: &[u8], at: usize) -> u32 {
+    u32::from_le_bytes([data[at], data[at + 1], data[at + 2], data[at + 3]])
+}
+
+/// MS-DOS time and date fields for `at`, clamped to the format's 1980 epoch.
+fn dos_date_time(at: DateTime<Utc>) -> (u16, u16) {
+    if at.year() < 1980 {
+        return (0, (1 << 5) | 1);
+    }
+    let time = (at.hour() << 11) | (at.minute() << 5) | (at.second() / 2);
+    let date = ((at.year().min(2107) as u32 - 1980) << 9) | (at.month() << 5) | at.day();
+    (time as u16, date as u16)
+}
+
+fn clamp_u32(value: u64) -> u32 {
+    value.min(MAX_U32) as u32
+}
+
+fn put_u16(out: &mut Vec<u8>, value: u16) {
+    out.extend_from_slice(&value.to_le_bytes());
+}
+
+fn put_u32(out: &mut Vec<u8>, value: u32) {
+    out.extend_from_slice(&value.to_le_bytes());
+}
+
+fn put_u64(out: &mut Vec<u8>, value: u64) {
+    out.extend_from_slice(&value.to_le_bytes());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_zip_writer_layout() {
+        let modified = "2026-10-17T12:34:56Z".parse().unwrap();
+        let mut zip = ZipWriter::new();
+        let mut archive = Vec::new();
+        for (name, data) in [("hello.txt", &b"hello"[..]), ("empty.md", &b""[..])] {
+            archive.extend_from_slice(&zip.start_entry(name, data.len() as u64, modified));
+            zip.write(data);
+            archive.extend_from_slice(data);
+            archive.extend_from_slice(&zip.finish_entry().unwrap());
+        }
+        archive.extend_from_slice(&zip.finish());
+
+        // Local header, data, then a data descriptor with the CRC-32
+        assert_eq!(u32_at(&archive, 0), LOCAL_HEADER_SIGNATURE);
+        assert_eq!(u32_at(&archive, 22), 5);
+        assert_eq!(&archive[30..39], b"hello.txt");
+        assert_eq!(&archive[39..44], b"hello");
+        assert_eq!(u32_at(&archive, 44), DATA_DESCRIPTOR_SIGNATURE);
+        assert_eq!(u32_at(&archive, 48), 0x3610_a686);
+
+        // End record points at a central directory of two entries
+        let end = archive.len() - 22;
+        assert_eq!(u32_at(&archive, end), END_SIGNATURE);
+        assert_eq!(u16_at(&archive, end + 10), 2);
+        let directory_size = u32_at(&archive, end + 12) as usize;
+        let directory_offset = u32_at(&archive, end + 16) as usize;
+        assert_eq!(directory_offset + directory_size, end);
+        assert_eq!(u32_at(&archive, directory_offset), CENTRAL_HEADER_SIGNATURE);
+        assert_eq!(u32_at(&archive, directory_offset + 16), 0x3610_a686);
+        assert_eq!(u16_at(&archive, directory_offset + 14), 0x5d51);
+    }
+
+    #[test]
+    fn test_zip_writer_rejects_short_entry() {
+        let mut zip = ZipWriter::new();
+        zip.start_entry("a.bin", 4, Utc::now());
+        zip.write(b"ab");
+        assert!(zip.finish_entry().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_zip_reader_reads_writer_output() {
+        let mut zip = ZipWriter::new();
+        let mut archive = Vec::new();
+        for (name, data) in [("manifest.json", &b"{}"[..]), ("blobs/1", &b"hello"[..])] {
+            archive.extend_from_slice(&zip.start_entry(name, data.len() as u64, Utc::now()));
+            zip.write(data);
+            archive.extend_from_slice(data);
+            archive.extend_from_slice(&zip.finish_entry().unwrap());
+        }
+        archive.extend_from_slice(&zip.finish());
+
+        let mut reader = ZipReader::new(&archive[..]);
+        let entry = reader.next_entry().await.unwrap().unwrap();
+        assert_eq!(entry.name, "manifest.json");
+        // Unread entries are skipped
+        let entry = reader.next_entry().await.unwrap().unwrap();
+        assert_eq!(entry.name, "blobs/1");
+        assert_eq!(entry.size, 5);
+        assert!(reader.read_entry(4).await.is_err());
+        assert_eq!(reader.read_entry(5).await.unwrap(), b"hello");
+        assert!(reader.next_entry().await.unwrap().is_none());
+        assert!(reader.next_entry().await.unwrap().is_none());
+
+        // Corrupted data fails the CRC-32 check
+        let at = archive.windows(5).position(|w| w == b"hello").unwrap();
+        archive[at] = b'j';
+        let mut reader = ZipReader::new(&archive[..]);
+        reader.next_entry().await.unwrap();
+        reader.next_entry().await.unwrap();
+        assert!(reader.read_entry(5).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_zip_reader_streams_entry_chunks() {
+        let data: Vec<u8> = (0..READ_CHUNK_SIZE + 10).map(|i| (i % 251) as u8).collect();
+        let mut zip = ZipWriter::new();
+        let mut archive = Vec::new();
+        archive.extend_from_slice(&zip.start_entry("blobs/1", data.len() as u64, Utc::now()));
+        zip.write(&data);
+        archive.extend_from_slice(&data);
+        archive.extend_from_slice(&zip.finish_entry().unwrap());
+        archive.extend_from_slice(&zip.finish());
+
+        let mut reader = ZipReader::new(&archive[..]);
+        reader.next_entry().await.unwrap();
+        let mut chunks = Vec::new();
+        while let Some(chunk) = reader.read_chunk().await.unwrap() {
+            chunks.push(chunk);
+        }
+        assert_eq!(chunks.len(), 2);
+        assert_eq!(chunks.concat(), data);
+        assert!(reader.next_entry().await.unwrap().is_none());
+
+        // The CRC-32 is checked once the last chunk has been read
+        let at = 30 + "blobs/1".len() + READ_CHUNK_SIZE;
+        archive[at] ^= 0xff;
+        let mut reader = ZipReader::new(&archive[..]);
+        reader.next_entry().await.unwrap();
+        assert!(reader.read_chunk().await.unwrap().is_some());
+        assert!(reader.read_chunk().await.unwrap().is_some());
+        assert!(reader.read_chunk().await.is_err());
+    }
+}
@@ -0,0 +1,152 @@
+//! Background sweep of expired uploads.
+//!
+//! Uploads that are initialized but never completed leave an `initiated` row
+//! in `uploads` and possibly an object (or multipart parts) in storage. The
+//! sweeper periodically marks such uploads `expired` once their session has
+//! expired, aborts backend multipart uploads and deletes the stored objects.
+
+use std::time::Duration;
+
+use crate::database::{SqlStorage, UploadRow};
+use crate::storage::{FileStorage, StorageRouter};
+
+/// Maximum number of uploads expired per database round trip.
+const SWEEP_BATCH_SIZE: i64 = 100;
+
+/// What a sweep cleaned up.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct SweepReport {
+    /// Uploads marked `expired`.
+    pub expired_uploads: usize,
+    /// Storage objects deleted.
+    pub deleted_objects: usize,
+    /// Backend multipart uploads aborted.
+    pub aborted_multipart_uploads: usize,
+    /// Uploads whose storage could not be cleaned up (see logs).
+    pub failed_cleanups: usize,
+}
+
+impl SweepReport {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// Periodic task expiring stale uploads and deleting their objects.
+#[derive(Clone)]
+pub struct UploadSweeper<S> {
+    sql_storage: S,
+    storage: StorageRouter,
+}
+
+impl<S: SqlStorage> UploadSweeper<S> {
+    pub fn new(sql_storage: S, storage: StorageRouter) -> Self {
+        Self {
+            sql_storage,
+            storage,
+        }
+    }
+
+    /// Run one sweep of every upload that expired before `now`.
+    pub async fn sweep(
+        &self,
+        now: chrono::DateTime<chrono::Utc>,
+    ) -> Result<SweepReport, crate::database::SqlStorageError> {
+        let mut report = SweepReport::default();
+
+        loop {
+            let expired = self
+                .sql_storage
+                .uploads_expire_stale(now, SWEEP_BATCH_SIZE)
+                .await?;
+            let done = (expired.len() as i64) < SWEEP_BATCH_SIZE;
+
+            for upload in &expired {
+                report.expired_uploads += 1;
+                self.clean_up(upload, &mut report).await;
+            }
+
+            if done {
+                return Ok(report);
+            }
+        }
+    }
+
+    /// Delete the storage left behind by an expired upload.
+    async fn clean_up(&self, upload: &UploadRow, report: &mut SweepReport) {
+        let Some(target) = self
+            .storage
+            .resolve(&upload.storage_backend, &upload.storage_profile)
+        else {
+            tracing::warn!(
+                upload_id = %upload.id,
+                storage_backend = %upload.storage_backend,
+                storage_profile = %upload.storage_profile,
+                "Cannot clean up expired upload: storage is not configured"
+            );
+            report.failed_cleanups += 1;
+            return;
+        };
+
+        if let Some(multipart_upload_id) = &upload.multipart_upload_id {
+            match target
+                .presigner()
+                .abort_multipart_upload(&upload.storage_key, multipart_upload_id)
+                .await
+            {
+                Ok(()) => report.aborted_multipart_uploads += 1,
+                Err(e) => {
+                    tracing::warn!(upload_id = %upload.id, "Failed to abort multipart upload: {e}");
+                    report.failed_cleanups += 1;
+                    return;
+                }
+            }
+        }
+
+        match target.file_storage().delete_file(&upload.storage_key).await {
+            Ok(true) => {
+                tracing::info!(
+                    upload_id = %upload.id,
+                    storage_key = %upload.storage_key,
+                    "Deleted object of expired upload"
+                );
+                report.deleted_objects += 1;
+            }
+            Ok(false) => {}
+            Err(e) => {
+                tracing::warn!(
+                    upload_id = %upload.id,
+                    storage_key = %upload.storage_key,
+                    "Failed to delete object of expired upload: {e}"
+                );
+                report.failed_cleanups += 1;
+            }
+        }
+    }
+
+    /// Spawn a task sweeping every `interval`.
+    pub fn spawn(self, interval: Duration) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            loop {
+                ticker.tick().await;
+                match self.sweep(chrono::Utc::now()).await {
+                    Ok(report) if report.is_empty() => {
+                        tracing::debug!("Upload sweep found nothing to clean up");
+                    }
+                    Ok(report) => {
+                        tracing::info!(
+                            expired_uploads = report.expired_uploads,
+                            deleted_objects = report.deleted_objects,
+                            aborted_multipart_uploads = report.aborted_multipart_uploads,
+                            failed_cleanups = report.failed_cleanups,
+                            "Upload sweep finished"
+                        );
+                    }
+                    Err(e) => tracing::error!("Upload sweep failed: {e:?}"),
+                }
+            }
+        })
+    }
+}
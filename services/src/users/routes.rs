@@ -28,8 +28,8 @@
 //! ```
 
 use axum::{
-    Json, Router,
-    extract::{Path, State},
+    Extension, Json, Router,
+    extract::{Path, Query, State},
     http::{HeaderMap, StatusCode},
     response::IntoResponse,
     routing::{delete, get, post, put},
@@ -45,8 +45,12 @@ use super::otp::{
 use super::revocation_cache::RevocationCache;
 use super::session_auth::{RequireAuth, hash_token};
 use super::storage::{UserStorage, UserStorageError};
+use crate::audit::{self, ClientIp};
+use crate::auth::AccessClaims;
 use crate::config::Config;
-use crate::database::{OtpAttemptRecord, OtpRateLimitConfig, SqlStorage};
+use crate::database::{
+    AuditLogRow, AuditLogsListParams, OtpAttemptRecord, OtpRateLimitConfig, SqlStorage,
+};
 
 /// Response for logout endpoint.
 #[derive(Debug, Serialize, Deserialize)]
@@ -153,6 +157,98 @@ pub struct UpdateProfileResponse {
     pub avatar_url: Option<String>,
 }
 
+/// Request to set a user's storage quota.
+#[derive(Debug, Deserialize)]
+pub struct UpdateQuotaRequest {
+    /// The quota in bytes (pass null to remove the limit).
+    pub quota_bytes: Option<i64>,
+}
+
+/// Response with a user's storage quota and usage.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UserQuotaResponse {
+    /// The username.
+    pub username: String,
+    /// The quota in bytes, null when unlimited.
+    pub quota_bytes: Option<i64>,
+    /// Total bytes counted against the quota.
+    pub used_bytes: i64,
+    /// Bytes of active contents.
+    pub active_bytes: i64,
+    /// Bytes of archived contents.
+    pub archived_bytes: i64,
+    /// Bytes of trashed contents.
+    pub trashed_bytes: i64,
+    /// Declared bytes of uploads not yet completed.
+    pub pending_bytes: i64,
+}
+
+/// Query parameters for listing audit log entries.
+///
+/// All filters are optional and combined with AND.
+#[derive(Debug, Default, Deserialize)]
+pub struct ListAuditLogsQuery {
+    /// Only entries of this acting user.
+    pub user_id: Option<uuid::Uuid>,
+    /// Only entries of the user with this username.
+    pub username: Option<String>,
+    /// Only entries with this action, e.g. `content.trash`.
+    pub action: Option<String>,
+    /// Only entries about this entity type, e.g. `content`.
+    pub entity_type: Option<String>,
+    /// Only entries about this entity.
+    pub entity_id: Option<uuid::Uuid>,
+    /// Only entries created at or after this time (RFC 3339).
+    pub created_after: Option<chrono::DateTime<chrono::Utc>>,
+    /// Only entries created before this time (RFC 3339).
+    pub created_before: Option<chrono::DateTime<chrono::Utc>>,
+    /// Maximum number of entries to return (1-100, default 50).
+    pub limit: Option<i64>,
+    /// Number of entries to skip.
+    pub offset: Option<i64>,
+}
+
+/// An audit log entry.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AuditLogItem {
+    pub id: uuid::Uuid,
+    /// The acting user, null for internal actions.
+    pub user_id: Option<uuid::Uuid>,
+    pub action: String,
+    pub entity_type: String,
+    pub entity_id: Option<uuid::Uuid>,
+    pub details: Option<serde_json::Value>,
+    pub ip_address: Option<String>,
+    /// When the action happened (ISO 8601 format).
+    pub created_at: String,
+}
+
+impl From<AuditLogRow> for AuditLogItem {
+    fn from(row: AuditLogRow) -> Self {
+        Self {
+            id: row.id,
+            user_id: row.user_id,
+            action: row.action,
+            entity_type: row.entity_type,
+            entity_id: row.entity_id,
+            details: row.details,
+            ip_address: row.ip_address.map(|ip| ip.to_string()),
+            created_at: row.created_at.to_rfc3339(),
+        }
+    }
+}
+
+/// Response for the list audit logs endpoint.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ListAuditLogsResponse {
+    /// Entries, newest first.
+    pub entries: Vec<AuditLogItem>,
+    /// The limit applied.
+    pub limit: i64,
+    /// The offset applied.
+    pub offset: i64,
+}
+
 /// Error response for API endpoints.
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ErrorResponse {
@@ -256,6 +352,9 @@ where
         .route("/users/{username}", delete(delete_user::<S, U>))
         .route("/users/{username}/revoke", post(revoke_otp::<S, U>))
         .route("/users/{username}/profile", put(update_profile::<S, U>))
+        .route("/users/{username}/quota", get(get_quota::<S, U>))
+        .route("/users/{username}/quota", put(update_quota::<S, U>))
+        .route("/audit-logs", get(list_audit_logs::<S, U>))
 }
 
 /// Creates the router for authentication endpoints.
@@ -305,6 +404,8 @@ where
 #[tracing::instrument(skip_all, fields(username = %payload.username))]
 async fn create_user<S, U>(
     State(state): State<AppState<S, U>>,
+    claims: Option<Extension<AccessClaims>>,
+    client_ip: ClientIp,
     Json(payload): Json<CreateUserRequest>,
 ) -> impl IntoResponse
 where
@@ -346,8 +447,17 @@ where
         .create_user(&payload.username, &secret)
         .await
     {
-        Ok(_stored_user) => {
+        Ok(stored_user) => {
             tracing::info!("Successfully created user and stored OTP secret");
+            audit::record(
+                &state.sql_storage,
+                None,
+                "user.create",
+                Some(stored_user.id),
+                Some(internal_audit_details(claims, &payload.username)),
+                client_ip,
+            )
+            .await;
 
             (
                 StatusCode::CREATED,
@@ -587,6 +697,22 @@ where
             tracing::info!("OTP verification successful");
             // Record successful attempt
             record_otp_attempt(&state.sql_storage, &payload.username, true, client_ip).await;
+            let user_id = match state.user_storage.get_user(&payload.username).await {
+                Ok(user) => user.map(|user| user.id),
+                Err(e) => {
+                    tracing::warn!("Failed to get user for audit log: {}", e);
+                    None
+                }
+            };
+            audit::record(
+                &state.sql_storage,
+                user_id,
+                "auth.login",
+                user_id,
+                None,
+                ClientIp(client_ip),
+            )
+            .await;
             // Generate session token for the authenticated user
             let token = match generate_session_token(&payload.username, config.jwt_secret()) {
                 Ok(t) => Some(t),
@@ -650,7 +776,7 @@ where
 /// 3. `X-Forwarded-For` - Standard proxy header (uses first IP in chain)
 ///
 /// Returns `None` if no valid IP address is found.
-fn extract_client_ip(headers: &HeaderMap) -> Option<IpAddr> {
+pub(crate) fn extract_client_ip(headers: &HeaderMap) -> Option<IpAddr> {
     // Try CF-Connecting-IP first (Cloudflare)
     if let Some(value) = headers.get("cf-connecting-ip")
         && let Ok(ip_str) = value.to_str()
@@ -816,6 +942,7 @@ async fn validate_token_handler(
 async fn logout_handler<S, U>(
     State(state): State<AppState<S, U>>,
     auth: RequireAuth,
+    client_ip: ClientIp,
     headers: HeaderMap,
 ) -> impl IntoResponse
 where
@@ -864,6 +991,22 @@ where
             // Add to in-memory cache for fast lookups on subsequent requests
             state.revocation_cache.add_revoked(&token_hash);
             tracing::info!(username = %auth.username(), "Logout successful - token revoked");
+            let user_id = match state.user_storage.get_user(auth.username()).await {
+                Ok(user) => user.map(|user| user.id),
+                Err(e) => {
+                    tracing::warn!("Failed to get user for audit log: {}", e);
+                    None
+                }
+            };
+            audit::record(
+                &state.sql_storage,
+                user_id,
+                "auth.logout",
+                user_id,
+                None,
+                client_ip,
+            )
+            .await;
             (
                 StatusCode::OK,
                 Json(LogoutResponse {
@@ -1006,6 +1149,8 @@ fn generate_otp_secret_url(username: &str, secret_base32: &str) -> Result<String
 #[tracing::instrument(skip_all, fields(username = %username))]
 async fn update_username<S, U>(
     State(state): State<AppState<S, U>>,
+    claims: Option<Extension<AccessClaims>>,
+    client_ip: ClientIp,
     Path(username): Path<String>,
     Json(payload): Json<UpdateUsernameRequest>,
 ) -> impl IntoResponse
@@ -1026,8 +1171,19 @@ where
         .update_username(&username, &payload.new_username)
         .await
     {
-        Ok(_updated_user) => {
+        Ok(updated_user) => {
             tracing::info!("Successfully updated username");
+            let mut details = internal_audit_details(claims, &username);
+            details["new_username"] = payload.new_username.clone().into();
+            audit::record(
+                &state.sql_storage,
+                None,
+                "user.rename",
+                Some(updated_user.id),
+                Some(details),
+                client_ip,
+            )
+            .await;
             (
                 StatusCode::OK,
                 Json(UpdateUsernameResponse {
@@ -1079,6 +1235,8 @@ where
 #[tracing::instrument(skip_all, fields(username = %username))]
 async fn delete_user<S, U>(
     State(state): State<AppState<S, U>>,
+    claims: Option<Extension<AccessClaims>>,
+    client_ip: ClientIp,
     Path(username): Path<String>,
 ) -> impl IntoResponse
 where
@@ -1087,10 +1245,28 @@ where
 {
     tracing::info!("Deleting user");
 
+    // Look up the ID first: the audit entry outlives the user row
+    let user_id = match state.user_storage.get_user(&username).await {
+        Ok(user) => user.map(|user| user.id),
+        Err(e) => {
+            tracing::warn!("Failed to get user for audit log: {}", e);
+            None
+        }
+    };
+
     match state.user_storage.delete_user(&username).await {
         Ok(deleted) => {
             if deleted {
                 tracing::info!("Successfully deleted user");
+                audit::record(
+                    &state.sql_storage,
+                    None,
+                    "user.delete",
+                    user_id,
+                    Some(internal_audit_details(claims, &username)),
+                    client_ip,
+                )
+                .await;
             } else {
                 tracing::warn!("User not found for deletion: {}", username);
             }
@@ -1127,6 +1303,8 @@ where
 #[tracing::instrument(skip_all, fields(username = %username))]
 async fn revoke_otp<S, U>(
     State(state): State<AppState<S, U>>,
+    claims: Option<Extension<AccessClaims>>,
+    client_ip: ClientIp,
     Path(username): Path<String>,
 ) -> impl IntoResponse
 where
@@ -1164,8 +1342,17 @@ where
 
     // Update the user's OTP secret
     match state.user_storage.revoke_otp(&username, &new_secret).await {
-        Ok(_updated_user) => {
+        Ok(updated_user) => {
             tracing::info!("Successfully revoked OTP for user");
+            audit::record(
+                &state.sql_storage,
+                None,
+                "user.otp_revoke",
+                Some(updated_user.id),
+                Some(internal_audit_details(claims, &username)),
+                client_ip,
+            )
+            .await;
             (
                 StatusCode::OK,
                 Json(RevokeOtpResponse {
@@ -1210,6 +1397,8 @@ where
 #[tracing::instrument(skip_all, fields(username = %username))]
 async fn update_profile<S, U>(
     State(state): State<AppState<S, U>>,
+    claims: Option<Extension<AccessClaims>>,
+    client_ip: ClientIp,
     Path(username): Path<String>,
     Json(payload): Json<UpdateProfileRequest>,
 ) -> impl IntoResponse
@@ -1237,6 +1426,15 @@ where
     {
         Ok(updated_user) => {
             tracing::info!("Successfully updated profile for user");
+            audit::record(
+                &state.sql_storage,
+                None,
+                "user.profile_update",
+                Some(updated_user.id),
+                Some(internal_audit_details(claims, &username)),
+                client_ip,
+            )
+            .await;
             (
                 StatusCode::OK,
                 Json(UpdateProfileResponse {
@@ -1267,6 +1465,269 @@ where
     }
 }
 
+/// Handler for getting a user's storage quota and usage.
+///
+/// # Request
+///
+/// GET /internal/users/:username/quota
+///
+/// # Response
+///
+/// ```json
+/// {
+///     "username": "john_doe",
+///     "quota_bytes": 1073741824,
+///     "used_bytes": 5242880,
+///     "active_bytes": 4194304,
+///     "archived_bytes": 0,
+///     "trashed_bytes": 1048576,
+///     "pending_bytes": 0
+/// }
+/// ```
+#[tracing::instrument(skip_all, fields(username = %username))]
+async fn get_quota<S, U>(
+    State(state): State<AppState<S, U>>,
+    Path(username): Path<String>,
+) -> impl IntoResponse
+where
+    S: SqlStorage,
+    U: UserStorage,
+{
+    let user = match state.user_storage.get_user(&username).await {
+        Ok(Some(user)) => user,
+        Ok(None) => {
+            let (status, json): (StatusCode, Json<ErrorResponse>) =
+                UserStorageError::UserNotFound(username).into();
+            return (status, json).into_response();
+        }
+        Err(e) => {
+            tracing::error!("Failed to get user: {}", e);
+            let (status, json): (StatusCode, Json<ErrorResponse>) =
+                UserStorageError::StorageError(e.to_string()).into();
+            return (status, json).into_response();
+        }
+    };
+
+    quota_response(&state.sql_storage, user.id, user.username).await
+}
+
+/// Handler for setting a user's storage quota.
+///
+/// Uploads that would take the user's usage over the quota are rejected.
+/// Lowering the quota below current usage keeps existing contents.
+///
+/// # Request
+///
+/// PUT /internal/users/:username/quota
+///
+/// ```json
+/// {
+///     "quota_bytes": 1073741824
+/// }
+/// ```
+///
+/// # Response
+///
+/// Same as `GET /internal/users/:username/quota`.
+#[tracing::instrument(skip_all, fields(username = %username))]
+async fn update_quota<S, U>(
+    State(state): State<AppState<S, U>>,
+    claims: Option<Extension<AccessClaims>>,
+    client_ip: ClientIp,
+    Path(username): Path<String>,
+    Json(payload): Json<UpdateQuotaRequest>,
+) -> impl IntoResponse
+where
+    S: SqlStorage,
+    U: UserStorage,
+{
+    tracing::info!(quota_bytes = ?payload.quota_bytes, "Updating storage quota for user");
+
+    if payload.quota_bytes.is_some_and(|quota| quota < 0) {
+        let (status, json): (StatusCode, Json<ErrorResponse>) =
+            UserStorageError::InvalidInput("Storage quota cannot be negative".to_owned()).into();
+        return (status, json).into_response();
+    }
+
+    let user = match state.user_storage.get_user(&username).await {
+        Ok(Some(user)) => user,
+        Ok(None) => {
+            let (status, json): (StatusCode, Json<ErrorResponse>) =
+                UserStorageError::UserNotFound(username).into();
+            return (status, json).into_response();
+        }
+        Err(e) => {
+            tracing::error!("Failed to get user: {}", e);
+            let (status, json): (StatusCode, Json<ErrorResponse>) =
+                UserStorageError::StorageError(e.to_string()).into();
+            return (status, json).into_response();
+        }
+    };
+
+    match state
+        .sql_storage
+        .users_set_storage_quota(user.id, payload.quota_bytes)
+        .await
+    {
+        Ok(true) => {
+            let mut details = internal_audit_details(claims, &username);
+            details["quota_bytes"] = payload.quota_bytes.into();
+            audit::record(
+                &state.sql_storage,
+                None,
+                "user.quota_update",
+                Some(user.id),
+                Some(details),
+                client_ip,
+            )
+            .await;
+            quota_response(&state.sql_storage, user.id, user.username).await
+        }
+        Ok(false) => {
+            let (status, json): (StatusCode, Json<ErrorResponse>) =
+                UserStorageError::UserNotFound(username).into();
+            (status, json).into_response()
+        }
+        Err(e) => {
+            tracing::error!("Failed to update storage quota: {:?}", e);
+            let (status, json): (StatusCode, Json<ErrorResponse>) =
+                UserStorageError::StorageError(format!("{e:?}")).into();
+            (status, json).into_response()
+        }
+    }
+}
+
+/// Audit log details for an internal endpoint acting on `username`.
+///
+/// Behind Zero Trust, the operator who made the call is recorded too.
+fn internal_audit_details(
+    claims: Option<Extension<AccessClaims>>,
+    username: &str,
+) -> serde_json::Value {
+    let operator = claims.map(|Extension(claims)| claims.email.unwrap_or(claims.sub));
+    serde_json::json!({ "username": username, "operator": operator })
+}
+
+/// Handler for listing audit log entries, newest first.
+///
+/// # Request
+///
+/// GET /internal/audit-logs?action=content.trash&username=john_doe&limit=20
+///
+/// # Response
+///
+/// ```json
+/// {
+///     "entries": [
+///         {
+///             "id": "...",
+///             "user_id": "...",
+///             "action": "content.trash",
+///             "entity_type": "content",
+///             "entity_id": "...",
+///             "details": null,
+///             "ip_address": "203.0.113.7",
+///             "created_at": "2026-01-01T00:00:00+00:00"
+///         }
+///     ],
+///     "limit": 20,
+///     "offset": 0
+/// }
+/// ```
+#[tracing::instrument(skip_all)]
+async fn list_audit_logs<S, U>(
+    State(state): State<AppState<S, U>>,
+    Query(query): Query<ListAuditLogsQuery>,
+) -> impl IntoResponse
+where
+    S: SqlStorage,
+    U: UserStorage,
+{
+    let mut user_id = query.user_id;
+    if let Some(username) = query.username {
+        match state.user_storage.get_user(&username).await {
+            Ok(Some(user)) if user_id.is_none_or(|id| id == user.id) => user_id = Some(user.id),
+            // Conflicting user filters match nothing
+            Ok(Some(_)) => user_id = Some(uuid::Uuid::nil()),
+            Ok(None) => {
+                let (status, json): (StatusCode, Json<ErrorResponse>) =
+                    UserStorageError::UserNotFound(username).into();
+                return (status, json).into_response();
+            }
+            Err(e) => {
+                tracing::error!("Failed to get user: {}", e);
+                let (status, json): (StatusCode, Json<ErrorResponse>) =
+                    UserStorageError::StorageError(e.to_string()).into();
+                return (status, json).into_response();
+            }
+        }
+    }
+
+    let limit = query.limit.unwrap_or(50).clamp(1, 100);
+    let offset = query.offset.unwrap_or(0).max(0);
+    let params = AuditLogsListParams {
+        limit,
+        offset,
+        user_id,
+        action: query.action,
+        entity_type: query.entity_type,
+        entity_id: query.entity_id,
+        created_after: query.created_after,
+        created_before: query.created_before,
+    };
+
+    match state.sql_storage.audit_logs_list(params).await {
+        Ok(rows) => (
+            StatusCode::OK,
+            Json(ListAuditLogsResponse {
+                entries: rows.into_iter().map(AuditLogItem::from).collect(),
+                limit,
+                offset,
+            }),
+        )
+            .into_response(),
+        Err(e) => {
+            tracing::error!("Failed to list audit logs: {:?}", e);
+            let (status, json): (StatusCode, Json<ErrorResponse>) =
+                UserStorageError::StorageError(format!("{e:?}")).into();
+            (status, json).into_response()
+        }
+    }
+}
+
+/// Build the quota response for a user from their current quota and usage.
+async fn quota_response<S: SqlStorage>(
+    sql_storage: &S,
+    user_id: uuid::Uuid,
+    username: String,
+) -> axum::response::Response {
+    let usage = sql_storage
+        .users_storage_usage(user_id, chrono::Utc::now())
+        .await;
+    let quota = sql_storage.users_get_storage_quota(user_id).await;
+    match (usage, quota) {
+        (Ok(usage), Ok(quota_bytes)) => (
+            StatusCode::OK,
+            Json(UserQuotaResponse {
+                username,
+                quota_bytes,
+                used_bytes: usage.total_bytes(),
+                active_bytes: usage.active_bytes,
+                archived_bytes: usage.archived_bytes,
+                trashed_bytes: usage.trashed_bytes,
+                pending_bytes: usage.pending_bytes,
+            }),
+        )
+            .into_response(),
+        (Err(e), _) | (_, Err(e)) => {
+            tracing::error!("Failed to get storage usage: {:?}", e);
+            let (status, json): (StatusCode, Json<ErrorResponse>) =
+                UserStorageError::StorageError(format!("{e:?}")).into();
+            (status, json).into_response()
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1296,6 +1757,15 @@ mod tests {
             ))
         }
 
+        async fn contents_insert_within_quota(
+            &self,
+            _input: crate::database::ContentsInsert,
+        ) -> Result<crate::database::ContentRow, crate::database::SqlStorageError> {
+            Err(crate::database::SqlStorageError::Db(
+                "MockSqlStorage.contents_insert_within_quota: unimplemented".to_owned(),
+            ))
+        }
+
         async fn contents_get(
             &self,
             _id: uuid::Uuid,
@@ -1311,6 +1781,15 @@ mod tests {
             Ok(vec![])
         }
 
+        async fn contents_search(
+            &self,
+            _user_id: uuid::Uuid,
+            _params: crate::database::ContentsSearchParams,
+        ) -> Result<Vec<crate::database::ContentSearchHit>, crate::database::SqlStorageError>
+        {
+            Ok(vec![])
+        }
+
         async fn contents_update_metadata(
             &self,
             _id: uuid::Uuid,
@@ -1427,10 +1906,19 @@ mod tests {
             Ok(vec![])
         }
 
+        async fn tags_get(
+            &self,
+            _user_id: uuid::Uuid,
+            _tag_id: uuid::Uuid,
+        ) -> Result<Option<crate::database::TagRow>, crate::database::SqlStorageError> {
+            Ok(None)
+        }
+
         async fn tags_delete(
             &self,
             _user_id: uuid::Uuid,
             _tag_id: uuid::Uuid,
+            _expected_updated_at: Option<chrono::DateTime<chrono::Utc>>,
         ) -> Result<bool, crate::database::SqlStorageError> {
             Ok(false)
         }
@@ -1522,6 +2010,7 @@ mod tests {
             &self,
             _id: uuid::Uuid,
             _owner_id: uuid::Uuid,
+            _expected_updated_at: Option<chrono::DateTime<chrono::Utc>>,
         ) -> Result<bool, crate::database::SqlStorageError> {
             Ok(false)
         }
@@ -1533,6 +2022,47 @@ mod tests {
             Ok(())
         }
 
+        async fn share_links_rehash_password(
+            &self,
+            _id: uuid::Uuid,
+            _current_hash: &str,
+            _new_hash: &str,
+        ) -> Result<(), crate::database::SqlStorageError> {
+            Ok(())
+        }
+
+        async fn share_link_accesses_insert(
+            &self,
+            _input: crate::database::ShareLinkAccessInsert,
+        ) -> Result<(), crate::database::SqlStorageError> {
+            Ok(())
+        }
+
+        async fn share_link_accesses_stats(
+            &self,
+            _share_link_id: uuid::Uuid,
+            _since: chrono::DateTime<chrono::Utc>,
+        ) -> Result<crate::database::ShareLinkAccessStats, crate::database::SqlStorageError>
+        {
+            Ok(crate::database::ShareLinkAccessStats::default())
+        }
+
+        async fn share_link_password_reserve_attempt(
+            &self,
+            _share_link_id: uuid::Uuid,
+            _ip_address: Option<std::net::IpAddr>,
+            _config: &crate::database::ShareLinkPasswordRateLimitConfig,
+        ) -> Result<Option<uuid::Uuid>, crate::database::SqlStorageError> {
+            Ok(Some(uuid::Uuid::new_v4()))
+        }
+
+        async fn share_link_password_attempt_succeeded(
+            &self,
+            _attempt_id: uuid::Uuid,
+        ) -> Result<(), crate::database::SqlStorageError> {
+            Ok(())
+        }
+
         async fn content_shares_attach_link(
             &self,
             _content_id: uuid::Uuid,
@@ -1613,6 +2143,52 @@ mod tests {
             ))
         }
 
+        async fn content_shares_list_for_users(
+            &self,
+            _content_id: uuid::Uuid,
+        ) -> Result<Vec<crate::database::UserShareRow>, crate::database::SqlStorageError> {
+            Ok(vec![])
+        }
+
+        async fn group_shares_list_for_users(
+            &self,
+            _group_id: uuid::Uuid,
+        ) -> Result<Vec<crate::database::UserShareRow>, crate::database::SqlStorageError> {
+            Ok(vec![])
+        }
+
+        async fn content_shares_delete_for_user(
+            &self,
+            _content_id: uuid::Uuid,
+            _shared_with_user_id: uuid::Uuid,
+        ) -> Result<bool, crate::database::SqlStorageError> {
+            Ok(false)
+        }
+
+        async fn group_shares_delete_for_user(
+            &self,
+            _group_id: uuid::Uuid,
+            _shared_with_user_id: uuid::Uuid,
+        ) -> Result<bool, crate::database::SqlStorageError> {
+            Ok(false)
+        }
+
+        async fn shares_list_shared_with_user(
+            &self,
+            _user_id: uuid::Uuid,
+        ) -> Result<Vec<crate::database::SharedWithUserRow>, crate::database::SqlStorageError>
+        {
+            Ok(vec![])
+        }
+
+        async fn content_shares_permission_for_user(
+            &self,
+            _content_id: uuid::Uuid,
+            _user_id: uuid::Uuid,
+        ) -> Result<Option<String>, crate::database::SqlStorageError> {
+            Ok(None)
+        }
+
         async fn otp_record_attempt(
             &self,
             _input: crate::database::OtpAttemptRecord,
@@ -1655,6 +2231,201 @@ mod tests {
             Ok(None)
         }
 
+        async fn uploads_abort(
+            &self,
+            _id: uuid::Uuid,
+            _user_id: uuid::Uuid,
+        ) -> Result<Option<crate::database::UploadRow>, crate::database::SqlStorageError> {
+            Ok(None)
+        }
+
+        async fn uploads_expire_stale(
+            &self,
+            _now: chrono::DateTime<chrono::Utc>,
+            _limit: i64,
+        ) -> Result<Vec<crate::database::UploadRow>, crate::database::SqlStorageError> {
+            Ok(vec![])
+        }
+
+        async fn upload_parts_create(
+            &self,
+            _upload_id: uuid::Uuid,
+            _parts: Vec<crate::database::UploadPartInsert>,
+        ) -> Result<(), crate::database::SqlStorageError> {
+            Ok(())
+        }
+
+        async fn upload_parts_list(
+            &self,
+            _upload_id: uuid::Uuid,
+        ) -> Result<Vec<crate::database::UploadPartRow>, crate::database::SqlStorageError> {
+            Ok(vec![])
+        }
+
+        async fn upload_parts_mark_uploaded(
+            &self,
+            _upload_id: uuid::Uuid,
+            _part_number: i32,
+            _etag: String,
+        ) -> Result<Option<crate::database::UploadPartRow>, crate::database::SqlStorageError>
+        {
+            Ok(None)
+        }
+
+        async fn content_revisions_list(
+            &self,
+            _content_id: uuid::Uuid,
+        ) -> Result<Vec<crate::database::ContentRevisionRow>, crate::database::SqlStorageError>
+        {
+            Ok(vec![])
+        }
+
+        async fn content_revisions_get(
+            &self,
+            _content_id: uuid::Uuid,
+            _revision: i32,
+        ) -> Result<Option<crate::database::ContentRevisionRow>, crate::database::SqlStorageError>
+        {
+            Ok(None)
+        }
+
+        async fn contents_batch(
+            &self,
+            _user_id: uuid::Uuid,
+            _ids: &[uuid::Uuid],
+            _op: crate::database::ContentsBatchOp,
+            _now: chrono::DateTime<chrono::Utc>,
+        ) -> Result<Vec<uuid::Uuid>, crate::database::SqlStorageError> {
+            Ok(vec![])
+        }
+
+        async fn contents_set_media_metadata(
+            &self,
+            _id: uuid::Uuid,
+            _metadata: serde_json::Value,
+        ) -> Result<bool, crate::database::SqlStorageError> {
+            Ok(false)
+        }
+
+        async fn contents_purge_trashed(
+            &self,
+            _params: crate::database::TrashPurgeParams,
+        ) -> Result<Vec<crate::database::ContentRow>, crate::database::SqlStorageError> {
+            Ok(vec![])
+        }
+
+        async fn groups_purge_trashed(
+            &self,
+            _params: crate::database::TrashPurgeParams,
+        ) -> Result<Vec<crate::database::ContentGroupRow>, crate::database::SqlStorageError>
+        {
+            Ok(vec![])
+        }
+
+        async fn users_storage_usage(
+            &self,
+            _user_id: uuid::Uuid,
+            _now: chrono::DateTime<chrono::Utc>,
+        ) -> Result<crate::database::StorageUsage, crate::database::SqlStorageError> {
+            Ok(crate::database::StorageUsage::default())
+        }
+
+        async fn users_get_storage_quota(
+            &self,
+            _user_id: uuid::Uuid,
+        ) -> Result<Option<i64>, crate::database::SqlStorageError> {
+            Ok(None)
+        }
+
+        async fn users_set_storage_quota(
+            &self,
+            _user_id: uuid::Uuid,
+            _quota_bytes: Option<i64>,
+        ) -> Result<bool, crate::database::SqlStorageError> {
+            Ok(true)
+        }
+
+        async fn contents_share_storage_object(
+            &self,
+            _content_id: uuid::Uuid,
+            _input: crate::database::StorageObjectInsert,
+        ) -> Result<
+            Option<(
+                crate::database::StorageObjectRow,
+                crate::database::ContentRow,
+            )>,
+            crate::database::SqlStorageError,
+        > {
+            Ok(None)
+        }
+
+        async fn storage_objects_release(
+            &self,
+            _user_id: uuid::Uuid,
+            _sha256: &str,
+        ) -> Result<Option<crate::database::StorageObjectRow>, crate::database::SqlStorageError>
+        {
+            Ok(None)
+        }
+
+        async fn share_links_targets_for_owner(
+            &self,
+            _owner_id: uuid::Uuid,
+        ) -> Result<Vec<crate::database::ShareLinkTargetRow>, crate::database::SqlStorageError>
+        {
+            Ok(vec![])
+        }
+
+        async fn account_exports_create(
+            &self,
+            _user_id: uuid::Uuid,
+            _expires_at: chrono::DateTime<chrono::Utc>,
+        ) -> Result<crate::database::AccountExportRow, crate::database::SqlStorageError> {
+            Err(crate::database::SqlStorageError::Db(
+                "MockSqlStorage.account_exports_create: unimplemented".to_owned(),
+            ))
+        }
+
+        async fn account_exports_get(
+            &self,
+            _id: uuid::Uuid,
+        ) -> Result<Option<crate::database::AccountExportRow>, crate::database::SqlStorageError>
+        {
+            Ok(None)
+        }
+
+        async fn account_exports_complete(
+            &self,
+            _id: uuid::Uuid,
+            _manifest: serde_json::Value,
+        ) -> Result<Option<crate::database::AccountExportRow>, crate::database::SqlStorageError>
+        {
+            Ok(None)
+        }
+
+        async fn account_exports_fail(
+            &self,
+            _id: uuid::Uuid,
+            _error: &str,
+        ) -> Result<Option<crate::database::AccountExportRow>, crate::database::SqlStorageError>
+        {
+            Ok(None)
+        }
+
+        async fn audit_logs_insert(
+            &self,
+            _entry: crate::database::AuditLogInsert,
+        ) -> Result<(), crate::database::SqlStorageError> {
+            Ok(())
+        }
+
+        async fn audit_logs_list(
+            &self,
+            _params: crate::database::AuditLogsListParams,
+        ) -> Result<Vec<crate::database::AuditLogRow>, crate::database::SqlStorageError> {
+            Ok(vec![])
+        }
+
         async fn revoked_tokens_add(
             &self,
             _token_hash: &str,
@@ -2894,4 +3665,58 @@ mod tests {
         // Should be unauthorized with malformed auth header
         assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
     }
+
+    #[tokio::test]
+    async fn test_update_quota() {
+        let sql_storage = MockSqlStorage { is_connected: true };
+        let user_storage = MockUserStorage::new();
+        user_storage
+            .create_user("quotauser", "SECRET123")
+            .await
+            .expect("Failed to create user");
+        let state = AppState::new(sql_storage, user_storage);
+
+        let app = Router::new()
+            .nest(
+                "/internal",
+                internal_routes::<MockSqlStorage, MockUserStorage>(),
+            )
+            .with_state(state);
+
+        let put_quota = |username: &str, body: &'static str| {
+            Request::builder()
+                .method("PUT")
+                .uri(format!("/internal/users/{username}/quota"))
+                .header("content-type", "application/json")
+                .body(Body::from(body))
+                .expect("Failed to create request")
+        };
+
+        let response = app
+            .clone()
+            .oneshot(put_quota("quotauser", r#"{"quota_bytes": 1024}"#))
+            .await
+            .expect("Failed to get response");
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .expect("Failed to read body");
+        let quota: UserQuotaResponse =
+            serde_json::from_slice(&body).expect("Failed to parse response");
+        assert_eq!(quota.username, "quotauser");
+        assert_eq!(quota.used_bytes, 0);
+
+        let response = app
+            .clone()
+            .oneshot(put_quota("quotauser", r#"{"quota_bytes": -1}"#))
+            .await
+            .expect("Failed to get response");
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        let response = app
+            .oneshot(put_quota("nobody", r#"{"quota_bytes": 1024}"#))
+            .await
+            .expect("Failed to get response");
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
 }
@@ -0,0 +1,475 @@
+//! Group archive endpoint handlers.
+//!
+//! A group is downloaded as one ZIP file, built while it is sent: each
+//! content is read from storage and written to the response in turn, so the
+//! archive is never held in memory.
+
+use std::collections::HashSet;
+use std::io;
+
+use crate::audit::ClientIp;
+use crate::config::Config;
+use crate::database::{ContentGroupRow, SqlStorage};
+use crate::storage::{
+    BackendFileStorage, ContentDisposition, FileStorage, StorageRouter, ZipWriter, stream_range,
+};
+use crate::users::routes::AppState;
+use crate::users::session_auth::RequireAuth;
+use crate::users::storage::UserStorage;
+use axum::{
+    Extension, Json,
+    body::{Body, Bytes},
+    extract::{Path, State},
+    http::{HeaderMap, HeaderValue, StatusCode, header},
+    response::{IntoResponse, Response},
+};
+use chrono::{DateTime, Utc};
+use futures_util::TryStreamExt as _;
+use tokio::sync::mpsc;
+
+use super::public::{record_access, validate_share_link};
+use super::raw::download_filename;
+use super::share_unlock::{
+    check_share_link_password, set_unlock_cookie, unlock_token_from_headers,
+};
+use super::types::V1ErrorResponse;
+
+/// Header carrying the password of a password-protected share link.
+pub const SHARE_PASSWORD_HEADER: &str = "x-share-password";
+
+/// Archive chunks buffered ahead of a slow client.
+const ARCHIVE_CHANNEL_CAPACITY: usize = 4;
+
+/// Download one of the authenticated user's groups as a ZIP file.
+///
+/// Contents are written in group order. Text contents become `.md` or `.txt`
+/// files; trashed contents are left out.
+#[utoipa::path(
+    get,
+    path = "/v1/groups/{id}/archive.zip",
+    tag = "groups",
+    params(
+        ("id" = String, Path, description = "Group ID (UUID)")
+    ),
+    responses(
+        (status = 200, description = "ZIP archive of the group's contents", content_type = "application/zip"),
+        (status = 400, description = "Invalid group ID format", body = V1ErrorResponse),
+        (status = 401, description = "Unauthorized", body = V1ErrorResponse),
+        (status = 404, description = "Group not found", body = V1ErrorResponse),
+        (status = 500, description = "Internal server error", body = V1ErrorResponse),
+        (status = 502, description = "Storage backend not configured", body = V1ErrorResponse),
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn v1_groups_archive_zip<S, U>(
+    State(state): State<AppState<S, U>>,
+    storage: Option<axum::Extension<StorageRouter>>,
+    auth: RequireAuth,
+    Path(id): Path<String>,
+) -> impl IntoResponse
+where
+    S: SqlStorage,
+    U: UserStorage,
+{
+    // Get user ID from username
+    let user = match state.user_storage.get_user(auth.username()).await {
+        Ok(Some(user)) => user,
+        Ok(None) => {
+            return (
+                StatusCode::UNAUTHORIZED,
+                Json(V1ErrorResponse::not_found("User not found")),
+            )
+                .into_response();
+        }
+        Err(e) => {
+            tracing::error!("Failed to get user: {}", e);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(V1ErrorResponse::internal_error("Failed to get user")),
+            )
+                .into_response();
+        }
+    };
+
+    // Parse group ID
+    let group_id = match uuid::Uuid::parse_str(&id) {
+        Ok(id) => id,
+        Err(_) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(V1ErrorResponse::bad_request("Invalid group ID format")),
+            )
+                .into_response();
+        }
+    };
+
+    // Verify user owns the group
+    let group = match state.sql_storage.groups_get(group_id).await {
+        Ok(Some(row)) if row.user_id == user.id => row,
+        Ok(_) => {
+            return (
+                StatusCode::NOT_FOUND,
+                Json(V1ErrorResponse::not_found("Group not found")),
+            )
+                .into_response();
+        }
+        Err(e) => {
+            tracing::error!("Failed to get group: {:?}", e);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(V1ErrorResponse::internal_error("Failed to get group")),
+            )
+                .into_response();
+        }
+    };
+
+    archive_response(&state, storage.as_ref().map(|e| &e.0), &group).await
+}
+
+/// Download a shared group as a ZIP file.
+///
+/// Requires a share link with `download` permission. Password-protected
+/// links take the password in the `X-Share-Password` header, or an unlock
+/// token in the `X-Share-Unlock` header or the link's unlock cookie, so that
+/// browsers can download with a plain link once the share page is unlocked.
+#[utoipa::path(
+    get,
+    path = "/v1/public/share/{token}/archive.zip",
+    tag = "public",
+    params(
+        ("token" = String, Path, description = "Share link token"),
+        ("X-Share-Password" = Option<String>, Header, description = "Password, for password-protected links"),
+        ("X-Share-Unlock" = Option<String>, Header, description = "Unlock token, in place of the password")
+    ),
+    responses(
+        (status = 200, description = "ZIP archive of the group's contents", content_type = "application/zip"),
+        (status = 401, description = "Unauthorized - password required or invalid", body = V1ErrorResponse),
+        (status = 403, description = "Share link does not allow downloads", body = V1ErrorResponse),
+        (status = 404, description = "Share link not found", body = V1ErrorResponse),
+        (status = 410, description = "Share link is no longer active", body = V1ErrorResponse),
+        (status = 429, description = "Too many wrong passwords", body = V1ErrorResponse),
+        (status = 500, description = "Internal server error", body = V1ErrorResponse),
+        (status = 502, description = "Storage backend not configured", body = V1ErrorResponse),
+    )
+)]
+pub async fn v1_public_share_archive_zip<S, U>(
+    State(state): State<AppState<S, U>>,
+    storage: Option<axum::Extension<StorageRouter>>,
+    Extension(config): Extension<Config>,
+    client_ip: ClientIp,
+    Path(token): Path<String>,
+    headers: HeaderMap,
+) -> impl IntoResponse
+where
+    S: SqlStorage,
+    U: UserStorage,
+{
+    let (group, share_link) = match state.sql_storage.groups_get_by_share_token(&token).await {
+        Ok(Some((group, share_link, _))) => (group, share_link),
+        Ok(None) => {
+            return (
+                StatusCode::NOT_FOUND,
+                Json(V1ErrorResponse::not_found("Share link not found")),
+            )
+                .into_response();
+        }
+        Err(e) => {
+            tracing::error!("Failed to get group by share token: {:?}", e);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(V1ErrorResponse::internal_error("Failed to get share")),
+            )
+                .into_response();
+        }
+    };
+
+    // Validate share link
+    if let Err((status, message)) = validate_share_link(&share_link) {
+        return (status, Json(V1ErrorResponse::bad_request(message))).into_response();
+    }
+
+    // Verify password if required
+    let password = headers
+        .get(SHARE_PASSWORD_HEADER)
+        .and_then(|value| value.to_str().ok());
+    let unlock = match check_share_link_password(
+        &state.sql_storage,
+        &config,
+        &share_link,
+        password,
+        unlock_token_from_headers(&headers, &token),
+        client_ip,
+    )
+    .await
+    {
+        Ok(unlock) => unlock,
+        Err(e) => return e.into_response(),
+    };
+
+    if share_link.permission != "download" {
+        return (
+            StatusCode::FORBIDDEN,
+            Json(V1ErrorResponse {
+                error: "forbidden".to_owned(),
+                message: "This share link does not allow downloads".to_owned(),
+            }),
+        )
+            .into_response();
+    }
+
+    // Increment access count
+    if let Err(e) = state
+        .sql_storage
+        .share_links_increment_access(share_link.id)
+        .await
+    {
+        tracing::warn!("Failed to increment share link access count: {:?}", e);
+        // Continue anyway - access count is best-effort
+    }
+    let password_supplied = headers.contains_key(SHARE_PASSWORD_HEADER);
+    record_access(
+        &state.sql_storage,
+        &share_link,
+        client_ip,
+        &headers,
+        password_supplied,
+    )
+    .await;
+
+    let mut response = archive_response(&state, storage.as_ref().map(|e| &e.0), &group).await;
+    if let Some(unlock) = &unlock {
+        set_unlock_cookie(&mut response, &share_link, unlock, &config);
+    }
+    response
+}
+
+/// One file of an archive, with everything needed to write it.
+pub(crate) struct ArchiveEntry {
+    pub(crate) name: String,
+    pub(crate) size: u64,
+    pub(crate) modified: DateTime<Utc>,
+    pub(crate) source: ArchiveSource,
+}
+
+pub(crate) enum ArchiveSource {
+    Text(Bytes),
+    File {
+        storage: BackendFileStorage,
+        key: String,
+    },
+}
+
+/// Plan the archive of `group` and start streaming it.
+///
+/// Every content is looked up, and every file's size read, before the
+/// response starts, so missing storage is still reported with a status code.
+async fn archive_response<S, U>(
+    state: &AppState<S, U>,
+    storage: Option<&StorageRouter>,
+    group: &ContentGroupRow,
+) -> Response
+where
+    S: SqlStorage,
+    U: UserStorage,
+{
+    let items = match state.sql_storage.group_items_list(group.id).await {
+        Ok(items) => items,
+        Err(e) => {
+            tracing::error!("Failed to list group contents: {:?}", e);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(V1ErrorResponse::internal_error(
+                    "Failed to list group contents",
+                )),
+            )
+                .into_response();
+        }
+    };
+
+    let mut names = HashSet::new();
+    let mut entries = Vec::with_capacity(items.len());
+    for item in items {
+        let content = match state.sql_storage.contents_get(item.content_id).await {
+            Ok(Some(content)) if content.user_id == group.user_id => content,
+            Ok(_) => continue,
+            Err(e) => {
+                tracing::error!("Failed to get content: {:?}", e);
+                return (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(V1ErrorResponse::internal_error("Failed to get content")),
+                )
+                    .into_response();
+            }
+        };
+        if content.status == "trashed" {
+            continue;
+        }
+
+        let name = unique_name(download_filename(&content), &mut names);
+        if content.kind == "text" {
+            let body = Bytes::from(content.body.unwrap_or_default());
+            entries.push(ArchiveEntry {
+                name,
+                size: body.len() as u64,
+                modified: content.updated_at,
+                source: ArchiveSource::Text(body),
+            });
+            continue;
+        }
+
+        let Some(target) = storage
+            .and_then(|router| router.resolve(&content.storage_backend, &content.storage_profile))
+        else {
+            return (
+                StatusCode::BAD_GATEWAY,
+                Json(V1ErrorResponse::internal_error(format!(
+                    "Storage backend '{}' (profile '{}') is not configured",
+                    content.storage_backend, content.storage_profile
+                ))),
+            )
+                .into_response();
+        };
+        let size = match target
+            .file_storage()
+            .get_file_metadata(&content.storage_key)
+            .await
+        {
+            Ok(Some(metadata)) => metadata.size,
+            Ok(None) => {
+                tracing::warn!(
+                    content_id = %content.id,
+                    "Content data not found in storage, leaving it out of the archive"
+                );
+                continue;
+            }
+            Err(e) => {
+                tracing::error!("Failed to read storage object metadata: {:?}", e);
+                return (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(V1ErrorResponse::internal_error("Failed to read content")),
+                )
+                    .into_response();
+            }
+        };
+        entries.push(ArchiveEntry {
+            name,
+            size,
+            modified: content.updated_at,
+            source: ArchiveSource::File {
+                storage: target.file_storage().clone(),
+                key: content.storage_key,
+            },
+        });
+    }
+
+    let filename = format!("{}.zip", group.name.trim().replace(['/', '\\'], "_"));
+    stream_archive(entries, &filename)
+}
+
+/// Respond with `entries` as a ZIP attachment named `filename`, written by a
+/// background task while the response is sent.
+pub(crate) fn stream_archive(entries: Vec<ArchiveEntry>, filename: &str) -> Response {
+    let (tx, rx) = mpsc::channel(ARCHIVE_CHANNEL_CAPACITY);
+    let archive = filename.to_owned();
+    tokio::spawn(async move {
+        if let Err(e) = write_archive(entries, &tx).await {
+            tracing::warn!(%archive, "Archive download aborted: {}", e);
+            let _ = tx.send(Err(e)).await;
+        }
+    });
+    let body = Body::from_stream(futures_util::stream::unfold(rx, |mut rx| async move {
+        rx.recv().await.map(|chunk| (chunk, rx))
+    }));
+
+    let disposition = ContentDisposition::Attachment.header_value(filename);
+    let mut headers = HeaderMap::new();
+    headers.insert(
+        header::CONTENT_TYPE,
+        HeaderValue::from_static("application/zip"),
+    );
+    headers.insert(
+        header::CONTENT_DISPOSITION,
+        HeaderValue::from_str(&disposition).unwrap_or(HeaderValue::from_static(
+            "attachment; filename=\"archive.zip\"",
+        )),
+    );
+    headers.insert(
+        header::X_CONTENT_TYPE_OPTIONS,
+        HeaderValue::from_static("nosniff"),
+    );
+    (StatusCode::OK, headers, body).into_response()
+}
+
+/// Write `entries` as a ZIP archive into `tx`, one chunk at a time.
+///
+/// Stops with an error if the client goes away or storage fails; the
+/// response then ends without a central directory, which clients reject.
+async fn write_archive(
+    entries: Vec<ArchiveEntry>,
+    tx: &mpsc::Sender<io::Result<Bytes>>,
+) -> io::Result<()> {
+    let send = |chunk: Bytes| async move {
+        tx.send(Ok(chunk))
+            .await
+            .map_err(|_| io::Error::new(io::ErrorKind::BrokenPipe, "client disconnected"))
+    };
+
+    let mut zip = ZipWriter::new();
+    for entry in entries {
+        send(zip.start_entry(&entry.name, entry.size, entry.modified)).await?;
+        match entry.source {
+            ArchiveSource::Text(body) => {
+                zip.write(&body);
+                send(body).await?;
+            }
+            ArchiveSource::File { storage, key } => {
+                let mut chunks = std::pin::pin!(stream_range(storage, key, 0..entry.size));
+                while let Some(chunk) = chunks.try_next().await.map_err(io::Error::other)? {
+                    zip.write(&chunk);
+                    send(chunk).await?;
+                }
+            }
+        }
+        send(zip.finish_entry()?).await?;
+    }
+    send(zip.finish()).await
+}
+
+/// `name`, or `name (2)`, `name (3)`... with the extension kept, whichever
+/// is not in `used` yet, compared case-insensitively.
+fn unique_name(name: String, used: &mut HashSet<String>) -> String {
+    if used.insert(name.to_lowercase()) {
+        return name;
+    }
+    let (stem, extension) = match name.rsplit_once('.') {
+        Some((stem, extension)) if !stem.is_empty() => (stem, format!(".{extension}")),
+        _ => (name.as_str(), String::new()),
+    };
+    (2..)
+        .map(|n| format!("{stem} ({n}){extension}"))
+        .find(|candidate| used.insert(candidate.to_lowercase()))
+        .expect("unbounded counter")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unique_name() {
+        let mut used = HashSet::new();
+        assert_eq!(unique_name("notes.md".to_owned(), &mut used), "notes.md");
+        assert_eq!(
+            unique_name("Notes.md".to_owned(), &mut used),
+            "Notes (2).md"
+        );
+        assert_eq!(
+            unique_name("notes.md".to_owned(), &mut used),
+            "notes (3).md"
+        );
+        assert_eq!(unique_name(".env".to_owned(), &mut used), ".env");
+        assert_eq!(unique_name(".env".to_owned(), &mut used), ".env (2)");
+    }
+}
@@ -1,5 +1,6 @@
 //! /v1/contents/:id/tags endpoint handlers.
 
+use crate::audit::{self, ClientIp};
 use crate::database::SqlStorage;
 use crate::users::routes::AppState;
 use crate::users::session_auth::RequireAuth;
@@ -147,6 +148,7 @@ where
 pub async fn v1_content_tags_attach<S, U>(
     State(state): State<AppState<S, U>>,
     auth: RequireAuth,
+    client_ip: ClientIp,
     Path(id): Path<String>,
     Json(request): Json<V1ContentTagsAttachRequest>,
 ) -> impl IntoResponse
@@ -231,7 +233,18 @@ where
         .content_tags_attach(content_id, tag_id)
         .await
     {
-        Ok(()) => StatusCode::NO_CONTENT.into_response(),
+        Ok(()) => {
+            audit::record(
+                &state.sql_storage,
+                Some(user.id),
+                "content.tag_attach",
+                Some(content_id),
+                Some(serde_json::json!({ "tag_id": tag_id })),
+                client_ip,
+            )
+            .await;
+            StatusCode::NO_CONTENT.into_response()
+        }
         Err(e) => {
             tracing::error!("Failed to attach tag: {:?}", e);
             (
@@ -266,6 +279,7 @@ where
 pub async fn v1_content_tags_detach<S, U>(
     State(state): State<AppState<S, U>>,
     auth: RequireAuth,
+    client_ip: ClientIp,
     Path((id, tag_id_str)): Path<(String, String)>,
 ) -> impl IntoResponse
 where
@@ -349,7 +363,18 @@ where
         .content_tags_detach(content_id, tag_id)
         .await
     {
-        Ok(true) => StatusCode::NO_CONTENT.into_response(),
+        Ok(true) => {
+            audit::record(
+                &state.sql_storage,
+                Some(user.id),
+                "content.tag_detach",
+                Some(content_id),
+                Some(serde_json::json!({ "tag_id": tag_id })),
+                client_ip,
+            )
+            .await;
+            StatusCode::NO_CONTENT.into_response()
+        }
         Ok(false) => (
             StatusCode::NOT_FOUND,
             Json(V1ErrorResponse::not_found("Tag not attached to content")),
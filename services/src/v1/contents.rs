@@ -1,29 +1,47 @@
 //! /v1/contents endpoint handlers.
 
+use base64::Engine as _;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+
+use crate::audit::{self, ClientIp};
+use crate::config::Config;
 use crate::database::{
-    ContentStatus, ContentsInsert, ContentsListParams, ContentsUpdate, SqlStorage, SqlStorageError,
+    ContentStatus, ContentsBatchOp, ContentsCursor, ContentsInsert, ContentsListParams,
+    ContentsSort, ContentsUpdate, SqlStorage, SqlStorageError,
+};
+use crate::storage::{
+    ContentDisposition, DEFAULT_PRESIGN_EXPIRY, FileStorage, StorageRouter, THUMBNAIL_SIZES,
+    ThumbnailFormat, thumbnail_key,
 };
-use crate::storage::{ContentDisposition, DEFAULT_PRESIGN_EXPIRY, R2Presigner};
 use crate::users::routes::AppState;
 use crate::users::session_auth::RequireAuth;
 use crate::users::storage::UserStorage;
 use axum::{
-    Json,
+    Extension, Json,
     extract::{Path, Query, State},
-    http::StatusCode,
+    http::{HeaderMap, StatusCode, header},
     response::IntoResponse,
 };
 
+use super::etag;
+use super::shares::{ContentAccess, content_access};
 use super::types::{
-    V1ContentCreateRequest, V1ContentCreateResponse, V1ContentItem, V1ContentsListQuery,
-    V1ContentsListResponse, V1ContentsUpdateRequest, V1ErrorResponse, V1ViewUrlRequest,
-    V1ViewUrlResponse, parse_visibility,
+    V1ContentCreateRequest, V1ContentCreateResponse, V1ContentItem, V1ContentsBatchItemResult,
+    V1ContentsBatchRequest, V1ContentsBatchResponse, V1ContentsListQuery, V1ContentsListResponse,
+    V1ContentsUpdateRequest, V1ErrorResponse, V1ViewUrlQuery, V1ViewUrlRequest, V1ViewUrlResponse,
+    parse_visibility,
 };
 
 /// Maximum allowed body size for text content (64KB).
 const MAX_TEXT_BODY_SIZE: usize = 64 * 1024;
 
+/// Maximum number of content IDs in one batch request.
+const MAX_BATCH_SIZE: usize = 500;
+
 /// List contents for the authenticated user.
+///
+/// Pages are keyset-based: pass `next_cursor` back as `cursor` (with the same
+/// `sort`) to continue, which stays stable while new contents arrive.
 #[utoipa::path(
     get,
     path = "/v1/contents",
@@ -31,6 +49,7 @@ const MAX_TEXT_BODY_SIZE: usize = 64 * 1024;
     params(V1ContentsListQuery),
     responses(
         (status = 200, description = "List of contents", body = V1ContentsListResponse),
+        (status = 400, description = "Invalid filter, sort or cursor", body = V1ErrorResponse),
         (status = 401, description = "Unauthorized", body = V1ErrorResponse),
         (status = 500, description = "Internal server error", body = V1ErrorResponse),
     ),
@@ -78,23 +97,45 @@ where
     let limit = query.limit.unwrap_or(50).clamp(1, 100);
     let offset = query.offset.unwrap_or(0).max(0);
 
-    let params = ContentsListParams {
-        limit,
-        offset,
-        status,
+    let params = match list_params(query, status) {
+        Ok(params) => ContentsListParams {
+            // One extra row tells whether there is a next page
+            limit: limit + 1,
+            offset,
+            ..params
+        },
+        Err(msg) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(V1ErrorResponse::bad_request(msg)),
+            )
+                .into_response();
+        }
     };
+    let sort = params.sort;
 
     match state
         .sql_storage
         .contents_list_for_user(user.id, params)
         .await
     {
-        Ok(rows) => {
+        Ok(mut rows) => {
+            let next_cursor = if rows.len() as i64 > limit {
+                rows.truncate(limit as usize);
+                rows.last()
+                    .map(|row| encode_cursor(sort, &sort.cursor(row)))
+            } else {
+                None
+            };
             let items: Vec<V1ContentItem> = rows.into_iter().map(V1ContentItem::from).collect();
             let total = items.len();
             (
                 StatusCode::OK,
-                Json(V1ContentsListResponse { items, total }),
+                Json(V1ContentsListResponse {
+                    items,
+                    total,
+                    next_cursor,
+                }),
             )
                 .into_response()
         }
@@ -109,6 +150,82 @@ where
     }
 }
 
+/// Validate the filters, sort and cursor of a list query.
+fn list_params(
+    query: V1ContentsListQuery,
+    status: Option<ContentStatus>,
+) -> Result<ContentsListParams, String> {
+    if let Some(kind) = query.kind.as_deref()
+        && kind != "file"
+        && kind != "text"
+    {
+        return Err("kind must be file or text".to_owned());
+    }
+    let visibility = match query.visibility.as_deref() {
+        Some(v) => Some(
+            parse_visibility(v)
+                .ok_or("visibility must be private, public or restricted".to_owned())?,
+        ),
+        None => None,
+    };
+    let tag_ids = query
+        .tags
+        .as_deref()
+        .unwrap_or_default()
+        .split(',')
+        .map(str::trim)
+        .filter(|id| !id.is_empty())
+        .map(uuid::Uuid::parse_str)
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|_| "Invalid tag ID format".to_owned())?;
+    let sort = match query.sort.as_deref() {
+        Some(sort) => ContentsSort::parse(sort).ok_or(format!("Unknown sort: {sort}"))?,
+        None => ContentsSort::default(),
+    };
+    let after = match query.cursor.as_deref() {
+        Some(cursor) => Some(decode_cursor(sort, cursor).ok_or("Invalid cursor".to_owned())?),
+        None => None,
+    };
+
+    Ok(ContentsListParams {
+        status,
+        kind: query.kind,
+        content_type_prefix: query.content_type.filter(|prefix| !prefix.is_empty()),
+        visibility,
+        tag_ids,
+        created_after: query.created_after,
+        created_before: query.created_before,
+        updated_after: query.updated_after,
+        updated_before: query.updated_before,
+        sort,
+        after,
+        ..Default::default()
+    })
+}
+
+/// Encode a keyset position as an opaque, URL-safe page cursor.
+fn encode_cursor(sort: ContentsSort, cursor: &ContentsCursor) -> String {
+    let json = serde_json::json!({
+        "sort": sort.as_str(),
+        "value": cursor.value,
+        "id": cursor.id,
+    });
+    URL_SAFE_NO_PAD.encode(json.to_string())
+}
+
+/// Decode a page cursor, `None` if it is malformed or from another sort order.
+fn decode_cursor(sort: ContentsSort, cursor: &str) -> Option<ContentsCursor> {
+    let json: serde_json::Value =
+        serde_json::from_slice(&URL_SAFE_NO_PAD.decode(cursor).ok()?).ok()?;
+    if json["sort"].as_str()? != sort.as_str() {
+        return None;
+    }
+    Some(ContentsCursor {
+        value: json["value"].as_str()?.to_owned(),
+        id: json["id"].as_str()?.parse().ok()?,
+    })
+}
+
 /// Create text content directly (without upload).
 ///
 /// This endpoint creates text content that is stored inline in the database
@@ -131,6 +248,7 @@ where
 pub async fn v1_contents_create<S, U>(
     State(state): State<AppState<S, U>>,
     auth: RequireAuth,
+    client_ip: ClientIp,
     Json(payload): Json<V1ContentCreateRequest>,
 ) -> impl IntoResponse
 where
@@ -209,16 +327,28 @@ where
         visibility,
         kind: Some("text".to_owned()),
         body: Some(payload.body),
+        sha256: None,
     };
 
     match state.sql_storage.contents_insert(content_input).await {
-        Ok(content) => (
-            StatusCode::CREATED,
-            Json(V1ContentCreateResponse {
-                content: V1ContentItem::from(content),
-            }),
-        )
-            .into_response(),
+        Ok(content) => {
+            audit::record(
+                &state.sql_storage,
+                Some(user.id),
+                "content.create",
+                Some(content.id),
+                None,
+                client_ip,
+            )
+            .await;
+            (
+                StatusCode::CREATED,
+                Json(V1ContentCreateResponse {
+                    content: V1ContentItem::from(content),
+                }),
+            )
+                .into_response()
+        }
         Err(e) => {
             tracing::error!("Failed to create content: {:?}", e);
             (
@@ -231,6 +361,10 @@ where
 }
 
 /// Get a specific content item by ID.
+///
+/// Contents other users shared with the current user can be read as well.
+/// The `ETag` header identifies this version of the content, for use with
+/// `If-Match` on PATCH.
 #[utoipa::path(
     get,
     path = "/v1/contents/{id}",
@@ -292,15 +426,31 @@ where
 
     match state.sql_storage.contents_get(content_id).await {
         Ok(Some(row)) => {
-            // Verify ownership
-            if row.user_id != user.id {
-                return (
-                    StatusCode::NOT_FOUND,
-                    Json(V1ErrorResponse::not_found("Content not found")),
-                )
-                    .into_response();
+            // Verify ownership or a direct share
+            match content_access(&state.sql_storage, &row, user.id).await {
+                Ok(Some(_)) => {}
+                Ok(None) => {
+                    return (
+                        StatusCode::NOT_FOUND,
+                        Json(V1ErrorResponse::not_found("Content not found")),
+                    )
+                        .into_response();
+                }
+                Err(e) => {
+                    tracing::error!("Failed to check content access: {:?}", e);
+                    return (
+                        StatusCode::INTERNAL_SERVER_ERROR,
+                        Json(V1ErrorResponse::internal_error("Failed to get content")),
+                    )
+                        .into_response();
+                }
             }
-            (StatusCode::OK, Json(V1ContentItem::from(row))).into_response()
+            (
+                StatusCode::OK,
+                [(header::ETAG, etag::etag(row.updated_at))],
+                Json(V1ContentItem::from(row)),
+            )
+                .into_response()
         }
         Ok(None) => (
             StatusCode::NOT_FOUND,
@@ -319,12 +469,19 @@ where
 }
 
 /// Update content metadata.
+///
+/// For text contents, the title, description and body being replaced are kept
+/// as a revision (see `/v1/contents/{id}/revisions`).
+///
+/// With `If-Match`, the update only applies while the content is unchanged;
+/// otherwise it fails with 412.
 #[utoipa::path(
     patch,
     path = "/v1/contents/{id}",
     tag = "contents",
     params(
-        ("id" = String, Path, description = "Content ID (UUID)")
+        ("id" = String, Path, description = "Content ID (UUID)"),
+        ("If-Match" = Option<String>, Header, description = "ETag from a previous GET")
     ),
     request_body = V1ContentsUpdateRequest,
     responses(
@@ -333,6 +490,7 @@ where
         (status = 401, description = "Unauthorized", body = V1ErrorResponse),
         (status = 403, description = "Forbidden", body = V1ErrorResponse),
         (status = 404, description = "Content not found", body = V1ErrorResponse),
+        (status = 412, description = "Content changed since it was fetched", body = V1ErrorResponse),
         (status = 500, description = "Internal server error", body = V1ErrorResponse),
     ),
     security(
@@ -341,8 +499,11 @@ where
 )]
 pub async fn v1_contents_update<S, U>(
     State(state): State<AppState<S, U>>,
+    Extension(config): Extension<Config>,
     auth: RequireAuth,
+    client_ip: ClientIp,
     Path(id): Path<String>,
+    headers: HeaderMap,
     Json(payload): Json<V1ContentsUpdateRequest>,
 ) -> impl IntoResponse
 where
@@ -413,11 +574,41 @@ where
             .into_response();
     }
 
+    // Honor If-Match against the content's current version
+    let expected_updated_at = match etag::if_match(&headers) {
+        None => None,
+        Some(if_match) => match state.sql_storage.contents_get(content_id).await {
+            Ok(Some(row)) if row.user_id == user.id => {
+                if !etag::matches(if_match, row.updated_at) {
+                    return etag::precondition_failed();
+                }
+                Some(row.updated_at)
+            }
+            Ok(_) => {
+                return (
+                    StatusCode::NOT_FOUND,
+                    Json(V1ErrorResponse::not_found("Content not found")),
+                )
+                    .into_response();
+            }
+            Err(e) => {
+                tracing::error!("Failed to get content: {:?}", e);
+                return (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(V1ErrorResponse::internal_error("Failed to get content")),
+                )
+                    .into_response();
+            }
+        },
+    };
+
     let changes = ContentsUpdate {
         title: payload.title,
         description: payload.description,
         visibility,
         body: payload.body,
+        max_revisions: config.content_revisions_max(),
+        expected_updated_at,
     };
 
     match state
@@ -425,7 +616,24 @@ where
         .contents_update_metadata(content_id, user.id, changes)
         .await
     {
-        Ok(Some(row)) => (StatusCode::OK, Json(V1ContentItem::from(row))).into_response(),
+        Ok(Some(row)) => {
+            audit::record(
+                &state.sql_storage,
+                Some(user.id),
+                "content.update",
+                Some(row.id),
+                None,
+                client_ip,
+            )
+            .await;
+            (
+                StatusCode::OK,
+                [(header::ETAG, etag::etag(row.updated_at))],
+                Json(V1ContentItem::from(row)),
+            )
+                .into_response()
+        }
+        Ok(None) if expected_updated_at.is_some() => etag::precondition_failed(),
         Ok(None) => (
             StatusCode::NOT_FOUND,
             Json(V1ErrorResponse::not_found("Content not found")),
@@ -473,13 +681,22 @@ where
 pub async fn v1_contents_trash<S, U>(
     State(state): State<AppState<S, U>>,
     auth: RequireAuth,
+    client_ip: ClientIp,
     Path(id): Path<String>,
 ) -> impl IntoResponse
 where
     S: SqlStorage,
     U: UserStorage,
 {
-    v1_contents_set_status::<S, U>(state, auth, id, ContentStatus::Trashed).await
+    v1_contents_set_status::<S, U>(
+        state,
+        auth,
+        client_ip,
+        id,
+        ContentStatus::Trashed,
+        "content.trash",
+    )
+    .await
 }
 
 /// Restore content from trash.
@@ -505,13 +722,22 @@ where
 pub async fn v1_contents_restore<S, U>(
     State(state): State<AppState<S, U>>,
     auth: RequireAuth,
+    client_ip: ClientIp,
     Path(id): Path<String>,
 ) -> impl IntoResponse
 where
     S: SqlStorage,
     U: UserStorage,
 {
-    v1_contents_set_status::<S, U>(state, auth, id, ContentStatus::Active).await
+    v1_contents_set_status::<S, U>(
+        state,
+        auth,
+        client_ip,
+        id,
+        ContentStatus::Active,
+        "content.restore",
+    )
+    .await
 }
 
 /// Archive content.
@@ -537,13 +763,22 @@ where
 pub async fn v1_contents_archive<S, U>(
     State(state): State<AppState<S, U>>,
     auth: RequireAuth,
+    client_ip: ClientIp,
     Path(id): Path<String>,
 ) -> impl IntoResponse
 where
     S: SqlStorage,
     U: UserStorage,
 {
-    v1_contents_set_status::<S, U>(state, auth, id, ContentStatus::Archived).await
+    v1_contents_set_status::<S, U>(
+        state,
+        auth,
+        client_ip,
+        id,
+        ContentStatus::Archived,
+        "content.archive",
+    )
+    .await
 }
 
 /// Unarchive content (restore to active).
@@ -569,21 +804,32 @@ where
 pub async fn v1_contents_unarchive<S, U>(
     State(state): State<AppState<S, U>>,
     auth: RequireAuth,
+    client_ip: ClientIp,
     Path(id): Path<String>,
 ) -> impl IntoResponse
 where
     S: SqlStorage,
     U: UserStorage,
 {
-    v1_contents_set_status::<S, U>(state, auth, id, ContentStatus::Active).await
+    v1_contents_set_status::<S, U>(
+        state,
+        auth,
+        client_ip,
+        id,
+        ContentStatus::Active,
+        "content.unarchive",
+    )
+    .await
 }
 
 /// Helper function to set content status.
 async fn v1_contents_set_status<S, U>(
     state: AppState<S, U>,
     auth: RequireAuth,
+    client_ip: ClientIp,
     id: String,
     new_status: ContentStatus,
+    action: &str,
 ) -> impl IntoResponse
 where
     S: SqlStorage,
@@ -628,7 +874,18 @@ where
         .contents_set_status(content_id, user.id, new_status, now)
         .await
     {
-        Ok(Some(row)) => (StatusCode::OK, Json(V1ContentItem::from(row))).into_response(),
+        Ok(Some(row)) => {
+            audit::record(
+                &state.sql_storage,
+                Some(user.id),
+                action,
+                Some(row.id),
+                None,
+                client_ip,
+            )
+            .await;
+            (StatusCode::OK, Json(V1ContentItem::from(row))).into_response()
+        }
         Ok(None) => (
             StatusCode::NOT_FOUND,
             Json(V1ErrorResponse::not_found("Content not found")),
@@ -655,16 +912,188 @@ where
     }
 }
 
+/// Apply one operation to many contents in a single transaction.
+///
+/// IDs that are malformed, missing or owned by someone else are reported per
+/// item and skipped; the operation is applied to all the others.
+#[utoipa::path(
+    post,
+    path = "/v1/contents/batch",
+    tag = "contents",
+    request_body = V1ContentsBatchRequest,
+    responses(
+        (status = 200, description = "Per-item results", body = V1ContentsBatchResponse),
+        (status = 400, description = "Invalid operation or too many IDs", body = V1ErrorResponse),
+        (status = 401, description = "Unauthorized", body = V1ErrorResponse),
+        (status = 404, description = "Tag or group not found", body = V1ErrorResponse),
+        (status = 500, description = "Internal server error", body = V1ErrorResponse),
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn v1_contents_batch<S, U>(
+    State(state): State<AppState<S, U>>,
+    auth: RequireAuth,
+    client_ip: ClientIp,
+    Json(payload): Json<V1ContentsBatchRequest>,
+) -> impl IntoResponse
+where
+    S: SqlStorage,
+    U: UserStorage,
+{
+    // Get user ID from username
+    let user = match state.user_storage.get_user(auth.username()).await {
+        Ok(Some(user)) => user,
+        Ok(None) => {
+            return (
+                StatusCode::UNAUTHORIZED,
+                Json(V1ErrorResponse::not_found("User not found")),
+            )
+                .into_response();
+        }
+        Err(e) => {
+            tracing::error!("Failed to get user: {}", e);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(V1ErrorResponse::internal_error("Failed to get user")),
+            )
+                .into_response();
+        }
+    };
+
+    if payload.ids.is_empty() || payload.ids.len() > MAX_BATCH_SIZE {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(V1ErrorResponse::bad_request(format!(
+                "ids must contain 1 to {MAX_BATCH_SIZE} entries"
+            ))),
+        )
+            .into_response();
+    }
+
+    let op = match batch_op(&payload) {
+        Ok(op) => op,
+        Err(message) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(V1ErrorResponse::bad_request(message)),
+            )
+                .into_response();
+        }
+    };
+
+    let mut ids: Vec<uuid::Uuid> = Vec::with_capacity(payload.ids.len());
+    for id in payload.ids.iter().filter_map(|id| id.parse().ok()) {
+        if !ids.contains(&id) {
+            ids.push(id);
+        }
+    }
+
+    let applied = match state
+        .sql_storage
+        .contents_batch(user.id, &ids, op, chrono::Utc::now())
+        .await
+    {
+        Ok(applied) => applied,
+        Err(SqlStorageError::NotFound) => {
+            let target = if payload.op == "tag" { "Tag" } else { "Group" };
+            return (
+                StatusCode::NOT_FOUND,
+                Json(V1ErrorResponse::not_found(format!("{target} not found"))),
+            )
+                .into_response();
+        }
+        Err(e) => {
+            tracing::error!("Failed to apply batch operation: {:?}", e);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(V1ErrorResponse::internal_error(
+                    "Failed to apply batch operation",
+                )),
+            )
+                .into_response();
+        }
+    };
+
+    if !applied.is_empty() {
+        audit::record(
+            &state.sql_storage,
+            Some(user.id),
+            "content.batch",
+            None,
+            Some(serde_json::json!({ "op": payload.op, "ids": applied })),
+            client_ip,
+        )
+        .await;
+    }
+
+    let results: Vec<V1ContentsBatchItemResult> = payload
+        .ids
+        .into_iter()
+        .map(|id| {
+            let status = match id.parse::<uuid::Uuid>() {
+                Ok(uuid) if applied.contains(&uuid) => "ok",
+                Ok(_) => "not_found",
+                Err(_) => "invalid_id",
+            };
+            V1ContentsBatchItemResult {
+                id,
+                status: status.to_owned(),
+            }
+        })
+        .collect();
+    let succeeded = results.iter().filter(|r| r.status == "ok").count();
+    let failed = results.len() - succeeded;
+
+    (
+        StatusCode::OK,
+        Json(V1ContentsBatchResponse {
+            results,
+            succeeded,
+            failed,
+        }),
+    )
+        .into_response()
+}
+
+/// Build the storage operation for a batch request.
+fn batch_op(payload: &V1ContentsBatchRequest) -> Result<ContentsBatchOp, String> {
+    let target = |id: Option<&str>, name: &str| {
+        id.ok_or(format!("{name}_id is required for {}", payload.op))?
+            .parse::<uuid::Uuid>()
+            .map_err(|_| format!("Invalid {name} ID format"))
+    };
+    match payload.op.as_str() {
+        "trash" => Ok(ContentsBatchOp::SetStatus(ContentStatus::Trashed)),
+        "restore" => Ok(ContentsBatchOp::SetStatus(ContentStatus::Active)),
+        "archive" => Ok(ContentsBatchOp::SetStatus(ContentStatus::Archived)),
+        "tag" => Ok(ContentsBatchOp::AttachTag(target(
+            payload.tag_id.as_deref(),
+            "tag",
+        )?)),
+        "add_to_group" => Ok(ContentsBatchOp::AddToGroup(target(
+            payload.group_id.as_deref(),
+            "group",
+        )?)),
+        _ => Err("op must be trash, restore, archive, tag or add_to_group".to_owned()),
+    }
+}
+
 /// Get a view URL for content.
 ///
 /// This endpoint generates a presigned GET URL for viewing/downloading content
-/// from R2 storage. The URL is valid for 15 minutes by default.
+/// from the storage backend it was uploaded to. The URL is valid for 15 minutes by default.
+/// With `size`, the URL points to a thumbnail of an image content instead.
+/// For a content shared with the current user at "view" permission, the URL
+/// is always for inline viewing.
 #[utoipa::path(
     post,
     path = "/v1/contents/{id}/view-url",
     tag = "contents",
     params(
-        ("id" = String, Path, description = "Content ID (UUID)")
+        ("id" = String, Path, description = "Content ID (UUID)"),
+        V1ViewUrlQuery
     ),
     request_body = V1ViewUrlRequest,
     responses(
@@ -680,9 +1109,10 @@ where
 )]
 pub async fn v1_contents_view_url<S, U>(
     State(state): State<AppState<S, U>>,
-    presigner: Option<axum::Extension<R2Presigner>>,
+    storage: Option<axum::Extension<StorageRouter>>,
     auth: RequireAuth,
     Path(id): Path<String>,
+    Query(query): Query<V1ViewUrlQuery>,
     Json(payload): Json<V1ViewUrlRequest>,
 ) -> impl IntoResponse
 where
@@ -741,14 +1171,25 @@ where
         }
     };
 
-    // Verify ownership
-    if content.user_id != user.id {
-        return (
-            StatusCode::NOT_FOUND,
-            Json(V1ErrorResponse::not_found("Content not found")),
-        )
-            .into_response();
-    }
+    // Verify ownership or a direct share
+    let access = match content_access(&state.sql_storage, &content, user.id).await {
+        Ok(Some(access)) => access,
+        Ok(None) => {
+            return (
+                StatusCode::NOT_FOUND,
+                Json(V1ErrorResponse::not_found("Content not found")),
+            )
+                .into_response();
+        }
+        Err(e) => {
+            tracing::error!("Failed to check content access: {:?}", e);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(V1ErrorResponse::internal_error("Failed to get content")),
+            )
+                .into_response();
+        }
+    };
 
     // Parse disposition
     let disposition = match ContentDisposition::try_from(payload.disposition.as_str()) {
@@ -764,10 +1205,60 @@ where
         }
     };
 
+    // A content shared with "view" permission can only be viewed inline
+    let disposition = if access == ContentAccess::View {
+        ContentDisposition::Inline
+    } else {
+        disposition
+    };
+
+    // Thumbnails exist only for the sizes and image types generated on upload
+    let thumbnail_format = match query.size {
+        Some(size) if !THUMBNAIL_SIZES.contains(&size) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(V1ErrorResponse::bad_request(format!(
+                    "Invalid size. Must be one of {THUMBNAIL_SIZES:?}"
+                ))),
+            )
+                .into_response();
+        }
+        Some(size) => match ThumbnailFormat::for_source(&content.content_type) {
+            Some(format) => Some((size, format)),
+            None => {
+                return (
+                    StatusCode::BAD_REQUEST,
+                    Json(V1ErrorResponse::bad_request(
+                        "Thumbnails are only available for JPEG, PNG, GIF and WebP images",
+                    )),
+                )
+                    .into_response();
+            }
+        },
+        None => None,
+    };
+
     // Generate presigned GET URL
-    let presigned = if let Some(axum::Extension(presigner)) = presigner {
-        match presigner
-            .presign_get(&content.storage_key, disposition, DEFAULT_PRESIGN_EXPIRY)
+    let mut thumbnail_size = None;
+    let presigned = if let Some(target) = storage.as_ref().and_then(|axum::Extension(router)| {
+        router.resolve(&content.storage_backend, &content.storage_profile)
+    }) {
+        let mut key = content.storage_key.clone();
+        if let Some((size, format)) = thumbnail_format {
+            let thumbnail = thumbnail_key(&content.storage_key, size, format);
+            // Fall back to the original while the thumbnail is being generated
+            match target.file_storage().file_exists(&thumbnail).await {
+                Ok(true) => {
+                    key = thumbnail;
+                    thumbnail_size = Some(size);
+                }
+                Ok(false) => {}
+                Err(e) => tracing::warn!("Failed to look up thumbnail: {e}"),
+            }
+        }
+        match target
+            .presigner()
+            .presign_get(&key, disposition, DEFAULT_PRESIGN_EXPIRY)
             .await
         {
             Ok(presigned) => presigned,
@@ -785,9 +1276,10 @@ where
     } else {
         return (
             StatusCode::BAD_GATEWAY,
-            Json(V1ErrorResponse::internal_error(
-                "R2 storage is not configured",
-            )),
+            Json(V1ErrorResponse::internal_error(format!(
+                "Storage backend '{}' (profile '{}') is not configured",
+                content.storage_backend, content.storage_profile
+            ))),
         )
             .into_response();
     };
@@ -797,6 +1289,7 @@ where
         Json(V1ViewUrlResponse {
             url: presigned.url,
             expires_at: presigned.expires_at.to_rfc3339(),
+            thumbnail_size,
         }),
     )
         .into_response()
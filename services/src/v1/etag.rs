@@ -0,0 +1,63 @@
+//! Entity tags and `If-Match` preconditions.
+//!
+//! A resource's ETag is derived from its `updated_at`, so it changes with
+//! every write. PATCH and DELETE handlers compare it against `If-Match` to
+//! reject edits made from a stale copy.
+
+use axum::{
+    Json,
+    http::{HeaderMap, StatusCode, header},
+    response::{IntoResponse, Response},
+};
+
+use super::types::V1ErrorResponse;
+
+/// The strong ETag of a resource last written at `updated_at`.
+pub fn etag(updated_at: chrono::DateTime<chrono::Utc>) -> String {
+    format!("\"{:x}\"", updated_at.timestamp_micros())
+}
+
+/// The `If-Match` header of a request, if any.
+pub fn if_match(headers: &HeaderMap) -> Option<&str> {
+    headers.get(header::IF_MATCH)?.to_str().ok()
+}
+
+/// Whether an `If-Match` value is satisfied by a resource last written at
+/// `updated_at`.
+///
+/// Uses strong comparison, so weak tags (`W/"..."`) never match.
+pub fn matches(if_match: &str, updated_at: chrono::DateTime<chrono::Utc>) -> bool {
+    let current = etag(updated_at);
+    if_match
+        .split(',')
+        .map(str::trim)
+        .any(|tag| tag == "*" || tag == current)
+}
+
+/// The 412 response for a failed `If-Match` precondition.
+pub fn precondition_failed() -> Response {
+    (
+        StatusCode::PRECONDITION_FAILED,
+        Json(V1ErrorResponse::precondition_failed(
+            "The resource was modified since it was fetched",
+        )),
+    )
+        .into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_matches_strong_tags_and_wildcard() {
+        let updated_at = chrono::Utc::now();
+        let current = etag(updated_at);
+
+        assert!(matches(&current, updated_at));
+        assert!(matches(&format!("\"0\", {current}"), updated_at));
+        assert!(matches("*", updated_at));
+        assert!(!matches(&format!("W/{current}"), updated_at));
+        assert!(!matches("\"0\"", updated_at));
+    }
+}
@@ -0,0 +1,495 @@
+//! /v1/exports and /v1/imports endpoint handlers.
+//!
+//! An export job snapshots the user's data into a manifest in the background;
+//! once completed, its archive is streamed on download with the files read
+//! from storage. Importing an archive recreates its data under the
+//! authenticated user.
+
+use std::io;
+
+use crate::audit::{self, ClientIp};
+use crate::database::{AccountExportRow, SqlStorage};
+use crate::export::{
+    AccountExporter, AccountImporter, EXPORT_RETENTION_DAYS, ExportManifest, ImportError,
+    MANIFEST_ENTRY, ManifestError, read_manifest,
+};
+use crate::storage::{FileStorage, StorageRouter, StorageSelectionError, ZipReader};
+use crate::users::routes::AppState;
+use crate::users::session_auth::RequireAuth;
+use crate::users::storage::UserStorage;
+use axum::{
+    Json,
+    body::{Body, Bytes},
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use futures_util::TryStreamExt as _;
+
+use super::archive::{ArchiveEntry, ArchiveSource, stream_archive};
+use super::types::{V1ErrorResponse, V1ExportItem, V1ImportResponse};
+
+/// Start exporting the authenticated user's data.
+///
+/// The export runs in the background; poll it until its status is
+/// `completed`, then download its archive. Archives hold the active and
+/// archived contents with their files, tags, groups and share link settings.
+#[utoipa::path(
+    post,
+    path = "/v1/exports",
+    tag = "exports",
+    responses(
+        (status = 202, description = "Export started", body = V1ExportItem),
+        (status = 401, description = "Unauthorized", body = V1ErrorResponse),
+        (status = 500, description = "Internal server error", body = V1ErrorResponse),
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn v1_exports_create<S, U>(
+    State(state): State<AppState<S, U>>,
+    auth: RequireAuth,
+    client_ip: ClientIp,
+) -> impl IntoResponse
+where
+    S: SqlStorage,
+    U: UserStorage,
+{
+    // Get user ID from username
+    let user = match state.user_storage.get_user(auth.username()).await {
+        Ok(Some(user)) => user,
+        Ok(None) => {
+            return (
+                StatusCode::UNAUTHORIZED,
+                Json(V1ErrorResponse::not_found("User not found")),
+            )
+                .into_response();
+        }
+        Err(e) => {
+            tracing::error!("Failed to get user: {}", e);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(V1ErrorResponse::internal_error("Failed to get user")),
+            )
+                .into_response();
+        }
+    };
+
+    let expires_at = chrono::Utc::now() + chrono::Duration::days(EXPORT_RETENTION_DAYS);
+    let export = match state
+        .sql_storage
+        .account_exports_create(user.id, expires_at)
+        .await
+    {
+        Ok(export) => export,
+        Err(e) => {
+            tracing::error!("Failed to create account export: {:?}", e);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(V1ErrorResponse::internal_error("Failed to create export")),
+            )
+                .into_response();
+        }
+    };
+
+    audit::record(
+        &state.sql_storage,
+        Some(user.id),
+        "export.create",
+        Some(export.id),
+        None,
+        client_ip,
+    )
+    .await;
+
+    let exporter = AccountExporter::new(state.sql_storage.clone());
+    let job = export.clone();
+    tokio::spawn(async move {
+        if let Err(e) = exporter.run(&job).await {
+            tracing::error!(export_id = %job.id, "Failed to record account export: {:?}", e);
+        }
+    });
+
+    (StatusCode::ACCEPTED, Json(V1ExportItem::from(export))).into_response()
+}
+
+/// Get an export of the authenticated user.
+#[utoipa::path(
+    get,
+    path = "/v1/exports/{id}",
+    tag = "exports",
+    params(
+        ("id" = String, Path, description = "Export ID (UUID)")
+    ),
+    responses(
+        (status = 200, description = "Export", body = V1ExportItem),
+        (status = 400, description = "Invalid export ID format", body = V1ErrorResponse),
+        (status = 401, description = "Unauthorized", body = V1ErrorResponse),
+        (status = 404, description = "Export not found", body = V1ErrorResponse),
+        (status = 500, description = "Internal server error", body = V1ErrorResponse),
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn v1_exports_get<S, U>(
+    State(state): State<AppState<S, U>>,
+    auth: RequireAuth,
+    Path(id): Path<String>,
+) -> impl IntoResponse
+where
+    S: SqlStorage,
+    U: UserStorage,
+{
+    match find_export(&state, auth.username(), &id).await {
+        Ok(export) => (StatusCode::OK, Json(V1ExportItem::from(export))).into_response(),
+        Err(response) => *response,
+    }
+}
+
+/// Download the archive of a completed export.
+///
+/// The archive is a ZIP file: `manifest.json` first, then one
+/// `blobs/{content id}` entry per file. Files deleted since the export was
+/// made are left out.
+#[utoipa::path(
+    get,
+    path = "/v1/exports/{id}/archive.zip",
+    tag = "exports",
+    params(
+        ("id" = String, Path, description = "Export ID (UUID)")
+    ),
+    responses(
+        (status = 200, description = "Export archive", content_type = "application/zip"),
+        (status = 400, description = "Invalid export ID format", body = V1ErrorResponse),
+        (status = 401, description = "Unauthorized", body = V1ErrorResponse),
+        (status = 404, description = "Export not found", body = V1ErrorResponse),
+        (status = 409, description = "Export is not completed", body = V1ErrorResponse),
+        (status = 410, description = "Export has expired", body = V1ErrorResponse),
+        (status = 500, description = "Internal server error", body = V1ErrorResponse),
+        (status = 502, description = "Storage backend not configured", body = V1ErrorResponse),
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn v1_exports_archive_zip<S, U>(
+    State(state): State<AppState<S, U>>,
+    storage: Option<axum::Extension<StorageRouter>>,
+    auth: RequireAuth,
+    Path(id): Path<String>,
+) -> impl IntoResponse
+where
+    S: SqlStorage,
+    U: UserStorage,
+{
+    let export = match find_export(&state, auth.username(), &id).await {
+        Ok(export) => export,
+        Err(response) => return *response,
+    };
+
+    if export.expires_at < chrono::Utc::now() {
+        return (
+            StatusCode::GONE,
+            Json(V1ErrorResponse::bad_request("Export has expired")),
+        )
+            .into_response();
+    }
+    let Some(manifest) = export.manifest.filter(|_| export.status == "completed") else {
+        return (
+            StatusCode::CONFLICT,
+            Json(V1ErrorResponse::bad_request(format!(
+                "Export is {}, not completed",
+                export.status
+            ))),
+        )
+            .into_response();
+    };
+
+    let parsed = serde_json::to_vec_pretty(&manifest)
+        .map_err(ManifestError::from)
+        .and_then(|data| Ok((ExportManifest::parse(&data)?, data)));
+    let (manifest, manifest_data) = match parsed {
+        Ok(parsed) => parsed,
+        Err(e) => {
+            tracing::error!(export_id = %export.id, "Failed to read export manifest: {}", e);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(V1ErrorResponse::internal_error("Failed to read export")),
+            )
+                .into_response();
+        }
+    };
+
+    let mut entries = vec![ArchiveEntry {
+        name: MANIFEST_ENTRY.to_owned(),
+        size: manifest_data.len() as u64,
+        modified: manifest.exported_at,
+        source: ArchiveSource::Text(Bytes::from(manifest_data)),
+    }];
+    for exported in &manifest.contents {
+        let Some(blob) = &exported.blob else {
+            continue;
+        };
+        let content = match state.sql_storage.contents_get(exported.id).await {
+            Ok(Some(content)) if content.user_id == export.user_id => content,
+            Ok(_) => continue,
+            Err(e) => {
+                tracing::error!("Failed to get content: {:?}", e);
+                return (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(V1ErrorResponse::internal_error("Failed to get content")),
+                )
+                    .into_response();
+            }
+        };
+
+        let Some(target) = storage.as_ref().and_then(|axum::Extension(router)| {
+            router.resolve(&content.storage_backend, &content.storage_profile)
+        }) else {
+            return (
+                StatusCode::BAD_GATEWAY,
+                Json(V1ErrorResponse::internal_error(format!(
+                    "Storage backend '{}' (profile '{}') is not configured",
+                    content.storage_backend, content.storage_profile
+                ))),
+            )
+                .into_response();
+        };
+        let size = match target
+            .file_storage()
+            .get_file_metadata(&content.storage_key)
+            .await
+        {
+            Ok(Some(metadata)) => metadata.size,
+            Ok(None) => {
+                tracing::warn!(
+                    content_id = %content.id,
+                    "Content data not found in storage, leaving it out of the export"
+                );
+                continue;
+            }
+            Err(e) => {
+                tracing::error!("Failed to read storage object metadata: {:?}", e);
+                return (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(V1ErrorResponse::internal_error("Failed to read content")),
+                )
+                    .into_response();
+            }
+        };
+        entries.push(ArchiveEntry {
+            name: blob.clone(),
+            size,
+            modified: content.updated_at,
+            source: ArchiveSource::File {
+                storage: target.file_storage().clone(),
+                key: content.storage_key,
+            },
+        });
+    }
+
+    let filename = format!("export-{}.zip", manifest.exported_at.format("%Y-%m-%d"));
+    stream_archive(entries, &filename)
+}
+
+/// Import an export archive into the authenticated user's account.
+///
+/// The request body is the archive as downloaded from an export, on this
+/// server or another one. Contents, tags, groups and share links are created
+/// anew; share links get new tokens and tags reuse existing tags of the same
+/// name. If the import fails, whatever it created is removed again.
+#[utoipa::path(
+    post,
+    path = "/v1/imports",
+    tag = "exports",
+    request_body(content = Vec<u8>, description = "Export archive", content_type = "application/zip"),
+    responses(
+        (status = 201, description = "Archive imported", body = V1ImportResponse),
+        (status = 400, description = "Invalid export archive", body = V1ErrorResponse),
+        (status = 401, description = "Unauthorized", body = V1ErrorResponse),
+        (status = 413, description = "Storage quota or file size limit exceeded", body = V1ErrorResponse),
+        (status = 500, description = "Internal server error", body = V1ErrorResponse),
+        (status = 502, description = "Storage backend not configured", body = V1ErrorResponse),
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn v1_imports_create<S, U>(
+    State(state): State<AppState<S, U>>,
+    storage: Option<axum::Extension<StorageRouter>>,
+    auth: RequireAuth,
+    client_ip: ClientIp,
+    body: Body,
+) -> impl IntoResponse
+where
+    S: SqlStorage,
+    U: UserStorage,
+{
+    // Get user ID from username
+    let user = match state.user_storage.get_user(auth.username()).await {
+        Ok(Some(user)) => user,
+        Ok(None) => {
+            return (
+                StatusCode::UNAUTHORIZED,
+                Json(V1ErrorResponse::not_found("User not found")),
+            )
+                .into_response();
+        }
+        Err(e) => {
+            tracing::error!("Failed to get user: {}", e);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(V1ErrorResponse::internal_error("Failed to get user")),
+            )
+                .into_response();
+        }
+    };
+
+    let stream = body.into_data_stream().map_err(io::Error::other);
+    let mut archive = ZipReader::new(tokio_util::io::StreamReader::new(stream));
+    let manifest = match read_manifest(&mut archive).await {
+        Ok(manifest) => manifest,
+        Err(e) => return import_error_response(e),
+    };
+
+    let router = storage
+        .map(|axum::Extension(router)| router)
+        .unwrap_or_default();
+    let importer = AccountImporter::new(state.sql_storage.clone(), router, user.id, &user.username);
+    match importer.import(manifest, archive).await {
+        Ok(report) => {
+            audit::record(
+                &state.sql_storage,
+                Some(user.id),
+                "import.create",
+                None,
+                Some(serde_json::json!({
+                    "contents": report.contents,
+                    "tags": report.tags,
+                    "groups": report.groups,
+                    "share_links": report.share_links,
+                    "missing_files": report.missing_blobs.len(),
+                })),
+                client_ip,
+            )
+            .await;
+            (
+                StatusCode::CREATED,
+                Json(V1ImportResponse {
+                    contents: report.contents,
+                    tags: report.tags,
+                    groups: report.groups,
+                    share_links: report.share_links,
+                    missing_files: report
+                        .missing_blobs
+                        .iter()
+                        .map(ToString::to_string)
+                        .collect(),
+                }),
+            )
+                .into_response()
+        }
+        Err(e) => import_error_response(e),
+    }
+}
+
+/// Look up export `id` of the user named `username`.
+async fn find_export<S, U>(
+    state: &AppState<S, U>,
+    username: &str,
+    id: &str,
+) -> Result<AccountExportRow, Box<Response>>
+where
+    S: SqlStorage,
+    U: UserStorage,
+{
+    let user = match state.user_storage.get_user(username).await {
+        Ok(Some(user)) => user,
+        Ok(None) => {
+            return Err((
+                StatusCode::UNAUTHORIZED,
+                Json(V1ErrorResponse::not_found("User not found")),
+            )
+                .into_response()
+                .into());
+        }
+        Err(e) => {
+            tracing::error!("Failed to get user: {}", e);
+            return Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(V1ErrorResponse::internal_error("Failed to get user")),
+            )
+                .into_response()
+                .into());
+        }
+    };
+
+    let Ok(export_id) = uuid::Uuid::parse_str(id) else {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(V1ErrorResponse::bad_request("Invalid export ID format")),
+        )
+            .into_response()
+            .into());
+    };
+
+    match state.sql_storage.account_exports_get(export_id).await {
+        Ok(Some(export)) if export.user_id == user.id => Ok(export),
+        Ok(_) => Err((
+            StatusCode::NOT_FOUND,
+            Json(V1ErrorResponse::not_found("Export not found")),
+        )
+            .into_response()
+            .into()),
+        Err(e) => {
+            tracing::error!("Failed to get account export: {:?}", e);
+            Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(V1ErrorResponse::internal_error("Failed to get export")),
+            )
+                .into_response()
+                .into())
+        }
+    }
+}
+
+fn import_error_response(e: ImportError) -> Response {
+    match e {
+        ImportError::Manifest(_) | ImportError::Archive(_) | ImportError::Invalid(_) => (
+            StatusCode::BAD_REQUEST,
+            Json(V1ErrorResponse::bad_request(e.to_string())),
+        )
+            .into_response(),
+        ImportError::QuotaExceeded { .. } => (
+            StatusCode::PAYLOAD_TOO_LARGE,
+            Json(V1ErrorResponse::quota_exceeded(e.to_string())),
+        )
+            .into_response(),
+        ImportError::Storage(StorageSelectionError::NotConfigured) => (
+            StatusCode::BAD_GATEWAY,
+            Json(V1ErrorResponse::internal_error(e.to_string())),
+        )
+            .into_response(),
+        ImportError::Storage(StorageSelectionError::TooLarge { .. }) => (
+            StatusCode::PAYLOAD_TOO_LARGE,
+            Json(V1ErrorResponse::bad_request(e.to_string())),
+        )
+            .into_response(),
+        ImportError::Storage(_) => (
+            StatusCode::BAD_REQUEST,
+            Json(V1ErrorResponse::bad_request(e.to_string())),
+        )
+            .into_response(),
+        ImportError::Upload(_) | ImportError::Db(_) => {
+            tracing::error!("Failed to import export archive: {}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(V1ErrorResponse::internal_error("Failed to import archive")),
+            )
+                .into_response()
+        }
+    }
+}
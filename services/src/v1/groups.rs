@@ -1,5 +1,6 @@
 //! /v1/groups endpoint handlers.
 
+use crate::audit::{self, ClientIp};
 use crate::database::{self, SqlStorage, SqlStorageError};
 use crate::users::routes::AppState;
 use crate::users::session_auth::RequireAuth;
@@ -7,10 +8,11 @@ use crate::users::storage::UserStorage;
 use axum::{
     Json,
     extract::{Path, Query, State},
-    http::StatusCode,
+    http::{HeaderMap, StatusCode, header},
     response::IntoResponse,
 };
 
+use super::etag;
 use super::types::{
     V1ErrorResponse, V1GroupAddContentRequest, V1GroupContentItem, V1GroupContentsListResponse,
     V1GroupCreateRequest, V1GroupItem, V1GroupReorderRequest, V1GroupUpdateRequest,
@@ -118,6 +120,7 @@ where
 pub async fn v1_groups_create<S, U>(
     State(state): State<AppState<S, U>>,
     auth: RequireAuth,
+    client_ip: ClientIp,
     Json(request): Json<V1GroupCreateRequest>,
 ) -> impl IntoResponse
 where
@@ -177,7 +180,18 @@ where
     };
 
     match state.sql_storage.groups_create(input).await {
-        Ok(row) => (StatusCode::CREATED, Json(V1GroupItem::from(row))).into_response(),
+        Ok(row) => {
+            audit::record(
+                &state.sql_storage,
+                Some(user.id),
+                "group.create",
+                Some(row.id),
+                None,
+                client_ip,
+            )
+            .await;
+            (StatusCode::CREATED, Json(V1GroupItem::from(row))).into_response()
+        }
         Err(e) => {
             tracing::error!("Failed to create group: {:?}", e);
             (
@@ -190,6 +204,9 @@ where
 }
 
 /// Get a specific group by ID.
+///
+/// The `ETag` header identifies this version of the group, for use with
+/// `If-Match` on PATCH.
 #[utoipa::path(
     get,
     path = "/v1/groups/{id}",
@@ -259,7 +276,12 @@ where
                 )
                     .into_response();
             }
-            (StatusCode::OK, Json(V1GroupItem::from(row))).into_response()
+            (
+                StatusCode::OK,
+                [(header::ETAG, etag::etag(row.updated_at))],
+                Json(V1GroupItem::from(row)),
+            )
+                .into_response()
         }
         Ok(None) => (
             StatusCode::NOT_FOUND,
@@ -278,12 +300,16 @@ where
 }
 
 /// Update a group.
+///
+/// With `If-Match`, the update only applies while the group is unchanged;
+/// otherwise it fails with 412.
 #[utoipa::path(
     patch,
     path = "/v1/groups/{id}",
     tag = "groups",
     params(
-        ("id" = String, Path, description = "Group ID (UUID)")
+        ("id" = String, Path, description = "Group ID (UUID)"),
+        ("If-Match" = Option<String>, Header, description = "ETag from a previous GET")
     ),
     request_body = V1GroupUpdateRequest,
     responses(
@@ -291,6 +317,7 @@ where
         (status = 400, description = "Bad request", body = V1ErrorResponse),
         (status = 401, description = "Unauthorized", body = V1ErrorResponse),
         (status = 404, description = "Group not found", body = V1ErrorResponse),
+        (status = 412, description = "Group changed since it was fetched", body = V1ErrorResponse),
         (status = 500, description = "Internal server error", body = V1ErrorResponse),
     ),
     security(
@@ -300,7 +327,9 @@ where
 pub async fn v1_groups_update<S, U>(
     State(state): State<AppState<S, U>>,
     auth: RequireAuth,
+    client_ip: ClientIp,
     Path(id): Path<String>,
+    headers: HeaderMap,
     Json(request): Json<V1GroupUpdateRequest>,
 ) -> impl IntoResponse
 where
@@ -368,10 +397,39 @@ where
         None => None,
     };
 
+    // Honor If-Match against the group's current version
+    let expected_updated_at = match etag::if_match(&headers) {
+        None => None,
+        Some(if_match) => match state.sql_storage.groups_get(group_id).await {
+            Ok(Some(row)) if row.user_id == user.id => {
+                if !etag::matches(if_match, row.updated_at) {
+                    return etag::precondition_failed();
+                }
+                Some(row.updated_at)
+            }
+            Ok(_) => {
+                return (
+                    StatusCode::NOT_FOUND,
+                    Json(V1ErrorResponse::not_found("Group not found")),
+                )
+                    .into_response();
+            }
+            Err(e) => {
+                tracing::error!("Failed to get group: {:?}", e);
+                return (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(V1ErrorResponse::internal_error("Failed to get group")),
+                )
+                    .into_response();
+            }
+        },
+    };
+
     let changes = database::GroupUpdate {
         name: request.name.map(|n| n.trim().to_owned()),
         description: request.description,
         visibility,
+        expected_updated_at,
     };
 
     match state
@@ -379,7 +437,24 @@ where
         .groups_update_metadata(group_id, user.id, changes)
         .await
     {
-        Ok(Some(row)) => (StatusCode::OK, Json(V1GroupItem::from(row))).into_response(),
+        Ok(Some(row)) => {
+            audit::record(
+                &state.sql_storage,
+                Some(user.id),
+                "group.update",
+                Some(row.id),
+                None,
+                client_ip,
+            )
+            .await;
+            (
+                StatusCode::OK,
+                [(header::ETAG, etag::etag(row.updated_at))],
+                Json(V1GroupItem::from(row)),
+            )
+                .into_response()
+        }
+        Ok(None) if expected_updated_at.is_some() => etag::precondition_failed(),
         Ok(None) => (
             StatusCode::NOT_FOUND,
             Json(V1ErrorResponse::not_found("Group not found")),
@@ -402,6 +477,8 @@ async fn v1_groups_set_status<S, U>(
     user_id: uuid::Uuid,
     group_id: uuid::Uuid,
     new_status: database::GroupStatus,
+    action: &str,
+    client_ip: ClientIp,
 ) -> axum::response::Response
 where
     S: SqlStorage,
@@ -413,7 +490,18 @@ where
         .groups_set_status(group_id, user_id, new_status, now)
         .await
     {
-        Ok(Some(row)) => (StatusCode::OK, Json(V1GroupItem::from(row))).into_response(),
+        Ok(Some(row)) => {
+            audit::record(
+                &state.sql_storage,
+                Some(user_id),
+                action,
+                Some(row.id),
+                None,
+                client_ip,
+            )
+            .await;
+            (StatusCode::OK, Json(V1GroupItem::from(row))).into_response()
+        }
         Ok(None) => (
             StatusCode::NOT_FOUND,
             Json(V1ErrorResponse::not_found("Group not found")),
@@ -463,6 +551,7 @@ where
 pub async fn v1_groups_trash<S, U>(
     State(state): State<AppState<S, U>>,
     auth: RequireAuth,
+    client_ip: ClientIp,
     Path(id): Path<String>,
 ) -> impl IntoResponse
 where
@@ -501,7 +590,15 @@ where
         }
     };
 
-    v1_groups_set_status(&state, user.id, group_id, database::GroupStatus::Trashed).await
+    v1_groups_set_status(
+        &state,
+        user.id,
+        group_id,
+        database::GroupStatus::Trashed,
+        "group.trash",
+        client_ip,
+    )
+    .await
 }
 
 /// Restore a group from trash.
@@ -527,6 +624,7 @@ where
 pub async fn v1_groups_restore<S, U>(
     State(state): State<AppState<S, U>>,
     auth: RequireAuth,
+    client_ip: ClientIp,
     Path(id): Path<String>,
 ) -> impl IntoResponse
 where
@@ -565,7 +663,15 @@ where
         }
     };
 
-    v1_groups_set_status(&state, user.id, group_id, database::GroupStatus::Active).await
+    v1_groups_set_status(
+        &state,
+        user.id,
+        group_id,
+        database::GroupStatus::Active,
+        "group.restore",
+        client_ip,
+    )
+    .await
 }
 
 /// Archive a group.
@@ -591,6 +697,7 @@ where
 pub async fn v1_groups_archive<S, U>(
     State(state): State<AppState<S, U>>,
     auth: RequireAuth,
+    client_ip: ClientIp,
     Path(id): Path<String>,
 ) -> impl IntoResponse
 where
@@ -629,7 +736,15 @@ where
         }
     };
 
-    v1_groups_set_status(&state, user.id, group_id, database::GroupStatus::Archived).await
+    v1_groups_set_status(
+        &state,
+        user.id,
+        group_id,
+        database::GroupStatus::Archived,
+        "group.archive",
+        client_ip,
+    )
+    .await
 }
 
 /// Unarchive a group.
@@ -655,6 +770,7 @@ where
 pub async fn v1_groups_unarchive<S, U>(
     State(state): State<AppState<S, U>>,
     auth: RequireAuth,
+    client_ip: ClientIp,
     Path(id): Path<String>,
 ) -> impl IntoResponse
 where
@@ -693,7 +809,15 @@ where
         }
     };
 
-    v1_groups_set_status(&state, user.id, group_id, database::GroupStatus::Active).await
+    v1_groups_set_status(
+        &state,
+        user.id,
+        group_id,
+        database::GroupStatus::Active,
+        "group.unarchive",
+        client_ip,
+    )
+    .await
 }
 
 /// List contents in a group.
@@ -831,6 +955,7 @@ where
 pub async fn v1_groups_contents_add<S, U>(
     State(state): State<AppState<S, U>>,
     auth: RequireAuth,
+    client_ip: ClientIp,
     Path(id): Path<String>,
     Json(request): Json<V1GroupAddContentRequest>,
 ) -> impl IntoResponse
@@ -945,7 +1070,18 @@ where
         .group_items_add(group_id, content_id, sort_order)
         .await
     {
-        Ok(()) => StatusCode::NO_CONTENT.into_response(),
+        Ok(()) => {
+            audit::record(
+                &state.sql_storage,
+                Some(user.id),
+                "group.content_add",
+                Some(group_id),
+                Some(serde_json::json!({ "content_id": content_id })),
+                client_ip,
+            )
+            .await;
+            StatusCode::NO_CONTENT.into_response()
+        }
         Err(e) => {
             tracing::error!("Failed to add content to group: {:?}", e);
             (
@@ -982,6 +1118,7 @@ where
 pub async fn v1_groups_contents_remove<S, U>(
     State(state): State<AppState<S, U>>,
     auth: RequireAuth,
+    client_ip: ClientIp,
     Path((id, content_id_str)): Path<(String, String)>,
 ) -> impl IntoResponse
 where
@@ -1065,7 +1202,18 @@ where
         .group_items_remove(group_id, content_id)
         .await
     {
-        Ok(true) => StatusCode::NO_CONTENT.into_response(),
+        Ok(true) => {
+            audit::record(
+                &state.sql_storage,
+                Some(user.id),
+                "group.content_remove",
+                Some(group_id),
+                Some(serde_json::json!({ "content_id": content_id })),
+                client_ip,
+            )
+            .await;
+            StatusCode::NO_CONTENT.into_response()
+        }
         Ok(false) => (
             StatusCode::NOT_FOUND,
             Json(V1ErrorResponse::not_found("Content not in group")),
@@ -1107,6 +1255,7 @@ where
 pub async fn v1_groups_contents_reorder<S, U>(
     State(state): State<AppState<S, U>>,
     auth: RequireAuth,
+    client_ip: ClientIp,
     Path(id): Path<String>,
     Json(request): Json<V1GroupReorderRequest>,
 ) -> impl IntoResponse
@@ -1169,7 +1318,18 @@ where
         .group_items_reorder(group_id, user.id, &items)
         .await
     {
-        Ok(()) => StatusCode::NO_CONTENT.into_response(),
+        Ok(()) => {
+            audit::record(
+                &state.sql_storage,
+                Some(user.id),
+                "group.reorder",
+                Some(group_id),
+                None,
+                client_ip,
+            )
+            .await;
+            StatusCode::NO_CONTENT.into_response()
+        }
         Err(SqlStorageError::Unauthorized) => (
             StatusCode::NOT_FOUND,
             Json(V1ErrorResponse::not_found("Group not found")),
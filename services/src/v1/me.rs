@@ -6,9 +6,9 @@ use crate::users::session_auth::RequireAuth;
 use crate::users::storage::UserStorage;
 use axum::{Json, extract::State, http::StatusCode, response::IntoResponse};
 
-use super::types::{V1ErrorResponse, V1MeResponse};
+use super::types::{V1ErrorResponse, V1MeResponse, V1StorageUsage};
 
-/// Get the current authenticated user's information.
+/// Get the current authenticated user's information and storage usage.
 #[utoipa::path(
     get,
     path = "/v1/me",
@@ -16,25 +16,67 @@ use super::types::{V1ErrorResponse, V1MeResponse};
     responses(
         (status = 200, description = "Current user information", body = V1MeResponse),
         (status = 401, description = "Unauthorized - missing or invalid token", body = V1ErrorResponse),
+        (status = 500, description = "Internal server error", body = V1ErrorResponse),
     ),
     security(
         ("bearer_auth" = [])
     )
 )]
 pub async fn v1_me<S, U>(
-    State(_state): State<AppState<S, U>>,
+    State(state): State<AppState<S, U>>,
     auth: RequireAuth,
 ) -> impl IntoResponse
 where
     S: SqlStorage,
     U: UserStorage,
 {
+    // Get user ID from username
+    let user = match state.user_storage.get_user(auth.username()).await {
+        Ok(Some(user)) => user,
+        Ok(None) => {
+            return (
+                StatusCode::UNAUTHORIZED,
+                Json(V1ErrorResponse::not_found("User not found")),
+            )
+                .into_response();
+        }
+        Err(e) => {
+            tracing::error!("Failed to get user: {}", e);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(V1ErrorResponse::internal_error("Failed to get user")),
+            )
+                .into_response();
+        }
+    };
+
+    let usage = state
+        .sql_storage
+        .users_storage_usage(user.id, chrono::Utc::now())
+        .await;
+    let quota = state.sql_storage.users_get_storage_quota(user.id).await;
+    let storage = match (usage, quota) {
+        (Ok(usage), Ok(quota)) => V1StorageUsage::new(usage, quota),
+        (Err(e), _) | (_, Err(e)) => {
+            tracing::error!("Failed to get storage usage: {:?}", e);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(V1ErrorResponse::internal_error(
+                    "Failed to get storage usage",
+                )),
+            )
+                .into_response();
+        }
+    };
+
     (
         StatusCode::OK,
         Json(V1MeResponse {
             username: auth.username().to_owned(),
             issued_at: auth.issued_at(),
             expires_at: auth.expires_at(),
+            storage,
         }),
     )
+        .into_response()
 }
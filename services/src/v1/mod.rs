@@ -1,23 +1,41 @@
 //! v1 API endpoints module.
 //!
 //! This module contains all v1 API handlers organized by domain:
+//! - `archive` - Groups downloaded as streamed ZIP files
 //! - `contents` - Content management endpoints
+//! - `etag` - ETags and `If-Match` preconditions
+//! - `exports` - Account data export and import
 //! - `content_tags` - Content-tag relationship endpoints
 //! - `groups` - Group management endpoints
 //! - `me` - Current user information
 //! - `public` - Public share access endpoints (unauthenticated)
+//! - `raw` - Content downloads streamed through the server
+//! - `revisions` - Revision history of text contents
+//! - `search` - Full-text search over contents
 //! - `share_links` - Share link management endpoints
+//! - `share_unlock` - Password checks and unlock tokens for share links
+//! - `shares` - Direct sharing with other users
 //! - `tags` - Tag management endpoints
+//! - `trash` - Trash listing and emptying
 //! - `types` - Shared types for API request/response
 //! - `uploads` - File upload endpoints
 
+pub mod archive;
 pub mod content_tags;
 pub mod contents;
+pub mod etag;
+pub mod exports;
 pub mod groups;
 pub mod me;
 pub mod public;
+pub mod raw;
+pub mod revisions;
+pub mod search;
 pub mod share_links;
+pub mod share_unlock;
+pub mod shares;
 pub mod tags;
+pub mod trash;
 pub mod types;
 pub mod uploads;
 
@@ -26,7 +44,7 @@ use crate::users::routes::AppState;
 use crate::users::storage::UserStorage;
 use axum::{
     Router,
-    routing::{delete, get, patch, post},
+    routing::{delete, get, patch, post, put},
 };
 
 /// Creates public (unauthenticated) v1 API routes.
@@ -44,6 +62,10 @@ where
             "/public/share/{token}/view-url",
             post(public::v1_public_share_view_url::<S, U>),
         )
+        .route(
+            "/public/share/{token}/archive.zip",
+            get(archive::v1_public_share_archive_zip::<S, U>),
+        )
 }
 
 /// Creates the v1 API routes.
@@ -59,11 +81,34 @@ where
             "/uploads/complete",
             post(uploads::v1_uploads_complete::<S, U>),
         )
+        .route(
+            "/uploads/multipart/init",
+            post(uploads::v1_uploads_multipart_init::<S, U>),
+        )
+        .route("/uploads/{upload_id}", get(uploads::v1_uploads_get::<S, U>))
+        .route(
+            "/uploads/{upload_id}/parts",
+            post(uploads::v1_uploads_parts_presign::<S, U>),
+        )
+        .route(
+            "/uploads/{upload_id}/parts/{part_number}",
+            put(uploads::v1_uploads_part_update::<S, U>),
+        )
+        .route(
+            "/uploads/{upload_id}/abort",
+            post(uploads::v1_uploads_abort::<S, U>),
+        )
+        // Search endpoints
+        .route("/search", get(search::v1_search::<S, U>))
+        // Trash endpoints
+        .route("/trash", get(trash::v1_trash_list::<S, U>))
+        .route("/trash/empty", post(trash::v1_trash_empty::<S, U>))
         // Contents endpoints
         .route(
             "/contents",
             get(contents::v1_contents_list::<S, U>).post(contents::v1_contents_create::<S, U>),
         )
+        .route("/contents/batch", post(contents::v1_contents_batch::<S, U>))
         .route("/contents/{id}", get(contents::v1_contents_get::<S, U>))
         .route(
             "/contents/{id}",
@@ -89,6 +134,20 @@ where
             "/contents/{id}/view-url",
             post(contents::v1_contents_view_url::<S, U>),
         )
+        .route("/contents/{id}/raw", get(raw::v1_contents_raw::<S, U>))
+        // Content revisions endpoints
+        .route(
+            "/contents/{id}/revisions",
+            get(revisions::v1_content_revisions_list::<S, U>),
+        )
+        .route(
+            "/contents/{id}/revisions/diff",
+            get(revisions::v1_content_revisions_diff::<S, U>),
+        )
+        .route(
+            "/contents/{id}/revisions/{revision}/restore",
+            post(revisions::v1_content_revisions_restore::<S, U>),
+        )
         // Content-Tags endpoints
         .route(
             "/contents/{id}/tags",
@@ -106,7 +165,9 @@ where
         )
         .route(
             "/tags/{id}",
-            patch(tags::v1_tags_update::<S, U>).delete(tags::v1_tags_delete::<S, U>),
+            get(tags::v1_tags_get::<S, U>)
+                .patch(tags::v1_tags_update::<S, U>)
+                .delete(tags::v1_tags_delete::<S, U>),
         )
         // Groups endpoints
         .route(
@@ -143,6 +204,18 @@ where
             "/groups/{id}/contents/reorder",
             patch(groups::v1_groups_contents_reorder::<S, U>),
         )
+        .route(
+            "/groups/{id}/archive.zip",
+            get(archive::v1_groups_archive_zip::<S, U>),
+        )
+        // Export and import endpoints
+        .route("/exports", post(exports::v1_exports_create::<S, U>))
+        .route("/exports/{id}", get(exports::v1_exports_get::<S, U>))
+        .route(
+            "/exports/{id}/archive.zip",
+            get(exports::v1_exports_archive_zip::<S, U>),
+        )
+        .route("/imports", post(exports::v1_imports_create::<S, U>))
         // Share links endpoints
         .route(
             "/share-links",
@@ -155,6 +228,10 @@ where
                 .patch(share_links::v1_share_links_update::<S, U>)
                 .delete(share_links::v1_share_links_delete::<S, U>),
         )
+        .route(
+            "/share-links/{id}/analytics",
+            get(share_links::v1_share_links_analytics::<S, U>),
+        )
         // Content share link creation
         .route(
             "/contents/{id}/share-link",
@@ -165,4 +242,24 @@ where
             "/groups/{id}/share-link",
             post(share_links::v1_groups_share_link_create::<S, U>),
         )
+        // Direct shares endpoints
+        .route(
+            "/contents/{id}/shares",
+            get(shares::v1_contents_shares_list::<S, U>)
+                .post(shares::v1_contents_shares_create::<S, U>),
+        )
+        .route(
+            "/contents/{id}/shares/{username}",
+            delete(shares::v1_contents_shares_delete::<S, U>),
+        )
+        .route(
+            "/groups/{id}/shares",
+            get(shares::v1_groups_shares_list::<S, U>)
+                .post(shares::v1_groups_shares_create::<S, U>),
+        )
+        .route(
+            "/groups/{id}/shares/{username}",
+            delete(shares::v1_groups_shares_delete::<S, U>),
+        )
+        .route("/shared-with-me", get(shares::v1_shared_with_me::<S, U>))
 }
@@ -1,23 +1,29 @@
 //! /v1/public endpoint handlers for unauthenticated share access.
 
-use crate::database::{ShareLinkRow, SqlStorage};
-use crate::storage::{ContentDisposition, DEFAULT_PRESIGN_EXPIRY, R2Presigner};
+use crate::audit::ClientIp;
+use crate::config::Config;
+use crate::database::{ShareLinkAccessInsert, ShareLinkRow, SqlStorage};
+use crate::storage::{ContentDisposition, DEFAULT_PRESIGN_EXPIRY, StorageRouter};
 use crate::users::routes::AppState;
 use crate::users::storage::UserStorage;
 use axum::{
-    Json,
+    Extension, Json,
     extract::{Path, State},
-    http::StatusCode,
+    http::{HeaderMap, StatusCode, header},
     response::IntoResponse,
 };
 
-use super::share_links::verify_password;
+use super::share_unlock::{
+    check_share_link_password, set_unlock_cookie, unlock_token_from_headers,
+};
 use super::types::{
     V1ErrorResponse, V1PublicShareResponse, V1PublicViewUrlRequest, V1PublicViewUrlResponse,
 };
 
 /// Validate a share link is accessible (active, not expired, access count not exceeded).
-fn validate_share_link(share_link: &ShareLinkRow) -> Result<(), (StatusCode, &'static str)> {
+pub(crate) fn validate_share_link(
+    share_link: &ShareLinkRow,
+) -> Result<(), (StatusCode, &'static str)> {
     // Check if link is active
     if !share_link.is_active {
         return Err((StatusCode::GONE, "Share link is no longer active"));
@@ -43,6 +49,33 @@ fn validate_share_link(share_link: &ShareLinkRow) -> Result<(), (StatusCode, &'s
     Ok(())
 }
 
+/// Record a public access to a share link.
+///
+/// Best-effort, like the access count: a failure is logged and the access
+/// goes ahead.
+pub(crate) async fn record_access<S: SqlStorage>(
+    storage: &S,
+    share_link: &ShareLinkRow,
+    ClientIp(ip_address): ClientIp,
+    headers: &HeaderMap,
+    password_supplied: bool,
+) {
+    let user_agent = headers
+        .get(header::USER_AGENT)
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_owned);
+    let input = ShareLinkAccessInsert {
+        share_link_id: share_link.id,
+        ip_address,
+        user_agent,
+        password_supplied,
+    };
+
+    if let Err(e) = storage.share_link_accesses_insert(input).await {
+        tracing::warn!("Failed to record share link access: {:?}", e);
+    }
+}
+
 /// Get public share metadata.
 ///
 /// Returns metadata about the shared content without requiring authentication.
@@ -64,6 +97,8 @@ fn validate_share_link(share_link: &ShareLinkRow) -> Result<(), (StatusCode, &'s
 )]
 pub async fn v1_public_share_get<S, U>(
     State(state): State<AppState<S, U>>,
+    client_ip: ClientIp,
+    headers: HeaderMap,
     Path(token): Path<String>,
 ) -> impl IntoResponse
 where
@@ -78,6 +113,8 @@ where
                 return (status, Json(V1ErrorResponse::bad_request(message))).into_response();
             }
 
+            record_access(&state.sql_storage, &share_link, client_ip, &headers, false).await;
+
             (
                 StatusCode::OK,
                 Json(V1PublicShareResponse {
@@ -101,6 +138,9 @@ where
                             .into_response();
                     }
 
+                    record_access(&state.sql_storage, &share_link, client_ip, &headers, false)
+                        .await;
+
                     (
                         StatusCode::OK,
                         Json(V1PublicShareResponse {
@@ -143,7 +183,9 @@ where
 /// Get a view URL for shared content.
 ///
 /// Returns a presigned URL for viewing/downloading shared content.
-/// If the share link is password protected, the password must be provided.
+/// If the share link is password protected, the password or an unlock token
+/// must be provided. A correct password returns an unlock token, also set as
+/// a cookie. Wrong passwords are rate-limited per link and per IP address.
 #[utoipa::path(
     post,
     path = "/v1/public/share/{token}/view-url",
@@ -158,12 +200,16 @@ where
         (status = 401, description = "Unauthorized - password required or invalid", body = V1ErrorResponse),
         (status = 404, description = "Share link not found", body = V1ErrorResponse),
         (status = 410, description = "Share link is no longer active", body = V1ErrorResponse),
+        (status = 429, description = "Too many wrong passwords", body = V1ErrorResponse),
         (status = 500, description = "Internal server error", body = V1ErrorResponse),
     )
 )]
 pub async fn v1_public_share_view_url<S, U>(
     State(state): State<AppState<S, U>>,
-    presigner: Option<axum::Extension<R2Presigner>>,
+    storage: Option<axum::Extension<StorageRouter>>,
+    Extension(config): Extension<Config>,
+    client_ip: ClientIp,
+    headers: HeaderMap,
     Path(token): Path<String>,
     Json(payload): Json<V1PublicViewUrlRequest>,
 ) -> impl IntoResponse
@@ -182,29 +228,23 @@ where
             }
 
             // Verify password if required
-            if let Some(ref password_hash) = share_link.password_hash {
-                match &payload.password {
-                    Some(password) if verify_password(password, password_hash) => {
-                        // Password correct, continue
-                    }
-                    Some(_) => {
-                        return (
-                            StatusCode::UNAUTHORIZED,
-                            Json(V1ErrorResponse::bad_request("Invalid password")),
-                        )
-                            .into_response();
-                    }
-                    None => {
-                        return (
-                            StatusCode::UNAUTHORIZED,
-                            Json(V1ErrorResponse::bad_request(
-                                "Password required for this share link",
-                            )),
-                        )
-                            .into_response();
-                    }
-                }
-            }
+            let unlock_token = payload
+                .unlock_token
+                .as_deref()
+                .or_else(|| unlock_token_from_headers(&headers, &token));
+            let unlock = match check_share_link_password(
+                &state.sql_storage,
+                &config,
+                &share_link,
+                payload.password.as_deref(),
+                unlock_token,
+                client_ip,
+            )
+            .await
+            {
+                Ok(unlock) => unlock,
+                Err(e) => return e.into_response(),
+            };
 
             // Check permission - view-url requires at least view permission
             // (both "view" and "download" allow viewing)
@@ -239,10 +279,22 @@ where
                 tracing::warn!("Failed to increment share link access count: {:?}", e);
                 // Continue anyway - access count is best-effort
             }
+            record_access(
+                &state.sql_storage,
+                &share_link,
+                client_ip,
+                &headers,
+                payload.password.is_some(),
+            )
+            .await;
 
             // Generate presigned URL
-            let presigned = if let Some(axum::Extension(presigner)) = presigner {
-                match presigner
+            let presigned = if let Some(target) =
+                storage.as_ref().and_then(|axum::Extension(router)| {
+                    router.resolve(&content.storage_backend, &content.storage_profile)
+                }) {
+                match target
+                    .presigner()
                     .presign_get(
                         &content.storage_key,
                         final_disposition,
@@ -265,21 +317,28 @@ where
             } else {
                 return (
                     StatusCode::BAD_GATEWAY,
-                    Json(V1ErrorResponse::internal_error(
-                        "R2 storage is not configured",
-                    )),
+                    Json(V1ErrorResponse::internal_error(format!(
+                        "Storage backend '{}' (profile '{}') is not configured",
+                        content.storage_backend, content.storage_profile
+                    ))),
                 )
                     .into_response();
             };
 
-            (
+            let mut response = (
                 StatusCode::OK,
                 Json(V1PublicViewUrlResponse {
                     url: presigned.url,
                     expires_at: presigned.expires_at.to_rfc3339(),
+                    unlock_token: unlock.as_ref().map(|unlock| unlock.token.clone()),
+                    unlock_expires_at: unlock.as_ref().map(|unlock| unlock.expires_at.to_rfc3339()),
                 }),
             )
-                .into_response()
+                .into_response();
+            if let Some(unlock) = &unlock {
+                set_unlock_cookie(&mut response, &share_link, unlock, &config);
+            }
+            response
         }
         Ok(None) => {
             // Content not found - check if it's a group share
@@ -341,6 +400,7 @@ mod tests {
             expires_at,
             is_active,
             created_at: chrono::Utc::now(),
+            updated_at: chrono::Utc::now(),
         }
     }
 
@@ -0,0 +1,458 @@
+//! /v1/contents/:id/raw endpoint handler.
+//!
+//! Streams a content's bytes through the server, for clients that cannot
+//! reach the storage backend directly. Presigned URLs (`view-url`) remain the
+//! cheaper option where they work.
+
+use std::ops::Range;
+
+use crate::database::{ContentRow, SqlStorage};
+use crate::storage::{ContentDisposition, FileStorage, StorageRouter, mime_essence, stream_range};
+use crate::users::routes::AppState;
+use crate::users::session_auth::RequireAuth;
+use crate::users::storage::UserStorage;
+use axum::{
+    Json,
+    body::Body,
+    extract::{Path, Query, State},
+    http::{HeaderMap, HeaderValue, StatusCode, header},
+    response::{IntoResponse, Response},
+};
+use sha2::{Digest, Sha256};
+
+use super::etag;
+use super::types::{V1ContentRawQuery, V1ErrorResponse};
+
+/// Download a content's bytes through the server.
+///
+/// Works for uploaded files and text contents alike. Supports a single
+/// `Range` (answered with 206), `If-Range` and `If-None-Match` (answered with
+/// 304).
+#[utoipa::path(
+    get,
+    path = "/v1/contents/{id}/raw",
+    tag = "contents",
+    params(
+        ("id" = String, Path, description = "Content ID (UUID)"),
+        V1ContentRawQuery,
+        ("Range" = Option<String>, Header, description = "Byte range, e.g. `bytes=0-1023`"),
+        ("If-None-Match" = Option<String>, Header, description = "ETag from a previous download")
+    ),
+    responses(
+        (status = 200, description = "Content bytes"),
+        (status = 206, description = "Requested byte range"),
+        (status = 304, description = "Not modified"),
+        (status = 400, description = "Bad request", body = V1ErrorResponse),
+        (status = 401, description = "Unauthorized", body = V1ErrorResponse),
+        (status = 404, description = "Content not found", body = V1ErrorResponse),
+        (status = 416, description = "Range not satisfiable"),
+        (status = 500, description = "Internal server error", body = V1ErrorResponse),
+        (status = 502, description = "Storage backend not configured", body = V1ErrorResponse),
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn v1_contents_raw<S, U>(
+    State(state): State<AppState<S, U>>,
+    storage: Option<axum::Extension<StorageRouter>>,
+    auth: RequireAuth,
+    Path(id): Path<String>,
+    Query(query): Query<V1ContentRawQuery>,
+    headers: HeaderMap,
+) -> impl IntoResponse
+where
+    S: SqlStorage,
+    U: UserStorage,
+{
+    // Resolve user from JWT username
+    let user = match state.user_storage.get_user(auth.username()).await {
+        Ok(Some(user)) => user,
+        Ok(None) => {
+            return (
+                StatusCode::UNAUTHORIZED,
+                Json(V1ErrorResponse::not_found("User not found")),
+            )
+                .into_response();
+        }
+        Err(e) => {
+            tracing::error!("Failed to get user: {}", e);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(V1ErrorResponse::internal_error("Failed to get user")),
+            )
+                .into_response();
+        }
+    };
+
+    // Parse content ID
+    let content_id = match uuid::Uuid::parse_str(&id) {
+        Ok(id) => id,
+        Err(_) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(V1ErrorResponse::bad_request("Invalid content ID format")),
+            )
+                .into_response();
+        }
+    };
+
+    // Parse disposition
+    let disposition = match query.disposition.as_deref() {
+        None => ContentDisposition::Inline,
+        Some(value) => match ContentDisposition::try_from(value) {
+            Ok(d) => d,
+            Err(_) => {
+                return (
+                    StatusCode::BAD_REQUEST,
+                    Json(V1ErrorResponse::bad_request(
+                        "Invalid disposition. Must be 'inline' or 'attachment'",
+                    )),
+                )
+                    .into_response();
+            }
+        },
+    };
+
+    // Get content and verify ownership
+    let content = match state.sql_storage.contents_get(content_id).await {
+        Ok(Some(content)) if content.user_id == user.id => content,
+        Ok(_) => {
+            return (
+                StatusCode::NOT_FOUND,
+                Json(V1ErrorResponse::not_found("Content not found")),
+            )
+                .into_response();
+        }
+        Err(e) => {
+            tracing::error!("Failed to get content: {:?}", e);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(V1ErrorResponse::internal_error("Failed to get content")),
+            )
+                .into_response();
+        }
+    };
+
+    raw_response(
+        storage.as_ref().map(|axum::Extension(router)| router),
+        content,
+        disposition,
+        &headers,
+    )
+    .await
+}
+
+/// Stream the bytes of `content`, honoring the conditional and `Range`
+/// headers of the request.
+pub(crate) async fn raw_response(
+    storage: Option<&StorageRouter>,
+    content: ContentRow,
+    disposition: ContentDisposition,
+    headers: &HeaderMap,
+) -> Response {
+    let etag = raw_etag(&content);
+    if let Some(if_none_match) = header_str(headers, header::IF_NONE_MATCH)
+        && if_none_match
+            .split(',')
+            .map(|tag| tag.trim().trim_start_matches("W/"))
+            .any(|tag| tag == "*" || tag == etag)
+    {
+        return (StatusCode::NOT_MODIFIED, [(header::ETAG, etag)]).into_response();
+    }
+
+    let range_header = honored_range(headers, &etag);
+
+    let mut response_headers = HeaderMap::new();
+    response_headers.insert(header::ETAG, header_value(&etag));
+    response_headers.insert(header::ACCEPT_RANGES, HeaderValue::from_static("bytes"));
+    response_headers.insert(
+        header::CONTENT_DISPOSITION,
+        header_value(&disposition.header_value(&download_filename(&content))),
+    );
+    // The bytes are user supplied and served from the API origin: never let
+    // a browser sniff or run them as an active document.
+    response_headers.insert(
+        header::X_CONTENT_TYPE_OPTIONS,
+        HeaderValue::from_static("nosniff"),
+    );
+    response_headers.insert(
+        header::CONTENT_SECURITY_POLICY,
+        HeaderValue::from_static("sandbox"),
+    );
+
+    if content.kind == "text" {
+        let body = content.body.unwrap_or_default().into_bytes();
+        let content_type = if content.content_type.contains("charset") {
+            content.content_type
+        } else {
+            format!("{}; charset=utf-8", content.content_type)
+        };
+        response_headers.insert(header::CONTENT_TYPE, header_value(&content_type));
+
+        let len = body.len() as u64;
+        return match byte_range(range_header, len) {
+            ByteRange::Full => {
+                response_headers.insert(header::CONTENT_LENGTH, HeaderValue::from(len));
+                (StatusCode::OK, response_headers, body).into_response()
+            }
+            ByteRange::Partial(range) => {
+                partial_headers(&mut response_headers, &range, len);
+                let part = body[range.start as usize..range.end as usize].to_vec();
+                (StatusCode::PARTIAL_CONTENT, response_headers, part).into_response()
+            }
+            ByteRange::Unsatisfiable => range_not_satisfiable(len),
+        };
+    }
+
+    let Some(target) = storage
+        .and_then(|router| router.resolve(&content.storage_backend, &content.storage_profile))
+    else {
+        return (
+            StatusCode::BAD_GATEWAY,
+            Json(V1ErrorResponse::internal_error(format!(
+                "Storage backend '{}' (profile '{}') is not configured",
+                content.storage_backend, content.storage_profile
+            ))),
+        )
+            .into_response();
+    };
+
+    let len = match target
+        .file_storage()
+        .get_file_metadata(&content.storage_key)
+        .await
+    {
+        Ok(Some(metadata)) => metadata.size,
+        Ok(None) => {
+            return (
+                StatusCode::NOT_FOUND,
+                Json(V1ErrorResponse::not_found(
+                    "Content data not found in storage",
+                )),
+            )
+                .into_response();
+        }
+        Err(e) => {
+            tracing::error!("Failed to read storage object metadata: {:?}", e);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(V1ErrorResponse::internal_error("Failed to read content")),
+            )
+                .into_response();
+        }
+    };
+    response_headers.insert(header::CONTENT_TYPE, header_value(&content.content_type));
+
+    let (status, range) = match byte_range(range_header, len) {
+        ByteRange::Full => {
+            response_headers.insert(header::CONTENT_LENGTH, HeaderValue::from(len));
+            (StatusCode::OK, 0..len)
+        }
+        ByteRange::Partial(range) => {
+            partial_headers(&mut response_headers, &range, len);
+            (StatusCode::PARTIAL_CONTENT, range)
+        }
+        ByteRange::Unsatisfiable => return range_not_satisfiable(len),
+    };
+
+    let body = Body::from_stream(stream_range(
+        target.file_storage().clone(),
+        content.storage_key,
+        range,
+    ));
+    (status, response_headers, body).into_response()
+}
+
+/// The name a downloaded content is saved as: the uploaded file name, or the
+/// title of a text content with a `.md` or `.txt` extension.
+pub(crate) fn download_filename(content: &ContentRow) -> String {
+    if content.kind == "text" {
+        let title: String = content
+            .title
+            .trim()
+            .chars()
+            .map(|c| {
+                if c == '/' || c == '\\' || c.is_control() {
+                    '_'
+                } else {
+                    c
+                }
+            })
+            .collect();
+        let stem = if title.is_empty() { "untitled" } else { &title };
+        let extension = if mime_essence(&content.content_type) == "text/markdown" {
+            "md"
+        } else {
+            "txt"
+        };
+        format!("{stem}.{extension}")
+    } else {
+        content
+            .storage_key
+            .rsplit('/')
+            .next()
+            .filter(|name| !name.is_empty())
+            .unwrap_or(&content.title)
+            .to_owned()
+    }
+}
+
+/// Strong ETag of a content's bytes.
+///
+/// Uses the SHA-256 of the bytes where known, so metadata edits leave it
+/// unchanged.
+fn raw_etag(content: &ContentRow) -> String {
+    if content.kind == "text" {
+        let body = content.body.as_deref().unwrap_or_default();
+        return format!("\"{}\"", hex::encode(Sha256::digest(body.as_bytes())));
+    }
+    match &content.sha256 {
+        Some(sha256) => format!("\"{sha256}\""),
+        None => etag::etag(content.updated_at),
+    }
+}
+
+/// Whether a request for `content` fetches it from its first byte: it has no
+/// `Range` that [`raw_response`] honors, or the range starts at byte 0.
+///
+/// Players and resumed downloads fetch the rest of a content in further
+/// requests, which should not count as accesses of their own.
+pub(crate) fn fetches_from_start(content: &ContentRow, headers: &HeaderMap) -> bool {
+    let len = if content.kind == "text" {
+        content.body.as_deref().map_or(0, str::len) as u64
+    } else {
+        u64::try_from(content.file_size).unwrap_or_default()
+    };
+    match byte_range(honored_range(headers, &raw_etag(content)), len) {
+        ByteRange::Full => true,
+        ByteRange::Partial(range) => range.start == 0,
+        ByteRange::Unsatisfiable => false,
+    }
+}
+
+/// The request's `Range` header, unless an `If-Range` no longer names the
+/// version with `etag`.
+fn honored_range<'a>(headers: &'a HeaderMap, etag: &str) -> Option<&'a str> {
+    match header_str(headers, header::IF_RANGE) {
+        Some(if_range) if if_range != etag => None,
+        _ => header_str(headers, header::RANGE),
+    }
+}
+
+/// How to answer a request's `Range` header.
+#[derive(Debug, PartialEq, Eq)]
+enum ByteRange {
+    /// No usable range: send the whole content.
+    Full,
+    /// Send these bytes with 206.
+    Partial(Range<u64>),
+    /// The range starts past the end: answer 416.
+    Unsatisfiable,
+}
+
+/// Interpret a `Range` header for a content of `len` bytes.
+///
+/// Only a single `bytes` range is supported; anything else (other units,
+/// multiple ranges, malformed values) is ignored and the whole content is
+/// sent, as RFC 9110 allows.
+fn byte_range(range: Option<&str>, len: u64) -> ByteRange {
+    let Some(spec) = range.and_then(|r| r.trim().strip_prefix("bytes=")) else {
+        return ByteRange::Full;
+    };
+    if spec.contains(',') {
+        return ByteRange::Full;
+    }
+    let Some((start, end)) = spec.trim().split_once('-') else {
+        return ByteRange::Full;
+    };
+
+    if start.is_empty() {
+        // Suffix range: the last `end` bytes
+        return match end.parse::<u64>() {
+            Ok(0) => ByteRange::Unsatisfiable,
+            Ok(_) if len == 0 => ByteRange::Unsatisfiable,
+            Ok(suffix) => ByteRange::Partial(len.saturating_sub(suffix)..len),
+            Err(_) => ByteRange::Full,
+        };
+    }
+
+    let Ok(start) = start.parse::<u64>() else {
+        return ByteRange::Full;
+    };
+    let end = match end {
+        "" => len,
+        end => match end.parse::<u64>() {
+            Ok(last) if last >= start => last.saturating_add(1).min(len),
+            _ => return ByteRange::Full,
+        },
+    };
+    if start >= len {
+        return ByteRange::Unsatisfiable;
+    }
+    ByteRange::Partial(start..end)
+}
+
+fn partial_headers(headers: &mut HeaderMap, range: &Range<u64>, len: u64) {
+    headers.insert(
+        header::CONTENT_RANGE,
+        header_value(&format!("bytes {}-{}/{len}", range.start, range.end - 1)),
+    );
+    headers.insert(
+        header::CONTENT_LENGTH,
+        HeaderValue::from(range.end - range.start),
+    );
+}
+
+fn range_not_satisfiable(len: u64) -> axum::response::Response {
+    (
+        StatusCode::RANGE_NOT_SATISFIABLE,
+        [(header::CONTENT_RANGE, format!("bytes */{len}"))],
+    )
+        .into_response()
+}
+
+fn header_str(headers: &HeaderMap, name: header::HeaderName) -> Option<&str> {
+    headers.get(name)?.to_str().ok()
+}
+
+/// A header value from text that may contain characters headers cannot
+/// carry, which are replaced.
+fn header_value(value: &str) -> HeaderValue {
+    HeaderValue::from_str(value).unwrap_or_else(|_| {
+        let printable: String = value
+            .chars()
+            .map(|c| {
+                if c.is_ascii() && !c.is_ascii_control() {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect();
+        HeaderValue::from_str(&printable).unwrap_or(HeaderValue::from_static(""))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_byte_range() {
+        assert_eq!(byte_range(None, 10), ByteRange::Full);
+        assert_eq!(byte_range(Some("bytes=2-4"), 10), ByteRange::Partial(2..5));
+        assert_eq!(byte_range(Some("bytes=2-"), 10), ByteRange::Partial(2..10));
+        assert_eq!(
+            byte_range(Some("bytes=2-99"), 10),
+            ByteRange::Partial(2..10)
+        );
+        assert_eq!(byte_range(Some("bytes=-3"), 10), ByteRange::Partial(7..10));
+        assert_eq!(byte_range(Some("bytes=-30"), 10), ByteRange::Partial(0..10));
+        assert_eq!(byte_range(Some("bytes=10-"), 10), ByteRange::Unsatisfiable);
+        assert_eq!(byte_range(Some("bytes=-0"), 10), ByteRange::Unsatisfiable);
+        assert_eq!(byte_range(Some("bytes=4-2"), 10), ByteRange::Full);
+        assert_eq!(byte_range(Some("bytes=0-1,4-5"), 10), ByteRange::Full);
+        assert_eq!(byte_range(Some("items=0-1"), 10), ByteRange::Full);
+    }
+}
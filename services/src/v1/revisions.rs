@@ -0,0 +1,345 @@
+//! /v1/contents/:id/revisions endpoint handlers.
+//!
+//! Text contents keep their earlier titles, descriptions and bodies as
+//! numbered revisions, recorded by `v1_contents_update`.
+
+use crate::audit::{self, ClientIp};
+use crate::config::Config;
+use crate::database::{ContentRow, ContentsUpdate, SqlStorage};
+use crate::users::routes::AppState;
+use crate::users::session_auth::RequireAuth;
+use crate::users::storage::UserStorage;
+use axum::{
+    Extension, Json,
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+
+use super::types::{
+    V1ContentItem, V1ContentRevisionDiffQuery, V1ContentRevisionDiffResponse,
+    V1ContentRevisionItem, V1ContentRevisionsListResponse, V1DiffLine, V1ErrorResponse,
+};
+
+/// List the earlier versions of a text content.
+#[utoipa::path(
+    get,
+    path = "/v1/contents/{id}/revisions",
+    tag = "content-revisions",
+    params(
+        ("id" = String, Path, description = "Content ID (UUID)")
+    ),
+    responses(
+        (status = 200, description = "Revisions, newest first", body = V1ContentRevisionsListResponse),
+        (status = 400, description = "Invalid content ID format", body = V1ErrorResponse),
+        (status = 401, description = "Unauthorized", body = V1ErrorResponse),
+        (status = 404, description = "Content not found", body = V1ErrorResponse),
+        (status = 500, description = "Internal server error", body = V1ErrorResponse),
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn v1_content_revisions_list<S, U>(
+    State(state): State<AppState<S, U>>,
+    auth: RequireAuth,
+    Path(id): Path<String>,
+) -> impl IntoResponse
+where
+    S: SqlStorage,
+    U: UserStorage,
+{
+    let content = match owned_content(&state, &auth, &id).await {
+        Ok(content) => content,
+        Err(response) => return response,
+    };
+
+    match state.sql_storage.content_revisions_list(content.id).await {
+        Ok(rows) => {
+            let items: Vec<V1ContentRevisionItem> =
+                rows.into_iter().map(V1ContentRevisionItem::from).collect();
+            let total = items.len();
+            (
+                StatusCode::OK,
+                Json(V1ContentRevisionsListResponse { items, total }),
+            )
+                .into_response()
+        }
+        Err(e) => {
+            tracing::error!("Failed to list content revisions: {:?}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(V1ErrorResponse::internal_error(
+                    "Failed to list content revisions",
+                )),
+            )
+                .into_response()
+        }
+    }
+}
+
+/// Show the differences between two versions of a text content.
+///
+/// Diffs revision `from` against revision `to`, or against the current
+/// version when `to` is omitted.
+#[utoipa::path(
+    get,
+    path = "/v1/contents/{id}/revisions/diff",
+    tag = "content-revisions",
+    params(
+        ("id" = String, Path, description = "Content ID (UUID)"),
+        V1ContentRevisionDiffQuery
+    ),
+    responses(
+        (status = 200, description = "Differences between the versions", body = V1ContentRevisionDiffResponse),
+        (status = 400, description = "Bad request", body = V1ErrorResponse),
+        (status = 401, description = "Unauthorized", body = V1ErrorResponse),
+        (status = 404, description = "Content or revision not found", body = V1ErrorResponse),
+        (status = 500, description = "Internal server error", body = V1ErrorResponse),
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn v1_content_revisions_diff<S, U>(
+    State(state): State<AppState<S, U>>,
+    auth: RequireAuth,
+    Path(id): Path<String>,
+    Query(query): Query<V1ContentRevisionDiffQuery>,
+) -> impl IntoResponse
+where
+    S: SqlStorage,
+    U: UserStorage,
+{
+    let content = match owned_content(&state, &auth, &id).await {
+        Ok(content) => content,
+        Err(response) => return response,
+    };
+
+    let from = match revision(&state, content.id, query.from).await {
+        Ok(from) => from,
+        Err(response) => return response,
+    };
+    let to = match query.to {
+        Some(number) => match revision(&state, content.id, number).await {
+            Ok(to) => to,
+            Err(response) => return response,
+        },
+        None => V1ContentRevisionItem {
+            revision: 0,
+            title: content.title,
+            description: content.description,
+            body: content.body.unwrap_or_default(),
+            created_at: content.updated_at.to_rfc3339(),
+        },
+    };
+
+    let lines = diff::lines(&from.body, &to.body)
+        .into_iter()
+        .map(|line| {
+            let (op, text) = match line {
+                diff::Result::Left(text) => ("delete", text),
+                diff::Result::Both(text, _) => ("equal", text),
+                diff::Result::Right(text) => ("insert", text),
+            };
+            V1DiffLine {
+                op: op.to_owned(),
+                text: text.to_owned(),
+            }
+        })
+        .collect();
+
+    (
+        StatusCode::OK,
+        Json(V1ContentRevisionDiffResponse {
+            from: from.revision,
+            to: query.to,
+            title_from: from.title,
+            title_to: to.title,
+            description_from: from.description,
+            description_to: to.description,
+            lines,
+        }),
+    )
+        .into_response()
+}
+
+/// Restore a text content to an earlier revision.
+///
+/// The version being replaced is itself kept as a new revision, so a restore
+/// can be undone.
+#[utoipa::path(
+    post,
+    path = "/v1/contents/{id}/revisions/{revision}/restore",
+    tag = "content-revisions",
+    params(
+        ("id" = String, Path, description = "Content ID (UUID)"),
+        ("revision" = i32, Path, description = "Revision number")
+    ),
+    responses(
+        (status = 200, description = "Content restored", body = V1ContentItem),
+        (status = 400, description = "Bad request", body = V1ErrorResponse),
+        (status = 401, description = "Unauthorized", body = V1ErrorResponse),
+        (status = 404, description = "Content or revision not found", body = V1ErrorResponse),
+        (status = 500, description = "Internal server error", body = V1ErrorResponse),
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn v1_content_revisions_restore<S, U>(
+    State(state): State<AppState<S, U>>,
+    Extension(config): Extension<Config>,
+    auth: RequireAuth,
+    client_ip: ClientIp,
+    Path((id, number)): Path<(String, i32)>,
+) -> impl IntoResponse
+where
+    S: SqlStorage,
+    U: UserStorage,
+{
+    let content = match owned_content(&state, &auth, &id).await {
+        Ok(content) => content,
+        Err(response) => return response,
+    };
+
+    let restored = match revision(&state, content.id, number).await {
+        Ok(restored) => restored,
+        Err(response) => return response,
+    };
+
+    let changes = ContentsUpdate {
+        title: Some(restored.title),
+        description: Some(restored.description),
+        body: Some(restored.body),
+        max_revisions: config.content_revisions_max(),
+        ..Default::default()
+    };
+
+    match state
+        .sql_storage
+        .contents_update_metadata(content.id, content.user_id, changes)
+        .await
+    {
+        Ok(Some(row)) => {
+            audit::record(
+                &state.sql_storage,
+                Some(content.user_id),
+                "content.revision_restore",
+                Some(row.id),
+                Some(serde_json::json!({ "revision": number })),
+                client_ip,
+            )
+            .await;
+            (StatusCode::OK, Json(V1ContentItem::from(row))).into_response()
+        }
+        Ok(None) => (
+            StatusCode::NOT_FOUND,
+            Json(V1ErrorResponse::not_found("Content not found")),
+        )
+            .into_response(),
+        Err(e) => {
+            tracing::error!("Failed to restore content revision: {:?}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(V1ErrorResponse::internal_error(
+                    "Failed to restore content revision",
+                )),
+            )
+                .into_response()
+        }
+    }
+}
+
+/// Look up the authenticated user's text content `id`.
+async fn owned_content<S, U>(
+    state: &AppState<S, U>,
+    auth: &RequireAuth,
+    id: &str,
+) -> Result<ContentRow, Response>
+where
+    S: SqlStorage,
+    U: UserStorage,
+{
+    // Get user ID from username
+    let user = match state.user_storage.get_user(auth.username()).await {
+        Ok(Some(user)) => user,
+        Ok(None) => {
+            return Err((
+                StatusCode::UNAUTHORIZED,
+                Json(V1ErrorResponse::not_found("User not found")),
+            )
+                .into_response());
+        }
+        Err(e) => {
+            tracing::error!("Failed to get user: {}", e);
+            return Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(V1ErrorResponse::internal_error("Failed to get user")),
+            )
+                .into_response());
+        }
+    };
+
+    // Parse content ID
+    let content_id = uuid::Uuid::parse_str(id).map_err(|_| {
+        (
+            StatusCode::BAD_REQUEST,
+            Json(V1ErrorResponse::bad_request("Invalid content ID format")),
+        )
+            .into_response()
+    })?;
+
+    match state.sql_storage.contents_get(content_id).await {
+        Ok(Some(row)) if row.user_id == user.id && row.kind == "text" => Ok(row),
+        Ok(_) => Err((
+            StatusCode::NOT_FOUND,
+            Json(V1ErrorResponse::not_found("Content not found")),
+        )
+            .into_response()),
+        Err(e) => {
+            tracing::error!("Failed to get content: {:?}", e);
+            Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(V1ErrorResponse::internal_error("Failed to get content")),
+            )
+                .into_response())
+        }
+    }
+}
+
+/// Look up revision `number` of `content_id`.
+async fn revision<S, U>(
+    state: &AppState<S, U>,
+    content_id: uuid::Uuid,
+    number: i32,
+) -> Result<V1ContentRevisionItem, Response>
+where
+    S: SqlStorage,
+    U: UserStorage,
+{
+    match state
+        .sql_storage
+        .content_revisions_get(content_id, number)
+        .await
+    {
+        Ok(Some(row)) => Ok(V1ContentRevisionItem::from(row)),
+        Ok(None) => Err((
+            StatusCode::NOT_FOUND,
+            Json(V1ErrorResponse::not_found(format!(
+                "Revision {number} not found"
+            ))),
+        )
+            .into_response()),
+        Err(e) => {
+            tracing::error!("Failed to get content revision: {:?}", e);
+            Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(V1ErrorResponse::internal_error(
+                    "Failed to get content revision",
+                )),
+            )
+                .into_response())
+        }
+    }
+}
@@ -0,0 +1,195 @@
+//! /v1/search endpoint handlers.
+
+use crate::database::{
+    ContentSearchHit, ContentStatus, ContentsSearchParams, SEARCH_HIGHLIGHT_END,
+    SEARCH_HIGHLIGHT_START, SqlStorage,
+};
+use crate::users::routes::AppState;
+use crate::users::session_auth::RequireAuth;
+use crate::users::storage::UserStorage;
+use axum::{
+    Json,
+    extract::{Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+
+use super::types::{V1ContentItem, V1ErrorResponse, V1SearchHit, V1SearchQuery, V1SearchResponse};
+
+/// Maximum length of a search query, in characters.
+const MAX_QUERY_LEN: usize = 256;
+
+/// Search the authenticated user's contents by title, description and text
+/// body.
+///
+/// Words are matched by stem ("notes" finds "note"); results are ranked by
+/// relevance, title matches first.
+#[utoipa::path(
+    get,
+    path = "/v1/search",
+    tag = "search",
+    params(V1SearchQuery),
+    responses(
+        (status = 200, description = "Matching contents", body = V1SearchResponse),
+        (status = 400, description = "Invalid query or filter", body = V1ErrorResponse),
+        (status = 401, description = "Unauthorized", body = V1ErrorResponse),
+        (status = 500, description = "Internal server error", body = V1ErrorResponse),
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn v1_search<S, U>(
+    State(state): State<AppState<S, U>>,
+    auth: RequireAuth,
+    Query(query): Query<V1SearchQuery>,
+) -> impl IntoResponse
+where
+    S: SqlStorage,
+    U: UserStorage,
+{
+    // Get user ID from username
+    let user = match state.user_storage.get_user(auth.username()).await {
+        Ok(Some(user)) => user,
+        Ok(None) => {
+            return (
+                StatusCode::UNAUTHORIZED,
+                Json(V1ErrorResponse::not_found("User not found")),
+            )
+                .into_response();
+        }
+        Err(e) => {
+            tracing::error!("Failed to get user: {}", e);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(V1ErrorResponse::internal_error("Failed to get user")),
+            )
+                .into_response();
+        }
+    };
+
+    let terms = query.q.trim();
+    if terms.is_empty() || terms.chars().count() > MAX_QUERY_LEN {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(V1ErrorResponse::bad_request(format!(
+                "q must be 1 to {MAX_QUERY_LEN} characters"
+            ))),
+        )
+            .into_response();
+    }
+
+    let status = match query.status.as_deref() {
+        None => None,
+        Some("active") => Some(ContentStatus::Active),
+        Some("archived") => Some(ContentStatus::Archived),
+        Some("trashed") => Some(ContentStatus::Trashed),
+        Some(_) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(V1ErrorResponse::bad_request(
+                    "status must be active, archived or trashed",
+                )),
+            )
+                .into_response();
+        }
+    };
+
+    if let Some(kind) = query.kind.as_deref()
+        && kind != "file"
+        && kind != "text"
+    {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(V1ErrorResponse::bad_request("kind must be file or text")),
+        )
+            .into_response();
+    }
+
+    let tag_ids = match query
+        .tags
+        .as_deref()
+        .unwrap_or_default()
+        .split(',')
+        .map(str::trim)
+        .filter(|id| !id.is_empty())
+        .map(uuid::Uuid::parse_str)
+        .collect::<Result<Vec<_>, _>>()
+    {
+        Ok(tag_ids) => tag_ids,
+        Err(_) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(V1ErrorResponse::bad_request("Invalid tag ID format")),
+            )
+                .into_response();
+        }
+    };
+
+    let params = ContentsSearchParams {
+        query: terms.to_owned(),
+        limit: query.limit.unwrap_or(50).clamp(1, 100),
+        offset: query.offset.unwrap_or(0).max(0),
+        status,
+        kind: query.kind,
+        tag_ids,
+    };
+
+    match state.sql_storage.contents_search(user.id, params).await {
+        Ok(hits) => {
+            let items: Vec<V1SearchHit> = hits.into_iter().map(V1SearchHit::from).collect();
+            let total = items.len();
+            (StatusCode::OK, Json(V1SearchResponse { items, total })).into_response()
+        }
+        Err(e) => {
+            tracing::error!("Failed to search contents: {:?}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(V1ErrorResponse::internal_error("Failed to search contents")),
+            )
+                .into_response()
+        }
+    }
+}
+
+impl From<ContentSearchHit> for V1SearchHit {
+    fn from(hit: ContentSearchHit) -> Self {
+        Self {
+            content: V1ContentItem::from(hit.content),
+            rank: hit.rank,
+            title_highlight: highlight_html(&hit.title_highlight),
+            snippet: hit.snippet.as_deref().map(highlight_html),
+        }
+    }
+}
+
+/// HTML-escape `text` and turn the storage highlight markers into `<mark>`.
+fn highlight_html(text: &str) -> String {
+    let mut html = String::with_capacity(text.len() + 16);
+    for c in text.chars() {
+        match c {
+            SEARCH_HIGHLIGHT_START => html.push_str("<mark>"),
+            SEARCH_HIGHLIGHT_END => html.push_str("</mark>"),
+            '&' => html.push_str("&amp;"),
+            '<' => html.push_str("&lt;"),
+            '>' => html.push_str("&gt;"),
+            '"' => html.push_str("&quot;"),
+            '\'' => html.push_str("&#39;"),
+            c => html.push(c),
+        }
+    }
+    html
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_highlight_html_escapes_and_marks() {
+        assert_eq!(
+            highlight_html("a <b>\u{2}cook\u{3}</b> & \u{2}eat\u{3}"),
+            "a &lt;b&gt;<mark>cook</mark>&lt;/b&gt; &amp; <mark>eat</mark>"
+        );
+    }
+}
@@ -1,19 +1,28 @@
 //! /v1/share-links endpoint handlers.
 
-use crate::database::{ShareLinkCreate, ShareLinkUpdate, SqlStorage};
+use crate::audit::{self, ClientIp};
+use crate::config::Config;
+use crate::database::{ShareLinkCreate, ShareLinkRow, ShareLinkUpdate, SqlStorage};
 use crate::users::routes::AppState;
 use crate::users::session_auth::RequireAuth;
 use crate::users::storage::UserStorage;
+use argon2::Argon2;
+use argon2::password_hash::{
+    PasswordHash, PasswordHasher, PasswordVerifier, SaltString, rand_core::OsRng,
+};
 use axum::{
-    Json,
-    extract::{Path, State},
-    http::StatusCode,
+    Extension, Json,
+    extract::{Path, Query, State},
+    http::{HeaderMap, StatusCode, header},
     response::IntoResponse,
 };
 use sha2::{Digest, Sha256};
+use subtle::ConstantTimeEq as _;
 
+use super::etag;
 use super::types::{
     V1ContentShareLinkCreateRequest, V1ErrorResponse, V1GroupShareLinkCreateRequest,
+    V1ShareLinkAccessDay, V1ShareLinkAnalyticsQuery, V1ShareLinkAnalyticsResponse,
     V1ShareLinkCreateRequest, V1ShareLinkResponse, V1ShareLinkUpdateRequest,
     V1ShareLinksListResponse, parse_share_permission,
 };
@@ -21,25 +30,101 @@ use super::types::{
 /// Length of generated share tokens (nanoid).
 const SHARE_TOKEN_LENGTH: usize = 21;
 
-/// Default base URL for share links (can be overridden via config in future).
-const DEFAULT_SHARE_BASE_URL: &str = "https://app.collects.io";
+/// Days of daily access counts in share link analytics by default.
+const DEFAULT_ANALYTICS_DAYS: i64 = 30;
+
+/// Maximum days of daily access counts in share link analytics.
+const MAX_ANALYTICS_DAYS: i64 = 365;
+
+/// Hash a share link password with Argon2id, as a PHC string.
+///
+/// Argon2 is deliberately slow, so it runs on the blocking thread pool.
+async fn hash_password(password: &str) -> String {
+    let password = password.to_owned();
+    tokio::task::spawn_blocking(move || {
+        let salt = SaltString::generate(&mut OsRng);
+        Argon2::default()
+            .hash_password(password.as_bytes(), &salt)
+            .expect("Argon2 with default parameters accepts any password")
+            .to_string()
+    })
+    .await
+    .expect("password hashing task panicked")
+}
 
-/// Hash a password using SHA256 (simple hashing for share link passwords).
-/// Note: For user passwords, we'd use bcrypt, but share link passwords
-/// are typically shorter-lived and this is simpler.
-fn hash_password(password: &str) -> String {
-    let mut hasher = Sha256::new();
-    hasher.update(password.as_bytes());
-    format!("{:x}", hasher.finalize())
+/// Hash an optional share link password.
+async fn hash_optional_password(password: Option<&str>) -> Option<String> {
+    match password {
+        Some(password) => Some(hash_password(password).await),
+        None => None,
+    }
 }
 
 /// Verify a password against a hash.
-pub fn verify_password(password: &str, hash: &str) -> bool {
-    hash_password(password) == hash
+///
+/// Accepts PHC strings as produced by [`hash_password`] as well as legacy
+/// unsalted SHA-256 hex digests; see [`is_legacy_hash`]. Legacy digests are
+/// compared in constant time, and Argon2 runs on the blocking thread pool.
+pub async fn verify_password(password: &str, hash: &str) -> bool {
+    if is_legacy_hash(hash) {
+        let digest = Sha256::digest(password.as_bytes());
+        return hex::decode(hash).is_ok_and(|expected| bool::from(digest.ct_eq(&expected)));
+    }
+
+    let (password, hash) = (password.to_owned(), hash.to_owned());
+    tokio::task::spawn_blocking(move || {
+        PasswordHash::new(&hash).is_ok_and(|parsed| {
+            Argon2::default()
+                .verify_password(password.as_bytes(), &parsed)
+                .is_ok()
+        })
+    })
+    .await
+    .unwrap_or(false)
+}
+
+/// Whether `hash` is a legacy unsalted SHA-256 hex digest, which
+/// [`verify_share_link_password`] replaces with an Argon2id hash.
+fn is_legacy_hash(hash: &str) -> bool {
+    hash.len() == 64 && hash.bytes().all(|b| b.is_ascii_hexdigit())
+}
+
+/// Verify a password against the share link's password hash.
+///
+/// A legacy hash is rehashed with Argon2id once the password verifies, so
+/// existing links are upgraded on their next successful unlock. The rehash is
+/// best-effort: a failure is logged and the password still counts as valid.
+pub(crate) async fn verify_share_link_password<S: SqlStorage>(
+    storage: &S,
+    share_link: &ShareLinkRow,
+    password: &str,
+) -> bool {
+    let Some(password_hash) = share_link.password_hash.as_deref() else {
+        return false;
+    };
+    if !verify_password(password, password_hash).await {
+        return false;
+    }
+
+    if is_legacy_hash(password_hash) {
+        let rehashed = hash_password(password).await;
+        if let Err(e) = storage
+            .share_links_rehash_password(share_link.id, password_hash, &rehashed)
+            .await
+        {
+            tracing::warn!(
+                share_link_id = %share_link.id,
+                error = %e,
+                "Failed to rehash legacy share link password"
+            );
+        }
+    }
+
+    true
 }
 
 /// Generate a unique share token using nanoid.
-fn generate_share_token() -> String {
+pub(crate) fn generate_share_token() -> String {
     nanoid::nanoid!(SHARE_TOKEN_LENGTH)
 }
 
@@ -66,6 +151,7 @@ fn parse_datetime(s: &str) -> Option<chrono::DateTime<chrono::Utc>> {
 )]
 pub async fn v1_share_links_list<S, U>(
     State(state): State<AppState<S, U>>,
+    Extension(config): Extension<Config>,
     auth: RequireAuth,
 ) -> impl IntoResponse
 where
@@ -96,7 +182,7 @@ where
         Ok(rows) => {
             let share_links: Vec<V1ShareLinkResponse> = rows
                 .into_iter()
-                .map(|row| V1ShareLinkResponse::from_row(row, DEFAULT_SHARE_BASE_URL))
+                .map(|row| V1ShareLinkResponse::from_row(row, config.share_base_url()))
                 .collect();
             (
                 StatusCode::OK,
@@ -135,7 +221,9 @@ where
 )]
 pub async fn v1_share_links_create<S, U>(
     State(state): State<AppState<S, U>>,
+    Extension(config): Extension<Config>,
     auth: RequireAuth,
+    client_ip: ClientIp,
     Json(payload): Json<V1ShareLinkCreateRequest>,
 ) -> impl IntoResponse
 where
@@ -196,7 +284,7 @@ where
     };
 
     // Hash password if provided
-    let password_hash = payload.password.as_ref().map(|p| hash_password(p));
+    let password_hash = hash_optional_password(payload.password.as_deref()).await;
 
     // Generate unique token
     let token = generate_share_token();
@@ -212,11 +300,22 @@ where
     };
 
     match state.sql_storage.share_links_create(input).await {
-        Ok(row) => (
-            StatusCode::CREATED,
-            Json(V1ShareLinkResponse::from_row(row, DEFAULT_SHARE_BASE_URL)),
-        )
-            .into_response(),
+        Ok(row) => {
+            audit::record(
+                &state.sql_storage,
+                Some(user.id),
+                "share_link.create",
+                Some(row.id),
+                None,
+                client_ip,
+            )
+            .await;
+            (
+                StatusCode::CREATED,
+                Json(V1ShareLinkResponse::from_row(row, config.share_base_url())),
+            )
+                .into_response()
+        }
         Err(e) => {
             tracing::error!("Failed to create share link: {:?}", e);
             (
@@ -231,6 +330,9 @@ where
 }
 
 /// Get a specific share link by ID.
+///
+/// The `ETag` header identifies this version of the link's settings, for use
+/// with `If-Match` on PATCH and DELETE.
 #[utoipa::path(
     get,
     path = "/v1/share-links/{id}",
@@ -251,6 +353,7 @@ where
 )]
 pub async fn v1_share_links_get<S, U>(
     State(state): State<AppState<S, U>>,
+    Extension(config): Extension<Config>,
     auth: RequireAuth,
     Path(id): Path<String>,
 ) -> impl IntoResponse
@@ -297,7 +400,8 @@ where
     {
         Ok(Some(row)) => (
             StatusCode::OK,
-            Json(V1ShareLinkResponse::from_row(row, DEFAULT_SHARE_BASE_URL)),
+            [(header::ETAG, etag::etag(row.updated_at))],
+            Json(V1ShareLinkResponse::from_row(row, config.share_base_url())),
         )
             .into_response(),
         Ok(None) => (
@@ -316,13 +420,166 @@ where
     }
 }
 
+/// Get access analytics of a share link.
+///
+/// Reports total accesses, unique visitors and the last access, plus daily
+/// counts for the last `days` days (UTC).
+#[utoipa::path(
+    get,
+    path = "/v1/share-links/{id}/analytics",
+    tag = "share-links",
+    params(
+        ("id" = String, Path, description = "Share link ID (UUID)"),
+        V1ShareLinkAnalyticsQuery
+    ),
+    responses(
+        (status = 200, description = "Share link analytics", body = V1ShareLinkAnalyticsResponse),
+        (status = 400, description = "Invalid share link ID format or days", body = V1ErrorResponse),
+        (status = 401, description = "Unauthorized", body = V1ErrorResponse),
+        (status = 404, description = "Share link not found", body = V1ErrorResponse),
+        (status = 500, description = "Internal server error", body = V1ErrorResponse),
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn v1_share_links_analytics<S, U>(
+    State(state): State<AppState<S, U>>,
+    auth: RequireAuth,
+    Path(id): Path<String>,
+    Query(query): Query<V1ShareLinkAnalyticsQuery>,
+) -> impl IntoResponse
+where
+    S: SqlStorage,
+    U: UserStorage,
+{
+    // Resolve user from JWT username
+    let user = match state.user_storage.get_user(auth.username()).await {
+        Ok(Some(user)) => user,
+        Ok(None) => {
+            return (
+                StatusCode::UNAUTHORIZED,
+                Json(V1ErrorResponse::not_found("User not found")),
+            )
+                .into_response();
+        }
+        Err(e) => {
+            tracing::error!("Failed to get user: {}", e);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(V1ErrorResponse::internal_error("Failed to get user")),
+            )
+                .into_response();
+        }
+    };
+
+    // Parse share link ID
+    let share_link_id = match uuid::Uuid::parse_str(&id) {
+        Ok(id) => id,
+        Err(_) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(V1ErrorResponse::bad_request("Invalid share link ID format")),
+            )
+                .into_response();
+        }
+    };
+
+    let days = query.days.unwrap_or(DEFAULT_ANALYTICS_DAYS);
+    if !(1..=MAX_ANALYTICS_DAYS).contains(&days) {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(V1ErrorResponse::bad_request(format!(
+                "days must be between 1 and {MAX_ANALYTICS_DAYS}"
+            ))),
+        )
+            .into_response();
+    }
+
+    // Only the owner sees a link's analytics
+    match state
+        .sql_storage
+        .share_links_get(share_link_id, user.id)
+        .await
+    {
+        Ok(Some(_)) => {}
+        Ok(None) => {
+            return (
+                StatusCode::NOT_FOUND,
+                Json(V1ErrorResponse::not_found("Share link not found")),
+            )
+                .into_response();
+        }
+        Err(e) => {
+            tracing::error!("Failed to get share link: {:?}", e);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(V1ErrorResponse::internal_error("Failed to get share link")),
+            )
+                .into_response();
+        }
+    }
+
+    let today = chrono::Utc::now().date_naive();
+    let first_day = today - chrono::Duration::days(days - 1);
+    let since = first_day.and_time(chrono::NaiveTime::MIN).and_utc();
+
+    let stats = match state
+        .sql_storage
+        .share_link_accesses_stats(share_link_id, since)
+        .await
+    {
+        Ok(stats) => stats,
+        Err(e) => {
+            tracing::error!("Failed to get share link access stats: {:?}", e);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(V1ErrorResponse::internal_error(
+                    "Failed to get share link analytics",
+                )),
+            )
+                .into_response();
+        }
+    };
+
+    // Fill in the days without accesses
+    let daily = first_day
+        .iter_days()
+        .take(days as usize)
+        .map(|date| {
+            let day = stats.daily.iter().find(|d| d.day == date);
+            V1ShareLinkAccessDay {
+                date: date.to_string(),
+                accesses: day.map_or(0, |d| d.accesses),
+                unique_visitors: day.map_or(0, |d| d.unique_visitors),
+            }
+        })
+        .collect();
+
+    (
+        StatusCode::OK,
+        Json(V1ShareLinkAnalyticsResponse {
+            share_link_id: share_link_id.to_string(),
+            total_accesses: stats.total_accesses,
+            unique_visitors: stats.unique_visitors,
+            last_accessed_at: stats.last_accessed_at.map(|t| t.to_rfc3339()),
+            daily,
+        }),
+    )
+        .into_response()
+}
+
 /// Update a share link.
+///
+/// With `If-Match`, the update only applies while the link is unchanged;
+/// otherwise it fails with 412.
 #[utoipa::path(
     patch,
     path = "/v1/share-links/{id}",
     tag = "share-links",
     params(
-        ("id" = String, Path, description = "Share link ID (UUID)")
+        ("id" = String, Path, description = "Share link ID (UUID)"),
+        ("If-Match" = Option<String>, Header, description = "ETag from a previous GET")
     ),
     request_body = V1ShareLinkUpdateRequest,
     responses(
@@ -330,6 +587,7 @@ where
         (status = 400, description = "Bad request", body = V1ErrorResponse),
         (status = 401, description = "Unauthorized", body = V1ErrorResponse),
         (status = 404, description = "Share link not found", body = V1ErrorResponse),
+        (status = 412, description = "Share link changed since it was fetched", body = V1ErrorResponse),
         (status = 500, description = "Internal server error", body = V1ErrorResponse),
     ),
     security(
@@ -338,8 +596,11 @@ where
 )]
 pub async fn v1_share_links_update<S, U>(
     State(state): State<AppState<S, U>>,
+    Extension(config): Extension<Config>,
     auth: RequireAuth,
+    client_ip: ClientIp,
     Path(id): Path<String>,
+    headers: HeaderMap,
     Json(payload): Json<V1ShareLinkUpdateRequest>,
 ) -> impl IntoResponse
 where
@@ -417,13 +678,39 @@ where
 
     // Handle password update
     // Empty string = remove password, non-empty = set new password hash
-    let password_hash = payload.password.as_ref().map(|p| {
-        if p.is_empty() {
-            None // Remove password
-        } else {
-            Some(hash_password(p)) // Set new password
-        }
-    });
+    let password_hash = match payload.password.as_deref() {
+        Some("") => Some(None),                        // Remove password
+        Some(p) => Some(Some(hash_password(p).await)), // Set new password
+        None => None,
+    };
+
+    // Honor If-Match against the share link's current version
+    let expected_updated_at = match etag::if_match(&headers) {
+        None => None,
+        Some(if_match) => match state
+            .sql_storage
+            .share_links_get(share_link_id, user.id)
+            .await
+        {
+            Ok(Some(row)) if etag::matches(if_match, row.updated_at) => Some(row.updated_at),
+            Ok(Some(_)) => return etag::precondition_failed(),
+            Ok(None) => {
+                return (
+                    StatusCode::NOT_FOUND,
+                    Json(V1ErrorResponse::not_found("Share link not found")),
+                )
+                    .into_response();
+            }
+            Err(e) => {
+                tracing::error!("Failed to get share link: {:?}", e);
+                return (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(V1ErrorResponse::internal_error("Failed to get share link")),
+                )
+                    .into_response();
+            }
+        },
+    };
 
     let input = ShareLinkUpdate {
         name: payload.name,
@@ -432,6 +719,7 @@ where
         expires_at,
         max_access_count: payload.max_access_count,
         is_active: payload.is_active,
+        expected_updated_at,
     };
 
     match state
@@ -439,11 +727,24 @@ where
         .share_links_update(share_link_id, user.id, input)
         .await
     {
-        Ok(Some(row)) => (
-            StatusCode::OK,
-            Json(V1ShareLinkResponse::from_row(row, DEFAULT_SHARE_BASE_URL)),
-        )
-            .into_response(),
+        Ok(Some(row)) => {
+            audit::record(
+                &state.sql_storage,
+                Some(user.id),
+                "share_link.update",
+                Some(row.id),
+                None,
+                client_ip,
+            )
+            .await;
+            (
+                StatusCode::OK,
+                [(header::ETAG, etag::etag(row.updated_at))],
+                Json(V1ShareLinkResponse::from_row(row, config.share_base_url())),
+            )
+                .into_response()
+        }
+        Ok(None) if expected_updated_at.is_some() => etag::precondition_failed(),
         Ok(None) => (
             StatusCode::NOT_FOUND,
             Json(V1ErrorResponse::not_found("Share link not found")),
@@ -463,18 +764,23 @@ where
 }
 
 /// Delete a share link.
+///
+/// With `If-Match`, the link is only deleted while it is unchanged;
+/// otherwise it fails with 412.
 #[utoipa::path(
     delete,
     path = "/v1/share-links/{id}",
     tag = "share-links",
     params(
-        ("id" = String, Path, description = "Share link ID (UUID)")
+        ("id" = String, Path, description = "Share link ID (UUID)"),
+        ("If-Match" = Option<String>, Header, description = "ETag from a previous GET")
     ),
     responses(
         (status = 204, description = "Share link deleted"),
         (status = 400, description = "Invalid share link ID format", body = V1ErrorResponse),
         (status = 401, description = "Unauthorized", body = V1ErrorResponse),
         (status = 404, description = "Share link not found", body = V1ErrorResponse),
+        (status = 412, description = "Share link changed since it was fetched", body = V1ErrorResponse),
         (status = 500, description = "Internal server error", body = V1ErrorResponse),
     ),
     security(
@@ -484,7 +790,9 @@ where
 pub async fn v1_share_links_delete<S, U>(
     State(state): State<AppState<S, U>>,
     auth: RequireAuth,
+    client_ip: ClientIp,
     Path(id): Path<String>,
+    headers: HeaderMap,
 ) -> impl IntoResponse
 where
     S: SqlStorage,
@@ -522,12 +830,52 @@ where
         }
     };
 
+    // Honor If-Match against the share link's current version
+    let expected_updated_at = match etag::if_match(&headers) {
+        None => None,
+        Some(if_match) => match state
+            .sql_storage
+            .share_links_get(share_link_id, user.id)
+            .await
+        {
+            Ok(Some(row)) if etag::matches(if_match, row.updated_at) => Some(row.updated_at),
+            Ok(Some(_)) => return etag::precondition_failed(),
+            Ok(None) => {
+                return (
+                    StatusCode::NOT_FOUND,
+                    Json(V1ErrorResponse::not_found("Share link not found")),
+                )
+                    .into_response();
+            }
+            Err(e) => {
+                tracing::error!("Failed to get share link: {:?}", e);
+                return (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(V1ErrorResponse::internal_error("Failed to get share link")),
+                )
+                    .into_response();
+            }
+        },
+    };
+
     match state
         .sql_storage
-        .share_links_delete(share_link_id, user.id)
+        .share_links_delete(share_link_id, user.id, expected_updated_at)
         .await
     {
-        Ok(true) => StatusCode::NO_CONTENT.into_response(),
+        Ok(true) => {
+            audit::record(
+                &state.sql_storage,
+                Some(user.id),
+                "share_link.delete",
+                Some(share_link_id),
+                None,
+                client_ip,
+            )
+            .await;
+            StatusCode::NO_CONTENT.into_response()
+        }
+        Ok(false) if expected_updated_at.is_some() => etag::precondition_failed(),
         Ok(false) => (
             StatusCode::NOT_FOUND,
             Json(V1ErrorResponse::not_found("Share link not found")),
@@ -568,7 +916,9 @@ where
 )]
 pub async fn v1_contents_share_link_create<S, U>(
     State(state): State<AppState<S, U>>,
+    Extension(config): Extension<Config>,
     auth: RequireAuth,
+    client_ip: ClientIp,
     Path(content_id): Path<String>,
     Json(payload): Json<V1ContentShareLinkCreateRequest>,
 ) -> impl IntoResponse
@@ -670,7 +1020,7 @@ where
     };
 
     // Hash password if provided
-    let password_hash = payload.password.as_ref().map(|p| hash_password(p));
+    let password_hash = hash_optional_password(payload.password.as_deref()).await;
 
     // Generate unique token
     let token = generate_share_token();
@@ -710,7 +1060,7 @@ where
         // Clean up the share link we just created
         let _ = state
             .sql_storage
-            .share_links_delete(share_link.id, user.id)
+            .share_links_delete(share_link.id, user.id, None)
             .await;
         return (
             StatusCode::INTERNAL_SERVER_ERROR,
@@ -721,11 +1071,21 @@ where
             .into_response();
     }
 
+    audit::record(
+        &state.sql_storage,
+        Some(user.id),
+        "share_link.create",
+        Some(share_link.id),
+        Some(serde_json::json!({ "content_id": content_uuid })),
+        client_ip,
+    )
+    .await;
+
     (
         StatusCode::CREATED,
         Json(V1ShareLinkResponse::from_row(
             share_link,
-            DEFAULT_SHARE_BASE_URL,
+            config.share_base_url(),
         )),
     )
         .into_response()
@@ -753,7 +1113,9 @@ where
 )]
 pub async fn v1_groups_share_link_create<S, U>(
     State(state): State<AppState<S, U>>,
+    Extension(config): Extension<Config>,
     auth: RequireAuth,
+    client_ip: ClientIp,
     Path(group_id): Path<String>,
     Json(payload): Json<V1GroupShareLinkCreateRequest>,
 ) -> impl IntoResponse
@@ -855,7 +1217,7 @@ where
     };
 
     // Hash password if provided
-    let password_hash = payload.password.as_ref().map(|p| hash_password(p));
+    let password_hash = hash_optional_password(payload.password.as_deref()).await;
 
     // Generate unique token
     let token = generate_share_token();
@@ -895,7 +1257,7 @@ where
         // Clean up the share link we just created
         let _ = state
             .sql_storage
-            .share_links_delete(share_link.id, user.id)
+            .share_links_delete(share_link.id, user.id, None)
             .await;
         return (
             StatusCode::INTERNAL_SERVER_ERROR,
@@ -906,11 +1268,21 @@ where
             .into_response();
     }
 
+    audit::record(
+        &state.sql_storage,
+        Some(user.id),
+        "share_link.create",
+        Some(share_link.id),
+        Some(serde_json::json!({ "group_id": group_uuid })),
+        client_ip,
+    )
+    .await;
+
     (
         StatusCode::CREATED,
         Json(V1ShareLinkResponse::from_row(
             share_link,
-            DEFAULT_SHARE_BASE_URL,
+            config.share_base_url(),
         )),
     )
         .into_response()
@@ -920,28 +1292,37 @@ where
 mod tests {
     use super::*;
 
-    #[test]
-    fn test_hash_password() {
+    #[tokio::test]
+    async fn test_hash_password() {
         let password = "secret123";
-        let hash = hash_password(password);
+        let hash = hash_password(password).await;
 
-        // Hash should be consistent
-        assert_eq!(hash, hash_password(password));
+        // Hash should be an Argon2id PHC string
+        assert!(hash.starts_with("$argon2id$"));
+        assert!(!is_legacy_hash(&hash));
 
-        // Hash should be 64 hex characters (SHA256)
-        assert_eq!(hash.len(), 64);
-
-        // Different passwords should have different hashes
-        assert_ne!(hash, hash_password("different"));
+        // Hashes should be salted
+        assert_ne!(hash, hash_password(password).await);
     }
 
-    #[test]
-    fn test_verify_password() {
+    #[tokio::test]
+    async fn test_verify_password() {
         let password = "secret123";
-        let hash = hash_password(password);
+        let hash = hash_password(password).await;
+
+        assert!(verify_password(password, &hash).await);
+        assert!(!verify_password("wrong", &hash).await);
+        assert!(!verify_password(password, "not-a-hash").await);
+    }
+
+    #[tokio::test]
+    async fn test_verify_legacy_password() {
+        let hash = format!("{:x}", Sha256::digest(b"secret123"));
+        assert!(is_legacy_hash(&hash));
 
-        assert!(verify_password(password, &hash));
-        assert!(!verify_password("wrong", &hash));
+        assert!(verify_password("secret123", &hash).await);
+        assert!(verify_password("secret123", &hash.to_ascii_uppercase()).await);
+        assert!(!verify_password("wrong", &hash).await);
     }
 
     #[test]
@@ -0,0 +1,334 @@
+//! Password checks and unlock tokens for password-protected share links.
+//!
+//! Password attempts are rate-limited per link and per IP address, like OTP
+//! logins. A correct password earns a short-lived unlock token, signed with
+//! the JWT secret, so later requests don't resend the password. Clients pass
+//! it back in the `X-Share-Unlock` header (or the `unlock_token` field of
+//! view-url requests); browsers get it as a cookie.
+//!
+//! Tokens are bound to the link's `updated_at`, so changing the link's
+//! settings, including its password, revokes them.
+
+use axum::{
+    Json,
+    http::{HeaderMap, HeaderValue, StatusCode, header},
+    response::{IntoResponse, Response},
+};
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+use crate::audit::ClientIp;
+use crate::config::Config;
+use crate::database::{ShareLinkPasswordRateLimitConfig, ShareLinkRow, SqlStorage};
+
+use super::share_links::verify_share_link_password;
+use super::types::V1ErrorResponse;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Header carrying an unlock token.
+pub const SHARE_UNLOCK_HEADER: &str = "x-share-unlock";
+
+/// Prefix of unlock cookies, followed by the share token.
+const SHARE_UNLOCK_COOKIE_PREFIX: &str = "share_unlock_";
+
+/// Unlock token expiration time in seconds (1 hour).
+pub const SHARE_UNLOCK_EXPIRY_SECS: i64 = 60 * 60;
+
+/// A signed token unlocking a password-protected share link.
+#[derive(Debug, Clone)]
+pub(crate) struct UnlockToken {
+    pub(crate) token: String,
+    pub(crate) expires_at: DateTime<Utc>,
+}
+
+/// Why a password-protected share link stays locked.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum PasswordError {
+    /// Neither a password nor a valid unlock token was sent.
+    Required,
+    /// The password is wrong.
+    Incorrect,
+    /// Too many wrong passwords, for the link or from the IP address.
+    RateLimited,
+}
+
+impl PasswordError {
+    pub(crate) fn status(self) -> StatusCode {
+        match self {
+            Self::Required | Self::Incorrect => StatusCode::UNAUTHORIZED,
+            Self::RateLimited => StatusCode::TOO_MANY_REQUESTS,
+        }
+    }
+
+    pub(crate) fn message(self) -> &'static str {
+        match self {
+            Self::Required => "Password required for this share link",
+            Self::Incorrect => "Invalid password",
+            Self::RateLimited => "Too many attempts. Please try again later.",
+        }
+    }
+}
+
+impl IntoResponse for PasswordError {
+    fn into_response(self) -> Response {
+        let body = match self {
+            Self::Required | Self::Incorrect => V1ErrorResponse::bad_request(self.message()),
+            Self::RateLimited => V1ErrorResponse {
+                error: "too_many_requests".to_owned(),
+                message: self.message().to_owned(),
+            },
+        };
+        (self.status(), Json(body)).into_response()
+    }
+}
+
+/// Check access to a share link's password protection.
+///
+/// Returns `Ok(None)` when the link has no password or `unlock_token` is
+/// valid, and a fresh unlock token when `password` is correct. Password
+/// attempts are rate-limited: each is reserved, and counted as failed, before
+/// the password is verified. Like OTP logins, a failing rate limit check is
+/// logged and lets the attempt through.
+pub(crate) async fn check_share_link_password<S: SqlStorage>(
+    storage: &S,
+    config: &Config,
+    share_link: &ShareLinkRow,
+    password: Option<&str>,
+    unlock_token: Option<&str>,
+    ClientIp(ip_address): ClientIp,
+) -> Result<Option<UnlockToken>, PasswordError> {
+    if share_link.password_hash.is_none() {
+        return Ok(None);
+    }
+    if unlock_token.is_some_and(|token| verify_unlock_token(share_link, config.jwt_secret(), token))
+    {
+        return Ok(None);
+    }
+    let Some(password) = password else {
+        return Err(PasswordError::Required);
+    };
+
+    let attempt_id = match storage
+        .share_link_password_reserve_attempt(
+            share_link.id,
+            ip_address,
+            &ShareLinkPasswordRateLimitConfig::default(),
+        )
+        .await
+    {
+        Ok(Some(attempt_id)) => Some(attempt_id),
+        Ok(None) => {
+            tracing::warn!(
+                share_link_id = %share_link.id,
+                client_ip = ?ip_address,
+                "Share link password attempt blocked due to rate limiting"
+            );
+            return Err(PasswordError::RateLimited);
+        }
+        Err(e) => {
+            tracing::error!("Failed to check share link password rate limit: {}", e);
+            None
+        }
+    };
+
+    if !verify_share_link_password(storage, share_link, password).await {
+        return Err(PasswordError::Incorrect);
+    }
+
+    // Best-effort: left failed, the attempt only counts towards the limits
+    if let Some(attempt_id) = attempt_id
+        && let Err(e) = storage
+            .share_link_password_attempt_succeeded(attempt_id)
+            .await
+    {
+        tracing::error!(
+            share_link_id = %share_link.id,
+            error = %e,
+            "Failed to record successful share link password attempt"
+        );
+    }
+    Ok(Some(issue_unlock_token(share_link, config.jwt_secret())))
+}
+
+/// Build the MAC of an unlock token expiring at `expires`.
+fn mac(share_link: &ShareLinkRow, secret: &str, expires: i64) -> HmacSha256 {
+    let mut mac =
+        HmacSha256::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any length");
+    mac.update(
+        format!(
+            "share-unlock\n{}\n{}\n{expires}",
+            share_link.id,
+            share_link.updated_at.timestamp_micros()
+        )
+        .as_bytes(),
+    );
+    mac
+}
+
+/// Issue an unlock token for `share_link`.
+pub(crate) fn issue_unlock_token(share_link: &ShareLinkRow, secret: &str) -> UnlockToken {
+    let expires_at = Utc::now() + chrono::Duration::seconds(SHARE_UNLOCK_EXPIRY_SECS);
+    let expires = expires_at.timestamp();
+    let signature = hex::encode(mac(share_link, secret, expires).finalize().into_bytes());
+
+    UnlockToken {
+        token: format!("{expires}.{signature}"),
+        expires_at,
+    }
+}
+
+/// Verify an unlock token.
+///
+/// Returns false if the token is malformed, doesn't match the link, or has
+/// expired.
+pub(crate) fn verify_unlock_token(share_link: &ShareLinkRow, secret: &str, token: &str) -> bool {
+    let Some((expires, signature)) = token.split_once('.') else {
+        return false;
+    };
+    let Ok(expires) = expires.parse::<i64>() else {
+        return false;
+    };
+    if expires < Utc::now().timestamp() {
+        return false;
+    }
+    let Ok(signature) = hex::decode(signature) else {
+        return false;
+    };
+
+    mac(share_link, secret, expires)
+        .verify_slice(&signature)
+        .is_ok()
+}
+
+/// The unlock token sent with a request, from the `X-Share-Unlock` header or
+/// else the link's unlock cookie.
+pub(crate) fn unlock_token_from_headers<'a>(
+    headers: &'a HeaderMap,
+    share_token: &str,
+) -> Option<&'a str> {
+    if let Some(token) = headers
+        .get(SHARE_UNLOCK_HEADER)
+        .and_then(|value| value.to_str().ok())
+    {
+        return Some(token);
+    }
+
+    let cookie_name = format!("{SHARE_UNLOCK_COOKIE_PREFIX}{share_token}");
+    headers
+        .get_all(header::COOKIE)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|cookies| cookies.split(';'))
+        .find_map(|cookie| {
+            let (name, value) = cookie.trim().split_once('=')?;
+            (name == cookie_name).then_some(value)
+        })
+}
+
+/// Set the unlock cookie of `share_link` on a response.
+///
+/// The cookie lets browsers follow plain links, such as the group archive
+/// download, without sending the password again.
+pub(crate) fn set_unlock_cookie(
+    response: &mut Response,
+    share_link: &ShareLinkRow,
+    unlock: &UnlockToken,
+    config: &Config,
+) {
+    let secure = if config.is_local() { "" } else { "; Secure" };
+    let cookie = format!(
+        "{SHARE_UNLOCK_COOKIE_PREFIX}{}={}; Path=/; Max-Age={SHARE_UNLOCK_EXPIRY_SECS}; HttpOnly; SameSite=Lax{secure}",
+        share_link.token, unlock.token
+    );
+
+    match HeaderValue::from_str(&cookie) {
+        Ok(value) => {
+            response.headers_mut().append(header::SET_COOKIE, value);
+        }
+        Err(e) => tracing::warn!("Failed to build share unlock cookie: {}", e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_share_link() -> ShareLinkRow {
+        ShareLinkRow {
+            id: uuid::Uuid::new_v4(),
+            owner_id: uuid::Uuid::new_v4(),
+            token: "abc_DEF-123".to_owned(),
+            name: None,
+            permission: "view".to_owned(),
+            password_hash: Some("hash".to_owned()),
+            max_access_count: None,
+            access_count: 0,
+            expires_at: None,
+            is_active: true,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_unlock_token_round_trip() {
+        let share_link = make_share_link();
+        let unlock = issue_unlock_token(&share_link, "secret");
+
+        assert!(verify_unlock_token(&share_link, "secret", &unlock.token));
+        assert!(!verify_unlock_token(&share_link, "other", &unlock.token));
+        assert!(!verify_unlock_token(
+            &make_share_link(),
+            "secret",
+            &unlock.token
+        ));
+        assert!(!verify_unlock_token(&share_link, "secret", "garbage"));
+    }
+
+    #[test]
+    fn test_unlock_token_revoked_by_update() {
+        let mut share_link = make_share_link();
+        let unlock = issue_unlock_token(&share_link, "secret");
+
+        share_link.updated_at += chrono::Duration::seconds(1);
+        assert!(!verify_unlock_token(&share_link, "secret", &unlock.token));
+    }
+
+    #[test]
+    fn test_unlock_token_expired() {
+        let share_link = make_share_link();
+        let expires = Utc::now().timestamp() - 1;
+        let signature = hex::encode(mac(&share_link, "secret", expires).finalize().into_bytes());
+
+        assert!(!verify_unlock_token(
+            &share_link,
+            "secret",
+            &format!("{expires}.{signature}")
+        ));
+    }
+
+    #[test]
+    fn test_unlock_token_from_headers() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(unlock_token_from_headers(&headers, "abc"), None);
+
+        headers.insert(
+            header::COOKIE,
+            "other=1; share_unlock_xyz=wrong; share_unlock_abc=from-cookie"
+                .parse()
+                .unwrap(),
+        );
+        assert_eq!(
+            unlock_token_from_headers(&headers, "abc"),
+            Some("from-cookie")
+        );
+
+        headers.insert(SHARE_UNLOCK_HEADER, "from-header".parse().unwrap());
+        assert_eq!(
+            unlock_token_from_headers(&headers, "abc"),
+            Some("from-header")
+        );
+    }
+}
@@ -0,0 +1,682 @@
+//! Direct shares of contents and groups with other users.
+//!
+//! The owner of a content or group can share it with a username at "view" or
+//! "download" permission; sharing it again with the same user updates the
+//! permission. Recipients find the item under `/v1/shared-with-me`, and
+//! [`content_access`] lets them read a shared content (or a content of a
+//! shared group) through `GET /v1/contents/{id}` and its view-url endpoint.
+
+use crate::audit::{self, ClientIp};
+use crate::database::{
+    ContentRow, ContentShareCreateForUser, GroupShareCreateForUser, SqlStorage, SqlStorageError,
+};
+use crate::users::routes::AppState;
+use crate::users::session_auth::RequireAuth;
+use crate::users::storage::{StoredUser, UserStorage};
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+
+use super::types::{
+    V1ErrorResponse, V1SharedWithMeItem, V1SharedWithMeResponse, V1UserShareCreateRequest,
+    V1UserShareResponse, V1UserSharesListResponse, parse_share_permission,
+};
+
+/// How a user may access a content.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ContentAccess {
+    /// The user owns the content.
+    Owner,
+    /// The content is shared with the user at "download" permission.
+    Download,
+    /// The content is shared with the user at "view" permission: it can only
+    /// be viewed inline.
+    View,
+}
+
+/// How `user_id` may access `content`, or `None` if it may not.
+///
+/// Other users have access through direct shares of the content or of a group
+/// containing it, unless the content is in its owner's trash.
+pub(crate) async fn content_access<S: SqlStorage>(
+    storage: &S,
+    content: &ContentRow,
+    user_id: uuid::Uuid,
+) -> Result<Option<ContentAccess>, SqlStorageError> {
+    if content.user_id == user_id {
+        return Ok(Some(ContentAccess::Owner));
+    }
+    if content.status == "trashed" {
+        return Ok(None);
+    }
+
+    let permission = storage
+        .content_shares_permission_for_user(content.id, user_id)
+        .await?;
+    Ok(match permission.as_deref() {
+        Some("download") => Some(ContentAccess::Download),
+        Some(_) => Some(ContentAccess::View),
+        None => None,
+    })
+}
+
+/// A kind of item that can be shared directly with users.
+#[derive(Debug, Clone, Copy)]
+enum SharedItem {
+    Content,
+    Group,
+}
+
+impl SharedItem {
+    /// Entity type of the item, as used in audit log actions.
+    fn entity_type(self) -> &'static str {
+        match self {
+            Self::Content => "content",
+            Self::Group => "group",
+        }
+    }
+
+    fn not_found(self) -> Response {
+        let message = match self {
+            Self::Content => "Content not found",
+            Self::Group => "Group not found",
+        };
+        (
+            StatusCode::NOT_FOUND,
+            Json(V1ErrorResponse::not_found(message)),
+        )
+            .into_response()
+    }
+}
+
+/// List the users a content is shared with.
+#[utoipa::path(
+    get,
+    path = "/v1/contents/{id}/shares",
+    tag = "shares",
+    params(
+        ("id" = String, Path, description = "Content ID (UUID)")
+    ),
+    responses(
+        (status = 200, description = "Direct shares of the content", body = V1UserSharesListResponse),
+        (status = 400, description = "Invalid content ID format", body = V1ErrorResponse),
+        (status = 401, description = "Unauthorized", body = V1ErrorResponse),
+        (status = 404, description = "Content not found", body = V1ErrorResponse),
+        (status = 500, description = "Internal server error", body = V1ErrorResponse),
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn v1_contents_shares_list<S, U>(
+    State(state): State<AppState<S, U>>,
+    auth: RequireAuth,
+    Path(id): Path<String>,
+) -> impl IntoResponse
+where
+    S: SqlStorage,
+    U: UserStorage,
+{
+    shares_list(&state, &auth, SharedItem::Content, &id).await
+}
+
+/// Share a content with a user.
+///
+/// Sharing a content again with the same user updates the permission.
+#[utoipa::path(
+    post,
+    path = "/v1/contents/{id}/shares",
+    tag = "shares",
+    params(
+        ("id" = String, Path, description = "Content ID (UUID)")
+    ),
+    request_body = V1UserShareCreateRequest,
+    responses(
+        (status = 201, description = "Content shared", body = V1UserShareResponse),
+        (status = 400, description = "Bad request", body = V1ErrorResponse),
+        (status = 401, description = "Unauthorized", body = V1ErrorResponse),
+        (status = 404, description = "Content or user not found", body = V1ErrorResponse),
+        (status = 500, description = "Internal server error", body = V1ErrorResponse),
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn v1_contents_shares_create<S, U>(
+    State(state): State<AppState<S, U>>,
+    auth: RequireAuth,
+    client_ip: ClientIp,
+    Path(id): Path<String>,
+    Json(payload): Json<V1UserShareCreateRequest>,
+) -> impl IntoResponse
+where
+    S: SqlStorage,
+    U: UserStorage,
+{
+    share_create(&state, &auth, client_ip, SharedItem::Content, &id, payload).await
+}
+
+/// Stop sharing a content with a user.
+#[utoipa::path(
+    delete,
+    path = "/v1/contents/{id}/shares/{username}",
+    tag = "shares",
+    params(
+        ("id" = String, Path, description = "Content ID (UUID)"),
+        ("username" = String, Path, description = "Username the content is shared with")
+    ),
+    responses(
+        (status = 204, description = "Share revoked"),
+        (status = 400, description = "Invalid content ID format", body = V1ErrorResponse),
+        (status = 401, description = "Unauthorized", body = V1ErrorResponse),
+        (status = 404, description = "Content or share not found", body = V1ErrorResponse),
+        (status = 500, description = "Internal server error", body = V1ErrorResponse),
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn v1_contents_shares_delete<S, U>(
+    State(state): State<AppState<S, U>>,
+    auth: RequireAuth,
+    client_ip: ClientIp,
+    Path((id, username)): Path<(String, String)>,
+) -> impl IntoResponse
+where
+    S: SqlStorage,
+    U: UserStorage,
+{
+    share_delete(
+        &state,
+        &auth,
+        client_ip,
+        SharedItem::Content,
+        &id,
+        &username,
+    )
+    .await
+}
+
+/// List the users a group is shared with.
+#[utoipa::path(
+    get,
+    path = "/v1/groups/{id}/shares",
+    tag = "shares",
+    params(
+        ("id" = String, Path, description = "Group ID (UUID)")
+    ),
+    responses(
+        (status = 200, description = "Direct shares of the group", body = V1UserSharesListResponse),
+        (status = 400, description = "Invalid group ID format", body = V1ErrorResponse),
+        (status = 401, description = "Unauthorized", body = V1ErrorResponse),
+        (status = 404, description = "Group not found", body = V1ErrorResponse),
+        (status = 500, description = "Internal server error", body = V1ErrorResponse),
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn v1_groups_shares_list<S, U>(
+    State(state): State<AppState<S, U>>,
+    auth: RequireAuth,
+    Path(id): Path<String>,
+) -> impl IntoResponse
+where
+    S: SqlStorage,
+    U: UserStorage,
+{
+    shares_list(&state, &auth, SharedItem::Group, &id).await
+}
+
+/// Share a group with a user.
+///
+/// The user can then access the contents of the group. Sharing a group again
+/// with the same user updates the permission.
+#[utoipa::path(
+    post,
+    path = "/v1/groups/{id}/shares",
+    tag = "shares",
+    params(
+        ("id" = String, Path, description = "Group ID (UUID)")
+    ),
+    request_body = V1UserShareCreateRequest,
+    responses(
+        (status = 201, description = "Group shared", body = V1UserShareResponse),
+        (status = 400, description = "Bad request", body = V1ErrorResponse),
+        (status = 401, description = "Unauthorized", body = V1ErrorResponse),
+        (status = 404, description = "Group or user not found", body = V1ErrorResponse),
+        (status = 500, description = "Internal server error", body = V1ErrorResponse),
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn v1_groups_shares_create<S, U>(
+    State(state): State<AppState<S, U>>,
+    auth: RequireAuth,
+    client_ip: ClientIp,
+    Path(id): Path<String>,
+    Json(payload): Json<V1UserShareCreateRequest>,
+) -> impl IntoResponse
+where
+    S: SqlStorage,
+    U: UserStorage,
+{
+    share_create(&state, &auth, client_ip, SharedItem::Group, &id, payload).await
+}
+
+/// Stop sharing a group with a user.
+#[utoipa::path(
+    delete,
+    path = "/v1/groups/{id}/shares/{username}",
+    tag = "shares",
+    params(
+        ("id" = String, Path, description = "Group ID (UUID)"),
+        ("username" = String, Path, description = "Username the group is shared with")
+    ),
+    responses(
+        (status = 204, description = "Share revoked"),
+        (status = 400, description = "Invalid group ID format", body = V1ErrorResponse),
+        (status = 401, description = "Unauthorized", body = V1ErrorResponse),
+        (status = 404, description = "Group or share not found", body = V1ErrorResponse),
+        (status = 500, description = "Internal server error", body = V1ErrorResponse),
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn v1_groups_shares_delete<S, U>(
+    State(state): State<AppState<S, U>>,
+    auth: RequireAuth,
+    client_ip: ClientIp,
+    Path((id, username)): Path<(String, String)>,
+) -> impl IntoResponse
+where
+    S: SqlStorage,
+    U: UserStorage,
+{
+    share_delete(&state, &auth, client_ip, SharedItem::Group, &id, &username).await
+}
+
+/// List the contents and groups other users shared with the current user.
+///
+/// Trashed items are left out.
+#[utoipa::path(
+    get,
+    path = "/v1/shared-with-me",
+    tag = "shares",
+    responses(
+        (status = 200, description = "Items shared with the current user", body = V1SharedWithMeResponse),
+        (status = 401, description = "Unauthorized", body = V1ErrorResponse),
+        (status = 500, description = "Internal server error", body = V1ErrorResponse),
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn v1_shared_with_me<S, U>(
+    State(state): State<AppState<S, U>>,
+    auth: RequireAuth,
+) -> impl IntoResponse
+where
+    S: SqlStorage,
+    U: UserStorage,
+{
+    let user = match current_user(&state, &auth).await {
+        Ok(user) => user,
+        Err(response) => return response,
+    };
+
+    match state
+        .sql_storage
+        .shares_list_shared_with_user(user.id)
+        .await
+    {
+        Ok(rows) => (
+            StatusCode::OK,
+            Json(V1SharedWithMeResponse {
+                items: rows.into_iter().map(V1SharedWithMeItem::from).collect(),
+            }),
+        )
+            .into_response(),
+        Err(e) => {
+            tracing::error!("Failed to list shared items: {:?}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(V1ErrorResponse::internal_error(
+                    "Failed to list shared items",
+                )),
+            )
+                .into_response()
+        }
+    }
+}
+
+async fn shares_list<S, U>(
+    state: &AppState<S, U>,
+    auth: &RequireAuth,
+    item: SharedItem,
+    id: &str,
+) -> Response
+where
+    S: SqlStorage,
+    U: UserStorage,
+{
+    let user = match current_user(state, auth).await {
+        Ok(user) => user,
+        Err(response) => return response,
+    };
+    let item_id = match owned_item(state, &user, item, id).await {
+        Ok(item_id) => item_id,
+        Err(response) => return response,
+    };
+
+    let result = match item {
+        SharedItem::Content => {
+            state
+                .sql_storage
+                .content_shares_list_for_users(item_id)
+                .await
+        }
+        SharedItem::Group => state.sql_storage.group_shares_list_for_users(item_id).await,
+    };
+    match result {
+        Ok(rows) => (
+            StatusCode::OK,
+            Json(V1UserSharesListResponse {
+                shares: rows.into_iter().map(V1UserShareResponse::from).collect(),
+            }),
+        )
+            .into_response(),
+        Err(e) => {
+            tracing::error!("Failed to list shares: {:?}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(V1ErrorResponse::internal_error("Failed to list shares")),
+            )
+                .into_response()
+        }
+    }
+}
+
+async fn share_create<S, U>(
+    state: &AppState<S, U>,
+    auth: &RequireAuth,
+    client_ip: ClientIp,
+    item: SharedItem,
+    id: &str,
+    payload: V1UserShareCreateRequest,
+) -> Response
+where
+    S: SqlStorage,
+    U: UserStorage,
+{
+    let user = match current_user(state, auth).await {
+        Ok(user) => user,
+        Err(response) => return response,
+    };
+    let item_id = match owned_item(state, &user, item, id).await {
+        Ok(item_id) => item_id,
+        Err(response) => return response,
+    };
+
+    let Some(permission) = parse_share_permission(&payload.permission) else {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(V1ErrorResponse::bad_request(format!(
+                "Invalid permission: {}. Must be 'view' or 'download'",
+                payload.permission
+            ))),
+        )
+            .into_response();
+    };
+
+    let recipient = match state.user_storage.get_user(&payload.username).await {
+        Ok(Some(recipient)) => recipient,
+        Ok(None) => {
+            return (
+                StatusCode::NOT_FOUND,
+                Json(V1ErrorResponse::not_found("User not found")),
+            )
+                .into_response();
+        }
+        Err(e) => {
+            tracing::error!("Failed to get user: {}", e);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(V1ErrorResponse::internal_error("Failed to get user")),
+            )
+                .into_response();
+        }
+    };
+    if recipient.id == user.id {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(V1ErrorResponse::bad_request("Cannot share with yourself")),
+        )
+            .into_response();
+    }
+
+    let result = match item {
+        SharedItem::Content => state
+            .sql_storage
+            .content_shares_create_for_user(ContentShareCreateForUser {
+                content_id: item_id,
+                shared_with_user_id: recipient.id,
+                permission,
+                created_by: user.id,
+            })
+            .await
+            .map(|row| (row.id, row.permission, row.created_at)),
+        SharedItem::Group => state
+            .sql_storage
+            .group_shares_create_for_user(GroupShareCreateForUser {
+                group_id: item_id,
+                shared_with_user_id: recipient.id,
+                permission,
+                created_by: user.id,
+            })
+            .await
+            .map(|row| (row.id, row.permission, row.created_at)),
+    };
+    let (share_id, permission, created_at) = match result {
+        Ok(share) => share,
+        Err(e) => {
+            tracing::error!("Failed to create share: {:?}", e);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(V1ErrorResponse::internal_error("Failed to create share")),
+            )
+                .into_response();
+        }
+    };
+
+    audit::record(
+        &state.sql_storage,
+        Some(user.id),
+        &format!("{}.share", item.entity_type()),
+        Some(item_id),
+        Some(serde_json::json!({
+            "username": recipient.username,
+            "permission": permission,
+        })),
+        client_ip,
+    )
+    .await;
+
+    (
+        StatusCode::CREATED,
+        Json(V1UserShareResponse {
+            id: share_id.to_string(),
+            username: recipient.username,
+            permission,
+            created_at: created_at.to_rfc3339(),
+        }),
+    )
+        .into_response()
+}
+
+async fn share_delete<S, U>(
+    state: &AppState<S, U>,
+    auth: &RequireAuth,
+    client_ip: ClientIp,
+    item: SharedItem,
+    id: &str,
+    username: &str,
+) -> Response
+where
+    S: SqlStorage,
+    U: UserStorage,
+{
+    let user = match current_user(state, auth).await {
+        Ok(user) => user,
+        Err(response) => return response,
+    };
+    let item_id = match owned_item(state, &user, item, id).await {
+        Ok(item_id) => item_id,
+        Err(response) => return response,
+    };
+
+    let share_not_found = || {
+        (
+            StatusCode::NOT_FOUND,
+            Json(V1ErrorResponse::not_found("Share not found")),
+        )
+            .into_response()
+    };
+
+    let recipient = match state.user_storage.get_user(username).await {
+        Ok(Some(recipient)) => recipient,
+        Ok(None) => return share_not_found(),
+        Err(e) => {
+            tracing::error!("Failed to get user: {}", e);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(V1ErrorResponse::internal_error("Failed to get user")),
+            )
+                .into_response();
+        }
+    };
+
+    let result = match item {
+        SharedItem::Content => {
+            state
+                .sql_storage
+                .content_shares_delete_for_user(item_id, recipient.id)
+                .await
+        }
+        SharedItem::Group => {
+            state
+                .sql_storage
+                .group_shares_delete_for_user(item_id, recipient.id)
+                .await
+        }
+    };
+    match result {
+        Ok(true) => {
+            audit::record(
+                &state.sql_storage,
+                Some(user.id),
+                &format!("{}.unshare", item.entity_type()),
+                Some(item_id),
+                Some(serde_json::json!({ "username": recipient.username })),
+                client_ip,
+            )
+            .await;
+            StatusCode::NO_CONTENT.into_response()
+        }
+        Ok(false) => share_not_found(),
+        Err(e) => {
+            tracing::error!("Failed to delete share: {:?}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(V1ErrorResponse::internal_error("Failed to delete share")),
+            )
+                .into_response()
+        }
+    }
+}
+
+/// Look up the authenticated user.
+async fn current_user<S, U>(
+    state: &AppState<S, U>,
+    auth: &RequireAuth,
+) -> Result<StoredUser, Response>
+where
+    S: SqlStorage,
+    U: UserStorage,
+{
+    match state.user_storage.get_user(auth.username()).await {
+        Ok(Some(user)) => Ok(user),
+        Ok(None) => Err((
+            StatusCode::UNAUTHORIZED,
+            Json(V1ErrorResponse::not_found("User not found")),
+        )
+            .into_response()),
+        Err(e) => {
+            tracing::error!("Failed to get user: {}", e);
+            Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(V1ErrorResponse::internal_error("Failed to get user")),
+            )
+                .into_response())
+        }
+    }
+}
+
+/// Parse `id` as the ID of a content or group owned by `user`.
+///
+/// Items of other users are reported as not found.
+async fn owned_item<S, U>(
+    state: &AppState<S, U>,
+    user: &StoredUser,
+    item: SharedItem,
+    id: &str,
+) -> Result<uuid::Uuid, Response>
+where
+    S: SqlStorage,
+    U: UserStorage,
+{
+    let Ok(item_id) = uuid::Uuid::parse_str(id) else {
+        let message = match item {
+            SharedItem::Content => "Invalid content ID format",
+            SharedItem::Group => "Invalid group ID format",
+        };
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(V1ErrorResponse::bad_request(message)),
+        )
+            .into_response());
+    };
+
+    let owner_id = match item {
+        SharedItem::Content => state
+            .sql_storage
+            .contents_get(item_id)
+            .await
+            .map(|row| row.map(|content| content.user_id)),
+        SharedItem::Group => state
+            .sql_storage
+            .groups_get(item_id)
+            .await
+            .map(|row| row.map(|group| group.user_id)),
+    };
+    match owner_id {
+        Ok(Some(owner_id)) if owner_id == user.id => Ok(item_id),
+        Ok(_) => Err(item.not_found()),
+        Err(e) => {
+            tracing::error!("Failed to get {}: {:?}", item.entity_type(), e);
+            Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(V1ErrorResponse::internal_error(format!(
+                    "Failed to get {}",
+                    item.entity_type()
+                ))),
+            )
+                .into_response())
+        }
+    }
+}
@@ -1,5 +1,6 @@
 //! /v1/tags endpoint handlers.
 
+use crate::audit::{self, ClientIp};
 use crate::database::{self, SqlStorage, SqlStorageError};
 use crate::users::routes::AppState;
 use crate::users::session_auth::RequireAuth;
@@ -7,10 +8,11 @@ use crate::users::storage::UserStorage;
 use axum::{
     Json,
     extract::{Path, State},
-    http::StatusCode,
+    http::{HeaderMap, StatusCode, header},
     response::IntoResponse,
 };
 
+use super::etag;
 use super::types::{
     V1ErrorResponse, V1TagCreateRequest, V1TagItem, V1TagUpdateRequest, V1TagsListResponse,
 };
@@ -94,6 +96,7 @@ where
 pub async fn v1_tags_create<S, U>(
     State(state): State<AppState<S, U>>,
     auth: RequireAuth,
+    client_ip: ClientIp,
     Json(request): Json<V1TagCreateRequest>,
 ) -> impl IntoResponse
 where
@@ -137,7 +140,18 @@ where
     };
 
     match state.sql_storage.tags_create(input).await {
-        Ok(row) => (StatusCode::CREATED, Json(V1TagItem::from(row))).into_response(),
+        Ok(row) => {
+            audit::record(
+                &state.sql_storage,
+                Some(user.id),
+                "tag.create",
+                Some(row.id),
+                None,
+                client_ip,
+            )
+            .await;
+            (StatusCode::CREATED, Json(V1TagItem::from(row))).into_response()
+        }
         Err(SqlStorageError::Conflict) => (
             StatusCode::CONFLICT,
             Json(V1ErrorResponse {
@@ -157,13 +171,103 @@ where
     }
 }
 
+/// Get a tag.
+///
+/// The `ETag` header identifies this version of the tag, for use with
+/// `If-Match` on PATCH and DELETE.
+#[utoipa::path(
+    get,
+    path = "/v1/tags/{id}",
+    tag = "tags",
+    params(
+        ("id" = String, Path, description = "Tag ID (UUID)")
+    ),
+    responses(
+        (status = 200, description = "Tag", body = V1TagItem),
+        (status = 400, description = "Bad request", body = V1ErrorResponse),
+        (status = 401, description = "Unauthorized", body = V1ErrorResponse),
+        (status = 404, description = "Tag not found", body = V1ErrorResponse),
+        (status = 500, description = "Internal server error", body = V1ErrorResponse),
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn v1_tags_get<S, U>(
+    State(state): State<AppState<S, U>>,
+    auth: RequireAuth,
+    Path(id): Path<String>,
+) -> impl IntoResponse
+where
+    S: SqlStorage,
+    U: UserStorage,
+{
+    // Get user ID from username
+    let user = match state.user_storage.get_user(auth.username()).await {
+        Ok(Some(user)) => user,
+        Ok(None) => {
+            return (
+                StatusCode::UNAUTHORIZED,
+                Json(V1ErrorResponse::not_found("User not found")),
+            )
+                .into_response();
+        }
+        Err(e) => {
+            tracing::error!("Failed to get user: {}", e);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(V1ErrorResponse::internal_error("Failed to get user")),
+            )
+                .into_response();
+        }
+    };
+
+    // Parse tag ID
+    let tag_id = match uuid::Uuid::parse_str(&id) {
+        Ok(id) => id,
+        Err(_) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(V1ErrorResponse::bad_request("Invalid tag ID format")),
+            )
+                .into_response();
+        }
+    };
+
+    match state.sql_storage.tags_get(user.id, tag_id).await {
+        Ok(Some(row)) => (
+            StatusCode::OK,
+            [(header::ETAG, etag::etag(row.updated_at))],
+            Json(V1TagItem::from(row)),
+        )
+            .into_response(),
+        Ok(None) => (
+            StatusCode::NOT_FOUND,
+            Json(V1ErrorResponse::not_found("Tag not found")),
+        )
+            .into_response(),
+        Err(e) => {
+            tracing::error!("Failed to get tag: {:?}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(V1ErrorResponse::internal_error("Failed to get tag")),
+            )
+                .into_response()
+        }
+    }
+}
+
 /// Update a tag.
+///
+/// With `If-Match`, the update only applies while the tag is unchanged;
+/// otherwise it fails with 412.
 #[utoipa::path(
     patch,
     path = "/v1/tags/{id}",
     tag = "tags",
     params(
-        ("id" = String, Path, description = "Tag ID (UUID)")
+        ("id" = String, Path, description = "Tag ID (UUID)"),
+        ("If-Match" = Option<String>, Header, description = "ETag from a previous GET")
     ),
     request_body = V1TagUpdateRequest,
     responses(
@@ -172,6 +276,7 @@ where
         (status = 401, description = "Unauthorized", body = V1ErrorResponse),
         (status = 404, description = "Tag not found", body = V1ErrorResponse),
         (status = 409, description = "Conflict - tag name already exists", body = V1ErrorResponse),
+        (status = 412, description = "Tag changed since it was fetched", body = V1ErrorResponse),
         (status = 500, description = "Internal server error", body = V1ErrorResponse),
     ),
     security(
@@ -181,7 +286,9 @@ where
 pub async fn v1_tags_update<S, U>(
     State(state): State<AppState<S, U>>,
     auth: RequireAuth,
+    client_ip: ClientIp,
     Path(id): Path<String>,
+    headers: HeaderMap,
     Json(request): Json<V1TagUpdateRequest>,
 ) -> impl IntoResponse
 where
@@ -231,13 +338,55 @@ where
             .into_response();
     }
 
+    // Honor If-Match against the tag's current version
+    let expected_updated_at = match etag::if_match(&headers) {
+        None => None,
+        Some(if_match) => match state.sql_storage.tags_get(user.id, tag_id).await {
+            Ok(Some(row)) if etag::matches(if_match, row.updated_at) => Some(row.updated_at),
+            Ok(Some(_)) => return etag::precondition_failed(),
+            Ok(None) => {
+                return (
+                    StatusCode::NOT_FOUND,
+                    Json(V1ErrorResponse::not_found("Tag not found")),
+                )
+                    .into_response();
+            }
+            Err(e) => {
+                tracing::error!("Failed to get tag: {:?}", e);
+                return (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(V1ErrorResponse::internal_error("Failed to get tag")),
+                )
+                    .into_response();
+            }
+        },
+    };
+
     let input = database::TagUpdate {
         name: request.name.map(|n| n.trim().to_owned()),
         color: request.color,
+        expected_updated_at,
     };
 
     match state.sql_storage.tags_update(user.id, tag_id, input).await {
-        Ok(Some(row)) => (StatusCode::OK, Json(V1TagItem::from(row))).into_response(),
+        Ok(Some(row)) => {
+            audit::record(
+                &state.sql_storage,
+                Some(user.id),
+                "tag.update",
+                Some(row.id),
+                None,
+                client_ip,
+            )
+            .await;
+            (
+                StatusCode::OK,
+                [(header::ETAG, etag::etag(row.updated_at))],
+                Json(V1TagItem::from(row)),
+            )
+                .into_response()
+        }
+        Ok(None) if expected_updated_at.is_some() => etag::precondition_failed(),
         Ok(None) => (
             StatusCode::NOT_FOUND,
             Json(V1ErrorResponse::not_found("Tag not found")),
@@ -263,18 +412,23 @@ where
 }
 
 /// Delete a tag.
+///
+/// With `If-Match`, the tag is only deleted while it is unchanged;
+/// otherwise it fails with 412.
 #[utoipa::path(
     delete,
     path = "/v1/tags/{id}",
     tag = "tags",
     params(
-        ("id" = String, Path, description = "Tag ID (UUID)")
+        ("id" = String, Path, description = "Tag ID (UUID)"),
+        ("If-Match" = Option<String>, Header, description = "ETag from a previous GET")
     ),
     responses(
         (status = 204, description = "Tag deleted"),
         (status = 400, description = "Bad request", body = V1ErrorResponse),
         (status = 401, description = "Unauthorized", body = V1ErrorResponse),
         (status = 404, description = "Tag not found", body = V1ErrorResponse),
+        (status = 412, description = "Tag changed since it was fetched", body = V1ErrorResponse),
         (status = 500, description = "Internal server error", body = V1ErrorResponse),
     ),
     security(
@@ -284,7 +438,9 @@ where
 pub async fn v1_tags_delete<S, U>(
     State(state): State<AppState<S, U>>,
     auth: RequireAuth,
+    client_ip: ClientIp,
     Path(id): Path<String>,
+    headers: HeaderMap,
 ) -> impl IntoResponse
 where
     S: SqlStorage,
@@ -322,8 +478,48 @@ where
         }
     };
 
-    match state.sql_storage.tags_delete(user.id, tag_id).await {
-        Ok(true) => StatusCode::NO_CONTENT.into_response(),
+    // Honor If-Match against the tag's current version
+    let expected_updated_at = match etag::if_match(&headers) {
+        None => None,
+        Some(if_match) => match state.sql_storage.tags_get(user.id, tag_id).await {
+            Ok(Some(row)) if etag::matches(if_match, row.updated_at) => Some(row.updated_at),
+            Ok(Some(_)) => return etag::precondition_failed(),
+            Ok(None) => {
+                return (
+                    StatusCode::NOT_FOUND,
+                    Json(V1ErrorResponse::not_found("Tag not found")),
+                )
+                    .into_response();
+            }
+            Err(e) => {
+                tracing::error!("Failed to get tag: {:?}", e);
+                return (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(V1ErrorResponse::internal_error("Failed to get tag")),
+                )
+                    .into_response();
+            }
+        },
+    };
+
+    match state
+        .sql_storage
+        .tags_delete(user.id, tag_id, expected_updated_at)
+        .await
+    {
+        Ok(true) => {
+            audit::record(
+                &state.sql_storage,
+                Some(user.id),
+                "tag.delete",
+                Some(tag_id),
+                None,
+                client_ip,
+            )
+            .await;
+            StatusCode::NO_CONTENT.into_response()
+        }
+        Ok(false) if expected_updated_at.is_some() => etag::precondition_failed(),
         Ok(false) => (
             StatusCode::NOT_FOUND,
             Json(V1ErrorResponse::not_found("Tag not found")),
@@ -0,0 +1,224 @@
+//! /v1/trash endpoint handlers.
+
+use crate::audit::{self, ClientIp};
+use crate::config::Config;
+use crate::database::{
+    ContentStatus, ContentsListParams, GroupStatus, GroupsListParams, SqlStorage,
+};
+use crate::purge::TrashPurger;
+use crate::storage::StorageRouter;
+use crate::users::routes::AppState;
+use crate::users::session_auth::RequireAuth;
+use crate::users::storage::UserStorage;
+use axum::{
+    Extension, Json,
+    extract::{Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+
+use super::types::{
+    V1ContentItem, V1ErrorResponse, V1GroupItem, V1TrashEmptyResponse, V1TrashListQuery,
+    V1TrashListResponse,
+};
+
+/// List trashed contents and groups for the authenticated user.
+///
+/// Items are permanently deleted once they have been in the trash for
+/// `retention_days`.
+#[utoipa::path(
+    get,
+    path = "/v1/trash",
+    tag = "trash",
+    params(V1TrashListQuery),
+    responses(
+        (status = 200, description = "Trashed contents and groups", body = V1TrashListResponse),
+        (status = 401, description = "Unauthorized", body = V1ErrorResponse),
+        (status = 500, description = "Internal server error", body = V1ErrorResponse),
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn v1_trash_list<S, U>(
+    State(state): State<AppState<S, U>>,
+    Extension(config): Extension<Config>,
+    auth: RequireAuth,
+    Query(query): Query<V1TrashListQuery>,
+) -> impl IntoResponse
+where
+    S: SqlStorage,
+    U: UserStorage,
+{
+    // Get user ID from username
+    let user = match state.user_storage.get_user(auth.username()).await {
+        Ok(Some(user)) => user,
+        Ok(None) => {
+            return (
+                StatusCode::UNAUTHORIZED,
+                Json(V1ErrorResponse::not_found("User not found")),
+            )
+                .into_response();
+        }
+        Err(e) => {
+            tracing::error!("Failed to get user: {}", e);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(V1ErrorResponse::internal_error("Failed to get user")),
+            )
+                .into_response();
+        }
+    };
+
+    let limit = query.limit.unwrap_or(50).clamp(1, 100);
+    let offset = query.offset.unwrap_or(0).max(0);
+
+    let contents = match state
+        .sql_storage
+        .contents_list_for_user(
+            user.id,
+            ContentsListParams {
+                limit,
+                offset,
+                status: Some(ContentStatus::Trashed),
+                ..Default::default()
+            },
+        )
+        .await
+    {
+        Ok(rows) => rows.into_iter().map(V1ContentItem::from).collect(),
+        Err(e) => {
+            tracing::error!("Failed to list trashed contents: {:?}", e);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(V1ErrorResponse::internal_error("Failed to list trash")),
+            )
+                .into_response();
+        }
+    };
+
+    let groups = match state
+        .sql_storage
+        .groups_list_for_user(
+            user.id,
+            GroupsListParams {
+                limit,
+                offset,
+                status: Some(GroupStatus::Trashed),
+            },
+        )
+        .await
+    {
+        Ok(rows) => rows.into_iter().map(V1GroupItem::from).collect(),
+        Err(e) => {
+            tracing::error!("Failed to list trashed groups: {:?}", e);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(V1ErrorResponse::internal_error("Failed to list trash")),
+            )
+                .into_response();
+        }
+    };
+
+    (
+        StatusCode::OK,
+        Json(V1TrashListResponse {
+            contents,
+            groups,
+            retention_days: config.trash_retention().as_secs() / (24 * 60 * 60),
+        }),
+    )
+        .into_response()
+}
+
+/// Permanently delete everything in the authenticated user's trash.
+///
+/// Group memberships, tags and shares of the deleted items are removed with
+/// them, and stored files no longer referenced by any content are deleted.
+#[utoipa::path(
+    post,
+    path = "/v1/trash/empty",
+    tag = "trash",
+    responses(
+        (status = 200, description = "Trash emptied", body = V1TrashEmptyResponse),
+        (status = 401, description = "Unauthorized", body = V1ErrorResponse),
+        (status = 500, description = "Internal server error", body = V1ErrorResponse),
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn v1_trash_empty<S, U>(
+    State(state): State<AppState<S, U>>,
+    Extension(config): Extension<Config>,
+    storage: Option<Extension<StorageRouter>>,
+    auth: RequireAuth,
+    client_ip: ClientIp,
+) -> impl IntoResponse
+where
+    S: SqlStorage,
+    U: UserStorage,
+{
+    // Get user ID from username
+    let user = match state.user_storage.get_user(auth.username()).await {
+        Ok(Some(user)) => user,
+        Ok(None) => {
+            return (
+                StatusCode::UNAUTHORIZED,
+                Json(V1ErrorResponse::not_found("User not found")),
+            )
+                .into_response();
+        }
+        Err(e) => {
+            tracing::error!("Failed to get user: {}", e);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(V1ErrorResponse::internal_error("Failed to get user")),
+            )
+                .into_response();
+        }
+    };
+
+    let router = storage.map(|Extension(router)| router).unwrap_or_default();
+    let purger = TrashPurger::new(state.sql_storage.clone(), router, config.trash_retention());
+
+    match purger.purge_user(user.id).await {
+        Ok(report) => {
+            if report.failed_cleanups > 0 {
+                tracing::warn!(
+                    user_id = %user.id,
+                    failed_cleanups = report.failed_cleanups,
+                    "Emptied trash with storage cleanup failures"
+                );
+            }
+            audit::record(
+                &state.sql_storage,
+                Some(user.id),
+                "trash.empty",
+                None,
+                Some(serde_json::json!({
+                    "purged_contents": report.purged_contents,
+                    "purged_groups": report.purged_groups,
+                })),
+                client_ip,
+            )
+            .await;
+            (
+                StatusCode::OK,
+                Json(V1TrashEmptyResponse {
+                    purged_contents: report.purged_contents,
+                    purged_groups: report.purged_groups,
+                }),
+            )
+                .into_response()
+        }
+        Err(e) => {
+            tracing::error!("Failed to empty trash: {:?}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(V1ErrorResponse::internal_error("Failed to empty trash")),
+            )
+                .into_response()
+        }
+    }
+}
@@ -1,6 +1,9 @@
 //! Shared types for the v1 API endpoints.
 
-use crate::database::{self, ContentRow, ShareLinkRow, SharePermission, Visibility};
+use crate::database::{
+    self, ContentRevisionRow, ContentRow, ShareLinkRow, SharePermission, SharedWithUserRow,
+    StorageUsage, UploadPartRow, UserShareRow, Visibility,
+};
 use serde::{Deserialize, Deserializer, Serialize};
 use utoipa::{IntoParams, ToSchema};
 
@@ -61,6 +64,48 @@ impl V1ErrorResponse {
             message: message.into(),
         }
     }
+
+    pub fn size_mismatch(message: impl Into<String>) -> Self {
+        Self {
+            error: "size_mismatch".to_string(),
+            message: message.into(),
+        }
+    }
+
+    pub fn content_type_mismatch(message: impl Into<String>) -> Self {
+        Self {
+            error: "content_type_mismatch".to_string(),
+            message: message.into(),
+        }
+    }
+
+    pub fn checksum_mismatch(message: impl Into<String>) -> Self {
+        Self {
+            error: "checksum_mismatch".to_string(),
+            message: message.into(),
+        }
+    }
+
+    pub fn quota_exceeded(message: impl Into<String>) -> Self {
+        Self {
+            error: "quota_exceeded".to_string(),
+            message: message.into(),
+        }
+    }
+
+    pub fn precondition_failed(message: impl Into<String>) -> Self {
+        Self {
+            error: "precondition_failed".to_string(),
+            message: message.into(),
+        }
+    }
+
+    pub fn multipart_unsupported(message: impl Into<String>) -> Self {
+        Self {
+            error: "multipart_unsupported".to_string(),
+            message: message.into(),
+        }
+    }
 }
 
 // =============================================================================
@@ -73,12 +118,43 @@ pub struct V1ContentsListQuery {
     /// Maximum number of results to return (default: 50, max: 100)
     #[serde(default)]
     pub limit: Option<i64>,
-    /// Offset for pagination
+    /// Offset for pagination; prefer `cursor`
     #[serde(default)]
     pub offset: Option<i64>,
     /// Filter by status: active, archived, trashed
     #[serde(default)]
     pub status: Option<String>,
+    /// Filter by kind: file, text
+    #[serde(default)]
+    pub kind: Option<String>,
+    /// Filter by MIME type prefix, e.g. `image/`
+    #[serde(default)]
+    pub content_type: Option<String>,
+    /// Filter by visibility: private, public, restricted
+    #[serde(default)]
+    pub visibility: Option<String>,
+    /// Comma-separated tag IDs; only contents carrying all of them match
+    #[serde(default)]
+    pub tags: Option<String>,
+    /// Only contents created at or after this time (RFC 3339)
+    #[serde(default)]
+    pub created_after: Option<chrono::DateTime<chrono::Utc>>,
+    /// Only contents created before this time (RFC 3339)
+    #[serde(default)]
+    pub created_before: Option<chrono::DateTime<chrono::Utc>>,
+    /// Only contents updated at or after this time (RFC 3339)
+    #[serde(default)]
+    pub updated_after: Option<chrono::DateTime<chrono::Utc>>,
+    /// Only contents updated before this time (RFC 3339)
+    #[serde(default)]
+    pub updated_before: Option<chrono::DateTime<chrono::Utc>>,
+    /// Sort order: created_at_desc (default), created_at_asc, updated_at_desc,
+    /// updated_at_asc, title_asc, title_desc, captured_at_desc, captured_at_asc
+    #[serde(default)]
+    pub sort: Option<String>,
+    /// `next_cursor` of the previous page, to continue after it
+    #[serde(default)]
+    pub cursor: Option<String>,
 }
 
 /// A content item in API responses.
@@ -110,6 +186,11 @@ pub struct V1ContentItem {
     /// Inline text content (only present when kind="text").
     #[serde(skip_serializing_if = "Option::is_none")]
     pub body: Option<String>,
+    /// Hex-encoded SHA-256 of the file, when declared at upload.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sha256: Option<String>,
+    /// Media metadata extracted from the file after upload.
+    pub metadata: V1MediaMetadata,
     /// Timestamp when content was trashed (ISO 8601 format).
     #[serde(skip_serializing_if = "Option::is_none")]
     pub trashed_at: Option<String>,
@@ -137,6 +218,8 @@ impl From<ContentRow> for V1ContentItem {
             visibility: row.visibility,
             kind: row.kind,
             body: row.body,
+            sha256: row.sha256,
+            metadata: serde_json::from_value(row.metadata).unwrap_or_default(),
             trashed_at: row.trashed_at.map(|t| t.to_rfc3339()),
             archived_at: row.archived_at.map(|t| t.to_rfc3339()),
             created_at: row.created_at.to_rfc3339(),
@@ -145,6 +228,33 @@ impl From<ContentRow> for V1ContentItem {
     }
 }
 
+/// Media metadata of a content.
+///
+/// Filled in shortly after an upload completes; fields that could not be read
+/// from the file are omitted.
+#[derive(Debug, Default, Serialize, Deserialize, ToSchema)]
+pub struct V1MediaMetadata {
+    /// Image width in pixels, before applying `orientation`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub width: Option<u32>,
+    /// Image height in pixels, before applying `orientation`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub height: Option<u32>,
+    /// EXIF orientation (1-8).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub orientation: Option<u16>,
+    /// EXIF capture time in the camera's local time
+    /// (`YYYY-MM-DDTHH:MM:SS`), with its UTC offset when recorded.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub captured_at: Option<String>,
+    /// Number of pages of a PDF.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub page_count: Option<u32>,
+    /// Duration of audio or video, in seconds.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duration_secs: Option<f64>,
+}
+
 /// Response for listing contents.
 #[derive(Debug, Serialize, ToSchema)]
 pub struct V1ContentsListResponse {
@@ -152,6 +262,9 @@ pub struct V1ContentsListResponse {
     pub items: Vec<V1ContentItem>,
     /// Total number of items returned.
     pub total: usize,
+    /// Cursor of the next page, absent on the last page.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<String>,
 }
 
 /// Request body for updating content metadata.
@@ -207,6 +320,14 @@ pub struct V1ViewUrlRequest {
     pub disposition: String,
 }
 
+/// Query parameters for view URL.
+#[derive(Debug, Deserialize, Default, ToSchema, IntoParams)]
+pub struct V1ViewUrlQuery {
+    /// Thumbnail size (128, 512 or 1024) to view instead of the original image.
+    #[serde(default)]
+    pub size: Option<u32>,
+}
+
 /// Response for view URL.
 #[derive(Debug, Serialize, ToSchema)]
 pub struct V1ViewUrlResponse {
@@ -214,6 +335,136 @@ pub struct V1ViewUrlResponse {
     pub url: String,
     /// URL expiration timestamp (ISO 8601 format).
     pub expires_at: String,
+    /// Size of the thumbnail the URL points to, absent for the original.
+    ///
+    /// A requested thumbnail that is not generated yet falls back to the original.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub thumbnail_size: Option<u32>,
+}
+
+/// Query parameters for downloading content through the server.
+#[derive(Debug, Deserialize, Default, ToSchema, IntoParams)]
+pub struct V1ContentRawQuery {
+    /// `inline` (default) or `attachment`.
+    #[serde(default)]
+    pub disposition: Option<String>,
+}
+
+/// Request body for applying one operation to many contents.
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct V1ContentsBatchRequest {
+    /// Content IDs (UUIDs), at most 500.
+    pub ids: Vec<String>,
+    /// Operation: "trash", "restore", "archive", "tag" or "add_to_group".
+    pub op: String,
+    /// Tag ID (UUID), required for "tag".
+    #[serde(default)]
+    pub tag_id: Option<String>,
+    /// Group ID (UUID), required for "add_to_group".
+    #[serde(default)]
+    pub group_id: Option<String>,
+}
+
+/// Outcome of a batch operation for one content ID.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct V1ContentsBatchItemResult {
+    /// Content ID as given in the request.
+    pub id: String,
+    /// "ok", "not_found" or "invalid_id".
+    pub status: String,
+}
+
+/// Response for a batch operation.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct V1ContentsBatchResponse {
+    /// One result per requested ID, in request order.
+    pub results: Vec<V1ContentsBatchItemResult>,
+    /// Number of contents the operation was applied to.
+    pub succeeded: usize,
+    /// Number of IDs that were skipped.
+    pub failed: usize,
+}
+
+// =============================================================================
+// Content Revisions API Types
+// =============================================================================
+
+/// An earlier version of a text content.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct V1ContentRevisionItem {
+    /// Revision number, increasing with every change of the content.
+    pub revision: i32,
+    /// Title at this revision.
+    pub title: String,
+    /// Description at this revision.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Text body at this revision.
+    pub body: String,
+    /// Timestamp when this version was replaced (ISO 8601 format).
+    pub created_at: String,
+}
+
+impl From<ContentRevisionRow> for V1ContentRevisionItem {
+    fn from(row: ContentRevisionRow) -> Self {
+        Self {
+            revision: row.revision,
+            title: row.title,
+            description: row.description,
+            body: row.body,
+            created_at: row.created_at.to_rfc3339(),
+        }
+    }
+}
+
+/// Response for listing content revisions.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct V1ContentRevisionsListResponse {
+    /// Revisions, newest first.
+    pub items: Vec<V1ContentRevisionItem>,
+    /// Total number of items returned.
+    pub total: usize,
+}
+
+/// Query parameters for diffing two versions of a content.
+#[derive(Debug, Deserialize, ToSchema, IntoParams)]
+pub struct V1ContentRevisionDiffQuery {
+    /// Revision to diff from.
+    pub from: i32,
+    /// Revision to diff to. Defaults to the current version.
+    #[serde(default)]
+    pub to: Option<i32>,
+}
+
+/// One line of a body diff.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct V1DiffLine {
+    /// "equal", "delete" (only in `from`) or "insert" (only in `to`).
+    pub op: String,
+    /// The line, without its line break.
+    pub text: String,
+}
+
+/// Differences between two versions of a content.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct V1ContentRevisionDiffResponse {
+    /// Revision diffed from.
+    pub from: i32,
+    /// Revision diffed to, absent for the current version.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub to: Option<i32>,
+    /// Title in `from`.
+    pub title_from: String,
+    /// Title in `to`.
+    pub title_to: String,
+    /// Description in `from`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description_from: Option<String>,
+    /// Description in `to`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description_to: Option<String>,
+    /// Line diff of the bodies.
+    pub lines: Vec<V1DiffLine>,
 }
 
 // =============================================================================
@@ -444,6 +695,142 @@ pub struct V1GroupReorderItem {
     pub sort_order: i32,
 }
 
+// =============================================================================
+// Search API Types
+// =============================================================================
+
+/// Query parameters for searching contents.
+#[derive(Debug, Deserialize, Default, ToSchema, IntoParams)]
+pub struct V1SearchQuery {
+    /// Search terms. Supports `"quoted phrases"`, `-excluded` words and `or`.
+    pub q: String,
+    /// Maximum number of results to return (default: 50, max: 100)
+    #[serde(default)]
+    pub limit: Option<i64>,
+    /// Offset for pagination
+    #[serde(default)]
+    pub offset: Option<i64>,
+    /// Filter by status: active, archived, trashed
+    #[serde(default)]
+    pub status: Option<String>,
+    /// Filter by kind: file, text
+    #[serde(default)]
+    pub kind: Option<String>,
+    /// Comma-separated tag IDs; only contents carrying all of them match
+    #[serde(default)]
+    pub tags: Option<String>,
+}
+
+/// A content matching a search.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct V1SearchHit {
+    /// The matching content.
+    pub content: V1ContentItem,
+    /// Relevance; higher is better. Only comparable within one search.
+    pub rank: f32,
+    /// HTML-escaped title with matched terms wrapped in `<mark>`.
+    pub title_highlight: String,
+    /// HTML-escaped fragments of the description and text body around
+    /// matched terms, wrapped in `<mark>`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub snippet: Option<String>,
+}
+
+/// Response for searching contents.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct V1SearchResponse {
+    /// Matching contents, best match first.
+    pub items: Vec<V1SearchHit>,
+    /// Total number of items returned.
+    pub total: usize,
+}
+
+// =============================================================================
+// Trash API Types
+// =============================================================================
+
+/// Query parameters for listing the trash.
+#[derive(Debug, Deserialize, Default, ToSchema, IntoParams)]
+pub struct V1TrashListQuery {
+    /// Maximum number of contents and of groups to return (default: 50, max: 100)
+    #[serde(default)]
+    pub limit: Option<i64>,
+    /// Offset for pagination
+    #[serde(default)]
+    pub offset: Option<i64>,
+}
+
+/// Response for listing the trash.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct V1TrashListResponse {
+    /// Trashed contents.
+    pub contents: Vec<V1ContentItem>,
+    /// Trashed groups.
+    pub groups: Vec<V1GroupItem>,
+    /// Days items stay in the trash before they are permanently deleted.
+    pub retention_days: u64,
+}
+
+/// Response for emptying the trash.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct V1TrashEmptyResponse {
+    /// Number of contents permanently deleted.
+    pub purged_contents: usize,
+    /// Number of groups permanently deleted.
+    pub purged_groups: usize,
+}
+
+// =============================================================================
+// Exports API Types
+// =============================================================================
+
+/// Response item for an account export.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct V1ExportItem {
+    /// Unique identifier (UUID).
+    pub id: String,
+    /// Export status (pending, completed, failed).
+    pub status: String,
+    /// Why the export failed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// Timestamp when the export was requested (ISO 8601 format).
+    pub created_at: String,
+    /// Timestamp when the export finished (ISO 8601 format).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub completed_at: Option<String>,
+    /// Timestamp after which the archive can no longer be downloaded (ISO 8601 format).
+    pub expires_at: String,
+}
+
+impl From<database::AccountExportRow> for V1ExportItem {
+    fn from(row: database::AccountExportRow) -> Self {
+        Self {
+            id: row.id.to_string(),
+            status: row.status,
+            error: row.error,
+            created_at: row.created_at.to_rfc3339(),
+            completed_at: row.completed_at.map(|t| t.to_rfc3339()),
+            expires_at: row.expires_at.to_rfc3339(),
+        }
+    }
+}
+
+/// Response for importing an export archive.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct V1ImportResponse {
+    /// Number of contents created.
+    pub contents: usize,
+    /// Number of tags created; tags named like an existing tag reuse it.
+    pub tags: usize,
+    /// Number of groups created.
+    pub groups: usize,
+    /// Number of share links created, with new tokens.
+    pub share_links: usize,
+    /// Manifest ids of file contents left out because the archive lacked their file.
+    pub missing_files: Vec<String>,
+}
+
 // =============================================================================
 // Uploads API Types
 // =============================================================================
@@ -457,6 +844,13 @@ pub struct V1UploadsInitRequest {
     pub content_type: String,
     /// File size in bytes.
     pub file_size: u64,
+    /// Storage profile to upload into. Defaults to the server's profile policy.
+    #[serde(default)]
+    pub storage_profile: Option<String>,
+    /// Hex-encoded SHA-256 of the file. Verified on completion; identical
+    /// files of the same user are stored once.
+    #[serde(default)]
+    pub sha256: Option<String>,
 }
 
 /// Response for upload initialization.
@@ -466,6 +860,8 @@ pub struct V1UploadsInitResponse {
     pub upload_id: String,
     /// Storage key/path where file will be stored.
     pub storage_key: String,
+    /// Storage profile the file will be stored in.
+    pub storage_profile: String,
     /// HTTP method to use for upload.
     pub method: String,
     /// Presigned URL for uploading the file.
@@ -485,6 +881,142 @@ pub struct V1UploadsCompleteRequest {
     /// Optional description for the content.
     #[serde(default)]
     pub description: Option<String>,
+    /// Uploaded parts (multipart uploads only).
+    ///
+    /// Parts listed here are recorded before completing; every part must be
+    /// uploaded, either here or via `PUT /v1/uploads/{upload_id}/parts/{part_number}`.
+    #[serde(default)]
+    pub parts: Vec<V1UploadPartCompleted>,
+}
+
+/// Request body for multipart upload initialization.
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct V1MultipartUploadInitRequest {
+    /// Original filename.
+    pub filename: String,
+    /// MIME type of the file.
+    pub content_type: String,
+    /// File size in bytes.
+    pub file_size: u64,
+    /// Part size in bytes (at least 5 MiB). Defaults to 16 MiB.
+    #[serde(default)]
+    pub part_size: Option<u64>,
+    /// Storage profile to upload into. Defaults to the server's profile policy.
+    #[serde(default)]
+    pub storage_profile: Option<String>,
+    /// Hex-encoded SHA-256 of the file. Verified on completion; identical
+    /// files of the same user are stored once.
+    #[serde(default)]
+    pub sha256: Option<String>,
+}
+
+/// Response for multipart upload initialization.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct V1MultipartUploadInitResponse {
+    /// Unique upload identifier (UUID).
+    pub upload_id: String,
+    /// Storage key/path where file will be stored.
+    pub storage_key: String,
+    /// Storage profile the file will be stored in.
+    pub storage_profile: String,
+    /// Size of every part except the last, in bytes.
+    pub part_size: u64,
+    /// Number of parts.
+    pub part_count: u32,
+    /// Upload session expiration timestamp (ISO 8601 format).
+    pub expires_at: String,
+}
+
+/// Request body for presigning part upload URLs.
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct V1UploadPartsRequest {
+    /// Part numbers to presign. Defaults to all parts not yet uploaded.
+    #[serde(default)]
+    pub part_numbers: Vec<u32>,
+}
+
+/// Presigned URL for one part.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct V1UploadPartUrl {
+    /// 1-based part number.
+    pub part_number: u32,
+    /// Exact size of the part in bytes.
+    pub size: u64,
+    /// HTTP method to use for upload.
+    pub method: String,
+    /// Presigned URL for uploading the part.
+    pub upload_url: String,
+    /// URL expiration timestamp (ISO 8601 format).
+    pub expires_at: String,
+}
+
+/// Response with presigned part upload URLs.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct V1UploadPartsResponse {
+    pub parts: Vec<V1UploadPartUrl>,
+}
+
+/// An uploaded part, identified by the `ETag` returned by storage.
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct V1UploadPartCompleted {
+    /// 1-based part number.
+    pub part_number: u32,
+    /// `ETag` header returned by the part upload.
+    pub etag: String,
+}
+
+/// Request body for recording an uploaded part.
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct V1UploadPartUpdateRequest {
+    /// `ETag` header returned by the part upload.
+    pub etag: String,
+}
+
+/// State of one part of a multipart upload.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct V1UploadPart {
+    /// 1-based part number.
+    pub part_number: u32,
+    /// Size of the part in bytes.
+    pub size: u64,
+    /// `pending` or `uploaded`.
+    pub status: String,
+    /// `ETag` of the uploaded part.
+    pub etag: Option<String>,
+}
+
+impl From<UploadPartRow> for V1UploadPart {
+    fn from(row: UploadPartRow) -> Self {
+        Self {
+            part_number: row.part_number as u32,
+            size: row.size as u64,
+            status: row.status,
+            etag: row.etag,
+        }
+    }
+}
+
+/// Upload session state, used to resume interrupted uploads.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct V1UploadStatusResponse {
+    /// Unique upload identifier (UUID).
+    pub upload_id: String,
+    /// Storage key/path where file will be stored.
+    pub storage_key: String,
+    /// Storage profile the file will be stored in.
+    pub storage_profile: String,
+    /// `initiated`, `completed`, `aborted` or `expired`.
+    pub status: String,
+    /// File size in bytes.
+    pub file_size: u64,
+    /// Whether this is a multipart upload.
+    pub multipart: bool,
+    /// Size of every part except the last (multipart uploads only).
+    pub part_size: Option<u64>,
+    /// Part states (multipart uploads only).
+    pub parts: Vec<V1UploadPart>,
+    /// Upload session expiration timestamp (ISO 8601 format).
+    pub expires_at: String,
 }
 
 /// Response for upload completion.
@@ -507,6 +1039,38 @@ pub struct V1MeResponse {
     pub issued_at: i64,
     /// Token expiration timestamp (Unix seconds).
     pub expires_at: i64,
+    /// Storage used by the user and their quota.
+    pub storage: V1StorageUsage,
+}
+
+/// Storage a user has used, counted against their quota.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct V1StorageUsage {
+    /// Bytes of active contents.
+    pub active_bytes: i64,
+    /// Bytes of archived contents.
+    pub archived_bytes: i64,
+    /// Bytes of trashed contents, freed once the trash is purged.
+    pub trashed_bytes: i64,
+    /// Declared bytes of uploads not yet completed.
+    pub pending_bytes: i64,
+    /// Total bytes counted against the quota.
+    pub used_bytes: i64,
+    /// Storage quota in bytes, `null` when unlimited.
+    pub quota_bytes: Option<i64>,
+}
+
+impl V1StorageUsage {
+    pub fn new(usage: StorageUsage, quota_bytes: Option<i64>) -> Self {
+        Self {
+            active_bytes: usage.active_bytes,
+            archived_bytes: usage.archived_bytes,
+            trashed_bytes: usage.trashed_bytes,
+            pending_bytes: usage.pending_bytes,
+            used_bytes: usage.total_bytes(),
+            quota_bytes,
+        }
+    }
 }
 
 // =============================================================================
@@ -632,6 +1196,46 @@ impl V1ShareLinkResponse {
     }
 }
 
+/// Query parameters for share link analytics.
+#[derive(Debug, Deserialize, Default, ToSchema, IntoParams)]
+pub struct V1ShareLinkAnalyticsQuery {
+    /// Number of days of daily counts, up to today (1-365, default 30).
+    #[serde(default)]
+    pub days: Option<i64>,
+}
+
+/// Accesses to a share link on one day (UTC).
+#[derive(Debug, Serialize, ToSchema)]
+pub struct V1ShareLinkAccessDay {
+    /// The day (YYYY-MM-DD).
+    pub date: String,
+    /// Number of accesses.
+    pub accesses: i64,
+    /// Number of distinct visitors.
+    pub unique_visitors: i64,
+}
+
+/// Access analytics of a share link.
+///
+/// Every public access to the link counts: fetching its metadata, getting a
+/// view URL and downloading an archive. A visitor is a distinct pair of IP
+/// address and user agent.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct V1ShareLinkAnalyticsResponse {
+    /// Share link ID (UUID).
+    pub share_link_id: String,
+    /// Accesses since the link was created.
+    pub total_accesses: i64,
+    /// Distinct visitors since the link was created.
+    pub unique_visitors: i64,
+    /// Time of the last access (ISO 8601 format).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_accessed_at: Option<String>,
+    /// Daily counts for the requested days, oldest first, including days
+    /// without accesses.
+    pub daily: Vec<V1ShareLinkAccessDay>,
+}
+
 /// Response for listing share links.
 #[derive(Debug, Serialize, ToSchema)]
 pub struct V1ShareLinksListResponse {
@@ -679,6 +1283,88 @@ pub struct V1GroupShareLinkCreateRequest {
     pub max_access_count: Option<i32>,
 }
 
+// =============================================================================
+// Direct Shares API Types
+// =============================================================================
+
+/// Request body for sharing a content or group with a user.
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct V1UserShareCreateRequest {
+    /// Username of the user to share with.
+    pub username: String,
+    /// Permission level: "view" or "download". Default: "view".
+    #[serde(default = "default_view_permission")]
+    pub permission: String,
+}
+
+/// A direct share of a content or group with a user.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct V1UserShareResponse {
+    /// Unique identifier (UUID).
+    pub id: String,
+    /// Username of the user the item is shared with.
+    pub username: String,
+    /// Permission level: "view" or "download".
+    pub permission: String,
+    /// Timestamp when the item was shared (ISO 8601 format).
+    pub created_at: String,
+}
+
+impl From<UserShareRow> for V1UserShareResponse {
+    fn from(row: UserShareRow) -> Self {
+        Self {
+            id: row.id.to_string(),
+            username: row.shared_with_username,
+            permission: row.permission,
+            created_at: row.created_at.to_rfc3339(),
+        }
+    }
+}
+
+/// Response for listing the direct shares of a content or group.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct V1UserSharesListResponse {
+    /// Direct shares, oldest first.
+    pub shares: Vec<V1UserShareResponse>,
+}
+
+/// A content or group shared with the current user.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct V1SharedWithMeItem {
+    /// Type of the shared item: "content" or "group".
+    pub item_type: String,
+    /// ID of the content or group (UUID).
+    pub id: String,
+    /// Title of the content or name of the group.
+    pub title: String,
+    /// Username of the owner.
+    pub owner_username: String,
+    /// Permission level: "view" or "download".
+    pub permission: String,
+    /// Timestamp when the item was shared (ISO 8601 format).
+    pub shared_at: String,
+}
+
+impl From<SharedWithUserRow> for V1SharedWithMeItem {
+    fn from(row: SharedWithUserRow) -> Self {
+        Self {
+            item_type: row.item_type,
+            id: row.item_id.to_string(),
+            title: row.title,
+            owner_username: row.owner_username,
+            permission: row.permission,
+            shared_at: row.shared_at.to_rfc3339(),
+        }
+    }
+}
+
+/// Response for listing the items shared with the current user.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct V1SharedWithMeResponse {
+    /// Shared contents and groups, most recently shared first.
+    pub items: Vec<V1SharedWithMeItem>,
+}
+
 // =============================================================================
 // Public Access API Types
 // =============================================================================
@@ -716,6 +1402,14 @@ pub struct V1PublicViewUrlResponse {
     pub url: String,
     /// URL expiration timestamp (ISO 8601 format).
     pub expires_at: String,
+    /// Unlock token, when a correct password was sent. Send it as
+    /// `unlock_token` (or in the `X-Share-Unlock` header) instead of the
+    /// password until it expires.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub unlock_token: Option<String>,
+    /// Unlock token expiration timestamp (ISO 8601 format).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub unlock_expires_at: Option<String>,
 }
 
 /// Request for getting a view URL from a public share.
@@ -724,6 +1418,9 @@ pub struct V1PublicViewUrlRequest {
     /// Password if the share link is protected.
     #[serde(default)]
     pub password: Option<String>,
+    /// Unlock token from an earlier request with the correct password.
+    #[serde(default)]
+    pub unlock_token: Option<String>,
     /// Content disposition: "inline" or "attachment".
     #[serde(default = "default_inline_disposition")]
     pub disposition: String,
@@ -1,20 +1,46 @@
 //! /v1/uploads endpoint handlers.
 
-use crate::database::{ContentsInsert, SqlStorage, SqlStorageError, UploadInsert, Visibility};
-use crate::storage::{DEFAULT_PRESIGN_EXPIRY, R2Presigner};
+use std::time::Duration;
+
+use crate::audit::{self, ClientIp};
+use crate::database::{
+    ContentRow, ContentsInsert, SqlStorage, SqlStorageError, StorageObjectInsert, UploadInsert,
+    UploadPartInsert, UploadPartStatus, UploadRow, Visibility,
+};
+use crate::storage::{
+    CompletedPart, DEFAULT_PRESIGN_EXPIRY, FileStorage, FileStorageError, MAX_MEDIA_SOURCE_SIZE,
+    MediaKind, PartPlan, PresignError, PresignFileMetadata, SNIFF_LEN, StorageRouter,
+    StorageSelectionError, StorageTarget, extract_media_metadata, generate_thumbnails,
+    mime_essence, normalize_sha256, object_sha256, sniff_mismatch,
+};
 use crate::users::routes::AppState;
 use crate::users::session_auth::RequireAuth;
 use crate::users::storage::UserStorage;
-use axum::{Json, extract::State, http::StatusCode, response::IntoResponse};
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
 
 use super::types::{
-    V1ContentItem, V1ErrorResponse, V1UploadsCompleteRequest, V1UploadsCompleteResponse,
-    V1UploadsInitRequest, V1UploadsInitResponse,
+    V1ContentItem, V1ErrorResponse, V1MultipartUploadInitRequest, V1MultipartUploadInitResponse,
+    V1UploadPart, V1UploadPartUpdateRequest, V1UploadPartUrl, V1UploadPartsRequest,
+    V1UploadPartsResponse, V1UploadStatusResponse, V1UploadsCompleteRequest,
+    V1UploadsCompleteResponse, V1UploadsInitRequest, V1UploadsInitResponse,
 };
 
+/// How long a multipart upload session stays open.
+///
+/// Part URLs are presigned on demand with [`DEFAULT_PRESIGN_EXPIRY`], so a
+/// client can resume an interrupted upload until the session expires.
+pub const MULTIPART_UPLOAD_EXPIRY: Duration = Duration::from_secs(24 * 60 * 60);
+
 /// Initialize an upload.
 ///
-/// This endpoint generates a presigned PUT URL for direct upload to R2.
+/// This endpoint generates a presigned PUT URL for direct upload to the
+/// storage profile selected for the file (explicit `storage_profile`, the
+/// user's assigned profile, a profile matching the MIME type, or the default).
 /// The client should use this URL to upload the file directly, then call
 /// `/v1/uploads/complete` to finalize the upload.
 #[utoipa::path(
@@ -24,8 +50,11 @@ use super::types::{
     request_body = V1UploadsInitRequest,
     responses(
         (status = 201, description = "Upload initialized", body = V1UploadsInitResponse),
+        (status = 400, description = "Invalid SHA-256, unknown storage profile or content type not allowed", body = V1ErrorResponse),
         (status = 401, description = "Unauthorized", body = V1ErrorResponse),
+        (status = 413, description = "File exceeds the storage profile's size limit or the user's storage quota", body = V1ErrorResponse),
         (status = 500, description = "Internal server error", body = V1ErrorResponse),
+        (status = 502, description = "Storage is not configured", body = V1ErrorResponse),
     ),
     security(
         ("bearer_auth" = [])
@@ -33,8 +62,9 @@ use super::types::{
 )]
 pub async fn v1_uploads_init<S, U>(
     State(state): State<AppState<S, U>>,
-    presigner: Option<axum::Extension<R2Presigner>>,
+    storage: Option<axum::Extension<StorageRouter>>,
     auth: RequireAuth,
+    client_ip: ClientIp,
     Json(payload): Json<V1UploadsInitRequest>,
 ) -> impl IntoResponse
 where
@@ -61,6 +91,22 @@ where
         }
     };
 
+    let target = match select_upload_target(
+        storage.as_ref(),
+        payload.storage_profile.as_deref(),
+        auth.username(),
+        &payload.content_type,
+        payload.file_size,
+    ) {
+        Ok(target) => target,
+        Err(response) => return *response,
+    };
+
+    let sha256 = match parse_sha256(payload.sha256.as_deref()) {
+        Ok(sha256) => sha256,
+        Err(response) => return *response,
+    };
+
     // Generate storage key: {user_id}/{uuid}/{filename}
     let upload_uuid = uuid::Uuid::new_v4();
     let storage_key = format!("{}/{}/{}", user.id, upload_uuid, payload.filename);
@@ -72,16 +118,22 @@ where
     // Create upload record in database
     let upload_input = UploadInsert {
         user_id: user.id,
-        storage_backend: "r2".to_owned(),
-        storage_profile: "default".to_owned(),
+        storage_backend: target.backend().to_owned(),
+        storage_profile: target.profile().to_owned(),
         storage_key: storage_key.clone(),
         content_type: payload.content_type.clone(),
         file_size: payload.file_size as i64,
         expires_at,
+        multipart_upload_id: None,
+        part_size: None,
+        sha256,
     };
 
     let upload = match state.sql_storage.uploads_create(upload_input).await {
         Ok(upload) => upload,
+        Err(SqlStorageError::QuotaExceeded { used, quota }) => {
+            return quota_exceeded(payload.file_size, used, quota);
+        }
         Err(e) => {
             tracing::error!("Failed to create upload record: {:?}", e);
             return (
@@ -95,38 +147,40 @@ where
     };
 
     // Generate presigned PUT URL
-    let presigned = if let Some(axum::Extension(presigner)) = presigner {
-        match presigner
-            .presign_put(&storage_key, &payload.content_type, DEFAULT_PRESIGN_EXPIRY)
-            .await
-        {
-            Ok(presigned) => presigned,
-            Err(e) => {
-                tracing::error!("Failed to generate presigned URL: {:?}", e);
-                return (
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    Json(V1ErrorResponse::internal_error(
-                        "Failed to generate upload URL",
-                    )),
-                )
-                    .into_response();
-            }
+    let presigned = match target
+        .presigner()
+        .presign_put(&storage_key, &payload.content_type, DEFAULT_PRESIGN_EXPIRY)
+        .await
+    {
+        Ok(presigned) => presigned,
+        Err(e) => {
+            tracing::error!("Failed to generate presigned URL: {:?}", e);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(V1ErrorResponse::internal_error(
+                    "Failed to generate upload URL",
+                )),
+            )
+                .into_response();
         }
-    } else {
-        return (
-            StatusCode::BAD_GATEWAY,
-            Json(V1ErrorResponse::internal_error(
-                "R2 storage is not configured",
-            )),
-        )
-            .into_response();
     };
 
+    audit::record(
+        &state.sql_storage,
+        Some(user.id),
+        "upload.init",
+        Some(upload.id),
+        None,
+        client_ip,
+    )
+    .await;
+
     (
         StatusCode::CREATED,
         Json(V1UploadsInitResponse {
             upload_id: upload.id.to_string(),
             storage_key,
+            storage_profile: target.profile().to_owned(),
             method: "PUT".to_owned(),
             upload_url: presigned.url,
             expires_at: presigned.expires_at.to_rfc3339(),
@@ -135,10 +189,15 @@ where
         .into_response()
 }
 
-/// Complete an upload after the file has been uploaded to R2.
+/// Complete an upload after the file has been uploaded to storage.
 ///
-/// This endpoint verifies the file exists in R2, creates the content record,
-/// and returns the created content.
+/// This endpoint verifies the file exists in storage and matches the size,
+/// MIME type (by stored metadata and magic bytes) and SHA-256 (if any)
+/// declared at init, creates the content record, and returns the created
+/// content. A mismatched upload is aborted and its file deleted. A file
+/// identical to one the user already stored is deduplicated: the content
+/// points at the existing object and the new copy is deleted. Thumbnails of
+/// image contents are generated in the background.
 #[utoipa::path(
     post,
     path = "/v1/uploads/complete",
@@ -146,7 +205,7 @@ where
     request_body = V1UploadsCompleteRequest,
     responses(
         (status = 201, description = "Upload completed", body = V1UploadsCompleteResponse),
-        (status = 400, description = "Bad request", body = V1ErrorResponse),
+        (status = 400, description = "Bad request, or the stored file does not match the declared size (`size_mismatch`), MIME type (`content_type_mismatch`) or SHA-256 (`checksum_mismatch`)", body = V1ErrorResponse),
         (status = 401, description = "Unauthorized", body = V1ErrorResponse),
         (status = 404, description = "Upload not found", body = V1ErrorResponse),
         (status = 500, description = "Internal server error", body = V1ErrorResponse),
@@ -157,8 +216,9 @@ where
 )]
 pub async fn v1_uploads_complete<S, U>(
     State(state): State<AppState<S, U>>,
-    presigner: Option<axum::Extension<R2Presigner>>,
+    storage: Option<axum::Extension<StorageRouter>>,
     auth: RequireAuth,
+    client_ip: ClientIp,
     Json(payload): Json<V1UploadsCompleteRequest>,
 ) -> impl IntoResponse
 where
@@ -247,39 +307,149 @@ where
             .into_response();
     }
 
-    // Verify file exists in R2 via HEAD request
-    let file_exists = if let Some(axum::Extension(ref presigner)) = presigner {
-        match presigner.file_exists(&upload.storage_key).await {
-            Ok(exists) => exists,
+    // The upload must be completed against the backend/profile it was initialized on
+    let Some(target) = storage.as_ref().and_then(|axum::Extension(router)| {
+        router.resolve(&upload.storage_backend, &upload.storage_profile)
+    }) else {
+        return (
+            StatusCode::BAD_GATEWAY,
+            Json(V1ErrorResponse::internal_error(format!(
+                "Storage backend '{}' (profile '{}') is not configured",
+                upload.storage_backend, upload.storage_profile
+            ))),
+        )
+            .into_response();
+    };
+
+    // Multipart uploads are assembled from their parts first
+    if let Some(multipart_upload_id) = &upload.multipart_upload_id {
+        for part in payload.parts {
+            match state
+                .sql_storage
+                .upload_parts_mark_uploaded(upload_id, part.part_number as i32, part.etag)
+                .await
+            {
+                Ok(Some(_)) => {}
+                Ok(None) => {
+                    return (
+                        StatusCode::BAD_REQUEST,
+                        Json(V1ErrorResponse::bad_request(format!(
+                            "Upload has no part {}",
+                            part.part_number
+                        ))),
+                    )
+                        .into_response();
+                }
+                Err(e) => {
+                    tracing::error!("Failed to record upload part: {:?}", e);
+                    return (
+                        StatusCode::INTERNAL_SERVER_ERROR,
+                        Json(V1ErrorResponse::internal_error(
+                            "Failed to record upload part",
+                        )),
+                    )
+                        .into_response();
+                }
+            }
+        }
+
+        let parts = match state.sql_storage.upload_parts_list(upload_id).await {
+            Ok(parts) => parts,
             Err(e) => {
-                tracing::error!("Failed to verify file existence: {:?}", e);
+                tracing::error!("Failed to list upload parts: {:?}", e);
                 return (
                     StatusCode::INTERNAL_SERVER_ERROR,
                     Json(V1ErrorResponse::internal_error(
-                        "Failed to verify upload completion",
+                        "Failed to list upload parts",
                     )),
                 )
                     .into_response();
             }
+        };
+
+        let mut completed = Vec::with_capacity(parts.len());
+        for part in parts {
+            match (UploadPartStatus::from_db_str(&part.status), part.etag) {
+                (Some(UploadPartStatus::Uploaded), Some(etag)) => completed.push(CompletedPart {
+                    part_number: part.part_number as u32,
+                    etag,
+                }),
+                _ => {
+                    return (
+                        StatusCode::BAD_REQUEST,
+                        Json(V1ErrorResponse::bad_request(format!(
+                            "Part {} has not been uploaded",
+                            part.part_number
+                        ))),
+                    )
+                        .into_response();
+                }
+            }
+        }
+
+        if let Err(e) = target
+            .presigner()
+            .complete_multipart_upload(&upload.storage_key, multipart_upload_id, &completed)
+            .await
+        {
+            tracing::warn!("Failed to complete multipart upload: {:?}", e);
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(V1ErrorResponse::bad_request(format!(
+                    "Failed to assemble uploaded parts: {e}"
+                ))),
+            )
+                .into_response();
+        }
+    }
+
+    // Verify the stored object via HEAD request
+    let metadata = match target.presigner().get_metadata(&upload.storage_key).await {
+        Ok(Some(metadata)) => metadata,
+        Ok(None) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(V1ErrorResponse::bad_request(
+                    "File not found in storage. Please upload the file first.",
+                )),
+            )
+                .into_response();
+        }
+        Err(e) => {
+            tracing::error!("Failed to verify file existence: {:?}", e);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(V1ErrorResponse::internal_error(
+                    "Failed to verify upload completion",
+                )),
+            )
+                .into_response();
         }
-    } else {
-        return (
-            StatusCode::BAD_GATEWAY,
-            Json(V1ErrorResponse::internal_error(
-                "R2 storage is not configured",
-            )),
-        )
-            .into_response();
     };
 
-    if !file_exists {
-        return (
-            StatusCode::BAD_REQUEST,
-            Json(V1ErrorResponse::bad_request(
-                "File not found in storage. Please upload the file first.",
-            )),
-        )
-            .into_response();
+    // The stored bytes must match what was declared at init
+    match stored_object_mismatch(target, &upload, &metadata).await {
+        Ok(None) => {}
+        Ok(Some(error)) => {
+            tracing::warn!(
+                upload_id = %upload.id,
+                storage_key = %upload.storage_key,
+                "Rejecting upload: {}",
+                error.message
+            );
+            reject_upload(&state.sql_storage, target, &upload).await;
+            return (StatusCode::BAD_REQUEST, Json(error)).into_response();
+        }
+        Err(e) => {
+            tracing::error!("Failed to read uploaded file: {:?}", e);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(V1ErrorResponse::internal_error(
+                    "Failed to verify upload completion",
+                )),
+            )
+                .into_response();
+        }
     }
 
     // Mark upload as completed
@@ -330,8 +500,9 @@ where
         content_type: upload.content_type,
         file_size: upload.file_size,
         visibility: Visibility::Private,
-        kind: None, // Defaults to "file" for uploaded content
-        body: None, // No inline body for uploaded files
+        kind: None,   // Defaults to "file" for uploaded content
+        body: None,   // No inline body for uploaded files
+        sha256: None, // Set when the stored object is shared below
     };
 
     let content = match state.sql_storage.contents_insert(content_input).await {
@@ -346,6 +517,35 @@ where
         }
     };
 
+    // Identical files of the same user share one stored object
+    let content = match upload.sha256 {
+        Some(sha256) => {
+            match share_stored_object(&state.sql_storage, target, &content, sha256).await {
+                Ok(Some(shared)) => shared,
+                Ok(None) => content,
+                Err(e) => {
+                    tracing::warn!(content_id = %content.id, "Failed to record storage object: {e:?}");
+                    content
+                }
+            }
+        }
+        None => content,
+    };
+
+    if let Some(axum::Extension(router)) = &storage {
+        spawn_media_processing(&state.sql_storage, router, &content);
+    }
+
+    audit::record(
+        &state.sql_storage,
+        Some(user.id),
+        "upload.complete",
+        Some(content.id),
+        Some(serde_json::json!({ "upload_id": upload_id })),
+        client_ip,
+    )
+    .await;
+
     (
         StatusCode::CREATED,
         Json(V1UploadsCompleteResponse {
@@ -354,3 +554,930 @@ where
     )
         .into_response()
 }
+
+/// Initialize a multipart upload.
+///
+/// The file is split into parts of `part_size` bytes (the last part may be
+/// smaller). Request presigned part URLs with `POST /v1/uploads/{upload_id}/parts`,
+/// upload each part, then call `/v1/uploads/complete` with the part `ETag`s.
+/// Only backends with multipart support (R2, local) accept this request.
+#[utoipa::path(
+    post,
+    path = "/v1/uploads/multipart/init",
+    tag = "uploads",
+    request_body = V1MultipartUploadInitRequest,
+    responses(
+        (status = 201, description = "Multipart upload initialized", body = V1MultipartUploadInitResponse),
+        (status = 400, description = "Invalid part size or SHA-256, unknown storage profile, content type not allowed, or multipart not supported by the storage backend (`multipart_unsupported`)", body = V1ErrorResponse),
+        (status = 401, description = "Unauthorized", body = V1ErrorResponse),
+        (status = 413, description = "File exceeds the storage profile's size limit or the user's storage quota", body = V1ErrorResponse),
+        (status = 500, description = "Internal server error", body = V1ErrorResponse),
+        (status = 502, description = "Storage is not configured", body = V1ErrorResponse),
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn v1_uploads_multipart_init<S, U>(
+    State(state): State<AppState<S, U>>,
+    storage: Option<axum::Extension<StorageRouter>>,
+    auth: RequireAuth,
+    client_ip: ClientIp,
+    Json(payload): Json<V1MultipartUploadInitRequest>,
+) -> impl IntoResponse
+where
+    S: SqlStorage,
+    U: UserStorage,
+{
+    // Resolve user from JWT username
+    let user = match state.user_storage.get_user(auth.username()).await {
+        Ok(Some(user)) => user,
+        Ok(None) => {
+            return (
+                StatusCode::UNAUTHORIZED,
+                Json(V1ErrorResponse::not_found("User not found")),
+            )
+                .into_response();
+        }
+        Err(e) => {
+            tracing::error!("Failed to get user: {}", e);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(V1ErrorResponse::internal_error("Failed to get user")),
+            )
+                .into_response();
+        }
+    };
+
+    let plan = match PartPlan::new(payload.file_size, payload.part_size) {
+        Ok(plan) => plan,
+        Err(msg) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(V1ErrorResponse::bad_request(msg)),
+            )
+                .into_response();
+        }
+    };
+
+    let target = match select_upload_target(
+        storage.as_ref(),
+        payload.storage_profile.as_deref(),
+        auth.username(),
+        &payload.content_type,
+        payload.file_size,
+    ) {
+        Ok(target) => target,
+        Err(response) => return *response,
+    };
+
+    let sha256 = match parse_sha256(payload.sha256.as_deref()) {
+        Ok(sha256) => sha256,
+        Err(response) => return *response,
+    };
+
+    // Generate storage key: {user_id}/{uuid}/{filename}
+    let upload_uuid = uuid::Uuid::new_v4();
+    let storage_key = format!("{}/{}/{}", user.id, upload_uuid, payload.filename);
+
+    let multipart_upload_id = match target
+        .presigner()
+        .create_multipart_upload(&storage_key, &payload.content_type)
+        .await
+    {
+        Ok(id) => id,
+        Err(e @ PresignError::Unsupported(_)) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(V1ErrorResponse::multipart_unsupported(e.to_string())),
+            )
+                .into_response();
+        }
+        Err(e) => {
+            tracing::error!("Failed to create multipart upload: {:?}", e);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(V1ErrorResponse::internal_error(
+                    "Failed to initialize upload",
+                )),
+            )
+                .into_response();
+        }
+    };
+
+    let expires_at =
+        chrono::Utc::now() + chrono::Duration::from_std(MULTIPART_UPLOAD_EXPIRY).unwrap();
+
+    let upload_input = UploadInsert {
+        user_id: user.id,
+        storage_backend: target.backend().to_owned(),
+        storage_profile: target.profile().to_owned(),
+        storage_key: storage_key.clone(),
+        content_type: payload.content_type.clone(),
+        file_size: payload.file_size as i64,
+        expires_at,
+        multipart_upload_id: Some(multipart_upload_id.clone()),
+        part_size: Some(plan.part_size as i64),
+        sha256,
+    };
+
+    let parts = (1..=plan.part_count)
+        .map(|part_number| UploadPartInsert {
+            part_number: part_number as i32,
+            size: plan.part_len(payload.file_size, part_number) as i64,
+        })
+        .collect();
+
+    let created = match state.sql_storage.uploads_create(upload_input).await {
+        Ok(upload) => state
+            .sql_storage
+            .upload_parts_create(upload.id, parts)
+            .await
+            .map(|()| upload),
+        Err(e) => Err(e),
+    };
+    let upload = match created {
+        Ok(upload) => upload,
+        Err(e) => {
+            if let Err(e) = target
+                .presigner()
+                .abort_multipart_upload(&storage_key, &multipart_upload_id)
+                .await
+            {
+                tracing::warn!("Failed to abort multipart upload: {:?}", e);
+            }
+            if let SqlStorageError::QuotaExceeded { used, quota } = e {
+                return quota_exceeded(payload.file_size, used, quota);
+            }
+            tracing::error!("Failed to create upload record: {:?}", e);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(V1ErrorResponse::internal_error(
+                    "Failed to initialize upload",
+                )),
+            )
+                .into_response();
+        }
+    };
+
+    audit::record(
+        &state.sql_storage,
+        Some(user.id),
+        "upload.multipart_init",
+        Some(upload.id),
+        None,
+        client_ip,
+    )
+    .await;
+
+    (
+        StatusCode::CREATED,
+        Json(V1MultipartUploadInitResponse {
+            upload_id: upload.id.to_string(),
+            storage_key,
+            storage_profile: target.profile().to_owned(),
+            part_size: plan.part_size,
+            part_count: plan.part_count,
+            expires_at: expires_at.to_rfc3339(),
+        }),
+    )
+        .into_response()
+}
+
+/// Get the state of an upload.
+///
+/// For multipart uploads this lists every part and whether it has been
+/// uploaded, so an interrupted upload can be resumed.
+#[utoipa::path(
+    get,
+    path = "/v1/uploads/{upload_id}",
+    tag = "uploads",
+    params(
+        ("upload_id" = String, Path, description = "Upload ID (UUID)")
+    ),
+    responses(
+        (status = 200, description = "Upload state", body = V1UploadStatusResponse),
+        (status = 400, description = "Invalid upload ID", body = V1ErrorResponse),
+        (status = 401, description = "Unauthorized", body = V1ErrorResponse),
+        (status = 404, description = "Upload not found", body = V1ErrorResponse),
+        (status = 500, description = "Internal server error", body = V1ErrorResponse),
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn v1_uploads_get<S, U>(
+    State(state): State<AppState<S, U>>,
+    auth: RequireAuth,
+    Path(upload_id): Path<String>,
+) -> impl IntoResponse
+where
+    S: SqlStorage,
+    U: UserStorage,
+{
+    let upload = match get_owned_upload(&state, &auth, &upload_id).await {
+        Ok(upload) => upload,
+        Err(response) => return *response,
+    };
+
+    let parts = if upload.multipart_upload_id.is_some() {
+        match state.sql_storage.upload_parts_list(upload.id).await {
+            Ok(parts) => parts.into_iter().map(V1UploadPart::from).collect(),
+            Err(e) => {
+                tracing::error!("Failed to list upload parts: {:?}", e);
+                return (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(V1ErrorResponse::internal_error(
+                        "Failed to list upload parts",
+                    )),
+                )
+                    .into_response();
+            }
+        }
+    } else {
+        Vec::new()
+    };
+
+    (
+        StatusCode::OK,
+        Json(V1UploadStatusResponse {
+            upload_id: upload.id.to_string(),
+            storage_key: upload.storage_key,
+            storage_profile: upload.storage_profile,
+            status: upload.status,
+            file_size: upload.file_size as u64,
+            multipart: upload.multipart_upload_id.is_some(),
+            part_size: upload.part_size.map(|size| size as u64),
+            parts,
+            expires_at: upload.expires_at.to_rfc3339(),
+        }),
+    )
+        .into_response()
+}
+
+/// Presign part upload URLs for a multipart upload.
+///
+/// Each URL accepts a PUT of exactly the part's bytes; the response carries an
+/// `ETag` header that must be reported back (here via
+/// `PUT /v1/uploads/{upload_id}/parts/{part_number}` or on completion).
+#[utoipa::path(
+    post,
+    path = "/v1/uploads/{upload_id}/parts",
+    tag = "uploads",
+    params(
+        ("upload_id" = String, Path, description = "Upload ID (UUID)")
+    ),
+    request_body = V1UploadPartsRequest,
+    responses(
+        (status = 200, description = "Presigned part URLs", body = V1UploadPartsResponse),
+        (status = 400, description = "Not a multipart upload, unknown part or upload no longer open", body = V1ErrorResponse),
+        (status = 401, description = "Unauthorized", body = V1ErrorResponse),
+        (status = 404, description = "Upload not found", body = V1ErrorResponse),
+        (status = 500, description = "Internal server error", body = V1ErrorResponse),
+        (status = 502, description = "Storage is not configured", body = V1ErrorResponse),
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn v1_uploads_parts_presign<S, U>(
+    State(state): State<AppState<S, U>>,
+    storage: Option<axum::Extension<StorageRouter>>,
+    auth: RequireAuth,
+    Path(upload_id): Path<String>,
+    Json(payload): Json<V1UploadPartsRequest>,
+) -> impl IntoResponse
+where
+    S: SqlStorage,
+    U: UserStorage,
+{
+    let upload = match get_owned_upload(&state, &auth, &upload_id).await {
+        Ok(upload) => upload,
+        Err(response) => return *response,
+    };
+    let multipart_upload_id = match open_multipart_upload(&upload) {
+        Ok(id) => id,
+        Err(response) => return *response,
+    };
+    let target = match upload_target(storage.as_ref(), &upload) {
+        Ok(target) => target,
+        Err(response) => return *response,
+    };
+
+    let parts = match state.sql_storage.upload_parts_list(upload.id).await {
+        Ok(parts) => parts,
+        Err(e) => {
+            tracing::error!("Failed to list upload parts: {:?}", e);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(V1ErrorResponse::internal_error(
+                    "Failed to list upload parts",
+                )),
+            )
+                .into_response();
+        }
+    };
+
+    let selected: Vec<_> = if payload.part_numbers.is_empty() {
+        parts
+            .into_iter()
+            .filter(|part| {
+                UploadPartStatus::from_db_str(&part.status) != Some(UploadPartStatus::Uploaded)
+            })
+            .collect()
+    } else {
+        let mut selected = Vec::with_capacity(payload.part_numbers.len());
+        for part_number in &payload.part_numbers {
+            match parts
+                .iter()
+                .find(|part| part.part_number as u32 == *part_number)
+            {
+                Some(part) => selected.push(part.clone()),
+                None => {
+                    return (
+                        StatusCode::BAD_REQUEST,
+                        Json(V1ErrorResponse::bad_request(format!(
+                            "Upload has no part {part_number}"
+                        ))),
+                    )
+                        .into_response();
+                }
+            }
+        }
+        selected
+    };
+
+    let mut urls = Vec::with_capacity(selected.len());
+    for part in selected {
+        let presigned = match target
+            .presigner()
+            .presign_upload_part(
+                &upload.storage_key,
+                multipart_upload_id,
+                part.part_number as u32,
+                DEFAULT_PRESIGN_EXPIRY,
+            )
+            .await
+        {
+            Ok(presigned) => presigned,
+            Err(e) => {
+                tracing::error!("Failed to presign upload part: {:?}", e);
+                return (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(V1ErrorResponse::internal_error(
+                        "Failed to generate upload URL",
+                    )),
+                )
+                    .into_response();
+            }
+        };
+        urls.push(V1UploadPartUrl {
+            part_number: part.part_number as u32,
+            size: part.size as u64,
+            method: "PUT".to_owned(),
+            upload_url: presigned.url,
+            expires_at: presigned.expires_at.to_rfc3339(),
+        });
+    }
+
+    (StatusCode::OK, Json(V1UploadPartsResponse { parts: urls })).into_response()
+}
+
+/// Record that a part of a multipart upload has been uploaded.
+#[utoipa::path(
+    put,
+    path = "/v1/uploads/{upload_id}/parts/{part_number}",
+    tag = "uploads",
+    params(
+        ("upload_id" = String, Path, description = "Upload ID (UUID)"),
+        ("part_number" = u32, Path, description = "1-based part number")
+    ),
+    request_body = V1UploadPartUpdateRequest,
+    responses(
+        (status = 200, description = "Part recorded", body = V1UploadPart),
+        (status = 400, description = "Not a multipart upload, unknown part or upload no longer open", body = V1ErrorResponse),
+        (status = 401, description = "Unauthorized", body = V1ErrorResponse),
+        (status = 404, description = "Upload not found", body = V1ErrorResponse),
+        (status = 500, description = "Internal server error", body = V1ErrorResponse),
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn v1_uploads_part_update<S, U>(
+    State(state): State<AppState<S, U>>,
+    auth: RequireAuth,
+    client_ip: ClientIp,
+    Path((upload_id, part_number)): Path<(String, u32)>,
+    Json(payload): Json<V1UploadPartUpdateRequest>,
+) -> impl IntoResponse
+where
+    S: SqlStorage,
+    U: UserStorage,
+{
+    let upload = match get_owned_upload(&state, &auth, &upload_id).await {
+        Ok(upload) => upload,
+        Err(response) => return *response,
+    };
+    if let Err(response) = open_multipart_upload(&upload) {
+        return *response;
+    }
+
+    match state
+        .sql_storage
+        .upload_parts_mark_uploaded(upload.id, part_number as i32, payload.etag)
+        .await
+    {
+        Ok(Some(part)) => {
+            audit::record(
+                &state.sql_storage,
+                Some(upload.user_id),
+                "upload.part_update",
+                Some(upload.id),
+                Some(serde_json::json!({ "part_number": part_number })),
+                client_ip,
+            )
+            .await;
+            (StatusCode::OK, Json(V1UploadPart::from(part))).into_response()
+        }
+        Ok(None) => (
+            StatusCode::BAD_REQUEST,
+            Json(V1ErrorResponse::bad_request(format!(
+                "Upload has no part {part_number}"
+            ))),
+        )
+            .into_response(),
+        Err(e) => {
+            tracing::error!("Failed to record upload part: {:?}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(V1ErrorResponse::internal_error(
+                    "Failed to record upload part",
+                )),
+            )
+                .into_response()
+        }
+    }
+}
+
+/// Abort an upload.
+///
+/// For multipart uploads the uploaded parts are discarded from storage.
+#[utoipa::path(
+    post,
+    path = "/v1/uploads/{upload_id}/abort",
+    tag = "uploads",
+    params(
+        ("upload_id" = String, Path, description = "Upload ID (UUID)")
+    ),
+    responses(
+        (status = 204, description = "Upload aborted"),
+        (status = 400, description = "Upload is no longer open", body = V1ErrorResponse),
+        (status = 401, description = "Unauthorized", body = V1ErrorResponse),
+        (status = 404, description = "Upload not found", body = V1ErrorResponse),
+        (status = 500, description = "Internal server error", body = V1ErrorResponse),
+        (status = 502, description = "Storage is not configured", body = V1ErrorResponse),
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn v1_uploads_abort<S, U>(
+    State(state): State<AppState<S, U>>,
+    storage: Option<axum::Extension<StorageRouter>>,
+    auth: RequireAuth,
+    client_ip: ClientIp,
+    Path(upload_id): Path<String>,
+) -> impl IntoResponse
+where
+    S: SqlStorage,
+    U: UserStorage,
+{
+    let upload = match get_owned_upload(&state, &auth, &upload_id).await {
+        Ok(upload) => upload,
+        Err(response) => return *response,
+    };
+
+    if let Some(multipart_upload_id) = &upload.multipart_upload_id {
+        let target = match upload_target(storage.as_ref(), &upload) {
+            Ok(target) => target,
+            Err(response) => return *response,
+        };
+        if let Err(e) = target
+            .presigner()
+            .abort_multipart_upload(&upload.storage_key, multipart_upload_id)
+            .await
+        {
+            tracing::error!("Failed to abort multipart upload: {:?}", e);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(V1ErrorResponse::internal_error("Failed to abort upload")),
+            )
+                .into_response();
+        }
+    }
+
+    match state
+        .sql_storage
+        .uploads_abort(upload.id, upload.user_id)
+        .await
+    {
+        Ok(Some(_)) => {
+            audit::record(
+                &state.sql_storage,
+                Some(upload.user_id),
+                "upload.abort",
+                Some(upload.id),
+                None,
+                client_ip,
+            )
+            .await;
+            StatusCode::NO_CONTENT.into_response()
+        }
+        Ok(None) => (
+            StatusCode::NOT_FOUND,
+            Json(V1ErrorResponse::not_found("Upload not found")),
+        )
+            .into_response(),
+        Err(SqlStorageError::Invalid(msg)) => (
+            StatusCode::BAD_REQUEST,
+            Json(V1ErrorResponse::bad_request(msg)),
+        )
+            .into_response(),
+        Err(e) => {
+            tracing::error!("Failed to abort upload: {:?}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(V1ErrorResponse::internal_error("Failed to abort upload")),
+            )
+                .into_response()
+        }
+    }
+}
+
+/// Extract the media metadata of a content and generate its thumbnails in
+/// the background.
+///
+/// The original is downloaded once for both. Failures are logged: the content
+/// keeps empty metadata and the view URL falls back to the original.
+fn spawn_media_processing<S: SqlStorage>(
+    sql_storage: &S,
+    router: &StorageRouter,
+    content: &ContentRow,
+) {
+    if MediaKind::for_content_type(&content.content_type).is_none()
+        || content.file_size.max(0) as u64 > MAX_MEDIA_SOURCE_SIZE
+    {
+        return;
+    }
+    let Some(target) = router.resolve(&content.storage_backend, &content.storage_profile) else {
+        return;
+    };
+    let sql_storage = sql_storage.clone();
+    let file_storage = target.file_storage().clone();
+    let content_id = content.id;
+    let storage_key = content.storage_key.clone();
+    let content_type = content.content_type.clone();
+
+    tokio::spawn(async move {
+        let source = match file_storage.download_file(&storage_key).await {
+            Ok(source) => source,
+            Err(e) => {
+                tracing::warn!(content_id = %content_id, "Failed to download media: {e}");
+                return;
+            }
+        };
+
+        let extracted = {
+            let content_type = content_type.clone();
+            tokio::task::spawn_blocking(move || {
+                let metadata = extract_media_metadata(&content_type, &source);
+                (metadata, source)
+            })
+            .await
+        };
+        let source = match extracted {
+            Ok((metadata, source)) => {
+                if !metadata.is_empty() {
+                    let metadata = serde_json::to_value(&metadata).unwrap_or_default();
+                    if let Err(e) = sql_storage
+                        .contents_set_media_metadata(content_id, metadata)
+                        .await
+                    {
+                        tracing::warn!(content_id = %content_id, "Failed to store media metadata: {e:?}");
+                    }
+                }
+                source
+            }
+            Err(e) => {
+                tracing::warn!(content_id = %content_id, "Media metadata task failed: {e}");
+                return;
+            }
+        };
+
+        match generate_thumbnails(&file_storage, &storage_key, &content_type, source).await {
+            Ok(sizes) if !sizes.is_empty() => {
+                tracing::debug!(content_id = %content_id, ?sizes, "Generated thumbnails");
+            }
+            Ok(_) => {}
+            Err(e) => {
+                tracing::warn!(content_id = %content_id, "Failed to generate thumbnails: {e}");
+            }
+        }
+    });
+}
+
+/// Select the storage target for a new upload, mapping selection errors to responses.
+fn select_upload_target<'a>(
+    storage: Option<&'a axum::Extension<StorageRouter>>,
+    requested_profile: Option<&str>,
+    username: &str,
+    content_type: &str,
+    file_size: u64,
+) -> Result<&'a StorageTarget, Box<Response>> {
+    let selected = match storage {
+        Some(axum::Extension(router)) => {
+            router.select_upload_target(requested_profile, username, content_type, file_size)
+        }
+        None => Err(StorageSelectionError::NotConfigured),
+    };
+    match selected {
+        Ok(target) => Ok(target),
+        Err(e @ StorageSelectionError::NotConfigured) => Err((
+            StatusCode::BAD_GATEWAY,
+            Json(V1ErrorResponse::internal_error(e.to_string())),
+        )
+            .into_response()
+            .into()),
+        Err(e @ StorageSelectionError::TooLarge { .. }) => Err((
+            StatusCode::PAYLOAD_TOO_LARGE,
+            Json(V1ErrorResponse::bad_request(e.to_string())),
+        )
+            .into_response()
+            .into()),
+        Err(e) => Err((
+            StatusCode::BAD_REQUEST,
+            Json(V1ErrorResponse::bad_request(e.to_string())),
+        )
+            .into_response()
+            .into()),
+    }
+}
+
+/// `413 quota_exceeded` for `file_size` bytes that do not fit the quota.
+fn quota_exceeded(file_size: u64, used: i64, quota: i64) -> Response {
+    (
+        StatusCode::PAYLOAD_TOO_LARGE,
+        Json(V1ErrorResponse::quota_exceeded(format!(
+            "Upload of {file_size} bytes exceeds the storage quota ({used} of {quota} bytes used)"
+        ))),
+    )
+        .into_response()
+}
+
+/// Validate the SHA-256 a client declared for an upload.
+fn parse_sha256(sha256: Option<&str>) -> Result<Option<String>, Box<Response>> {
+    sha256
+        .map(|digest| {
+            normalize_sha256(digest).ok_or_else(|| {
+                (
+                    StatusCode::BAD_REQUEST,
+                    Json(V1ErrorResponse::bad_request(
+                        "sha256 must be a hex-encoded SHA-256 digest",
+                    )),
+                )
+                    .into_response()
+                    .into()
+            })
+        })
+        .transpose()
+}
+
+/// Check the stored object of an upload against what was declared at init.
+///
+/// Returns the error to reject the upload with, or `None` if the object
+/// matches.
+async fn stored_object_mismatch(
+    target: &StorageTarget,
+    upload: &UploadRow,
+    metadata: &PresignFileMetadata,
+) -> Result<Option<V1ErrorResponse>, FileStorageError> {
+    if metadata.content_length != upload.file_size as u64 {
+        return Ok(Some(V1ErrorResponse::size_mismatch(format!(
+            "Stored file is {} bytes, declared {}",
+            metadata.content_length, upload.file_size
+        ))));
+    }
+
+    if mime_essence(&metadata.content_type) != mime_essence(&upload.content_type) {
+        return Ok(Some(V1ErrorResponse::content_type_mismatch(format!(
+            "Stored file has content type {}, declared {}",
+            metadata.content_type, upload.content_type
+        ))));
+    }
+
+    let head = target
+        .file_storage()
+        .download_range(&upload.storage_key, 0..SNIFF_LEN)
+        .await?;
+    if let Some(detected) = sniff_mismatch(&upload.content_type, &head) {
+        return Ok(Some(V1ErrorResponse::content_type_mismatch(format!(
+            "File content is {detected}, declared {}",
+            upload.content_type
+        ))));
+    }
+
+    if let Some(expected) = &upload.sha256 {
+        let actual = object_sha256(target.file_storage(), &upload.storage_key).await?;
+        if actual != *expected {
+            return Ok(Some(V1ErrorResponse::checksum_mismatch(format!(
+                "Stored file has SHA-256 {actual}, expected {expected}"
+            ))));
+        }
+    }
+
+    Ok(None)
+}
+
+/// Abort a rejected upload and delete its stored object.
+async fn reject_upload<S: SqlStorage>(sql_storage: &S, target: &StorageTarget, upload: &UploadRow) {
+    if let Err(e) = sql_storage.uploads_abort(upload.id, upload.user_id).await {
+        tracing::warn!(upload_id = %upload.id, "Failed to abort rejected upload: {:?}", e);
+    }
+    if let Err(e) = target.file_storage().delete_file(&upload.storage_key).await {
+        tracing::warn!(
+            upload_id = %upload.id,
+            storage_key = %upload.storage_key,
+            "Failed to delete rejected upload: {e}"
+        );
+    }
+}
+
+/// Take a reference on the user's stored object with `sha256` for a content.
+///
+/// The content's object becomes the shared object unless the user already
+/// stored an identical file, in which case the content is moved to the
+/// existing object and its copy is deleted. Returns `None` if the content no
+/// longer exists.
+async fn share_stored_object<S: SqlStorage>(
+    sql_storage: &S,
+    target: &StorageTarget,
+    content: &ContentRow,
+    sha256: String,
+) -> Result<Option<ContentRow>, SqlStorageError> {
+    let Some((object, shared)) = sql_storage
+        .contents_share_storage_object(
+            content.id,
+            StorageObjectInsert {
+                user_id: content.user_id,
+                sha256,
+                storage_backend: content.storage_backend.clone(),
+                storage_profile: content.storage_profile.clone(),
+                storage_key: content.storage_key.clone(),
+                file_size: content.file_size,
+            },
+        )
+        .await?
+    else {
+        return Ok(None);
+    };
+
+    if object.storage_key != content.storage_key {
+        tracing::info!(
+            content_id = %content.id,
+            storage_key = %object.storage_key,
+            "Upload duplicates a stored object, deleting the new copy"
+        );
+        if let Err(e) = target
+            .file_storage()
+            .delete_file(&content.storage_key)
+            .await
+        {
+            tracing::warn!(
+                content_id = %content.id,
+                storage_key = %content.storage_key,
+                "Failed to delete duplicate upload: {e}"
+            );
+        }
+    }
+
+    Ok(Some(shared))
+}
+
+/// Resolve the storage target an existing upload was initialized on.
+fn upload_target<'a>(
+    storage: Option<&'a axum::Extension<StorageRouter>>,
+    upload: &UploadRow,
+) -> Result<&'a StorageTarget, Box<Response>> {
+    storage
+        .and_then(|axum::Extension(router)| {
+            router.resolve(&upload.storage_backend, &upload.storage_profile)
+        })
+        .ok_or_else(|| {
+            (
+                StatusCode::BAD_GATEWAY,
+                Json(V1ErrorResponse::internal_error(format!(
+                    "Storage backend '{}' (profile '{}') is not configured",
+                    upload.storage_backend, upload.storage_profile
+                ))),
+            )
+                .into_response()
+                .into()
+        })
+}
+
+/// Load an upload owned by the authenticated user.
+///
+/// Uploads of other users are reported as not found.
+async fn get_owned_upload<S, U>(
+    state: &AppState<S, U>,
+    auth: &RequireAuth,
+    upload_id: &str,
+) -> Result<UploadRow, Box<Response>>
+where
+    S: SqlStorage,
+    U: UserStorage,
+{
+    let user = match state.user_storage.get_user(auth.username()).await {
+        Ok(Some(user)) => user,
+        Ok(None) => {
+            return Err((
+                StatusCode::UNAUTHORIZED,
+                Json(V1ErrorResponse::not_found("User not found")),
+            )
+                .into_response()
+                .into());
+        }
+        Err(e) => {
+            tracing::error!("Failed to get user: {}", e);
+            return Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(V1ErrorResponse::internal_error("Failed to get user")),
+            )
+                .into_response()
+                .into());
+        }
+    };
+
+    let Ok(upload_id) = uuid::Uuid::parse_str(upload_id) else {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(V1ErrorResponse::bad_request("Invalid upload ID format")),
+        )
+            .into_response()
+            .into());
+    };
+
+    match state.sql_storage.uploads_get(upload_id).await {
+        Ok(Some(upload)) if upload.user_id == user.id => Ok(upload),
+        Ok(_) => Err((
+            StatusCode::NOT_FOUND,
+            Json(V1ErrorResponse::not_found("Upload not found")),
+        )
+            .into_response()
+            .into()),
+        Err(e) => {
+            tracing::error!("Failed to get upload: {:?}", e);
+            Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(V1ErrorResponse::internal_error("Failed to get upload")),
+            )
+                .into_response()
+                .into())
+        }
+    }
+}
+
+/// Return the backend upload ID of a multipart upload that can still receive parts.
+fn open_multipart_upload(upload: &UploadRow) -> Result<&str, Box<Response>> {
+    let Some(multipart_upload_id) = upload.multipart_upload_id.as_deref() else {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(V1ErrorResponse::bad_request(
+                "Upload is not a multipart upload",
+            )),
+        )
+            .into_response()
+            .into());
+    };
+    if upload.status != "initiated" {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(V1ErrorResponse::bad_request(format!(
+                "Upload is already {}",
+                upload.status
+            ))),
+        )
+            .into_response()
+            .into());
+    }
+    if upload.expires_at < chrono::Utc::now() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(V1ErrorResponse::bad_request("Upload has expired")),
+        )
+            .into_response()
+            .into());
+    }
+    Ok(multipart_upload_id)
+}
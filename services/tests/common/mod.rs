@@ -4,15 +4,21 @@
 //! - `MockSqlStorage` - A mock implementation of `SqlStorage` for testing
 //! - Test constants and helper functions
 
+use std::sync::{Arc, RwLock};
+
 use collects_services::{
     config::Config,
     database::{
-        ContentGroupItemRow, ContentGroupRow, ContentGroupShareRow, ContentRow,
+        AccountExportRow, AuditLogInsert, AuditLogRow, AuditLogsListParams, ContentGroupItemRow,
+        ContentGroupRow, ContentGroupShareRow, ContentRevisionRow, ContentRow, ContentSearchHit,
         ContentShareCreateForLink, ContentShareCreateForUser, ContentShareRow, ContentStatus,
-        ContentsInsert, ContentsListParams, ContentsUpdate, GroupCreate, GroupShareCreateForLink,
-        GroupShareCreateForUser, GroupStatus, GroupUpdate, GroupsListParams, OtpAttemptRecord,
-        OtpRateLimitConfig, ShareLinkCreate, ShareLinkRow, ShareLinkUpdate, SqlStorage,
-        SqlStorageError, TagCreate, TagRow, TagUpdate, UploadInsert, UploadRow,
+        ContentsBatchOp, ContentsInsert, ContentsListParams, ContentsSearchParams, ContentsUpdate,
+        GroupCreate, GroupShareCreateForLink, GroupShareCreateForUser, GroupStatus, GroupUpdate,
+        GroupsListParams, OtpAttemptRecord, OtpRateLimitConfig, ShareLinkAccessInsert,
+        ShareLinkAccessStats, ShareLinkCreate, ShareLinkPasswordRateLimitConfig, ShareLinkRow,
+        ShareLinkTargetRow, ShareLinkUpdate, SharedWithUserRow, SqlStorage, SqlStorageError,
+        StorageObjectInsert, StorageObjectRow, StorageUsage, TagCreate, TagRow, TagUpdate,
+        TrashPurgeParams, UploadInsert, UploadPartInsert, UploadPartRow, UploadRow, UserShareRow,
     },
     routes,
     users::storage::{MockUserStorage, StoredUser},
@@ -30,12 +36,23 @@ pub const TEST_CONTENT_ID: uuid::Uuid =
 /// JWT secret used for test token generation.
 pub const TEST_JWT_SECRET: &str = "test-jwt-secret-key-for-local-development";
 
+/// A recorded share link password attempt.
+#[derive(Debug, Clone)]
+pub struct PasswordAttempt {
+    pub id: uuid::Uuid,
+    pub share_link_id: uuid::Uuid,
+    pub ip_address: Option<std::net::IpAddr>,
+    pub success: bool,
+}
+
 /// Mock SQL storage for testing.
 #[derive(Clone)]
 pub struct MockSqlStorage {
     pub is_connected: bool,
     /// When set, mock methods will use this user ID for ownership checks.
     pub mock_user_id: Option<uuid::Uuid>,
+    /// Share link password attempts, counted against the rate limits.
+    pub password_attempts: Arc<RwLock<Vec<PasswordAttempt>>>,
 }
 
 impl MockSqlStorage {
@@ -44,6 +61,7 @@ impl MockSqlStorage {
         Self {
             is_connected: true,
             mock_user_id: None,
+            password_attempts: Arc::default(),
         }
     }
 
@@ -53,6 +71,7 @@ impl MockSqlStorage {
         Self {
             is_connected: true,
             mock_user_id: Some(user_id),
+            password_attempts: Arc::default(),
         }
     }
 
@@ -62,6 +81,7 @@ impl MockSqlStorage {
         Self {
             is_connected: false,
             mock_user_id: None,
+            password_attempts: Arc::default(),
         }
     }
 }
@@ -118,6 +138,8 @@ impl SqlStorage for MockSqlStorage {
             visibility: input.visibility.as_db_str().to_owned(),
             kind: input.kind.unwrap_or_else(|| "file".to_owned()),
             body: input.body,
+            sha256: input.sha256,
+            metadata: serde_json::json!({}),
             trashed_at: None,
             archived_at: None,
             created_at: chrono::Utc::now(),
@@ -125,6 +147,13 @@ impl SqlStorage for MockSqlStorage {
         })
     }
 
+    async fn contents_insert_within_quota(
+        &self,
+        input: ContentsInsert,
+    ) -> Result<ContentRow, SqlStorageError> {
+        self.contents_insert(input).await
+    }
+
     async fn contents_get(&self, id: uuid::Uuid) -> Result<Option<ContentRow>, SqlStorageError> {
         if id == TEST_CONTENT_ID
             && let Some(user_id) = self.mock_user_id
@@ -143,6 +172,8 @@ impl SqlStorage for MockSqlStorage {
                 visibility: "private".to_owned(),
                 kind: "file".to_owned(),
                 body: None,
+                sha256: None,
+                metadata: serde_json::json!({}),
                 trashed_at: None,
                 archived_at: None,
                 created_at: chrono::Utc::now(),
@@ -160,6 +191,14 @@ impl SqlStorage for MockSqlStorage {
         Ok(vec![])
     }
 
+    async fn contents_search(
+        &self,
+        _user_id: uuid::Uuid,
+        _params: ContentsSearchParams,
+    ) -> Result<Vec<ContentSearchHit>, SqlStorageError> {
+        Ok(vec![])
+    }
+
     async fn contents_update_metadata(
         &self,
         _id: uuid::Uuid,
@@ -265,10 +304,19 @@ impl SqlStorage for MockSqlStorage {
         Ok(vec![])
     }
 
+    async fn tags_get(
+        &self,
+        _user_id: uuid::Uuid,
+        _tag_id: uuid::Uuid,
+    ) -> Result<Option<TagRow>, SqlStorageError> {
+        Ok(None)
+    }
+
     async fn tags_delete(
         &self,
         _user_id: uuid::Uuid,
         _tag_id: uuid::Uuid,
+        _expected_updated_at: Option<chrono::DateTime<chrono::Utc>>,
     ) -> Result<bool, SqlStorageError> {
         Ok(false)
     }
@@ -357,6 +405,7 @@ impl SqlStorage for MockSqlStorage {
         &self,
         _id: uuid::Uuid,
         _owner_id: uuid::Uuid,
+        _expected_updated_at: Option<chrono::DateTime<chrono::Utc>>,
     ) -> Result<bool, SqlStorageError> {
         Ok(false)
     }
@@ -365,6 +414,75 @@ impl SqlStorage for MockSqlStorage {
         Ok(())
     }
 
+    async fn share_links_rehash_password(
+        &self,
+        _id: uuid::Uuid,
+        _current_hash: &str,
+        _new_hash: &str,
+    ) -> Result<(), SqlStorageError> {
+        Ok(())
+    }
+
+    async fn share_link_accesses_insert(
+        &self,
+        _input: ShareLinkAccessInsert,
+    ) -> Result<(), SqlStorageError> {
+        Ok(())
+    }
+
+    async fn share_link_accesses_stats(
+        &self,
+        _share_link_id: uuid::Uuid,
+        _since: chrono::DateTime<chrono::Utc>,
+    ) -> Result<ShareLinkAccessStats, SqlStorageError> {
+        Ok(ShareLinkAccessStats::default())
+    }
+
+    // Every recorded failure counts; the mock has no time window. The lock is
+    // held from the check to the insert, so concurrent attempts are counted
+    // one at a time.
+    async fn share_link_password_reserve_attempt(
+        &self,
+        share_link_id: uuid::Uuid,
+        ip_address: Option<std::net::IpAddr>,
+        config: &ShareLinkPasswordRateLimitConfig,
+    ) -> Result<Option<uuid::Uuid>, SqlStorageError> {
+        let mut attempts = self.password_attempts.write().unwrap();
+        let failures = attempts.iter().filter(|attempt| !attempt.success);
+        let link_failures = failures
+            .clone()
+            .filter(|attempt| attempt.share_link_id == share_link_id)
+            .count() as i64;
+        let ip_failures = failures
+            .filter(|attempt| ip_address.is_some() && attempt.ip_address == ip_address)
+            .count() as i64;
+        if link_failures >= config.max_attempts_per_link
+            || ip_failures >= config.max_attempts_per_ip
+        {
+            return Ok(None);
+        }
+
+        let id = uuid::Uuid::new_v4();
+        attempts.push(PasswordAttempt {
+            id,
+            share_link_id,
+            ip_address,
+            success: false,
+        });
+        Ok(Some(id))
+    }
+
+    async fn share_link_password_attempt_succeeded(
+        &self,
+        attempt_id: uuid::Uuid,
+    ) -> Result<(), SqlStorageError> {
+        let mut attempts = self.password_attempts.write().unwrap();
+        if let Some(attempt) = attempts.iter_mut().find(|attempt| attempt.id == attempt_id) {
+            attempt.success = true;
+        }
+        Ok(())
+    }
+
     async fn content_shares_attach_link(
         &self,
         _content_id: uuid::Uuid,
@@ -433,6 +551,51 @@ impl SqlStorage for MockSqlStorage {
         ))
     }
 
+    async fn content_shares_list_for_users(
+        &self,
+        _content_id: uuid::Uuid,
+    ) -> Result<Vec<UserShareRow>, SqlStorageError> {
+        Ok(vec![])
+    }
+
+    async fn group_shares_list_for_users(
+        &self,
+        _group_id: uuid::Uuid,
+    ) -> Result<Vec<UserShareRow>, SqlStorageError> {
+        Ok(vec![])
+    }
+
+    async fn content_shares_delete_for_user(
+        &self,
+        _content_id: uuid::Uuid,
+        _shared_with_user_id: uuid::Uuid,
+    ) -> Result<bool, SqlStorageError> {
+        Ok(false)
+    }
+
+    async fn group_shares_delete_for_user(
+        &self,
+        _group_id: uuid::Uuid,
+        _shared_with_user_id: uuid::Uuid,
+    ) -> Result<bool, SqlStorageError> {
+        Ok(false)
+    }
+
+    async fn shares_list_shared_with_user(
+        &self,
+        _user_id: uuid::Uuid,
+    ) -> Result<Vec<SharedWithUserRow>, SqlStorageError> {
+        Ok(vec![])
+    }
+
+    async fn content_shares_permission_for_user(
+        &self,
+        _content_id: uuid::Uuid,
+        _user_id: uuid::Uuid,
+    ) -> Result<Option<String>, SqlStorageError> {
+        Ok(None)
+    }
+
     async fn otp_record_attempt(&self, _input: OtpAttemptRecord) -> Result<(), SqlStorageError> {
         Ok(())
     }
@@ -459,6 +622,9 @@ impl SqlStorage for MockSqlStorage {
             expires_at: input.expires_at,
             created_at: chrono::Utc::now(),
             completed_at: None,
+            multipart_upload_id: input.multipart_upload_id,
+            part_size: input.part_size,
+            sha256: input.sha256,
         })
     }
 
@@ -474,6 +640,183 @@ impl SqlStorage for MockSqlStorage {
         Ok(None)
     }
 
+    async fn uploads_abort(
+        &self,
+        _id: uuid::Uuid,
+        _user_id: uuid::Uuid,
+    ) -> Result<Option<UploadRow>, SqlStorageError> {
+        Ok(None)
+    }
+
+    async fn uploads_expire_stale(
+        &self,
+        _now: chrono::DateTime<chrono::Utc>,
+        _limit: i64,
+    ) -> Result<Vec<UploadRow>, SqlStorageError> {
+        Ok(vec![])
+    }
+
+    async fn upload_parts_create(
+        &self,
+        _upload_id: uuid::Uuid,
+        _parts: Vec<UploadPartInsert>,
+    ) -> Result<(), SqlStorageError> {
+        Ok(())
+    }
+
+    async fn upload_parts_list(
+        &self,
+        _upload_id: uuid::Uuid,
+    ) -> Result<Vec<UploadPartRow>, SqlStorageError> {
+        Ok(vec![])
+    }
+
+    async fn upload_parts_mark_uploaded(
+        &self,
+        _upload_id: uuid::Uuid,
+        _part_number: i32,
+        _etag: String,
+    ) -> Result<Option<UploadPartRow>, SqlStorageError> {
+        Ok(None)
+    }
+
+    async fn content_revisions_list(
+        &self,
+        _content_id: uuid::Uuid,
+    ) -> Result<Vec<ContentRevisionRow>, SqlStorageError> {
+        Ok(vec![])
+    }
+
+    async fn content_revisions_get(
+        &self,
+        _content_id: uuid::Uuid,
+        _revision: i32,
+    ) -> Result<Option<ContentRevisionRow>, SqlStorageError> {
+        Ok(None)
+    }
+
+    async fn contents_batch(
+        &self,
+        _user_id: uuid::Uuid,
+        _ids: &[uuid::Uuid],
+        _op: ContentsBatchOp,
+        _now: chrono::DateTime<chrono::Utc>,
+    ) -> Result<Vec<uuid::Uuid>, SqlStorageError> {
+        Ok(vec![])
+    }
+
+    async fn contents_set_media_metadata(
+        &self,
+        _id: uuid::Uuid,
+        _metadata: serde_json::Value,
+    ) -> Result<bool, SqlStorageError> {
+        Ok(false)
+    }
+
+    async fn contents_purge_trashed(
+        &self,
+        _params: TrashPurgeParams,
+    ) -> Result<Vec<ContentRow>, SqlStorageError> {
+        Ok(vec![])
+    }
+
+    async fn groups_purge_trashed(
+        &self,
+        _params: TrashPurgeParams,
+    ) -> Result<Vec<ContentGroupRow>, SqlStorageError> {
+        Ok(vec![])
+    }
+
+    async fn users_storage_usage(
+        &self,
+        _user_id: uuid::Uuid,
+        _now: chrono::DateTime<chrono::Utc>,
+    ) -> Result<StorageUsage, SqlStorageError> {
+        Ok(StorageUsage::default())
+    }
+
+    async fn users_get_storage_quota(
+        &self,
+        _user_id: uuid::Uuid,
+    ) -> Result<Option<i64>, SqlStorageError> {
+        Ok(None)
+    }
+
+    async fn users_set_storage_quota(
+        &self,
+        _user_id: uuid::Uuid,
+        _quota_bytes: Option<i64>,
+    ) -> Result<bool, SqlStorageError> {
+        Ok(false)
+    }
+
+    async fn contents_share_storage_object(
+        &self,
+        _content_id: uuid::Uuid,
+        _input: StorageObjectInsert,
+    ) -> Result<Option<(StorageObjectRow, ContentRow)>, SqlStorageError> {
+        Ok(None)
+    }
+
+    async fn storage_objects_release(
+        &self,
+        _user_id: uuid::Uuid,
+        _sha256: &str,
+    ) -> Result<Option<StorageObjectRow>, SqlStorageError> {
+        Ok(None)
+    }
+
+    async fn share_links_targets_for_owner(
+        &self,
+        _owner_id: uuid::Uuid,
+    ) -> Result<Vec<ShareLinkTargetRow>, SqlStorageError> {
+        Ok(vec![])
+    }
+
+    async fn account_exports_create(
+        &self,
+        _user_id: uuid::Uuid,
+        _expires_at: chrono::DateTime<chrono::Utc>,
+    ) -> Result<AccountExportRow, SqlStorageError> {
+        Err(SqlStorageError::Db(
+            "MockSqlStorage.account_exports_create: unimplemented".to_owned(),
+        ))
+    }
+
+    async fn account_exports_get(
+        &self,
+        _id: uuid::Uuid,
+    ) -> Result<Option<AccountExportRow>, SqlStorageError> {
+        Ok(None)
+    }
+
+    async fn account_exports_complete(
+        &self,
+        _id: uuid::Uuid,
+        _manifest: serde_json::Value,
+    ) -> Result<Option<AccountExportRow>, SqlStorageError> {
+        Ok(None)
+    }
+
+    async fn account_exports_fail(
+        &self,
+        _id: uuid::Uuid,
+        _error: &str,
+    ) -> Result<Option<AccountExportRow>, SqlStorageError> {
+        Ok(None)
+    }
+
+    async fn audit_logs_insert(&self, _entry: AuditLogInsert) -> Result<(), SqlStorageError> {
+        Ok(())
+    }
+
+    async fn audit_logs_list(
+        &self,
+        _params: AuditLogsListParams,
+    ) -> Result<Vec<AuditLogRow>, SqlStorageError> {
+        Ok(vec![])
+    }
+
     async fn revoked_tokens_add(
         &self,
         _token_hash: &str,
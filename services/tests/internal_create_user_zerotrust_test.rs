@@ -14,11 +14,15 @@ use collects_services::{
     auth::{AccessClaims, JwksKeyResolver},
     config::Config,
     database::{
-        ContentGroupItemRow, ContentGroupRow, ContentGroupShareRow, ContentRow, ContentShareRow,
-        ContentStatus, ContentsInsert, ContentsListParams, ContentsUpdate, GroupCreate,
-        GroupShareCreateForLink, GroupShareCreateForUser, GroupStatus, GroupUpdate,
-        GroupsListParams, ShareLinkCreate, ShareLinkRow, ShareLinkUpdate, SqlStorage,
-        SqlStorageError, TagCreate, TagRow, TagUpdate, UploadInsert, UploadRow,
+        AccountExportRow, AuditLogInsert, AuditLogRow, AuditLogsListParams, ContentGroupItemRow,
+        ContentGroupRow, ContentGroupShareRow, ContentRevisionRow, ContentRow, ContentSearchHit,
+        ContentShareRow, ContentStatus, ContentsBatchOp, ContentsInsert, ContentsListParams,
+        ContentsSearchParams, ContentsUpdate, GroupCreate, GroupShareCreateForLink,
+        GroupShareCreateForUser, GroupStatus, GroupUpdate, GroupsListParams, ShareLinkAccessInsert,
+        ShareLinkAccessStats, ShareLinkCreate, ShareLinkPasswordRateLimitConfig, ShareLinkRow,
+        ShareLinkTargetRow, ShareLinkUpdate, SharedWithUserRow, SqlStorage, SqlStorageError,
+        StorageObjectInsert, StorageObjectRow, StorageUsage, TagCreate, TagRow, TagUpdate,
+        TrashPurgeParams, UploadInsert, UploadPartInsert, UploadPartRow, UploadRow, UserShareRow,
     },
     internal,
     users::AppState,
@@ -49,6 +53,15 @@ impl SqlStorage for MockSqlStorage {
         ))
     }
 
+    async fn contents_insert_within_quota(
+        &self,
+        _input: ContentsInsert,
+    ) -> Result<ContentRow, SqlStorageError> {
+        Err(SqlStorageError::Db(
+            "MockSqlStorage.contents_insert_within_quota: unimplemented".to_owned(),
+        ))
+    }
+
     async fn contents_get(&self, _id: uuid::Uuid) -> Result<Option<ContentRow>, SqlStorageError> {
         Ok(None)
     }
@@ -61,6 +74,14 @@ impl SqlStorage for MockSqlStorage {
         Ok(vec![])
     }
 
+    async fn contents_search(
+        &self,
+        _user_id: uuid::Uuid,
+        _params: ContentsSearchParams,
+    ) -> Result<Vec<ContentSearchHit>, SqlStorageError> {
+        Ok(vec![])
+    }
+
     async fn contents_update_metadata(
         &self,
         _id: uuid::Uuid,
@@ -166,10 +187,19 @@ impl SqlStorage for MockSqlStorage {
         Ok(vec![])
     }
 
+    async fn tags_get(
+        &self,
+        _user_id: uuid::Uuid,
+        _tag_id: uuid::Uuid,
+    ) -> Result<Option<TagRow>, SqlStorageError> {
+        Ok(None)
+    }
+
     async fn tags_delete(
         &self,
         _user_id: uuid::Uuid,
         _tag_id: uuid::Uuid,
+        _expected_updated_at: Option<chrono::DateTime<chrono::Utc>>,
     ) -> Result<bool, SqlStorageError> {
         Ok(false)
     }
@@ -258,6 +288,7 @@ impl SqlStorage for MockSqlStorage {
         &self,
         _id: uuid::Uuid,
         _owner_id: uuid::Uuid,
+        _expected_updated_at: Option<chrono::DateTime<chrono::Utc>>,
     ) -> Result<bool, SqlStorageError> {
         Ok(false)
     }
@@ -266,6 +297,46 @@ impl SqlStorage for MockSqlStorage {
         Ok(())
     }
 
+    async fn share_links_rehash_password(
+        &self,
+        _id: uuid::Uuid,
+        _current_hash: &str,
+        _new_hash: &str,
+    ) -> Result<(), SqlStorageError> {
+        Ok(())
+    }
+
+    async fn share_link_accesses_insert(
+        &self,
+        _input: ShareLinkAccessInsert,
+    ) -> Result<(), SqlStorageError> {
+        Ok(())
+    }
+
+    async fn share_link_accesses_stats(
+        &self,
+        _share_link_id: uuid::Uuid,
+        _since: chrono::DateTime<chrono::Utc>,
+    ) -> Result<ShareLinkAccessStats, SqlStorageError> {
+        Ok(ShareLinkAccessStats::default())
+    }
+
+    async fn share_link_password_reserve_attempt(
+        &self,
+        _share_link_id: uuid::Uuid,
+        _ip_address: Option<std::net::IpAddr>,
+        _config: &ShareLinkPasswordRateLimitConfig,
+    ) -> Result<Option<uuid::Uuid>, SqlStorageError> {
+        Ok(Some(uuid::Uuid::new_v4()))
+    }
+
+    async fn share_link_password_attempt_succeeded(
+        &self,
+        _attempt_id: uuid::Uuid,
+    ) -> Result<(), SqlStorageError> {
+        Ok(())
+    }
+
     async fn content_shares_attach_link(
         &self,
         _content_id: uuid::Uuid,
@@ -334,6 +405,51 @@ impl SqlStorage for MockSqlStorage {
         ))
     }
 
+    async fn content_shares_list_for_users(
+        &self,
+        _content_id: uuid::Uuid,
+    ) -> Result<Vec<UserShareRow>, SqlStorageError> {
+        Ok(vec![])
+    }
+
+    async fn group_shares_list_for_users(
+        &self,
+        _group_id: uuid::Uuid,
+    ) -> Result<Vec<UserShareRow>, SqlStorageError> {
+        Ok(vec![])
+    }
+
+    async fn content_shares_delete_for_user(
+        &self,
+        _content_id: uuid::Uuid,
+        _shared_with_user_id: uuid::Uuid,
+    ) -> Result<bool, SqlStorageError> {
+        Ok(false)
+    }
+
+    async fn group_shares_delete_for_user(
+        &self,
+        _group_id: uuid::Uuid,
+        _shared_with_user_id: uuid::Uuid,
+    ) -> Result<bool, SqlStorageError> {
+        Ok(false)
+    }
+
+    async fn shares_list_shared_with_user(
+        &self,
+        _user_id: uuid::Uuid,
+    ) -> Result<Vec<SharedWithUserRow>, SqlStorageError> {
+        Ok(vec![])
+    }
+
+    async fn content_shares_permission_for_user(
+        &self,
+        _content_id: uuid::Uuid,
+        _user_id: uuid::Uuid,
+    ) -> Result<Option<String>, SqlStorageError> {
+        Ok(None)
+    }
+
     async fn otp_record_attempt(
         &self,
         _input: collects_services::database::OtpAttemptRecord,
@@ -370,6 +486,183 @@ impl SqlStorage for MockSqlStorage {
         Ok(None)
     }
 
+    async fn uploads_abort(
+        &self,
+        _id: uuid::Uuid,
+        _user_id: uuid::Uuid,
+    ) -> Result<Option<UploadRow>, SqlStorageError> {
+        Ok(None)
+    }
+
+    async fn uploads_expire_stale(
+        &self,
+        _now: chrono::DateTime<chrono::Utc>,
+        _limit: i64,
+    ) -> Result<Vec<UploadRow>, SqlStorageError> {
+        Ok(vec![])
+    }
+
+    async fn upload_parts_create(
+        &self,
+        _upload_id: uuid::Uuid,
+        _parts: Vec<UploadPartInsert>,
+    ) -> Result<(), SqlStorageError> {
+        Ok(())
+    }
+
+    async fn upload_parts_list(
+        &self,
+        _upload_id: uuid::Uuid,
+    ) -> Result<Vec<UploadPartRow>, SqlStorageError> {
+        Ok(vec![])
+    }
+
+    async fn upload_parts_mark_uploaded(
+        &self,
+        _upload_id: uuid::Uuid,
+        _part_number: i32,
+        _etag: String,
+    ) -> Result<Option<UploadPartRow>, SqlStorageError> {
+        Ok(None)
+    }
+
+    async fn content_revisions_list(
+        &self,
+        _content_id: uuid::Uuid,
+    ) -> Result<Vec<ContentRevisionRow>, SqlStorageError> {
+        Ok(vec![])
+    }
+
+    async fn content_revisions_get(
+        &self,
+        _content_id: uuid::Uuid,
+        _revision: i32,
+    ) -> Result<Option<ContentRevisionRow>, SqlStorageError> {
+        Ok(None)
+    }
+
+    async fn contents_batch(
+        &self,
+        _user_id: uuid::Uuid,
+        _ids: &[uuid::Uuid],
+        _op: ContentsBatchOp,
+        _now: chrono::DateTime<chrono::Utc>,
+    ) -> Result<Vec<uuid::Uuid>, SqlStorageError> {
+        Ok(vec![])
+    }
+
+    async fn contents_set_media_metadata(
+        &self,
+        _id: uuid::Uuid,
+        _metadata: serde_json::Value,
+    ) -> Result<bool, SqlStorageError> {
+        Ok(false)
+    }
+
+    async fn contents_purge_trashed(
+        &self,
+        _params: TrashPurgeParams,
+    ) -> Result<Vec<ContentRow>, SqlStorageError> {
+        Ok(vec![])
+    }
+
+    async fn groups_purge_trashed(
+        &self,
+        _params: TrashPurgeParams,
+    ) -> Result<Vec<ContentGroupRow>, SqlStorageError> {
+        Ok(vec![])
+    }
+
+    async fn users_storage_usage(
+        &self,
+        _user_id: uuid::Uuid,
+        _now: chrono::DateTime<chrono::Utc>,
+    ) -> Result<StorageUsage, SqlStorageError> {
+        Ok(StorageUsage::default())
+    }
+
+    async fn users_get_storage_quota(
+        &self,
+        _user_id: uuid::Uuid,
+    ) -> Result<Option<i64>, SqlStorageError> {
+        Ok(None)
+    }
+
+    async fn users_set_storage_quota(
+        &self,
+        _user_id: uuid::Uuid,
+        _quota_bytes: Option<i64>,
+    ) -> Result<bool, SqlStorageError> {
+        Ok(false)
+    }
+
+    async fn contents_share_storage_object(
+        &self,
+        _content_id: uuid::Uuid,
+        _input: StorageObjectInsert,
+    ) -> Result<Option<(StorageObjectRow, ContentRow)>, SqlStorageError> {
+        Ok(None)
+    }
+
+    async fn storage_objects_release(
+        &self,
+        _user_id: uuid::Uuid,
+        _sha256: &str,
+    ) -> Result<Option<StorageObjectRow>, SqlStorageError> {
+        Ok(None)
+    }
+
+    async fn share_links_targets_for_owner(
+        &self,
+        _owner_id: uuid::Uuid,
+    ) -> Result<Vec<ShareLinkTargetRow>, SqlStorageError> {
+        Ok(vec![])
+    }
+
+    async fn account_exports_create(
+        &self,
+        _user_id: uuid::Uuid,
+        _expires_at: chrono::DateTime<chrono::Utc>,
+    ) -> Result<AccountExportRow, SqlStorageError> {
+        Err(SqlStorageError::Db(
+            "MockSqlStorage.account_exports_create: unimplemented".to_owned(),
+        ))
+    }
+
+    async fn account_exports_get(
+        &self,
+        _id: uuid::Uuid,
+    ) -> Result<Option<AccountExportRow>, SqlStorageError> {
+        Ok(None)
+    }
+
+    async fn account_exports_complete(
+        &self,
+        _id: uuid::Uuid,
+        _manifest: serde_json::Value,
+    ) -> Result<Option<AccountExportRow>, SqlStorageError> {
+        Ok(None)
+    }
+
+    async fn account_exports_fail(
+        &self,
+        _id: uuid::Uuid,
+        _error: &str,
+    ) -> Result<Option<AccountExportRow>, SqlStorageError> {
+        Ok(None)
+    }
+
+    async fn audit_logs_insert(&self, _entry: AuditLogInsert) -> Result<(), SqlStorageError> {
+        Ok(())
+    }
+
+    async fn audit_logs_list(
+        &self,
+        _params: AuditLogsListParams,
+    ) -> Result<Vec<AuditLogRow>, SqlStorageError> {
+        Ok(vec![])
+    }
+
     async fn revoked_tokens_add(
         &self,
         _token_hash: &str,
@@ -16,11 +16,14 @@ use axum::{
 use collects_services::{
     config::Config,
     database::{
-        ContentGroupRow, ContentRow, ShareLinkCreate, ShareLinkRow, ShareLinkUpdate, SqlStorage,
-        SqlStorageError,
+        AccountExportRow, AuditLogInsert, AuditLogRow, AuditLogsListParams, ContentGroupRow,
+        ContentGroupShareRow, ContentRow, ContentShareRow, ShareLinkAccessDay,
+        ShareLinkAccessInsert, ShareLinkAccessStats, ShareLinkCreate,
+        ShareLinkPasswordRateLimitConfig, ShareLinkRow, ShareLinkTargetRow, ShareLinkUpdate,
+        SharedWithUserRow, SqlStorage, SqlStorageError, UserShareRow,
     },
     routes,
-    users::storage::MockUserStorage,
+    users::storage::{MockUserStorage, StoredUser},
 };
 use common::{MockSqlStorage, TEST_USER_ID, create_test_user_storage, generate_test_token};
 use serde_json::{Value, json};
@@ -31,6 +34,12 @@ use tower::ServiceExt;
 // Test Helpers
 // =============================================================================
 
+/// ID of a second user, to share contents and groups with.
+const OTHER_USER_ID: uuid::Uuid = uuid::Uuid::from_u128(0x00000000_0000_0000_0000_000000000002);
+
+/// Recorded share link accesses with the time they were recorded at.
+type AccessLog = Vec<(ShareLinkAccessInsert, chrono::DateTime<chrono::Utc>)>;
+
 /// A MockSqlStorage that can return share links for testing.
 #[derive(Clone)]
 struct ShareLinksMockSqlStorage {
@@ -40,6 +49,10 @@ struct ShareLinksMockSqlStorage {
     groups: Arc<RwLock<Vec<ContentGroupRow>>>,
     content_shares: Arc<RwLock<Vec<(uuid::Uuid, uuid::Uuid)>>>, // (content_id, share_link_id)
     group_shares: Arc<RwLock<Vec<(uuid::Uuid, uuid::Uuid)>>>,   // (group_id, share_link_id)
+    accesses: Arc<RwLock<AccessLog>>,
+    user_content_shares: Arc<RwLock<Vec<ContentShareRow>>>,
+    user_group_shares: Arc<RwLock<Vec<ContentGroupShareRow>>>,
+    group_contents: Arc<RwLock<Vec<(uuid::Uuid, uuid::Uuid)>>>, // (group_id, content_id)
 }
 
 impl ShareLinksMockSqlStorage {
@@ -51,6 +64,19 @@ impl ShareLinksMockSqlStorage {
             groups: Arc::new(RwLock::new(vec![])),
             content_shares: Arc::new(RwLock::new(vec![])),
             group_shares: Arc::new(RwLock::new(vec![])),
+            accesses: Arc::new(RwLock::new(vec![])),
+            user_content_shares: Arc::new(RwLock::new(vec![])),
+            user_group_shares: Arc::new(RwLock::new(vec![])),
+            group_contents: Arc::new(RwLock::new(vec![])),
+        }
+    }
+
+    /// Username of the test users, in place of joining the users table.
+    fn username(id: uuid::Uuid) -> String {
+        if id == OTHER_USER_ID {
+            "otheruser".to_owned()
+        } else {
+            "testuser".to_owned()
         }
     }
 
@@ -82,6 +108,7 @@ impl ShareLinksMockSqlStorage {
             expires_at: None,
             is_active: true,
             created_at: chrono::Utc::now(),
+            updated_at: chrono::Utc::now(),
         }
     }
 
@@ -100,6 +127,8 @@ impl ShareLinksMockSqlStorage {
             visibility: "private".to_owned(),
             kind: "file".to_owned(),
             body: None,
+            sha256: None,
+            metadata: serde_json::json!({}),
             trashed_at: None,
             archived_at: None,
             created_at: chrono::Utc::now(),
@@ -137,6 +166,13 @@ impl SqlStorage for ShareLinksMockSqlStorage {
         self.inner.contents_insert(input).await
     }
 
+    async fn contents_insert_within_quota(
+        &self,
+        input: collects_services::database::ContentsInsert,
+    ) -> Result<ContentRow, SqlStorageError> {
+        self.inner.contents_insert_within_quota(input).await
+    }
+
     async fn contents_get(&self, id: uuid::Uuid) -> Result<Option<ContentRow>, SqlStorageError> {
         let contents = self.contents.read().unwrap();
         Ok(contents.iter().find(|c| c.id == id).cloned())
@@ -150,6 +186,14 @@ impl SqlStorage for ShareLinksMockSqlStorage {
         self.inner.contents_list_for_user(user_id, params).await
     }
 
+    async fn contents_search(
+        &self,
+        user_id: uuid::Uuid,
+        params: collects_services::database::ContentsSearchParams,
+    ) -> Result<Vec<collects_services::database::ContentSearchHit>, SqlStorageError> {
+        self.inner.contents_search(user_id, params).await
+    }
+
     async fn contents_update_metadata(
         &self,
         id: uuid::Uuid,
@@ -267,12 +311,23 @@ impl SqlStorage for ShareLinksMockSqlStorage {
         self.inner.tags_list_for_user(user_id).await
     }
 
+    async fn tags_get(
+        &self,
+        user_id: uuid::Uuid,
+        tag_id: uuid::Uuid,
+    ) -> Result<Option<collects_services::database::TagRow>, SqlStorageError> {
+        self.inner.tags_get(user_id, tag_id).await
+    }
+
     async fn tags_delete(
         &self,
         user_id: uuid::Uuid,
         tag_id: uuid::Uuid,
+        expected_updated_at: Option<chrono::DateTime<chrono::Utc>>,
     ) -> Result<bool, SqlStorageError> {
-        self.inner.tags_delete(user_id, tag_id).await
+        self.inner
+            .tags_delete(user_id, tag_id, expected_updated_at)
+            .await
     }
 
     async fn tags_update(
@@ -324,6 +379,7 @@ impl SqlStorage for ShareLinksMockSqlStorage {
             expires_at: input.expires_at,
             is_active: true,
             created_at: chrono::Utc::now(),
+            updated_at: chrono::Utc::now(),
         };
         self.share_links.write().unwrap().push(share_link.clone());
         Ok(share_link)
@@ -385,10 +441,13 @@ impl SqlStorage for ShareLinksMockSqlStorage {
         input: ShareLinkUpdate,
     ) -> Result<Option<ShareLinkRow>, SqlStorageError> {
         let mut share_links = self.share_links.write().unwrap();
-        if let Some(link) = share_links
-            .iter_mut()
-            .find(|s| s.id == id && s.owner_id == owner_id)
-        {
+        if let Some(link) = share_links.iter_mut().find(|s| {
+            s.id == id
+                && s.owner_id == owner_id
+                && input
+                    .expected_updated_at
+                    .is_none_or(|expected| expected == s.updated_at)
+        }) {
             // Option<Option<T>>: None=no change, Some(None)=clear, Some(Some(v))=set
             if let Some(name) = input.name {
                 link.name = name; // This correctly handles Some(None) to clear
@@ -408,6 +467,7 @@ impl SqlStorage for ShareLinksMockSqlStorage {
             if let Some(is_active) = input.is_active {
                 link.is_active = is_active;
             }
+            link.updated_at = chrono::Utc::now();
             Ok(Some(link.clone()))
         } else {
             Ok(None)
@@ -418,10 +478,15 @@ impl SqlStorage for ShareLinksMockSqlStorage {
         &self,
         id: uuid::Uuid,
         owner_id: uuid::Uuid,
+        expected_updated_at: Option<chrono::DateTime<chrono::Utc>>,
     ) -> Result<bool, SqlStorageError> {
         let mut share_links = self.share_links.write().unwrap();
         let initial_len = share_links.len();
-        share_links.retain(|s| !(s.id == id && s.owner_id == owner_id));
+        share_links.retain(|s| {
+            !(s.id == id
+                && s.owner_id == owner_id
+                && expected_updated_at.is_none_or(|expected| expected == s.updated_at))
+        });
         Ok(share_links.len() < initial_len)
     }
 
@@ -433,6 +498,89 @@ impl SqlStorage for ShareLinksMockSqlStorage {
         Ok(())
     }
 
+    async fn share_links_rehash_password(
+        &self,
+        id: uuid::Uuid,
+        current_hash: &str,
+        new_hash: &str,
+    ) -> Result<(), SqlStorageError> {
+        let mut share_links = self.share_links.write().unwrap();
+        if let Some(link) = share_links
+            .iter_mut()
+            .find(|s| s.id == id && s.password_hash.as_deref() == Some(current_hash))
+        {
+            link.password_hash = Some(new_hash.to_owned());
+        }
+        Ok(())
+    }
+
+    async fn share_link_accesses_insert(
+        &self,
+        input: ShareLinkAccessInsert,
+    ) -> Result<(), SqlStorageError> {
+        self.accesses
+            .write()
+            .unwrap()
+            .push((input, chrono::Utc::now()));
+        Ok(())
+    }
+
+    async fn share_link_accesses_stats(
+        &self,
+        share_link_id: uuid::Uuid,
+        since: chrono::DateTime<chrono::Utc>,
+    ) -> Result<ShareLinkAccessStats, SqlStorageError> {
+        let accesses = self.accesses.read().unwrap();
+        let link_accesses: Vec<_> = accesses
+            .iter()
+            .filter(|(access, _)| access.share_link_id == share_link_id)
+            .collect();
+        let visitors = |items: &[&(ShareLinkAccessInsert, chrono::DateTime<chrono::Utc>)]| {
+            items
+                .iter()
+                .map(|(access, _)| (access.ip_address, access.user_agent.clone()))
+                .collect::<std::collections::HashSet<_>>()
+                .len() as i64
+        };
+        let mut days = std::collections::BTreeMap::<chrono::NaiveDate, Vec<_>>::new();
+        for item in link_accesses.iter().filter(|(_, at)| *at >= since) {
+            days.entry(item.1.date_naive()).or_default().push(*item);
+        }
+        Ok(ShareLinkAccessStats {
+            total_accesses: link_accesses.len() as i64,
+            unique_visitors: visitors(&link_accesses),
+            last_accessed_at: link_accesses.iter().map(|(_, at)| *at).max(),
+            daily: days
+                .into_iter()
+                .map(|(day, items)| ShareLinkAccessDay {
+                    day,
+                    accesses: items.len() as i64,
+                    unique_visitors: visitors(&items),
+                })
+                .collect(),
+        })
+    }
+
+    async fn share_link_password_reserve_attempt(
+        &self,
+        share_link_id: uuid::Uuid,
+        ip_address: Option<std::net::IpAddr>,
+        config: &ShareLinkPasswordRateLimitConfig,
+    ) -> Result<Option<uuid::Uuid>, SqlStorageError> {
+        self.inner
+            .share_link_password_reserve_attempt(share_link_id, ip_address, config)
+            .await
+    }
+
+    async fn share_link_password_attempt_succeeded(
+        &self,
+        attempt_id: uuid::Uuid,
+    ) -> Result<(), SqlStorageError> {
+        self.inner
+            .share_link_password_attempt_succeeded(attempt_id)
+            .await
+    }
+
     async fn content_shares_attach_link(
         &self,
         content_id: uuid::Uuid,
@@ -500,8 +648,27 @@ impl SqlStorage for ShareLinksMockSqlStorage {
     async fn content_shares_create_for_user(
         &self,
         input: collects_services::database::ContentShareCreateForUser,
-    ) -> Result<collects_services::database::ContentShareRow, SqlStorageError> {
-        self.inner.content_shares_create_for_user(input).await
+    ) -> Result<ContentShareRow, SqlStorageError> {
+        let mut shares = self.user_content_shares.write().unwrap();
+        let permission = input.permission.as_db_str().to_owned();
+        if let Some(share) = shares.iter_mut().find(|s| {
+            s.content_id == input.content_id
+                && s.shared_with_user_id == Some(input.shared_with_user_id)
+        }) {
+            share.permission = permission;
+            return Ok(share.clone());
+        }
+        let share = ContentShareRow {
+            id: uuid::Uuid::new_v4(),
+            content_id: input.content_id,
+            shared_with_user_id: Some(input.shared_with_user_id),
+            share_link_id: None,
+            permission,
+            created_at: chrono::Utc::now(),
+            created_by: input.created_by,
+        };
+        shares.push(share.clone());
+        Ok(share)
     }
 
     async fn content_shares_create_for_link(
@@ -514,8 +681,26 @@ impl SqlStorage for ShareLinksMockSqlStorage {
     async fn group_shares_create_for_user(
         &self,
         input: collects_services::database::GroupShareCreateForUser,
-    ) -> Result<collects_services::database::ContentGroupShareRow, SqlStorageError> {
-        self.inner.group_shares_create_for_user(input).await
+    ) -> Result<ContentGroupShareRow, SqlStorageError> {
+        let mut shares = self.user_group_shares.write().unwrap();
+        let permission = input.permission.as_db_str().to_owned();
+        if let Some(share) = shares.iter_mut().find(|s| {
+            s.group_id == input.group_id && s.shared_with_user_id == Some(input.shared_with_user_id)
+        }) {
+            share.permission = permission;
+            return Ok(share.clone());
+        }
+        let share = ContentGroupShareRow {
+            id: uuid::Uuid::new_v4(),
+            group_id: input.group_id,
+            shared_with_user_id: Some(input.shared_with_user_id),
+            share_link_id: None,
+            permission,
+            created_at: chrono::Utc::now(),
+            created_by: input.created_by,
+        };
+        shares.push(share.clone());
+        Ok(share)
     }
 
     async fn group_shares_create_for_link(
@@ -525,6 +710,158 @@ impl SqlStorage for ShareLinksMockSqlStorage {
         self.inner.group_shares_create_for_link(input).await
     }
 
+    async fn content_shares_list_for_users(
+        &self,
+        content_id: uuid::Uuid,
+    ) -> Result<Vec<UserShareRow>, SqlStorageError> {
+        let shares = self.user_content_shares.read().unwrap();
+        Ok(shares
+            .iter()
+            .filter(|s| s.content_id == content_id)
+            .filter_map(|s| {
+                let user_id = s.shared_with_user_id?;
+                Some(UserShareRow {
+                    id: s.id,
+                    shared_with_user_id: user_id,
+                    shared_with_username: Self::username(user_id),
+                    permission: s.permission.clone(),
+                    created_at: s.created_at,
+                })
+            })
+            .collect())
+    }
+
+    async fn group_shares_list_for_users(
+        &self,
+        group_id: uuid::Uuid,
+    ) -> Result<Vec<UserShareRow>, SqlStorageError> {
+        let shares = self.user_group_shares.read().unwrap();
+        Ok(shares
+            .iter()
+            .filter(|s| s.group_id == group_id)
+            .filter_map(|s| {
+                let user_id = s.shared_with_user_id?;
+                Some(UserShareRow {
+                    id: s.id,
+                    shared_with_user_id: user_id,
+                    shared_with_username: Self::username(user_id),
+                    permission: s.permission.clone(),
+                    created_at: s.created_at,
+                })
+            })
+            .collect())
+    }
+
+    async fn content_shares_delete_for_user(
+        &self,
+        content_id: uuid::Uuid,
+        shared_with_user_id: uuid::Uuid,
+    ) -> Result<bool, SqlStorageError> {
+        let mut shares = self.user_content_shares.write().unwrap();
+        let before = shares.len();
+        shares.retain(|s| {
+            !(s.content_id == content_id && s.shared_with_user_id == Some(shared_with_user_id))
+        });
+        Ok(shares.len() < before)
+    }
+
+    async fn group_shares_delete_for_user(
+        &self,
+        group_id: uuid::Uuid,
+        shared_with_user_id: uuid::Uuid,
+    ) -> Result<bool, SqlStorageError> {
+        let mut shares = self.user_group_shares.write().unwrap();
+        let before = shares.len();
+        shares.retain(|s| {
+            !(s.group_id == group_id && s.shared_with_user_id == Some(shared_with_user_id))
+        });
+        Ok(shares.len() < before)
+    }
+
+    async fn shares_list_shared_with_user(
+        &self,
+        user_id: uuid::Uuid,
+    ) -> Result<Vec<SharedWithUserRow>, SqlStorageError> {
+        let contents = self.contents.read().unwrap();
+        let groups = self.groups.read().unwrap();
+        let mut rows: Vec<SharedWithUserRow> = self
+            .user_content_shares
+            .read()
+            .unwrap()
+            .iter()
+            .filter(|s| s.shared_with_user_id == Some(user_id))
+            .filter_map(|s| {
+                let content = contents
+                    .iter()
+                    .find(|c| c.id == s.content_id && c.status != "trashed")?;
+                Some(SharedWithUserRow {
+                    item_type: "content".to_owned(),
+                    item_id: content.id,
+                    title: content.title.clone(),
+                    owner_id: content.user_id,
+                    owner_username: Self::username(content.user_id),
+                    permission: s.permission.clone(),
+                    shared_at: s.created_at,
+                })
+            })
+            .collect();
+        rows.extend(
+            self.user_group_shares
+                .read()
+                .unwrap()
+                .iter()
+                .filter(|s| s.shared_with_user_id == Some(user_id))
+                .filter_map(|s| {
+                    let group = groups
+                        .iter()
+                        .find(|g| g.id == s.group_id && g.status != "trashed")?;
+                    Some(SharedWithUserRow {
+                        item_type: "group".to_owned(),
+                        item_id: group.id,
+                        title: group.name.clone(),
+                        owner_id: group.user_id,
+                        owner_username: Self::username(group.user_id),
+                        permission: s.permission.clone(),
+                        shared_at: s.created_at,
+                    })
+                }),
+        );
+        rows.sort_by_key(|row| std::cmp::Reverse(row.shared_at));
+        Ok(rows)
+    }
+
+    async fn content_shares_permission_for_user(
+        &self,
+        content_id: uuid::Uuid,
+        user_id: uuid::Uuid,
+    ) -> Result<Option<String>, SqlStorageError> {
+        let group_contents = self.group_contents.read().unwrap();
+        let mut permissions: Vec<String> = self
+            .user_content_shares
+            .read()
+            .unwrap()
+            .iter()
+            .filter(|s| s.content_id == content_id && s.shared_with_user_id == Some(user_id))
+            .map(|s| s.permission.clone())
+            .collect();
+        permissions.extend(
+            self.user_group_shares
+                .read()
+                .unwrap()
+                .iter()
+                .filter(|s| {
+                    s.shared_with_user_id == Some(user_id)
+                        && group_contents.contains(&(s.group_id, content_id))
+                })
+                .map(|s| s.permission.clone()),
+        );
+        Ok(permissions
+            .iter()
+            .find(|p| *p == "download")
+            .or_else(|| permissions.first())
+            .cloned())
+    }
+
     async fn otp_record_attempt(
         &self,
         input: collects_services::database::OtpAttemptRecord,
@@ -565,6 +902,188 @@ impl SqlStorage for ShareLinksMockSqlStorage {
         self.inner.uploads_complete(id, user_id).await
     }
 
+    async fn uploads_abort(
+        &self,
+        id: uuid::Uuid,
+        user_id: uuid::Uuid,
+    ) -> Result<Option<collects_services::database::UploadRow>, SqlStorageError> {
+        self.inner.uploads_abort(id, user_id).await
+    }
+
+    async fn uploads_expire_stale(
+        &self,
+        now: chrono::DateTime<chrono::Utc>,
+        limit: i64,
+    ) -> Result<Vec<collects_services::database::UploadRow>, SqlStorageError> {
+        self.inner.uploads_expire_stale(now, limit).await
+    }
+
+    async fn upload_parts_create(
+        &self,
+        upload_id: uuid::Uuid,
+        parts: Vec<collects_services::database::UploadPartInsert>,
+    ) -> Result<(), SqlStorageError> {
+        self.inner.upload_parts_create(upload_id, parts).await
+    }
+
+    async fn upload_parts_list(
+        &self,
+        upload_id: uuid::Uuid,
+    ) -> Result<Vec<collects_services::database::UploadPartRow>, SqlStorageError> {
+        self.inner.upload_parts_list(upload_id).await
+    }
+
+    async fn upload_parts_mark_uploaded(
+        &self,
+        upload_id: uuid::Uuid,
+        part_number: i32,
+        etag: String,
+    ) -> Result<Option<collects_services::database::UploadPartRow>, SqlStorageError> {
+        self.inner
+            .upload_parts_mark_uploaded(upload_id, part_number, etag)
+            .await
+    }
+
+    async fn content_revisions_list(
+        &self,
+        content_id: uuid::Uuid,
+    ) -> Result<Vec<collects_services::database::ContentRevisionRow>, SqlStorageError> {
+        self.inner.content_revisions_list(content_id).await
+    }
+
+    async fn content_revisions_get(
+        &self,
+        content_id: uuid::Uuid,
+        revision: i32,
+    ) -> Result<Option<collects_services::database::ContentRevisionRow>, SqlStorageError> {
+        self.inner.content_revisions_get(content_id, revision).await
+    }
+
+    async fn contents_batch(
+        &self,
+        user_id: uuid::Uuid,
+        ids: &[uuid::Uuid],
+        op: collects_services::database::ContentsBatchOp,
+        now: chrono::DateTime<chrono::Utc>,
+    ) -> Result<Vec<uuid::Uuid>, SqlStorageError> {
+        self.inner.contents_batch(user_id, ids, op, now).await
+    }
+
+    async fn contents_set_media_metadata(
+        &self,
+        id: uuid::Uuid,
+        metadata: serde_json::Value,
+    ) -> Result<bool, SqlStorageError> {
+        self.inner.contents_set_media_metadata(id, metadata).await
+    }
+
+    async fn contents_purge_trashed(
+        &self,
+        params: collects_services::database::TrashPurgeParams,
+    ) -> Result<Vec<collects_services::database::ContentRow>, SqlStorageError> {
+        self.inner.contents_purge_trashed(params).await
+    }
+
+    async fn groups_purge_trashed(
+        &self,
+        params: collects_services::database::TrashPurgeParams,
+    ) -> Result<Vec<collects_services::database::ContentGroupRow>, SqlStorageError> {
+        self.inner.groups_purge_trashed(params).await
+    }
+
+    async fn users_storage_usage(
+        &self,
+        user_id: uuid::Uuid,
+        now: chrono::DateTime<chrono::Utc>,
+    ) -> Result<collects_services::database::StorageUsage, SqlStorageError> {
+        self.inner.users_storage_usage(user_id, now).await
+    }
+
+    async fn users_get_storage_quota(
+        &self,
+        user_id: uuid::Uuid,
+    ) -> Result<Option<i64>, SqlStorageError> {
+        self.inner.users_get_storage_quota(user_id).await
+    }
+
+    async fn users_set_storage_quota(
+        &self,
+        user_id: uuid::Uuid,
+        quota_bytes: Option<i64>,
+    ) -> Result<bool, SqlStorageError> {
+        self.inner
+            .users_set_storage_quota(user_id, quota_bytes)
+            .await
+    }
+
+    async fn contents_share_storage_object(
+        &self,
+        content_id: uuid::Uuid,
+        input: collects_services::database::StorageObjectInsert,
+    ) -> Result<Option<(collects_services::database::StorageObjectRow, ContentRow)>, SqlStorageError>
+    {
+        self.inner
+            .contents_share_storage_object(content_id, input)
+            .await
+    }
+
+    async fn storage_objects_release(
+        &self,
+        user_id: uuid::Uuid,
+        sha256: &str,
+    ) -> Result<Option<collects_services::database::StorageObjectRow>, SqlStorageError> {
+        self.inner.storage_objects_release(user_id, sha256).await
+    }
+
+    async fn share_links_targets_for_owner(
+        &self,
+        owner_id: uuid::Uuid,
+    ) -> Result<Vec<ShareLinkTargetRow>, SqlStorageError> {
+        self.inner.share_links_targets_for_owner(owner_id).await
+    }
+
+    async fn account_exports_create(
+        &self,
+        user_id: uuid::Uuid,
+        expires_at: chrono::DateTime<chrono::Utc>,
+    ) -> Result<AccountExportRow, SqlStorageError> {
+        self.inner.account_exports_create(user_id, expires_at).await
+    }
+
+    async fn account_exports_get(
+        &self,
+        id: uuid::Uuid,
+    ) -> Result<Option<AccountExportRow>, SqlStorageError> {
+        self.inner.account_exports_get(id).await
+    }
+
+    async fn account_exports_complete(
+        &self,
+        id: uuid::Uuid,
+        manifest: serde_json::Value,
+    ) -> Result<Option<AccountExportRow>, SqlStorageError> {
+        self.inner.account_exports_complete(id, manifest).await
+    }
+
+    async fn account_exports_fail(
+        &self,
+        id: uuid::Uuid,
+        error: &str,
+    ) -> Result<Option<AccountExportRow>, SqlStorageError> {
+        self.inner.account_exports_fail(id, error).await
+    }
+
+    async fn audit_logs_insert(&self, entry: AuditLogInsert) -> Result<(), SqlStorageError> {
+        self.inner.audit_logs_insert(entry).await
+    }
+
+    async fn audit_logs_list(
+        &self,
+        params: AuditLogsListParams,
+    ) -> Result<Vec<AuditLogRow>, SqlStorageError> {
+        self.inner.audit_logs_list(params).await
+    }
+
     async fn revoked_tokens_add(
         &self,
         token_hash: &str,
@@ -1040,47 +1559,114 @@ async fn test_share_links_delete_not_found() {
     assert_eq!(response.status(), StatusCode::NOT_FOUND);
 }
 
-// =============================================================================
-// Content Share Link Attachment Tests
-// =============================================================================
-
 #[tokio::test]
-async fn test_content_share_link_create_success() {
-    let content = ShareLinksMockSqlStorage::create_test_content(TEST_USER_ID);
-    let content_id = content.id;
+async fn test_share_links_update_and_delete_honor_if_match() {
+    let share_link =
+        ShareLinksMockSqlStorage::create_test_share_link(TEST_USER_ID, "test-token-123", "view");
+    let share_link_id = share_link.id;
 
-    let sql_storage = ShareLinksMockSqlStorage::new().with_content(content);
+    let sql_storage = ShareLinksMockSqlStorage::new().with_share_link(share_link);
     let user_storage = create_test_user_storage();
     let config = Config::new_for_test();
     let app = routes(sql_storage, user_storage, config).await;
 
     let token = generate_test_token();
+    let request = |method: &str, if_match: &str, body: Body| {
+        Request::builder()
+            .method(method)
+            .uri(format!("/v1/share-links/{}", share_link_id))
+            .header("Authorization", format!("Bearer {}", token))
+            .header("Content-Type", "application/json")
+            .header("If-Match", if_match)
+            .body(body)
+            .unwrap()
+    };
 
     let response = app
+        .clone()
         .oneshot(
             Request::builder()
-                .method("POST")
-                .uri(format!("/v1/contents/{}/share-link", content_id))
+                .method("GET")
+                .uri(format!("/v1/share-links/{}", share_link_id))
                 .header("Authorization", format!("Bearer {}", token))
-                .header("Content-Type", "application/json")
-                .body(Body::from(
-                    json!({
-                        "name": "Content Share",
-                        "permission": "download"
-                    })
-                    .to_string(),
-                ))
+                .body(Body::empty())
                 .unwrap(),
         )
         .await
         .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let etag = response.headers()["etag"].to_str().unwrap().to_owned();
 
-    assert_eq!(response.status(), StatusCode::CREATED);
+    let response = app
+        .clone()
+        .oneshot(request(
+            "PATCH",
+            &etag,
+            Body::from(r#"{"permission": "download"}"#),
+        ))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let new_etag = response.headers()["etag"].to_str().unwrap().to_owned();
 
+    // The first ETag no longer matches the link's settings.
+    let response = app
+        .clone()
+        .oneshot(request("DELETE", &etag, Body::empty()))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::PRECONDITION_FAILED);
     let json = get_response_json(response).await;
-    assert_eq!(json["name"], "Content Share");
-    assert_eq!(json["permission"], "download");
-}
+    assert_eq!(json["error"], "precondition_failed");
+
+    let response = app
+        .oneshot(request("DELETE", &new_etag, Body::empty()))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+}
+
+// =============================================================================
+// Content Share Link Attachment Tests
+// =============================================================================
+
+#[tokio::test]
+async fn test_content_share_link_create_success() {
+    let content = ShareLinksMockSqlStorage::create_test_content(TEST_USER_ID);
+    let content_id = content.id;
+
+    let sql_storage = ShareLinksMockSqlStorage::new().with_content(content);
+    let user_storage = create_test_user_storage();
+    let config = Config::new_for_test();
+    let app = routes(sql_storage, user_storage, config).await;
+
+    let token = generate_test_token();
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri(format!("/v1/contents/{}/share-link", content_id))
+                .header("Authorization", format!("Bearer {}", token))
+                .header("Content-Type", "application/json")
+                .body(Body::from(
+                    json!({
+                        "name": "Content Share",
+                        "permission": "download"
+                    })
+                    .to_string(),
+                ))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::CREATED);
+
+    let json = get_response_json(response).await;
+    assert_eq!(json["name"], "Content Share");
+    assert_eq!(json["permission"], "download");
+}
 
 #[tokio::test]
 async fn test_content_share_link_create_content_not_found() {
@@ -1559,7 +2145,7 @@ async fn test_public_share_view_url_password_required() {
     let content = ShareLinksMockSqlStorage::create_test_content(TEST_USER_ID);
     let content_id = content.id;
 
-    // Hash "secret123" using SHA256 (same as in share_links.rs)
+    // Legacy unsalted SHA-256 hash of "secret123"
     use sha2::{Digest, Sha256};
     let mut hasher = Sha256::new();
     hasher.update("secret123".as_bytes());
@@ -1605,7 +2191,7 @@ async fn test_public_share_view_url_with_correct_password() {
     let content = ShareLinksMockSqlStorage::create_test_content(TEST_USER_ID);
     let content_id = content.id;
 
-    // Hash "secret123" using SHA256
+    // Legacy unsalted SHA-256 hash of "secret123"
     use sha2::{Digest, Sha256};
     let mut hasher = Sha256::new();
     hasher.update("secret123".as_bytes());
@@ -1701,6 +2287,64 @@ async fn test_public_share_view_url_with_wrong_password() {
     assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
 }
 
+#[tokio::test]
+async fn test_public_share_view_url_rehashes_legacy_password() {
+    let content = ShareLinksMockSqlStorage::create_test_content(TEST_USER_ID);
+    let content_id = content.id;
+
+    use sha2::{Digest, Sha256};
+    let legacy_hash = format!("{:x}", Sha256::digest("secret123".as_bytes()));
+    let mut share_link =
+        ShareLinksMockSqlStorage::create_test_share_link(TEST_USER_ID, "pwd-token-4", "download");
+    share_link.password_hash = Some(legacy_hash.clone());
+    let share_link_id = share_link.id;
+    let updated_at = share_link.updated_at;
+
+    let sql_storage = ShareLinksMockSqlStorage::new()
+        .with_content(content)
+        .with_share_link(share_link);
+    sql_storage
+        .content_shares
+        .write()
+        .unwrap()
+        .push((content_id, share_link_id));
+
+    let user_storage = MockUserStorage::new();
+    let config = Config::new_for_test();
+    let app = routes(sql_storage.clone(), user_storage, config).await;
+
+    let view_url = |password: &str| {
+        Request::builder()
+            .method("POST")
+            .uri("/v1/public/share/pwd-token-4/view-url")
+            .header("Content-Type", "application/json")
+            .body(Body::from(
+                json!({"password": password, "disposition": "inline"}).to_string(),
+            ))
+            .unwrap()
+    };
+
+    // A wrong password leaves the legacy hash alone
+    let response = app.clone().oneshot(view_url("wrong")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    let stored = sql_storage.share_links.read().unwrap()[0].clone();
+    assert_eq!(stored.password_hash.as_deref(), Some(legacy_hash.as_str()));
+
+    // The first successful unlock upgrades it to Argon2id
+    let response = app.clone().oneshot(view_url("secret123")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let stored = sql_storage.share_links.read().unwrap()[0].clone();
+    let password_hash = stored.password_hash.unwrap();
+    assert!(password_hash.starts_with("$argon2id$"));
+    assert_eq!(stored.updated_at, updated_at);
+
+    // The upgraded hash keeps accepting the same password
+    let response = app.clone().oneshot(view_url("secret123")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let response = app.oneshot(view_url("wrong")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+}
+
 #[tokio::test]
 async fn test_public_share_view_url_invalid_disposition() {
     let content = ShareLinksMockSqlStorage::create_test_content(TEST_USER_ID);
@@ -1981,3 +2625,1026 @@ async fn test_group_share_link_invalid_group_id() {
 
     assert_eq!(response.status(), StatusCode::BAD_REQUEST);
 }
+
+// =============================================================================
+// Share Link Access Analytics Tests
+// =============================================================================
+
+#[tokio::test]
+async fn test_public_share_accesses_are_recorded() {
+    let content = ShareLinksMockSqlStorage::create_test_content(TEST_USER_ID);
+    let content_id = content.id;
+
+    use sha2::{Digest, Sha256};
+    let password_hash = format!("{:x}", Sha256::digest("secret123".as_bytes()));
+    let mut share_link =
+        ShareLinksMockSqlStorage::create_test_share_link(TEST_USER_ID, "logged-token", "view");
+    share_link.password_hash = Some(password_hash);
+    let share_link_id = share_link.id;
+
+    let sql_storage = ShareLinksMockSqlStorage::new()
+        .with_content(content)
+        .with_share_link(share_link);
+    sql_storage
+        .content_shares
+        .write()
+        .unwrap()
+        .push((content_id, share_link_id));
+
+    let user_storage = MockUserStorage::new();
+    let config = Config::new_for_test();
+    let app = routes(sql_storage.clone(), user_storage, config).await;
+
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method("GET")
+                .uri("/v1/public/share/logged-token")
+                .header("User-Agent", "test-browser/1.0")
+                .header("X-Forwarded-For", "203.0.113.7")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/v1/public/share/logged-token/view-url")
+                .header("Content-Type", "application/json")
+                .header("User-Agent", "test-browser/1.0")
+                .header("X-Forwarded-For", "203.0.113.7")
+                .body(Body::from(
+                    json!({ "password": "secret123", "disposition": "inline" }).to_string(),
+                ))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let accesses = sql_storage.accesses.read().unwrap();
+    let supplied: Vec<bool> = accesses
+        .iter()
+        .map(|(access, _)| access.password_supplied)
+        .collect();
+    assert_eq!(supplied, [false, true]);
+    for (access, _) in accesses.iter() {
+        assert_eq!(access.share_link_id, share_link_id);
+        assert_eq!(access.ip_address, Some("203.0.113.7".parse().unwrap()));
+        assert_eq!(access.user_agent.as_deref(), Some("test-browser/1.0"));
+    }
+}
+
+#[tokio::test]
+async fn test_share_links_analytics() {
+    let share_link =
+        ShareLinksMockSqlStorage::create_test_share_link(TEST_USER_ID, "stats-token", "view");
+    let share_link_id = share_link.id;
+    let sql_storage = ShareLinksMockSqlStorage::new().with_share_link(share_link);
+
+    let now = chrono::Utc::now();
+    for (ip, days_ago) in [("203.0.113.7", 0), ("203.0.113.7", 0), ("198.51.100.1", 2)] {
+        let access = ShareLinkAccessInsert {
+            share_link_id,
+            ip_address: Some(ip.parse().unwrap()),
+            user_agent: Some("test-browser/1.0".to_owned()),
+            password_supplied: false,
+        };
+        sql_storage
+            .accesses
+            .write()
+            .unwrap()
+            .push((access, now - chrono::Duration::days(days_ago)));
+    }
+
+    let user_storage = create_test_user_storage();
+    let config = Config::new_for_test();
+    let app = routes(sql_storage, user_storage, config).await;
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("GET")
+                .uri(format!("/v1/share-links/{share_link_id}/analytics?days=7"))
+                .header("Authorization", format!("Bearer {}", generate_test_token()))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let json = get_response_json(response).await;
+    assert_eq!(json["total_accesses"], 3);
+    assert_eq!(json["unique_visitors"], 2);
+    assert!(json["last_accessed_at"].is_string());
+    let daily = json["daily"].as_array().unwrap();
+    assert_eq!(daily.len(), 7);
+    assert_eq!(daily[6]["date"], now.date_naive().to_string());
+    assert_eq!(daily[6]["accesses"], 2);
+    assert_eq!(daily[6]["unique_visitors"], 1);
+    assert_eq!(daily[4]["accesses"], 1);
+    assert_eq!(daily[5]["accesses"], 0);
+}
+
+#[tokio::test]
+async fn test_share_links_analytics_of_other_owner_returns_404() {
+    let share_link = ShareLinksMockSqlStorage::create_test_share_link(
+        uuid::Uuid::new_v4(),
+        "other-stats-token",
+        "view",
+    );
+    let share_link_id = share_link.id;
+    let sql_storage = ShareLinksMockSqlStorage::new().with_share_link(share_link);
+
+    let user_storage = create_test_user_storage();
+    let config = Config::new_for_test();
+    let app = routes(sql_storage, user_storage, config).await;
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("GET")
+                .uri(format!("/v1/share-links/{share_link_id}/analytics"))
+                .header("Authorization", format!("Bearer {}", generate_test_token()))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
+
+// =============================================================================
+// Direct Share Tests
+// =============================================================================
+
+/// Generate a valid test JWT token for the "otheruser" user.
+fn generate_other_user_token() -> String {
+    collects_services::users::otp::generate_session_token("otheruser", common::TEST_JWT_SECRET)
+        .unwrap()
+}
+
+/// User storage with "testuser" and "otheruser".
+fn create_two_user_storage() -> MockUserStorage {
+    create_test_user_storage().with_user(StoredUser::with_id(
+        OTHER_USER_ID,
+        "otheruser",
+        "SECRET456",
+    ))
+}
+
+fn json_request(method: &str, uri: &str, token: &str, body: Value) -> Request<Body> {
+    Request::builder()
+        .method(method)
+        .uri(uri)
+        .header("Authorization", format!("Bearer {token}"))
+        .header("Content-Type", "application/json")
+        .body(Body::from(body.to_string()))
+        .unwrap()
+}
+
+fn empty_request(method: &str, uri: &str, token: &str) -> Request<Body> {
+    Request::builder()
+        .method(method)
+        .uri(uri)
+        .header("Authorization", format!("Bearer {token}"))
+        .body(Body::empty())
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_direct_content_share_lifecycle() {
+    let content = ShareLinksMockSqlStorage::create_test_content(TEST_USER_ID);
+    let content_id = content.id;
+    let sql_storage = ShareLinksMockSqlStorage::new().with_content(content);
+
+    let config = Config::new_for_test();
+    let app = routes(sql_storage, create_two_user_storage(), config).await;
+    let owner = generate_test_token();
+    let other = generate_other_user_token();
+    let content_uri = format!("/v1/contents/{content_id}");
+    let shares_uri = format!("/v1/contents/{content_id}/shares");
+
+    // Not shared yet
+    let response = app
+        .clone()
+        .oneshot(empty_request("GET", &content_uri, &other))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    let response = app
+        .clone()
+        .oneshot(json_request(
+            "POST",
+            &shares_uri,
+            &owner,
+            json!({"username": "otheruser"}),
+        ))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::CREATED);
+    let json = get_response_json(response).await;
+    assert_eq!(json["username"], "otheruser");
+    assert_eq!(json["permission"], "view");
+
+    let response = app
+        .clone()
+        .oneshot(empty_request("GET", &content_uri, &other))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let json = get_response_json(response).await;
+    assert_eq!(json["id"], content_id.to_string());
+
+    let response = app
+        .clone()
+        .oneshot(empty_request("GET", "/v1/shared-with-me", &other))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let json = get_response_json(response).await;
+    let items = json["items"].as_array().unwrap();
+    assert_eq!(items.len(), 1);
+    assert_eq!(items[0]["item_type"], "content");
+    assert_eq!(items[0]["id"], content_id.to_string());
+    assert_eq!(items[0]["owner_username"], "testuser");
+
+    // Sharing again updates the permission
+    let response = app
+        .clone()
+        .oneshot(json_request(
+            "POST",
+            &shares_uri,
+            &owner,
+            json!({"username": "otheruser", "permission": "download"}),
+        ))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::CREATED);
+
+    let response = app
+        .clone()
+        .oneshot(empty_request("GET", &shares_uri, &owner))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let json = get_response_json(response).await;
+    let shares = json["shares"].as_array().unwrap();
+    assert_eq!(shares.len(), 1);
+    assert_eq!(shares[0]["username"], "otheruser");
+    assert_eq!(shares[0]["permission"], "download");
+
+    // Only the owner manages shares
+    let response = app
+        .clone()
+        .oneshot(empty_request("GET", &shares_uri, &other))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    let response = app
+        .clone()
+        .oneshot(empty_request(
+            "DELETE",
+            &format!("{shares_uri}/otheruser"),
+            &owner,
+        ))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+
+    let response = app
+        .clone()
+        .oneshot(empty_request("GET", &content_uri, &other))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    let response = app
+        .oneshot(empty_request(
+            "DELETE",
+            &format!("{shares_uri}/otheruser"),
+            &owner,
+        ))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_direct_share_validation() {
+    let content = ShareLinksMockSqlStorage::create_test_content(TEST_USER_ID);
+    let content_id = content.id;
+    let sql_storage = ShareLinksMockSqlStorage::new().with_content(content);
+
+    let config = Config::new_for_test();
+    let app = routes(sql_storage, create_two_user_storage(), config).await;
+    let owner = generate_test_token();
+    let shares_uri = format!("/v1/contents/{content_id}/shares");
+
+    let cases = [
+        (json!({"username": "testuser"}), StatusCode::BAD_REQUEST),
+        (json!({"username": "nobody"}), StatusCode::NOT_FOUND),
+        (
+            json!({"username": "otheruser", "permission": "edit"}),
+            StatusCode::BAD_REQUEST,
+        ),
+    ];
+    for (body, status) in cases {
+        let response = app
+            .clone()
+            .oneshot(json_request("POST", &shares_uri, &owner, body.clone()))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), status, "{body}");
+    }
+
+    // Recipients cannot share further
+    let response = app
+        .oneshot(json_request(
+            "POST",
+            &shares_uri,
+            &generate_other_user_token(),
+            json!({"username": "testuser"}),
+        ))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_direct_group_share_grants_content_access() {
+    let content = ShareLinksMockSqlStorage::create_test_content(TEST_USER_ID);
+    let content_id = content.id;
+    let group = ShareLinksMockSqlStorage::create_test_group(TEST_USER_ID);
+    let group_id = group.id;
+    let sql_storage = ShareLinksMockSqlStorage::new()
+        .with_content(content)
+        .with_group(group);
+    sql_storage
+        .group_contents
+        .write()
+        .unwrap()
+        .push((group_id, content_id));
+
+    let config = Config::new_for_test();
+    let app = routes(sql_storage, create_two_user_storage(), config).await;
+    let other = generate_other_user_token();
+
+    let response = app
+        .clone()
+        .oneshot(json_request(
+            "POST",
+            &format!("/v1/groups/{group_id}/shares"),
+            &generate_test_token(),
+            json!({"username": "otheruser", "permission": "view"}),
+        ))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::CREATED);
+
+    let response = app
+        .clone()
+        .oneshot(empty_request("GET", "/v1/shared-with-me", &other))
+        .await
+        .unwrap();
+    let json = get_response_json(response).await;
+    assert_eq!(json["items"][0]["item_type"], "group");
+    assert_eq!(json["items"][0]["id"], group_id.to_string());
+
+    let response = app
+        .clone()
+        .oneshot(empty_request(
+            "GET",
+            &format!("/v1/contents/{content_id}"),
+            &other,
+        ))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    // View permission only allows inline viewing
+    let response = app
+        .oneshot(json_request(
+            "POST",
+            &format!("/v1/contents/{content_id}/view-url"),
+            &other,
+            json!({"disposition": "attachment"}),
+        ))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let json = get_response_json(response).await;
+    assert!(json["url"].as_str().unwrap().contains("disposition=inline"));
+}
+
+async fn get_response_text(response: axum::response::Response) -> String {
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    String::from_utf8(body.to_vec()).unwrap()
+}
+
+fn form_request(uri: &str, body: &str) -> Request<Body> {
+    Request::builder()
+        .method("POST")
+        .uri(uri)
+        .header("Content-Type", "application/x-www-form-urlencoded")
+        .body(Body::from(body.to_owned()))
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_share_page_content() {
+    let mut content = ShareLinksMockSqlStorage::create_test_content(TEST_USER_ID);
+    content.title = "Holiday <photos>".to_owned();
+    content.content_type = "image/png".to_owned();
+    let content_id = content.id;
+
+    let share_link =
+        ShareLinksMockSqlStorage::create_test_share_link(TEST_USER_ID, "page-token", "download");
+    let share_link_id = share_link.id;
+
+    let sql_storage = ShareLinksMockSqlStorage::new()
+        .with_content(content)
+        .with_share_link(share_link);
+    sql_storage
+        .content_shares
+        .write()
+        .unwrap()
+        .push((content_id, share_link_id));
+
+    let app = routes(
+        sql_storage.clone(),
+        MockUserStorage::new(),
+        Config::new_for_test(),
+    )
+    .await;
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .uri("/s/page-token")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(
+        response.headers()["content-type"]
+            .to_str()
+            .unwrap()
+            .starts_with("text/html")
+    );
+    assert_eq!(response.headers()["cache-control"], "no-store");
+
+    let html = get_response_text(response).await;
+    assert!(html.contains(r#"<meta property="og:title" content="Holiday &lt;photos&gt;">"#));
+    assert!(html.contains(r#"<meta property="og:description" content="Test description">"#));
+    assert!(
+        html.contains(r#"<meta property="og:url" content="https://app.collects.io/s/page-token">"#)
+    );
+    assert!(html.contains(
+        r#"<meta property="og:image" content="https://app.collects.io/s/page-token/thumbnail">"#
+    ));
+    assert!(html.contains(r#"<meta name="twitter:card" content="summary_large_image">"#));
+    assert!(html.contains(r#"<img class="preview" src="/s/page-token/raw""#));
+    assert!(html.contains(r#"href="/s/page-token/raw?disposition=attachment""#));
+    assert!(html.contains(">Download</a>"));
+
+    // Rendering the page is not an access
+    assert_eq!(sql_storage.share_links.read().unwrap()[0].access_count, 0);
+}
+
+#[tokio::test]
+async fn test_share_page_view_permission_hides_download() {
+    let mut content = ShareLinksMockSqlStorage::create_test_content(TEST_USER_ID);
+    content.kind = "text".to_owned();
+    content.body = Some("Some <b>notes</b>".to_owned());
+    content.description = None;
+    let content_id = content.id;
+
+    let share_link =
+        ShareLinksMockSqlStorage::create_test_share_link(TEST_USER_ID, "text-token", "view");
+    let share_link_id = share_link.id;
+
+    let sql_storage = ShareLinksMockSqlStorage::new()
+        .with_content(content)
+        .with_share_link(share_link);
+    sql_storage
+        .content_shares
+        .write()
+        .unwrap()
+        .push((content_id, share_link_id));
+
+    let app = routes(
+        sql_storage.clone(),
+        MockUserStorage::new(),
+        Config::new_for_test(),
+    )
+    .await;
+
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .uri("/s/text-token")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let html = get_response_text(response).await;
+    assert!(html.contains("<pre class=\"text\">Some &lt;b&gt;notes&lt;/b&gt;</pre>"));
+    assert!(
+        html.contains(
+            r#"<meta property="og:description" content="Some &lt;b&gt;notes&lt;/b&gt;">"#
+        )
+    );
+    assert!(html.contains(r#"<meta name="twitter:card" content="summary">"#));
+    assert!(!html.contains("og:image"));
+    assert!(!html.contains(">Download</a>"));
+    assert_eq!(sql_storage.share_links.read().unwrap()[0].access_count, 0);
+
+    // Fetching the content is, and view links only serve it inline
+    let response = app
+        .oneshot(
+            Request::builder()
+                .uri("/s/text-token/raw?disposition=attachment")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(
+        response.headers()["content-disposition"]
+            .to_str()
+            .unwrap()
+            .starts_with("inline")
+    );
+    assert_eq!(get_response_text(response).await, "Some <b>notes</b>");
+    assert_eq!(sql_storage.share_links.read().unwrap()[0].access_count, 1);
+}
+
+#[tokio::test]
+async fn test_share_page_raw_counts_only_fetches_from_start() {
+    let mut content = ShareLinksMockSqlStorage::create_test_content(TEST_USER_ID);
+    content.kind = "text".to_owned();
+    content.body = Some("x".repeat(200));
+    let content_id = content.id;
+
+    let share_link =
+        ShareLinksMockSqlStorage::create_test_share_link(TEST_USER_ID, "range-token", "view");
+    let share_link_id = share_link.id;
+
+    let sql_storage = ShareLinksMockSqlStorage::new()
+        .with_content(content)
+        .with_share_link(share_link);
+    sql_storage
+        .content_shares
+        .write()
+        .unwrap()
+        .push((content_id, share_link_id));
+
+    let app = routes(
+        sql_storage.clone(),
+        MockUserStorage::new(),
+        Config::new_for_test(),
+    )
+    .await;
+    let fetch = |range: Option<&str>| {
+        let mut request = Request::builder().uri("/s/range-token/raw");
+        if let Some(range) = range {
+            request = request.header("Range", range);
+        }
+        app.clone().oneshot(request.body(Body::empty()).unwrap())
+    };
+    let access_count = || sql_storage.share_links.read().unwrap()[0].access_count;
+
+    // A player seeking or a download resuming is not a new access
+    let response = fetch(Some("bytes=100-")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::PARTIAL_CONTENT);
+    assert_eq!(access_count(), 0);
+
+    let response = fetch(Some("bytes=0-99")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::PARTIAL_CONTENT);
+    assert_eq!(access_count(), 1);
+
+    let response = fetch(None).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(access_count(), 2);
+}
+
+#[tokio::test]
+async fn test_share_page_password_protected() {
+    let content = ShareLinksMockSqlStorage::create_test_content(TEST_USER_ID);
+    let content_id = content.id;
+
+    // Legacy unsalted SHA-256 hash of "secret123"
+    use sha2::{Digest, Sha256};
+    let mut hasher = Sha256::new();
+    hasher.update("secret123".as_bytes());
+    let password_hash = format!("{:x}", hasher.finalize());
+
+    let mut share_link =
+        ShareLinksMockSqlStorage::create_test_share_link(TEST_USER_ID, "locked-token", "view");
+    share_link.password_hash = Some(password_hash);
+    let share_link_id = share_link.id;
+
+    let sql_storage = ShareLinksMockSqlStorage::new()
+        .with_content(content)
+        .with_share_link(share_link);
+    sql_storage
+        .content_shares
+        .write()
+        .unwrap()
+        .push((content_id, share_link_id));
+
+    let app = routes(sql_storage, MockUserStorage::new(), Config::new_for_test()).await;
+
+    // Neither the content nor its thumbnail are served before unlocking
+    for uri in ["/s/locked-token/raw", "/s/locked-token/thumbnail"] {
+        let response = app
+            .clone()
+            .oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_ne!(response.status(), StatusCode::OK);
+    }
+
+    // The form reveals nothing about the content
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .uri("/s/locked-token")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let html = get_response_text(response).await;
+    assert!(html.contains(r#"<form method="post">"#));
+    assert!(!html.contains("Test Content"));
+    assert!(!html.contains("Test description"));
+
+    let response = app
+        .clone()
+        .oneshot(form_request("/s/locked-token", "password=wrong"))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    let html = get_response_text(response).await;
+    assert!(html.contains("Incorrect password"));
+    assert!(!html.contains("Test Content"));
+
+    let response = app
+        .oneshot(form_request("/s/locked-token", "password=secret123"))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let html = get_response_text(response).await;
+    assert!(html.contains("<h1>Test Content</h1>"));
+    assert!(!html.contains("<form"));
+}
+
+#[tokio::test]
+async fn test_share_page_group() {
+    let group = ShareLinksMockSqlStorage::create_test_group(TEST_USER_ID);
+    let group_id = group.id;
+
+    let share_link =
+        ShareLinksMockSqlStorage::create_test_share_link(TEST_USER_ID, "group-page", "download");
+    let share_link_id = share_link.id;
+
+    let sql_storage = ShareLinksMockSqlStorage::new()
+        .with_group(group)
+        .with_share_link(share_link);
+    sql_storage
+        .group_shares
+        .write()
+        .unwrap()
+        .push((group_id, share_link_id));
+
+    let app = routes(sql_storage, MockUserStorage::new(), Config::new_for_test()).await;
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .uri("/s/group-page")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let html = get_response_text(response).await;
+    assert!(html.contains(r#"<meta property="og:title" content="Test Group">"#));
+    assert!(html.contains("5 files"));
+    assert!(html.contains(r#"href="/v1/public/share/group-page/archive.zip""#));
+}
+
+#[tokio::test]
+async fn test_share_page_not_found() {
+    let app = routes(
+        ShareLinksMockSqlStorage::new(),
+        MockUserStorage::new(),
+        Config::new_for_test(),
+    )
+    .await;
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .uri("/s/missing-token")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    let html = get_response_text(response).await;
+    assert!(html.contains("Share not found"));
+}
+
+/// Legacy unsalted SHA-256 hash of a share link password.
+fn legacy_password_hash(password: &str) -> String {
+    use sha2::{Digest, Sha256};
+    format!("{:x}", Sha256::digest(password.as_bytes()))
+}
+
+/// Storage with a content shared by a link protected with "secret123".
+fn create_protected_content_storage(token: &str) -> ShareLinksMockSqlStorage {
+    let content = ShareLinksMockSqlStorage::create_test_content(TEST_USER_ID);
+    let content_id = content.id;
+
+    let mut share_link =
+        ShareLinksMockSqlStorage::create_test_share_link(TEST_USER_ID, token, "download");
+    share_link.password_hash = Some(legacy_password_hash("secret123"));
+    let share_link_id = share_link.id;
+
+    let sql_storage = ShareLinksMockSqlStorage::new()
+        .with_content(content)
+        .with_share_link(share_link);
+    sql_storage
+        .content_shares
+        .write()
+        .unwrap()
+        .push((content_id, share_link_id));
+    sql_storage
+}
+
+fn view_url_request(token: &str, body: Value, client_ip: &str) -> Request<Body> {
+    Request::builder()
+        .method("POST")
+        .uri(format!("/v1/public/share/{token}/view-url"))
+        .header("Content-Type", "application/json")
+        .header("X-Real-IP", client_ip)
+        .body(Body::from(body.to_string()))
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_public_share_view_url_unlock_token() {
+    let sql_storage = create_protected_content_storage("unlock-token");
+    let app = routes(sql_storage, MockUserStorage::new(), Config::new_for_test()).await;
+
+    let response = app
+        .clone()
+        .oneshot(view_url_request(
+            "unlock-token",
+            json!({ "password": "secret123" }),
+            "203.0.113.1",
+        ))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let cookie = response.headers()["set-cookie"]
+        .to_str()
+        .unwrap()
+        .to_owned();
+    assert!(cookie.starts_with("share_unlock_unlock-token="));
+    assert!(cookie.contains("HttpOnly"));
+    let json = get_response_json(response).await;
+    let unlock_token = json["unlock_token"].as_str().unwrap().to_owned();
+    assert!(json["unlock_expires_at"].as_str().is_some());
+
+    // The unlock token stands in for the password
+    let response = app
+        .clone()
+        .oneshot(view_url_request(
+            "unlock-token",
+            json!({ "unlock_token": unlock_token }),
+            "203.0.113.1",
+        ))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(!response.headers().contains_key("set-cookie"));
+    let json = get_response_json(response).await;
+    assert!(json.get("unlock_token").is_none());
+
+    // So does the cookie
+    let cookie_pair = cookie.split(';').next().unwrap().to_owned();
+    let mut request = view_url_request("unlock-token", json!({}), "203.0.113.1");
+    request
+        .headers_mut()
+        .insert("cookie", cookie_pair.parse().unwrap());
+    let response = app.clone().oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = app
+        .oneshot(view_url_request(
+            "unlock-token",
+            json!({ "unlock_token": "4102444800.deadbeef" }),
+            "203.0.113.1",
+        ))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn test_public_share_view_url_password_rate_limited_per_link() {
+    let sql_storage = create_protected_content_storage("limited-token");
+    let app = routes(sql_storage, MockUserStorage::new(), Config::new_for_test()).await;
+
+    // Wrong passwords from different addresses add up on the link
+    for i in 0..10 {
+        let response = app
+            .clone()
+            .oneshot(view_url_request(
+                "limited-token",
+                json!({ "password": "wrong" }),
+                &format!("203.0.113.{i}"),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    // Even the right password is blocked now
+    let response = app
+        .oneshot(view_url_request(
+            "limited-token",
+            json!({ "password": "secret123" }),
+            "198.51.100.1",
+        ))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+    let json = get_response_json(response).await;
+    assert_eq!(json["error"], "too_many_requests");
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_public_share_view_url_concurrent_wrong_passwords_stay_within_limit() {
+    let sql_storage = create_protected_content_storage("race-token");
+    let app = routes(
+        sql_storage.clone(),
+        MockUserStorage::new(),
+        Config::new_for_test(),
+    )
+    .await;
+    let max_attempts = ShareLinkPasswordRateLimitConfig::default().max_attempts_per_link;
+
+    // Guesses racing each other from different addresses
+    let mut requests = tokio::task::JoinSet::new();
+    for i in 0..30 {
+        let request = view_url_request(
+            "race-token",
+            json!({ "password": "wrong" }),
+            &format!("203.0.113.{i}"),
+        );
+        requests.spawn(app.clone().oneshot(request));
+    }
+    let mut unauthorized = 0;
+    let mut limited = 0;
+    while let Some(response) = requests.join_next().await {
+        match response.unwrap().unwrap().status() {
+            StatusCode::UNAUTHORIZED => unauthorized += 1,
+            StatusCode::TOO_MANY_REQUESTS => limited += 1,
+            status => panic!("unexpected status {status}"),
+        }
+    }
+
+    // Only as many passwords as the link allows were checked
+    assert_eq!(unauthorized, max_attempts);
+    assert_eq!(limited, 30 - max_attempts);
+    assert_eq!(
+        sql_storage.inner.password_attempts.read().unwrap().len() as i64,
+        max_attempts
+    );
+}
+
+#[tokio::test]
+async fn test_public_share_view_url_password_rate_limited_per_ip() {
+    let sql_storage = create_protected_content_storage("ip-token");
+    for i in 0..3 {
+        let content = ShareLinksMockSqlStorage::create_test_content(TEST_USER_ID);
+        let content_id = content.id;
+        let mut share_link = ShareLinksMockSqlStorage::create_test_share_link(
+            TEST_USER_ID,
+            &format!("ip-token-{i}"),
+            "view",
+        );
+        share_link.password_hash = Some(legacy_password_hash("other"));
+        let share_link_id = share_link.id;
+        sql_storage.contents.write().unwrap().push(content);
+        sql_storage.share_links.write().unwrap().push(share_link);
+        sql_storage
+            .content_shares
+            .write()
+            .unwrap()
+            .push((content_id, share_link_id));
+    }
+    let app = routes(sql_storage, MockUserStorage::new(), Config::new_for_test()).await;
+
+    // 20 wrong passwords from one address, spread over links
+    for i in 0..20 {
+        let token = format!("ip-token-{}", i % 3);
+        let response = app
+            .clone()
+            .oneshot(view_url_request(
+                &token,
+                json!({ "password": "wrong" }),
+                "203.0.113.7",
+            ))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    let response = app
+        .clone()
+        .oneshot(view_url_request(
+            "ip-token",
+            json!({ "password": "secret123" }),
+            "203.0.113.7",
+        ))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+
+    // Other addresses are unaffected
+    let response = app
+        .oneshot(view_url_request(
+            "ip-token",
+            json!({ "password": "secret123" }),
+            "198.51.100.1",
+        ))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_share_page_unlock_cookie() {
+    let sql_storage = create_protected_content_storage("page-unlock");
+    let app = routes(sql_storage, MockUserStorage::new(), Config::new_for_test()).await;
+
+    let response = app
+        .clone()
+        .oneshot(form_request("/s/page-unlock", "password=secret123"))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let cookie = response.headers()["set-cookie"]
+        .to_str()
+        .unwrap()
+        .to_owned();
+    let cookie_pair = cookie.split(';').next().unwrap().to_owned();
+
+    // Later visits with the cookie skip the form
+    let response = app
+        .oneshot(
+            Request::builder()
+                .uri("/s/page-unlock")
+                .header("cookie", cookie_pair)
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let html = get_response_text(response).await;
+    assert!(html.contains("<h1>Test Content</h1>"));
+    assert!(html.contains(">Download</a>"));
+}